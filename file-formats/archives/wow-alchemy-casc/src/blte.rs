@@ -0,0 +1,144 @@
+//! BLTE, the encoding of every file in the storage: a sequence of chunks
+//! that are stored raw, zlib compressed, nested or encrypted
+
+use crate::error::{CascError, Result};
+use crate::key::Key;
+use flate2::read::ZlibDecoder;
+use std::io::Read;
+
+/// Magic of BLTE encoded data
+pub const MAGIC: &[u8; 4] = b"BLTE";
+
+/// Size of a chunk description in the header
+const CHUNK_INFO_SIZE: usize = 24;
+
+/// Decode BLTE encoded data
+///
+/// Fails with [`CascError::Encrypted`] if a chunk is encrypted.
+pub fn decode(data: &[u8]) -> Result<Vec<u8>> {
+    let error = |message: String| CascError::InvalidBlte(message);
+    if data.len() < 8 || &data[..4] != MAGIC {
+        return Err(error("missing BLTE magic".into()));
+    }
+    let header_size = u32::from_be_bytes(data[4..8].try_into().unwrap()) as usize;
+    if header_size == 0 {
+        return decode_chunk(&data[8..], None);
+    }
+
+    let header = data
+        .get(..header_size)
+        .filter(|header| header.len() >= 12)
+        .ok_or_else(|| error(format!("header of {header_size} bytes out of bounds")))?;
+    if header[8] != 0x0F {
+        return Err(error(format!("unsupported flags {:#04x}", header[8])));
+    }
+    let count = u32::from_be_bytes([0, header[9], header[10], header[11]]) as usize;
+    if 12 + count * CHUNK_INFO_SIZE != header_size {
+        return Err(error(format!(
+            "header of {header_size} bytes for {count} chunks"
+        )));
+    }
+
+    let mut output = Vec::new();
+    let mut position = header_size;
+    for (i, info) in header[12..].chunks_exact(CHUNK_INFO_SIZE).enumerate() {
+        let compressed_size = u32::from_be_bytes(info[0..4].try_into().unwrap()) as usize;
+        let decompressed_size = u32::from_be_bytes(info[4..8].try_into().unwrap()) as usize;
+        let checksum = Key::from_slice(&info[8..]).unwrap();
+        let chunk = data
+            .get(position..position + compressed_size)
+            .ok_or_else(|| error(format!("chunk {i} out of bounds")))?;
+        if checksum != Key::default() && Key::md5(chunk) != checksum {
+            return Err(CascError::ChecksumMismatch(format!("BLTE chunk {i}")));
+        }
+        let decoded = decode_chunk(chunk, Some(decompressed_size))?;
+        if decoded.len() != decompressed_size {
+            return Err(error(format!(
+                "chunk {i} decoded to {} bytes instead of {decompressed_size}",
+                decoded.len()
+            )));
+        }
+        output.extend_from_slice(&decoded);
+        position += compressed_size;
+    }
+    Ok(output)
+}
+
+/// Decode a chunk, whose first byte is its mode
+fn decode_chunk(chunk: &[u8], size_hint: Option<usize>) -> Result<Vec<u8>> {
+    let Some((&mode, payload)) = chunk.split_first() else {
+        return Err(CascError::InvalidBlte("empty chunk".into()));
+    };
+    match mode {
+        b'N' => Ok(payload.to_vec()),
+        b'Z' => {
+            let mut output = Vec::with_capacity(size_hint.unwrap_or(payload.len() * 2));
+            ZlibDecoder::new(payload)
+                .read_to_end(&mut output)
+                .map_err(|error| CascError::Decompression(error.to_string()))?;
+            Ok(output)
+        }
+        b'F' => decode(payload),
+        b'E' => Err(CascError::Encrypted {
+            key_name: encryption_key_name(payload)?,
+        }),
+        _ => Err(CascError::InvalidBlte(format!(
+            "unsupported chunk mode {:?}",
+            mode as char
+        ))),
+    }
+}
+
+/// The name of the key an encrypted chunk needs, from the start of its
+/// payload: the key name length, which is 8, and the name
+fn encryption_key_name(payload: &[u8]) -> Result<u64> {
+    match payload {
+        [8, name @ ..] if name.len() >= 8 => Ok(u64::from_le_bytes(name[..8].try_into().unwrap())),
+        _ => Err(CascError::InvalidBlte("malformed encrypted chunk".into())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_storage::{blte, blte_single};
+
+    #[test]
+    fn test_decode() {
+        let text = b"Ironforge and Stormwind, Orgrimmar and Undercity".repeat(20);
+        assert_eq!(decode(&blte_single(b'N', &text)).unwrap(), text);
+        assert_eq!(decode(&blte_single(b'Z', &text)).unwrap(), text);
+        let chunked = blte(&[(b'Z', &text[..300]), (b'N', &text[300..])]);
+        assert_eq!(decode(&chunked).unwrap(), text);
+        let nested = blte(&[(b'N', b"head"), (b'F', &chunked)]);
+        assert_eq!(
+            decode(&nested).unwrap(),
+            [b"head".as_slice(), &text].concat()
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(decode(b"BLT").is_err());
+        assert!(decode(&blte_single(b'4', b"lz4")).is_err());
+
+        let mut corrupt = blte(&[(b'N', b"data"), (b'N', b"more")]);
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 1;
+        assert!(matches!(
+            decode(&corrupt),
+            Err(CascError::ChecksumMismatch(_))
+        ));
+
+        let mut encrypted = vec![8];
+        encrypted.extend_from_slice(&0xFA50_5078_126A_CB3Eu64.to_le_bytes());
+        encrypted.extend_from_slice(&[4, 0, 0, 0, 0, b'S']);
+        let chunked = blte(&[(b'N', b"data"), (b'E', &encrypted)]);
+        assert!(matches!(
+            decode(&chunked),
+            Err(CascError::Encrypted {
+                key_name: 0xFA50_5078_126A_CB3E
+            })
+        ));
+    }
+}
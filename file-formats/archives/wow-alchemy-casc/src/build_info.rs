@@ -0,0 +1,97 @@
+//! The `.build.info` at the root of an installation
+
+use crate::error::{CascError, Result};
+use crate::key::Key;
+use std::path::Path;
+
+/// The builds of an installation, one row per product
+///
+/// The file is a pipe separated table whose header names each column as
+/// `Name!TYPE:size`, e.g. `Build Key!HEX:16`.
+#[derive(Debug, Clone, Default)]
+pub struct BuildInfo {
+    /// The column names, without their types
+    pub columns: Vec<String>,
+    /// The rows, with a value for each column
+    pub rows: Vec<Vec<String>>,
+}
+
+impl BuildInfo {
+    /// Parse the contents of a `.build.info`
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        let header = lines
+            .next()
+            .ok_or_else(|| CascError::InvalidBuildInfo("empty file".into()))?;
+        let columns = header
+            .split('|')
+            .map(|column| column.split('!').next().unwrap_or(column).to_string())
+            .collect::<Vec<_>>();
+        let rows = lines
+            .map(|line| {
+                let row = line.split('|').map(str::to_string).collect::<Vec<_>>();
+                if row.len() != columns.len() {
+                    return Err(CascError::InvalidBuildInfo(format!(
+                        "row has {} values for {} columns",
+                        row.len(),
+                        columns.len()
+                    )));
+                }
+                Ok(row)
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { columns, rows })
+    }
+
+    /// Read the `.build.info` of the installation in `dir`
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(dir.as_ref().join(".build.info"))?)
+    }
+
+    /// The value of `column` in `row`
+    pub fn get(&self, row: usize, column: &str) -> Option<&str> {
+        let column = self.columns.iter().position(|name| name == column)?;
+        self.rows.get(row)?.get(column).map(String::as_str)
+    }
+
+    /// The row of the active build, of `product` if given
+    pub fn active(&self, product: Option<&str>) -> Option<usize> {
+        (0..self.rows.len()).find(|&row| {
+            self.get(row, "Active") == Some("1")
+                && product.is_none_or(|product| self.get(row, "Product") == Some(product))
+        })
+    }
+
+    /// The key of the build configuration of `row`
+    pub fn build_key(&self, row: usize) -> Result<Key> {
+        let value = self
+            .get(row, "Build Key")
+            .ok_or_else(|| CascError::InvalidBuildInfo("no Build Key".into()))?;
+        value.parse().map_err(CascError::InvalidBuildInfo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = "Branch!STRING:0|Active!DEC:1|Build Key!HEX:16|Version!STRING:0|Product!STRING:0\n\
+                    eu|0|00000000000000000000000000000000|1.13.2.31650|wow_classic\n\
+                    eu|1|0123456789abcdef0123456789abcdef|4.3.4.15595|wow\n";
+        let info = BuildInfo::parse(text).unwrap();
+        assert_eq!(info.columns[2], "Build Key");
+        assert_eq!(info.active(None), Some(1));
+        assert_eq!(info.active(Some("wow_classic")), None);
+        assert_eq!(info.get(1, "Version"), Some("4.3.4.15595"));
+        assert_eq!(
+            info.build_key(1).unwrap().to_string(),
+            "0123456789abcdef0123456789abcdef"
+        );
+        assert!(BuildInfo::parse("A!DEC:1|B!DEC:1\n1\n").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+//! Build configurations, stored in `Data/config`
+
+use crate::error::{CascError, Result};
+use crate::key::Key;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A `key = value` configuration file, such as the build configuration
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// The values by key
+    pub values: HashMap<String, String>,
+}
+
+impl Config {
+    /// Parse the contents of a configuration file
+    pub fn parse(text: &str) -> Self {
+        let values = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Self { values }
+    }
+
+    /// Read the configuration `key` from the `Data` directory of an
+    /// installation
+    pub fn open<P: AsRef<Path>>(data_dir: P, key: &Key) -> Result<Self> {
+        let path = config_path(data_dir.as_ref(), key);
+        let text = std::fs::read_to_string(&path).map_err(|error| {
+            CascError::InvalidConfig(format!("can't read {}: {error}", path.display()))
+        })?;
+        Ok(Self::parse(&text))
+    }
+
+    /// The value of `key`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// The keys listed in the value of `key`
+    pub fn keys(&self, key: &str) -> Result<Vec<Key>> {
+        let value = self
+            .get(key)
+            .ok_or_else(|| CascError::InvalidConfig(format!("no {key}")))?;
+        value
+            .split_whitespace()
+            .map(|hex| hex.parse().map_err(CascError::InvalidConfig))
+            .collect()
+    }
+
+    /// The content key of the root file
+    pub fn root(&self) -> Result<Key> {
+        self.first_key("root")
+    }
+
+    /// The content key and, if listed, the encoding key of the encoding file
+    pub fn encoding(&self) -> Result<(Key, Option<Key>)> {
+        let keys = self.keys("encoding")?;
+        let ckey = *keys
+            .first()
+            .ok_or_else(|| CascError::InvalidConfig("empty encoding".into()))?;
+        Ok((ckey, keys.get(1).copied()))
+    }
+
+    fn first_key(&self, key: &str) -> Result<Key> {
+        self.keys(key)?
+            .first()
+            .copied()
+            .ok_or_else(|| CascError::InvalidConfig(format!("empty {key}")))
+    }
+}
+
+/// The path of configuration `key`: `config/ab/cd/abcd…`
+pub fn config_path(data_dir: &Path, key: &Key) -> PathBuf {
+    let hex = key.to_hex();
+    data_dir
+        .join("config")
+        .join(&hex[0..2])
+        .join(&hex[2..4])
+        .join(hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(
+            "# Build Configuration\n\n\
+             root = 0123456789abcdef0123456789abcdef\n\
+             encoding = 00000000000000000000000000000001 00000000000000000000000000000002\n\
+             build-name = WOW-15595patch4.3.4_Retail\n",
+        );
+        assert_eq!(config.get("build-name"), Some("WOW-15595patch4.3.4_Retail"));
+        assert_eq!(config.root().unwrap().0[0], 0x01);
+        let (ckey, ekey) = config.encoding().unwrap();
+        assert_eq!(ckey.0[15], 1);
+        assert_eq!(ekey.unwrap().0[15], 2);
+        assert!(config.keys("install").is_err());
+        assert_eq!(
+            config_path(Path::new("Data"), &ckey),
+            Path::new("Data/config/00/00/00000000000000000000000000000001")
+        );
+    }
+}
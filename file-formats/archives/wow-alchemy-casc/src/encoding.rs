@@ -0,0 +1,144 @@
+//! The encoding file, mapping the content key of every file to the
+//! encoding keys of its BLTE encodings
+
+use crate::error::{CascError, Result};
+use crate::key::Key;
+use std::collections::HashMap;
+
+/// Magic of the encoding file
+pub const MAGIC: &[u8; 2] = b"EN";
+
+/// Size of the encoding file header
+const HEADER_SIZE: usize = 22;
+
+/// The encodings of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodingEntry {
+    /// The decoded size of the file
+    pub size: u64,
+    /// The encoding keys of its encodings, any of which may be stored
+    pub ekeys: Vec<Key>,
+}
+
+/// A decoded encoding file
+#[derive(Debug, Clone, Default)]
+pub struct EncodingFile {
+    entries: HashMap<Key, EncodingEntry>,
+}
+
+impl EncodingFile {
+    /// Parse the decoded encoding file
+    ///
+    /// Only the content key pages are read, the encoding specifications
+    /// after them aren't needed to read files.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let error = |message: String| CascError::InvalidEncoding(message);
+        if data.len() < HEADER_SIZE || &data[..2] != MAGIC {
+            return Err(error("missing EN magic".into()));
+        }
+        if data[2] != 1 {
+            return Err(error(format!("unsupported version {}", data[2])));
+        }
+        if data[3] != 16 || data[4] != 16 {
+            return Err(error(format!(
+                "unsupported key sizes {} and {}",
+                data[3], data[4]
+            )));
+        }
+        let page_size = u16::from_be_bytes([data[5], data[6]]) as usize * 1024;
+        let page_count = u32::from_be_bytes(data[9..13].try_into().unwrap()) as usize;
+        let espec_size = u32::from_be_bytes(data[18..22].try_into().unwrap()) as usize;
+
+        let table_start = HEADER_SIZE + espec_size;
+        let pages_start = table_start + page_count * 32;
+        let pages_end = pages_start + page_count * page_size;
+        if data.len() < pages_end {
+            return Err(error(format!(
+                "{page_count} pages of {page_size} bytes out of bounds"
+            )));
+        }
+
+        let mut entries = HashMap::new();
+        for (i, page) in data[pages_start..pages_end]
+            .chunks_exact(page_size)
+            .enumerate()
+        {
+            let checksum = Key::from_slice(&data[table_start + i * 32 + 16..]).unwrap();
+            if Key::md5(page) != checksum {
+                return Err(CascError::ChecksumMismatch(format!("encoding page {i}")));
+            }
+
+            let mut rest = page;
+            while let Some((&count, tail)) = rest.split_first() {
+                if count == 0 {
+                    break;
+                }
+                let entry_size = 5 + 16 + count as usize * 16;
+                if tail.len() < entry_size {
+                    return Err(error(format!("entry out of bounds in page {i}")));
+                }
+                let size = tail[..5]
+                    .iter()
+                    .fold(0u64, |value, &byte| (value << 8) | byte as u64);
+                let ckey = Key::from_slice(&tail[5..]).unwrap();
+                let ekeys = tail[21..entry_size]
+                    .chunks_exact(16)
+                    .map(|key| Key::from_slice(key).unwrap())
+                    .collect();
+                entries.insert(ckey, EncodingEntry { size, ekeys });
+                rest = &tail[entry_size..];
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    /// The encodings of the file with content key `ckey`
+    pub fn find(&self, ckey: &Key) -> Option<&EncodingEntry> {
+        self.entries.get(ckey)
+    }
+
+    /// Number of files
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no files
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_storage::encoding_file;
+
+    #[test]
+    fn test_parse() {
+        let files = (0..300u32)
+            .map(|i| {
+                let ckey = Key::md5(&i.to_le_bytes());
+                let ekeys = vec![Key::md5(&ckey.0); 1 + i as usize % 2];
+                (
+                    ckey,
+                    EncodingEntry {
+                        size: i as u64,
+                        ekeys,
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        let data = encoding_file(&files);
+        let encoding = EncodingFile::parse(&data).unwrap();
+        assert_eq!(encoding.len(), 300);
+        for (ckey, entry) in &files {
+            assert_eq!(encoding.find(ckey), Some(entry));
+        }
+
+        let mut corrupt = data.clone();
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 1;
+        assert!(EncodingFile::parse(&corrupt).is_err());
+        assert!(EncodingFile::parse(b"EN").is_err());
+    }
+}
@@ -0,0 +1,63 @@
+//! Error handling for CASC storages
+
+use std::io;
+use thiserror::Error;
+
+/// Errors that can occur when reading a CASC storage
+#[derive(Debug, Error)]
+pub enum CascError {
+    /// An I/O error occurred
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    /// The `.build.info` of the installation is malformed or has no active
+    /// build
+    #[error("Invalid .build.info: {0}")]
+    InvalidBuildInfo(String),
+
+    /// A configuration file is malformed or lacks a required key
+    #[error("Invalid config: {0}")]
+    InvalidConfig(String),
+
+    /// A local index file is malformed
+    #[error("Invalid index: {0}")]
+    InvalidIndex(String),
+
+    /// BLTE encoded data is malformed
+    #[error("Invalid BLTE: {0}")]
+    InvalidBlte(String),
+
+    /// A BLTE frame is encrypted, the named key is needed to read it
+    #[error("Encrypted with key {key_name:016X}")]
+    Encrypted {
+        /// The name of the encryption key
+        key_name: u64,
+    },
+
+    /// The encoding file is malformed
+    #[error("Invalid encoding file: {0}")]
+    InvalidEncoding(String),
+
+    /// The root file is malformed
+    #[error("Invalid root file: {0}")]
+    InvalidRoot(String),
+
+    /// Compressed data is malformed
+    #[error("Decompression error: {0}")]
+    Decompression(String),
+
+    /// Data doesn't match its checksum
+    #[error("Checksum mismatch: {0}")]
+    ChecksumMismatch(String),
+
+    /// A content or encoding key isn't in the storage
+    #[error("Key not found: {0}")]
+    KeyNotFound(String),
+
+    /// The file isn't in the storage
+    #[error("File not found: {0}")]
+    FileNotFound(String),
+}
+
+/// Result type for CASC operations
+pub type Result<T> = std::result::Result<T, CascError>;
@@ -0,0 +1,98 @@
+//! Jenkins hash of file names, which root files index files by
+
+/// Bob Jenkins' `hashlittle2`, returning the `c` and `b` values
+fn hashlittle2(key: &[u8], pc: u32, pb: u32) -> (u32, u32) {
+    fn mix(a: &mut u32, b: &mut u32, c: &mut u32) {
+        *a = a.wrapping_sub(*c) ^ c.rotate_left(4);
+        *c = c.wrapping_add(*b);
+        *b = b.wrapping_sub(*a) ^ a.rotate_left(6);
+        *a = a.wrapping_add(*c);
+        *c = c.wrapping_sub(*b) ^ b.rotate_left(8);
+        *b = b.wrapping_add(*a);
+        *a = a.wrapping_sub(*c) ^ c.rotate_left(16);
+        *c = c.wrapping_add(*b);
+        *b = b.wrapping_sub(*a) ^ a.rotate_left(19);
+        *a = a.wrapping_add(*c);
+        *c = c.wrapping_sub(*b) ^ b.rotate_left(4);
+        *b = b.wrapping_add(*a);
+    }
+
+    fn finalize(a: &mut u32, b: &mut u32, c: &mut u32) {
+        *c = (*c ^ *b).wrapping_sub(b.rotate_left(14));
+        *a = (*a ^ *c).wrapping_sub(c.rotate_left(11));
+        *b = (*b ^ *a).wrapping_sub(a.rotate_left(25));
+        *c = (*c ^ *b).wrapping_sub(b.rotate_left(16));
+        *a = (*a ^ *c).wrapping_sub(c.rotate_left(4));
+        *b = (*b ^ *a).wrapping_sub(a.rotate_left(14));
+        *c = (*c ^ *b).wrapping_sub(b.rotate_left(24));
+    }
+
+    let word = |bytes: &[u8]| {
+        let mut padded = [0u8; 4];
+        padded[..bytes.len()].copy_from_slice(bytes);
+        u32::from_le_bytes(padded)
+    };
+
+    let mut a = 0xDEAD_BEEFu32
+        .wrapping_add(key.len() as u32)
+        .wrapping_add(pc);
+    let mut b = a;
+    let mut c = a.wrapping_add(pb);
+
+    let mut rest = key;
+    while rest.len() > 12 {
+        a = a.wrapping_add(word(&rest[0..4]));
+        b = b.wrapping_add(word(&rest[4..8]));
+        c = c.wrapping_add(word(&rest[8..12]));
+        mix(&mut a, &mut b, &mut c);
+        rest = &rest[12..];
+    }
+    if rest.is_empty() {
+        return (c, b);
+    }
+    a = a.wrapping_add(word(&rest[..rest.len().min(4)]));
+    if rest.len() > 4 {
+        b = b.wrapping_add(word(&rest[4..rest.len().min(8)]));
+    }
+    if rest.len() > 8 {
+        c = c.wrapping_add(word(&rest[8..]));
+    }
+    finalize(&mut a, &mut b, &mut c);
+    (c, b)
+}
+
+/// 64 bit name hash of a path as stored in root files: Jenkins'
+/// `hashlittle2` of the upper-case path with backslashes
+pub fn name_hash(name: &str) -> u64 {
+    let normalized = name
+        .bytes()
+        .map(|byte| match byte {
+            b'/' => b'\\',
+            _ => byte.to_ascii_uppercase(),
+        })
+        .collect::<Vec<_>>();
+    let (high, low) = hashlittle2(&normalized, 0, 0);
+    ((high as u64) << 32) | low as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashlittle2() {
+        let text = b"Four score and seven years ago";
+        assert_eq!(hashlittle2(text, 0, 0).0, 0x1777_0551);
+        assert_eq!(hashlittle2(text, 1, 0).0, 0xCD62_8161);
+        assert_eq!(hashlittle2(b"", 0, 0), (0xDEAD_BEEF, 0xDEAD_BEEF));
+    }
+
+    #[test]
+    fn test_name_hash() {
+        assert_eq!(
+            name_hash("world/maps/azeroth/azeroth.wdt"),
+            name_hash("WORLD\\MAPS\\Azeroth\\Azeroth.wdt")
+        );
+        assert_ne!(name_hash("a.m2"), name_hash("b.m2"));
+    }
+}
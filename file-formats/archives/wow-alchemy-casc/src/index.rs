@@ -0,0 +1,157 @@
+//! Local indices, `Data/data/BBVVVVVVVV.idx`, locating files in the
+//! `data.NNN` archives by their truncated encoding key
+
+use crate::error::{CascError, Result};
+use crate::key::Key;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Size of the index header, which is padded to 16 bytes before the entries
+const HEADER_SIZE: usize = 0x20;
+
+/// Where a file is stored in the `data.NNN` archives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// The number of the `data.NNN` archive
+    pub archive: u32,
+    /// The offset of the file in the archive
+    pub offset: u64,
+    /// The size of the file, including its 30 byte header
+    pub size: u32,
+}
+
+/// Parse the entries of an index file
+pub fn parse_index(data: &[u8]) -> Result<Vec<([u8; 9], IndexEntry)>> {
+    let error = |message: &str| CascError::InvalidIndex(message.to_string());
+    if data.len() < HEADER_SIZE + 8 {
+        return Err(error("file too small"));
+    }
+    let version = u16::from_le_bytes([data[8], data[9]]);
+    if version != 7 {
+        return Err(CascError::InvalidIndex(format!(
+            "unsupported version {version}"
+        )));
+    }
+    let size_length = data[12] as usize;
+    let offset_length = data[13] as usize;
+    let key_length = data[14] as usize;
+    let offset_bits = data[15] as u32;
+    if !(1..=4).contains(&size_length)
+        || !(1..=8).contains(&offset_length)
+        || !(9..=16).contains(&key_length)
+        || offset_bits >= offset_length as u32 * 8
+    {
+        return Err(error("unsupported field sizes"));
+    }
+
+    let entries_size = u32::from_le_bytes(data[0x20..0x24].try_into().unwrap()) as usize;
+    let entries = data
+        .get(HEADER_SIZE + 8..HEADER_SIZE + 8 + entries_size)
+        .ok_or_else(|| error("entries out of bounds"))?;
+    let entry_size = key_length + offset_length + size_length;
+    Ok(entries
+        .chunks_exact(entry_size)
+        .map(|entry| {
+            let mut key = [0u8; 9];
+            key.copy_from_slice(&entry[..9]);
+            let location = entry[key_length..key_length + offset_length]
+                .iter()
+                .fold(0u64, |value, &byte| (value << 8) | byte as u64);
+            let size = entry[key_length + offset_length..]
+                .iter()
+                .rev()
+                .fold(0u32, |value, &byte| (value << 8) | byte as u32);
+            let entry = IndexEntry {
+                archive: (location >> offset_bits) as u32,
+                offset: location & ((1 << offset_bits) - 1),
+                size,
+            };
+            (key, entry)
+        })
+        .collect())
+}
+
+/// The entries of all local indices of a storage
+#[derive(Debug, Clone, Default)]
+pub struct LocalIndex {
+    entries: HashMap<[u8; 9], IndexEntry>,
+}
+
+impl LocalIndex {
+    /// Read the newest index file of every bucket in `Data/data`
+    pub fn open<P: AsRef<Path>>(data_dir: P) -> Result<Self> {
+        let mut newest = HashMap::<u8, (u32, std::path::PathBuf)>::new();
+        for entry in std::fs::read_dir(data_dir.as_ref().join("data"))? {
+            let path = entry?.path();
+            let Some((bucket, version)) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(index_file_name)
+            else {
+                continue;
+            };
+            let current = newest.entry(bucket).or_insert((version, path.clone()));
+            if version > current.0 {
+                *current = (version, path);
+            }
+        }
+
+        let mut index = Self::default();
+        for (_, path) in newest.into_values() {
+            index.entries.extend(parse_index(&std::fs::read(path)?)?);
+        }
+        Ok(index)
+    }
+
+    /// Where the file with encoding key `ekey` is stored
+    pub fn find(&self, ekey: &Key) -> Option<&IndexEntry> {
+        self.entries.get(&ekey.truncated())
+    }
+
+    /// Number of indexed files
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no files are indexed
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// The bucket and version of an index file named `BBVVVVVVVV.idx`
+fn index_file_name(name: &str) -> Option<(u8, u32)> {
+    let stem = name.strip_suffix(".idx")?;
+    if stem.len() != 10 || !stem.is_ascii() {
+        return None;
+    }
+    let bucket = u8::from_str_radix(&stem[..2], 16).ok()?;
+    let version = u32::from_str_radix(&stem[2..], 16).ok()?;
+    Some((bucket, version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_storage::index_file;
+
+    #[test]
+    fn test_parse() {
+        let key = Key([0xAB; 16]);
+        let entry = IndexEntry {
+            archive: 3,
+            offset: 0x1234_5678,
+            size: 0x100,
+        };
+        let entries = parse_index(&index_file(&[(key, entry)])).unwrap();
+        assert_eq!(entries, vec![(key.truncated(), entry)]);
+        assert!(parse_index(&[0; 16]).is_err());
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(index_file_name("0f00000002.idx"), Some((0x0F, 2)));
+        assert_eq!(index_file_name("data.000"), None);
+        assert_eq!(index_file_name("0f0000002.idx"), None);
+    }
+}
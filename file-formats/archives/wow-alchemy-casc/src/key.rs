@@ -0,0 +1,77 @@
+//! Content and encoding keys
+
+use md5::{Digest, Md5};
+use std::fmt;
+use std::str::FromStr;
+
+/// A 16 byte MD5 key: a content key (CKey) hashes the decoded file, an
+/// encoding key (EKey) its BLTE encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Key(pub [u8; 16]);
+
+impl Key {
+    /// The MD5 of `data`
+    pub fn md5(data: &[u8]) -> Self {
+        Self(Md5::digest(data).into())
+    }
+
+    /// Read a key from the first 16 bytes of `bytes`
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        bytes.get(..16)?.try_into().ok().map(Self)
+    }
+
+    /// The first 9 bytes, which the local indices store
+    pub fn truncated(&self) -> [u8; 9] {
+        let mut truncated = [0u8; 9];
+        truncated.copy_from_slice(&self.0[..9]);
+        truncated
+    }
+
+    /// The lower-case hexadecimal form
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.len() != 32 || !s.is_ascii() {
+            return Err(format!("expected 32 hex digits, got {s:?}"));
+        }
+        let mut key = [0u8; 16];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16)
+                .map_err(|_| format!("invalid hex key {s:?}"))?;
+        }
+        Ok(Self(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex() {
+        let key: Key = "0123456789ABCDEF0123456789abcdef".parse().unwrap();
+        assert_eq!(key.0[0], 0x01);
+        assert_eq!(key.0[15], 0xEF);
+        assert_eq!(key.to_string(), "0123456789abcdef0123456789abcdef");
+        assert_eq!(key.truncated(), key.0[..9]);
+        assert!("0123".parse::<Key>().is_err());
+        assert!("zz23456789abcdef0123456789abcdef".parse::<Key>().is_err());
+        assert_eq!(
+            Key::md5(b"").to_string(),
+            "d41d8cd98f00b204e9800998ecf8427e"
+        );
+    }
+}
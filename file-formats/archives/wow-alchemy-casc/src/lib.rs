@@ -0,0 +1,28 @@
+#![doc = include_str!("../README.md")]
+#![forbid(unsafe_code)]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+pub mod blte;
+pub mod build_info;
+pub mod config;
+pub mod encoding;
+pub mod error;
+pub mod hash;
+pub mod index;
+pub mod key;
+pub mod listfile;
+pub mod root;
+pub mod storage;
+
+#[cfg(test)]
+mod test_storage;
+
+// Re-export primary types
+pub use build_info::BuildInfo;
+pub use config::Config;
+pub use encoding::EncodingFile;
+pub use error::{CascError, Result};
+pub use index::{IndexEntry, LocalIndex};
+pub use key::Key;
+pub use root::{ContentFlags, LocaleFlags, RootFile};
+pub use storage::CascStorage;
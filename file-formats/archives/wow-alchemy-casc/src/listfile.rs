@@ -0,0 +1,42 @@
+//! Listfiles of `id;path` lines, naming the FileDataIDs of a storage
+
+use std::path::Path;
+
+/// Parse the `id;path` lines of a listfile, skipping malformed ones
+pub fn parse_listfile(text: &str) -> Vec<(u32, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let (id, path) = line.trim().split_once(';')?;
+            Some((id.trim().parse().ok()?, path.trim().to_string()))
+        })
+        .filter(|(_, path)| !path.is_empty())
+        .collect()
+}
+
+/// Read a listfile from disk
+pub fn read_listfile<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<(u32, String)>> {
+    Ok(parse_listfile(&std::fs::read_to_string(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let entries = parse_listfile(
+            "775971;world/maps/azeroth/azeroth.wdt\r\n\
+             garbage\n\
+             12;\n\
+             x;a.m2\n\
+             189077;creature/murloc/murloc.m2\n",
+        );
+        assert_eq!(
+            entries,
+            vec![
+                (775971, "world/maps/azeroth/azeroth.wdt".to_string()),
+                (189077, "creature/murloc/murloc.m2".to_string())
+            ]
+        );
+    }
+}
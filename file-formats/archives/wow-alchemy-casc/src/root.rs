@@ -0,0 +1,341 @@
+//! The root file, mapping FileDataIDs and name hashes to content keys
+
+use crate::error::{CascError, Result};
+use crate::key::Key;
+use bitflags::bitflags;
+use std::collections::HashMap;
+
+/// Magic of the manifest layout of root files, from 8.2
+pub const MAGIC: &[u8; 4] = b"TSFM";
+
+bitflags! {
+    /// The locales a root entry is for
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct LocaleFlags: u32 {
+        /// English (US)
+        const EN_US = 0x2;
+        /// Korean
+        const KO_KR = 0x4;
+        /// French
+        const FR_FR = 0x10;
+        /// German
+        const DE_DE = 0x20;
+        /// Chinese (simplified)
+        const ZH_CN = 0x40;
+        /// Spanish (Spain)
+        const ES_ES = 0x80;
+        /// Chinese (traditional)
+        const ZH_TW = 0x100;
+        /// English (GB)
+        const EN_GB = 0x200;
+        /// English (China)
+        const EN_CN = 0x400;
+        /// English (Taiwan)
+        const EN_TW = 0x800;
+        /// Spanish (Mexico)
+        const ES_MX = 0x1000;
+        /// Russian
+        const RU_RU = 0x2000;
+        /// Portuguese (Brazil)
+        const PT_BR = 0x4000;
+        /// Italian
+        const IT_IT = 0x8000;
+        /// Portuguese (Portugal)
+        const PT_PT = 0x10000;
+
+        const _ = !0;
+    }
+}
+
+impl LocaleFlags {
+    /// The locale of a client locale code such as `enUS`
+    pub fn from_code(code: &str) -> Option<Self> {
+        Some(match code.to_ascii_lowercase().as_str() {
+            "enus" => Self::EN_US,
+            "kokr" => Self::KO_KR,
+            "frfr" => Self::FR_FR,
+            "dede" => Self::DE_DE,
+            "zhcn" => Self::ZH_CN,
+            "eses" => Self::ES_ES,
+            "zhtw" => Self::ZH_TW,
+            "engb" => Self::EN_GB,
+            "encn" => Self::EN_CN,
+            "entw" => Self::EN_TW,
+            "esmx" => Self::ES_MX,
+            "ruru" => Self::RU_RU,
+            "ptbr" => Self::PT_BR,
+            "itit" => Self::IT_IT,
+            "ptpt" => Self::PT_PT,
+            _ => return None,
+        })
+    }
+}
+
+bitflags! {
+    /// Properties of the files of a root block
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ContentFlags: u32 {
+        /// Only installed on Windows
+        const WINDOWS = 0x8;
+        /// Only installed on macOS
+        const MACOS = 0x10;
+        /// The censored variant of a file
+        const LOW_VIOLENCE = 0x80;
+        /// Not downloaded until needed
+        const DO_NOT_LOAD = 0x100;
+        /// Only in the manifest of an update
+        const UPDATE_PLUGIN = 0x800;
+        /// Encrypted
+        const ENCRYPTED = 0x800_0000;
+        /// The block stores no name hashes
+        const NO_NAME_HASH = 0x1000_0000;
+        /// Compressed ahead of the rest
+        const UNCOMMON_RESOLUTION = 0x2000_0000;
+        /// Part of a bundle
+        const BUNDLE = 0x4000_0000;
+        /// Stored without compression
+        const NO_COMPRESSION = 0x8000_0000;
+
+        const _ = !0;
+    }
+}
+
+/// A variant of a file in the root
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootEntry {
+    /// The content key of the file
+    pub ckey: Key,
+    /// The locales the variant is for
+    pub locale: LocaleFlags,
+    /// The properties of the variant
+    pub content: ContentFlags,
+}
+
+/// A decoded root file
+#[derive(Debug, Clone, Default)]
+pub struct RootFile {
+    files: HashMap<u32, Vec<RootEntry>>,
+    names: HashMap<u64, u32>,
+}
+
+impl RootFile {
+    /// Parse the decoded root file, of either layout
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut root = Self::default();
+        let mut reader = Reader { data, position: 0 };
+
+        let (version, allow_unnamed) = if data.starts_with(MAGIC) {
+            reader.position = 4;
+            let first = reader.u32()?;
+            if first == 24 {
+                let version = reader.u32()?;
+                let total = reader.u32()?;
+                let named = reader.u32()?;
+                reader.u32()?;
+                (version, total != named)
+            } else {
+                let named = reader.u32()?;
+                (1, first != named)
+            }
+        } else {
+            (0, false)
+        };
+        if version > 2 {
+            return Err(CascError::InvalidRoot(format!(
+                "unsupported version {version}"
+            )));
+        }
+
+        while reader.position < data.len() {
+            let count = reader.u32()? as usize;
+            let (locale, content) = if version == 2 {
+                let locale = reader.u32()?;
+                let first = reader.u32()?;
+                let second = reader.u32()?;
+                let third = reader.bytes(1)?[0] as u32;
+                (locale, first | second | (third << 17))
+            } else {
+                let content = reader.u32()?;
+                (reader.u32()?, content)
+            };
+            let locale = LocaleFlags::from_bits_retain(locale);
+            let content = ContentFlags::from_bits_retain(content);
+
+            let mut file_data_ids = Vec::with_capacity(count);
+            let mut next = 0u32;
+            for _ in 0..count {
+                let file_data_id = next.wrapping_add(reader.u32()?);
+                file_data_ids.push(file_data_id);
+                next = file_data_id.wrapping_add(1);
+            }
+
+            let mut ckeys = Vec::with_capacity(count);
+            let mut hashes = Vec::with_capacity(count);
+            if version == 0 {
+                for _ in 0..count {
+                    ckeys.push(reader.key()?);
+                    hashes.push(reader.u64()?);
+                }
+            } else {
+                for _ in 0..count {
+                    ckeys.push(reader.key()?);
+                }
+                if !(allow_unnamed && content.contains(ContentFlags::NO_NAME_HASH)) {
+                    for _ in 0..count {
+                        hashes.push(reader.u64()?);
+                    }
+                }
+            }
+
+            for (i, (&file_data_id, &ckey)) in file_data_ids.iter().zip(&ckeys).enumerate() {
+                root.files.entry(file_data_id).or_default().push(RootEntry {
+                    ckey,
+                    locale,
+                    content,
+                });
+                if let Some(&hash) = hashes.get(i) {
+                    root.names.insert(hash, file_data_id);
+                }
+            }
+        }
+        Ok(root)
+    }
+
+    /// The variants of the file `file_data_id`
+    pub fn entries(&self, file_data_id: u32) -> &[RootEntry] {
+        self.files.get(&file_data_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The variant of the file `file_data_id` best suited to `locale`:
+    /// preferably one for the locale, not the low violence one, and the one
+    /// for the fewest other locales
+    pub fn find(&self, file_data_id: u32, locale: LocaleFlags) -> Option<&RootEntry> {
+        self.entries(file_data_id).iter().max_by_key(|entry| {
+            (
+                entry.locale.intersects(locale),
+                !entry.content.contains(ContentFlags::LOW_VIOLENCE),
+                std::cmp::Reverse(entry.locale.bits().count_ones()),
+            )
+        })
+    }
+
+    /// The FileDataID of the file whose name hashes to `hash`
+    pub fn file_data_id(&self, hash: u64) -> Option<u32> {
+        self.names.get(&hash).copied()
+    }
+
+    /// The FileDataIDs of all files
+    pub fn file_data_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.files.keys().copied()
+    }
+
+    /// Number of files
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether there are no files
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + count)
+            .ok_or_else(|| {
+                CascError::InvalidRoot(format!("unexpected end at {:#x}", self.position))
+            })?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn key(&mut self) -> Result<Key> {
+        Ok(Key::from_slice(self.bytes(16)?).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_storage::{RootBlock, RootLayout, root_file};
+
+    fn blocks() -> Vec<RootBlock> {
+        vec![
+            RootBlock {
+                locale: LocaleFlags::all(),
+                content: ContentFlags::empty(),
+                files: vec![(10, Key([1; 16]), Some(1)), (11, Key([2; 16]), Some(2))],
+            },
+            RootBlock {
+                locale: LocaleFlags::DE_DE,
+                content: ContentFlags::empty(),
+                files: vec![(10, Key([3; 16]), Some(1))],
+            },
+            RootBlock {
+                locale: LocaleFlags::all(),
+                content: ContentFlags::LOW_VIOLENCE,
+                files: vec![(11, Key([4; 16]), Some(2))],
+            },
+            RootBlock {
+                locale: LocaleFlags::all(),
+                content: ContentFlags::NO_NAME_HASH,
+                files: vec![(500, Key([5; 16]), None), (4000, Key([6; 16]), None)],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_layouts() {
+        for layout in [
+            RootLayout::Legacy,
+            RootLayout::Manifest,
+            RootLayout::ManifestV2,
+        ] {
+            let root = RootFile::parse(&root_file(layout, &blocks())).unwrap();
+            assert_eq!(root.len(), 4, "{layout:?}");
+            assert_eq!(root.entries(10).len(), 2);
+            assert_eq!(
+                root.find(10, LocaleFlags::EN_US).unwrap().ckey,
+                Key([1; 16])
+            );
+            assert_eq!(
+                root.find(10, LocaleFlags::DE_DE).unwrap().ckey,
+                Key([3; 16])
+            );
+            assert_eq!(
+                root.find(11, LocaleFlags::EN_US).unwrap().ckey,
+                Key([2; 16])
+            );
+            assert_eq!(
+                root.find(4000, LocaleFlags::EN_US).unwrap().ckey,
+                Key([6; 16])
+            );
+            assert_eq!(root.file_data_id(2), Some(11));
+            assert!(root.find(12, LocaleFlags::EN_US).is_none());
+        }
+        assert!(RootFile::parse(b"TSFM\x18\0\0\0\x03\0\0\0").is_err());
+    }
+
+    #[test]
+    fn test_locale_code() {
+        assert_eq!(LocaleFlags::from_code("enUS"), Some(LocaleFlags::EN_US));
+        assert_eq!(LocaleFlags::from_code("deDE"), Some(LocaleFlags::DE_DE));
+        assert_eq!(LocaleFlags::from_code("xxXX"), None);
+    }
+}
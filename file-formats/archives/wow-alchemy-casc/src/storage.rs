@@ -0,0 +1,286 @@
+//! Reading files from the local storage of an installation
+
+use crate::blte;
+use crate::build_info::BuildInfo;
+use crate::config::Config;
+use crate::encoding::EncodingFile;
+use crate::error::{CascError, Result};
+use crate::hash::name_hash;
+use crate::index::{IndexEntry, LocalIndex};
+use crate::key::Key;
+use crate::listfile::read_listfile;
+use crate::root::{LocaleFlags, RootFile};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Size of the header before each file in the `data.NNN` archives
+const DATA_HEADER_SIZE: usize = 30;
+
+/// The local CASC storage of an installation
+#[derive(Debug)]
+pub struct CascStorage {
+    data_dir: PathBuf,
+    build_info: BuildInfo,
+    build: usize,
+    build_config: Config,
+    index: LocalIndex,
+    encoding: EncodingFile,
+    root: RootFile,
+    names: HashMap<u64, u32>,
+    paths: HashMap<u32, String>,
+    locale: LocaleFlags,
+}
+
+impl CascStorage {
+    /// Open the storage of the installation in `dir`, the directory with
+    /// the `.build.info` and `Data`, for its active build
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        Self::open_product(dir, None)
+    }
+
+    /// Open the storage for the active build of `product`, e.g. `wow` or
+    /// `wow_classic`, if the installation has several
+    pub fn open_product<P: AsRef<Path>>(dir: P, product: Option<&str>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let build_info = BuildInfo::open(dir)?;
+        let build = build_info.active(product).ok_or_else(|| {
+            CascError::InvalidBuildInfo(match product {
+                Some(product) => format!("no active build of {product}"),
+                None => "no active build".into(),
+            })
+        })?;
+        let data_dir = dir.join("Data");
+        let build_config = Config::open(&data_dir, &build_info.build_key(build)?)?;
+        let index = LocalIndex::open(&data_dir)?;
+
+        let mut storage = Self {
+            data_dir,
+            build_info,
+            build,
+            build_config,
+            index,
+            encoding: EncodingFile::default(),
+            root: RootFile::default(),
+            names: HashMap::new(),
+            paths: HashMap::new(),
+            locale: LocaleFlags::EN_US,
+        };
+
+        let (_, encoding_ekey) = storage.build_config.encoding()?;
+        let encoding_ekey = encoding_ekey.ok_or_else(|| {
+            CascError::InvalidConfig("no encoding key of the encoding file".into())
+        })?;
+        storage.encoding = EncodingFile::parse(&storage.read_ekey(&encoding_ekey)?)?;
+        storage.root = RootFile::parse(&storage.read_ckey(&storage.build_config.root()?)?)?;
+        Ok(storage)
+    }
+
+    /// The `.build.info` of the installation
+    pub fn build_info(&self) -> &BuildInfo {
+        &self.build_info
+    }
+
+    /// The version of the opened build, e.g. `4.4.0.54525`
+    pub fn version(&self) -> Option<&str> {
+        self.build_info.get(self.build, "Version")
+    }
+
+    /// The build configuration of the opened build
+    pub fn build_config(&self) -> &Config {
+        &self.build_config
+    }
+
+    /// The encoding file
+    pub fn encoding(&self) -> &EncodingFile {
+        &self.encoding
+    }
+
+    /// The root file
+    pub fn root(&self) -> &RootFile {
+        &self.root
+    }
+
+    /// The locale whose variants of files are read, `enUS` by default
+    pub fn locale(&self) -> LocaleFlags {
+        self.locale
+    }
+
+    /// Set the locale whose variants of files are read
+    pub fn set_locale(&mut self, locale: LocaleFlags) {
+        self.locale = locale;
+    }
+
+    /// Name FileDataIDs, in addition to the name hashes of the root
+    pub fn add_names<'a, I>(&mut self, names: I)
+    where
+        I: IntoIterator<Item = (u32, &'a str)>,
+    {
+        for (file_data_id, name) in names {
+            self.names.insert(name_hash(name), file_data_id);
+            self.paths.insert(file_data_id, name.to_string());
+        }
+    }
+
+    /// Name FileDataIDs with the `id;path` lines of a listfile
+    pub fn load_listfile<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let entries = read_listfile(path)?;
+        self.add_names(entries.iter().map(|(id, name)| (*id, name.as_str())));
+        Ok(())
+    }
+
+    /// The FileDataID of the file named `name`, from the listfile or the
+    /// name hashes of the root
+    pub fn file_data_id(&self, name: &str) -> Option<u32> {
+        let hash = name_hash(name);
+        self.names
+            .get(&hash)
+            .copied()
+            .or_else(|| self.root.file_data_id(hash))
+    }
+
+    /// The name of the file `file_data_id` in the listfile
+    pub fn file_name(&self, file_data_id: u32) -> Option<&str> {
+        self.paths.get(&file_data_id).map(String::as_str)
+    }
+
+    /// Whether the root has the file `file_data_id`
+    pub fn contains(&self, file_data_id: u32) -> bool {
+        !self.root.entries(file_data_id).is_empty()
+    }
+
+    /// The FileDataIDs of all files in the root
+    pub fn file_data_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.root.file_data_ids()
+    }
+
+    /// Read the file `file_data_id`, in the variant of the locale
+    pub fn read_by_id(&self, file_data_id: u32) -> Result<Vec<u8>> {
+        let entry = self
+            .root
+            .find(file_data_id, self.locale)
+            .ok_or_else(|| CascError::FileNotFound(format!("FileDataID {file_data_id}")))?;
+        self.read_ckey(&entry.ckey)
+    }
+
+    /// Read the file named `name`
+    pub fn read_by_name(&self, name: &str) -> Result<Vec<u8>> {
+        let file_data_id = self
+            .file_data_id(name)
+            .ok_or_else(|| CascError::FileNotFound(name.to_string()))?;
+        self.read_by_id(file_data_id)
+    }
+
+    /// Read the file with content key `ckey`
+    pub fn read_ckey(&self, ckey: &Key) -> Result<Vec<u8>> {
+        let entry = self
+            .encoding
+            .find(ckey)
+            .ok_or_else(|| CascError::KeyNotFound(format!("content key {ckey}")))?;
+        let ekey = entry
+            .ekeys
+            .iter()
+            .find(|ekey| self.index.find(ekey).is_some())
+            .ok_or_else(|| CascError::KeyNotFound(format!("local encoding of {ckey}")))?;
+        let data = self.read_ekey(ekey)?;
+        if Key::md5(&data) != *ckey {
+            return Err(CascError::ChecksumMismatch(format!("content key {ckey}")));
+        }
+        Ok(data)
+    }
+
+    /// Read and decode the file with encoding key `ekey`
+    pub fn read_ekey(&self, ekey: &Key) -> Result<Vec<u8>> {
+        let entry = self
+            .index
+            .find(ekey)
+            .ok_or_else(|| CascError::KeyNotFound(format!("encoding key {ekey}")))?;
+        blte::decode(&self.read_raw(ekey, entry)?)
+    }
+
+    /// Read the BLTE encoded file at `entry`, without its header
+    fn read_raw(&self, ekey: &Key, entry: &IndexEntry) -> Result<Vec<u8>> {
+        let path = self
+            .data_dir
+            .join("data")
+            .join(format!("data.{:03}", entry.archive));
+        let mut file = File::open(&path)?;
+        file.seek(SeekFrom::Start(entry.offset))?;
+        let mut data = vec![0u8; entry.size as usize];
+        file.read_exact(&mut data)?;
+
+        let mut header_key = data
+            .get(..16)
+            .ok_or_else(|| CascError::InvalidIndex(format!("entry of {ekey} too small")))?
+            .to_vec();
+        header_key.reverse();
+        if header_key[..9] != ekey.0[..9] || data.len() < DATA_HEADER_SIZE {
+            return Err(CascError::InvalidIndex(format!(
+                "{} at {:#x} isn't {ekey}",
+                path.display(),
+                entry.offset
+            )));
+        }
+        data.drain(..DATA_HEADER_SIZE);
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_storage::TestStorage;
+
+    #[test]
+    fn test_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = TestStorage::new()
+            .file(1, Some("interface/icons/inv_misc_qmark.blp"), b"BLP2 icon")
+            .file(2, None, &b"MD21 model".repeat(500))
+            .localized(2, LocaleFlags::DE_DE, b"MD21 Modell")
+            .file(775971, Some("world/maps/azeroth/azeroth.wdt"), b"MVER wdt")
+            .write(dir.path());
+
+        let mut storage = CascStorage::open(dir.path()).unwrap();
+        assert_eq!(storage.version(), Some("4.4.0.54525"));
+        assert_eq!(storage.root().len(), 3);
+        assert_eq!(storage.read_by_id(1).unwrap(), b"BLP2 icon");
+        assert_eq!(storage.read_by_id(2).unwrap(), files[&2]);
+        assert_eq!(
+            storage
+                .read_by_name("World\\Maps\\Azeroth\\Azeroth.wdt")
+                .unwrap(),
+            b"MVER wdt"
+        );
+        assert!(matches!(
+            storage.read_by_id(3),
+            Err(CascError::FileNotFound(_))
+        ));
+        assert!(storage.read_by_name("creature/murloc/murloc.m2").is_err());
+
+        storage.set_locale(LocaleFlags::DE_DE);
+        assert_eq!(storage.read_by_id(2).unwrap(), b"MD21 Modell");
+
+        let listfile = dir.path().join("listfile.csv");
+        std::fs::write(&listfile, "2;creature/murloc/murloc.m2\n").unwrap();
+        storage.load_listfile(&listfile).unwrap();
+        assert_eq!(storage.file_data_id("Creature/Murloc/Murloc.m2"), Some(2));
+        assert_eq!(storage.file_name(2), Some("creature/murloc/murloc.m2"));
+        assert_eq!(
+            storage.read_by_name("creature/murloc/murloc.m2").unwrap(),
+            b"MD21 Modell"
+        );
+    }
+
+    #[test]
+    fn test_product() {
+        let dir = tempfile::tempdir().unwrap();
+        TestStorage::new().file(1, None, b"data").write(dir.path());
+        assert!(CascStorage::open_product(dir.path(), Some("wow")).is_ok());
+        assert!(matches!(
+            CascStorage::open_product(dir.path(), Some("wow_beta")),
+            Err(CascError::InvalidBuildInfo(_))
+        ));
+    }
+}
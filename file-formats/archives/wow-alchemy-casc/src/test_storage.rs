@@ -0,0 +1,339 @@
+//! Builders of synthetic storages for tests
+
+use crate::blte;
+use crate::encoding::EncodingEntry;
+use crate::hash::name_hash;
+use crate::index::IndexEntry;
+use crate::key::Key;
+use crate::root::{ContentFlags, LocaleFlags};
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+/// BLTE encode `chunks` of `(mode, decoded data)`, with a chunk table
+pub fn blte(chunks: &[(u8, &[u8])]) -> Vec<u8> {
+    let encoded = chunks
+        .iter()
+        .map(|&(mode, data)| {
+            let size = match mode {
+                b'F' => blte::decode(data).unwrap().len(),
+                _ => data.len(),
+            };
+            (encode_chunk(mode, data), size)
+        })
+        .collect::<Vec<_>>();
+    let header_size = 12 + encoded.len() * 24;
+    let mut output = blte::MAGIC.to_vec();
+    output.extend_from_slice(&(header_size as u32).to_be_bytes());
+    output.push(0x0F);
+    output.extend_from_slice(&(encoded.len() as u32).to_be_bytes()[1..]);
+    for (chunk, size) in &encoded {
+        output.extend_from_slice(&(chunk.len() as u32).to_be_bytes());
+        output.extend_from_slice(&(*size as u32).to_be_bytes());
+        output.extend_from_slice(&Key::md5(chunk).0);
+    }
+    for (chunk, _) in encoded {
+        output.extend_from_slice(&chunk);
+    }
+    output
+}
+
+/// BLTE encode `data` as a single chunk, without a chunk table
+pub fn blte_single(mode: u8, data: &[u8]) -> Vec<u8> {
+    let mut output = blte::MAGIC.to_vec();
+    output.extend_from_slice(&0u32.to_be_bytes());
+    output.extend_from_slice(&encode_chunk(mode, data));
+    output
+}
+
+fn encode_chunk(mode: u8, data: &[u8]) -> Vec<u8> {
+    let mut chunk = vec![mode];
+    if mode == b'Z' {
+        let mut encoder = ZlibEncoder::new(chunk, Compression::default());
+        encoder.write_all(data).unwrap();
+        chunk = encoder.finish().unwrap();
+    } else {
+        chunk.extend_from_slice(data);
+    }
+    chunk
+}
+
+/// A version 7 index file of `entries`
+pub fn index_file(entries: &[(Key, IndexEntry)]) -> Vec<u8> {
+    let mut output = vec![0u8; 0x20];
+    output[0] = 0x10;
+    output[8] = 7;
+    output[12] = 4;
+    output[13] = 5;
+    output[14] = 9;
+    output[15] = 30;
+    output[16..24].copy_from_slice(&0x4000_0000u64.to_le_bytes());
+    output.extend_from_slice(&(entries.len() as u32 * 18).to_le_bytes());
+    output.extend_from_slice(&0u32.to_le_bytes());
+    for (key, entry) in entries {
+        output.extend_from_slice(&key.truncated());
+        let location = ((entry.archive as u64) << 30) | entry.offset;
+        output.extend_from_slice(&location.to_be_bytes()[3..]);
+        output.extend_from_slice(&entry.size.to_le_bytes());
+    }
+    output
+}
+
+/// An encoding file of `entries`, in pages of 4 KiB
+pub fn encoding_file(entries: &[(Key, EncodingEntry)]) -> Vec<u8> {
+    const PAGE_SIZE: usize = 4096;
+    let mut entries = entries.to_vec();
+    entries.sort_by_key(|(ckey, _)| *ckey);
+
+    let mut pages: Vec<(Key, Vec<u8>)> = Vec::new();
+    for (ckey, entry) in &entries {
+        let mut bytes = vec![entry.ekeys.len() as u8];
+        bytes.extend_from_slice(&entry.size.to_be_bytes()[3..]);
+        bytes.extend_from_slice(&ckey.0);
+        for ekey in &entry.ekeys {
+            bytes.extend_from_slice(&ekey.0);
+        }
+        match pages.last_mut() {
+            Some((_, page)) if page.len() + bytes.len() <= PAGE_SIZE => {
+                page.extend_from_slice(&bytes)
+            }
+            _ => pages.push((*ckey, bytes)),
+        }
+    }
+
+    let mut output = b"EN".to_vec();
+    output.extend_from_slice(&[1, 16, 16]);
+    output.extend_from_slice(&4u16.to_be_bytes());
+    output.extend_from_slice(&4u16.to_be_bytes());
+    output.extend_from_slice(&(pages.len() as u32).to_be_bytes());
+    output.extend_from_slice(&0u32.to_be_bytes());
+    output.push(0);
+    output.extend_from_slice(&0u32.to_be_bytes());
+    for (_, page) in &mut pages {
+        page.resize(PAGE_SIZE, 0);
+    }
+    for (first, page) in &pages {
+        output.extend_from_slice(&first.0);
+        output.extend_from_slice(&Key::md5(page).0);
+    }
+    for (_, page) in pages {
+        output.extend_from_slice(&page);
+    }
+    output
+}
+
+/// The layouts of root files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootLayout {
+    /// Blocks of interleaved content keys and name hashes, before 8.2
+    Legacy,
+    /// `MFST` with the total and named counts
+    Manifest,
+    /// `MFST` with a 24 byte header and version 2 blocks
+    ManifestV2,
+}
+
+/// A block of files sharing locale and content flags
+pub struct RootBlock {
+    pub locale: LocaleFlags,
+    pub content: ContentFlags,
+    /// `(FileDataID, content key, name hash)`, by ascending FileDataID
+    pub files: Vec<(u32, Key, Option<u64>)>,
+}
+
+/// A root file of `blocks`
+pub fn root_file(layout: RootLayout, blocks: &[RootBlock]) -> Vec<u8> {
+    let files = blocks.iter().flat_map(|block| &block.files);
+    let total = files.clone().count() as u32;
+    let named = files.filter(|(_, _, hash)| hash.is_some()).count() as u32;
+
+    let mut output = Vec::new();
+    match layout {
+        RootLayout::Legacy => {}
+        RootLayout::Manifest => {
+            output.extend_from_slice(crate::root::MAGIC);
+            output.extend_from_slice(&total.to_le_bytes());
+            output.extend_from_slice(&named.to_le_bytes());
+        }
+        RootLayout::ManifestV2 => {
+            output.extend_from_slice(crate::root::MAGIC);
+            for value in [24, 2, total, named, 0] {
+                output.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+    }
+
+    for block in blocks {
+        output.extend_from_slice(&(block.files.len() as u32).to_le_bytes());
+        if layout == RootLayout::ManifestV2 {
+            output.extend_from_slice(&block.locale.bits().to_le_bytes());
+            output.extend_from_slice(&block.content.bits().to_le_bytes());
+            output.extend_from_slice(&0u32.to_le_bytes());
+            output.push(0);
+        } else {
+            output.extend_from_slice(&block.content.bits().to_le_bytes());
+            output.extend_from_slice(&block.locale.bits().to_le_bytes());
+        }
+        let mut next = 0;
+        for (file_data_id, _, _) in &block.files {
+            output.extend_from_slice(&(file_data_id - next).to_le_bytes());
+            next = file_data_id + 1;
+        }
+        let hash = |hash: &Option<u64>| hash.unwrap_or_default().to_le_bytes();
+        if layout == RootLayout::Legacy {
+            for (_, ckey, name_hash) in &block.files {
+                output.extend_from_slice(&ckey.0);
+                output.extend_from_slice(&hash(name_hash));
+            }
+        } else {
+            for (_, ckey, _) in &block.files {
+                output.extend_from_slice(&ckey.0);
+            }
+            if !block.content.contains(ContentFlags::NO_NAME_HASH) {
+                for (_, _, name_hash) in &block.files {
+                    output.extend_from_slice(&hash(name_hash));
+                }
+            }
+        }
+    }
+    output
+}
+
+/// A storage of files to write to an installation directory
+pub struct TestStorage {
+    files: Vec<(u32, Option<String>, LocaleFlags, Vec<u8>)>,
+}
+
+impl TestStorage {
+    pub fn new() -> Self {
+        Self { files: Vec::new() }
+    }
+
+    /// Add the file `file_data_id` for all locales
+    pub fn file(mut self, file_data_id: u32, name: Option<&str>, data: &[u8]) -> Self {
+        self.files.push((
+            file_data_id,
+            name.map(str::to_string),
+            LocaleFlags::all(),
+            data.to_vec(),
+        ));
+        self
+    }
+
+    /// Add a variant of the file `file_data_id` for `locale`
+    pub fn localized(mut self, file_data_id: u32, locale: LocaleFlags, data: &[u8]) -> Self {
+        self.files.push((file_data_id, None, locale, data.to_vec()));
+        self
+    }
+
+    /// Write the storage of an installation to `dir`, returning the data of
+    /// the files for all locales
+    pub fn write(self, dir: &Path) -> HashMap<u32, Vec<u8>> {
+        let data_dir = dir.join("Data");
+        std::fs::create_dir_all(data_dir.join("data")).unwrap();
+
+        let mut blocks: Vec<RootBlock> = Vec::new();
+        let mut stored = Vec::new();
+        for (file_data_id, name, locale, data) in &self.files {
+            let content = match name {
+                Some(_) => ContentFlags::empty(),
+                None => ContentFlags::NO_NAME_HASH,
+            };
+            let position = blocks
+                .iter()
+                .position(|block| block.locale == *locale && block.content == content)
+                .unwrap_or_else(|| {
+                    blocks.push(RootBlock {
+                        locale: *locale,
+                        content,
+                        files: Vec::new(),
+                    });
+                    blocks.len() - 1
+                });
+            let hash = name.as_deref().map(name_hash);
+            blocks[position]
+                .files
+                .push((*file_data_id, Key::md5(data), hash));
+            stored.push(data.clone());
+        }
+        for block in &mut blocks {
+            block
+                .files
+                .sort_by_key(|(file_data_id, _, _)| *file_data_id);
+        }
+        let root = root_file(RootLayout::Manifest, &blocks);
+        let root_ckey = Key::md5(&root);
+        stored.push(root);
+
+        let mut archive = Vec::new();
+        let mut index = Vec::new();
+        let mut encoding = Vec::new();
+        let mut store = |data: &[u8]| {
+            let encoded = if data.len() > 1024 {
+                blte(&[(b'Z', &data[..1024]), (b'N', &data[1024..])])
+            } else if data.len() % 2 == 0 {
+                blte_single(b'Z', data)
+            } else {
+                blte_single(b'N', data)
+            };
+            let ekey = Key::md5(&encoded);
+            let size = (30 + encoded.len()) as u32;
+            index.push((
+                ekey,
+                IndexEntry {
+                    archive: 0,
+                    offset: archive.len() as u64,
+                    size,
+                },
+            ));
+            let mut reversed = ekey.0;
+            reversed.reverse();
+            archive.extend_from_slice(&reversed);
+            archive.extend_from_slice(&size.to_le_bytes());
+            archive.extend_from_slice(&[0; 10]);
+            archive.extend_from_slice(&encoded);
+            ekey
+        };
+        for data in &stored {
+            let ekey = store(data);
+            encoding.push((
+                Key::md5(data),
+                EncodingEntry {
+                    size: data.len() as u64,
+                    ekeys: vec![ekey],
+                },
+            ));
+        }
+        let encoding = encoding_file(&encoding);
+        let encoding_ekey = store(&encoding);
+
+        std::fs::write(data_dir.join("data/data.000"), archive).unwrap();
+        std::fs::write(data_dir.join("data/0000000001.idx"), b"stale").unwrap();
+        std::fs::write(data_dir.join("data/0000000002.idx"), index_file(&index)).unwrap();
+
+        let build_config = format!(
+            "# Build Configuration\n\nroot = {root_ckey}\nencoding = {} {encoding_ekey}\n",
+            Key::md5(&encoding)
+        );
+        let build_key = Key::md5(build_config.as_bytes());
+        let path = crate::config::config_path(&data_dir, &build_key);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, build_config).unwrap();
+        std::fs::write(
+            dir.join(".build.info"),
+            format!(
+                "Branch!STRING:0|Active!DEC:1|Build Key!HEX:16|Version!STRING:0|Product!STRING:0\n\
+                 eu|1|{build_key}|4.4.0.54525|wow\n"
+            ),
+        )
+        .unwrap();
+
+        self.files
+            .into_iter()
+            .filter(|(_, _, locale, _)| locale.is_all())
+            .map(|(file_data_id, _, _, data)| (file_data_id, data))
+            .collect()
+    }
+}
@@ -0,0 +1,741 @@
+//! Opening archives and reading their files
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::attributes::{ATTRIBUTES_NAME, Attributes};
+use crate::compression::decompress;
+use crate::crypto::{block_table_key, decrypt, file_key, hash_table_key};
+use crate::error::{MpqError, Result};
+use crate::explode::explode;
+use crate::header::{FormatVersion, MpqHeader, UserData, find_header};
+use crate::listfile::{LISTFILE_NAME, parse_listfile};
+use crate::tables::{
+    BET_MAGIC, BetTable, FileEntry, FileFlags, HET_MAGIC, HashEntry, HetTable, find_hash_entries,
+    find_hash_entry, parse_block_table, parse_hash_table,
+};
+
+/// Header in front of the data of patch files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchInfo {
+    /// Size of the header
+    pub length: u32,
+    pub flags: u32,
+    /// Size of the patch data
+    pub data_size: u32,
+    pub md5: [u8; 16],
+}
+
+impl PatchInfo {
+    pub const SIZE: usize = 28;
+
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let u32_at =
+            |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        Self {
+            length: u32_at(0),
+            flags: u32_at(4),
+            data_size: u32_at(8),
+            md5: bytes[12..28].try_into().unwrap(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[0..4].copy_from_slice(&self.length.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.flags.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.data_size.to_le_bytes());
+        bytes[12..28].copy_from_slice(&self.md5);
+        bytes
+    }
+}
+
+/// Adler-32 checksum of sectors, which starts from zero instead of one
+pub fn sector_checksum(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (0u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+/// An MPQ archive
+#[derive(Debug)]
+pub struct Archive<R> {
+    reader: R,
+    offset: u64,
+    user_data: Option<UserData>,
+    header: MpqHeader,
+    hash_table: Vec<HashEntry>,
+    het_table: Option<HetTable>,
+    bet_table: Option<BetTable>,
+    files: Vec<FileEntry>,
+    locale: u16,
+}
+
+impl Archive<BufReader<File>> {
+    /// Open an archive file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> Archive<R> {
+    /// Read the header and tables of an archive
+    pub fn new(mut reader: R) -> Result<Self> {
+        let (offset, user_data, header) = find_header(&mut reader)?;
+        let version = header.format_version;
+
+        let mut archive = Self {
+            reader,
+            offset,
+            user_data,
+            header,
+            hash_table: Vec::new(),
+            het_table: None,
+            bet_table: None,
+            files: Vec::new(),
+            locale: 0,
+        };
+
+        let header = &archive.header;
+        let (hash_pos, hash_entries, hash_size) = (
+            header.hash_table_pos,
+            header.hash_table_entries as usize,
+            header.hash_table_size,
+        );
+        let (block_pos, block_entries, block_size) = (
+            header.block_table_pos,
+            header.block_table_entries as usize,
+            header.block_table_size,
+        );
+        let hi_block_pos = header.hi_block_table_pos;
+        let (het_pos, bet_pos) = (header.het_table_pos, header.bet_table_pos);
+
+        if hash_entries > 0 {
+            let data = archive.read_table(
+                hash_pos,
+                hash_entries * HashEntry::SIZE,
+                hash_size,
+                hash_table_key(),
+            )?;
+            archive.hash_table = parse_hash_table(&data);
+        }
+
+        let mut block_table = Vec::new();
+        if block_entries > 0 {
+            let data = archive.read_table(
+                block_pos,
+                block_entries * FileEntry::SIZE,
+                block_size,
+                block_table_key(),
+            )?;
+            let hi_block_table = if version >= FormatVersion::V2 && hi_block_pos != 0 {
+                Some(archive.read_at(hi_block_pos, block_entries * 2)?)
+            } else {
+                None
+            };
+            block_table = parse_block_table(&data, hi_block_table.as_deref());
+        }
+
+        if version >= FormatVersion::V3 && het_pos != 0 && bet_pos != 0 {
+            let het = archive.read_ext_table(het_pos, HET_MAGIC, hash_table_key())?;
+            let bet = archive.read_ext_table(bet_pos, BET_MAGIC, block_table_key())?;
+            archive.het_table = Some(HetTable::parse(&het)?);
+            archive.bet_table = Some(BetTable::parse(&bet)?);
+        }
+
+        archive.files = match &archive.bet_table {
+            Some(bet) => bet.files.clone(),
+            None => block_table,
+        };
+
+        Ok(archive)
+    }
+
+    fn seek(&mut self, pos: u64) -> Result<()> {
+        self.reader.seek(SeekFrom::Start(self.offset + pos))?;
+        Ok(())
+    }
+
+    /// Read bytes at a position relative to the archive. The length comes
+    /// from the archive, so the buffer only grows as far as the data does.
+    fn read_at(&mut self, pos: u64, len: usize) -> Result<Vec<u8>> {
+        self.seek(pos)?;
+        let mut data = Vec::new();
+        (&mut self.reader).take(len as u64).read_to_end(&mut data)?;
+        if data.len() != len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{len} bytes at {pos} exceed the archive"),
+            )
+            .into());
+        }
+        Ok(data)
+    }
+
+    /// Read an encrypted table, compressed when its size in the header of
+    /// version 4 archives is smaller than its entries
+    fn read_table(&mut self, pos: u64, len: usize, stored_size: u64, key: u32) -> Result<Vec<u8>> {
+        let stored_len = match stored_size as usize {
+            0 => len,
+            size => size.min(len),
+        };
+        let mut data = self.read_at(pos, stored_len)?;
+        decrypt(&mut data, key);
+        if stored_len < len {
+            data = decompress(&data, len)?;
+        }
+        Ok(data)
+    }
+
+    /// Size of a HET or BET table, which version 3 archives don't store,
+    /// from the position of the table or archive end that follows it
+    fn ext_table_size(&self, pos: u64) -> Option<u64> {
+        let header = &self.header;
+        if pos == header.het_table_pos && header.het_table_size != 0 {
+            return Some(header.het_table_size);
+        }
+        if pos == header.bet_table_pos && header.bet_table_size != 0 {
+            return Some(header.bet_table_size);
+        }
+        [
+            header.het_table_pos,
+            header.bet_table_pos,
+            header.hash_table_pos,
+            header.block_table_pos,
+            header.hi_block_table_pos,
+            header.archive_size,
+        ]
+        .into_iter()
+        .filter(|&next| next > pos)
+        .min()
+        .map(|next| next - pos)
+    }
+
+    /// Read the data of a HET or BET table after its signature, version and
+    /// size
+    fn read_ext_table(&mut self, pos: u64, magic: [u8; 4], key: u32) -> Result<Vec<u8>> {
+        let ext_header = self.read_at(pos, 12)?;
+        if ext_header[0..4] != magic {
+            return Err(MpqError::InvalidTable(format!(
+                "expected {} table signature",
+                String::from_utf8_lossy(&magic[..3])
+            )));
+        }
+        let data_size = u32::from_le_bytes(ext_header[8..12].try_into().unwrap()) as usize;
+
+        let stored_size = self
+            .ext_table_size(pos)
+            .map(|size| (size as usize).saturating_sub(12).min(data_size))
+            .unwrap_or(data_size);
+        let mut data = self.read_at(pos + 12, stored_size)?;
+        decrypt(&mut data, key);
+        if stored_size < data_size {
+            data = decompress(&data, data_size)?;
+        }
+        Ok(data)
+    }
+
+    pub fn header(&self) -> &MpqHeader {
+        &self.header
+    }
+
+    pub fn user_data(&self) -> Option<&UserData> {
+        self.user_data.as_ref()
+    }
+
+    /// Position of the archive header in the file
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn hash_table(&self) -> &[HashEntry] {
+        &self.hash_table
+    }
+
+    pub fn het_table(&self) -> Option<&HetTable> {
+        self.het_table.as_ref()
+    }
+
+    /// Files of the BET table if the archive has one, otherwise of the
+    /// block table
+    pub fn files(&self) -> &[FileEntry] {
+        &self.files
+    }
+
+    /// Locale of the files to prefer when an archive has several versions of
+    /// a file, neutral files are used when there isn't one of the locale
+    pub fn set_locale(&mut self, locale: u16) {
+        self.locale = locale;
+    }
+
+    /// Index in [`files`](Self::files) of a file, including delete markers.
+    /// The hash table is preferred over the HET table, which has no locales.
+    pub fn find_file(&self, name: &str) -> Option<usize> {
+        if self.hash_table.is_empty() {
+            let (het, bet) = (self.het_table.as_ref()?, self.bet_table.as_ref()?);
+            return het.find(name, bet);
+        }
+
+        let pos = find_hash_entry(&self.hash_table, name, self.locale)?;
+        let index = self.hash_table[pos].block_index as usize;
+        self.files
+            .get(index)
+            .filter(|entry| entry.exists())
+            .map(|_| index)
+    }
+
+    /// Locales and indexes in [`files`](Self::files) of all versions of a
+    /// file. Archives with only a HET table have a single neutral version.
+    pub fn find_locales(&self, name: &str) -> Vec<(u16, usize)> {
+        if self.hash_table.is_empty() {
+            return self
+                .find_file(name)
+                .map(|index| (0, index))
+                .into_iter()
+                .collect();
+        }
+        find_hash_entries(&self.hash_table, name)
+            .into_iter()
+            .map(|pos| {
+                let entry = &self.hash_table[pos];
+                (entry.locale, entry.block_index as usize)
+            })
+            .filter(|&(_, index)| self.files.get(index).is_some_and(FileEntry::exists))
+            .collect()
+    }
+
+    /// Whether the archive has a file that isn't a delete marker
+    pub fn contains(&self, name: &str) -> bool {
+        self.find_file(name)
+            .is_some_and(|index| !self.files[index].is_delete_marker())
+    }
+
+    /// Read the contents of a file
+    pub fn read_file(&mut self, name: &str) -> Result<Vec<u8>> {
+        let index = self
+            .find_file(name)
+            .ok_or_else(|| MpqError::FileNotFound(name.to_owned()))?;
+        self.read_file_at(index, Some(name))
+    }
+
+    /// Read the contents of a file by its index in [`files`](Self::files).
+    /// The name is needed for encrypted files. Patch files are returned
+    /// without applying them.
+    pub fn read_file_at(&mut self, index: usize, name: Option<&str>) -> Result<Vec<u8>> {
+        self.read_file_and_compression(index, name)
+            .map(|(contents, _)| contents)
+    }
+
+    /// Read the contents of a file like [`read_file_at`](Self::read_file_at),
+    /// with the compression mask of its first compressed sector, or `None` if
+    /// no sector is compressed with a mask
+    pub(crate) fn read_file_and_compression(
+        &mut self,
+        index: usize,
+        name: Option<&str>,
+    ) -> Result<(Vec<u8>, Option<u8>)> {
+        let label = || {
+            name.map(str::to_owned)
+                .unwrap_or_else(|| format!("#{index}"))
+        };
+        let entry = *self
+            .files
+            .get(index)
+            .ok_or_else(|| MpqError::FileNotFound(label()))?;
+        if !entry.exists() || entry.is_delete_marker() {
+            return Err(MpqError::FileNotFound(label()));
+        }
+
+        let key = if entry.flags.contains(FileFlags::ENCRYPTED) {
+            let name = name.ok_or(MpqError::UnknownKey(index))?;
+            Some(file_key(
+                name,
+                entry.position,
+                entry.file_size,
+                entry.flags.contains(FileFlags::FIX_KEY),
+            ))
+        } else {
+            None
+        };
+
+        let mut position = entry.position;
+        let mut compressed_size = entry.compressed_size as usize;
+        let mut file_size = entry.file_size as usize;
+        if entry.is_patch() {
+            let bytes = self.read_at(position, PatchInfo::SIZE)?;
+            let info = PatchInfo::from_bytes(bytes.as_slice().try_into().unwrap());
+            position += info.length as u64;
+            compressed_size = compressed_size.saturating_sub(info.length as usize);
+            file_size = info.data_size as usize;
+        }
+
+        if file_size == 0 {
+            return Ok((Vec::new(), None));
+        }
+
+        let mask = |sector: &[u8], expected: usize| {
+            let masked = entry.flags.contains(FileFlags::COMPRESS) && sector.len() < expected;
+            masked.then(|| sector.first().copied()).flatten()
+        };
+
+        let raw = self.read_at(position, compressed_size)?;
+        if entry.flags.contains(FileFlags::SINGLE_UNIT) {
+            let mut data = raw;
+            if let Some(key) = key {
+                decrypt(&mut data, key);
+            }
+            let compression = mask(&data, file_size);
+            return Ok((
+                decompress_sector(entry.flags, data, file_size)?,
+                compression,
+            ));
+        }
+
+        let sector_size = self.header.sector_size();
+        let sectors = file_size.div_ceil(sector_size);
+        let has_crc = entry.flags.contains(FileFlags::SECTOR_CRC) && entry.flags.is_compressed();
+
+        let offsets = if entry.flags.is_compressed() {
+            let count = sectors + 1 + has_crc as usize;
+            let mut table = raw
+                .get(..count * 4)
+                .ok_or_else(|| MpqError::InvalidTable(format!("truncated sectors of {}", label())))?
+                .to_vec();
+            if let Some(key) = key {
+                decrypt(&mut table, key.wrapping_sub(1));
+            }
+            table
+                .chunks_exact(4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+                .collect::<Vec<_>>()
+        } else {
+            (0..=sectors)
+                .map(|i| (i * sector_size).min(file_size))
+                .collect()
+        };
+        if offsets.windows(2).any(|w| w[0] > w[1]) || offsets.last() > Some(&raw.len()) {
+            return Err(MpqError::InvalidTable(format!(
+                "invalid sector offsets of {}",
+                label()
+            )));
+        }
+
+        let checksums = if has_crc {
+            let data = &raw[offsets[sectors]..offsets[sectors + 1]];
+            let data = if data.len() == sectors * 4 {
+                data.to_vec()
+            } else {
+                decompress(data, sectors * 4)?
+            };
+            Some(
+                data.chunks_exact(4)
+                    .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect::<Vec<_>>(),
+            )
+        } else {
+            None
+        };
+
+        // Sectors that don't decompress to the size of the block entry fail,
+        // so the bytes read bound the buffer until they do
+        let mut contents = Vec::with_capacity(file_size.min(raw.len()));
+        let mut compression = None;
+        for i in 0..sectors {
+            let mut sector = raw[offsets[i]..offsets[i + 1]].to_vec();
+            if let Some(key) = key {
+                decrypt(&mut sector, key.wrapping_add(i as u32));
+            }
+            let checksum = checksums.as_ref().and_then(|c| c.get(i)).copied();
+            if checksum.is_some_and(|c| c != 0 && sector_checksum(&sector) != c) {
+                return Err(MpqError::ChecksumMismatch {
+                    file: label(),
+                    sector: i,
+                });
+            }
+            let expected = sector_size.min(file_size - i * sector_size);
+            compression = compression.or_else(|| mask(&sector, expected));
+            contents.extend(decompress_sector(entry.flags, sector, expected)?);
+        }
+        Ok((contents, compression))
+    }
+
+    /// Names of the `(listfile)` that are in the archive, or none if it
+    /// doesn't have one
+    pub fn list_files(&mut self) -> Result<Vec<String>> {
+        let Some(index) = self.find_file(LISTFILE_NAME) else {
+            return Ok(Vec::new());
+        };
+        let data = self.read_file_at(index, Some(LISTFILE_NAME))?;
+        Ok(parse_listfile(&data)
+            .into_iter()
+            .filter(|name| self.contains(name))
+            .collect())
+    }
+
+    /// The `(attributes)` of the files
+    pub fn attributes(&mut self) -> Result<Attributes> {
+        let data = self.read_file(ATTRIBUTES_NAME)?;
+        Attributes::parse(&data, self.files.len())
+    }
+
+    /// Read a file and check it against its CRC32 and MD5 of the
+    /// `(attributes)`, if the archive has one
+    pub fn verify_file(&mut self, name: &str) -> Result<bool> {
+        let index = self
+            .find_file(name)
+            .ok_or_else(|| MpqError::FileNotFound(name.to_owned()))?;
+        let contents = self.read_file_at(index, Some(name))?;
+        if !self.contains(ATTRIBUTES_NAME) {
+            return Ok(true);
+        }
+        Ok(self.attributes()?.verify(index, &contents))
+    }
+}
+
+/// Decompress a sector, or a file stored as a single unit, that is smaller
+/// than its expected size
+fn decompress_sector(flags: FileFlags, mut data: Vec<u8>, size: usize) -> Result<Vec<u8>> {
+    if data.len() >= size || !flags.is_compressed() {
+        data.truncate(size);
+        return Ok(data);
+    }
+    if flags.contains(FileFlags::IMPLODE) {
+        explode(&data, size)
+    } else {
+        decompress(&data, size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::{
+        COMPRESSION_BZIP2, COMPRESSION_PKWARE, COMPRESSION_SPARSE, COMPRESSION_ZLIB,
+    };
+    use crate::test_archive::{TestArchive, TestFile};
+    use md5::Digest;
+    use std::io::Cursor;
+
+    fn text(len: usize) -> Vec<u8> {
+        let mut data = b"World of Warcraft ".repeat(len / 18 + 1);
+        data.truncate(len);
+        data[len / 2..len / 2 + 100.min(len / 2)].fill(0);
+        data
+    }
+
+    fn test_files() -> Vec<TestFile> {
+        let compressed = FileFlags::COMPRESS;
+        vec![
+            TestFile::new("plain.txt", &text(1300), FileFlags::empty()),
+            TestFile::new("zlib.txt", &text(1300), compressed | FileFlags::SECTOR_CRC),
+            TestFile::new("dir\\bzip2.txt", &text(2000), compressed).compression(COMPRESSION_BZIP2),
+            TestFile::new("sparse.txt", &text(700), compressed | FileFlags::SECTOR_CRC)
+                .compression(COMPRESSION_SPARSE | COMPRESSION_ZLIB),
+            TestFile::new("pkware.txt", &text(1100), compressed).compression(COMPRESSION_PKWARE),
+            TestFile::new("implode.txt", &text(600), FileFlags::IMPLODE),
+            TestFile::new(
+                "dir/encrypted.txt",
+                &text(1500),
+                compressed | FileFlags::ENCRYPTED | FileFlags::FIX_KEY | FileFlags::SECTOR_CRC,
+            ),
+            TestFile::new(
+                "single.txt",
+                &text(900),
+                compressed | FileFlags::SINGLE_UNIT | FileFlags::ENCRYPTED,
+            ),
+            TestFile::new("empty.txt", b"", compressed),
+            TestFile::new(
+                LISTFILE_NAME,
+                b"plain.txt\r\nzlib.txt\r\ndir\\bzip2.txt\r\nsparse.txt\r\npkware.txt\r\n\
+                  implode.txt\r\ndir\\encrypted.txt\r\nsingle.txt\r\nempty.txt\r\nmissing.txt\r\n",
+                compressed,
+            ),
+        ]
+    }
+
+    fn check_archive(test: &TestArchive) {
+        let mut archive = Archive::new(Cursor::new(test.build())).unwrap();
+        assert_eq!(archive.header().format_version, test.version);
+        assert_eq!(archive.offset(), 0x200);
+        assert_eq!(archive.user_data().is_some(), test.user_data);
+
+        for file in &test.files {
+            assert!(archive.contains(&file.name), "{}", file.name);
+            assert_eq!(
+                archive.read_file(&file.name).unwrap(),
+                file.data,
+                "{}",
+                file.name
+            );
+        }
+        // Either separator and case
+        assert_eq!(
+            archive.read_file("DIR/ENCRYPTED.TXT").unwrap(),
+            test.files[6].data
+        );
+        assert!(!archive.contains("missing.txt"));
+        assert!(matches!(
+            archive.read_file("missing.txt"),
+            Err(MpqError::FileNotFound(_))
+        ));
+
+        let index = archive.find_file("single.txt").unwrap();
+        assert!(matches!(
+            archive.read_file_at(index, None),
+            Err(MpqError::UnknownKey(_))
+        ));
+        let index = archive.find_file("zlib.txt").unwrap();
+        assert_eq!(
+            archive.read_file_at(index, None).unwrap(),
+            test.files[1].data
+        );
+
+        let names = archive.list_files().unwrap();
+        assert_eq!(names.len(), 9);
+        assert_eq!(names[2], "dir\\bzip2.txt");
+    }
+
+    #[test]
+    fn test_read_v1() {
+        let mut test = TestArchive::new(FormatVersion::V1);
+        test.files = test_files();
+        check_archive(&test);
+
+        let archive = Archive::new(Cursor::new(test.build())).unwrap();
+        assert!(archive.het_table().is_none());
+        assert_eq!(archive.files().len(), test.files.len());
+        assert_eq!(archive.hash_table().len(), 32);
+    }
+
+    #[test]
+    fn test_read_later_versions() {
+        let mut test = TestArchive::new(FormatVersion::V2);
+        test.files = test_files();
+        test.user_data = true;
+        test.sector_size_shift = 1;
+        check_archive(&test);
+
+        // Only HET and BET tables
+        test.version = FormatVersion::V3;
+        test.het_bet = true;
+        test.classic_tables = false;
+        check_archive(&test);
+        let archive = Archive::new(Cursor::new(test.build())).unwrap();
+        assert!(archive.hash_table().is_empty());
+        assert_eq!(archive.het_table().unwrap().entry_count, 10);
+
+        test.version = FormatVersion::V4;
+        test.classic_tables = true;
+        test.compress_tables = true;
+        check_archive(&test);
+
+        // Without HET and BET tables
+        test.het_bet = false;
+        check_archive(&test);
+    }
+
+    #[test]
+    fn test_sector_checksum() {
+        assert_eq!(sector_checksum(b""), 0);
+        assert_eq!(sector_checksum(b"a"), 0x0061_0061);
+
+        let data = text(1300);
+        let test = TestArchive::new(FormatVersion::V1).file(TestFile::new(
+            "crc.txt",
+            &data,
+            FileFlags::COMPRESS | FileFlags::SECTOR_CRC,
+        ));
+        let mut bytes = test.build();
+        // After the header and the offsets of 3 sectors and the checksums
+        bytes[0x200 + 0x20 + 5 * 4 + 5] ^= 0xFF;
+
+        let mut archive = Archive::new(Cursor::new(bytes)).unwrap();
+        assert!(matches!(
+            archive.read_file("crc.txt"),
+            Err(MpqError::ChecksumMismatch { sector: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_table_beyond_archive() {
+        let test = TestArchive::new(FormatVersion::V1).file(TestFile::new(
+            "a.txt",
+            b"data",
+            FileFlags::empty(),
+        ));
+        let mut bytes = test.build();
+        // Hash table entries in the header after the junk in front of it
+        bytes[0x218..0x21C].copy_from_slice(&0x0FFF_FFFFu32.to_le_bytes());
+        assert!(matches!(
+            Archive::new(Cursor::new(bytes)),
+            Err(MpqError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_locales() {
+        let test = TestArchive::new(FormatVersion::V1)
+            .file(TestFile::new("a.txt", b"neutral", FileFlags::empty()))
+            .file(TestFile::new("a.txt", b"german", FileFlags::empty()).locale(0x407));
+        let mut archive = Archive::new(Cursor::new(test.build())).unwrap();
+        assert_eq!(archive.read_file("a.txt").unwrap(), b"neutral");
+        archive.set_locale(0x407);
+        assert_eq!(archive.read_file("a.txt").unwrap(), b"german");
+        archive.set_locale(0x40C);
+        assert_eq!(archive.read_file("a.txt").unwrap(), b"neutral");
+        assert_eq!(archive.find_locales("A.TXT").len(), 2);
+        assert!(archive.find_locales("b.txt").is_empty());
+    }
+
+    #[test]
+    fn test_attributes() {
+        let files = [b"first".as_slice(), b"second"];
+        let mut attributes = Attributes {
+            crc32: Some(
+                files
+                    .iter()
+                    .map(|f| crc32fast::hash(f))
+                    .chain([0])
+                    .collect(),
+            ),
+            filetimes: Some(vec![0x01D0_0000_0000_0000; 3]),
+            md5: Some(
+                files
+                    .iter()
+                    .map(|f| md5::Md5::digest(f).into())
+                    .chain([[0; 16]])
+                    .collect(),
+            ),
+            patch_bits: None,
+        };
+        let build = |attributes: &Attributes| {
+            TestArchive::new(FormatVersion::V1)
+                .file(TestFile::new("1.txt", files[0], FileFlags::empty()))
+                .file(TestFile::new("2.txt", files[1], FileFlags::COMPRESS))
+                .file(TestFile::new(
+                    ATTRIBUTES_NAME,
+                    &attributes.to_bytes(),
+                    FileFlags::COMPRESS,
+                ))
+                .build()
+        };
+
+        let mut archive = Archive::new(Cursor::new(build(&attributes))).unwrap();
+        assert_eq!(archive.attributes().unwrap(), attributes);
+        assert!(archive.verify_file("1.txt").unwrap());
+        assert!(archive.verify_file("2.txt").unwrap());
+
+        attributes.md5.as_mut().unwrap()[1] = [1; 16];
+        let mut archive = Archive::new(Cursor::new(build(&attributes))).unwrap();
+        assert!(archive.verify_file("1.txt").unwrap());
+        assert!(!archive.verify_file("2.txt").unwrap());
+    }
+}
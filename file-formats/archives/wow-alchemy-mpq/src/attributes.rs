@@ -0,0 +1,170 @@
+//! The `(attributes)` file with checksums and times of the files
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use md5::{Digest, Md5};
+
+use crate::error::{MpqError, Result};
+
+/// Name of the attributes file
+pub const ATTRIBUTES_NAME: &str = "(attributes)";
+
+pub const ATTRIBUTES_VERSION: u32 = 100;
+
+pub const ATTRIBUTE_CRC32: u32 = 0x01;
+pub const ATTRIBUTE_FILETIME: u32 = 0x02;
+pub const ATTRIBUTE_MD5: u32 = 0x04;
+pub const ATTRIBUTE_PATCH_BIT: u32 = 0x08;
+
+/// Windows `FILETIME` of a time, in 100 nanoseconds since 1601
+pub fn filetime(time: SystemTime) -> u64 {
+    const UNIX_EPOCH_FILETIME: u64 = 116_444_736_000_000_000;
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => UNIX_EPOCH_FILETIME + (since.as_nanos() / 100) as u64,
+        Err(e) => UNIX_EPOCH_FILETIME.saturating_sub((e.duration().as_nanos() / 100) as u64),
+    }
+}
+
+/// Attributes of each entry of the block table. Each kind is optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Attributes {
+    pub crc32: Option<Vec<u32>>,
+    /// Windows `FILETIME` of the files
+    pub filetimes: Option<Vec<u64>>,
+    pub md5: Option<Vec<[u8; 16]>>,
+    /// Whether files are patch files
+    pub patch_bits: Option<Vec<bool>>,
+}
+
+impl Attributes {
+    /// Parse the attributes of `file_count` files
+    pub fn parse(data: &[u8], file_count: usize) -> Result<Self> {
+        let truncated = || MpqError::InvalidTable("truncated (attributes)".into());
+        let mut rest = data;
+        let mut take = |len: usize| -> Result<&[u8]> {
+            let (head, tail) = rest.split_at_checked(len).ok_or_else(truncated)?;
+            rest = tail;
+            Ok(head)
+        };
+
+        let header = take(8)?;
+        let version = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let flags = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        if version != ATTRIBUTES_VERSION {
+            return Err(MpqError::InvalidTable(format!(
+                "(attributes) version {version}"
+            )));
+        }
+
+        let mut attributes = Self::default();
+        if flags & ATTRIBUTE_CRC32 != 0 {
+            attributes.crc32 = Some(
+                take(file_count * 4)?
+                    .chunks_exact(4)
+                    .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect(),
+            );
+        }
+        if flags & ATTRIBUTE_FILETIME != 0 {
+            attributes.filetimes = Some(
+                take(file_count * 8)?
+                    .chunks_exact(8)
+                    .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+                    .collect(),
+            );
+        }
+        if flags & ATTRIBUTE_MD5 != 0 {
+            attributes.md5 = Some(
+                take(file_count * 16)?
+                    .chunks_exact(16)
+                    .map(|b| b.try_into().unwrap())
+                    .collect(),
+            );
+        }
+        if flags & ATTRIBUTE_PATCH_BIT != 0 {
+            let bits = take(file_count.div_ceil(8))?;
+            attributes.patch_bits = Some(
+                (0..file_count)
+                    .map(|i| bits[i / 8] & (1 << (i % 8)) != 0)
+                    .collect(),
+            );
+        }
+        Ok(attributes)
+    }
+
+    /// Bytes of the attributes file
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut flags = 0;
+        let mut data = Vec::new();
+        if let Some(crc32) = &self.crc32 {
+            flags |= ATTRIBUTE_CRC32;
+            data.extend(crc32.iter().flat_map(|v| v.to_le_bytes()));
+        }
+        if let Some(filetimes) = &self.filetimes {
+            flags |= ATTRIBUTE_FILETIME;
+            data.extend(filetimes.iter().flat_map(|v| v.to_le_bytes()));
+        }
+        if let Some(md5) = &self.md5 {
+            flags |= ATTRIBUTE_MD5;
+            data.extend(md5.iter().flatten());
+        }
+        if let Some(patch_bits) = &self.patch_bits {
+            flags |= ATTRIBUTE_PATCH_BIT;
+            let mut bits = vec![0u8; patch_bits.len().div_ceil(8)];
+            for (i, _) in patch_bits.iter().enumerate().filter(|(_, bit)| **bit) {
+                bits[i / 8] |= 1 << (i % 8);
+            }
+            data.extend(bits);
+        }
+
+        let mut bytes = Vec::with_capacity(8 + data.len());
+        bytes.extend(ATTRIBUTES_VERSION.to_le_bytes());
+        bytes.extend(flags.to_le_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    /// Whether the contents of a file match its CRC32 and MD5. A zero
+    /// checksum, or a missing one, isn't checked.
+    pub fn verify(&self, index: usize, contents: &[u8]) -> bool {
+        let crc_ok = match self.crc32.as_ref().and_then(|crc32| crc32.get(index)) {
+            Some(&crc) if crc != 0 => crc32fast::hash(contents) == crc,
+            _ => true,
+        };
+        let md5_ok = match self.md5.as_ref().and_then(|md5| md5.get(index)) {
+            Some(md5) if *md5 != [0; 16] => Md5::digest(contents).as_slice() == md5,
+            _ => true,
+        };
+        crc_ok && md5_ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attributes_roundtrip() {
+        let attributes = Attributes {
+            crc32: Some(vec![crc32fast::hash(b"abc"), 0, 3]),
+            filetimes: Some(vec![1, 2, 3]),
+            md5: Some(vec![Md5::digest(b"abc").into(), [0; 16], [1; 16]]),
+            patch_bits: Some(vec![false, true, false]),
+        };
+        let bytes = attributes.to_bytes();
+        assert_eq!(Attributes::parse(&bytes, 3).unwrap(), attributes);
+        assert!(Attributes::parse(&bytes, 4).is_err());
+
+        assert!(attributes.verify(0, b"abc"));
+        assert!(!attributes.verify(0, b"abd"));
+        assert!(attributes.verify(1, b"anything"));
+        assert!(!attributes.verify(2, b"abc"));
+    }
+
+    #[test]
+    fn test_filetime() {
+        assert_eq!(filetime(UNIX_EPOCH), 116_444_736_000_000_000);
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        assert_eq!(filetime(time), 133_444_736_000_000_000);
+    }
+}
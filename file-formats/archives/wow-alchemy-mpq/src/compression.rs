@@ -0,0 +1,189 @@
+//! Compression of sectors and tables
+
+use std::io::{Read, Write};
+
+use crate::error::{MpqError, Result};
+use crate::explode::{explode, implode};
+
+/// Huffman coding, only used by WAVE files
+pub const COMPRESSION_HUFFMAN: u8 = 0x01;
+pub const COMPRESSION_ZLIB: u8 = 0x02;
+pub const COMPRESSION_PKWARE: u8 = 0x08;
+pub const COMPRESSION_BZIP2: u8 = 0x10;
+/// Runs of zeros, combined with zlib or bzip2
+pub const COMPRESSION_SPARSE: u8 = 0x20;
+pub const COMPRESSION_ADPCM_MONO: u8 = 0x40;
+pub const COMPRESSION_ADPCM_STEREO: u8 = 0x80;
+/// LZMA, which isn't combined with other methods
+pub const COMPRESSION_LZMA: u8 = 0x12;
+
+const SUPPORTED: u8 =
+    COMPRESSION_ZLIB | COMPRESSION_PKWARE | COMPRESSION_BZIP2 | COMPRESSION_SPARSE;
+
+fn read_all(mut reader: impl Read, size: usize, method: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(size);
+    reader
+        .read_to_end(&mut out)
+        .map_err(|e| MpqError::Decompression(format!("{method}: {e}")))?;
+    Ok(out)
+}
+
+fn unsparse(data: &[u8]) -> Result<Vec<u8>> {
+    let (size, mut data) = data
+        .split_first_chunk::<4>()
+        .ok_or_else(|| MpqError::Decompression("sparse data without size".into()))?;
+    let size = u32::from_be_bytes(*size) as usize;
+
+    let mut out = Vec::with_capacity(size);
+    while let Some((&byte, rest)) = data.split_first() {
+        if byte & 0x80 != 0 {
+            let len = ((byte & 0x7F) as usize + 1).min(rest.len());
+            out.extend_from_slice(&rest[..len]);
+            data = &rest[len..];
+        } else {
+            out.resize(out.len() + (byte & 0x7F) as usize + 3, 0);
+            data = rest;
+        }
+    }
+    out.truncate(size);
+    Ok(out)
+}
+
+/// Decompress data that starts with the mask of the compression methods,
+/// which are undone in the reverse order they were applied
+pub fn decompress(data: &[u8], size: usize) -> Result<Vec<u8>> {
+    let (&mask, data) = data
+        .split_first()
+        .ok_or_else(|| MpqError::Decompression("empty compressed data".into()))?;
+    if mask == COMPRESSION_LZMA || mask & !SUPPORTED != 0 {
+        return Err(MpqError::UnsupportedCompression(mask));
+    }
+
+    let mut out = data.to_vec();
+    if mask & COMPRESSION_BZIP2 != 0 {
+        out = read_all(bzip2::read::BzDecoder::new(out.as_slice()), size, "bzip2")?;
+    }
+    if mask & COMPRESSION_PKWARE != 0 {
+        out = explode(&out, size)?;
+    }
+    if mask & COMPRESSION_ZLIB != 0 {
+        out = read_all(flate2::read::ZlibDecoder::new(out.as_slice()), size, "zlib")?;
+    }
+    if mask & COMPRESSION_SPARSE != 0 {
+        out = unsparse(&out)?;
+    }
+
+    if out.len() != size {
+        return Err(MpqError::Decompression(format!(
+            "expected {size} bytes, got {}",
+            out.len()
+        )));
+    }
+    Ok(out)
+}
+
+/// Compress data with the methods of a mask, and put the mask in front of
+/// it
+pub fn compress(data: &[u8], mask: u8) -> Result<Vec<u8>> {
+    if mask == COMPRESSION_LZMA || mask & !SUPPORTED != 0 {
+        return Err(MpqError::UnsupportedCompression(mask));
+    }
+
+    let mut out = data.to_vec();
+    if mask & COMPRESSION_SPARSE != 0 {
+        out = sparse(&out);
+    }
+    if mask & COMPRESSION_ZLIB != 0 {
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(&out)?;
+        out = encoder.finish()?;
+    }
+    if mask & COMPRESSION_PKWARE != 0 {
+        out = implode(&out);
+    }
+    if mask & COMPRESSION_BZIP2 != 0 {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::best());
+        encoder.write_all(&out)?;
+        out = encoder.finish()?;
+    }
+
+    let mut compressed = Vec::with_capacity(out.len() + 1);
+    compressed.push(mask);
+    compressed.extend(out);
+    Ok(compressed)
+}
+
+/// Sparse compression of runs of zeros
+pub fn sparse(data: &[u8]) -> Vec<u8> {
+    let mut out = (data.len() as u32).to_be_bytes().to_vec();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let zeros = rest.iter().take_while(|&&b| b == 0).count().min(0x7F + 3);
+        if zeros >= 3 {
+            out.push((zeros - 3) as u8);
+            rest = &rest[zeros..];
+        } else {
+            let len = rest
+                .windows(3)
+                .position(|w| w == [0, 0, 0])
+                .unwrap_or(rest.len())
+                .clamp(1, 0x80);
+            out.push(0x80 | (len - 1) as u8);
+            out.extend_from_slice(&rest[..len]);
+            rest = &rest[len..];
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decompress() {
+        let mut data = vec![0u8; 300];
+        data.extend_from_slice(b"some text some text some text");
+        data.extend_from_slice(&[0, 0, 1, 0, 0, 0, 0]);
+
+        let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::best());
+        zlib.write_all(&sparse(&data)).unwrap();
+        let mut compressed = vec![COMPRESSION_ZLIB | COMPRESSION_SPARSE];
+        compressed.extend(zlib.finish().unwrap());
+        assert_eq!(decompress(&compressed, data.len()).unwrap(), data);
+
+        let mut bzip2 = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::best());
+        bzip2.write_all(&data).unwrap();
+        let mut compressed = vec![COMPRESSION_BZIP2];
+        compressed.extend(bzip2.finish().unwrap());
+        assert_eq!(decompress(&compressed, data.len()).unwrap(), data);
+
+        let mut compressed = vec![COMPRESSION_PKWARE];
+        compressed.extend(implode(&data));
+        assert_eq!(decompress(&compressed, data.len()).unwrap(), data);
+
+        for mask in [
+            COMPRESSION_ZLIB,
+            COMPRESSION_BZIP2 | COMPRESSION_SPARSE,
+            COMPRESSION_PKWARE,
+        ] {
+            let compressed = compress(&data, mask).unwrap();
+            assert_eq!(compressed[0], mask);
+            assert!(compressed.len() < data.len());
+            assert_eq!(decompress(&compressed, data.len()).unwrap(), data);
+        }
+        assert!(matches!(
+            compress(&data, COMPRESSION_ADPCM_MONO),
+            Err(MpqError::UnsupportedCompression(COMPRESSION_ADPCM_MONO))
+        ));
+
+        assert!(matches!(
+            decompress(&[COMPRESSION_LZMA, 0], 1),
+            Err(MpqError::UnsupportedCompression(COMPRESSION_LZMA))
+        ));
+        assert!(matches!(
+            decompress(&compressed, data.len() + 1),
+            Err(MpqError::Decompression(_))
+        ));
+    }
+}
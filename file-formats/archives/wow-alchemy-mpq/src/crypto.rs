@@ -0,0 +1,222 @@
+//! Name hashes and encryption of MPQ archives
+
+/// Hash type of [`hash_string`] for the start position in the hash table
+pub const HASH_TABLE_OFFSET: u32 = 0;
+/// Hash type of [`hash_string`] for the first name check of hash entries
+pub const HASH_NAME_A: u32 = 1;
+/// Hash type of [`hash_string`] for the second name check of hash entries
+pub const HASH_NAME_B: u32 = 2;
+/// Hash type of [`hash_string`] for encryption keys
+pub const HASH_FILE_KEY: u32 = 3;
+
+const fn build_crypt_table() -> [u32; 0x500] {
+    let mut table = [0u32; 0x500];
+    let mut seed: u32 = 0x0010_0001;
+    let mut index1 = 0;
+    while index1 < 0x100 {
+        let mut index2 = index1;
+        let mut i = 0;
+        while i < 5 {
+            seed = (seed * 125 + 3) % 0x2A_AAAB;
+            let high = (seed & 0xFFFF) << 0x10;
+            seed = (seed * 125 + 3) % 0x2A_AAAB;
+            let low = seed & 0xFFFF;
+            table[index2] = high | low;
+            index2 += 0x100;
+            i += 1;
+        }
+        index1 += 1;
+    }
+    table
+}
+
+static CRYPT_TABLE: [u32; 0x500] = build_crypt_table();
+
+/// Names are hashed case-insensitively, with either path separator
+fn normalize(byte: u8) -> u8 {
+    match byte {
+        b'/' => b'\\',
+        _ => byte.to_ascii_uppercase(),
+    }
+}
+
+/// Hash of a file name of the given type
+pub fn hash_string(name: &str, hash_type: u32) -> u32 {
+    let mut seed1: u32 = 0x7FED_7FED;
+    let mut seed2: u32 = 0xEEEE_EEEE;
+    for &byte in name.as_bytes() {
+        let ch = normalize(byte) as u32;
+        seed1 = CRYPT_TABLE[(hash_type * 0x100 + ch) as usize] ^ seed1.wrapping_add(seed2);
+        seed2 = ch
+            .wrapping_add(seed1)
+            .wrapping_add(seed2)
+            .wrapping_add(seed2 << 5)
+            .wrapping_add(3);
+    }
+    seed1
+}
+
+/// Key of the hash table and the HET table
+pub fn hash_table_key() -> u32 {
+    hash_string("(hash table)", HASH_FILE_KEY)
+}
+
+/// Key of the block table and the BET table
+pub fn block_table_key() -> u32 {
+    hash_string("(block table)", HASH_FILE_KEY)
+}
+
+/// Encryption key of a file, from the name without its directories. With
+/// `fix_key` it's adjusted by the position of the file in the archive and
+/// its size.
+pub fn file_key(name: &str, position: u64, file_size: u32, fix_key: bool) -> u32 {
+    let base_name = name.rsplit(['\\', '/']).next().unwrap_or(name);
+    let key = hash_string(base_name, HASH_FILE_KEY);
+    if fix_key {
+        key.wrapping_add(position as u32) ^ file_size
+    } else {
+        key
+    }
+}
+
+fn next_key(key: u32) -> u32 {
+    ((!key << 0x15).wrapping_add(0x1111_1111)) | (key >> 0x0B)
+}
+
+/// Decrypt data in place. Trailing bytes that don't fill a `u32` aren't
+/// encrypted.
+pub fn decrypt(data: &mut [u8], mut key: u32) {
+    let mut seed: u32 = 0xEEEE_EEEE;
+    for chunk in data.chunks_exact_mut(4) {
+        seed = seed.wrapping_add(CRYPT_TABLE[0x400 + (key & 0xFF) as usize]);
+        let value =
+            u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) ^ key.wrapping_add(seed);
+        key = next_key(key);
+        seed = value
+            .wrapping_add(seed)
+            .wrapping_add(seed << 5)
+            .wrapping_add(3);
+        chunk.copy_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// Encrypt data in place, the reverse of [`decrypt`]
+pub fn encrypt(data: &mut [u8], mut key: u32) {
+    let mut seed: u32 = 0xEEEE_EEEE;
+    for chunk in data.chunks_exact_mut(4) {
+        seed = seed.wrapping_add(CRYPT_TABLE[0x400 + (key & 0xFF) as usize]);
+        let value = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        chunk.copy_from_slice(&(value ^ key.wrapping_add(seed)).to_le_bytes());
+        key = next_key(key);
+        seed = value
+            .wrapping_add(seed)
+            .wrapping_add(seed << 5)
+            .wrapping_add(3);
+    }
+}
+
+/// Bob Jenkins' `hashlittle2`, returning the `c` and `b` values
+fn hashlittle2(key: &[u8], pc: u32, pb: u32) -> (u32, u32) {
+    fn mix(a: &mut u32, b: &mut u32, c: &mut u32) {
+        *a = a.wrapping_sub(*c) ^ c.rotate_left(4);
+        *c = c.wrapping_add(*b);
+        *b = b.wrapping_sub(*a) ^ a.rotate_left(6);
+        *a = a.wrapping_add(*c);
+        *c = c.wrapping_sub(*b) ^ b.rotate_left(8);
+        *b = b.wrapping_add(*a);
+        *a = a.wrapping_sub(*c) ^ c.rotate_left(16);
+        *c = c.wrapping_add(*b);
+        *b = b.wrapping_sub(*a) ^ a.rotate_left(19);
+        *a = a.wrapping_add(*c);
+        *c = c.wrapping_sub(*b) ^ b.rotate_left(4);
+        *b = b.wrapping_add(*a);
+    }
+
+    fn finalize(a: &mut u32, b: &mut u32, c: &mut u32) {
+        *c = (*c ^ *b).wrapping_sub(b.rotate_left(14));
+        *a = (*a ^ *c).wrapping_sub(c.rotate_left(11));
+        *b = (*b ^ *a).wrapping_sub(a.rotate_left(25));
+        *c = (*c ^ *b).wrapping_sub(b.rotate_left(16));
+        *a = (*a ^ *c).wrapping_sub(c.rotate_left(4));
+        *b = (*b ^ *a).wrapping_sub(a.rotate_left(14));
+        *c = (*c ^ *b).wrapping_sub(b.rotate_left(24));
+    }
+
+    let word = |bytes: &[u8]| {
+        let mut padded = [0u8; 4];
+        padded[..bytes.len()].copy_from_slice(bytes);
+        u32::from_le_bytes(padded)
+    };
+
+    let mut a = 0xDEAD_BEEFu32
+        .wrapping_add(key.len() as u32)
+        .wrapping_add(pc);
+    let mut b = a;
+    let mut c = a.wrapping_add(pb);
+
+    let mut rest = key;
+    while rest.len() > 12 {
+        a = a.wrapping_add(word(&rest[0..4]));
+        b = b.wrapping_add(word(&rest[4..8]));
+        c = c.wrapping_add(word(&rest[8..12]));
+        mix(&mut a, &mut b, &mut c);
+        rest = &rest[12..];
+    }
+    if rest.is_empty() {
+        return (c, b);
+    }
+    a = a.wrapping_add(word(&rest[..rest.len().min(4)]));
+    if rest.len() > 4 {
+        b = b.wrapping_add(word(&rest[4..rest.len().min(8)]));
+    }
+    if rest.len() > 8 {
+        c = c.wrapping_add(word(&rest[8..]));
+    }
+    finalize(&mut a, &mut b, &mut c);
+    (c, b)
+}
+
+/// 64 bit name hash of the HET and BET tables
+pub fn jenkins_hash(name: &str) -> u64 {
+    let normalized = name
+        .bytes()
+        .map(|byte| match byte {
+            b'/' => b'\\',
+            _ => byte.to_ascii_lowercase(),
+        })
+        .collect::<Vec<_>>();
+    let (secondary, primary) = hashlittle2(&normalized, 1, 2);
+    ((primary as u64) << 32) | secondary as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashes() {
+        assert_eq!(hash_table_key(), 0xC3AF_3770);
+        assert_eq!(block_table_key(), 0xEC83_B3A3);
+        assert_eq!(
+            hash_string("interface/glues/x.blp", HASH_NAME_A),
+            hash_string("INTERFACE\\GLUES\\X.BLP", HASH_NAME_A)
+        );
+
+        let text = b"Four score and seven years ago";
+        assert_eq!(hashlittle2(text, 0, 0).0, 0x1777_0551);
+        assert_eq!(hashlittle2(text, 1, 0).0, 0xCD62_8161);
+        assert_eq!(hashlittle2(b"", 0, 0), (0xDEAD_BEEF, 0xDEAD_BEEF));
+        assert_eq!(jenkins_hash("A/b.txt"), jenkins_hash("a\\B.TXT"));
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let plain = (0..=41u8).collect::<Vec<_>>();
+        let mut data = plain.clone();
+        encrypt(&mut data, hash_table_key());
+        assert_ne!(data[..40], plain[..40]);
+        assert_eq!(data[40..], plain[40..]);
+        decrypt(&mut data, hash_table_key());
+        assert_eq!(data, plain);
+    }
+}
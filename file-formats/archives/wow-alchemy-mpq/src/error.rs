@@ -0,0 +1,70 @@
+//! Error handling for MPQ archives
+
+use std::io;
+use thiserror::Error;
+
+/// Errors that can occur when working with MPQ archives
+#[derive(Debug, Error)]
+pub enum MpqError {
+    /// An I/O error occurred
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    /// No archive header was found in the file
+    #[error("No MPQ header found")]
+    HeaderNotFound,
+
+    /// The archive header is malformed
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
+
+    /// A hash, block, HET or BET table is malformed
+    #[error("Invalid table: {0}")]
+    InvalidTable(String),
+
+    /// The file is not in the archive
+    #[error("File not found: {0}")]
+    FileNotFound(String),
+
+    /// The file is encrypted and its name, which the key is derived from, is
+    /// unknown
+    #[error("Unknown encryption key of file #{0}")]
+    UnknownKey(usize),
+
+    /// The data is compressed with methods that aren't supported
+    #[error("Unsupported compression: {0:#04x}")]
+    UnsupportedCompression(u8),
+
+    /// Compressed data is malformed
+    #[error("Decompression error: {0}")]
+    Decompression(String),
+
+    /// A sector doesn't match its checksum
+    #[error("Checksum mismatch in sector {sector} of {file}")]
+    ChecksumMismatch {
+        /// The name of the file
+        file: String,
+        /// The index of the sector
+        sector: usize,
+    },
+
+    /// A file of an archive that is rewritten isn't in its `(listfile)`, so
+    /// it can't be copied
+    #[error("File #{0} is not in the (listfile)")]
+    UnnamedFile(usize),
+
+    /// A file or archive exceeds the sizes the format version can store
+    #[error("Too large: {0}")]
+    TooLarge(String),
+
+    /// Sector sizes are powers of two from 512 bytes
+    #[error("Invalid sector size: {0}")]
+    InvalidSectorSize(usize),
+
+    /// A patch file is malformed or doesn't apply to the file it patches
+    #[error("Invalid patch: {0}")]
+    InvalidPatch(String),
+}
+
+/// Type alias for Results from MPQ operations
+pub type Result<T> = std::result::Result<T, MpqError>;
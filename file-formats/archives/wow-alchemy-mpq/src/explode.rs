@@ -0,0 +1,356 @@
+//! The PKWARE Data Compression Library "implode" method, with decompression
+//! after Mark Adler's blast.c
+
+use std::sync::OnceLock;
+
+use crate::error::{MpqError, Result};
+
+const MAX_BITS: usize = 13;
+
+/// Compact code lengths: the low 4 bits of each byte are a code length and
+/// the high 4 bits the number of consecutive symbols with it, minus one
+const LITERAL_LENGTHS: [u8; 98] = [
+    11, 124, 8, 7, 28, 7, 188, 13, 76, 4, 10, 8, 12, 10, 12, 10, 8, 23, 8, 9, 7, 6, 7, 8, 7, 6, 55,
+    8, 23, 24, 12, 11, 7, 9, 11, 12, 6, 7, 22, 5, 7, 24, 6, 11, 9, 6, 7, 22, 7, 11, 38, 7, 9, 8,
+    25, 11, 8, 11, 9, 12, 8, 12, 5, 38, 5, 38, 5, 11, 7, 5, 6, 21, 6, 10, 53, 8, 7, 24, 10, 27, 44,
+    253, 253, 253, 252, 252, 252, 13, 12, 45, 12, 45, 12, 61, 12, 45, 44, 173,
+];
+const LENGTH_LENGTHS: [u8; 6] = [2, 35, 36, 53, 38, 23];
+const DISTANCE_LENGTHS: [u8; 7] = [2, 20, 53, 230, 247, 151, 248];
+
+const LENGTH_BASE: [u16; 16] = [3, 2, 4, 5, 6, 7, 8, 9, 10, 12, 16, 24, 40, 72, 136, 264];
+const LENGTH_EXTRA: [u32; 16] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8];
+
+/// Length that marks the end of the stream
+const END_LENGTH: usize = 519;
+
+/// Canonical Huffman code as the number of codes of each length and the
+/// symbols ordered by code
+struct Huffman {
+    count: [u16; MAX_BITS + 1],
+    symbol: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(compact: &[u8]) -> Self {
+        let lengths = compact
+            .iter()
+            .flat_map(|byte| std::iter::repeat_n(byte & 0x0F, (byte >> 4) as usize + 1))
+            .collect::<Vec<_>>();
+
+        let mut count = [0u16; MAX_BITS + 1];
+        for &len in &lengths {
+            count[len as usize] += 1;
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offsets[len + 1] = offsets[len] + count[len];
+        }
+
+        let mut symbol = vec![0u16; lengths.len()];
+        for (sym, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbol[offsets[len as usize] as usize] = sym as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+
+        Self { count, symbol }
+    }
+}
+
+struct Codes {
+    literal: Huffman,
+    length: Huffman,
+    distance: Huffman,
+}
+
+fn codes() -> &'static Codes {
+    static CODES: OnceLock<Codes> = OnceLock::new();
+    CODES.get_or_init(|| Codes {
+        literal: Huffman::new(&LITERAL_LENGTHS),
+        length: Huffman::new(&LENGTH_LENGTHS),
+        distance: Huffman::new(&DISTANCE_LENGTHS),
+    })
+}
+
+/// Reads bits from the lowest of each byte
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    fn bits(&mut self, need: u32) -> Result<u32> {
+        let mut value = self.buffer;
+        while self.count < need {
+            let byte = *self
+                .data
+                .get(self.pos)
+                .ok_or_else(|| MpqError::Decompression("unexpected end of imploded data".into()))?;
+            self.pos += 1;
+            value |= (byte as u32) << self.count;
+            self.count += 8;
+        }
+        self.buffer = value >> need;
+        self.count -= need;
+        Ok(value & ((1 << need) - 1))
+    }
+
+    /// Decode a symbol. Codes are stored with their bits inverted.
+    fn decode(&mut self, huffman: &Huffman) -> Result<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..=MAX_BITS {
+            code |= (self.bits(1)? ^ 1) as i32;
+            let count = huffman.count[len] as i32;
+            if code < first + count {
+                return Ok(huffman.symbol[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(MpqError::Decompression("invalid imploded code".into()))
+    }
+}
+
+/// Decompress imploded data, stopping at the end of the stream or once
+/// `size` bytes are written
+pub fn explode(data: &[u8], size: usize) -> Result<Vec<u8>> {
+    let codes = codes();
+    let mut bits = BitReader::new(data);
+
+    let coded_literals = bits.bits(8)?;
+    if coded_literals > 1 {
+        return Err(MpqError::Decompression(format!(
+            "invalid imploded literal mode {coded_literals}"
+        )));
+    }
+    let dictionary_bits = bits.bits(8)?;
+    if !(4..=6).contains(&dictionary_bits) {
+        return Err(MpqError::Decompression(format!(
+            "invalid imploded dictionary size {dictionary_bits}"
+        )));
+    }
+
+    let mut out = Vec::with_capacity(size);
+    while out.len() < size {
+        if bits.bits(1)? != 0 {
+            let symbol = bits.decode(&codes.length)? as usize;
+            let len = LENGTH_BASE[symbol] as usize + bits.bits(LENGTH_EXTRA[symbol])? as usize;
+            if len == END_LENGTH {
+                break;
+            }
+
+            let extra_bits = if len == 2 { 2 } else { dictionary_bits };
+            let distance = ((bits.decode(&codes.distance)? as usize) << extra_bits)
+                + bits.bits(extra_bits)? as usize
+                + 1;
+            if distance > out.len() {
+                return Err(MpqError::Decompression(
+                    "imploded distance too far back".into(),
+                ));
+            }
+            for _ in 0..len {
+                out.push(out[out.len() - distance]);
+            }
+        } else {
+            let literal = if coded_literals == 1 {
+                bits.decode(&codes.literal)? as u8
+            } else {
+                bits.bits(8)? as u8
+            };
+            out.push(literal);
+        }
+    }
+
+    out.truncate(size);
+    Ok(out)
+}
+
+/// Bits of the stream, from the lowest of each byte
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn push(&mut self, value: u32, bits: u32) {
+        self.buffer |= value << self.count;
+        self.count += bits;
+        while self.count >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Push a code from its highest bit, inverted
+    fn push_code(&mut self, (code, len): (u32, u32)) {
+        for i in (0..len).rev() {
+            self.push((code >> i & 1) ^ 1, 1);
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+impl Huffman {
+    /// Code and code length of each symbol
+    fn codes(&self) -> Vec<(u32, u32)> {
+        let mut codes = vec![(0, 0); self.symbol.len()];
+        let (mut code, mut index) = (0u32, 0);
+        for len in 1..=MAX_BITS {
+            for _ in 0..self.count[len] {
+                codes[self.symbol[index] as usize] = (code, len as u32);
+                code += 1;
+                index += 1;
+            }
+            code <<= 1;
+        }
+        codes
+    }
+}
+
+/// Implode data with uncoded literals and the longest earlier match at each
+/// position, with a 4 KiB dictionary
+pub fn implode(data: &[u8]) -> Vec<u8> {
+    const DICTIONARY_BITS: u32 = 6;
+    /// Earlier positions with the same two bytes that are tried
+    const MAX_CHAIN: usize = 256;
+    const NONE: usize = usize::MAX;
+
+    let codes = codes();
+    let (length_codes, distance_codes) = (codes.length.codes(), codes.distance.codes());
+
+    let mut writer = BitWriter {
+        out: Vec::new(),
+        buffer: 0,
+        count: 0,
+    };
+    writer.push(0, 8);
+    writer.push(DICTIONARY_BITS, 8);
+
+    // Chains of the positions starting with each pair of bytes, the most
+    // recent first
+    let pair = |i: usize| (data[i] as usize) << 8 | data[i + 1] as usize;
+    let mut head = vec![NONE; 1 << 16];
+    let mut previous = vec![NONE; data.len()];
+    let mut inserted = 0;
+
+    let mut pos = 0;
+    while pos < data.len() {
+        while inserted < pos {
+            if inserted + 1 < data.len() {
+                previous[inserted] = head[pair(inserted)];
+                head[pair(inserted)] = inserted;
+            }
+            inserted += 1;
+        }
+
+        let max_len = (data.len() - pos).min(END_LENGTH - 1);
+        let window_start = pos.saturating_sub(64 << DICTIONARY_BITS);
+        let mut best = (0, 0);
+        let mut start = if max_len >= 2 { head[pair(pos)] } else { NONE };
+        for _ in 0..MAX_CHAIN {
+            if start == NONE || start < window_start {
+                break;
+            }
+            let len = (0..max_len)
+                .take_while(|&i| data[start + i] == data[pos + i])
+                .count();
+            let distance = pos - start;
+            if len > best.0 && (len > 2 || distance <= 256) {
+                best = (len, distance);
+            }
+            start = previous[start];
+        }
+
+        let (len, distance) = best;
+        if len < 2 {
+            writer.push(0, 1);
+            writer.push(data[pos] as u32, 8);
+            pos += 1;
+            continue;
+        }
+
+        let symbol = (0..16)
+            .find(|&s| {
+                let base = LENGTH_BASE[s] as usize;
+                len >= base && (len - base) >> LENGTH_EXTRA[s] == 0
+            })
+            .unwrap();
+        writer.push(1, 1);
+        writer.push_code(length_codes[symbol]);
+        writer.push(
+            (len - LENGTH_BASE[symbol] as usize) as u32,
+            LENGTH_EXTRA[symbol],
+        );
+
+        let extra_bits = if len == 2 { 2 } else { DICTIONARY_BITS };
+        let distance = distance - 1;
+        writer.push_code(distance_codes[distance >> extra_bits]);
+        writer.push((distance & ((1 << extra_bits) - 1)) as u32, extra_bits);
+        pos += len;
+    }
+
+    writer.push(1, 1);
+    writer.push_code(length_codes[15]);
+    writer.push(0xFF, 8);
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_tables() {
+        let codes = codes();
+        for (huffman, symbols) in [
+            (&codes.literal, 256),
+            (&codes.length, 16),
+            (&codes.distance, 64),
+        ] {
+            assert_eq!(huffman.symbol.len(), symbols);
+            // Complete prefix codes
+            let kraft: f64 = (1..=MAX_BITS)
+                .map(|len| huffman.count[len] as f64 / (1u32 << len) as f64)
+                .sum();
+            assert_eq!(kraft, 1.0);
+        }
+    }
+
+    #[test]
+    fn test_explode() {
+        // Test vector of blast.c
+        let data = [0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+        assert_eq!(explode(&data, 100).unwrap(), b"AIAIAIAIAIAIA");
+        assert_eq!(explode(&data, 4).unwrap(), b"AIAI");
+
+        let text = b"repeated text, repeated text, repeated text \x00\x00\xff".repeat(20);
+        let imploded = implode(&text);
+        assert!(imploded.len() < text.len() / 4);
+        assert_eq!(explode(&imploded, text.len()).unwrap(), text);
+
+        assert!(explode(&data[..5], 100).is_err());
+    }
+}
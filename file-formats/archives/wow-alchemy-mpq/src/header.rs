@@ -0,0 +1,314 @@
+//! Archive header and the user data block in front of it
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::error::{MpqError, Result};
+
+/// Signature of the archive header
+pub const MPQ_MAGIC: [u8; 4] = *b"MPQ\x1A";
+/// Signature of the user data block
+pub const USER_DATA_MAGIC: [u8; 4] = *b"MPQ\x1B";
+
+/// Archives are searched at multiples of this offset in the file
+pub const HEADER_ALIGNMENT: u64 = 0x200;
+
+/// Format version of an archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FormatVersion {
+    /// Original format, used by all clients before Cataclysm
+    V1,
+    /// Adds the hi-block table for archives larger than 4 GiB
+    V2,
+    /// Adds the HET and BET tables
+    V3,
+    /// Adds table sizes and MD5 hashes of the tables
+    V4,
+}
+
+impl FormatVersion {
+    /// Size of the header of the version
+    pub fn header_size(&self) -> u32 {
+        match self {
+            FormatVersion::V1 => 0x20,
+            FormatVersion::V2 => 0x2C,
+            FormatVersion::V3 => 0x44,
+            FormatVersion::V4 => 0xD0,
+        }
+    }
+
+    fn from_raw(value: u16) -> Result<Self> {
+        match value {
+            0 => Ok(FormatVersion::V1),
+            1 => Ok(FormatVersion::V2),
+            2 => Ok(FormatVersion::V3),
+            3 => Ok(FormatVersion::V4),
+            _ => Err(MpqError::InvalidHeader(format!(
+                "unsupported format version {value}"
+            ))),
+        }
+    }
+}
+
+/// User data block that can precede the archive header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserData {
+    /// Maximum size of the user data
+    pub size: u32,
+    /// Offset of the archive header from the start of the block
+    pub header_offset: u32,
+    /// Size of the user data header
+    pub header_size: u32,
+}
+
+/// Archive header. Positions are relative to the start of the header,
+/// fields of later versions are zero in earlier ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MpqHeader {
+    pub format_version: FormatVersion,
+    pub header_size: u32,
+    pub archive_size: u64,
+    /// Sectors are `512 << sector_size_shift` bytes
+    pub sector_size_shift: u16,
+    pub hash_table_pos: u64,
+    pub block_table_pos: u64,
+    /// Number of entries of the hash table, a power of two
+    pub hash_table_entries: u32,
+    pub block_table_entries: u32,
+
+    pub hi_block_table_pos: u64,
+
+    pub het_table_pos: u64,
+    pub bet_table_pos: u64,
+
+    /// Sizes in bytes of the tables, which can be compressed
+    pub hash_table_size: u64,
+    pub block_table_size: u64,
+    pub hi_block_table_size: u64,
+    pub het_table_size: u64,
+    pub bet_table_size: u64,
+    pub raw_chunk_size: u32,
+    pub md5_block_table: [u8; 16],
+    pub md5_hash_table: [u8; 16],
+    pub md5_hi_block_table: [u8; 16],
+    pub md5_bet_table: [u8; 16],
+    pub md5_het_table: [u8; 16],
+    pub md5_header: [u8; 16],
+}
+
+impl MpqHeader {
+    /// Empty header of a version
+    pub fn new(format_version: FormatVersion, sector_size_shift: u16) -> Self {
+        Self {
+            format_version,
+            header_size: format_version.header_size(),
+            archive_size: 0,
+            sector_size_shift,
+            hash_table_pos: 0,
+            block_table_pos: 0,
+            hash_table_entries: 0,
+            block_table_entries: 0,
+            hi_block_table_pos: 0,
+            het_table_pos: 0,
+            bet_table_pos: 0,
+            hash_table_size: 0,
+            block_table_size: 0,
+            hi_block_table_size: 0,
+            het_table_size: 0,
+            bet_table_size: 0,
+            raw_chunk_size: 0,
+            md5_block_table: [0; 16],
+            md5_hash_table: [0; 16],
+            md5_hi_block_table: [0; 16],
+            md5_bet_table: [0; 16],
+            md5_het_table: [0; 16],
+            md5_header: [0; 16],
+        }
+    }
+
+    /// Size of the sectors files are split into
+    pub fn sector_size(&self) -> usize {
+        512 << self.sector_size_shift
+    }
+
+    /// Read a header after its signature
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let header_size = reader.read_u32::<LittleEndian>()?;
+        let archive_size = reader.read_u32::<LittleEndian>()?;
+        let format_version = FormatVersion::from_raw(reader.read_u16::<LittleEndian>()?)?;
+        let sector_size_shift = reader.read_u16::<LittleEndian>()?;
+
+        let mut header = Self::new(format_version, sector_size_shift);
+        header.header_size = header_size;
+        header.archive_size = archive_size as u64;
+        header.hash_table_pos = reader.read_u32::<LittleEndian>()? as u64;
+        header.block_table_pos = reader.read_u32::<LittleEndian>()? as u64;
+        header.hash_table_entries = reader.read_u32::<LittleEndian>()?;
+        header.block_table_entries = reader.read_u32::<LittleEndian>()?;
+
+        if header_size < format_version.header_size() {
+            return Err(MpqError::InvalidHeader(format!(
+                "header of {header_size} bytes for {format_version:?}"
+            )));
+        }
+        if sector_size_shift > 22 {
+            return Err(MpqError::InvalidHeader(format!(
+                "sector size shift {sector_size_shift}"
+            )));
+        }
+
+        if format_version >= FormatVersion::V2 {
+            header.hi_block_table_pos = reader.read_u64::<LittleEndian>()?;
+            header.hash_table_pos |= (reader.read_u16::<LittleEndian>()? as u64) << 32;
+            header.block_table_pos |= (reader.read_u16::<LittleEndian>()? as u64) << 32;
+        }
+
+        if format_version >= FormatVersion::V3 {
+            header.archive_size = reader.read_u64::<LittleEndian>()?;
+            header.bet_table_pos = reader.read_u64::<LittleEndian>()?;
+            header.het_table_pos = reader.read_u64::<LittleEndian>()?;
+        }
+
+        if format_version >= FormatVersion::V4 {
+            header.hash_table_size = reader.read_u64::<LittleEndian>()?;
+            header.block_table_size = reader.read_u64::<LittleEndian>()?;
+            header.hi_block_table_size = reader.read_u64::<LittleEndian>()?;
+            header.het_table_size = reader.read_u64::<LittleEndian>()?;
+            header.bet_table_size = reader.read_u64::<LittleEndian>()?;
+            header.raw_chunk_size = reader.read_u32::<LittleEndian>()?;
+            for md5 in [
+                &mut header.md5_block_table,
+                &mut header.md5_hash_table,
+                &mut header.md5_hi_block_table,
+                &mut header.md5_bet_table,
+                &mut header.md5_het_table,
+                &mut header.md5_header,
+            ] {
+                reader.read_exact(md5)?;
+            }
+        }
+
+        Ok(header)
+    }
+
+    /// Write the header with its signature
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let version = self.format_version;
+
+        writer.write_all(&MPQ_MAGIC)?;
+        writer.write_u32::<LittleEndian>(version.header_size())?;
+        writer.write_u32::<LittleEndian>(self.archive_size.min(u32::MAX as u64) as u32)?;
+        writer.write_u16::<LittleEndian>(version as u16)?;
+        writer.write_u16::<LittleEndian>(self.sector_size_shift)?;
+        writer.write_u32::<LittleEndian>(self.hash_table_pos as u32)?;
+        writer.write_u32::<LittleEndian>(self.block_table_pos as u32)?;
+        writer.write_u32::<LittleEndian>(self.hash_table_entries)?;
+        writer.write_u32::<LittleEndian>(self.block_table_entries)?;
+
+        if version >= FormatVersion::V2 {
+            writer.write_u64::<LittleEndian>(self.hi_block_table_pos)?;
+            writer.write_u16::<LittleEndian>((self.hash_table_pos >> 32) as u16)?;
+            writer.write_u16::<LittleEndian>((self.block_table_pos >> 32) as u16)?;
+        }
+
+        if version >= FormatVersion::V3 {
+            writer.write_u64::<LittleEndian>(self.archive_size)?;
+            writer.write_u64::<LittleEndian>(self.bet_table_pos)?;
+            writer.write_u64::<LittleEndian>(self.het_table_pos)?;
+        }
+
+        if version >= FormatVersion::V4 {
+            writer.write_u64::<LittleEndian>(self.hash_table_size)?;
+            writer.write_u64::<LittleEndian>(self.block_table_size)?;
+            writer.write_u64::<LittleEndian>(self.hi_block_table_size)?;
+            writer.write_u64::<LittleEndian>(self.het_table_size)?;
+            writer.write_u64::<LittleEndian>(self.bet_table_size)?;
+            writer.write_u32::<LittleEndian>(self.raw_chunk_size)?;
+            for md5 in [
+                &self.md5_block_table,
+                &self.md5_hash_table,
+                &self.md5_hi_block_table,
+                &self.md5_bet_table,
+                &self.md5_het_table,
+                &self.md5_header,
+            ] {
+                writer.write_all(md5)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Find the archive header at the start of the file or at a multiple of
+/// [`HEADER_ALIGNMENT`], following a user data block. Returns the offset
+/// of the archive in the file.
+pub fn find_header<R: Read + Seek>(reader: &mut R) -> Result<(u64, Option<UserData>, MpqHeader)> {
+    let file_size = reader.seek(SeekFrom::End(0))?;
+
+    let mut offset = 0;
+    while offset + 0x20 <= file_size {
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+
+        if magic == MPQ_MAGIC {
+            return Ok((offset, None, MpqHeader::read(reader)?));
+        }
+
+        if magic == USER_DATA_MAGIC {
+            let user_data = UserData {
+                size: reader.read_u32::<LittleEndian>()?,
+                header_offset: reader.read_u32::<LittleEndian>()?,
+                header_size: reader.read_u32::<LittleEndian>()?,
+            };
+            let archive_offset = offset + user_data.header_offset as u64;
+            reader.seek(SeekFrom::Start(archive_offset))?;
+            reader.read_exact(&mut magic)?;
+            if magic == MPQ_MAGIC {
+                return Ok((archive_offset, Some(user_data), MpqHeader::read(reader)?));
+            }
+        }
+
+        offset += HEADER_ALIGNMENT;
+    }
+
+    Err(MpqError::HeaderNotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_header_roundtrip() {
+        for version in [
+            FormatVersion::V1,
+            FormatVersion::V2,
+            FormatVersion::V3,
+            FormatVersion::V4,
+        ] {
+            let mut header = MpqHeader::new(version, 3);
+            header.hash_table_pos = 0x1234;
+            header.block_table_pos = 0x5678;
+            header.hash_table_entries = 16;
+            header.block_table_entries = 3;
+            header.archive_size = 0x9000;
+            if version >= FormatVersion::V4 {
+                header.md5_header = [7; 16];
+            }
+
+            let mut bytes = vec![0u8; 0x200];
+            header.write(&mut bytes).unwrap();
+            assert_eq!(bytes.len(), 0x200 + version.header_size() as usize);
+
+            let (offset, user_data, read) = find_header(&mut Cursor::new(&bytes)).unwrap();
+            assert_eq!(offset, 0x200);
+            assert_eq!(user_data, None);
+            assert_eq!(read, header);
+            assert_eq!(read.sector_size(), 4096);
+        }
+    }
+}
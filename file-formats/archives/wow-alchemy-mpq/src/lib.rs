@@ -0,0 +1,27 @@
+#![doc = include_str!("../README.md")]
+#![forbid(unsafe_code)]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+pub mod archive;
+pub mod attributes;
+pub mod compression;
+pub mod crypto;
+pub mod error;
+pub mod explode;
+pub mod header;
+pub mod listfile;
+pub mod patch;
+pub mod tables;
+pub mod writer;
+
+#[cfg(test)]
+mod test_archive;
+
+// Re-export primary types
+pub use archive::Archive;
+pub use attributes::Attributes;
+pub use error::{MpqError, Result};
+pub use header::{FormatVersion, MpqHeader};
+pub use patch::{PatchChain, client_load_order};
+pub use tables::{FileEntry, FileFlags};
+pub use writer::{ArchiveWriter, FileOptions};
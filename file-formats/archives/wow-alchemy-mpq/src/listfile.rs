@@ -0,0 +1,27 @@
+//! The `(listfile)` file with the names of the files
+
+/// Name of the list file
+pub const LISTFILE_NAME: &str = "(listfile)";
+
+/// Names of a list file, separated by new lines or semicolons
+pub fn parse_listfile(data: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(data)
+        .split(['\r', '\n', ';'])
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_listfile() {
+        assert_eq!(
+            parse_listfile(b"a.txt\r\nDBFilesClient\\Spell.dbc;b.txt\n\n c.txt \r\n"),
+            ["a.txt", "DBFilesClient\\Spell.dbc", "b.txt", "c.txt"]
+        );
+    }
+}
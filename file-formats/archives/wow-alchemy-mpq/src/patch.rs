@@ -0,0 +1,580 @@
+//! Patch chains: archives read in load order, where later archives override,
+//! delete or patch the files of earlier ones
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::{Path, PathBuf};
+
+use md5::{Digest, Md5};
+
+use crate::archive::Archive;
+use crate::error::{MpqError, Result};
+
+pub const PATCH_MAGIC: [u8; 4] = *b"PTCH";
+const MD5_MAGIC: [u8; 4] = *b"MD5_";
+const XFRM_MAGIC: [u8; 4] = *b"XFRM";
+const BSDIFF_MAGIC: [u8; 8] = *b"BSDIFF40";
+
+/// Size of the `PTCH`, `MD5_` and `XFRM` headers of a patch file
+const PATCH_HEADER_SIZE: usize = 0x44;
+
+/// Transformation of a patch file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchType {
+    /// The patch data is the new file
+    Copy,
+    /// The patch data is a bsdiff patch, optionally run-length encoded
+    Bsd0,
+}
+
+/// A parsed `PTCH` patch file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch<'a> {
+    pub size_before: u32,
+    pub size_after: u32,
+    pub md5_before: [u8; 16],
+    pub md5_after: [u8; 16],
+    pub patch_type: PatchType,
+    /// Uncompressed size of the whole patch
+    pub patch_size: u32,
+    pub data: &'a [u8],
+}
+
+fn u32_at(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+impl<'a> Patch<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        if data.len() < PATCH_HEADER_SIZE
+            || data[0..4] != PATCH_MAGIC
+            || data[16..20] != MD5_MAGIC
+            || data[0x38..0x3C] != XFRM_MAGIC
+        {
+            return Err(MpqError::InvalidPatch("missing PTCH headers".into()));
+        }
+
+        let xfrm_size = u32_at(data, 0x3C) as usize;
+        let patch_type = match &data[0x40..0x44] {
+            b"COPY" => PatchType::Copy,
+            b"BSD0" => PatchType::Bsd0,
+            other => {
+                return Err(MpqError::InvalidPatch(format!(
+                    "unknown patch type {}",
+                    String::from_utf8_lossy(other)
+                )));
+            }
+        };
+        let data_end = (0x38 + xfrm_size).min(data.len());
+
+        Ok(Self {
+            patch_size: u32_at(data, 4),
+            size_before: u32_at(data, 8),
+            size_after: u32_at(data, 12),
+            md5_before: data[0x18..0x28].try_into().unwrap(),
+            md5_after: data[0x28..0x38].try_into().unwrap(),
+            patch_type,
+            data: &data[PATCH_HEADER_SIZE..data_end.max(PATCH_HEADER_SIZE)],
+        })
+    }
+
+    /// Apply the patch to the file it was made for
+    pub fn apply(&self, base: &[u8]) -> Result<Vec<u8>> {
+        if base.len() != self.size_before as usize
+            || Md5::digest(base).as_slice() != self.md5_before
+        {
+            return Err(MpqError::InvalidPatch(
+                "the file doesn't match the patch".into(),
+            ));
+        }
+
+        let patched = match self.patch_type {
+            PatchType::Copy => self.data.to_vec(),
+            PatchType::Bsd0 => {
+                let size = (self.patch_size as usize).saturating_sub(PATCH_HEADER_SIZE);
+                if self.data.len() < size {
+                    apply_bsdiff(base, &unrle(self.data, size))?
+                } else {
+                    apply_bsdiff(base, self.data)?
+                }
+            }
+        };
+
+        if patched.len() != self.size_after as usize
+            || Md5::digest(&patched).as_slice() != self.md5_after
+        {
+            return Err(MpqError::InvalidPatch(
+                "the patched file doesn't match the patch".into(),
+            ));
+        }
+        Ok(patched)
+    }
+}
+
+/// Undo the run-length encoding of bsdiff patches: after a 4 byte size,
+/// bytes with the high bit set copy the next `(byte & 0x7F) + 1` bytes and
+/// others skip `byte + 1` zeros
+fn unrle(data: &[u8], size: usize) -> Vec<u8> {
+    let mut out = vec![0u8; size];
+    let mut pos = 0;
+    let mut input = data.get(4..).unwrap_or_default().iter();
+    while pos < size {
+        let Some(&byte) = input.next() else { break };
+        if byte & 0x80 != 0 {
+            for _ in 0..(byte & 0x7F) as usize + 1 {
+                match (out.get_mut(pos), input.next()) {
+                    (Some(slot), Some(&value)) => *slot = value,
+                    _ => break,
+                }
+                pos += 1;
+            }
+        } else {
+            pos += byte as usize + 1;
+        }
+    }
+    out
+}
+
+/// Apply a `BSDIFF40` patch with 32 bit control values, where the high bit
+/// of the seek marks it negative
+fn apply_bsdiff(old: &[u8], patch: &[u8]) -> Result<Vec<u8>> {
+    let invalid = || MpqError::InvalidPatch("truncated bsdiff patch".into());
+    if patch.len() < 32 || patch[0..8] != BSDIFF_MAGIC {
+        return Err(MpqError::InvalidPatch("missing BSDIFF40 header".into()));
+    }
+    let u64_at = |pos: usize| u64::from_le_bytes(patch[pos..pos + 8].try_into().unwrap()) as usize;
+    let (ctrl_size, data_size, new_size) = (u64_at(8), u64_at(16), u64_at(24));
+
+    let body = &patch[32..];
+    let extra_start = ctrl_size.checked_add(data_size).ok_or_else(invalid)?;
+    let ctrl = body.get(..ctrl_size).ok_or_else(invalid)?;
+    let mut diff = body.get(ctrl_size..extra_start).ok_or_else(invalid)?;
+    let mut extra = body.get(extra_start..).ok_or_else(invalid)?;
+    // Every byte of the new file comes from the diff or extra block
+    if new_size > diff.len() + extra.len() {
+        return Err(invalid());
+    }
+
+    let mut new = Vec::with_capacity(new_size);
+    let mut old_pos = 0usize;
+    for triple in ctrl.chunks_exact(12) {
+        if new.len() >= new_size {
+            break;
+        }
+        let add = u32_at(triple, 0) as usize;
+        let copy = u32_at(triple, 4) as usize;
+        let seek = u32_at(triple, 8);
+
+        let (added, rest) = diff.split_at_checked(add).ok_or_else(invalid)?;
+        diff = rest;
+        for (i, &byte) in added.iter().enumerate() {
+            let base = old.get(old_pos + i).copied().unwrap_or(0);
+            new.push(byte.wrapping_add(base));
+        }
+        old_pos += add;
+
+        let (copied, rest) = extra.split_at_checked(copy).ok_or_else(invalid)?;
+        extra = rest;
+        new.extend_from_slice(copied);
+
+        let distance = (seek & 0x7FFF_FFFF) as usize;
+        old_pos = if seek & 0x8000_0000 != 0 {
+            old_pos.checked_sub(distance).ok_or_else(invalid)?
+        } else {
+            old_pos + distance
+        };
+    }
+
+    if new.len() < new_size {
+        return Err(invalid());
+    }
+    new.truncate(new_size);
+    Ok(new)
+}
+
+/// Archives of a client in load order, where later ones take precedence
+#[derive(Debug)]
+pub struct PatchChain<R = BufReader<File>> {
+    archives: Vec<Archive<R>>,
+}
+
+impl<R> Default for PatchChain<R> {
+    fn default() -> Self {
+        Self {
+            archives: Vec::new(),
+        }
+    }
+}
+
+impl PatchChain {
+    /// Open archive files in load order
+    pub fn open<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Result<Self> {
+        let mut chain = Self::default();
+        for path in paths {
+            chain.push(Archive::open(path)?);
+        }
+        Ok(chain)
+    }
+
+    /// Open the archives of a client data directory with
+    /// [`client_load_order`]
+    pub fn open_client<P: AsRef<Path>>(data_dir: P, locale: &str) -> Result<Self> {
+        Self::open(client_load_order(data_dir.as_ref(), locale)?)
+    }
+}
+
+impl<R: Read + Seek> PatchChain<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an archive that takes precedence over the previous ones
+    pub fn push(&mut self, archive: Archive<R>) {
+        self.archives.push(archive);
+    }
+
+    pub fn archives(&self) -> &[Archive<R>] {
+        &self.archives
+    }
+
+    /// Whether the file is in an archive and not deleted by a later one
+    pub fn contains(&self, name: &str) -> bool {
+        for archive in self.archives.iter().rev() {
+            if let Some(index) = archive.find_file(name) {
+                return !archive.files()[index].is_delete_marker();
+            }
+        }
+        false
+    }
+
+    /// Read a file from the last archive that has it, applying the patch
+    /// files of the archives after the one with the full file
+    pub fn read_file(&mut self, name: &str) -> Result<Vec<u8>> {
+        let mut patches = Vec::new();
+        for archive in self.archives.iter_mut().rev() {
+            let Some(index) = archive.find_file(name) else {
+                continue;
+            };
+            let entry = archive.files()[index];
+            if entry.is_delete_marker() {
+                break;
+            }
+
+            let data = archive.read_file_at(index, Some(name))?;
+            if entry.is_patch() {
+                patches.push(data);
+                continue;
+            }
+
+            let mut data = data;
+            for patch in patches.iter().rev() {
+                data = Patch::parse(patch)?.apply(&data)?;
+            }
+            return Ok(data);
+        }
+        Err(MpqError::FileNotFound(name.to_owned()))
+    }
+
+    /// Names in the `(listfile)` of the archives of files that aren't
+    /// deleted, sorted case-insensitively
+    pub fn list_files(&mut self) -> Result<Vec<String>> {
+        let mut names = BTreeMap::new();
+        for archive in &mut self.archives {
+            for name in archive.list_files()? {
+                names
+                    .entry(name.to_ascii_uppercase().replace('/', "\\"))
+                    .or_insert(name);
+            }
+        }
+        Ok(names
+            .into_values()
+            .filter(|name| self.contains(name))
+            .collect())
+    }
+}
+
+/// Archives of a client data directory in the order they are loaded: base
+/// archives, locale archives, then patches and locale patches
+/// (`patch.MPQ`, `patch-2.MPQ`, …, `<locale>/patch-<locale>-2.MPQ`, …).
+/// Names are matched case-insensitively and missing archives are skipped.
+pub fn client_load_order(data_dir: &Path, locale: &str) -> Result<Vec<PathBuf>> {
+    let locale_dir = find_entry(data_dir, locale)?.unwrap_or_else(|| data_dir.join(locale));
+
+    let mut paths = Vec::new();
+    for name in [
+        "common.MPQ",
+        "common-2.MPQ",
+        "expansion.MPQ",
+        "lichking.MPQ",
+    ] {
+        paths.extend(find_entry(data_dir, name)?);
+    }
+    for prefix in [
+        "locale",
+        "speech",
+        "expansion-locale",
+        "lichking-locale",
+        "expansion-speech",
+        "lichking-speech",
+    ] {
+        paths.extend(find_entry(&locale_dir, &format!("{prefix}-{locale}.MPQ"))?);
+    }
+    paths.extend(patch_archives(data_dir, "patch")?);
+    paths.extend(patch_archives(&locale_dir, &format!("patch-{locale}"))?);
+    Ok(paths)
+}
+
+fn dir_entries(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        entries.push(entry?.path());
+    }
+    Ok(entries)
+}
+
+fn find_entry(dir: &Path, name: &str) -> Result<Option<PathBuf>> {
+    Ok(dir_entries(dir)?.into_iter().find(|path| {
+        path.file_name()
+            .is_some_and(|file_name| file_name.to_string_lossy().eq_ignore_ascii_case(name))
+    }))
+}
+
+/// `<prefix>.MPQ` followed by `<prefix>-<suffix>.MPQ` sorted by suffix
+fn patch_archives(dir: &Path, prefix: &str) -> Result<Vec<PathBuf>> {
+    let prefix = prefix.to_ascii_lowercase();
+    let mut patches = dir_entries(dir)?
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().to_ascii_lowercase();
+            let stem = name.strip_suffix(".mpq")?;
+            let suffix = match stem.strip_prefix(&prefix)? {
+                "" => String::new(),
+                rest => rest.strip_prefix('-')?.to_owned(),
+            };
+            Some((suffix, path))
+        })
+        .collect::<Vec<_>>();
+    patches.sort_by(|a, b| (a.0.len(), &a.0).cmp(&(b.0.len(), &b.0)));
+    Ok(patches.into_iter().map(|(_, path)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tables::FileFlags;
+    use crate::test_archive::{TestArchive, TestFile};
+    use crate::{FormatVersion, listfile::LISTFILE_NAME};
+    use std::io::Cursor;
+
+    fn bsdiff(ctrl: &[[u32; 3]], diff: &[u8], extra: &[u8], new_size: usize) -> Vec<u8> {
+        let ctrl = ctrl
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+        let mut patch = BSDIFF_MAGIC.to_vec();
+        for size in [ctrl.len(), diff.len(), new_size] {
+            patch.extend((size as u64).to_le_bytes());
+        }
+        patch.extend(ctrl);
+        patch.extend(diff);
+        patch.extend(extra);
+        patch
+    }
+
+    fn rle(data: &[u8]) -> Vec<u8> {
+        let mut out = (data.len() as u32).to_le_bytes().to_vec();
+        let mut rest = data;
+        while !rest.is_empty() {
+            let zeros = rest.iter().take_while(|&&b| b == 0).count().min(0x80);
+            if zeros > 0 {
+                out.push(zeros as u8 - 1);
+                rest = &rest[zeros..];
+            } else {
+                let len = rest.iter().take_while(|&&b| b != 0).count().min(0x80);
+                out.push(0x80 | (len as u8 - 1));
+                out.extend(&rest[..len]);
+                rest = &rest[len..];
+            }
+        }
+        out
+    }
+
+    fn ptch(base: &[u8], new: &[u8], patch_type: &[u8; 4], data: &[u8], size: usize) -> Vec<u8> {
+        let mut patch = PATCH_MAGIC.to_vec();
+        for value in [PATCH_HEADER_SIZE + size, base.len(), new.len()] {
+            patch.extend((value as u32).to_le_bytes());
+        }
+        patch.extend(MD5_MAGIC);
+        patch.extend(0x28u32.to_le_bytes());
+        patch.extend(Md5::digest(base));
+        patch.extend(Md5::digest(new));
+        patch.extend(XFRM_MAGIC);
+        patch.extend((12 + data.len() as u32).to_le_bytes());
+        patch.extend(patch_type);
+        patch.extend(data);
+        patch
+    }
+
+    #[test]
+    fn test_apply_bsdiff() {
+        // Add 4 bytes, insert 3, seek back 2 and add 3 more
+        let patch = bsdiff(
+            &[[4, 3, 0x8000_0002], [3, 0, 0]],
+            &[0, 0, 0, 1, 0, 0, 0],
+            b"XYZ",
+            10,
+        );
+        assert_eq!(apply_bsdiff(b"0123456789", &patch).unwrap(), b"0124XYZ234");
+        assert!(apply_bsdiff(b"0123456789", &patch[..patch.len() - 1]).is_err());
+
+        // Sizes in the header that overflow or exceed the patch
+        let mut overflow = patch.clone();
+        overflow[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+        overflow[16..24].copy_from_slice(&1u64.to_le_bytes());
+        assert!(matches!(
+            apply_bsdiff(b"0123456789", &overflow),
+            Err(MpqError::InvalidPatch(_))
+        ));
+        let huge = bsdiff(&[[4, 3, 0]], &[0; 7], b"XYZ", usize::MAX);
+        assert!(matches!(
+            apply_bsdiff(b"0123456789", &huge),
+            Err(MpqError::InvalidPatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let base = b"0123456789";
+        let new = b"0124XYZ234";
+        let patch = bsdiff(
+            &[[4, 3, 0x8000_0002], [3, 0, 0]],
+            &[0, 0, 0, 1, 0, 0, 0],
+            b"XYZ",
+            10,
+        );
+
+        let plain = ptch(base, new, b"BSD0", &patch, patch.len());
+        assert_eq!(Patch::parse(&plain).unwrap().apply(base).unwrap(), new);
+
+        let encoded = ptch(base, new, b"BSD0", &rle(&patch), patch.len());
+        assert!(encoded.len() < plain.len());
+        assert_eq!(Patch::parse(&encoded).unwrap().apply(base).unwrap(), new);
+
+        let copy = ptch(base, new, b"COPY", new, new.len());
+        assert_eq!(Patch::parse(&copy).unwrap().apply(base).unwrap(), new);
+
+        assert!(matches!(
+            Patch::parse(&plain).unwrap().apply(b"012345678X"),
+            Err(MpqError::InvalidPatch(_))
+        ));
+        assert!(Patch::parse(&plain[..0x40]).is_err());
+    }
+
+    #[test]
+    fn test_patch_chain() {
+        let listfile = b"a.txt\nb.txt\nc.txt\nd.txt";
+        let base = TestArchive::new(FormatVersion::V1)
+            .file(TestFile::new("a.txt", b"0123456789", FileFlags::COMPRESS))
+            .file(TestFile::new("b.txt", b"old b", FileFlags::empty()))
+            .file(TestFile::new("c.txt", b"deleted c", FileFlags::empty()))
+            .file(TestFile::new("d.txt", b"same d", FileFlags::empty()))
+            .file(TestFile::new(LISTFILE_NAME, listfile, FileFlags::COMPRESS));
+
+        let new_a = b"0124XYZ234";
+        let diff = bsdiff(
+            &[[4, 3, 0x8000_0002], [3, 0, 0]],
+            &[0, 0, 0, 1, 0, 0, 0],
+            b"XYZ",
+            10,
+        );
+        let patch = TestArchive::new(FormatVersion::V1)
+            .file(TestFile::new(
+                "a.txt",
+                &ptch(b"0123456789", new_a, b"BSD0", &rle(&diff), diff.len()),
+                FileFlags::COMPRESS | FileFlags::PATCH_FILE,
+            ))
+            .file(TestFile::new("b.txt", b"new b", FileFlags::COMPRESS))
+            .file(TestFile::new("c.txt", b"", FileFlags::DELETE_MARKER))
+            .file(TestFile::new("e.txt", b"added e", FileFlags::empty()))
+            .file(TestFile::new(
+                LISTFILE_NAME,
+                b"a.txt;b.txt;c.txt;e.txt",
+                FileFlags::empty(),
+            ));
+
+        let mut chain = PatchChain::new();
+        for archive in [base, patch] {
+            chain.push(Archive::new(Cursor::new(archive.build())).unwrap());
+        }
+        assert_eq!(chain.archives().len(), 2);
+
+        assert_eq!(chain.read_file("a.txt").unwrap(), new_a);
+        assert_eq!(chain.read_file("B.TXT").unwrap(), b"new b");
+        assert!(matches!(
+            chain.read_file("c.txt"),
+            Err(MpqError::FileNotFound(_))
+        ));
+        assert_eq!(chain.read_file("d.txt").unwrap(), b"same d");
+        assert_eq!(chain.read_file("e.txt").unwrap(), b"added e");
+        assert!(!chain.contains("c.txt"));
+        assert_eq!(
+            chain.list_files().unwrap(),
+            ["a.txt", "b.txt", "d.txt", "e.txt"]
+        );
+    }
+
+    #[test]
+    fn test_client_load_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path();
+        std::fs::create_dir(data.join("enUS")).unwrap();
+        for name in [
+            "common.MPQ",
+            "Expansion.MPQ",
+            "lichking.MPQ",
+            "patch.MPQ",
+            "patch-3.MPQ",
+            "patch-2.MPQ",
+            "Patch-A.mpq",
+            "readme.txt",
+            "enUS/locale-enUS.MPQ",
+            "enUS/lichking-speech-enUS.MPQ",
+            "enUS/patch-enUS-2.MPQ",
+            "enUS/patch-enUS.MPQ",
+        ] {
+            std::fs::write(data.join(name), b"").unwrap();
+        }
+
+        let order = client_load_order(data, "enUS")
+            .unwrap()
+            .into_iter()
+            .map(|path| {
+                path.strip_prefix(data)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            [
+                "common.MPQ",
+                "Expansion.MPQ",
+                "lichking.MPQ",
+                "enUS/locale-enUS.MPQ",
+                "enUS/lichking-speech-enUS.MPQ",
+                "patch.MPQ",
+                "patch-2.MPQ",
+                "patch-3.MPQ",
+                "Patch-A.mpq",
+                "enUS/patch-enUS.MPQ",
+                "enUS/patch-enUS-2.MPQ",
+            ]
+        );
+    }
+}
@@ -0,0 +1,501 @@
+//! Hash, block, HET and BET tables
+
+use bitflags::bitflags;
+
+use crate::crypto::{HASH_NAME_A, HASH_NAME_B, HASH_TABLE_OFFSET, hash_string, jenkins_hash};
+use crate::error::{MpqError, Result};
+
+/// Signature of the HET table
+pub const HET_MAGIC: [u8; 4] = *b"HET\x1A";
+/// Signature of the BET table
+pub const BET_MAGIC: [u8; 4] = *b"BET\x1A";
+
+/// Block index of hash entries that were never used
+pub const HASH_ENTRY_EMPTY: u32 = 0xFFFF_FFFF;
+/// Block index of hash entries of deleted files
+pub const HASH_ENTRY_DELETED: u32 = 0xFFFF_FFFE;
+
+bitflags! {
+    /// Flags of a block table entry
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct FileFlags: u32 {
+        /// Compressed with PKWARE implode, without a compression mask
+        const IMPLODE = 0x0000_0100;
+        /// Compressed with the methods of the mask in front of each sector
+        const COMPRESS = 0x0000_0200;
+        const ENCRYPTED = 0x0001_0000;
+        /// The key is adjusted by the position and size of the file
+        const FIX_KEY = 0x0002_0000;
+        /// The file is a patch of the file in lower archives of the chain
+        const PATCH_FILE = 0x0010_0000;
+        /// The file is stored as a single unit instead of sectors
+        const SINGLE_UNIT = 0x0100_0000;
+        /// The file was deleted by a patch archive
+        const DELETE_MARKER = 0x0200_0000;
+        /// Sectors have Adler-32 checksums
+        const SECTOR_CRC = 0x0400_0000;
+        const EXISTS = 0x8000_0000;
+    }
+}
+
+impl FileFlags {
+    /// Whether sectors can be compressed
+    pub fn is_compressed(&self) -> bool {
+        self.intersects(FileFlags::IMPLODE | FileFlags::COMPRESS)
+    }
+}
+
+/// Entry of the hash table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashEntry {
+    pub name_a: u32,
+    pub name_b: u32,
+    pub locale: u16,
+    pub platform: u16,
+    /// Index of the file in the block table, or [`HASH_ENTRY_EMPTY`] or
+    /// [`HASH_ENTRY_DELETED`]
+    pub block_index: u32,
+}
+
+impl HashEntry {
+    pub const SIZE: usize = 16;
+
+    pub const EMPTY: Self = Self {
+        name_a: HASH_ENTRY_EMPTY,
+        name_b: HASH_ENTRY_EMPTY,
+        locale: 0xFFFF,
+        platform: 0xFFFF,
+        block_index: HASH_ENTRY_EMPTY,
+    };
+
+    /// Entry of a file name
+    pub fn new(name: &str, locale: u16, block_index: u32) -> Self {
+        Self {
+            name_a: hash_string(name, HASH_NAME_A),
+            name_b: hash_string(name, HASH_NAME_B),
+            locale,
+            platform: 0,
+            block_index,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.block_index == HASH_ENTRY_EMPTY
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.block_index == HASH_ENTRY_DELETED
+    }
+
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let u32_at =
+            |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        Self {
+            name_a: u32_at(0),
+            name_b: u32_at(4),
+            locale: u16::from_le_bytes([bytes[8], bytes[9]]),
+            platform: u16::from_le_bytes([bytes[10], bytes[11]]),
+            block_index: u32_at(12),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[0..4].copy_from_slice(&self.name_a.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.name_b.to_le_bytes());
+        bytes[8..10].copy_from_slice(&self.locale.to_le_bytes());
+        bytes[10..12].copy_from_slice(&self.platform.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.block_index.to_le_bytes());
+        bytes
+    }
+}
+
+/// Positions of the entries of a name in the hash table, one for each of
+/// its locales, probing from the slot of its hash
+pub fn find_hash_entries(table: &[HashEntry], name: &str) -> Vec<usize> {
+    if table.is_empty() {
+        return Vec::new();
+    }
+
+    let start = hash_string(name, HASH_TABLE_OFFSET) as usize % table.len();
+    let (name_a, name_b) = (
+        hash_string(name, HASH_NAME_A),
+        hash_string(name, HASH_NAME_B),
+    );
+
+    let mut found = Vec::new();
+    for i in 0..table.len() {
+        let pos = (start + i) % table.len();
+        let entry = &table[pos];
+        if entry.is_empty() {
+            break;
+        }
+        if !entry.is_deleted() && entry.name_a == name_a && entry.name_b == name_b {
+            found.push(pos);
+        }
+    }
+    found
+}
+
+/// Position of the entry of a name in the hash table. Entries of the locale
+/// are preferred over neutral ones, which are preferred over other locales.
+pub fn find_hash_entry(table: &[HashEntry], name: &str, locale: u16) -> Option<usize> {
+    let found = find_hash_entries(table, name);
+    let with_locale = |locale: u16| {
+        found
+            .iter()
+            .copied()
+            .find(|&pos| table[pos].locale == locale)
+    };
+    with_locale(locale)
+        .or_else(|| with_locale(0))
+        .or_else(|| found.first().copied())
+}
+
+/// Insert the entry of a name in the first free slot from the slot of its
+/// hash. Returns false if the table is full.
+pub fn insert_hash_entry(
+    table: &mut [HashEntry],
+    name: &str,
+    locale: u16,
+    block_index: u32,
+) -> bool {
+    if table.is_empty() {
+        return false;
+    }
+    let start = hash_string(name, HASH_TABLE_OFFSET) as usize % table.len();
+    for i in 0..table.len() {
+        let pos = (start + i) % table.len();
+        if table[pos].is_empty() || table[pos].is_deleted() {
+            table[pos] = HashEntry::new(name, locale, block_index);
+            return true;
+        }
+    }
+    false
+}
+
+/// Parse entries of a decrypted hash table
+pub fn parse_hash_table(data: &[u8]) -> Vec<HashEntry> {
+    data.chunks_exact(HashEntry::SIZE)
+        .map(|chunk| HashEntry::from_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+/// File of the block or BET table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileEntry {
+    /// Position of the file data, relative to the archive header
+    pub position: u64,
+    pub compressed_size: u32,
+    pub file_size: u32,
+    pub flags: FileFlags,
+}
+
+impl FileEntry {
+    pub const SIZE: usize = 16;
+
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let u32_at =
+            |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        Self {
+            position: u32_at(0) as u64,
+            compressed_size: u32_at(4),
+            file_size: u32_at(8),
+            flags: FileFlags::from_bits_retain(u32_at(12)),
+        }
+    }
+
+    /// Block table entry, without the high bits of the position
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[0..4].copy_from_slice(&(self.position as u32).to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.compressed_size.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.file_size.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.flags.bits().to_le_bytes());
+        bytes
+    }
+
+    pub fn exists(&self) -> bool {
+        self.flags.contains(FileFlags::EXISTS)
+    }
+
+    pub fn is_delete_marker(&self) -> bool {
+        self.flags.contains(FileFlags::DELETE_MARKER)
+    }
+
+    pub fn is_patch(&self) -> bool {
+        self.flags.contains(FileFlags::PATCH_FILE)
+    }
+}
+
+/// Parse entries of a decrypted block table, with the high 16 bits of the
+/// positions from the hi-block table of large archives
+pub fn parse_block_table(data: &[u8], hi_block_table: Option<&[u8]>) -> Vec<FileEntry> {
+    data.chunks_exact(FileEntry::SIZE)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut entry = FileEntry::from_bytes(chunk.try_into().unwrap());
+            if let Some(hi) = hi_block_table.and_then(|hi| hi.get(i * 2..i * 2 + 2)) {
+                entry.position |= (u16::from_le_bytes([hi[0], hi[1]]) as u64) << 32;
+            }
+            entry
+        })
+        .collect()
+}
+
+/// Read `count` bits at a bit offset, from the lowest bit of each byte
+pub fn read_bits(data: &[u8], offset: u64, count: u32) -> u64 {
+    let mut value = 0u64;
+    for i in 0..count.min(64) as u64 {
+        let bit = offset + i;
+        if data
+            .get((bit / 8) as usize)
+            .is_some_and(|byte| byte >> (bit % 8) & 1 != 0)
+        {
+            value |= 1 << i;
+        }
+    }
+    value
+}
+
+/// Write the lowest `count` bits of a value at a bit offset, the reverse
+/// of [`read_bits`]
+pub fn write_bits(data: &mut [u8], offset: u64, count: u32, value: u64) {
+    for i in 0..count.min(64) as u64 {
+        let bit = offset + i;
+        let byte = &mut data[(bit / 8) as usize];
+        if value >> i & 1 != 0 {
+            *byte |= 1 << (bit % 8);
+        } else {
+            *byte &= !(1 << (bit % 8));
+        }
+    }
+}
+
+/// Number of bits needed to store the value
+pub fn bit_count(value: u64) -> u32 {
+    64 - value.leading_zeros()
+}
+
+fn u32_field(data: &[u8], index: usize) -> Result<u32> {
+    data.get(index * 4..index * 4 + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| MpqError::InvalidTable("truncated table header".into()))
+}
+
+fn table_slice<'a>(data: &'a [u8], start: usize, len: usize, table: &str) -> Result<&'a [u8]> {
+    data.get(start..start + len)
+        .ok_or_else(|| MpqError::InvalidTable(format!("truncated {table}")))
+}
+
+/// Hash table of version 3 archives, indexed by the Jenkins hash of names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HetTable {
+    /// Number of files in the table
+    pub entry_count: u32,
+    /// Number of slots in the table
+    pub total_count: u32,
+    pub name_hash_bit_size: u32,
+    pub index_size_total: u32,
+    pub index_size: u32,
+    /// Highest 8 bits of the name hash of each slot, zero for free slots
+    pub name_hashes: Vec<u8>,
+    /// Index in the BET table of each slot, as packed bits
+    pub file_indexes: Vec<u8>,
+}
+
+impl HetTable {
+    pub const HEADER_SIZE: usize = 32;
+
+    /// Parse the decrypted table data after its signature, version and size
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let entry_count = u32_field(data, 1)?;
+        let total_count = u32_field(data, 2)?;
+        let name_hash_bit_size = u32_field(data, 3)?;
+        let index_size_total = u32_field(data, 4)?;
+        let index_size = u32_field(data, 6)?;
+        let index_table_size = u32_field(data, 7)? as usize;
+
+        if !(8..=64).contains(&name_hash_bit_size) {
+            return Err(MpqError::InvalidTable(format!(
+                "HET name hash of {name_hash_bit_size} bits"
+            )));
+        }
+
+        let name_hashes =
+            table_slice(data, Self::HEADER_SIZE, total_count as usize, "HET table")?.to_vec();
+        let file_indexes = table_slice(
+            data,
+            Self::HEADER_SIZE + total_count as usize,
+            index_table_size,
+            "HET table",
+        )?
+        .to_vec();
+
+        Ok(Self {
+            entry_count,
+            total_count,
+            name_hash_bit_size,
+            index_size_total,
+            index_size,
+            name_hashes,
+            file_indexes,
+        })
+    }
+
+    /// Name hash of the size used by the table
+    pub fn name_hash(&self, name: &str) -> u64 {
+        let and_mask = if self.name_hash_bit_size == 64 {
+            u64::MAX
+        } else {
+            (1 << self.name_hash_bit_size) - 1
+        };
+        let or_mask = 1 << (self.name_hash_bit_size - 1);
+        (jenkins_hash(name) & and_mask) | or_mask
+    }
+
+    /// Index of a file in the BET table
+    pub fn find(&self, name: &str, bet: &BetTable) -> Option<usize> {
+        if self.total_count == 0 {
+            return None;
+        }
+
+        let hash = self.name_hash(name);
+        let high_byte = (hash >> (self.name_hash_bit_size - 8)) as u8;
+        let start = (hash % self.total_count as u64) as usize;
+
+        for i in 0..self.total_count as usize {
+            let slot = (start + i) % self.total_count as usize;
+            match self.name_hashes.get(slot) {
+                None | Some(0) => break,
+                Some(&byte) if byte == high_byte => {
+                    let index = read_bits(
+                        &self.file_indexes,
+                        slot as u64 * self.index_size_total as u64,
+                        self.index_size,
+                    ) as usize;
+                    let full_hash = ((byte as u64) << bet.name_hash_bits)
+                        | bet.name_hashes.get(index).copied().unwrap_or_default();
+                    if index < bet.name_hashes.len() && full_hash == hash {
+                        return Some(index);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// Block table of version 3 archives, with the lower bits of the name
+/// hashes of the HET table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BetTable {
+    pub files: Vec<FileEntry>,
+    /// Name hashes without the highest 8 bits stored in the HET table
+    pub name_hashes: Vec<u64>,
+    pub name_hash_bits: u32,
+}
+
+impl BetTable {
+    pub const HEADER_SIZE: usize = 76;
+
+    /// Parse the decrypted table data after its signature, version and size
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let entry_count = u32_field(data, 1)? as usize;
+        let table_entry_size = u32_field(data, 3)?;
+        let bit_index_file_pos = u32_field(data, 4)?;
+        let bit_index_file_size = u32_field(data, 5)?;
+        let bit_index_cmp_size = u32_field(data, 6)?;
+        let bit_index_flag_index = u32_field(data, 7)?;
+        let bit_count_file_pos = u32_field(data, 9)?;
+        let bit_count_file_size = u32_field(data, 10)?;
+        let bit_count_cmp_size = u32_field(data, 11)?;
+        let bit_count_flag_index = u32_field(data, 12)?;
+        let name_hash_bits_total = u32_field(data, 14)?;
+        let name_hash_bits = u32_field(data, 16)?;
+        let name_hash_array_size = u32_field(data, 17)? as usize;
+        let flag_count = u32_field(data, 18)? as usize;
+
+        let flags = table_slice(data, Self::HEADER_SIZE, flag_count * 4, "BET flags")?
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect::<Vec<_>>();
+
+        let table_start = Self::HEADER_SIZE + flag_count * 4;
+        let table_size = (table_entry_size as usize * entry_count).div_ceil(8);
+        let table = table_slice(data, table_start, table_size, "BET file table")?;
+        let hashes = table_slice(
+            data,
+            table_start + table_size,
+            name_hash_array_size,
+            "BET name hashes",
+        )?;
+
+        let mut files = Vec::with_capacity(entry_count);
+        let mut name_hashes = Vec::with_capacity(entry_count);
+        for i in 0..entry_count as u64 {
+            let entry_bit = i * table_entry_size as u64;
+            let field = |index: u32, count: u32| read_bits(table, entry_bit + index as u64, count);
+            let flag_index = field(bit_index_flag_index, bit_count_flag_index) as usize;
+            files.push(FileEntry {
+                position: field(bit_index_file_pos, bit_count_file_pos),
+                file_size: field(bit_index_file_size, bit_count_file_size) as u32,
+                compressed_size: field(bit_index_cmp_size, bit_count_cmp_size) as u32,
+                flags: FileFlags::from_bits_retain(flags.get(flag_index).copied().unwrap_or(0)),
+            });
+            name_hashes.push(read_bits(
+                hashes,
+                i * name_hash_bits_total as u64,
+                name_hash_bits,
+            ));
+        }
+
+        Ok(Self {
+            files,
+            name_hashes,
+            name_hash_bits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_lookup() {
+        let mut table = vec![HashEntry::EMPTY; 4];
+        assert!(insert_hash_entry(&mut table, "a.txt", 0, 0));
+        assert!(insert_hash_entry(&mut table, "a.txt", 0x407, 1));
+        assert!(insert_hash_entry(&mut table, "b.txt", 0, 2));
+
+        let block = |name: &str, locale: u16| {
+            find_hash_entry(&table, name, locale).map(|pos| table[pos].block_index)
+        };
+        assert_eq!(block("A.TXT", 0), Some(0));
+        assert_eq!(block("a.txt", 0x407), Some(1));
+        assert_eq!(block("a.txt", 0x409), Some(0));
+        assert_eq!(block("b.txt", 0x407), Some(2));
+        assert_eq!(block("c.txt", 0), None);
+        assert_eq!(find_hash_entries(&table, "a.txt").len(), 2);
+
+        assert!(insert_hash_entry(&mut table, "c.txt", 0, 3));
+        assert!(!insert_hash_entry(&mut table, "d.txt", 0, 4));
+
+        let bytes = table.iter().flat_map(|e| e.to_bytes()).collect::<Vec<_>>();
+        assert_eq!(parse_hash_table(&bytes), table);
+    }
+
+    #[test]
+    fn test_bits() {
+        let mut data = vec![0u8; 8];
+        write_bits(&mut data, 3, 13, 0x1ABC);
+        write_bits(&mut data, 16, 40, 0xAB_CDEF_0123);
+        assert_eq!(read_bits(&data, 3, 13), 0x1ABC);
+        assert_eq!(read_bits(&data, 16, 40), 0xAB_CDEF_0123);
+        assert_eq!(read_bits(&data, 60, 8), 0);
+        assert_eq!(bit_count(0), 0);
+        assert_eq!(bit_count(0x100), 9);
+    }
+}
@@ -0,0 +1,403 @@
+//! Small archives generated for the tests
+
+use std::io::Write;
+
+use md5::{Digest, Md5};
+
+use crate::archive::{PatchInfo, sector_checksum};
+use crate::compression::{
+    COMPRESSION_BZIP2, COMPRESSION_PKWARE, COMPRESSION_SPARSE, COMPRESSION_ZLIB, sparse,
+};
+use crate::crypto::{
+    HASH_TABLE_OFFSET, block_table_key, encrypt, file_key, hash_string, hash_table_key,
+    jenkins_hash,
+};
+use crate::explode::implode;
+use crate::header::{FormatVersion, HEADER_ALIGNMENT, MpqHeader, USER_DATA_MAGIC};
+use crate::tables::{BET_MAGIC, FileEntry, FileFlags, HET_MAGIC, HashEntry, bit_count, write_bits};
+
+pub(crate) struct TestFile {
+    pub name: String,
+    pub data: Vec<u8>,
+    pub flags: FileFlags,
+    /// Compression mask of files with [`FileFlags::COMPRESS`]
+    pub compression: u8,
+    pub locale: u16,
+}
+
+impl TestFile {
+    pub fn new(name: &str, data: &[u8], flags: FileFlags) -> Self {
+        Self {
+            name: name.to_owned(),
+            data: data.to_vec(),
+            flags: flags | FileFlags::EXISTS,
+            compression: COMPRESSION_ZLIB,
+            locale: 0,
+        }
+    }
+
+    pub fn compression(mut self, compression: u8) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn locale(mut self, locale: u16) -> Self {
+        self.locale = locale;
+        self
+    }
+}
+
+pub(crate) struct TestArchive {
+    pub version: FormatVersion,
+    pub sector_size_shift: u16,
+    pub files: Vec<TestFile>,
+    /// Write the hash and block tables
+    pub classic_tables: bool,
+    /// Write the HET and BET tables of version 3 and later
+    pub het_bet: bool,
+    /// Compress the hash and block tables of version 4
+    pub compress_tables: bool,
+    /// Put a user data block in front of the header
+    pub user_data: bool,
+}
+
+fn compress(mask: u8, data: &[u8]) -> Vec<u8> {
+    let mut out = data.to_vec();
+    if mask & COMPRESSION_SPARSE != 0 {
+        out = sparse(&out);
+    }
+    if mask & COMPRESSION_ZLIB != 0 {
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(&out).unwrap();
+        out = encoder.finish().unwrap();
+    }
+    if mask & COMPRESSION_PKWARE != 0 {
+        out = implode(&out);
+    }
+    if mask & COMPRESSION_BZIP2 != 0 {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::best());
+        encoder.write_all(&out).unwrap();
+        out = encoder.finish().unwrap();
+    }
+    let mut compressed = vec![mask];
+    compressed.extend(out);
+    compressed
+}
+
+fn u32_bytes(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// HET or BET table with its signature, version and size
+fn ext_table(magic: [u8; 4], mut data: Vec<u8>, key: u32) -> Vec<u8> {
+    let mut out = magic.to_vec();
+    out.extend(u32_bytes(&[1, data.len() as u32]));
+    encrypt(&mut data, key);
+    out.extend(data);
+    out
+}
+
+impl TestArchive {
+    pub fn new(version: FormatVersion) -> Self {
+        Self {
+            version,
+            sector_size_shift: 0,
+            files: Vec::new(),
+            classic_tables: true,
+            het_bet: version >= FormatVersion::V3,
+            compress_tables: false,
+            user_data: false,
+        }
+    }
+
+    pub fn file(mut self, file: TestFile) -> Self {
+        self.files.push(file);
+        self
+    }
+
+    fn encode_sector(&self, file: &TestFile, data: &[u8]) -> Vec<u8> {
+        let compressed = if file.flags.contains(FileFlags::IMPLODE) {
+            implode(data)
+        } else if file.flags.contains(FileFlags::COMPRESS) {
+            compress(file.compression, data)
+        } else {
+            return data.to_vec();
+        };
+        if compressed.len() < data.len() {
+            compressed
+        } else {
+            data.to_vec()
+        }
+    }
+
+    fn encode_file(&self, file: &TestFile, position: u64) -> Vec<u8> {
+        let key = file.flags.contains(FileFlags::ENCRYPTED).then(|| {
+            file_key(
+                &file.name,
+                position,
+                file.data.len() as u32,
+                file.flags.contains(FileFlags::FIX_KEY),
+            )
+        });
+
+        let mut out = Vec::new();
+        if file.flags.contains(FileFlags::PATCH_FILE) {
+            let info = PatchInfo {
+                length: PatchInfo::SIZE as u32,
+                flags: 0x8000_0000,
+                data_size: file.data.len() as u32,
+                md5: Md5::digest(&file.data).into(),
+            };
+            out.extend(info.to_bytes());
+        }
+
+        if file.flags.contains(FileFlags::SINGLE_UNIT) {
+            let mut data = self.encode_sector(file, &file.data);
+            if let Some(key) = key {
+                encrypt(&mut data, key);
+            }
+            out.extend(data);
+            return out;
+        }
+
+        let sector_size = 512 << self.sector_size_shift;
+        let sectors = file.data.chunks(sector_size).collect::<Vec<_>>();
+        if !file.flags.is_compressed() {
+            for (i, sector) in sectors.iter().enumerate() {
+                let mut sector = sector.to_vec();
+                if let Some(key) = key {
+                    encrypt(&mut sector, key.wrapping_add(i as u32));
+                }
+                out.extend(sector);
+            }
+            return out;
+        }
+
+        let has_crc = file.flags.contains(FileFlags::SECTOR_CRC);
+        let encoded = sectors
+            .iter()
+            .map(|sector| self.encode_sector(file, sector))
+            .collect::<Vec<_>>();
+        let checksums = encoded
+            .iter()
+            .map(|sector| sector_checksum(sector))
+            .collect::<Vec<_>>();
+
+        let mut offsets = vec![((sectors.len() + 1 + has_crc as usize) * 4) as u32];
+        for sector in &encoded {
+            offsets.push(offsets.last().unwrap() + sector.len() as u32);
+        }
+        if has_crc {
+            offsets.push(offsets.last().unwrap() + checksums.len() as u32 * 4);
+        }
+
+        let mut table = u32_bytes(&offsets);
+        if let Some(key) = key {
+            encrypt(&mut table, key.wrapping_sub(1));
+        }
+        out.extend(table);
+        for (i, mut sector) in encoded.into_iter().enumerate() {
+            if let Some(key) = key {
+                encrypt(&mut sector, key.wrapping_add(i as u32));
+            }
+            out.extend(sector);
+        }
+        if has_crc {
+            out.extend(u32_bytes(&checksums));
+        }
+        out
+    }
+
+    fn het_table(&self) -> Vec<u8> {
+        let count = self.files.len();
+        let total_count = (count * 4 / 3).max(count + 1);
+        let index_bits = bit_count(count as u64).max(1);
+        let index_table_size = (index_bits as usize * total_count).div_ceil(8);
+
+        let mut name_hashes = vec![0u8; total_count];
+        let mut indexes = vec![0u8; index_table_size];
+        for (i, file) in self.files.iter().enumerate() {
+            let hash = jenkins_hash(&file.name) | (1 << 63);
+            let mut slot = (hash % total_count as u64) as usize;
+            while name_hashes[slot] != 0 {
+                slot = (slot + 1) % total_count;
+            }
+            name_hashes[slot] = (hash >> 56) as u8;
+            write_bits(
+                &mut indexes,
+                (slot as u32 * index_bits) as u64,
+                index_bits,
+                i as u64,
+            );
+        }
+
+        let mut data = u32_bytes(&[
+            (32 + total_count + index_table_size) as u32,
+            count as u32,
+            total_count as u32,
+            64,
+            index_bits,
+            0,
+            index_bits,
+            index_table_size as u32,
+        ]);
+        data.extend(name_hashes);
+        data.extend(indexes);
+        ext_table(HET_MAGIC, data, hash_table_key())
+    }
+
+    fn bet_table(&self, entries: &[FileEntry]) -> Vec<u8> {
+        let mut flags = Vec::new();
+        for entry in entries {
+            if !flags.contains(&entry.flags.bits()) {
+                flags.push(entry.flags.bits());
+            }
+        }
+
+        let max =
+            |field: fn(&FileEntry) -> u64| bit_count(entries.iter().map(field).max().unwrap_or(0));
+        let bits_position = max(|e| e.position);
+        let bits_file_size = max(|e| e.file_size as u64);
+        let bits_compressed_size = max(|e| e.compressed_size as u64);
+        let bits_flag_index = bit_count(flags.len().saturating_sub(1) as u64);
+        let entry_bits = bits_position + bits_file_size + bits_compressed_size + bits_flag_index;
+
+        let mut table = vec![0u8; (entry_bits as usize * entries.len()).div_ceil(8)];
+        let mut hashes = vec![0u8; (56 * entries.len()).div_ceil(8)];
+        for (i, (entry, file)) in entries.iter().zip(&self.files).enumerate() {
+            let mut bit = (i as u32 * entry_bits) as u64;
+            let flag_index = flags.iter().position(|&f| f == entry.flags.bits()).unwrap();
+            for (value, bits) in [
+                (entry.position, bits_position),
+                (entry.file_size as u64, bits_file_size),
+                (entry.compressed_size as u64, bits_compressed_size),
+                (flag_index as u64, bits_flag_index),
+            ] {
+                write_bits(&mut table, bit, bits, value);
+                bit += bits as u64;
+            }
+            write_bits(&mut hashes, i as u64 * 56, 56, jenkins_hash(&file.name));
+        }
+
+        let mut data = u32_bytes(&[
+            (76 + flags.len() * 4 + table.len() + hashes.len()) as u32,
+            entries.len() as u32,
+            0x10,
+            entry_bits,
+            0,
+            bits_position,
+            bits_position + bits_file_size,
+            bits_position + bits_file_size + bits_compressed_size,
+            entry_bits,
+            bits_position,
+            bits_file_size,
+            bits_compressed_size,
+            bits_flag_index,
+            0,
+            56,
+            0,
+            56,
+            hashes.len() as u32,
+            flags.len() as u32,
+        ]);
+        data.extend(u32_bytes(&flags));
+        data.extend(table);
+        data.extend(hashes);
+        ext_table(BET_MAGIC, data, block_table_key())
+    }
+
+    /// Encrypted table, compressed if enabled. Returns the stored size.
+    fn write_table(&self, out: &mut Vec<u8>, data: Vec<u8>, key: u32) -> u64 {
+        let mut data = if self.compress_tables && self.version >= FormatVersion::V4 {
+            compress(COMPRESSION_ZLIB, &data)
+        } else {
+            data
+        };
+        encrypt(&mut data, key);
+        out.extend(&data);
+        data.len() as u64
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        if self.user_data {
+            out.extend(USER_DATA_MAGIC);
+            out.extend(u32_bytes(&[0x100, HEADER_ALIGNMENT as u32, 0x10]));
+        }
+        // Junk in front of the archive
+        out.resize(HEADER_ALIGNMENT as usize, 0xCC);
+        let start = out.len();
+        out.resize(start + self.version.header_size() as usize, 0);
+
+        let mut header = MpqHeader::new(self.version, self.sector_size_shift);
+        let mut entries = Vec::new();
+        for file in &self.files {
+            let position = (out.len() - start) as u64;
+            let data = self.encode_file(file, position);
+            entries.push(FileEntry {
+                position,
+                compressed_size: data.len() as u32,
+                file_size: file.data.len() as u32,
+                flags: file.flags,
+            });
+            out.extend(data);
+        }
+
+        if self.het_bet {
+            header.het_table_pos = (out.len() - start) as u64;
+            let het = self.het_table();
+            header.het_table_size = het.len() as u64;
+            out.extend(het);
+
+            header.bet_table_pos = (out.len() - start) as u64;
+            let bet = self.bet_table(&entries);
+            header.bet_table_size = bet.len() as u64;
+            out.extend(bet);
+        }
+
+        if self.classic_tables {
+            let hash_entries = (self.files.len() * 2).next_power_of_two().max(4);
+            let mut hash_table = vec![HashEntry::EMPTY; hash_entries];
+            for (i, file) in self.files.iter().enumerate() {
+                let mut pos = hash_string(&file.name, HASH_TABLE_OFFSET) as usize % hash_entries;
+                while !hash_table[pos].is_empty() {
+                    pos = (pos + 1) % hash_entries;
+                }
+                hash_table[pos] = HashEntry::new(&file.name, file.locale, i as u32);
+            }
+
+            header.hash_table_pos = (out.len() - start) as u64;
+            header.hash_table_entries = hash_entries as u32;
+            let data = hash_table.iter().flat_map(|e| e.to_bytes()).collect();
+            header.hash_table_size = self.write_table(&mut out, data, hash_table_key());
+
+            header.block_table_pos = (out.len() - start) as u64;
+            header.block_table_entries = entries.len() as u32;
+            let data = entries.iter().flat_map(|e| e.to_bytes()).collect();
+            header.block_table_size = self.write_table(&mut out, data, block_table_key());
+
+            if self.version >= FormatVersion::V2 {
+                header.hi_block_table_pos = (out.len() - start) as u64;
+                header.hi_block_table_size = entries.len() as u64 * 2;
+                out.extend(
+                    entries
+                        .iter()
+                        .flat_map(|e| ((e.position >> 32) as u16).to_le_bytes()),
+                );
+            }
+        }
+
+        header.archive_size = (out.len() - start) as u64;
+        if self.version < FormatVersion::V4 {
+            header.hash_table_size = 0;
+            header.block_table_size = 0;
+            header.hi_block_table_size = 0;
+            header.het_table_size = 0;
+            header.bet_table_size = 0;
+        }
+        let mut header_bytes = Vec::new();
+        header.write(&mut header_bytes).unwrap();
+        out[start..start + header_bytes.len()].copy_from_slice(&header_bytes);
+        out
+    }
+}
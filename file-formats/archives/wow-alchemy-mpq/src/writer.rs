@@ -0,0 +1,934 @@
+//! Creating archives and changing the files of existing ones
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use md5::{Digest, Md5};
+
+use crate::archive::{Archive, PatchInfo, sector_checksum};
+use crate::attributes::{ATTRIBUTES_NAME, Attributes, filetime};
+use crate::compression::{COMPRESSION_ZLIB, compress};
+use crate::crypto::{block_table_key, encrypt, file_key, hash_table_key, jenkins_hash};
+use crate::error::{MpqError, Result};
+use crate::explode::implode;
+use crate::header::{FormatVersion, MpqHeader};
+use crate::listfile::{LISTFILE_NAME, parse_listfile};
+use crate::tables::{
+    BET_MAGIC, FileEntry, FileFlags, HET_MAGIC, HashEntry, bit_count, insert_hash_entry, write_bits,
+};
+
+/// Name of the weak signature, which is invalid once an archive is rewritten
+const SIGNATURE_NAME: &str = "(signature)";
+
+/// Flags of a file that are kept when an archive is rewritten
+const STORAGE_FLAGS: FileFlags = FileFlags::IMPLODE
+    .union(FileFlags::COMPRESS)
+    .union(FileFlags::ENCRYPTED)
+    .union(FileFlags::FIX_KEY)
+    .union(FileFlags::PATCH_FILE)
+    .union(FileFlags::SINGLE_UNIT)
+    .union(FileFlags::SECTOR_CRC);
+
+/// How a file is stored in an archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileOptions {
+    /// Storage flags of the file, [`FileFlags::EXISTS`] is added when it's
+    /// written
+    pub flags: FileFlags,
+    /// Compression mask of files with [`FileFlags::COMPRESS`]
+    pub compression: u8,
+    pub locale: u16,
+}
+
+impl FileOptions {
+    /// Uncompressed sectors
+    pub fn stored() -> Self {
+        Self {
+            flags: FileFlags::empty(),
+            compression: 0,
+            locale: 0,
+        }
+    }
+
+    /// Sectors compressed with the methods of a mask, with checksums
+    pub fn compressed(compression: u8) -> Self {
+        Self {
+            flags: FileFlags::COMPRESS | FileFlags::SECTOR_CRC,
+            compression,
+            locale: 0,
+        }
+    }
+
+    /// Encrypt the file, with a key adjusted by its position and size
+    pub fn encrypted(mut self) -> Self {
+        self.flags |= FileFlags::ENCRYPTED | FileFlags::FIX_KEY;
+        self
+    }
+
+    /// Store the file as a single unit instead of sectors
+    pub fn single_unit(mut self) -> Self {
+        self.flags |= FileFlags::SINGLE_UNIT;
+        self
+    }
+
+    pub fn locale(mut self, locale: u16) -> Self {
+        self.locale = locale;
+        self
+    }
+}
+
+impl Default for FileOptions {
+    fn default() -> Self {
+        Self::compressed(COMPRESSION_ZLIB)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct NewFile {
+    name: String,
+    data: Vec<u8>,
+    options: FileOptions,
+    /// Windows `FILETIME` of the `(attributes)`
+    filetime: u64,
+}
+
+impl NewFile {
+    fn is_delete_marker(&self) -> bool {
+        self.options.flags.contains(FileFlags::DELETE_MARKER)
+    }
+}
+
+/// Names use backslashes as separators
+fn normalize_name(name: &str) -> String {
+    name.replace('/', "\\")
+}
+
+/// Writes archives from files held in memory, which can be loaded from an
+/// existing archive to change its files. Archives are always written
+/// without unused space, so rewriting one compacts it.
+#[derive(Debug, Clone)]
+pub struct ArchiveWriter {
+    version: FormatVersion,
+    sector_size_shift: u16,
+    files: Vec<NewFile>,
+    default_options: FileOptions,
+    /// Options of files by their lowercase extension
+    file_types: Vec<(String, FileOptions)>,
+    listfile: bool,
+    attributes: bool,
+}
+
+impl ArchiveWriter {
+    /// Empty archive with 4 KiB sectors, a `(listfile)` and `(attributes)`.
+    /// Files are compressed with zlib, except MP3 and Ogg files.
+    pub fn new(version: FormatVersion) -> Self {
+        Self {
+            version,
+            sector_size_shift: 3,
+            files: Vec::new(),
+            default_options: FileOptions::default(),
+            file_types: vec![
+                ("mp3".to_owned(), FileOptions::stored()),
+                ("ogg".to_owned(), FileOptions::stored()),
+            ],
+            listfile: true,
+            attributes: true,
+        }
+    }
+
+    /// Load the files of an archive, with the names of its `(listfile)`.
+    /// Files keep their flags, compression, locales and times, the `(listfile)`,
+    /// `(attributes)` and `(signature)` are generated again.
+    pub fn from_archive<R: Read + Seek>(archive: &mut Archive<R>) -> Result<Self> {
+        let header = archive.header();
+        let mut writer = Self::new(header.format_version);
+        writer.sector_size_shift = header.sector_size_shift;
+
+        let filetimes = if archive.contains(ATTRIBUTES_NAME) {
+            archive.attributes()?.filetimes
+        } else {
+            None
+        };
+        let mut names = match archive.find_file(LISTFILE_NAME) {
+            Some(index) => parse_listfile(&archive.read_file_at(index, Some(LISTFILE_NAME))?),
+            None => Vec::new(),
+        };
+        names.extend([LISTFILE_NAME, ATTRIBUTES_NAME, SIGNATURE_NAME].map(str::to_owned));
+
+        let mut named = vec![false; archive.files().len()];
+        for name in names {
+            let internal = [LISTFILE_NAME, ATTRIBUTES_NAME, SIGNATURE_NAME].contains(&&*name);
+            for (locale, index) in archive.find_locales(&name) {
+                if std::mem::replace(&mut named[index], true) || internal {
+                    continue;
+                }
+
+                let entry = archive.files()[index];
+                let (data, compression) = if entry.is_delete_marker() {
+                    (Vec::new(), None)
+                } else {
+                    archive.read_file_and_compression(index, Some(&name))?
+                };
+                let mut options = FileOptions {
+                    flags: entry.flags & (STORAGE_FLAGS | FileFlags::DELETE_MARKER),
+                    compression: compression
+                        .unwrap_or_else(|| writer.options_for(&name).compression),
+                    locale,
+                };
+                if options.compression == 0 {
+                    options.compression = COMPRESSION_ZLIB;
+                }
+                writer.insert(NewFile {
+                    name: normalize_name(&name),
+                    data,
+                    options,
+                    filetime: filetimes
+                        .as_ref()
+                        .and_then(|f| f.get(index))
+                        .copied()
+                        .unwrap_or(0),
+                });
+            }
+        }
+
+        if let Some(index) = archive
+            .files()
+            .iter()
+            .enumerate()
+            .position(|(i, entry)| entry.exists() && !named[i])
+        {
+            return Err(MpqError::UnnamedFile(index));
+        }
+        Ok(writer)
+    }
+
+    pub fn version(&self) -> FormatVersion {
+        self.version
+    }
+
+    pub fn set_version(&mut self, version: FormatVersion) {
+        self.version = version;
+    }
+
+    /// Size of the sectors files are split into
+    pub fn sector_size(&self) -> usize {
+        512 << self.sector_size_shift
+    }
+
+    /// Set the size of sectors, a power of two from 512 bytes
+    pub fn set_sector_size(&mut self, size: usize) -> Result<()> {
+        if !size.is_power_of_two() || !(512..=512 << 22).contains(&size) {
+            return Err(MpqError::InvalidSectorSize(size));
+        }
+        self.sector_size_shift = (size / 512).trailing_zeros() as u16;
+        Ok(())
+    }
+
+    /// Whether to write a `(listfile)` with the names of the files
+    pub fn set_listfile(&mut self, listfile: bool) {
+        self.listfile = listfile;
+    }
+
+    /// Whether to write `(attributes)` with the CRC32, MD5 and time of the
+    /// files
+    pub fn set_attributes(&mut self, attributes: bool) {
+        self.attributes = attributes;
+    }
+
+    /// Options of files without options for their type
+    pub fn set_default_options(&mut self, options: FileOptions) {
+        self.default_options = options;
+    }
+
+    /// Options of the files with an extension, like `"blp"`
+    pub fn set_file_type(&mut self, extension: &str, options: FileOptions) {
+        let extension = extension.trim_start_matches('.').to_ascii_lowercase();
+        self.file_types.retain(|(ext, _)| *ext != extension);
+        self.file_types.push((extension, options));
+    }
+
+    /// Options a file is added with by [`add`](Self::add)
+    pub fn options_for(&self, name: &str) -> FileOptions {
+        let base_name = name.rsplit(['\\', '/']).next().unwrap_or(name);
+        base_name
+            .rsplit_once('.')
+            .and_then(|(_, extension)| {
+                self.file_types
+                    .iter()
+                    .find(|(ext, _)| ext.eq_ignore_ascii_case(extension))
+            })
+            .map(|(_, options)| *options)
+            .unwrap_or(self.default_options)
+    }
+
+    /// Add a file, or replace the file of the same name and locale
+    fn insert(&mut self, file: NewFile) {
+        let existing = self.files.iter_mut().find(|f| {
+            f.name.eq_ignore_ascii_case(&file.name) && f.options.locale == file.options.locale
+        });
+        match existing {
+            Some(existing) => *existing = file,
+            None => self.files.push(file),
+        }
+    }
+
+    /// Add or replace a file with the options of its type. Its time in the
+    /// `(attributes)` is zero.
+    pub fn add(&mut self, name: &str, data: impl Into<Vec<u8>>) {
+        let options = self.options_for(name);
+        self.add_with_options(name, data, options);
+    }
+
+    /// Add or replace a file with the given options
+    pub fn add_with_options(&mut self, name: &str, data: impl Into<Vec<u8>>, options: FileOptions) {
+        self.insert(NewFile {
+            name: normalize_name(name),
+            data: data.into(),
+            options,
+            filetime: 0,
+        });
+    }
+
+    /// Add or replace a file with the contents and modification time of a
+    /// file on disk
+    pub fn add_path<P: AsRef<Path>>(&mut self, name: &str, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let modified = fs::metadata(path)?.modified()?;
+        self.insert(NewFile {
+            name: normalize_name(name),
+            data: fs::read(path)?,
+            options: self.options_for(name),
+            filetime: filetime(modified),
+        });
+        Ok(())
+    }
+
+    /// Add a marker that deletes a file of the lower archives of a patch
+    /// chain
+    pub fn add_delete_marker(&mut self, name: &str) {
+        self.insert(NewFile {
+            name: normalize_name(name),
+            data: Vec::new(),
+            options: FileOptions {
+                flags: FileFlags::DELETE_MARKER,
+                ..FileOptions::stored()
+            },
+            filetime: 0,
+        });
+    }
+
+    /// Remove all locales of a file. Returns whether there was one.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let name = normalize_name(name);
+        let count = self.files.len();
+        self.files.retain(|f| !f.name.eq_ignore_ascii_case(&name));
+        self.files.len() != count
+    }
+
+    /// Whether a file or a delete marker of any locale was added
+    pub fn contains(&self, name: &str) -> bool {
+        let name = normalize_name(name);
+        self.files
+            .iter()
+            .any(|f| f.name.eq_ignore_ascii_case(&name))
+    }
+
+    /// Names of the files, once for each locale
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.files.iter().map(|f| f.name.as_str())
+    }
+
+    fn listfile_bytes(&self) -> Vec<u8> {
+        let mut names: Vec<&str> = Vec::new();
+        for file in &self.files {
+            if !names.iter().any(|n| n.eq_ignore_ascii_case(&file.name)) {
+                names.push(&file.name);
+            }
+        }
+        names
+            .iter()
+            .flat_map(|name| [name, "\r\n"])
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    /// Attributes of the files followed by the empty entry of the
+    /// attributes file
+    fn attributes_bytes(&self, files: &[&NewFile]) -> Vec<u8> {
+        fn with_empty<T>(values: impl Iterator<Item = T>, empty: T) -> Option<Vec<T>> {
+            Some(values.chain([empty]).collect())
+        }
+        let has_patches = files
+            .iter()
+            .any(|f| f.options.flags.contains(FileFlags::PATCH_FILE));
+        Attributes {
+            crc32: with_empty(files.iter().map(|f| crc32fast::hash(&f.data)), 0),
+            filetimes: with_empty(files.iter().map(|f| f.filetime), 0),
+            md5: with_empty(files.iter().map(|f| Md5::digest(&f.data).into()), [0; 16]),
+            patch_bits: has_patches
+                .then(|| {
+                    with_empty(
+                        files
+                            .iter()
+                            .map(|f| f.options.flags.contains(FileFlags::PATCH_FILE)),
+                        false,
+                    )
+                })
+                .flatten(),
+        }
+        .to_bytes()
+    }
+
+    /// Write the archive at the current position of a writer
+    pub fn write<W: Write + Seek>(&self, writer: &mut W) -> Result<()> {
+        let start = writer.stream_position()?;
+        let version = self.version;
+
+        let mut files = self.files.iter().collect::<Vec<_>>();
+        let listfile = self.listfile.then(|| NewFile {
+            name: LISTFILE_NAME.to_owned(),
+            data: self.listfile_bytes(),
+            options: FileOptions::compressed(COMPRESSION_ZLIB),
+            filetime: 0,
+        });
+        files.extend(&listfile);
+        let attributes = self.attributes.then(|| NewFile {
+            name: ATTRIBUTES_NAME.to_owned(),
+            data: self.attributes_bytes(&files),
+            options: FileOptions::compressed(COMPRESSION_ZLIB),
+            filetime: 0,
+        });
+        files.extend(&attributes);
+
+        let mut header = MpqHeader::new(version, self.sector_size_shift);
+        writer.write_all(&vec![0; version.header_size() as usize])?;
+        let mut position = version.header_size() as u64;
+
+        let mut entries = Vec::with_capacity(files.len());
+        for file in &files {
+            let file_size = u32::try_from(file.data.len())
+                .map_err(|_| MpqError::TooLarge(file.name.clone()))?;
+            let data = if file.is_delete_marker() {
+                Vec::new()
+            } else {
+                encode_file(
+                    &file.name,
+                    &file.data,
+                    file.options.flags,
+                    file.options.compression,
+                    position,
+                    self.sector_size(),
+                )?
+            };
+            entries.push(FileEntry {
+                position,
+                compressed_size: u32::try_from(data.len())
+                    .map_err(|_| MpqError::TooLarge(file.name.clone()))?,
+                file_size,
+                flags: file.options.flags | FileFlags::EXISTS,
+            });
+            writer.write_all(&data)?;
+            position += data.len() as u64;
+        }
+
+        let mut write_table = |data: &[u8], md5: &mut [u8; 16]| -> Result<(u64, u64)> {
+            let table_pos = position;
+            writer.write_all(data)?;
+            *md5 = Md5::digest(data).into();
+            position += data.len() as u64;
+            Ok((table_pos, data.len() as u64))
+        };
+
+        if version >= FormatVersion::V3 {
+            let names = files.iter().map(|f| f.name.as_str()).collect::<Vec<_>>();
+            (header.het_table_pos, header.het_table_size) =
+                write_table(&het_table(&names), &mut header.md5_het_table)?;
+            (header.bet_table_pos, header.bet_table_size) =
+                write_table(&bet_table(&entries, &names), &mut header.md5_bet_table)?;
+        }
+
+        let hash_entries = (files.len() * 2).next_power_of_two().max(16);
+        let mut hash_table = vec![HashEntry::EMPTY; hash_entries];
+        for (i, file) in files.iter().enumerate() {
+            insert_hash_entry(&mut hash_table, &file.name, file.options.locale, i as u32);
+        }
+        let mut data = hash_table
+            .iter()
+            .flat_map(|e| e.to_bytes())
+            .collect::<Vec<_>>();
+        encrypt(&mut data, hash_table_key());
+        header.hash_table_entries = hash_entries as u32;
+        (header.hash_table_pos, header.hash_table_size) =
+            write_table(&data, &mut header.md5_hash_table)?;
+
+        let mut data = entries
+            .iter()
+            .flat_map(|e| e.to_bytes())
+            .collect::<Vec<_>>();
+        encrypt(&mut data, block_table_key());
+        header.block_table_entries = entries.len() as u32;
+        (header.block_table_pos, header.block_table_size) =
+            write_table(&data, &mut header.md5_block_table)?;
+
+        if entries.iter().any(|e| e.position > u32::MAX as u64) {
+            if version < FormatVersion::V2 {
+                return Err(MpqError::TooLarge(format!(
+                    "archive over 4 GiB for {version:?}"
+                )));
+            }
+            let data = entries
+                .iter()
+                .flat_map(|e| ((e.position >> 32) as u16).to_le_bytes())
+                .collect::<Vec<_>>();
+            (header.hi_block_table_pos, header.hi_block_table_size) =
+                write_table(&data, &mut header.md5_hi_block_table)?;
+        }
+
+        header.archive_size = position;
+        if version < FormatVersion::V4 {
+            header.hash_table_size = 0;
+            header.block_table_size = 0;
+            header.hi_block_table_size = 0;
+            header.het_table_size = 0;
+            header.bet_table_size = 0;
+        } else {
+            let mut bytes = Vec::new();
+            header.write(&mut bytes)?;
+            header.md5_header = Md5::digest(&bytes[..bytes.len() - 16]).into();
+        }
+
+        writer.seek(SeekFrom::Start(start))?;
+        header.write(writer)?;
+        writer.seek(SeekFrom::Start(start + position))?;
+        Ok(())
+    }
+
+    /// Write the archive to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Compress a sector, or a file stored as a single unit, which is stored
+/// as is when compression doesn't make it smaller
+fn encode_sector(flags: FileFlags, compression: u8, data: &[u8]) -> Result<Vec<u8>> {
+    let compressed = if flags.contains(FileFlags::IMPLODE) {
+        implode(data)
+    } else if flags.contains(FileFlags::COMPRESS) {
+        compress(data, compression)?
+    } else {
+        return Ok(data.to_vec());
+    };
+    if compressed.len() < data.len() {
+        Ok(compressed)
+    } else {
+        Ok(data.to_vec())
+    }
+}
+
+/// Stored data of a file at a position relative to the archive header
+pub(crate) fn encode_file(
+    name: &str,
+    data: &[u8],
+    flags: FileFlags,
+    compression: u8,
+    position: u64,
+    sector_size: usize,
+) -> Result<Vec<u8>> {
+    let key = flags.contains(FileFlags::ENCRYPTED).then(|| {
+        file_key(
+            name,
+            position,
+            data.len() as u32,
+            flags.contains(FileFlags::FIX_KEY),
+        )
+    });
+
+    let mut out = Vec::new();
+    if flags.contains(FileFlags::PATCH_FILE) {
+        let info = PatchInfo {
+            length: PatchInfo::SIZE as u32,
+            flags: 0x8000_0000,
+            data_size: data.len() as u32,
+            md5: Md5::digest(data).into(),
+        };
+        out.extend(info.to_bytes());
+    }
+
+    if flags.contains(FileFlags::SINGLE_UNIT) {
+        let mut data = encode_sector(flags, compression, data)?;
+        if let Some(key) = key {
+            encrypt(&mut data, key);
+        }
+        out.extend(data);
+        return Ok(out);
+    }
+
+    let sectors = data.chunks(sector_size).collect::<Vec<_>>();
+    if !flags.is_compressed() {
+        for (i, sector) in sectors.iter().enumerate() {
+            let mut sector = sector.to_vec();
+            if let Some(key) = key {
+                encrypt(&mut sector, key.wrapping_add(i as u32));
+            }
+            out.extend(sector);
+        }
+        return Ok(out);
+    }
+
+    let has_crc = flags.contains(FileFlags::SECTOR_CRC);
+    let encoded = sectors
+        .iter()
+        .map(|sector| encode_sector(flags, compression, sector))
+        .collect::<Result<Vec<_>>>()?;
+    let checksums = encoded
+        .iter()
+        .map(|sector| sector_checksum(sector))
+        .collect::<Vec<_>>();
+
+    let mut offsets = vec![((sectors.len() + 1 + has_crc as usize) * 4) as u32];
+    for sector in &encoded {
+        offsets.push(offsets.last().unwrap() + sector.len() as u32);
+    }
+    if has_crc {
+        offsets.push(offsets.last().unwrap() + checksums.len() as u32 * 4);
+    }
+
+    let mut table = u32_bytes(&offsets);
+    if let Some(key) = key {
+        encrypt(&mut table, key.wrapping_sub(1));
+    }
+    out.extend(table);
+    for (i, mut sector) in encoded.into_iter().enumerate() {
+        if let Some(key) = key {
+            encrypt(&mut sector, key.wrapping_add(i as u32));
+        }
+        out.extend(sector);
+    }
+    if has_crc {
+        out.extend(u32_bytes(&checksums));
+    }
+    Ok(out)
+}
+
+fn u32_bytes(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// HET or BET table with its signature, version and size
+fn ext_table(magic: [u8; 4], mut data: Vec<u8>, key: u32) -> Vec<u8> {
+    let mut out = magic.to_vec();
+    out.extend(u32_bytes(&[1, data.len() as u32]));
+    encrypt(&mut data, key);
+    out.extend(data);
+    out
+}
+
+/// Encrypted HET table of the names of the files, in block order
+pub(crate) fn het_table(names: &[&str]) -> Vec<u8> {
+    let count = names.len();
+    let total_count = (count * 4 / 3).max(count + 1);
+    let index_bits = bit_count(count as u64).max(1);
+    let index_table_size = (index_bits as usize * total_count).div_ceil(8);
+
+    let mut name_hashes = vec![0u8; total_count];
+    let mut indexes = vec![0u8; index_table_size];
+    for (i, name) in names.iter().enumerate() {
+        let hash = jenkins_hash(name) | (1 << 63);
+        let mut slot = (hash % total_count as u64) as usize;
+        while name_hashes[slot] != 0 {
+            slot = (slot + 1) % total_count;
+        }
+        name_hashes[slot] = (hash >> 56) as u8;
+        write_bits(
+            &mut indexes,
+            (slot as u32 * index_bits) as u64,
+            index_bits,
+            i as u64,
+        );
+    }
+
+    let mut data = u32_bytes(&[
+        (32 + total_count + index_table_size) as u32,
+        count as u32,
+        total_count as u32,
+        64,
+        index_bits,
+        0,
+        index_bits,
+        index_table_size as u32,
+    ]);
+    data.extend(name_hashes);
+    data.extend(indexes);
+    ext_table(HET_MAGIC, data, hash_table_key())
+}
+
+/// Encrypted BET table of the files and their names
+pub(crate) fn bet_table(entries: &[FileEntry], names: &[&str]) -> Vec<u8> {
+    let mut flags = Vec::new();
+    for entry in entries {
+        if !flags.contains(&entry.flags.bits()) {
+            flags.push(entry.flags.bits());
+        }
+    }
+
+    let max =
+        |field: fn(&FileEntry) -> u64| bit_count(entries.iter().map(field).max().unwrap_or(0));
+    let bits_position = max(|e| e.position);
+    let bits_file_size = max(|e| e.file_size as u64);
+    let bits_compressed_size = max(|e| e.compressed_size as u64);
+    let bits_flag_index = bit_count(flags.len().saturating_sub(1) as u64);
+    let entry_bits = bits_position + bits_file_size + bits_compressed_size + bits_flag_index;
+
+    let mut table = vec![0u8; (entry_bits as usize * entries.len()).div_ceil(8)];
+    let mut hashes = vec![0u8; (56 * entries.len()).div_ceil(8)];
+    for (i, (entry, name)) in entries.iter().zip(names).enumerate() {
+        let mut bit = (i as u32 * entry_bits) as u64;
+        let flag_index = flags.iter().position(|&f| f == entry.flags.bits()).unwrap();
+        for (value, bits) in [
+            (entry.position, bits_position),
+            (entry.file_size as u64, bits_file_size),
+            (entry.compressed_size as u64, bits_compressed_size),
+            (flag_index as u64, bits_flag_index),
+        ] {
+            write_bits(&mut table, bit, bits, value);
+            bit += bits as u64;
+        }
+        write_bits(&mut hashes, i as u64 * 56, 56, jenkins_hash(name));
+    }
+
+    let mut data = u32_bytes(&[
+        (76 + flags.len() * 4 + table.len() + hashes.len()) as u32,
+        entries.len() as u32,
+        0x10,
+        entry_bits,
+        0,
+        bits_position,
+        bits_position + bits_file_size,
+        bits_position + bits_file_size + bits_compressed_size,
+        entry_bits,
+        bits_position,
+        bits_file_size,
+        bits_compressed_size,
+        bits_flag_index,
+        0,
+        56,
+        0,
+        56,
+        hashes.len() as u32,
+        flags.len() as u32,
+    ]);
+    data.extend(u32_bytes(&flags));
+    data.extend(table);
+    data.extend(hashes);
+    ext_table(BET_MAGIC, data, block_table_key())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::{COMPRESSION_BZIP2, COMPRESSION_PKWARE};
+    use crate::header::MPQ_MAGIC;
+    use std::io::Cursor;
+
+    fn text(len: usize) -> Vec<u8> {
+        let mut data = b"Azeroth ".repeat(len / 8 + 1);
+        data.truncate(len);
+        data
+    }
+
+    fn write(writer: &ArchiveWriter) -> Archive<Cursor<Vec<u8>>> {
+        let mut cursor = Cursor::new(Vec::new());
+        writer.write(&mut cursor).unwrap();
+        Archive::new(Cursor::new(cursor.into_inner())).unwrap()
+    }
+
+    fn test_writer(version: FormatVersion) -> ArchiveWriter {
+        let mut writer = ArchiveWriter::new(version);
+        writer.set_file_type("dbc", FileOptions::compressed(COMPRESSION_BZIP2));
+        writer.add("DBFilesClient/Spell.dbc", text(10000));
+        writer.add("Interface\\Icons\\Icon.blp", text(5000));
+        writer.add("Sound\\Music.mp3", text(3000));
+        writer.add_with_options(
+            "Data\\secret.txt",
+            text(6000),
+            FileOptions::compressed(COMPRESSION_PKWARE).encrypted(),
+        );
+        writer.add_with_options(
+            "Data\\single.txt",
+            text(700),
+            FileOptions::default().single_unit().encrypted(),
+        );
+        writer.add_with_options(
+            "Data\\implode.txt",
+            text(900),
+            FileOptions {
+                flags: FileFlags::IMPLODE,
+                ..FileOptions::stored()
+            },
+        );
+        writer.add_with_options(
+            "Data\\secret.txt",
+            b"german".to_vec(),
+            FileOptions::default().locale(0x407),
+        );
+        writer.add_delete_marker("Data\\old.txt");
+        writer
+    }
+
+    fn check(archive: &mut Archive<Cursor<Vec<u8>>>) {
+        assert_eq!(
+            archive.read_file("dbfilesclient\\spell.dbc").unwrap(),
+            text(10000)
+        );
+        assert_eq!(
+            archive.read_file("Interface/Icons/Icon.blp").unwrap(),
+            text(5000)
+        );
+        assert_eq!(archive.read_file("Sound\\Music.mp3").unwrap(), text(3000));
+        assert_eq!(archive.read_file("Data\\secret.txt").unwrap(), text(6000));
+        assert_eq!(archive.read_file("Data\\single.txt").unwrap(), text(700));
+        assert_eq!(archive.read_file("Data\\implode.txt").unwrap(), text(900));
+        archive.set_locale(0x407);
+        assert_eq!(archive.read_file("Data\\secret.txt").unwrap(), b"german");
+        archive.set_locale(0);
+        assert!(!archive.contains("Data\\old.txt"));
+        assert!(archive.find_file("Data\\old.txt").is_some());
+
+        for name in archive.list_files().unwrap() {
+            assert!(archive.verify_file(&name).unwrap(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_write() {
+        for version in [
+            FormatVersion::V1,
+            FormatVersion::V2,
+            FormatVersion::V3,
+            FormatVersion::V4,
+        ] {
+            let writer = test_writer(version);
+            let mut archive = write(&writer);
+            assert_eq!(archive.header().format_version, version);
+            assert_eq!(archive.header().sector_size(), 4096);
+            assert_eq!(archive.het_table().is_some(), version >= FormatVersion::V3);
+            check(&mut archive);
+
+            let names = archive.list_files().unwrap();
+            assert_eq!(names.len(), 6);
+            assert_eq!(names[0], "DBFilesClient\\Spell.dbc");
+
+            let index = archive.find_file("Sound\\Music.mp3").unwrap();
+            assert!(!archive.files()[index].flags.is_compressed());
+            let index = archive.find_file("DBFilesClient\\Spell.dbc").unwrap();
+            assert!(archive.files()[index].compressed_size < 10000);
+        }
+    }
+
+    #[test]
+    fn test_write_header() {
+        let mut writer = ArchiveWriter::new(FormatVersion::V4);
+        writer.add("a.txt", text(100));
+        writer.set_listfile(false);
+        writer.set_attributes(false);
+        assert!(matches!(
+            writer.set_sector_size(1000),
+            Err(MpqError::InvalidSectorSize(1000))
+        ));
+        writer.set_sector_size(512).unwrap();
+
+        let mut cursor = Cursor::new(vec![0xCC; 0x200]);
+        cursor.set_position(0x200);
+        writer.write(&mut cursor).unwrap();
+        let bytes = cursor.into_inner();
+        assert_eq!(bytes[0x200..0x204], MPQ_MAGIC);
+        assert_eq!(
+            Md5::digest(&bytes[0x200..0x2C0]).as_slice(),
+            &bytes[0x2C0..0x2D0]
+        );
+
+        let archive_size = bytes.len() as u64 - 0x200;
+        let mut archive = Archive::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(archive.header().sector_size(), 512);
+        assert_eq!(archive.header().archive_size, archive_size);
+        assert_eq!(archive.files().len(), 1);
+        assert!(!archive.contains(LISTFILE_NAME));
+        assert_eq!(archive.read_file("a.txt").unwrap(), text(100));
+    }
+
+    #[test]
+    fn test_modify() {
+        let mut archive = write(&test_writer(FormatVersion::V2));
+        let mut writer = ArchiveWriter::from_archive(&mut archive).unwrap();
+        assert_eq!(writer.version(), FormatVersion::V2);
+        assert_eq!(writer.names().count(), 8);
+        let mut rewritten = write(&writer);
+        check(&mut rewritten);
+
+        // Files keep their compression rather than getting the default one
+        for (name, mask) in [
+            ("DBFilesClient\\Spell.dbc", COMPRESSION_BZIP2),
+            ("Data\\secret.txt", COMPRESSION_PKWARE),
+        ] {
+            let index = rewritten.find_file(name).unwrap();
+            let (_, compression) = rewritten
+                .read_file_and_compression(index, Some(name))
+                .unwrap();
+            assert_eq!(compression, Some(mask), "{name}");
+        }
+
+        assert!(writer.remove("dbfilesclient/spell.dbc"));
+        assert!(!writer.remove("missing.txt"));
+        writer.add("Interface\\Icons\\Icon.blp", b"replaced".to_vec());
+        writer.add("new.txt", b"new".to_vec());
+        assert!(writer.contains("NEW.TXT"));
+
+        let mut archive = write(&writer);
+        assert!(!archive.contains("DBFilesClient\\Spell.dbc"));
+        assert_eq!(
+            archive.read_file("Interface\\Icons\\Icon.blp").unwrap(),
+            b"replaced"
+        );
+        assert_eq!(archive.read_file("new.txt").unwrap(), b"new");
+        assert_eq!(archive.read_file("Data\\secret.txt").unwrap(), text(6000));
+        assert_eq!(archive.files().len(), 10);
+
+        // Files that aren't in the (listfile) can't be kept
+        writer.set_listfile(false);
+        let mut archive = write(&writer);
+        assert!(matches!(
+            ArchiveWriter::from_archive(&mut archive),
+            Err(MpqError::UnnamedFile(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.dbc");
+        fs::write(&path, text(100)).unwrap();
+
+        let mut writer = ArchiveWriter::new(FormatVersion::V1);
+        writer.add_path("DBFilesClient\\a.dbc", &path).unwrap();
+        let mut archive = write(&writer);
+        let index = archive.find_file("DBFilesClient\\a.dbc").unwrap();
+        let filetimes = archive.attributes().unwrap().filetimes.unwrap();
+        assert_eq!(
+            filetimes[index],
+            filetime(fs::metadata(&path).unwrap().modified().unwrap())
+        );
+
+        let path = dir.path().join("out.MPQ");
+        writer.save(&path).unwrap();
+        let mut archive = Archive::open(&path).unwrap();
+        assert_eq!(
+            archive.read_file("DBFilesClient\\a.dbc").unwrap(),
+            text(100)
+        );
+    }
+}
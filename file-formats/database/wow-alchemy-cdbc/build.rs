@@ -0,0 +1,65 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory with the `.dbd` files to embed when the `embedded-dbd` feature
+/// is enabled, usually the `definitions` folder of a WoWDBDefs checkout
+const DBD_DIR_VAR: &str = "WOW_ALCHEMY_DBD_DIR";
+
+fn main() {
+    println!("cargo:rerun-if-env-changed={DBD_DIR_VAR}");
+
+    if env::var_os("CARGO_FEATURE_EMBEDDED_DBD").is_none() {
+        return;
+    }
+
+    let entries = match env::var_os(DBD_DIR_VAR).map(PathBuf::from) {
+        Some(dbd_dir) => {
+            println!("cargo:rerun-if-changed={}", dbd_dir.display());
+            dbd_files(&dbd_dir)
+        }
+        None => {
+            println!(
+                "cargo:warning=the embedded-dbd feature embeds the .dbd files of {DBD_DIR_VAR}, \
+                 which is not set, so no definitions are embedded"
+            );
+            Vec::new()
+        }
+    };
+
+    let mut code = String::from("pub static EMBEDDED_DBD: &[(&str, &str)] = &[\n");
+    for path in entries {
+        let name = path.file_name().unwrap().to_string_lossy();
+        let path = path.canonicalize().unwrap();
+        code.push_str(&format!(
+            "    ({name:?}, include_str!({:?})),\n",
+            path.display().to_string()
+        ));
+    }
+    code.push_str("];\n");
+
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    fs::write(Path::new(&out_dir).join("embedded_dbd.rs"), code).unwrap();
+}
+
+/// The `.dbd` files of a directory, sorted by path. A directory that can't be
+/// read embeds no definitions rather than failing the build.
+fn dbd_files(dbd_dir: &Path) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(dbd_dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            println!(
+                "cargo:warning=can't read {}: {err}, so no definitions are embedded",
+                dbd_dir.display()
+            );
+            return Vec::new();
+        }
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "dbd"))
+        .collect();
+    files.sort();
+    files
+}
@@ -0,0 +1,109 @@
+use std::io::{Read, Write};
+
+use crate::dbd::DbdFile;
+use crate::flatten::{
+    LocStringColumns, flatten_values, format_value, parse_value, table_columns, unflatten_values,
+};
+use crate::{Error, Record, Result};
+
+/// Write records as CSV with a header of the DBD field names, one column
+/// per array element and locale
+pub fn write_csv<W: Write>(
+    writer: W,
+    dbd: &DbdFile,
+    records: &[Record],
+    locstrings: LocStringColumns,
+) -> Result<()> {
+    let columns = table_columns(dbd, locstrings)?;
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(columns.iter().map(|(name, _)| name))?;
+    for record in records {
+        csv.write_record(flatten_values(record, locstrings)?.iter().map(format_value))?;
+    }
+    csv.flush()?;
+    Ok(())
+}
+
+/// Read records written by [`write_csv`]. Columns are matched by their
+/// header so they can be in any order.
+pub fn read_csv<R: Read>(
+    reader: R,
+    dbd: &DbdFile,
+    locstrings: LocStringColumns,
+) -> Result<Vec<Record>> {
+    let columns = table_columns(dbd, locstrings)?;
+    let mut csv = csv::Reader::from_reader(reader);
+    let headers = csv.headers()?.clone();
+    let positions = columns
+        .iter()
+        .map(|(name, _)| {
+            headers
+                .iter()
+                .position(|header| header == name)
+                .ok_or_else(|| Error::InvalidRecord(format!("missing column {name}")))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut records = Vec::new();
+    for row in csv.records() {
+        let row = row?;
+        let cells = columns
+            .iter()
+            .zip(&positions)
+            .map(|((name, field_type), position)| {
+                parse_value(row.get(*position).unwrap_or_default(), *field_type, name)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        records.push(unflatten_values(dbd, cells, locstrings)?);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dbd::parse_dbd_content;
+    use crate::{Locale, Value};
+
+    const DBD: &str = "COLUMNS
+int ID
+locstring Name_lang
+float Scale
+int Flags
+
+BUILD 3.3.5.12340
+$id$ID<32>
+Name_lang
+Scale
+Flags<u8>[2]
+";
+
+    #[test]
+    fn test_csv_roundtrip() {
+        let dbd = parse_dbd_content(&"3.3.5.12340".try_into().unwrap(), DBD).unwrap();
+        let mut strings = vec![Some(String::new()); 16];
+        strings[Locale::EnUS.index()] = Some("Quote \"and\", comma\nnewline".into());
+        let records = vec![vec![
+            Value::Int32(-3),
+            Value::LocString {
+                strings,
+                flags: 0xFF01FE,
+            },
+            Value::Float32(0.1),
+            Value::Array(vec![Value::UInt8(1), Value::UInt8(255)]),
+        ]];
+
+        let mut bytes = Vec::new();
+        write_csv(&mut bytes, &dbd, &records, LocStringColumns::PerLocale).unwrap();
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        assert!(text.starts_with("ID,Name_lang_enus,Name_lang_kokr,"));
+        assert!(text.contains(",Name_lang_flags,Scale,Flags_0,Flags_1\n"));
+        assert!(text.contains(",0.1,1,255\n"));
+
+        let read = read_csv(bytes.as_slice(), &dbd, LocStringColumns::PerLocale).unwrap();
+        assert_eq!(read, records);
+
+        let missing = read_csv("ID\n1\n".as_bytes(), &dbd, LocStringColumns::PerLocale);
+        assert!(matches!(missing, Err(Error::InvalidRecord(_))));
+    }
+}
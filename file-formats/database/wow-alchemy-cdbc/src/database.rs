@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::dbd::{DbdSource, GameBuild};
+use crate::{DbcTable, Error, Record, Result, Value};
+
+/// Loaded tables by name, resolving foreign keys of the DBD columns between
+/// them
+#[derive(Debug, Clone, Default)]
+pub struct DbcDatabase {
+    tables: HashMap<String, DbcTable>,
+}
+
+impl DbcDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a table, replacing the one with the same name. Names are case
+    /// insensitive.
+    pub fn insert(&mut self, name: &str, table: DbcTable) -> Option<DbcTable> {
+        self.tables.insert(name.to_lowercase(), table)
+    }
+
+    /// Load a file with the definition of the source, named like the DBD
+    /// file. The layout is selected by the layout hash of the file when it
+    /// has one.
+    pub fn load<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        source: &DbdSource,
+        game_build: &GameBuild,
+    ) -> Result<&DbcTable> {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| Error::GenericError(format!("not a file: {}", path.display())))?;
+        let definition = source.load(&file_name.to_string_lossy())?;
+
+        let table = DbcTable::open_definition(path, &definition, game_build)?;
+
+        let name = definition.name.to_lowercase();
+        self.tables.insert(name.clone(), table);
+        Ok(&self.tables[&name])
+    }
+
+    pub fn table(&self, name: &str) -> Option<&DbcTable> {
+        self.tables.get(&name.to_lowercase())
+    }
+
+    pub fn table_mut(&mut self, name: &str) -> Option<&mut DbcTable> {
+        self.tables.get_mut(&name.to_lowercase())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<DbcTable> {
+        self.tables.remove(&name.to_lowercase())
+    }
+
+    /// Lowercase names of the loaded tables
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tables.keys().map(String::as_str)
+    }
+
+    /// Rows referenced by a foreign key column of a row of `table`. Array
+    /// fields reference a row per element; ids of 0 reference nothing.
+    pub fn resolve(&self, table: &str, row: &Record, column: &str) -> Result<Vec<&Record>> {
+        let source = self.get(table)?;
+        let foreign_key = source
+            .dbd()
+            .columns
+            .get(column)
+            .and_then(|column| column.foreign_key.as_ref())
+            .ok_or_else(|| Error::GenericError(format!("{table}.{column} is not a foreign key")))?;
+        let target = self.get(&foreign_key.table)?;
+        let by_key = target.column_index(&foreign_key.field) == Some(target.key_index());
+
+        let ids = match source.field(row, column) {
+            Some(Value::Array(values)) => values.iter().filter_map(Value::as_i64).collect(),
+            Some(value) => value.as_i64().into_iter().collect(),
+            None => Vec::new(),
+        };
+
+        let mut rows = Vec::new();
+        for id in ids.into_iter().filter(|id| *id != 0) {
+            if by_key {
+                rows.extend(target.get(id as u32));
+            } else {
+                rows.extend(target.iter().filter(|target_row| {
+                    target
+                        .field(target_row, &foreign_key.field)
+                        .and_then(Value::as_i64)
+                        == Some(id)
+                }));
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Pairs of each row of `table` with the rows its foreign key column
+    /// references
+    pub fn join(&self, table: &str, column: &str) -> Result<Vec<(&Record, &Record)>> {
+        let mut pairs = Vec::new();
+        for row in self.get(table)? {
+            for target_row in self.resolve(table, row, column)? {
+                pairs.push((row, target_row));
+            }
+        }
+        Ok(pairs)
+    }
+
+    fn get(&self, name: &str) -> Result<&DbcTable> {
+        self.table(name)
+            .ok_or_else(|| Error::TableNotLoaded(name.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::DbcVersion;
+    use crate::dbd::parse_dbd_content;
+
+    const ICON_DBD: &str = "COLUMNS
+int ID
+string TextureFilename
+
+BUILD 3.3.5.12340
+$id$ID<32>
+TextureFilename
+";
+
+    const SPELL_DBD: &str = "COLUMNS
+int ID
+int<SpellIcon::ID> SpellIconID
+int<SpellIcon::ID> Icons
+
+BUILD 3.3.5.12340
+$id$ID<32>
+SpellIconID<32>
+Icons<32>[2]
+";
+
+    #[test]
+    fn test_load_and_resolve() {
+        let game_build: GameBuild = "3.3.5.12340".try_into().unwrap();
+        let dir = tempfile::tempdir().expect("temp dir");
+        fs::write(dir.path().join("SpellIcon.dbd"), ICON_DBD).unwrap();
+        fs::write(dir.path().join("Spell.dbd"), SPELL_DBD).unwrap();
+
+        let icons = DbcTable::new(
+            parse_dbd_content(&game_build, ICON_DBD).unwrap(),
+            vec![
+                vec![Value::Int32(1), Value::String(Some("Fire".into()))],
+                vec![Value::Int32(2), Value::String(Some("Frost".into()))],
+            ],
+        );
+        let mut bytes = Vec::new();
+        icons.write(&mut bytes, DbcVersion::WDBC).unwrap();
+        fs::write(dir.path().join("SpellIcon.dbc"), bytes).unwrap();
+
+        let spells = DbcTable::new(
+            parse_dbd_content(&game_build, SPELL_DBD).unwrap(),
+            vec![
+                vec![
+                    Value::Int32(10),
+                    Value::Int32(2),
+                    Value::Array(vec![Value::Int32(1), Value::Int32(0)]),
+                ],
+                vec![
+                    Value::Int32(11),
+                    Value::Int32(3),
+                    Value::Array(vec![Value::Int32(1), Value::Int32(2)]),
+                ],
+            ],
+        );
+
+        let mut database = DbcDatabase::new();
+        database.insert("Spell", spells);
+        let spell = database.table("spell").unwrap().get(10).unwrap().clone();
+        assert!(matches!(
+            database.resolve("Spell", &spell, "SpellIconID"),
+            Err(Error::TableNotLoaded(_))
+        ));
+
+        let source = DbdSource::Directory(dir.path().to_owned());
+        let loaded = database
+            .load(dir.path().join("SpellIcon.dbc"), &source, &game_build)
+            .unwrap();
+        assert_eq!(loaded.len(), 2);
+
+        let icons = database.resolve("Spell", &spell, "SpellIconID").unwrap();
+        assert_eq!(icons[0][1], Value::String(Some("Frost".into())));
+        assert_eq!(database.resolve("Spell", &spell, "Icons").unwrap().len(), 1);
+        assert!(database.resolve("Spell", &spell, "ID").is_err());
+
+        // Spell 11 references a missing icon
+        let pairs = database.join("Spell", "SpellIconID").unwrap();
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(database.join("Spell", "Icons").unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,998 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::SeekFrom;
+
+use wow_alchemy_data::error::Result as WDResult;
+use wow_alchemy_data::prelude::*;
+use wow_alchemy_data_derive::{WowEnumFrom, WowHeaderR, WowHeaderW};
+
+use crate::wdb::{DbcVersion, VGTE4, VGTE5, Wdb2Fields, WdbFile, WdbHeader};
+use crate::{Error, Result};
+
+/// Records have variable sizes and are located through the offset map
+pub const FLAG_OFFSET_MAP: u32 = 0x01;
+/// Ids are stored in a list after the records instead of inside them
+pub const FLAG_ID_LIST: u32 = 0x04;
+
+#[derive(Debug, Clone, Default, WowHeaderR)]
+pub struct Wdb5Header {
+    pub record_count: u32,
+    pub field_count: u32,
+    pub record_size: u32,
+    /// Absolute offset of the offset map when the file has one
+    pub string_table_size: u32,
+    pub table_hash: u32,
+    pub layout_hash: u32,
+    pub min_id: u32,
+    pub max_id: u32,
+    pub locale: u32,
+    pub copy_table_size: u32,
+    pub flags: u16,
+    pub id_index: u16,
+}
+
+#[derive(Debug, Clone, Default, WowHeaderR)]
+pub struct Wdc1Header {
+    pub record_count: u32,
+    pub field_count: u32,
+    pub record_size: u32,
+    pub string_table_size: u32,
+    pub table_hash: u32,
+    pub layout_hash: u32,
+    pub min_id: u32,
+    pub max_id: u32,
+    pub locale: u32,
+    pub copy_table_size: u32,
+    pub flags: u16,
+    pub id_index: u16,
+    pub total_field_count: u32,
+    pub bitpacked_data_offset: u32,
+    pub lookup_column_count: u32,
+    pub offset_map_offset: u32,
+    pub id_list_size: u32,
+    pub field_storage_info_size: u32,
+    pub common_data_size: u32,
+    pub pallet_data_size: u32,
+    pub relationship_data_size: u32,
+}
+
+#[derive(Debug, Clone, Default, WowHeaderR)]
+pub struct Wdc2Header {
+    pub record_count: u32,
+    pub field_count: u32,
+    pub record_size: u32,
+    pub string_table_size: u32,
+    pub table_hash: u32,
+    pub layout_hash: u32,
+    pub min_id: u32,
+    pub max_id: u32,
+    pub locale: u32,
+    pub flags: u16,
+    pub id_index: u16,
+    pub total_field_count: u32,
+    pub bitpacked_data_offset: u32,
+    pub lookup_column_count: u32,
+    pub field_storage_info_size: u32,
+    pub common_data_size: u32,
+    pub pallet_data_size: u32,
+    pub section_count: u32,
+}
+
+#[derive(Debug, Clone, Default, WowHeaderR)]
+pub struct Wdc2SectionHeader {
+    pub tact_key_hash: u64,
+    pub file_offset: u32,
+    pub record_count: u32,
+    pub string_table_size: u32,
+    pub copy_table_size: u32,
+    pub offset_map_offset: u32,
+    pub id_list_size: u32,
+    pub relationship_data_size: u32,
+}
+
+/// Size and position of a field in WDB5+ records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, WowHeaderR)]
+pub struct FieldStructure {
+    /// Unused bits of a 32 bit value, `32 - bits`
+    pub size: i16,
+    /// Byte offset of the field in the record
+    pub position: u16,
+}
+
+impl FieldStructure {
+    /// Size of a single value of the field in bytes, failing when the field
+    /// has a negative size or more than 64 bits
+    pub fn byte_size(&self) -> Result<usize> {
+        usize::try_from(32 - self.size as i32)
+            .ok()
+            .filter(|bits| *bits <= 64)
+            .map(|bits| bits / 8)
+            .ok_or_else(|| Error::InvalidRecord(format!("invalid field size {}", self.size)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, WowEnumFrom, WowHeaderR, WowHeaderW)]
+#[wow_data(from_type=u32)]
+pub enum FieldCompression {
+    #[default]
+    #[wow_data(expr = 0)]
+    None,
+    #[wow_data(expr = 1)]
+    Bitpacked,
+    /// Value of the common data block for the record id, or the default
+    #[wow_data(expr = 2)]
+    CommonData,
+    /// Bitpacked index into the pallet
+    #[wow_data(expr = 3)]
+    BitpackedIndexed,
+    /// Bitpacked index of an array in the pallet
+    #[wow_data(expr = 4)]
+    BitpackedIndexedArray,
+    #[wow_data(expr = 5)]
+    BitpackedSigned,
+}
+
+/// Storage of a field in WDC1+ records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, WowHeaderR)]
+pub struct FieldStorageInfo {
+    pub offset_bits: u16,
+    /// Size of the field in the record, including all array values
+    pub size_bits: u16,
+    /// Size of the field data in the pallet or common data block
+    pub additional_data_size: u32,
+    pub compression: FieldCompression,
+    /// Bitpacking offset, bitpacking size and flags for bitpacked fields,
+    /// default value for common data and bitpacking offset, bitpacking size
+    /// and array size for pallet fields
+    pub values: [u32; 3],
+}
+
+/// Location of a record in the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordInfo {
+    /// Absolute offset of the record data
+    pub offset: u64,
+    pub size: u32,
+    /// Index of the record in the file, rows of the copy table share the
+    /// index of the copied record
+    pub index: u32,
+    /// Id from the id list, the offset map or the copy table when the id is
+    /// not stored in the record
+    pub id: Option<u32>,
+    /// Id of the copied record for rows of the copy table
+    pub source_id: Option<u32>,
+}
+
+/// How the fields are stored in the records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordLayout {
+    /// Fields follow each other with the sizes of the DBD types
+    Plain,
+    /// Fields are at the positions of the WDB5 field structures, with
+    /// integers truncated to the structure size
+    Structured,
+    /// Variable sized records with inline strings
+    Sparse,
+    /// Fields are bitpacked or stored in the pallet or common data as in
+    /// the field storage info of WDC1+ files
+    Packed,
+}
+
+/// Part of a file with its own records and trailing tables. Files before
+/// WDC2 have a single section.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Section {
+    pub file_offset: u64,
+    pub record_count: u32,
+    pub string_table_size: u32,
+    /// Absolute offset of the offset map of sparse tables
+    pub offset_map_offset: u64,
+    pub id_list_size: u32,
+    pub copy_table_size: u32,
+    pub relationship_data_size: u32,
+}
+
+impl WdbFile {
+    pub(crate) fn empty(version: DbcVersion, header: WdbHeader, header_size: usize) -> Self {
+        Self {
+            version,
+            header,
+            header_size,
+            layout_hash: None,
+            strings: Vec::new(),
+            string_pos: BTreeMap::new(),
+            field_structures: Vec::new(),
+            storage_info: Vec::new(),
+            pallet_data: Vec::new(),
+            common_data: Vec::new(),
+            relationships: HashMap::new(),
+            records: Vec::new(),
+        }
+    }
+
+    /// Read WDB5 files, the first with field structures and layout hashes
+    pub(crate) fn read_wdb5<R: Read + Seek>(reader: &mut R) -> WDResult<Self> {
+        let header: Wdb5Header = reader.wow_read()?;
+        let mut field_structures = Vec::with_capacity(header.field_count as usize);
+        for _ in 0..header.field_count {
+            field_structures.push(reader.wow_read()?);
+        }
+        let header_size = reader.stream_position()? as usize;
+
+        let flags = header.flags as u32;
+        let sparse = flags & FLAG_OFFSET_MAP != 0;
+        let section = Section {
+            file_offset: header_size as u64,
+            record_count: header.record_count,
+            string_table_size: if sparse { 0 } else { header.string_table_size },
+            offset_map_offset: if sparse {
+                header.string_table_size as u64
+            } else {
+                0
+            },
+            id_list_size: if flags & FLAG_ID_LIST != 0 {
+                header.record_count * 4
+            } else {
+                0
+            },
+            copy_table_size: header.copy_table_size,
+            relationship_data_size: 0,
+        };
+
+        let mut wdb = Self::empty(
+            DbcVersion::WDB5,
+            WdbHeader {
+                record_count: header.record_count,
+                field_count: header.field_count,
+                record_size: header.record_size,
+                string_block_size: header.string_table_size,
+                wdb2: Wdb2Fields::Fields {
+                    table_hash: header.table_hash,
+                    build: 0,
+                    timestamp_last_written: 0,
+                    min_id: header.min_id,
+                    max_id: header.max_id,
+                    locale: header.locale,
+                    copy_table_size: header.copy_table_size,
+                },
+                flags: VGTE4::Some(flags),
+                id_index: VGTE5::Some(header.id_index as u32),
+            },
+            header_size,
+        );
+        wdb.layout_hash = Some(header.layout_hash);
+        wdb.field_structures = field_structures;
+        wdb.read_section(reader, &section)?;
+        Ok(wdb)
+    }
+
+    /// Read WDC1 files, the first with field storage info
+    pub(crate) fn read_wdc1<R: Read + Seek>(reader: &mut R) -> WDResult<Self> {
+        let header: Wdc1Header = reader.wow_read()?;
+        let mut field_structures = Vec::with_capacity(header.total_field_count as usize);
+        for _ in 0..header.total_field_count {
+            field_structures.push(reader.wow_read()?);
+        }
+        let header_size = reader.stream_position()? as usize;
+
+        let flags = header.flags as u32;
+        let sparse = flags & FLAG_OFFSET_MAP != 0;
+        let section = Section {
+            file_offset: header_size as u64,
+            record_count: header.record_count,
+            string_table_size: if sparse { 0 } else { header.string_table_size },
+            offset_map_offset: if sparse {
+                header.offset_map_offset as u64
+            } else {
+                0
+            },
+            id_list_size: header.id_list_size,
+            copy_table_size: header.copy_table_size,
+            relationship_data_size: 0,
+        };
+
+        let mut wdb = Self::empty(
+            DbcVersion::WDC1,
+            packed_header(
+                header.record_count,
+                header.field_count,
+                header.record_size,
+                header.string_table_size,
+                header.table_hash,
+                header.min_id,
+                header.max_id,
+                header.locale,
+                header.copy_table_size,
+                flags,
+                header.id_index,
+            ),
+            header_size,
+        );
+        wdb.layout_hash = Some(header.layout_hash);
+        wdb.field_structures = field_structures;
+        let end = wdb.read_section(reader, &section)?;
+
+        reader.seek(SeekFrom::Start(end))?;
+        wdb.read_storage_info(
+            reader,
+            header.field_storage_info_size,
+            header.pallet_data_size,
+            header.common_data_size,
+        )?;
+        if header.relationship_data_size > 0 {
+            wdb.read_relationships(reader, 0)?;
+        }
+        let copies = wdb.read_copy_table(reader, &section, end - section.copy_table_size as u64)?;
+        wdb.resolve_copies(reader, &copies)?;
+        Ok(wdb)
+    }
+
+    /// Read WDC2 files, with records split into sections
+    pub(crate) fn read_wdc2<R: Read + Seek>(reader: &mut R) -> WDResult<Self> {
+        let header: Wdc2Header = reader.wow_read()?;
+        let mut sections = Vec::with_capacity(header.section_count as usize);
+        for _ in 0..header.section_count {
+            let section: Wdc2SectionHeader = reader.wow_read()?;
+            sections.push(section);
+        }
+        let mut field_structures = Vec::with_capacity(header.total_field_count as usize);
+        for _ in 0..header.total_field_count {
+            field_structures.push(reader.wow_read()?);
+        }
+
+        let flags = header.flags as u32;
+        let sparse = flags & FLAG_OFFSET_MAP != 0;
+        let header_size = sections
+            .first()
+            .map(|section| section.file_offset as usize)
+            .unwrap_or(0);
+        let copy_table_size = sections.iter().map(|s| s.copy_table_size).sum();
+        let mut wdb = Self::empty(
+            DbcVersion::WDC2,
+            packed_header(
+                header.record_count,
+                header.field_count,
+                header.record_size,
+                header.string_table_size,
+                header.table_hash,
+                header.min_id,
+                header.max_id,
+                header.locale,
+                copy_table_size,
+                flags,
+                header.id_index,
+            ),
+            header_size,
+        );
+        wdb.layout_hash = Some(header.layout_hash);
+        wdb.field_structures = field_structures;
+        wdb.read_storage_info(
+            reader,
+            header.field_storage_info_size,
+            header.pallet_data_size,
+            header.common_data_size,
+        )?;
+
+        for section in sections {
+            // Sections of encrypted tables missing their key are zeroed
+            if section.tact_key_hash != 0 && section.file_offset == 0 {
+                continue;
+            }
+            let section = Section {
+                file_offset: section.file_offset as u64,
+                record_count: section.record_count,
+                string_table_size: section.string_table_size,
+                offset_map_offset: if sparse {
+                    section.offset_map_offset as u64
+                } else {
+                    0
+                },
+                id_list_size: section.id_list_size,
+                copy_table_size: section.copy_table_size,
+                relationship_data_size: section.relationship_data_size,
+            };
+            wdb.read_section(reader, &section)?;
+        }
+        Ok(wdb)
+    }
+
+    /// Read the records, strings, id list, copy table and relationship map
+    /// of a section. Returns the position after the copy table.
+    pub(crate) fn read_section<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        section: &Section,
+    ) -> WDResult<u64> {
+        let first_record = self.records.len();
+        let record_size = self.header.record_size;
+
+        let mut pos = if section.offset_map_offset != 0 {
+            let (min_id, max_id) = self.id_range();
+            reader.seek(SeekFrom::Start(section.offset_map_offset))?;
+            let mut seen = HashMap::new();
+            for id in min_id..=max_id {
+                let offset: u32 = reader.wow_read()?;
+                let size: u16 = reader.wow_read()?;
+                if offset == 0 || size == 0 {
+                    continue;
+                }
+                // Several ids can share the data of a record
+                let index = *seen
+                    .entry(offset)
+                    .or_insert((self.records.len() - first_record) as u32);
+                self.records.push(RecordInfo {
+                    offset: offset as u64,
+                    size: size as u32,
+                    index: (first_record as u32) + index,
+                    id: Some(id),
+                    source_id: None,
+                });
+            }
+            reader.stream_position()?
+        } else {
+            let records_size = section.record_count as u64 * record_size as u64;
+            for i in 0..section.record_count {
+                self.records.push(RecordInfo {
+                    offset: section.file_offset + i as u64 * record_size as u64,
+                    size: record_size,
+                    index: (first_record as u32) + i,
+                    id: None,
+                    source_id: None,
+                });
+            }
+
+            let string_start = section.file_offset + records_size;
+            reader.seek(SeekFrom::Start(string_start))?;
+            let mut string_block = vec![0u8; section.string_table_size as usize];
+            reader.read_exact(&mut string_block)?;
+            // WDC2+ string offsets are relative to the field in the record, so
+            // the strings are keyed by the position in the file
+            let base = if self.version >= DbcVersion::WDC2 {
+                string_start as usize
+            } else {
+                0
+            };
+            self.add_strings(&string_block, base);
+            string_start + section.string_table_size as u64
+        };
+
+        if section.id_list_size > 0 {
+            reader.seek(SeekFrom::Start(pos))?;
+            for record in self.records[first_record..].iter_mut() {
+                record.id = Some(reader.wow_read()?);
+            }
+            pos += section.id_list_size as u64;
+        }
+
+        let end = pos + section.copy_table_size as u64;
+        // WDC1 stores the field storage info between the copy table and the
+        // relationship map, so its copies are resolved when that is read
+        if self.version != DbcVersion::WDC1 {
+            let copies = self.read_copy_table(reader, section, pos)?;
+            self.resolve_copies(reader, &copies)?;
+        }
+
+        if section.relationship_data_size > 0 {
+            reader.seek(SeekFrom::Start(end))?;
+            self.read_relationships(reader, first_record as u32)?;
+        }
+
+        Ok(end)
+    }
+
+    /// Pairs of new and copied ids of the copy table at `pos`
+    fn read_copy_table<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        section: &Section,
+        pos: u64,
+    ) -> WDResult<Vec<(u32, u32)>> {
+        if section.copy_table_size == 0 || self.version < DbcVersion::WDB3 {
+            return Ok(Vec::new());
+        }
+        reader.seek(SeekFrom::Start(pos))?;
+        let count = section.copy_table_size / 8;
+        let mut copies = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let new_id: u32 = reader.wow_read()?;
+            let source_id: u32 = reader.wow_read()?;
+            copies.push((new_id, source_id));
+        }
+        Ok(copies)
+    }
+
+    /// Add rows of the copy table as records sharing the data of the copied
+    /// record
+    fn resolve_copies<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        copies: &[(u32, u32)],
+    ) -> WDResult<()> {
+        if copies.is_empty() {
+            return Ok(());
+        }
+        let mut by_id = HashMap::with_capacity(self.records.len());
+        for (idx, record) in self.records.iter().enumerate() {
+            let id = match record.id {
+                Some(id) => id,
+                None => {
+                    reader.seek(SeekFrom::Start(record.offset))?;
+                    let mut data = vec![0u8; record.size as usize];
+                    reader.read_exact(&mut data)?;
+                    self.inline_id(&data)?
+                }
+            };
+            by_id.entry(id).or_insert(idx);
+        }
+        for &(new_id, source_id) in copies {
+            let Some(&idx) = by_id.get(&source_id) else {
+                continue;
+            };
+            let source = self.records[idx];
+            self.records.push(RecordInfo {
+                id: Some(new_id),
+                source_id: Some(source_id),
+                ..source
+            });
+        }
+        Ok(())
+    }
+
+    fn read_storage_info<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        storage_info_size: u32,
+        pallet_data_size: u32,
+        common_data_size: u32,
+    ) -> WDResult<()> {
+        // Each field storage info is 24 bytes
+        for _ in 0..storage_info_size / 24 {
+            let info: FieldStorageInfo = reader.wow_read()?;
+            self.storage_info.push(info);
+        }
+
+        let mut pallet = vec![0u8; pallet_data_size as usize];
+        reader.read_exact(&mut pallet)?;
+        let mut common = vec![0u8; common_data_size as usize];
+        reader.read_exact(&mut common)?;
+
+        let (mut pallet_pos, mut common_pos) = (0, 0);
+        for info in &self.storage_info {
+            let size = info.additional_data_size as usize;
+            let mut values = Vec::new();
+            let mut common_values = HashMap::new();
+            match info.compression {
+                FieldCompression::BitpackedIndexed | FieldCompression::BitpackedIndexedArray => {
+                    let chunk = pallet.get(pallet_pos..pallet_pos + size).unwrap_or(&[]);
+                    values = chunk.chunks_exact(4).map(le_u32).collect();
+                    pallet_pos += size;
+                }
+                FieldCompression::CommonData => {
+                    let chunk = common.get(common_pos..common_pos + size).unwrap_or(&[]);
+                    for entry in chunk.chunks_exact(8) {
+                        common_values.insert(le_u32(&entry[..4]), le_u32(&entry[4..]));
+                    }
+                    common_pos += size;
+                }
+                _ => {}
+            }
+            self.pallet_data.push(values);
+            self.common_data.push(common_values);
+        }
+        Ok(())
+    }
+
+    fn read_relationships<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        first_record: u32,
+    ) -> WDResult<()> {
+        let count: u32 = reader.wow_read()?;
+        let _min_id: u32 = reader.wow_read()?;
+        let _max_id: u32 = reader.wow_read()?;
+        for _ in 0..count {
+            let foreign_id: u32 = reader.wow_read()?;
+            let record_index: u32 = reader.wow_read()?;
+            self.relationships
+                .insert(first_record + record_index, foreign_id);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn add_strings(&mut self, string_block: &[u8], base: usize) {
+        let mut current_pos = base;
+        for item in string_block.split(|i| *i == 0) {
+            self.string_pos.insert(current_pos, self.strings.len());
+            current_pos += item.len() + 1;
+            self.strings.push(String::from_utf8_lossy(item).into());
+        }
+    }
+
+    pub(crate) fn id_range(&self) -> (u32, u32) {
+        match self.header.wdb2 {
+            Wdb2Fields::Fields { min_id, max_id, .. } => (min_id, max_id),
+            Wdb2Fields::None => (0, 0),
+        }
+    }
+
+    /// Id stored in the data of a record
+    pub(crate) fn inline_id(&self, data: &[u8]) -> Result<u32> {
+        let id_index = self.id_index() as usize;
+        match self.record_layout() {
+            RecordLayout::Packed => Ok(self.packed_value(id_index, data, 0, 1, 0)? as u32),
+            RecordLayout::Structured => {
+                let structure = self.field_structures.get(id_index).ok_or_else(|| {
+                    Error::InvalidRecord(format!("no structure for id field {id_index}"))
+                })?;
+                Ok(read_le(data, structure.position as usize, structure.byte_size()?)? as u32)
+            }
+            RecordLayout::Plain | RecordLayout::Sparse => Ok(read_le(data, 0, 4)? as u32),
+        }
+    }
+
+    /// Raw value of a field stored as in the field storage info
+    pub(crate) fn packed_value(
+        &self,
+        column: usize,
+        data: &[u8],
+        element: usize,
+        count: usize,
+        id: u32,
+    ) -> Result<u64> {
+        let info = self
+            .storage_info
+            .get(column)
+            .ok_or_else(|| Error::InvalidRecord(format!("no storage info for field {column}")))?;
+        let offset = info.offset_bits as usize;
+        let pallet_value = |index: usize| {
+            self.pallet_data[column]
+                .get(index)
+                .map(|v| *v as u64)
+                .ok_or_else(|| Error::InvalidRecord(format!("pallet index {index} out of range")))
+        };
+        Ok(match info.compression {
+            FieldCompression::None | FieldCompression::Bitpacked => {
+                let bits = info.size_bits as usize / count.max(1);
+                read_bits(data, offset + element * bits, bits)?
+            }
+            FieldCompression::BitpackedSigned => {
+                let bits = info.size_bits as usize / count.max(1);
+                let value = read_bits(data, offset + element * bits, bits)?;
+                // Sign extend, a field without bits is 0
+                match bits {
+                    0 => 0,
+                    _ => {
+                        let shift = 64 - bits as u32;
+                        (((value << shift) as i64) >> shift) as u64
+                    }
+                }
+            }
+            FieldCompression::CommonData => self.common_data[column]
+                .get(&id)
+                .copied()
+                .unwrap_or(info.values[0]) as u64,
+            FieldCompression::BitpackedIndexed => {
+                pallet_value(read_bits(data, offset, info.size_bits as usize)? as usize)?
+            }
+            FieldCompression::BitpackedIndexedArray => {
+                let index = read_bits(data, offset, info.size_bits as usize)? as usize;
+                pallet_value(index * info.values[2] as usize + element)?
+            }
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn packed_header(
+    record_count: u32,
+    field_count: u32,
+    record_size: u32,
+    string_block_size: u32,
+    table_hash: u32,
+    min_id: u32,
+    max_id: u32,
+    locale: u32,
+    copy_table_size: u32,
+    flags: u32,
+    id_index: u16,
+) -> WdbHeader {
+    WdbHeader {
+        record_count,
+        field_count,
+        record_size,
+        string_block_size,
+        wdb2: Wdb2Fields::Fields {
+            table_hash,
+            build: 0,
+            timestamp_last_written: 0,
+            min_id,
+            max_id,
+            locale,
+            copy_table_size,
+        },
+        flags: VGTE4::Some(flags),
+        id_index: VGTE5::Some(id_index as u32),
+    }
+}
+
+fn le_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Little endian integer of `size` bytes at `offset`
+pub(crate) fn read_le(data: &[u8], offset: usize, size: usize) -> Result<u64> {
+    let bytes = data
+        .get(offset..offset + size)
+        .filter(|_| size <= 8)
+        .ok_or_else(|| {
+            Error::InvalidRecord(format!("{size} bytes at {offset} out of record bounds"))
+        })?;
+    Ok(bytes
+        .iter()
+        .rev()
+        .fold(0u64, |value, byte| (value << 8) | *byte as u64))
+}
+
+/// Little endian value of `bits` bits at the bit `offset`
+pub(crate) fn read_bits(data: &[u8], offset: usize, bits: usize) -> Result<u64> {
+    if bits > 64 || offset + bits > data.len() * 8 {
+        return Err(Error::InvalidRecord(format!(
+            "{bits} bits at {offset} out of record bounds"
+        )));
+    }
+    let mut value = 0u64;
+    for i in 0..bits {
+        let bit = offset + i;
+        if data[bit / 8] >> (bit % 8) & 1 != 0 {
+            value |= 1 << i;
+        }
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::dbd::parse_dbd;
+    use crate::{LazyRecordIterator, Value};
+
+    fn u32s(buf: &mut Vec<u8>, values: &[u32]) {
+        for value in values {
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    fn read_all(bytes: Vec<u8>, dbd: &str, build: &str) -> (WdbFile, Vec<Vec<Value>>) {
+        let mut cursor = Cursor::new(bytes);
+        let wdb = WdbFile::wow_read(&mut cursor).expect("read header");
+        let dbd = parse_dbd(dbd)
+            .unwrap()
+            .for_layout(wdb.layout_hash.unwrap_or(0), &build.try_into().unwrap())
+            .unwrap();
+        let records = LazyRecordIterator::new(&mut cursor, &dbd, &wdb)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .expect("read records");
+        (wdb, records)
+    }
+
+    #[test]
+    fn test_invalid_field_sizes() {
+        let structure = |size| FieldStructure { size, position: 0 };
+        assert_eq!(structure(8).byte_size().unwrap(), 3);
+        assert_eq!(structure(-32).byte_size().unwrap(), 8);
+        assert!(structure(40).byte_size().is_err());
+        assert!(structure(-40).byte_size().is_err());
+
+        let wdb = WdbFile {
+            version: DbcVersion::WDC1,
+            header: Default::default(),
+            header_size: 0,
+            layout_hash: None,
+            strings: Vec::new(),
+            string_pos: Default::default(),
+            field_structures: Vec::new(),
+            storage_info: vec![FieldStorageInfo {
+                compression: FieldCompression::BitpackedSigned,
+                ..Default::default()
+            }],
+            pallet_data: vec![Vec::new()],
+            common_data: vec![Default::default()],
+            relationships: Default::default(),
+            records: Vec::new(),
+        };
+        assert_eq!(wdb.packed_value(0, &[0xFF], 0, 1, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_read_wdb5() {
+        let dbd = "COLUMNS\nint ID\nstring Name\nint Value\nfloat Pos\nint Flags\n\n\
+                   LAYOUT 00000A5E\n$noninline,id$ID<32>\nName\nValue<32>\nPos[2]\nFlags<u8>\n";
+        let mut bytes = b"WDB5".to_vec();
+        u32s(&mut bytes, &[2, 4, 16, 14, 1, 0xA5E, 10, 30, 0, 8]);
+        bytes.extend_from_slice(&4u16.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        // Name, 24 bit Value, Pos array, 8 bit Flags
+        for (size, position) in [(0i16, 0u16), (8, 4), (0, 7), (24, 15)] {
+            bytes.extend_from_slice(&size.to_le_bytes());
+            bytes.extend_from_slice(&position.to_le_bytes());
+        }
+        for (name, value, flags) in [(1u32, 0x123456u32, 7u8), (7, 5, 255)] {
+            u32s(&mut bytes, &[name]);
+            bytes.extend_from_slice(&value.to_le_bytes()[..3]);
+            u32s(&mut bytes, &[1.0f32.to_bits(), 2.0f32.to_bits()]);
+            bytes.push(flags);
+        }
+        bytes.extend_from_slice(b"\0first\0second\0");
+        u32s(&mut bytes, &[10, 20]);
+        u32s(&mut bytes, &[30, 20]);
+
+        let (wdb, records) = read_all(bytes, dbd, "7.0.3.22000");
+        assert_eq!(wdb.record_layout(), RecordLayout::Structured);
+        assert_eq!(records.len(), 3);
+        let pos = Value::Array(vec![Value::Float32(1.0), Value::Float32(2.0)]);
+        assert_eq!(
+            records[0],
+            [
+                Value::Int32(10),
+                Value::String(Some("first".into())),
+                Value::Int32(0x123456),
+                pos.clone(),
+                Value::UInt8(7),
+            ]
+        );
+        assert_eq!(records[1][0], Value::Int32(20));
+        assert_eq!(records[1][4], Value::UInt8(255));
+        // Row of the copy table
+        assert_eq!(records[2][0], Value::Int32(30));
+        assert_eq!(records[2][1..], records[1][1..]);
+    }
+
+    #[test]
+    fn test_read_sparse_wdb4() {
+        let dbd = "COLUMNS\nint ID\nstring Name\nint Count\n\n\
+                   BUILD 7.0.1.20810\n$id$ID<32>\nName\nCount<u16>\n";
+        let mut bytes = b"WDB4".to_vec();
+        u32s(
+            &mut bytes,
+            &[2, 3, 10, 71, 1, 20810, 0, 3, 5, 0, 0, FLAG_OFFSET_MAP],
+        );
+        u32s(&mut bytes, &[3]);
+        bytes.extend_from_slice(b"ab\0\x07\0");
+        u32s(&mut bytes, &[5]);
+        bytes.extend_from_slice(b"xyz\0\x09\0");
+        for (offset, size) in [(52u32, 9u16), (0, 0), (61, 10)] {
+            u32s(&mut bytes, &[offset]);
+            bytes.extend_from_slice(&size.to_le_bytes());
+        }
+
+        let (wdb, records) = read_all(bytes, dbd, "7.0.1.20810");
+        assert_eq!(wdb.record_layout(), RecordLayout::Sparse);
+        assert_eq!(
+            records,
+            [
+                [
+                    Value::Int32(3),
+                    Value::String(Some("ab".into())),
+                    Value::UInt16(7)
+                ],
+                [
+                    Value::Int32(5),
+                    Value::String(Some("xyz".into())),
+                    Value::UInt16(9)
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_wdc1() {
+        let dbd = "COLUMNS\nint ID\nint MapID\nint Kind\nint Color\nint Rate\nint Delta\n\n\
+                   LAYOUT 0000C001\n$id$ID<32>\nKind<32>\nColor<u8>[3]\nRate<32>\nDelta<32>\n\
+                   $noninline,relation$MapID<32>\n";
+        let mut bytes = b"WDC1".to_vec();
+        u32s(&mut bytes, &[2, 5, 3, 0, 1, 0xC001, 5, 12, 0, 8]);
+        bytes.extend_from_slice(&[0; 4]);
+        u32s(&mut bytes, &[5, 0, 0, 0, 0, 120, 8, 36, 28]);
+        bytes.extend_from_slice(&[0; 5 * 4]);
+        // Bitpacked id, pallet Kind, pallet array Color, signed Delta
+        for record in [5u32 | 2 << 10 | 1 << 12 | 13 << 13, 9 | 7 << 13] {
+            bytes.extend_from_slice(&record.to_le_bytes()[..3]);
+        }
+        u32s(&mut bytes, &[12, 5]);
+        let storage = [
+            (0u16, 10u16, 0u32, 1u32, [0, 10, 0]),
+            (10, 2, 12, 3, [0, 2, 0]),
+            (12, 1, 24, 4, [0, 1, 3]),
+            (0, 0, 8, 2, [50, 0, 0]),
+            (13, 4, 0, 5, [0, 4, 0]),
+        ];
+        for (offset_bits, size_bits, additional, compression, values) in storage {
+            bytes.extend_from_slice(&offset_bits.to_le_bytes());
+            bytes.extend_from_slice(&size_bits.to_le_bytes());
+            u32s(&mut bytes, &[additional, compression]);
+            u32s(&mut bytes, &values);
+        }
+        u32s(&mut bytes, &[100, 200, 300, 1, 2, 3, 4, 5, 6]);
+        u32s(&mut bytes, &[5, 77]);
+        u32s(&mut bytes, &[2, 42, 43, 42, 0, 43, 1]);
+
+        let (wdb, records) = read_all(bytes, dbd, "7.3.5.25600");
+        assert_eq!(wdb.record_layout(), RecordLayout::Packed);
+        let color = |values: [u8; 3]| Value::Array(values.map(Value::UInt8).to_vec());
+        assert_eq!(
+            records,
+            [
+                [
+                    Value::Int32(5),
+                    Value::Int32(300),
+                    color([4, 5, 6]),
+                    Value::Int32(77),
+                    Value::Int32(-3),
+                    Value::Int32(42),
+                ],
+                [
+                    Value::Int32(9),
+                    Value::Int32(100),
+                    color([1, 2, 3]),
+                    Value::Int32(50),
+                    Value::Int32(7),
+                    Value::Int32(43),
+                ],
+                [
+                    Value::Int32(12),
+                    Value::Int32(300),
+                    color([4, 5, 6]),
+                    Value::Int32(77),
+                    Value::Int32(-3),
+                    Value::Int32(42),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_wdc2() {
+        let dbd = "COLUMNS\nint ID\nstring Name\nint Level\n\n\
+                   LAYOUT 0000C002\n$noninline,id$ID<32>\nName\nLevel<u16>\n";
+        let mut bytes = b"WDC2".to_vec();
+        u32s(&mut bytes, &[2, 2, 6, 12, 1, 0xC002, 100, 101, 0]);
+        bytes.extend_from_slice(&[0; 4]);
+        u32s(&mut bytes, &[2, 0, 0, 48, 0, 0, 1]);
+        // Section header
+        bytes.extend_from_slice(&[0; 8]);
+        u32s(&mut bytes, &[164, 2, 12, 0, 0, 8, 0]);
+        bytes.extend_from_slice(&[0; 2 * 4]);
+        for (offset_bits, size_bits) in [(0u16, 32u16), (32, 16)] {
+            bytes.extend_from_slice(&offset_bits.to_le_bytes());
+            bytes.extend_from_slice(&size_bits.to_le_bytes());
+            u32s(&mut bytes, &[0, 0, 0, 0, 0]);
+        }
+        assert_eq!(bytes.len(), 164);
+        // String offsets are relative to the field
+        for (name, level) in [(13u32, 1u16), (13, 2)] {
+            u32s(&mut bytes, &[name]);
+            bytes.extend_from_slice(&level.to_le_bytes());
+        }
+        bytes.extend_from_slice(b"\0alpha\0beta\0");
+        u32s(&mut bytes, &[100, 101]);
+
+        let (_, records) = read_all(bytes, dbd, "8.0.1.26231");
+        assert_eq!(
+            records,
+            [
+                [
+                    Value::Int32(100),
+                    Value::String(Some("alpha".into())),
+                    Value::UInt16(1)
+                ],
+                [
+                    Value::Int32(101),
+                    Value::String(Some("beta".into())),
+                    Value::UInt16(2)
+                ],
+            ]
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt::Write;
+
+use super::{DbdField, DbdFile, TypeSize, locstring_locales};
+use crate::{Error, Result};
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+    "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while", "abstract", "become", "box", "do", "final", "macro", "override",
+    "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Generate a module with a `#[derive(DbcRecord)]` struct for each table,
+/// given as the table name with its layout
+pub fn generate_module(tables: &[(String, DbdFile)]) -> Result<String> {
+    let mut output = String::from(
+        "// Generated from DBD definitions\n\n\
+         #![allow(dead_code, unused_imports)]\n\n\
+         use wow_alchemy_cdbc::{DbcRecord, LocString};\n",
+    );
+    for (name, dbd_file) in tables {
+        output.push('\n');
+        output.push_str(&generate_record(name, dbd_file)?);
+    }
+    Ok(output)
+}
+
+/// Generate the source of a struct with the layout of a table
+pub fn generate_record(table_name: &str, dbd_file: &DbdFile) -> Result<String> {
+    let struct_name = type_name(table_name);
+    let mut output = String::new();
+    let _ = writeln!(
+        output,
+        "/// `{table_name}` record of build {}",
+        dbd_file.game_build
+    );
+    let _ = writeln!(output, "#[derive(Debug, Clone, PartialEq, DbcRecord)]");
+    if struct_name != table_name {
+        let _ = writeln!(output, "#[dbc(table = \"{table_name}\")]");
+    }
+    let _ = writeln!(output, "pub struct {struct_name} {{");
+
+    for field in &dbd_file.build.fields {
+        let column = dbd_file
+            .columns
+            .get(&field.name)
+            .ok_or_else(|| Error::GenericError(format!("column not found: {}", field.name)))?;
+        if let Some(comment) = &column.comment {
+            let _ = writeln!(output, "    /// {comment}");
+        }
+        if let Some(comment) = &field.comment {
+            let _ = writeln!(output, "    /// {comment}");
+        }
+
+        let ident = field_name(&field.name);
+        let mut attrs = Vec::new();
+        if field.is_key {
+            attrs.push("key".to_owned());
+        }
+        if field.is_noninline {
+            attrs.push("noninline".to_owned());
+        }
+        if field.is_relation {
+            attrs.push("relation".to_owned());
+        }
+        if ident.trim_start_matches("r#") != field.name {
+            attrs.push(format!("name = \"{}\"", field.name));
+        }
+        if let Some(foreign_key) = &column.foreign_key {
+            attrs.push(format!(
+                "foreign_key = \"{}::{}\"",
+                foreign_key.table, foreign_key.field
+            ));
+        }
+        if !attrs.is_empty() {
+            let _ = writeln!(output, "    #[dbc({})]", attrs.join(", "));
+        }
+
+        let base = base_rust_type(dbd_file, &column.base_type, field)?;
+        let ty = match field.array_size {
+            Some(size) if field.is_array => format!("[{base}; {size}]"),
+            _ => base.to_owned(),
+        };
+        let _ = writeln!(output, "    pub {ident}: {ty},");
+    }
+
+    output.push_str("}\n");
+    Ok(output)
+}
+
+fn base_rust_type(dbd_file: &DbdFile, base_type: &str, field: &DbdField) -> Result<&'static str> {
+    Ok(match base_type {
+        "string" => "String",
+        "locstring" if locstring_locales(&dbd_file.game_build) > 1 => "LocString",
+        "locstring" => "String",
+        "float" => "f32",
+        _ => match field.type_size {
+            TypeSize::Int8 => "i8",
+            TypeSize::UInt8 => "u8",
+            TypeSize::Int16 => "i16",
+            TypeSize::UInt16 => "u16",
+            TypeSize::Int32 => "i32",
+            TypeSize::UInt32 => "u32",
+            TypeSize::Int64 => "i64",
+            TypeSize::UInt64 => "u64",
+            TypeSize::Unspecified => {
+                return Err(Error::GenericError(format!(
+                    "no size for integer field {}",
+                    field.name
+                )));
+            }
+        },
+    })
+}
+
+/// Snake case field name of a DBD column, like `spell_icon_id` for
+/// `SpellIconID`
+fn field_name(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut result = String::with_capacity(name.len() + 4);
+    for (idx, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && idx > 0 {
+            let prev = chars[idx - 1];
+            let next_lower = chars.get(idx + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_lower)
+            {
+                result.push('_');
+            }
+        }
+        if c.is_ascii_alphanumeric() {
+            result.push(c.to_ascii_lowercase());
+        } else if !result.ends_with('_') {
+            result.push('_');
+        }
+    }
+
+    if result.is_empty() || result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    if KEYWORDS.contains(&result.as_str()) {
+        result.insert_str(0, "r#");
+    }
+    result
+}
+
+/// Struct name of a table, keeping its case but dropping characters that are
+/// not valid in identifiers
+fn type_name(table_name: &str) -> String {
+    let mut result = table_name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect::<String>();
+    if result.is_empty() || result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dbd::parse_dbd_content;
+
+    #[test]
+    fn test_field_name() {
+        for (name, expected) in [
+            ("ID", "id"),
+            ("SpellIconID", "spell_icon_id"),
+            ("Name_lang", "name_lang"),
+            ("EffectBasePoints", "effect_base_points"),
+            ("Field_3_3_5_12340_001", "field_3_3_5_12340_001"),
+            ("Type", "r#type"),
+            ("UIName", "ui_name"),
+        ] {
+            assert_eq!(field_name(name), expected);
+        }
+    }
+
+    #[test]
+    fn test_generate_record() {
+        let content = "COLUMNS
+int ID
+locstring Name_lang // display name
+int<SpellIcon::ID> SpellIconID
+float Speed
+int Type
+
+BUILD 3.3.5.12340
+$id$ID<32>
+Name_lang
+SpellIconID<u32>
+Speed[2]
+Type<u8>
+";
+        let dbd = parse_dbd_content(&"3.3.5.12340".try_into().unwrap(), content).unwrap();
+        let source = generate_record("Spell", &dbd).unwrap();
+        assert_eq!(
+            source,
+            "/// `Spell` record of build 3.3.5.12340
+#[derive(Debug, Clone, PartialEq, DbcRecord)]
+pub struct Spell {
+    #[dbc(key, name = \"ID\")]
+    pub id: i32,
+    /// display name
+    #[dbc(name = \"Name_lang\")]
+    pub name_lang: LocString,
+    #[dbc(name = \"SpellIconID\", foreign_key = \"SpellIcon::ID\")]
+    pub spell_icon_id: u32,
+    #[dbc(name = \"Speed\")]
+    pub speed: [f32; 2],
+    #[dbc(name = \"Type\")]
+    pub r#type: u8,
+}
+"
+        );
+    }
+}
@@ -1,65 +1,53 @@
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
 use std::path::Path;
 
-use crate::{Error, Result};
+use crate::{Error, FieldType, Result};
 
+/// Rust structs for `#[derive(DbcRecord)]` from DBD layouts
+pub mod codegen;
+#[cfg(feature = "download")]
 pub mod download;
 pub mod file_map;
-
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct GameBuild {
-    pub major: u32,
-    pub minor: u32,
-    pub patch: u32,
-    pub build: u32,
-}
-
-impl TryFrom<&str> for GameBuild {
-    type Error = Error;
-
-    fn try_from(value: &str) -> Result<Self> {
-        let parts = value.split(&".");
-        let mut vals: [u32; 4] = [0, 0, 0, 0];
-        let mut count = 0;
-
-        for part in parts {
-            let Ok(val) = part.parse() else {
-                return Err(Error::GameBuild(format!(
-                    "can't convert string {} to game build",
-                    value
-                )));
-            };
-            vals[count] = val;
-
-            count += 1;
-            if count > 4 {
-                return Err(Error::GameBuild(format!(
-                    "can't convert string {} to game build",
-                    value
-                )));
-            }
-        }
-
-        Ok(Self {
-            major: vals[0],
-            minor: vals[1],
-            patch: vals[2],
-            build: vals[3],
-        })
+/// Parsing of the DBD text format
+mod parser;
+pub mod source;
+
+pub use parser::parse_dbd;
+pub use source::{DbdDefinition, DbdSource};
+pub use wow_alchemy_data::game_version::{GameBuild, GameVersion};
+
+/// Amount of strings in a `locstring` field, without the flags that
+/// follow them. Files from Cataclysm on have only the client locale and
+/// store it as a plain string.
+pub fn locstring_locales(game_build: &GameBuild) -> usize {
+    match game_build.version() {
+        Ok(GameVersion::Vanilla) => 8,
+        Ok(GameVersion::TBC | GameVersion::WotLK) => 16,
+        _ => 1,
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct DbdColumn {
     pub name: String,
+    /// `int`, `float`, `string` or `locstring`
     pub base_type: String,
     pub foreign_key: Option<ForeignKey>,
     pub comment: Option<String>,
+    /// The name is marked with `?` as not verified
     pub is_optional: bool,
 }
 
-#[derive(Debug, Clone)]
+impl DbdColumn {
+    /// Localized string with one value per locale in files before Cataclysm
+    pub fn is_localized(&self) -> bool {
+        self.base_type == "locstring"
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ForeignKey {
     pub table: String,
     pub field: String,
@@ -71,9 +59,13 @@ pub struct DbdField {
     pub type_size: TypeSize,
     pub is_array: bool,
     pub array_size: Option<usize>,
+    /// `$id$` annotation
     pub is_key: bool,
+    /// `$relation$` annotation
     pub is_relation: bool,
+    /// `$noninline$` annotation, the value is not stored in the record
     pub is_noninline: bool,
+    pub comment: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -123,244 +115,147 @@ impl TypeSize {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Layout of a table used by a set of builds and layout hashes
+#[derive(Debug, Clone, Default)]
 pub struct DbdBuild {
     pub versions: Vec<GameBuildSpec>,
+    /// Layout hashes of files using the fields, from `LAYOUT` lines
+    pub layouts: Vec<u32>,
+    pub comments: Vec<String>,
     pub fields: Vec<DbdField>,
 }
 
+impl DbdBuild {
+    pub fn matches_build(&self, game_build: &GameBuild) -> bool {
+        self.versions.iter().any(|spec| spec.contains(game_build))
+    }
+
+    pub fn matches_layout(&self, layout_hash: u32) -> bool {
+        self.layouts.contains(&layout_hash)
+    }
+}
+
+/// Layout of a table for a single build, with the column definitions
 #[derive(Debug, Clone)]
 pub struct DbdFile {
+    pub game_build: GameBuild,
     pub columns: HashMap<String, DbdColumn>,
     pub build: DbdBuild,
 }
 
-pub fn parse_dbd_file(game_build: &GameBuild, path: &Path) -> Result<DbdFile> {
-    let content = fs::read_to_string(path)?;
-    parse_dbd_content(game_build, &content)
+impl DbdFile {
+    /// Storage type of a field of the build layout
+    pub fn field_type(&self, field: &DbdField) -> Result<FieldType> {
+        let column = self
+            .columns
+            .get(&field.name)
+            .ok_or_else(|| Error::GenericError(format!("column not found: {}", field.name)))?;
+
+        Ok(match column.base_type.as_str() {
+            "string" => FieldType::String,
+            "locstring" => match locstring_locales(&self.game_build) {
+                1 => FieldType::String,
+                locales => FieldType::LocString(locales),
+            },
+            "float" => FieldType::Float32,
+            _ => match field.type_size {
+                TypeSize::Unspecified => {
+                    return Err(Error::GenericError(format!(
+                        "got unspecified field for col: {:?}",
+                        column
+                    )));
+                }
+                TypeSize::Int8 => FieldType::Int8,
+                TypeSize::UInt8 => FieldType::UInt8,
+                TypeSize::Int16 => FieldType::Int16,
+                TypeSize::UInt16 => FieldType::UInt16,
+                TypeSize::Int32 => FieldType::Int32,
+                TypeSize::UInt32 => FieldType::UInt32,
+                TypeSize::Int64 => FieldType::Int64,
+                TypeSize::UInt64 => FieldType::UInt64,
+            },
+        })
+    }
 }
 
-#[derive(Debug, Clone)]
-pub enum GameBuildSpec {
-    Single(GameBuild),
-    Range((GameBuild, GameBuild)),
+/// Whole DBD file with the column definitions and the layouts of all builds
+#[derive(Debug, Clone, Default)]
+pub struct Dbd {
+    pub columns: HashMap<String, DbdColumn>,
+    /// Names of the columns in the order they are defined
+    pub column_order: Vec<String>,
+    pub builds: Vec<DbdBuild>,
 }
 
-pub fn parse_dbd_content(game_build: &GameBuild, content: &str) -> Result<DbdFile> {
-    let mut columns = HashMap::new();
-
-    let mut current_section = None;
-    let mut build_state = 0;
-    let mut current_build_versions = Vec::new();
-    let mut current_build_fields = Vec::new();
-
-    for line in content.lines() {
-        let line = line.trim();
-
-        if line.is_empty() {
-            continue;
-        }
-
-        if line == "COLUMNS" {
-            current_section = Some("COLUMNS");
-            continue;
-        } else if let Some(stripped) = line.strip_prefix("BUILD ") {
-            if build_state == 2 {
-                break;
-            }
-            current_section = Some("BUILD");
-            let versions: Vec<String> = stripped.split(", ").map(|s| s.to_string()).collect();
-            for version in versions {
-                match version.split_once("-") {
-                    Some((a, b)) => {
-                        let current_build_a: GameBuild = a.try_into()?;
-                        let current_build_b: GameBuild = b.try_into()?;
-                        if *game_build >= current_build_a && *game_build <= current_build_b {
-                            build_state = 1;
-                        }
-                        current_build_versions
-                            .push(GameBuildSpec::Range((current_build_a, current_build_b)));
-                    }
-                    None => {
-                        let current_build: GameBuild = version.as_str().try_into()?;
-                        if current_build == *game_build {
-                            build_state = 1;
-                        }
-                        current_build_versions.push(GameBuildSpec::Single(current_build));
-                    }
-                }
-            }
-            continue;
-        } else if line.strip_prefix("LAYOUT ").is_some() {
-            if build_state == 2 {
-                break;
-            }
-            continue;
-        } else if line.strip_prefix("COMMENT ").is_some() {
-            continue;
-        }
-
-        match current_section {
-            Some("COLUMNS") => {
-                if let Some(column) = parse_column_line(line) {
-                    columns.insert(column.name.trim_end_matches("?").into(), column);
-                }
-            }
-            Some("BUILD") if build_state >= 1 => {
-                build_state = 2;
-                let field = parse_field_line(line);
-                current_build_fields.push(field);
-            }
-            _ => {
-                current_build_versions = Vec::new();
-            }
-        }
+impl Dbd {
+    /// First layout that covers the build
+    pub fn find_build(&self, game_build: &GameBuild) -> Option<&DbdBuild> {
+        self.builds
+            .iter()
+            .find(|build| build.matches_build(game_build))
     }
 
-    if current_build_fields.is_empty() {
-        println!("{content}");
-        return Err(Error::NoFieldsForBuild);
+    /// Layout with the layout hash of a file
+    pub fn find_layout(&self, layout_hash: u32) -> Option<&DbdBuild> {
+        self.builds
+            .iter()
+            .find(|build| build.matches_layout(layout_hash))
     }
 
-    Ok(DbdFile {
-        columns,
-        build: DbdBuild {
-            versions: current_build_versions,
-            fields: current_build_fields,
-        },
-    })
-}
-
-fn parse_column_line(line: &str) -> Option<DbdColumn> {
-    let parts: Vec<&str> = line.splitn(3, ' ').collect();
-    if parts.len() < 2 {
-        return None;
+    /// Select the layout of a build
+    pub fn for_build(&self, game_build: &GameBuild) -> Result<DbdFile> {
+        let build = self.find_build(game_build).ok_or(Error::NoFieldsForBuild)?;
+        Ok(self.make_file(game_build, build))
     }
 
-    let type_and_rest = parts[0];
-    let rest = parts[1..].join(" ");
-
-    // Extract base type and check for foreign key in the type specification
-    let (base_type, type_foreign_key) = if let Some(angle_start) = type_and_rest.find('<') {
-        let base = &type_and_rest[..angle_start];
-        if let Some(angle_end) = type_and_rest.find('>') {
-            let fk_str = &type_and_rest[angle_start + 1..angle_end];
-            let foreign_key = fk_str.find("::").map(|sep_pos| ForeignKey {
-                table: fk_str[..sep_pos].to_string(),
-                field: fk_str[sep_pos + 2..].to_string(),
-            });
-            (base, foreign_key)
-        } else {
-            (type_and_rest, None)
+    /// Select the layout by the layout hash of a file, falling back to the
+    /// build when no layout has the hash
+    pub fn for_layout(&self, layout_hash: u32, game_build: &GameBuild) -> Result<DbdFile> {
+        match self.find_layout(layout_hash) {
+            Some(build) => Ok(self.make_file(game_build, build)),
+            None => self.for_build(game_build),
         }
-    } else {
-        (type_and_rest, None)
-    };
-
-    let is_optional = rest.trim_end().ends_with('?');
-    let rest = if is_optional {
-        rest.trim_end().trim_end_matches('?')
-    } else {
-        rest.trim_end()
-    };
+    }
 
-    let (name, remaining) = {
-        let comment_pos = rest.find("//");
-        if let Some(pos) = comment_pos {
-            (rest[..pos].trim().to_string(), &rest[pos..])
-        } else {
-            (rest.trim().to_string(), "")
+    fn make_file(&self, game_build: &GameBuild, build: &DbdBuild) -> DbdFile {
+        DbdFile {
+            game_build: *game_build,
+            columns: self.columns.clone(),
+            build: build.clone(),
         }
-    };
-
-    let comment = if remaining.trim().starts_with("//") {
-        Some(remaining.trim()[2..].trim().to_string())
-    } else {
-        None
-    };
-
-    Some(DbdColumn {
-        name,
-        base_type: base_type.to_string(),
-        foreign_key: type_foreign_key,
-        comment,
-        is_optional,
-    })
+    }
 }
 
-fn parse_field_line(line: &str) -> DbdField {
-    let mut name: String;
-    let mut type_size = TypeSize::Unspecified;
-    let mut is_array = false;
-    let mut array_size = None;
-    let mut is_key = false;
-    let mut is_relation = false;
-    let mut is_noninline = false;
-
-    // Check for special markers
-    let line = if let Some(stripped) = line.strip_prefix("$id$") {
-        is_key = true;
-        stripped
-    } else if let Some(stripped) = line.strip_prefix("$noninline,id$") {
-        is_key = true;
-        is_noninline = true;
-        stripped
-    } else if let Some(stripped) = line.strip_prefix("$relation$") {
-        is_relation = true;
-        stripped
-    } else {
-        line
-    };
+pub fn parse_dbd_file(game_build: &GameBuild, path: &Path) -> Result<DbdFile> {
+    let content = fs::read_to_string(path)?;
+    parse_dbd_content(game_build, &content)
+}
 
-    // Handle array notation first (can be combined with type size)
-    let (base_part, array_info) = if let Some(bracket_start) = line.find('[') {
-        if let Some(bracket_end) = line.find(']') {
-            let array_str = &line[bracket_start + 1..bracket_end];
-            is_array = true;
-            array_size = array_str.parse().ok();
+#[derive(Debug, Clone)]
+pub enum GameBuildSpec {
+    Single(GameBuild),
+    Range((GameBuild, GameBuild)),
+}
 
-            // Check if there's a type spec before the array
-            let before_bracket = &line[..bracket_start];
-            let after_bracket = &line[bracket_end + 1..];
-            (
-                before_bracket.to_string() + after_bracket,
-                Some((is_array, array_size)),
-            )
-        } else {
-            (line.to_string(), None)
+impl GameBuildSpec {
+    pub fn contains(&self, game_build: &GameBuild) -> bool {
+        match self {
+            GameBuildSpec::Single(build) => build == game_build,
+            GameBuildSpec::Range((start, end)) => start <= game_build && game_build <= end,
         }
-    } else {
-        (line.to_string(), None)
-    };
-
-    // Apply array info if found
-    if let Some((arr, size)) = array_info {
-        is_array = arr;
-        array_size = size;
     }
+}
 
-    // Parse type size notation
-    if let Some(angle_start) = base_part.find('<') {
-        name = base_part[..angle_start].to_string();
-        if let Some(angle_end) = base_part.find('>') {
-            let size_str = &base_part[angle_start + 1..angle_end];
-            type_size = TypeSize::parse_type_size(size_str);
+impl fmt::Display for GameBuildSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameBuildSpec::Single(build) => write!(f, "{build}"),
+            GameBuildSpec::Range((start, end)) => write!(f, "{start}-{end}"),
         }
-    } else {
-        name = base_part.trim().to_string();
-    }
-
-    if let Some(idx) = name.find(" ") {
-        name.truncate(idx);
     }
+}
 
-    DbdField {
-        name,
-        type_size,
-        is_array,
-        array_size,
-        is_key,
-        is_relation,
-        is_noninline,
-    }
+/// Parse a DBD file and select the layout of the build
+pub fn parse_dbd_content(game_build: &GameBuild, content: &str) -> Result<DbdFile> {
+    parse_dbd(content)?.for_build(game_build)
 }
@@ -0,0 +1,349 @@
+use super::{Dbd, DbdBuild, DbdColumn, DbdField, ForeignKey, GameBuild, GameBuildSpec, TypeSize};
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    None,
+    Columns,
+    Definition,
+}
+
+/// Parse all column definitions and build layouts of a DBD file
+pub fn parse_dbd(content: &str) -> Result<Dbd> {
+    let mut dbd = Dbd::default();
+    let mut section = Section::None;
+    let mut current = DbdBuild::default();
+
+    for (idx, line) in content.lines().enumerate() {
+        let line_number = idx + 1;
+        let error = |message: String| Error::DbdParse {
+            line: line_number,
+            message,
+        };
+        let line = line.trim();
+
+        if line.is_empty() {
+            finish_definition(&mut dbd, &mut current);
+            section = Section::None;
+            continue;
+        }
+
+        if line == "COLUMNS" {
+            finish_definition(&mut dbd, &mut current);
+            section = Section::Columns;
+            continue;
+        }
+
+        let header = if let Some(rest) = line.strip_prefix("LAYOUT ") {
+            Some((rest, true))
+        } else {
+            line.strip_prefix("BUILD ").map(|rest| (rest, false))
+        };
+        if let Some((rest, is_layout)) = header {
+            // A new header after the fields starts another definition even
+            // without an empty line between them
+            if !current.fields.is_empty() {
+                finish_definition(&mut dbd, &mut current);
+            }
+            section = Section::Definition;
+            for item in rest.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+                if is_layout {
+                    current.layouts.push(
+                        u32::from_str_radix(item, 16)
+                            .map_err(|_| error(format!("invalid layout hash {item}")))?,
+                    );
+                } else {
+                    current
+                        .versions
+                        .push(parse_build_spec(item).map_err(error)?);
+                }
+            }
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix("COMMENT ") {
+            if section != Section::Definition {
+                return Err(error("comment outside of a build definition".into()));
+            }
+            current.comments.push(comment.trim().to_string());
+            continue;
+        }
+
+        match section {
+            Section::Columns => {
+                let column = parse_column_line(line).map_err(error)?;
+                dbd.column_order.push(column.name.clone());
+                dbd.columns.insert(column.name.clone(), column);
+            }
+            Section::Definition => {
+                let field = parse_field_line(line).map_err(error)?;
+                if !dbd.columns.contains_key(&field.name) {
+                    return Err(error(format!("column not found: {}", field.name)));
+                }
+                current.fields.push(field);
+            }
+            Section::None => {
+                return Err(error(format!(
+                    "unexpected line outside of a section: {line}"
+                )));
+            }
+        }
+    }
+
+    finish_definition(&mut dbd, &mut current);
+
+    Ok(dbd)
+}
+
+fn finish_definition(dbd: &mut Dbd, current: &mut DbdBuild) {
+    let build = std::mem::take(current);
+    if !build.fields.is_empty() {
+        dbd.builds.push(build);
+    }
+}
+
+fn parse_build_spec(value: &str) -> std::result::Result<GameBuildSpec, String> {
+    let parse = |v: &str| GameBuild::try_from(v).map_err(|err| err.to_string());
+    Ok(match value.split_once('-') {
+        Some((start, end)) => GameBuildSpec::Range((parse(start)?, parse(end)?)),
+        None => GameBuildSpec::Single(parse(value)?),
+    })
+}
+
+/// Split the trailing `//` comment from a line
+fn split_comment(line: &str) -> (&str, Option<String>) {
+    match line.find("//") {
+        Some(pos) => (line[..pos].trim(), Some(line[pos + 2..].trim().to_string())),
+        None => (line.trim(), None),
+    }
+}
+
+fn parse_column_line(line: &str) -> std::result::Result<DbdColumn, String> {
+    let (line, comment) = split_comment(line);
+    let Some((type_part, name)) = line.split_once(char::is_whitespace) else {
+        return Err(format!("invalid column definition: {line}"));
+    };
+
+    let (base_type, foreign_key) = match type_part.split_once('<') {
+        Some((base, fk)) => {
+            let fk = fk
+                .strip_suffix('>')
+                .ok_or_else(|| format!("invalid foreign key: {type_part}"))?;
+            let (table, field) = fk
+                .split_once("::")
+                .ok_or_else(|| format!("invalid foreign key: {type_part}"))?;
+            (
+                base,
+                Some(ForeignKey {
+                    table: table.to_string(),
+                    field: field.to_string(),
+                }),
+            )
+        }
+        None => (type_part, None),
+    };
+
+    if !matches!(base_type, "int" | "float" | "string" | "locstring") {
+        return Err(format!("unknown column type {base_type}"));
+    }
+
+    let name = name.trim();
+    let (name, is_optional) = match name.strip_suffix('?') {
+        Some(name) => (name, true),
+        None => (name, false),
+    };
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(format!("invalid column name: {name}"));
+    }
+
+    Ok(DbdColumn {
+        name: name.to_string(),
+        base_type: base_type.to_string(),
+        foreign_key,
+        comment,
+        is_optional,
+    })
+}
+
+fn parse_field_line(line: &str) -> std::result::Result<DbdField, String> {
+    let (line, comment) = split_comment(line);
+    let mut field = DbdField {
+        name: String::new(),
+        type_size: TypeSize::Unspecified,
+        is_array: false,
+        array_size: None,
+        is_key: false,
+        is_relation: false,
+        is_noninline: false,
+        comment,
+    };
+
+    let mut rest = line.to_string();
+    if let Some(stripped) = line.strip_prefix('$') {
+        let end = stripped
+            .find('$')
+            .ok_or_else(|| format!("unterminated annotation: {line}"))?;
+        for annotation in stripped[..end].split(',').map(str::trim) {
+            match annotation {
+                "id" => field.is_key = true,
+                "relation" => field.is_relation = true,
+                "noninline" => field.is_noninline = true,
+                _ => return Err(format!("unknown annotation {annotation}")),
+            }
+        }
+        rest = stripped[end + 1..].to_string();
+    }
+
+    if let Some(start) = rest.find('[') {
+        let end = rest
+            .find(']')
+            .ok_or_else(|| format!("unterminated array size: {line}"))?;
+        let size = &rest[start + 1..end];
+        field.is_array = true;
+        field.array_size = Some(
+            size.parse()
+                .map_err(|_| format!("invalid array size {size}"))?,
+        );
+        rest = format!("{}{}", &rest[..start], &rest[end + 1..]);
+    }
+
+    if let Some(start) = rest.find('<') {
+        let end = rest
+            .find('>')
+            .ok_or_else(|| format!("unterminated type size: {line}"))?;
+        let size = &rest[start + 1..end];
+        field.type_size = TypeSize::parse_type_size(size);
+        if field.type_size == TypeSize::Unspecified {
+            return Err(format!("invalid type size {size}"));
+        }
+        rest = format!("{}{}", &rest[..start], &rest[end + 1..]);
+    }
+
+    let name = rest.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(format!("invalid field definition: {line}"));
+    }
+    field.name = name.to_string();
+
+    Ok(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DBD: &str = "COLUMNS
+int ID
+locstring Name_lang // display name
+string Path
+int<Map::ID> MapID
+float Pos
+int Unknown?
+
+LAYOUT 1A2B3C4D, 0F0F0F0F
+BUILD 7.3.5.25600-7.3.5.26972
+BUILD 8.0.1.26231
+COMMENT moved id out of the record
+$noninline,id$ID<32>
+Name_lang
+$relation$MapID<u16>
+Pos[3]
+
+BUILD 3.3.5.12340
+BUILD 1.12.1.5875, 2.4.3.8606
+$id$ID<32>
+Name_lang
+Path
+MapID<32> // zone map
+Pos[3]
+Unknown<u8>[2]
+";
+
+    fn build(value: &str) -> GameBuild {
+        value.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_parse_whole_file() {
+        let dbd = parse_dbd(DBD).expect("parse");
+        assert_eq!(
+            dbd.column_order,
+            ["ID", "Name_lang", "Path", "MapID", "Pos", "Unknown"]
+        );
+        assert!(dbd.columns["Name_lang"].is_localized());
+        assert!(!dbd.columns["Path"].is_localized());
+        assert_eq!(
+            dbd.columns["Name_lang"].comment.as_deref(),
+            Some("display name")
+        );
+        assert_eq!(
+            dbd.columns["MapID"].foreign_key.as_ref().unwrap().table,
+            "Map"
+        );
+        assert!(dbd.columns["Unknown"].is_optional);
+        assert_eq!(dbd.builds.len(), 2);
+
+        let legion = &dbd.builds[0];
+        assert_eq!(legion.layouts, [0x1A2B3C4D, 0x0F0F0F0F]);
+        assert_eq!(legion.comments, ["moved id out of the record"]);
+        assert_eq!(legion.versions[0].to_string(), "7.3.5.25600-7.3.5.26972");
+        assert!(legion.fields[0].is_key && legion.fields[0].is_noninline);
+        assert!(legion.fields[2].is_relation);
+        assert_eq!(legion.fields[2].type_size, TypeSize::UInt16);
+        assert_eq!(legion.fields[3].array_size, Some(3));
+
+        let classic = &dbd.builds[1];
+        assert_eq!(classic.versions.len(), 3);
+        assert_eq!(classic.fields[3].comment.as_deref(), Some("zone map"));
+        assert_eq!(classic.fields[5].type_size, TypeSize::UInt8);
+        assert_eq!(classic.fields[5].array_size, Some(2));
+    }
+
+    #[test]
+    fn test_select_layout() {
+        let dbd = parse_dbd(DBD).expect("parse");
+        assert_eq!(
+            dbd.for_build(&build("7.3.5.26000"))
+                .unwrap()
+                .build
+                .fields
+                .len(),
+            4
+        );
+        assert_eq!(
+            dbd.for_build(&build("2.4.3.8606"))
+                .unwrap()
+                .build
+                .fields
+                .len(),
+            6
+        );
+        assert!(matches!(
+            dbd.for_build(&build("2.4.3.8607")),
+            Err(Error::NoFieldsForBuild)
+        ));
+
+        let by_layout = dbd.for_layout(0x0F0F0F0F, &build("9.0.1.1")).unwrap();
+        assert_eq!(by_layout.build.layouts[0], 0x1A2B3C4D);
+        let fallback = dbd.for_layout(0xDEADBEEF, &build("3.3.5.12340")).unwrap();
+        assert!(fallback.build.layouts.is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("COLUMNS\nblob Data\n", 2),
+            ("COLUMNS\nint ID\n\nBUILD 1.x\nID<32>\n", 4),
+            ("COLUMNS\nint ID\n\nBUILD 1.0.0.1\n$key$ID<32>\n", 5),
+            ("COLUMNS\nint ID\n\nBUILD 1.0.0.1\nOther<32>\n", 5),
+            ("COLUMNS\nint ID\n\nBUILD 1.0.0.1\nID<31>\n", 5),
+            ("COLUMNS\nint ID\n\nID<32>\n", 4),
+        ];
+        for (content, expected_line) in cases {
+            match parse_dbd(content) {
+                Err(Error::DbdParse { line, .. }) => assert_eq!(line, expected_line, "{content}"),
+                other => panic!("unexpected result {other:?} for {content}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{Error, Result, WdbFile};
+
+use super::file_map::get_dbd_name;
+use super::{Dbd, DbdFile, GameBuild, parse_dbd};
+
+#[cfg(feature = "embedded-dbd")]
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/embedded_dbd.rs"));
+}
+
+/// Contents of a DBD file found by a [`DbdSource`]
+#[derive(Debug, Clone)]
+pub struct DbdDefinition {
+    /// Name of the table as in the DBD file name, like `AreaTable`
+    pub name: String,
+    pub content: String,
+}
+
+impl DbdDefinition {
+    /// Parse the layout of the definition for the build
+    pub fn parse(&self, game_build: &GameBuild) -> Result<DbdFile> {
+        self.parse_all()?.for_build(game_build)
+    }
+
+    /// Parse the layout used by a file. Files since WDB5 tell it with their
+    /// layout hash, older ones are matched by the build.
+    pub fn parse_for(&self, wdb: &WdbFile, game_build: &GameBuild) -> Result<DbdFile> {
+        let dbd = self.parse_all()?;
+        match wdb.layout_hash {
+            Some(layout_hash) => dbd.for_layout(layout_hash, game_build),
+            None => dbd.for_build(game_build),
+        }
+    }
+
+    /// Parse the layouts of all builds
+    pub fn parse_all(&self) -> Result<Dbd> {
+        parse_dbd(&self.content)
+    }
+}
+
+/// Where DBD definitions are loaded from
+#[derive(Debug, Clone)]
+pub enum DbdSource {
+    /// Directory of `.dbd` files, like the `definitions` folder of a
+    /// WoWDBDefs checkout
+    Directory(PathBuf),
+    /// Definitions embedded at build time from the directory in the
+    /// `WOW_ALCHEMY_DBD_DIR` environment variable
+    #[cfg(feature = "embedded-dbd")]
+    Embedded,
+    /// Download from the WoWDBDefs repository into the user cache directory
+    #[cfg(feature = "download")]
+    Download,
+    /// Try each source in order and use the first one that has the
+    /// definition. When none has it, the error is
+    /// [`DbdNotFound`](Error::DbdNotFound) if a source reported it, so a
+    /// failed download doesn't hide that the offline sources lack the table.
+    Chain(Vec<DbdSource>),
+}
+
+impl DbdSource {
+    /// Find the definition of a table by its file name, with or without the
+    /// extension, like `AreaTable.dbc` or `areatable`
+    pub fn load(&self, file_name: &str) -> Result<DbdDefinition> {
+        match self {
+            DbdSource::Directory(dir) => {
+                let path = find_in_directory(dir, file_name)?;
+                Ok(DbdDefinition {
+                    name: dbd_stem(&path.file_name().unwrap().to_string_lossy()),
+                    content: fs::read_to_string(&path)?,
+                })
+            }
+            #[cfg(feature = "embedded-dbd")]
+            DbdSource::Embedded => {
+                let (name, content) = find_by_name(
+                    embedded::EMBEDDED_DBD.iter().map(|(name, _)| *name),
+                    file_name,
+                )
+                .and_then(|name| {
+                    embedded::EMBEDDED_DBD
+                        .iter()
+                        .find(|(embedded_name, _)| *embedded_name == name)
+                })
+                .ok_or_else(|| Error::DbdNotFound(file_name.to_owned()))?;
+                Ok(DbdDefinition {
+                    name: dbd_stem(name),
+                    content: (*content).to_owned(),
+                })
+            }
+            #[cfg(feature = "download")]
+            DbdSource::Download => {
+                let path = super::download::download_dbd(file_name)?;
+                Ok(DbdDefinition {
+                    name: dbd_stem(&path.file_name().unwrap().to_string_lossy()),
+                    content: fs::read_to_string(&path)?,
+                })
+            }
+            DbdSource::Chain(sources) => {
+                let mut last_err = None;
+                for source in sources {
+                    match source.load(file_name) {
+                        Ok(definition) => return Ok(definition),
+                        Err(err @ Error::DbdNotFound(_)) => last_err = Some(err),
+                        Err(err) => {
+                            log::debug!("Failed to load the definition of {file_name}: {err}");
+                            if !matches!(last_err, Some(Error::DbdNotFound(_))) {
+                                last_err = Some(err);
+                            }
+                        }
+                    }
+                }
+                Err(last_err.unwrap_or_else(|| Error::DbdNotFound(file_name.to_owned())))
+            }
+        }
+    }
+}
+
+fn dbd_stem(file_name: &str) -> String {
+    file_name.trim_end_matches(".dbd").to_owned()
+}
+
+fn table_stem(file_name: &str) -> &str {
+    match file_name.find('.') {
+        Some(idx) => &file_name[..idx],
+        None => file_name,
+    }
+}
+
+/// Pick the DBD file of a table from the known names, falling back to a case
+/// insensitive match for tables missing in the file map
+fn find_by_name<'a>(names: impl Iterator<Item = &'a str>, file_name: &str) -> Option<&'a str> {
+    let mapped = get_dbd_name(file_name).ok();
+    let stem = table_stem(file_name);
+    let mut fallback = None;
+    for name in names {
+        if Some(name) == mapped {
+            return Some(name);
+        }
+        if fallback.is_none() && table_stem(name).eq_ignore_ascii_case(stem) {
+            fallback = Some(name);
+        }
+    }
+    fallback
+}
+
+fn find_in_directory(dir: &Path, file_name: &str) -> Result<PathBuf> {
+    if let Ok(name) = get_dbd_name(file_name) {
+        let path = dir.join(name);
+        if path.is_file() {
+            return Ok(path);
+        }
+    }
+
+    let names = dir
+        .read_dir()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".dbd"))
+        .collect::<Vec<_>>();
+    find_by_name(names.iter().map(|name| name.as_str()), file_name)
+        .map(|name| dir.join(name))
+        .ok_or_else(|| Error::DbdNotFound(file_name.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directory_source() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let content = "COLUMNS\nint ID\n\nBUILD 3.3.5.12340\n$id$ID<32>\n";
+        fs::write(dir.path().join("AreaTable.dbd"), content).unwrap();
+        fs::write(dir.path().join("NotInMap.dbd"), content).unwrap();
+
+        let source = DbdSource::Directory(dir.path().to_owned());
+        let definition = source.load("areatable.dbc").expect("mapped name");
+        assert_eq!(definition.name, "AreaTable");
+        assert_eq!(definition.content, content);
+        let game_build = "3.3.5.12340".try_into().unwrap();
+        assert_eq!(definition.parse(&game_build).unwrap().build.fields.len(), 1);
+
+        assert_eq!(
+            source.load("NOTINMAP.DBC").expect("fallback").name,
+            "NotInMap"
+        );
+        assert!(matches!(
+            source.load("Spell.dbc"),
+            Err(Error::DbdNotFound(_))
+        ));
+
+        let chain = DbdSource::Chain(vec![
+            DbdSource::Directory(dir.path().join("missing")),
+            source,
+        ]);
+        assert_eq!(chain.load("AreaTable").expect("chain").name, "AreaTable");
+
+        // A missing directory fails with an I/O error, the source without
+        // the table decides the error
+        let chain = DbdSource::Chain(vec![
+            DbdSource::Directory(dir.path().to_owned()),
+            DbdSource::Directory(dir.path().join("missing")),
+        ]);
+        assert!(matches!(
+            chain.load("Spell.dbc"),
+            Err(Error::DbdNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::flatten::locale_column_name;
+use crate::{DbcTable, Locale, TableRow, Value};
+
+/// Value of a column that differs between the two tables
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct ValueChange {
+    pub column: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Changed columns of a row that is in both tables
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct RowDiff {
+    pub id: u32,
+    pub changes: Vec<ValueChange>,
+}
+
+/// Differences between two versions of a table, with rows aligned by their
+/// key and columns matched by their DBD name
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct TableDiff {
+    /// Ids only in the new table
+    pub added: Vec<u32>,
+    /// Ids only in the old table
+    pub removed: Vec<u32>,
+    pub changed: Vec<RowDiff>,
+    /// Columns only in the layout of the new table
+    pub added_columns: Vec<String>,
+    /// Columns only in the layout of the old table
+    pub removed_columns: Vec<String>,
+    /// Ids of several rows of the old table, only the first of which is
+    /// compared
+    pub old_duplicates: Vec<u32>,
+    /// Ids of several rows of the new table, only the first of which is
+    /// compared
+    pub new_duplicates: Vec<u32>,
+}
+
+impl TableDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.added_columns.is_empty()
+            && self.removed_columns.is_empty()
+            && self.old_duplicates.is_empty()
+            && self.new_duplicates.is_empty()
+    }
+}
+
+/// Bits a scalar is stored with, zero extended, and their width
+fn raw_bits(value: &Value) -> Option<(u64, u8)> {
+    Some(match *value {
+        Value::Int8(v) => (v as u8 as u64, 8),
+        Value::UInt8(v) => (v as u64, 8),
+        Value::Int16(v) => (v as u16 as u64, 16),
+        Value::UInt16(v) => (v as u64, 16),
+        Value::Int32(v) => (v as u32 as u64, 32),
+        Value::UInt32(v) => (v as u64, 32),
+        Value::Float32(v) => (v.to_bits() as u64, 32),
+        Value::Int64(v) => (v as u64, 64),
+        Value::UInt64(v) => (v, 64),
+        _ => return None,
+    })
+}
+
+/// Ids the table has several rows for
+fn duplicate_ids(table: &DbcTable) -> Vec<u32> {
+    let mut counts = BTreeMap::<u32, usize>::new();
+    for row in table.iter() {
+        *counts.entry(row.id(table.key_index())).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// Compare values of the same column that may be stored with different
+/// types by the two layouts. Scalars of the same width are compared by
+/// their bits, so signedness changes are not reported, and integers of
+/// different widths by their value.
+fn same_value(old: &Value, new: &Value) -> bool {
+    match (old, new) {
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_value(a, b))
+        }
+        (Value::LocString { .. }, Value::LocString { .. }) => old == new,
+        (
+            Value::String(_) | Value::LocString { .. },
+            Value::String(_) | Value::LocString { .. },
+        ) => {
+            old.localized(Locale::EnUS).unwrap_or_default()
+                == new.localized(Locale::EnUS).unwrap_or_default()
+        }
+        _ => match (raw_bits(old), raw_bits(new)) {
+            (Some((a, a_width)), Some((b, b_width))) if a_width == b_width => a == b,
+            _ => match (old.as_i64(), new.as_i64()) {
+                (Some(a), Some(b)) => a == b,
+                _ => old == new,
+            },
+        },
+    }
+}
+
+/// Add the changes of a column, with a change per element of arrays and
+/// per locale of localized strings of the same size, named like the
+/// columns of [`flatten::field_columns`](crate::flatten::field_columns)
+fn push_changes(changes: &mut Vec<ValueChange>, column: &str, old: &Value, new: &Value) {
+    match (old, new) {
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+            for (i, (old, new)) in a.iter().zip(b).enumerate() {
+                push_changes(changes, &format!("{column}_{i}"), old, new);
+            }
+        }
+        (
+            Value::LocString {
+                strings: a,
+                flags: a_flags,
+            },
+            Value::LocString {
+                strings: b,
+                flags: b_flags,
+            },
+        ) if a.len() == b.len() => {
+            for (i, (old, new)) in a.iter().zip(b).enumerate() {
+                push_changes(
+                    changes,
+                    &format!("{column}_{}", locale_column_name(i)),
+                    &Value::String(old.clone()),
+                    &Value::String(new.clone()),
+                );
+            }
+            push_changes(
+                changes,
+                &format!("{column}_flags"),
+                &Value::UInt32(*a_flags),
+                &Value::UInt32(*b_flags),
+            );
+        }
+        _ if same_value(old, new) => {}
+        _ => changes.push(ValueChange {
+            column: column.to_owned(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+    }
+}
+
+/// Differences from `old` to `new`
+pub fn diff_tables(old: &DbcTable, new: &DbcTable) -> TableDiff {
+    let old_fields = &old.dbd().build.fields;
+    let new_fields = &new.dbd().build.fields;
+
+    // Positions of the columns in both layouts
+    let columns = old_fields
+        .iter()
+        .enumerate()
+        .filter_map(|(old_idx, field)| {
+            let new_idx = new.column_index(&field.name)?;
+            Some((field.name.as_str(), old_idx, new_idx))
+        })
+        .collect::<Vec<_>>();
+
+    let old_ids = old.ids().collect::<BTreeSet<_>>();
+    let new_ids = new.ids().collect::<BTreeSet<_>>();
+
+    let changed = old_ids
+        .intersection(&new_ids)
+        .filter_map(|id| {
+            let (old_row, new_row) = (old.get(*id)?, new.get(*id)?);
+            let mut changes = Vec::new();
+            for (name, old_idx, new_idx) in &columns {
+                if let (Some(old), Some(new)) = (old_row.get(*old_idx), new_row.get(*new_idx)) {
+                    push_changes(&mut changes, name, old, new);
+                }
+            }
+            (!changes.is_empty()).then_some(RowDiff { id: *id, changes })
+        })
+        .collect();
+
+    TableDiff {
+        added: new_ids.difference(&old_ids).copied().collect(),
+        removed: old_ids.difference(&new_ids).copied().collect(),
+        changed,
+        added_columns: new_fields
+            .iter()
+            .filter(|field| old.column_index(&field.name).is_none())
+            .map(|field| field.name.clone())
+            .collect(),
+        removed_columns: old_fields
+            .iter()
+            .filter(|field| new.column_index(&field.name).is_none())
+            .map(|field| field.name.clone())
+            .collect(),
+        old_duplicates: duplicate_ids(old),
+        new_duplicates: duplicate_ids(new),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dbd::parse_dbd_content;
+
+    const DBD: &str = "COLUMNS
+int ID
+string Name
+float Speed
+int Flags
+int Extra
+
+BUILD 3.3.5.12340
+$id$ID<32>
+Name
+Speed
+Flags<32>
+
+BUILD 4.3.4.15595
+Extra<u8>
+Name
+$id$ID<u32>
+Flags<u16>
+";
+
+    #[test]
+    fn test_diff_across_layouts() {
+        let old = DbcTable::new(
+            parse_dbd_content(&"3.3.5.12340".try_into().unwrap(), DBD).unwrap(),
+            vec![
+                vec![
+                    Value::Int32(1),
+                    Value::String(Some("Same".into())),
+                    Value::Float32(1.0),
+                    Value::Int32(3),
+                ],
+                vec![
+                    Value::Int32(2),
+                    Value::String(Some("Old".into())),
+                    Value::Float32(1.0),
+                    Value::Int32(3),
+                ],
+                vec![
+                    Value::Int32(3),
+                    Value::String(Some("Gone".into())),
+                    Value::Float32(1.0),
+                    Value::Int32(0),
+                ],
+            ],
+        );
+        let new = DbcTable::new(
+            parse_dbd_content(&"4.3.4.15595".try_into().unwrap(), DBD).unwrap(),
+            vec![
+                vec![
+                    Value::UInt8(9),
+                    Value::String(Some("Same".into())),
+                    Value::UInt32(1),
+                    Value::UInt16(3),
+                ],
+                vec![
+                    Value::UInt8(0),
+                    Value::String(Some("New".into())),
+                    Value::UInt32(2),
+                    Value::UInt16(4),
+                ],
+                vec![
+                    Value::UInt8(0),
+                    Value::String(Some("Added".into())),
+                    Value::UInt32(5),
+                    Value::UInt16(0),
+                ],
+            ],
+        );
+
+        let diff = diff_tables(&old, &new);
+        assert_eq!(diff.added, [5]);
+        assert_eq!(diff.removed, [3]);
+        assert_eq!(diff.added_columns, ["Extra"]);
+        assert_eq!(diff.removed_columns, ["Speed"]);
+        assert_eq!(
+            diff.changed,
+            [RowDiff {
+                id: 2,
+                changes: vec![
+                    ValueChange {
+                        column: "Name".into(),
+                        old: Value::String(Some("Old".into())),
+                        new: Value::String(Some("New".into())),
+                    },
+                    ValueChange {
+                        column: "Flags".into(),
+                        old: Value::Int32(3),
+                        new: Value::UInt16(4),
+                    },
+                ],
+            }]
+        );
+        assert!(diff_tables(&old, &old).is_empty());
+
+        let mut changes = Vec::new();
+        push_changes(
+            &mut changes,
+            "Effect",
+            &Value::Array(vec![Value::Int32(1), Value::Int32(2)]),
+            &Value::Array(vec![Value::Int32(1), Value::Int32(5)]),
+        );
+        let loc_string = |en: &str, flags| Value::LocString {
+            strings: vec![Some(en.into()), Some(String::new())],
+            flags,
+        };
+        push_changes(
+            &mut changes,
+            "Name_lang",
+            &loc_string("a", 1),
+            &loc_string("b", 1),
+        );
+        let columns: Vec<_> = changes.iter().map(|c| c.column.as_str()).collect();
+        assert_eq!(columns, ["Effect_1", "Name_lang_enus"]);
+    }
+
+    #[test]
+    fn test_diff_signedness_and_duplicates() {
+        assert!(same_value(&Value::Int32(-1), &Value::UInt32(u32::MAX)));
+        assert!(same_value(&Value::Int8(-1), &Value::UInt8(u8::MAX)));
+        assert!(same_value(&Value::UInt8(3), &Value::Int32(3)));
+        assert!(!same_value(&Value::Int8(-1), &Value::UInt32(u32::MAX)));
+
+        let dbd = parse_dbd_content(&"3.3.5.12340".try_into().unwrap(), DBD).unwrap();
+        let row = |id, name: &str| {
+            vec![
+                Value::Int32(id),
+                Value::String(Some(name.into())),
+                Value::Float32(1.0),
+                Value::Int32(0),
+            ]
+        };
+        let old = DbcTable::new(dbd.clone(), vec![row(1, "A"), row(2, "B")]);
+        let new = DbcTable::new(dbd, vec![row(1, "A"), row(2, "B"), row(2, "C")]);
+
+        let diff = diff_tables(&old, &new);
+        assert!(diff.old_duplicates.is_empty());
+        assert_eq!(diff.new_duplicates, [2]);
+        assert!(diff.changed.is_empty());
+        assert!(!diff.is_empty());
+    }
+}
@@ -7,6 +7,7 @@ pub enum Error {
     #[error("I/O error: {0}")]
     Io(#[from] io::Error),
 
+    #[cfg(feature = "download")]
     #[error("Request error: {0}")]
     Reqwest(#[from] reqwest::Error),
 
@@ -14,11 +15,22 @@ pub enum Error {
     #[error("Rusqlite error: {0}")]
     Rusqlite(#[from] rusqlite::Error),
 
+    #[cfg(feature = "csv")]
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[cfg(feature = "json")]
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
     #[error("WowData error: {0}")]
     WowData(#[from] wow_alchemy_data::error::WowDataError),
 
-    #[error("GameBuild error: {0}")]
-    GameBuild(String),
+    #[error("DBD parse error at line {line}: {message}")]
+    DbdParse { line: usize, message: String },
+
+    #[error("No dbd definition found for {0}")]
+    DbdNotFound(String),
 
     #[error("No dbd field definitions were found for the specified build")]
     NoFieldsForBuild,
@@ -26,6 +38,24 @@ pub enum Error {
     #[error("Error generating SQLite table definition: {0}")]
     SqliteTableDefinition(String),
 
+    #[error("Writing {0:?} files is not supported")]
+    UnsupportedVersion(crate::DbcVersion),
+
+    #[error("Invalid record data: {0}")]
+    InvalidRecord(String),
+
+    #[error("Invalid value for field {0}: {1}")]
+    InvalidValue(String, String),
+
+    #[error("A record with id {0} already exists")]
+    DuplicateKey(u32),
+
+    #[error("No record with id {0}")]
+    KeyNotFound(u32),
+
+    #[error("Table {0} is not loaded")]
+    TableNotLoaded(String),
+
     #[error("Generic error: {0}")]
     GenericError(String),
 }
@@ -1,6 +1,150 @@
+use std::io::Cursor;
+
 use wow_alchemy_data::prelude::*;
 
-use crate::{FieldType, Result, Value, WdbFile};
+use crate::db2::{FieldStructure, read_le};
+use crate::dbd::DbdFile;
+use crate::{Error, FieldType, Record, RecordInfo, RecordLayout, Result, Value, WdbFile};
+
+/// Decode the fields of a record from its data
+pub fn parse_record(
+    wdb: &WdbFile,
+    dbd_file: &DbdFile,
+    record: &RecordInfo,
+    data: &[u8],
+) -> Result<Record> {
+    let layout = wdb.record_layout();
+    let mut values = Vec::with_capacity(dbd_file.build.fields.len());
+    let mut cursor = Cursor::new(data);
+    // Index of the field among the fields stored in the record
+    let mut column = 0;
+    let data_id = match (layout, record.source_id.or(record.id)) {
+        (_, Some(id)) => id,
+        (RecordLayout::Packed, None) => wdb.inline_id(data)?,
+        _ => 0,
+    };
+
+    for field in &dbd_file.build.fields {
+        let field_type = dbd_file.field_type(field)?;
+        let count = if field.is_array {
+            field.array_size.unwrap_or(0)
+        } else {
+            1
+        };
+
+        if field.is_noninline {
+            let raw = if field.is_relation {
+                wdb.relationships
+                    .get(&record.index)
+                    .copied()
+                    .unwrap_or_default()
+            } else {
+                record.id.ok_or_else(|| {
+                    Error::InvalidRecord(format!("no id for non-inline field {}", field.name))
+                })?
+            };
+            values.push(int_value(field_type, raw as u64)?);
+            continue;
+        }
+
+        let mut elements = Vec::with_capacity(count);
+        for element in 0..count {
+            let value = match layout {
+                RecordLayout::Plain => parse_field_value(&mut cursor, wdb, field_type)?,
+                RecordLayout::Sparse => {
+                    if field_type == FieldType::String {
+                        Value::String(Some(read_inline_string(&mut cursor)?))
+                    } else {
+                        let size = wdb
+                            .field_structures
+                            .get(column)
+                            .map(FieldStructure::byte_size)
+                            .transpose()?
+                            .unwrap_or(field_type.size());
+                        let pos = cursor.position() as usize;
+                        cursor.set_position((pos + size) as u64);
+                        int_value(field_type, read_le(data, pos, size)?)?
+                    }
+                }
+                RecordLayout::Structured => {
+                    let structure = wdb.field_structures.get(column).ok_or_else(|| {
+                        Error::InvalidRecord(format!("no structure for field {}", field.name))
+                    })?;
+                    let size = structure.byte_size()?;
+                    let offset = structure.position as usize + element * size;
+                    let raw = read_le(data, offset, size)?;
+                    match field_type {
+                        FieldType::String => Value::String(
+                            wdb.get_record_string(record, offset, raw as u32)
+                                .map(str::to_owned),
+                        ),
+                        _ => int_value(field_type, raw)?,
+                    }
+                }
+                RecordLayout::Packed => {
+                    let raw = wdb.packed_value(column, data, element, count, data_id)?;
+                    match field_type {
+                        FieldType::String => {
+                            let info = &wdb.storage_info[column];
+                            let offset = info.offset_bits as usize / 8
+                                + element * (info.size_bits as usize / 8 / count.max(1));
+                            Value::String(
+                                wdb.get_record_string(record, offset, raw as u32)
+                                    .map(str::to_owned),
+                            )
+                        }
+                        _ => int_value(field_type, raw)?,
+                    }
+                }
+            };
+            elements.push(value);
+        }
+        column += 1;
+
+        let value = match (field.is_array, record.id) {
+            (true, _) => Value::Array(elements),
+            // Rows of the copy table keep the data of the copied row but have
+            // their own id
+            (false, Some(id)) if field.is_key => int_value(field_type, id as u64)?,
+            (false, _) => elements.remove(0),
+        };
+        values.push(value);
+    }
+
+    Ok(values)
+}
+
+/// Value of an integer or float field from its raw bits
+fn int_value(field_type: FieldType, raw: u64) -> Result<Value> {
+    Ok(match field_type {
+        FieldType::Int32 => Value::Int32(raw as i32),
+        FieldType::UInt32 => Value::UInt32(raw as u32),
+        FieldType::Float32 => Value::Float32(f32::from_bits(raw as u32)),
+        FieldType::Bool => Value::Bool(raw != 0),
+        FieldType::UInt8 => Value::UInt8(raw as u8),
+        FieldType::Int8 => Value::Int8(raw as i8),
+        FieldType::UInt16 => Value::UInt16(raw as u16),
+        FieldType::Int16 => Value::Int16(raw as i16),
+        FieldType::Int64 => Value::Int64(raw as i64),
+        FieldType::UInt64 => Value::UInt64(raw),
+        FieldType::String | FieldType::LocString(_) => {
+            return Err(Error::InvalidRecord(format!(
+                "{field_type:?} cannot be read from an integer"
+            )));
+        }
+    })
+}
+
+fn read_inline_string(cursor: &mut Cursor<&[u8]>) -> Result<String> {
+    let data = *cursor.get_ref();
+    let start = cursor.position() as usize;
+    let len = data
+        .get(start..)
+        .and_then(|rest| rest.iter().position(|b| *b == 0))
+        .ok_or_else(|| Error::InvalidRecord("unterminated inline string".into()))?;
+    cursor.set_position((start + len + 1) as u64);
+    Ok(String::from_utf8_lossy(&data[start..start + len]).into())
+}
 
 pub fn parse_field_value<R: Read + Seek>(
     reader: &mut R,
@@ -14,7 +158,7 @@ pub fn parse_field_value<R: Read + Seek>(
         FieldType::String => {
             let index: u32 = reader.wow_read()?;
             Ok(Value::String(
-                wdb.get_string_by_offset(index as usize).cloned(),
+                wdb.get_string_by_offset(index as usize).map(str::to_owned),
             ))
         }
         FieldType::Bool => {
@@ -27,5 +171,16 @@ pub fn parse_field_value<R: Read + Seek>(
         FieldType::Int16 => Ok(Value::Int16(reader.wow_read()?)),
         FieldType::Int64 => Ok(Value::Int64(reader.wow_read()?)),
         FieldType::UInt64 => Ok(Value::UInt64(reader.wow_read()?)),
+        FieldType::LocString(locales) => {
+            let mut strings = Vec::with_capacity(locales);
+            for _ in 0..locales {
+                let index: u32 = reader.wow_read()?;
+                strings.push(wdb.get_string_by_offset(index as usize).map(str::to_owned));
+            }
+            Ok(Value::LocString {
+                strings,
+                flags: reader.wow_read()?,
+            })
+        }
     }
 }
@@ -0,0 +1,298 @@
+//! Records as flat rows of scalar values, one column per field, array
+//! element and locale, as stored in SQLite, CSV and JSON exports
+
+use crate::dbd::{DbdField, DbdFile};
+use crate::{Error, FieldType, Locale, Record, Result, Value};
+
+/// How `locstring` fields of files before Cataclysm are flattened
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LocStringColumns {
+    /// One column for each locale, named like `Name_lang_enus`, and one for
+    /// the flags, named like `Name_lang_flags`
+    #[default]
+    PerLocale,
+    /// A single column with the string of the locale. Other locales and
+    /// the flags are left empty when converting back to DBC.
+    Locale(Locale),
+}
+
+pub(crate) fn locale_column_name(index: usize) -> String {
+    match Locale::from_index(index) {
+        Some(locale) => locale.name().to_lowercase(),
+        None => format!("unk{index}"),
+    }
+}
+
+/// Columns of a DBD field with the type of their values, in the order of
+/// [`flatten_values`]. Arrays are expanded as `Field_0..N`.
+pub fn field_columns(
+    dbd: &DbdFile,
+    field: &DbdField,
+    locstrings: LocStringColumns,
+) -> Result<Vec<(String, FieldType)>> {
+    let names = match field.array_size {
+        Some(array_size) if field.is_array => (0..array_size)
+            .map(|i| format!("{}_{}", field.name, i))
+            .collect(),
+        _ => vec![field.name.clone()],
+    };
+
+    Ok(match (dbd.field_type(field)?, locstrings) {
+        (FieldType::LocString(locales), LocStringColumns::PerLocale) => names
+            .into_iter()
+            .flat_map(|name| {
+                (0..locales)
+                    .map(|i| {
+                        (
+                            format!("{}_{}", name, locale_column_name(i)),
+                            FieldType::String,
+                        )
+                    })
+                    .chain([(format!("{}_flags", name), FieldType::UInt32)])
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+        (FieldType::LocString(_), LocStringColumns::Locale(_)) => names
+            .into_iter()
+            .map(|name| (name, FieldType::String))
+            .collect(),
+        (field_type, _) => names.into_iter().map(|name| (name, field_type)).collect(),
+    })
+}
+
+/// Columns of all fields of the layout
+pub fn table_columns(
+    dbd: &DbdFile,
+    locstrings: LocStringColumns,
+) -> Result<Vec<(String, FieldType)>> {
+    let mut columns = Vec::new();
+    for field in &dbd.build.fields {
+        columns.extend(field_columns(dbd, field, locstrings)?);
+    }
+    Ok(columns)
+}
+
+fn push_value(values: &mut Vec<Value>, value: &Value, locstrings: LocStringColumns) -> Result<()> {
+    match (value, locstrings) {
+        (Value::Array(_), _) => {
+            return Err(Error::InvalidRecord(
+                "arrays of arrays can't be flattened".to_owned(),
+            ));
+        }
+        (Value::LocString { strings, flags }, LocStringColumns::PerLocale) => {
+            for string in strings {
+                values.push(Value::String(string.clone()));
+            }
+            values.push(Value::UInt32(*flags));
+        }
+        (Value::LocString { strings, .. }, LocStringColumns::Locale(locale)) => {
+            values.push(Value::String(
+                strings.get(locale.index()).cloned().flatten(),
+            ));
+        }
+        _ => values.push(value.clone()),
+    }
+    Ok(())
+}
+
+/// Scalar values of a record, one per column of [`table_columns`]
+pub fn flatten_values(item: &[Value], locstrings: LocStringColumns) -> Result<Vec<Value>> {
+    let mut values = Vec::new();
+    for ii in item {
+        match ii {
+            Value::Array(array) => {
+                for j in array {
+                    push_value(&mut values, j, locstrings)?;
+                }
+            }
+            _ => push_value(&mut values, ii, locstrings)?,
+        }
+    }
+    Ok(values)
+}
+
+fn unflatten_value(
+    cells: &mut impl Iterator<Item = Value>,
+    field: &DbdField,
+    field_type: FieldType,
+    locstrings: LocStringColumns,
+) -> Result<Value> {
+    let mut next = || {
+        cells
+            .next()
+            .ok_or_else(|| Error::InvalidRecord(format!("missing value for {}", field.name)))
+    };
+    let mut next_string = || match next()? {
+        Value::String(string) => Ok(string),
+        value => Err(Error::InvalidValue(
+            field.name.clone(),
+            format!("expected a string, got {value}"),
+        )),
+    };
+
+    Ok(match (field_type, locstrings) {
+        (FieldType::LocString(locales), LocStringColumns::PerLocale) => {
+            let strings = (0..locales)
+                .map(|_| next_string())
+                .collect::<Result<Vec<_>>>()?;
+            let flags = next()?;
+            let flags = flags
+                .as_i64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| {
+                    Error::InvalidValue(field.name.clone(), format!("invalid flags {flags}"))
+                })?;
+            Value::LocString { strings, flags }
+        }
+        (FieldType::LocString(locales), LocStringColumns::Locale(locale)) => {
+            let mut strings = vec![None; locales];
+            let string = next_string()?;
+            if let Some(slot) = strings.get_mut(locale.index()) {
+                *slot = string;
+            }
+            Value::LocString { strings, flags: 0 }
+        }
+        _ => next()?,
+    })
+}
+
+/// Fold the scalar values of [`flatten_values`] back into a record, with
+/// [`Value::Array`] and [`Value::LocString`] values
+pub fn unflatten_values(
+    dbd: &DbdFile,
+    cells: impl IntoIterator<Item = Value>,
+    locstrings: LocStringColumns,
+) -> Result<Record> {
+    let mut cells = cells.into_iter();
+    let mut record = Vec::with_capacity(dbd.build.fields.len());
+    for field in &dbd.build.fields {
+        let field_type = dbd.field_type(field)?;
+        match field.array_size {
+            Some(array_size) if field.is_array => {
+                let values = (0..array_size)
+                    .map(|_| unflatten_value(&mut cells, field, field_type, locstrings))
+                    .collect::<Result<Vec<_>>>()?;
+                record.push(Value::Array(values));
+            }
+            _ => record.push(unflatten_value(&mut cells, field, field_type, locstrings)?),
+        }
+    }
+    Ok(record)
+}
+
+/// Text of a float that parses back to the same bits with [`parse_value`].
+/// NaN and infinite values are written as their bits, like `0x7fc00000`.
+pub fn format_float(value: f32) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        format!("{:#010x}", value.to_bits())
+    }
+}
+
+/// Text of a scalar value, strings are not quoted
+pub fn format_value(value: &Value) -> String {
+    match value {
+        Value::Float32(v) => format_float(*v),
+        Value::String(v) => v.clone().unwrap_or_default(),
+        Value::Bool(v) => (*v as u8).to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Parse the text of a scalar value of a column
+pub fn parse_value(text: &str, field_type: FieldType, column: &str) -> Result<Value> {
+    let invalid = || {
+        Error::InvalidValue(
+            column.to_owned(),
+            format!("can't parse {text:?} as {field_type:?}"),
+        )
+    };
+    let int = || text.trim().parse::<i128>().map_err(|_| invalid());
+    let out_of_range = |_| {
+        Error::InvalidValue(
+            column.to_owned(),
+            format!("{text:?} is out of range for {field_type:?}"),
+        )
+    };
+
+    Ok(match field_type {
+        FieldType::String | FieldType::LocString(_) => Value::String(Some(text.to_owned())),
+        FieldType::Float32 => {
+            let text = text.trim();
+            match text.strip_prefix("0x") {
+                Some(bits) => Value::Float32(f32::from_bits(
+                    u32::from_str_radix(bits, 16).map_err(|_| invalid())?,
+                )),
+                None => Value::Float32(text.parse().map_err(|_| invalid())?),
+            }
+        }
+        FieldType::Int8 => Value::Int8(i8::try_from(int()?).map_err(out_of_range)?),
+        FieldType::UInt8 => Value::UInt8(u8::try_from(int()?).map_err(out_of_range)?),
+        FieldType::Int16 => Value::Int16(i16::try_from(int()?).map_err(out_of_range)?),
+        FieldType::UInt16 => Value::UInt16(u16::try_from(int()?).map_err(out_of_range)?),
+        FieldType::Int32 => Value::Int32(i32::try_from(int()?).map_err(out_of_range)?),
+        FieldType::UInt32 => Value::UInt32(u32::try_from(int()?).map_err(out_of_range)?),
+        FieldType::Int64 => Value::Int64(i64::try_from(int()?).map_err(out_of_range)?),
+        FieldType::UInt64 => Value::UInt64(u64::try_from(int()?).map_err(out_of_range)?),
+        FieldType::Bool => Value::Bool(int()? != 0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_float_text_roundtrip() {
+        for value in [
+            0.1f32,
+            -0.0,
+            1.0e-40,
+            f32::MAX,
+            f32::MIN_POSITIVE,
+            f32::INFINITY,
+            f32::from_bits(0x7fc0_0001),
+        ] {
+            let parsed = parse_value(&format_float(value), FieldType::Float32, "f").unwrap();
+            let Value::Float32(parsed) = parsed else {
+                panic!("not a float")
+            };
+            assert_eq!(parsed.to_bits(), value.to_bits());
+        }
+        assert_eq!(format_float(0.1), "0.1");
+        assert_eq!(format_float(f32::NAN), "0x7fc00000");
+    }
+
+    #[test]
+    fn test_parse_int_range() {
+        assert_eq!(
+            parse_value("255", FieldType::UInt8, "u").unwrap(),
+            Value::UInt8(255)
+        );
+        assert_eq!(
+            parse_value("-1", FieldType::Int32, "i").unwrap(),
+            Value::Int32(-1)
+        );
+        for (text, field_type) in [
+            ("300", FieldType::UInt8),
+            ("-1", FieldType::UInt32),
+            ("2147483648", FieldType::Int32),
+            ("18446744073709551616", FieldType::UInt64),
+        ] {
+            assert!(
+                matches!(
+                    parse_value(text, field_type, "c"),
+                    Err(Error::InvalidValue(..))
+                ),
+                "{text} as {field_type:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_flatten_nested_array() {
+        let record = vec![Value::Array(vec![Value::Array(vec![Value::UInt8(1)])])];
+        assert!(flatten_values(&record, LocStringColumns::PerLocale).is_err());
+    }
+}
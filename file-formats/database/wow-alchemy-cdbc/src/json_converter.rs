@@ -0,0 +1,159 @@
+use std::io::{Read, Write};
+
+use serde::Serialize;
+use serde::ser::{Error as _, SerializeMap, SerializeSeq, Serializer};
+
+use crate::dbd::DbdFile;
+use crate::flatten::{
+    LocStringColumns, flatten_values, parse_value, table_columns, unflatten_values,
+};
+use crate::{Error, FieldType, Record, Result, Value};
+
+/// Rows as objects with the keys in the order of the columns
+struct JsonRows<'a> {
+    columns: &'a [(String, FieldType)],
+    records: &'a [Record],
+    locstrings: LocStringColumns,
+}
+
+impl Serialize for JsonRows<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.records.len()))?;
+        for record in self.records {
+            let values = flatten_values(record, self.locstrings).map_err(S::Error::custom)?;
+            let row = JsonRow {
+                columns: self.columns,
+                values: &values,
+            };
+            seq.serialize_element(&row)?;
+        }
+        seq.end()
+    }
+}
+
+struct JsonRow<'a> {
+    columns: &'a [(String, FieldType)],
+    values: &'a [Value],
+}
+
+impl Serialize for JsonRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for ((name, _), value) in self.columns.iter().zip(self.values) {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Write records as a JSON array of objects keyed by the DBD field names,
+/// one key per array element and locale
+pub fn write_json<W: Write>(
+    mut writer: W,
+    dbd: &DbdFile,
+    records: &[Record],
+    locstrings: LocStringColumns,
+) -> Result<()> {
+    let columns = table_columns(dbd, locstrings)?;
+    let rows = JsonRows {
+        columns: &columns,
+        records,
+        locstrings,
+    };
+    serde_json::to_writer_pretty(&mut writer, &rows)?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn json_to_value(value: &serde_json::Value, field_type: FieldType, column: &str) -> Result<Value> {
+    match (value, field_type) {
+        (serde_json::Value::Null, FieldType::String) => Ok(Value::String(None)),
+        (serde_json::Value::String(text), _) => parse_value(text, field_type, column),
+        (serde_json::Value::Number(number), _) if field_type != FieldType::String => {
+            parse_value(&number.to_string(), field_type, column)
+        }
+        _ => Err(Error::InvalidValue(
+            column.to_owned(),
+            format!("unexpected JSON value {value} for {field_type:?}"),
+        )),
+    }
+}
+
+/// Read records written by [`write_json`]
+pub fn read_json<R: Read>(
+    reader: R,
+    dbd: &DbdFile,
+    locstrings: LocStringColumns,
+) -> Result<Vec<Record>> {
+    let columns = table_columns(dbd, locstrings)?;
+    let rows: Vec<serde_json::Map<String, serde_json::Value>> = serde_json::from_reader(reader)?;
+
+    let mut records = Vec::with_capacity(rows.len());
+    for row in rows {
+        let cells = columns
+            .iter()
+            .map(|(name, field_type)| {
+                let value = row
+                    .get(name)
+                    .ok_or_else(|| Error::InvalidRecord(format!("missing column {name}")))?;
+                json_to_value(value, *field_type, name)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        records.push(unflatten_values(dbd, cells, locstrings)?);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Locale;
+    use crate::dbd::parse_dbd_content;
+
+    const DBD: &str = "COLUMNS
+int ID
+locstring Name_lang
+float Scale
+int Big
+
+BUILD 3.3.5.12340
+$id$ID<32>
+Name_lang
+Scale[2]
+Big<u64>
+";
+
+    #[test]
+    fn test_json_roundtrip() {
+        let dbd = parse_dbd_content(&"3.3.5.12340".try_into().unwrap(), DBD).unwrap();
+        let mut strings = vec![Some(String::new()); 16];
+        strings[Locale::DeDE.index()] = Some("Zauber \"\\\"".into());
+        let records = vec![vec![
+            Value::Int32(7),
+            Value::LocString { strings, flags: 3 },
+            Value::Array(vec![
+                Value::Float32(0.3),
+                Value::Float32(f32::from_bits(0x7fc0_0001)),
+            ]),
+            Value::UInt64(u64::MAX),
+        ]];
+
+        let mut bytes = Vec::new();
+        write_json(&mut bytes, &dbd, &records, LocStringColumns::PerLocale).unwrap();
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        assert!(text.contains("\"Scale_0\": 0.3,"));
+        assert!(text.contains("\"Scale_1\": \"0x7fc00001\","));
+        assert!(text.find("\"ID\"") < text.find("\"Name_lang_enus\""));
+
+        let read = read_json(bytes.as_slice(), &dbd, LocStringColumns::PerLocale).unwrap();
+        let Value::Array(scales) = &read[0][2] else {
+            panic!("not an array")
+        };
+        assert_eq!(scales[0], Value::Float32(0.3));
+        assert!(matches!(scales[1], Value::Float32(v) if v.to_bits() == 0x7fc0_0001));
+        assert_eq!(read[0][0], records[0][0]);
+        assert_eq!(read[0][1], records[0][1]);
+        assert_eq!(read[0][3], records[0][3]);
+    }
+}
@@ -2,31 +2,21 @@ use std::cmp;
 use std::io::SeekFrom;
 use wow_alchemy_data::prelude::*;
 
-use crate::dbd::{DbdColumn, TypeSize};
-use crate::{Error, FieldType, Record, WdbFile};
-use crate::{Result, Value, dbd::DbdFile};
+use crate::{Record, WdbFile};
+use crate::{Result, dbd::DbdFile};
 
 pub struct LazyRecordIterator<'a, R: Read + Seek> {
     reader: &'a mut R,
     dbd_file: &'a DbdFile,
     wdb: &'a WdbFile,
-    current_index: u32,
-    start_offset: u32,
-    count: u32,
+    current_index: usize,
+    end_index: usize,
+    data: Vec<u8>,
 }
 
 impl<'a, R: Read + Seek> LazyRecordIterator<'a, R> {
     pub fn new(reader: &'a mut R, dbd_file: &'a DbdFile, wdb: &'a WdbFile) -> Result<Self> {
-        reader.seek(SeekFrom::Start(wdb.records_start_offset()))?;
-
-        Ok(Self {
-            reader,
-            count: wdb.header.record_count,
-            dbd_file,
-            wdb,
-            start_offset: wdb.records_start_offset() as u32,
-            current_index: 0,
-        })
+        Self::new_from_start(reader, dbd_file, wdb, 0, wdb.records.len())
     }
 
     pub fn new_from_start(
@@ -36,17 +26,14 @@ impl<'a, R: Read + Seek> LazyRecordIterator<'a, R> {
         start: usize,
         count: usize,
     ) -> Result<Self> {
+        let current_index = cmp::min(start, wdb.records.len());
         Ok(Self {
             reader,
-            start_offset: wdb.records_start_offset() as u32
-                + (start as u32 * wdb.header.record_size),
-            count: cmp::min(
-                count as u32,
-                wdb.header.record_count.saturating_sub(start as u32),
-            ),
             dbd_file,
             wdb,
-            current_index: 0,
+            current_index,
+            end_index: cmp::min(current_index.saturating_add(count), wdb.records.len()),
+            data: Vec::new(),
         })
     }
 }
@@ -55,17 +42,11 @@ impl<R: Read + Seek> Iterator for LazyRecordIterator<'_, R> {
     type Item = Result<Record>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current_index >= self.count {
+        if self.current_index >= self.end_index {
             return None;
         }
 
-        if let Err(err) = self.reader.seek(SeekFrom::Start(
-            (self.start_offset + (self.current_index * self.wdb.header.record_size)) as u64,
-        )) {
-            return Some(Err(err.into()));
-        }
-
-        let record = self.parse_record();
+        let record = self.parse_record(self.current_index);
 
         self.current_index += 1;
         Some(record)
@@ -73,62 +54,18 @@ impl<R: Read + Seek> Iterator for LazyRecordIterator<'_, R> {
 }
 
 impl<R: Read + Seek> LazyRecordIterator<'_, R> {
-    fn parse_record(&mut self) -> Result<Record> {
-        let mut values = Vec::with_capacity(self.dbd_file.build.fields.len());
-
-        for field in &self.dbd_file.build.fields {
-            let value = if field.is_array {
-                let array_size = field.array_size.unwrap_or(0);
-                let mut array_values = Vec::with_capacity(array_size);
-
-                for _ in 0..array_size {
-                    array_values.push(self.parse_field_value(
-                        &self.dbd_file.columns[&field.name],
-                        &field.type_size,
-                    )?);
-                }
-
-                Value::Array(array_values)
-            } else {
-                self.parse_field_value(&self.dbd_file.columns[&field.name], &field.type_size)?
-            };
-
-            values.push(value);
-        }
+    fn parse_record(&mut self, index: usize) -> Result<Record> {
+        let record = &self.wdb.records[index];
+        self.reader.seek(SeekFrom::Start(record.offset))?;
+        self.data.resize(record.size as usize, 0);
+        self.reader.read_exact(&mut self.data)?;
 
-        Ok(values)
-    }
-
-    fn parse_field_value(&mut self, column: &DbdColumn, type_size: &TypeSize) -> Result<Value> {
-        crate::field_parser::parse_field_value(
-            &mut self.reader,
-            self.wdb,
-            if column.base_type == "string" || column.base_type == "locstring" {
-                FieldType::String
-            } else if column.base_type == "float" {
-                FieldType::Float32
-            } else {
-                match type_size {
-                    TypeSize::Unspecified => {
-                        return Err(Error::GenericError(format!(
-                            "got unspecified field for col: {:?}",
-                            column
-                        )));
-                    }
-                    TypeSize::Int8 => FieldType::Int8,
-                    TypeSize::UInt8 => FieldType::UInt8,
-                    TypeSize::Int16 => FieldType::Int16,
-                    TypeSize::UInt16 => FieldType::UInt16,
-                    TypeSize::Int32 => FieldType::Int32,
-                    TypeSize::UInt32 => FieldType::UInt32,
-                    TypeSize::Int64 => FieldType::Int64,
-                    TypeSize::UInt64 => FieldType::UInt64,
-                }
-            },
-        )
+        crate::field_parser::parse_record(self.wdb, self.dbd_file, record, &self.data)
     }
 }
 
+#[cfg(feature = "parallel")]
+use crate::Value;
 #[cfg(feature = "parallel")]
 use std::path::Path;
 
@@ -140,7 +77,7 @@ pub fn process_parallel(
 ) -> Vec<Vec<Result<Vec<Value>>>> {
     use std::fs::File;
     let chunks: usize = std::thread::available_parallelism().unwrap().into();
-    let record_count = wdb.header.record_count as usize;
+    let record_count = wdb.records.len();
     let chunk_size = (record_count + (chunks - (record_count % chunks))) / chunks;
 
     let mut results_chunks = Vec::with_capacity(chunks);
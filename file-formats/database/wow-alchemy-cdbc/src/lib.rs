@@ -1,17 +1,38 @@
+// Lets the code generated by `#[derive(DbcRecord)]` refer to this crate by
+// name from inside it
+extern crate self as wow_alchemy_cdbc;
+
+mod database;
 mod error;
 mod field_parser;
 mod lazy;
+mod record;
+mod table;
 mod types;
 mod wdb;
+mod writer;
 
+pub mod db2;
 pub mod dbd;
+pub mod diff;
+pub mod flatten;
 
+#[cfg(feature = "csv")]
+pub mod csv_converter;
+#[cfg(feature = "json")]
+pub mod json_converter;
 #[cfg(feature = "sqlite")]
 pub mod sqlite_converter;
 
+pub use database::DbcDatabase;
+pub use db2::{FieldCompression, FieldStorageInfo, FieldStructure, RecordInfo, RecordLayout};
 pub use error::Error;
 pub use lazy::LazyRecordIterator;
+pub use record::{DbcRecord, DbcValue, LocString};
+pub use table::{DbcTable, TableRow};
 pub use types::*;
 pub use wdb::{DbcVersion, WdbFile};
+pub use wow_alchemy_data_derive::DbcRecord;
+pub use writer::{StringBlock, WdbWriter};
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,356 @@
+use crate::dbd::{DbdBuild, DbdColumn, DbdField, DbdFile, GameBuild, GameBuildSpec, TypeSize};
+use crate::{Error, Locale, Record, Result, Value};
+
+/// Rust type of a field of a [`DbcRecord`]
+pub trait DbcValue: Sized {
+    /// Base type of the DBD column: `int`, `float`, `string` or `locstring`
+    const BASE_TYPE: &'static str;
+    /// Size of integer fields
+    const TYPE_SIZE: TypeSize = TypeSize::Unspecified;
+    /// Length of fixed size arrays
+    const ARRAY_SIZE: Option<usize> = None;
+
+    /// Convert a value, `None` when it has another type or doesn't fit
+    fn from_value(value: &Value) -> Option<Self>;
+
+    fn to_value(&self) -> Value;
+
+    /// Convert the value at `index` of a record, with the field name in the
+    /// error when it has another type
+    fn from_field(record: &[Value], index: usize, name: &str) -> Result<Self> {
+        let value = record.get(index).ok_or_else(|| {
+            Error::InvalidValue(name.to_owned(), "missing from the record".into())
+        })?;
+        Self::from_value(value).ok_or_else(|| {
+            Error::InvalidValue(
+                name.to_owned(),
+                format!("{value} can't be read as {}", std::any::type_name::<Self>()),
+            )
+        })
+    }
+}
+
+macro_rules! impl_int_value {
+    ($($ty:ty => $size:ident, $variant:ident);* $(;)?) => {
+        $(
+            impl DbcValue for $ty {
+                const BASE_TYPE: &'static str = "int";
+                const TYPE_SIZE: TypeSize = TypeSize::$size;
+
+                fn from_value(value: &Value) -> Option<Self> {
+                    match *value {
+                        Value::UInt64(v) => <$ty>::try_from(v).ok(),
+                        _ => <$ty>::try_from(value.as_i64()?).ok(),
+                    }
+                }
+
+                fn to_value(&self) -> Value {
+                    Value::$variant(*self)
+                }
+            }
+        )*
+    };
+}
+
+impl_int_value! {
+    i8 => Int8, Int8;
+    u8 => UInt8, UInt8;
+    i16 => Int16, Int16;
+    u16 => UInt16, UInt16;
+    i32 => Int32, Int32;
+    u32 => UInt32, UInt32;
+    i64 => Int64, Int64;
+    u64 => UInt64, UInt64;
+}
+
+impl DbcValue for bool {
+    const BASE_TYPE: &'static str = "int";
+    const TYPE_SIZE: TypeSize = TypeSize::UInt32;
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_i64().map(|v| v != 0)
+    }
+
+    fn to_value(&self) -> Value {
+        Value::UInt32(*self as u32)
+    }
+}
+
+impl DbcValue for f32 {
+    const BASE_TYPE: &'static str = "float";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Float32(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Float32(*self)
+    }
+}
+
+impl DbcValue for String {
+    const BASE_TYPE: &'static str = "string";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(v) => Some(v.clone().unwrap_or_default()),
+            Value::LocString { .. } => Some(value.localized(Locale::EnUS)?.to_owned()),
+            _ => None,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(Some(self.clone()))
+    }
+}
+
+impl DbcValue for Option<String> {
+    const BASE_TYPE: &'static str = "string";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(v) => Some(v.clone()),
+            Value::LocString { .. } => Some(value.localized(Locale::EnUS).map(str::to_owned)),
+            _ => None,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.clone())
+    }
+}
+
+/// Strings of a `locstring` field for all locales, with the flags mask.
+/// Files from Cataclysm on have a single string.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocString {
+    pub strings: Vec<Option<String>>,
+    pub flags: u32,
+}
+
+impl LocString {
+    /// Text of the preferred locale, see [`Value::localized`]
+    pub fn localized(&self, locale: Locale) -> Option<&str> {
+        match self.strings.get(locale.index()).and_then(|v| v.as_deref()) {
+            Some(v) if !v.is_empty() => Some(v),
+            _ => self
+                .strings
+                .iter()
+                .flatten()
+                .map(|v| v.as_str())
+                .find(|v| !v.is_empty())
+                .or_else(|| self.strings.first().and_then(|v| v.as_deref())),
+        }
+    }
+}
+
+impl DbcValue for LocString {
+    const BASE_TYPE: &'static str = "locstring";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::LocString { strings, flags } => Some(Self {
+                strings: strings.clone(),
+                flags: *flags,
+            }),
+            Value::String(v) => Some(Self {
+                strings: vec![v.clone()],
+                flags: 0,
+            }),
+            _ => None,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        match self.strings.as_slice() {
+            [string] if self.flags == 0 => Value::String(string.clone()),
+            _ => Value::LocString {
+                strings: self.strings.clone(),
+                flags: self.flags,
+            },
+        }
+    }
+}
+
+impl<T: DbcValue, const N: usize> DbcValue for [T; N] {
+    const BASE_TYPE: &'static str = T::BASE_TYPE;
+    const TYPE_SIZE: TypeSize = T::TYPE_SIZE;
+    const ARRAY_SIZE: Option<usize> = Some(N);
+
+    fn from_value(value: &Value) -> Option<Self> {
+        let Value::Array(values) = value else {
+            return None;
+        };
+        values
+            .iter()
+            .map(T::from_value)
+            .collect::<Option<Vec<_>>>()?
+            .try_into()
+            .ok()
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(T::to_value).collect())
+    }
+}
+
+/// Struct with the layout of a table, usually implemented with
+/// `#[derive(DbcRecord)]`
+///
+/// ```
+/// use wow_alchemy_cdbc::DbcRecord;
+///
+/// #[derive(Debug, DbcRecord)]
+/// #[dbc(table = "SpellIcon")]
+/// struct SpellIcon {
+///     #[dbc(key, name = "ID")]
+///     id: u32,
+///     #[dbc(name = "TextureFilename")]
+///     texture: String,
+/// }
+/// ```
+pub trait DbcRecord: Sized {
+    /// Name of the table, like `Spell`
+    const TABLE_NAME: &'static str;
+
+    /// Columns of the fields in order
+    fn columns() -> Vec<DbdColumn>;
+
+    /// Fields of the layout in order
+    fn fields() -> Vec<DbdField>;
+
+    fn from_record(record: &[Value]) -> Result<Self>;
+
+    fn to_record(&self) -> Record;
+
+    /// Id of the record, from the key field or the first one
+    fn id(&self) -> u32;
+
+    /// Layout as a DBD definition for a build, which decides the amount of
+    /// strings of `locstring` fields
+    fn dbd_file(game_build: &GameBuild) -> DbdFile {
+        DbdFile {
+            game_build: *game_build,
+            columns: Self::columns()
+                .into_iter()
+                .map(|column| (column.name.clone(), column))
+                .collect(),
+            build: DbdBuild {
+                versions: vec![GameBuildSpec::Single(*game_build)],
+                fields: Self::fields(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::dbd::ForeignKey;
+    use crate::{DbcTable, DbcVersion};
+    use wow_alchemy_data_derive::DbcRecord;
+
+    #[derive(Debug, Clone, PartialEq, DbcRecord)]
+    #[dbc(table = "Spell")]
+    struct Spell {
+        #[dbc(key, name = "ID")]
+        id: u32,
+        #[dbc(name = "Name_lang")]
+        name: LocString,
+        #[dbc(name = "SpellIconID", foreign_key = "SpellIcon")]
+        icon: i32,
+        #[dbc(name = "Effect")]
+        effects: [u8; 3],
+        #[dbc(name = "Speed")]
+        speed: f32,
+        #[dbc(name = "Passive")]
+        passive: bool,
+    }
+
+    fn spell(id: u32, name: &str) -> Spell {
+        let mut strings = vec![Some(String::new()); 16];
+        strings[Locale::EnUS.index()] = Some(name.to_owned());
+        Spell {
+            id,
+            name: LocString {
+                strings,
+                flags: 0xFF01FE,
+            },
+            icon: id as i32 * 10,
+            effects: [1, 2, id as u8],
+            speed: 1.5,
+            passive: id % 2 == 0,
+        }
+    }
+
+    #[test]
+    fn test_record_layout() {
+        let dbd = Spell::dbd_file(&"3.3.5.12340".try_into().unwrap());
+        let fields = &dbd.build.fields;
+        assert_eq!(fields.len(), 6);
+        assert_eq!(fields[0].type_size, TypeSize::UInt32);
+        assert!(fields[0].is_key);
+        assert!(!fields[2].is_key);
+        assert_eq!(fields[3].array_size, Some(3));
+        assert!(dbd.columns["Name_lang"].is_localized());
+        assert_eq!(
+            dbd.columns["SpellIconID"].foreign_key,
+            Some(ForeignKey {
+                table: "SpellIcon".into(),
+                field: "ID".into()
+            })
+        );
+        assert_eq!(Spell::TABLE_NAME, "Spell");
+    }
+
+    #[test]
+    fn test_table_roundtrip() {
+        let game_build: GameBuild = "3.3.5.12340".try_into().unwrap();
+        let table = DbcTable::from_records(
+            &game_build,
+            vec![spell(1, "Fireball"), spell(2, "Frostbolt")],
+        );
+        assert_eq!(table.get(2).map(|s| s.icon), Some(20));
+
+        let mut bytes = Vec::new();
+        table.write(&mut bytes, DbcVersion::WDBC).expect("write");
+        let read = DbcTable::<Spell>::read(&mut Cursor::new(bytes), &game_build).expect("read");
+        assert_eq!(read.records(), table.records());
+        assert_eq!(
+            read.get(1).unwrap().name.localized(Locale::FrFR),
+            Some("Fireball")
+        );
+        assert!(read.get(10).is_none());
+
+        let mismatched = Spell::from_record(&[Value::Float32(1.0)]);
+        assert!(matches!(mismatched, Err(Error::InvalidRecord(_))));
+    }
+
+    #[test]
+    fn test_value_conversions() {
+        assert_eq!(u8::from_value(&Value::Int32(255)), Some(255));
+        assert_eq!(u8::from_value(&Value::Int32(256)), None);
+        assert_eq!(i32::from_value(&Value::UInt32(u32::MAX)), None);
+        assert_eq!(u64::from_value(&Value::UInt64(u64::MAX)), Some(u64::MAX));
+        assert_eq!(u64::from_value(&Value::Int64(-1)), None);
+        assert!(matches!(
+            u8::from_field(&[Value::Int32(-1)], 0, "Effect"),
+            Err(Error::InvalidValue(..))
+        ));
+
+        let loc_string = Value::LocString {
+            strings: vec![Some("Fireball".into()), None],
+            flags: 0,
+        };
+        assert_eq!(
+            Option::<String>::from_value(&loc_string),
+            Some(Some("Fireball".into()))
+        );
+    }
+}
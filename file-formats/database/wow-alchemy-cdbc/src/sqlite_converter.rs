@@ -1,13 +1,47 @@
 use std::{fs, path::Path};
 
-use rusqlite::{Connection, params_from_iter, types::ToSqlOutput};
+use rusqlite::{
+    Connection, params_from_iter,
+    types::{ToSqlOutput, ValueRef},
+};
+
+pub use crate::flatten::LocStringColumns;
+use wow_alchemy_data::types::MagicStr;
 
 use crate::{
-    Error, Result, Value, WdbFile,
-    dbd::{DbdFile, GameBuild, download::download_dbd, parse_dbd_file},
+    DbcVersion, Error, FieldType, Record, Result, Value, WdbFile, WdbWriter,
+    dbd::{DbdField, DbdFile, DbdSource, GameBuild},
+    flatten,
 };
 
-pub fn base_type_to_sqlite_type(base_type: &str) -> Result<&str> {
+/// Columns of a DBD field with their SQLite types, in the order of the
+/// values of a record
+fn field_columns(
+    dbd: &DbdFile,
+    field: &DbdField,
+    locstrings: LocStringColumns,
+) -> Result<Vec<(String, &'static str)>> {
+    if !dbd.columns.contains_key(&field.name) {
+        return Err(Error::SqliteTableDefinition(format!(
+            "column not found: {}",
+            field.name
+        )));
+    }
+
+    Ok(flatten::field_columns(dbd, field, locstrings)?
+        .into_iter()
+        .map(|(name, field_type)| {
+            let sqlite_type = match field_type {
+                FieldType::String | FieldType::LocString(_) => "text",
+                FieldType::Float32 => "real",
+                _ => "integer",
+            };
+            (name.to_lowercase(), sqlite_type)
+        })
+        .collect())
+}
+
+pub fn base_type_to_sqlite_type(base_type: &str) -> Result<&'static str> {
     Ok(match base_type {
         "locstring" | "string" => "text",
         "int" => "integer",
@@ -21,48 +55,36 @@ pub fn base_type_to_sqlite_type(base_type: &str) -> Result<&str> {
     })
 }
 
-pub fn make_table_definition(dbd: &DbdFile, table_name: &str) -> Result<String> {
+pub fn make_table_definition(
+    dbd: &DbdFile,
+    table_name: &str,
+    locstrings: LocStringColumns,
+) -> Result<String> {
     let mut table_cols = Vec::new();
     let mut table_fks = Vec::new();
     for field in &dbd.build.fields {
-        let field_name = field.name.to_lowercase();
-
-        let Some(column) = &dbd.columns.get(&field.name) else {
-            return Err(Error::SqliteTableDefinition(format!(
-                "column not found: {}",
-                field.name
-            )));
-        };
+        let foreign_key = dbd
+            .columns
+            .get(&field.name)
+            .and_then(|column| column.foreign_key.as_ref());
 
-        if field.is_array {
-            let sqlite_type = base_type_to_sqlite_type(&column.base_type)?;
-            for i in 0..field.array_size.unwrap() {
-                let col_def = format!("\"{}_{}\" {}", field_name, i, sqlite_type,);
-                table_cols.push(col_def);
-
-                if let Some(fk) = &column.foreign_key {
-                    table_fks.push(format!(
-                        "foreign key (\"{}_{}\") references {}(\"{}\")",
-                        field_name,
-                        i,
-                        fk.table.to_lowercase(),
-                        fk.field.to_lowercase()
-                    ));
-                }
-            }
-        } else {
+        for (col_name, sqlite_type) in field_columns(dbd, field, locstrings)? {
             let col_def = format!(
                 "\"{}\" {}{}",
-                field_name,
-                base_type_to_sqlite_type(&column.base_type)?,
-                if field.is_key { " primary key" } else { "" }
+                col_name,
+                sqlite_type,
+                if field.is_key && !field.is_array {
+                    " primary key"
+                } else {
+                    ""
+                }
             );
             table_cols.push(col_def);
 
-            if let Some(fk) = &column.foreign_key {
+            if let Some(fk) = foreign_key {
                 table_fks.push(format!(
                     "foreign key (\"{}\") references {}(\"{}\")",
-                    field_name,
+                    col_name,
                     fk.table.to_lowercase(),
                     fk.field.to_lowercase()
                 ));
@@ -79,28 +101,16 @@ pub fn make_table_definition(dbd: &DbdFile, table_name: &str) -> Result<String>
     ))
 }
 
-pub fn make_insert_query(dbd: &DbdFile, table_name: &str) -> Result<String> {
+pub fn make_insert_query(
+    dbd: &DbdFile,
+    table_name: &str,
+    locstrings: LocStringColumns,
+) -> Result<String> {
     let mut table_cols = Vec::new();
     let mut row_params = Vec::new();
     for field in &dbd.build.fields {
-        if !dbd.columns.contains_key(&field.name) {
-            return Err(Error::SqliteTableDefinition(format!(
-                "column not found: {}",
-                field.name
-            )));
-        };
-
-        let field_name = field.name.to_lowercase();
-
-        if field.is_array {
-            for i in 0..field.array_size.unwrap() {
-                let col_def = format!("\"{}_{}\"", field_name, i);
-                table_cols.push(col_def);
-                row_params.push("?");
-            }
-        } else {
-            let col_def = format!("\"{}\"", field_name);
-            table_cols.push(col_def);
+        for (col_name, _) in field_columns(dbd, field, locstrings)? {
+            table_cols.push(format!("\"{}\"", col_name));
             row_params.push("?");
         }
     }
@@ -113,60 +123,159 @@ pub fn make_insert_query(dbd: &DbdFile, table_name: &str) -> Result<String> {
     ))
 }
 
-pub fn flatten_values(item: &[Value]) -> Vec<ToSqlOutput<'static>> {
-    let mut params = Vec::<ToSqlOutput>::new();
-    for ii in item {
-        match ii {
-            crate::Value::Array(values) => {
-                for j in values {
-                    match j {
-                        crate::Value::Array(_) => unreachable!(),
-                        _ => params.push(j.clone().into()),
-                    }
-                }
+/// Query selecting the columns created by [`make_table_definition`] in the
+/// order of the DBD fields, sorted by the primary key
+pub fn make_select_query(
+    dbd: &DbdFile,
+    table_name: &str,
+    locstrings: LocStringColumns,
+) -> Result<String> {
+    let mut table_cols = Vec::new();
+    let mut order_by = "rowid".to_string();
+    for field in &dbd.build.fields {
+        for (col_name, _) in field_columns(dbd, field, locstrings)? {
+            if field.is_key && !field.is_array {
+                order_by = format!("\"{}\"", col_name);
             }
-            _ => params.push(ii.clone().into()),
+            table_cols.push(format!("\"{}\"", col_name));
         }
     }
-    params
+
+    Ok(format!(
+        "select {} from {} order by {}",
+        table_cols.join(","),
+        table_name,
+        order_by
+    ))
+}
+
+pub fn flatten_values(
+    item: &[Value],
+    locstrings: LocStringColumns,
+) -> Result<Vec<ToSqlOutput<'static>>> {
+    Ok(flatten::flatten_values(item, locstrings)?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+/// Table of the file name, format and WDB2 header of each converted file,
+/// so [`convert_from_sqlite`] writes them back the way they were
+pub const SOURCE_FILES_TABLE: &str = "wow_alchemy_source_files";
+
+fn make_source_files_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        &format!(
+            "CREATE TABLE {SOURCE_FILES_TABLE} (\
+             table_name text primary key, file_name text, version text, build integer, \
+             table_hash integer, timestamp_last_written integer, locale integer)"
+        ),
+        (),
+    )?;
+    Ok(())
+}
+
+fn insert_source_file(
+    conn: &Connection,
+    table_name: &str,
+    file_name: &str,
+    writer: &WdbWriter,
+) -> Result<()> {
+    let version = MagicStr::from(writer.version);
+    conn.execute(
+        &format!("insert into {SOURCE_FILES_TABLE} values (?, ?, ?, ?, ?, ?, ?)"),
+        (
+            table_name,
+            file_name,
+            String::from_utf8_lossy(&version),
+            writer.build,
+            writer.table_hash,
+            writer.timestamp_last_written,
+            writer.locale,
+        ),
+    )?;
+    Ok(())
+}
+
+/// File name and writer of a table from [`SOURCE_FILES_TABLE`], or `None`
+/// for databases without it and tables added by hand
+fn source_file<'a>(
+    conn: &Connection,
+    dbd: &'a DbdFile,
+    table_name: &str,
+) -> Result<Option<(String, WdbWriter<'a>)>> {
+    let exists = conn
+        .prepare("select 1 from sqlite_master where type = 'table' and name = ?")?
+        .exists([SOURCE_FILES_TABLE])?;
+    if !exists {
+        return Ok(None);
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "select file_name, version, build, table_hash, timestamp_last_written, locale \
+         from {SOURCE_FILES_TABLE} where table_name = ?"
+    ))?;
+    let mut rows = stmt.query([table_name])?;
+    let Some(row) = rows.next()? else {
+        return Ok(None);
+    };
+
+    let version: String = row.get(1)?;
+    let version = <MagicStr>::try_from(version.as_bytes())
+        .ok()
+        .and_then(|magic| DbcVersion::try_from(magic).ok())
+        .ok_or_else(|| {
+            Error::InvalidValue(
+                format!("{SOURCE_FILES_TABLE}.version"),
+                format!("unknown version {version:?} of {table_name}"),
+            )
+        })?;
+    let mut writer = WdbWriter::new(dbd, version);
+    writer.build = row.get(2)?;
+    writer.table_hash = row.get(3)?;
+    writer.timestamp_last_written = row.get(4)?;
+    writer.locale = row.get(5)?;
+    Ok(Some((row.get(0)?, writer)))
 }
 
 /// Convert all dbc files in a folder to a single SQLite database file
 pub fn convert_to_sqlite(
     game_build: GameBuild,
+    dbd_source: &DbdSource,
     source_dir: &Path,
     output_sqlite: &Path,
+    locstrings: LocStringColumns,
 ) -> Result<()> {
-    let root_dir = match source_dir.read_dir() {
-        Ok(iter) => iter,
-        Err(err) => return Err(err.into()),
-    };
+    let root_dir = source_dir.read_dir()?;
 
     fs::remove_file(output_sqlite).ok();
 
     let mut conn = Connection::open(output_sqlite)?;
     conn.execute("PRAGMA foreign_keys = OFF;", [])?;
+    make_source_files_table(&conn)?;
 
     for dir_entry in root_dir {
         let Ok(dir_entry) = dir_entry else { continue };
         let filename: String = dir_entry.file_name().to_string_lossy().into();
 
         log::info!("Converting table: {filename:?}");
-        let dbd_file = match download_dbd(&filename) {
-            Ok(dbd_file) => dbd_file,
+        let definition = match dbd_source.load(&filename) {
+            Ok(definition) => definition,
             Err(err) => {
                 println!("skipping dbd file {:?} due to error {:?}", filename, err);
                 continue;
             }
         };
 
-        let mut table_name: String = dbd_file.file_name().unwrap().to_string_lossy().into();
-        if let Some(idx) = table_name.find(".") {
-            table_name.truncate(idx);
+        let table_name = definition.name.to_lowercase();
+
+        let mut reader = fs::File::open(dir_entry.path())?;
+        let Ok(wdb) = WdbFile::wow_read(&mut reader) else {
+            println!("error parsing dbc file: {filename}");
+            continue;
         };
-        table_name = table_name.to_lowercase();
 
-        let dbd = match parse_dbd_file(&game_build, &dbd_file) {
+        let dbd = match definition.parse_for(&wdb, &game_build) {
             Ok(dbd) => dbd,
             Err(err) => match err {
                 Error::NoFieldsForBuild => {
@@ -177,15 +286,15 @@ pub fn convert_to_sqlite(
             },
         };
 
-        conn.execute(&make_table_definition(&dbd, &table_name)?, ())?;
+        conn.execute(&make_table_definition(&dbd, &table_name, locstrings)?, ())?;
+        insert_source_file(
+            &conn,
+            &table_name,
+            &filename,
+            &WdbWriter::from_wdb(&dbd, &wdb),
+        )?;
 
-        let mut reader = fs::File::open(dir_entry.path())?;
-        let Ok(wdb) = WdbFile::wow_read(&mut reader) else {
-            println!("error parsing dbc file: {filename}");
-            continue;
-        };
-
-        let insert_qr = make_insert_query(&dbd, &table_name)?;
+        let insert_qr = make_insert_query(&dbd, &table_name, locstrings)?;
 
         let tx = conn.transaction()?;
         {
@@ -197,7 +306,9 @@ pub fn convert_to_sqlite(
                     for (idx, values) in chunk.iter().enumerate() {
                         match values {
                             Ok(values) => {
-                                stmt.execute(params_from_iter(flatten_values(values)))?;
+                                stmt.execute(params_from_iter(flatten_values(
+                                    values, locstrings,
+                                )?))?;
                             }
                             Err(err) => {
                                 println!("{table_name}: item {idx} parse failed: {err}");
@@ -213,7 +324,7 @@ pub fn convert_to_sqlite(
                 for (idx, values) in iter.enumerate() {
                     match values {
                         Ok(values) => {
-                            stmt.execute(params_from_iter(flatten_values(&values)))?;
+                            stmt.execute(params_from_iter(flatten_values(&values, locstrings)?))?;
                         }
                         Err(err) => {
                             println!("{table_name}: item {idx} parse failed: {err}");
@@ -228,3 +339,399 @@ pub fn convert_to_sqlite(
 
     Ok(())
 }
+
+/// Value of a cell for a column of `field_type`, failing for values out of
+/// its range and for NULL outside of string and optional columns, where it
+/// is read as 0
+fn sqlite_to_value(
+    value: ValueRef,
+    field_type: FieldType,
+    column: &str,
+    optional: bool,
+) -> Result<Value> {
+    let value = match (value, field_type) {
+        (ValueRef::Null, FieldType::Float32) if optional => ValueRef::Real(0.0),
+        (ValueRef::Null, FieldType::String) => ValueRef::Null,
+        (ValueRef::Null, _) if optional => ValueRef::Integer(0),
+        _ => value,
+    };
+    let invalid = || {
+        Error::InvalidValue(
+            column.to_owned(),
+            format!("unexpected sqlite value {value:?} for {field_type:?}"),
+        )
+    };
+    let out_of_range = |_| {
+        Error::InvalidValue(
+            column.to_owned(),
+            format!("{value:?} is out of range for {field_type:?}"),
+        )
+    };
+
+    Ok(match (value, field_type) {
+        (ValueRef::Null, FieldType::String) => Value::String(None),
+        (ValueRef::Text(text), FieldType::String) => {
+            Value::String(Some(String::from_utf8_lossy(text).into()))
+        }
+        (ValueRef::Real(v), FieldType::Float32) => Value::Float32(v as f32),
+        (ValueRef::Integer(v), FieldType::Float32) if v as f32 as i64 == v => {
+            Value::Float32(v as f32)
+        }
+        (ValueRef::Integer(v), FieldType::Int8) => {
+            Value::Int8(i8::try_from(v).map_err(out_of_range)?)
+        }
+        (ValueRef::Integer(v), FieldType::UInt8) => {
+            Value::UInt8(u8::try_from(v).map_err(out_of_range)?)
+        }
+        (ValueRef::Integer(v), FieldType::Int16) => {
+            Value::Int16(i16::try_from(v).map_err(out_of_range)?)
+        }
+        (ValueRef::Integer(v), FieldType::UInt16) => {
+            Value::UInt16(u16::try_from(v).map_err(out_of_range)?)
+        }
+        (ValueRef::Integer(v), FieldType::Int32) => {
+            Value::Int32(i32::try_from(v).map_err(out_of_range)?)
+        }
+        (ValueRef::Integer(v), FieldType::UInt32) => {
+            Value::UInt32(u32::try_from(v).map_err(out_of_range)?)
+        }
+        (ValueRef::Integer(v), FieldType::Int64) => Value::Int64(v),
+        // Stored with the same bits, SQLite integers are signed
+        (ValueRef::Integer(v), FieldType::UInt64) => Value::UInt64(v as u64),
+        (ValueRef::Integer(v @ (0 | 1)), FieldType::Bool) => Value::Bool(v == 1),
+        _ => return Err(invalid()),
+    })
+}
+
+/// Read rows of a table created by [`convert_to_sqlite`] as DBC records,
+/// folding array and locale columns back into [`Value::Array`] and
+/// [`Value::LocString`]
+pub fn read_table_records(
+    conn: &Connection,
+    dbd: &DbdFile,
+    table_name: &str,
+    locstrings: LocStringColumns,
+) -> Result<Vec<Record>> {
+    // Columns with an optional DBD column may be left NULL
+    let mut columns = Vec::new();
+    for field in &dbd.build.fields {
+        let optional = dbd
+            .columns
+            .get(&field.name)
+            .is_some_and(|column| column.is_optional);
+        for (column, field_type) in flatten::field_columns(dbd, field, locstrings)? {
+            columns.push((column, field_type, optional));
+        }
+    }
+
+    let mut stmt = conn.prepare(&make_select_query(dbd, table_name, locstrings)?)?;
+    let mut rows = stmt.query([])?;
+
+    let mut records = Vec::new();
+    while let Some(row) = rows.next()? {
+        let cells = columns
+            .iter()
+            .enumerate()
+            .map(|(i, (column, field_type, optional))| {
+                sqlite_to_value(row.get_ref(i)?, *field_type, column, *optional)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        records.push(flatten::unflatten_values(dbd, cells, locstrings)?);
+    }
+
+    Ok(records)
+}
+
+/// Convert the tables of a SQLite database created by [`convert_to_sqlite`]
+/// back into files in a folder, with the name, format and header of the file
+/// each table was converted from. Tables without them are written as `.dbc`
+/// files in the format of the build. Tables of formats that can't be written,
+/// like WDB5 and later, are skipped.
+pub fn convert_from_sqlite(
+    game_build: GameBuild,
+    dbd_source: &DbdSource,
+    input_sqlite: &Path,
+    output_dir: &Path,
+    locstrings: LocStringColumns,
+) -> Result<()> {
+    let conn = Connection::open(input_sqlite)?;
+    fs::create_dir_all(output_dir)?;
+
+    let table_names = {
+        let mut stmt = conn.prepare(
+            "select name from sqlite_master where type = 'table' and name not like 'sqlite_%' and name != ? order by name",
+        )?;
+        stmt.query_map([SOURCE_FILES_TABLE], |row| row.get::<_, String>(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?
+    };
+
+    for table_name in table_names {
+        log::info!("Exporting table: {table_name:?}");
+        let definition = match dbd_source.load(&table_name) {
+            Ok(definition) => definition,
+            Err(err) => {
+                println!("skipping table {:?} due to error {:?}", table_name, err);
+                continue;
+            }
+        };
+
+        let dbd = match definition.parse(&game_build) {
+            Ok(dbd) => dbd,
+            Err(err) => match err {
+                Error::NoFieldsForBuild => {
+                    println!("Error: {err}");
+                    continue;
+                }
+                _ => return Err(err),
+            },
+        };
+
+        let (file_name, wdb_writer) = match source_file(&conn, &dbd, &table_name)? {
+            Some(source) => source,
+            None => (
+                format!("{}.dbc", definition.name),
+                WdbWriter::for_build(&dbd, &game_build, false),
+            ),
+        };
+        if !wdb_writer.version.is_writable() {
+            println!(
+                "skipping table {table_name:?}: {:?} files can't be written",
+                wdb_writer.version
+            );
+            continue;
+        }
+
+        let records = read_table_records(&conn, &dbd, &table_name, locstrings)?;
+        // Only the name, the database doesn't choose where files go
+        let file_name = Path::new(&file_name)
+            .file_name()
+            .ok_or_else(|| Error::InvalidValue(table_name.clone(), "empty file name".into()))?;
+        let mut writer = std::io::BufWriter::new(fs::File::create(output_dir.join(file_name))?);
+        wdb_writer.write(&mut writer, &records)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::dbd::parse_dbd_content;
+    use crate::{LazyRecordIterator, Locale};
+
+    const DBD: &str = "COLUMNS
+int ID
+locstring Name_lang
+float Scale
+int<Other::ID> OtherID
+int Flags
+int Extra?
+
+BUILD 3.3.5.12340
+$id$ID<32>
+Name_lang
+Scale
+OtherID<u16>
+Flags<u8>[2]
+Extra<u64>
+";
+
+    fn loc_string(en: &str, fr: &str) -> Value {
+        let mut strings = vec![Some(String::new()); 16];
+        strings[Locale::EnUS.index()] = Some(en.into());
+        strings[Locale::FrFR.index()] = Some(fr.into());
+        Value::LocString {
+            strings,
+            flags: 0xFF01FE,
+        }
+    }
+
+    #[test]
+    fn test_sqlite_roundtrip() {
+        let game_build: GameBuild = "3.3.5.12340".try_into().unwrap();
+        let dbd = parse_dbd_content(&game_build, DBD).expect("dbd");
+        let records = vec![
+            vec![
+                Value::Int32(9),
+                loc_string("second", "deuxième"),
+                Value::Float32(2.5),
+                Value::UInt16(1),
+                Value::Array(vec![Value::UInt8(1), Value::UInt8(255)]),
+                Value::UInt64(u64::MAX),
+            ],
+            vec![
+                Value::Int32(2),
+                loc_string("first", "premier"),
+                Value::Float32(-1.0),
+                Value::UInt16(0),
+                Value::Array(vec![Value::UInt8(0), Value::UInt8(3)]),
+                Value::UInt64(0),
+            ],
+        ];
+
+        let conn = Connection::open_in_memory().expect("sqlite");
+        conn.execute("PRAGMA foreign_keys = OFF;", [])
+            .expect("pragma");
+        for (table, locstrings) in [
+            ("test", LocStringColumns::PerLocale),
+            ("test_fr", LocStringColumns::Locale(Locale::FrFR)),
+        ] {
+            conn.execute(&make_table_definition(&dbd, table, locstrings).unwrap(), ())
+                .expect("create");
+            let insert = make_insert_query(&dbd, table, locstrings).unwrap();
+            for record in &records {
+                conn.execute(
+                    &insert,
+                    params_from_iter(flatten_values(record, locstrings).unwrap()),
+                )
+                .expect("insert");
+            }
+        }
+        // Optional column and other locales left out by hand edits
+        conn.execute(
+            "insert into test (id, name_lang_enus, name_lang_flags, scale, otherid, flags_0, flags_1) values (5, 'new', 1, 1.0, 9, 0, 0)",
+            (),
+        )
+        .expect("insert");
+
+        let exported =
+            read_table_records(&conn, &dbd, "test", LocStringColumns::PerLocale).expect("export");
+        assert_eq!(exported.len(), 3);
+        assert_eq!(exported[2][5], Value::UInt64(u64::MAX));
+        assert_eq!(exported[1][5], Value::UInt64(0));
+        assert_eq!(exported[1][1].localized(Locale::FrFR), Some("new"));
+
+        // Values out of range of the column and NULL in required columns
+        conn.execute("update test set flags_1 = 256 where id = 5", ())
+            .unwrap();
+        let result = read_table_records(&conn, &dbd, "test", LocStringColumns::PerLocale);
+        assert!(matches!(result, Err(Error::InvalidValue(column, _)) if column == "Flags_1"));
+        conn.execute(
+            "update test set flags_1 = 0, otherid = null where id = 5",
+            (),
+        )
+        .unwrap();
+        let result = read_table_records(&conn, &dbd, "test", LocStringColumns::PerLocale);
+        assert!(matches!(result, Err(Error::InvalidValue(column, _)) if column == "OtherID"));
+
+        let french = read_table_records(
+            &conn,
+            &dbd,
+            "test_fr",
+            LocStringColumns::Locale(Locale::FrFR),
+        )
+        .expect("export");
+        assert_eq!(french[1][1].localized(Locale::FrFR), Some("deuxième"));
+        assert_eq!(french[1][1].localized(Locale::EnUS), Some("deuxième"));
+
+        let mut bytes = Vec::new();
+        WdbWriter::new(&dbd, DbcVersion::WDBC)
+            .write(&mut bytes, &exported)
+            .expect("write");
+
+        let mut cursor = Cursor::new(bytes.as_slice());
+        let wdb = WdbFile::wow_read(&mut cursor).expect("wdb");
+        let read = LazyRecordIterator::new(&mut cursor, &dbd, &wdb)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .expect("records");
+        let ids: Vec<_> = read.iter().map(|record| record[0].clone()).collect();
+        assert_eq!(ids, [Value::Int32(2), Value::Int32(5), Value::Int32(9)]);
+        assert_eq!(read[0], records[1]);
+        assert_eq!(read[2], records[0]);
+    }
+
+    #[test]
+    fn test_sqlite_keeps_source_format() {
+        let game_build: GameBuild = "4.3.4.15595".parse().unwrap();
+        let dir = tempfile::tempdir().expect("temp dir");
+        let dbd_dir = dir.path().join("definitions");
+        let source_dir = dir.path().join("source");
+        let output_dir = dir.path().join("output");
+        fs::create_dir_all(&dbd_dir).unwrap();
+        fs::create_dir_all(&source_dir).unwrap();
+        fs::write(
+            dbd_dir.join("Test.dbd"),
+            "COLUMNS\nint ID\nstring Name\n\nBUILD 4.3.4.15595\n$id$ID<32>\nName\n",
+        )
+        .unwrap();
+        let dbd_source = DbdSource::Directory(dbd_dir);
+        let dbd = dbd_source.load("Test").unwrap().parse(&game_build).unwrap();
+
+        let records = vec![
+            vec![Value::Int32(3), Value::String(Some("a".into()))],
+            vec![Value::Int32(5), Value::String(Some("b".into()))],
+        ];
+        let mut writer = WdbWriter::new(&dbd, DbcVersion::WDB2);
+        writer.build = 15595;
+        writer.table_hash = 0xABCD_0123;
+        writer.locale = 2;
+        let mut bytes = Vec::new();
+        writer.write(&mut bytes, &records).unwrap();
+        fs::write(source_dir.join("Test.db2"), &bytes).unwrap();
+
+        let sqlite = dir.path().join("test.sqlite");
+        convert_to_sqlite(
+            game_build,
+            &dbd_source,
+            &source_dir,
+            &sqlite,
+            LocStringColumns::PerLocale,
+        )
+        .unwrap();
+        convert_from_sqlite(
+            game_build,
+            &dbd_source,
+            &sqlite,
+            &output_dir,
+            LocStringColumns::PerLocale,
+        )
+        .unwrap();
+        assert_eq!(fs::read(output_dir.join("Test.db2")).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_sqlite_skips_unwritable_format() {
+        let game_build: GameBuild = "3.3.5.12340".parse().unwrap();
+        let dir = tempfile::tempdir().expect("temp dir");
+        let dbd_dir = dir.path().join("definitions");
+        let output_dir = dir.path().join("output");
+        fs::create_dir_all(&dbd_dir).unwrap();
+        for table in ["Modern", "Test"] {
+            fs::write(
+                dbd_dir.join(format!("{table}.dbd")),
+                "COLUMNS\nint ID\n\nBUILD 3.3.5.12340\n$id$ID<32>\n",
+            )
+            .unwrap();
+        }
+        let dbd_source = DbdSource::Directory(dbd_dir);
+        let dbd = dbd_source.load("Test").unwrap().parse(&game_build).unwrap();
+
+        let sqlite = dir.path().join("test.sqlite");
+        let conn = Connection::open(&sqlite).unwrap();
+        make_source_files_table(&conn).unwrap();
+        for table in ["Modern", "Test"] {
+            let locstrings = LocStringColumns::PerLocale;
+            conn.execute(&make_table_definition(&dbd, table, locstrings).unwrap(), ())
+                .unwrap();
+            conn.execute(&format!("insert into {table} values (1)"), ())
+                .unwrap();
+        }
+        let writer = WdbWriter::new(&dbd, DbcVersion::WDB5);
+        insert_source_file(&conn, "Modern", "Modern.db2", &writer).unwrap();
+        drop(conn);
+
+        convert_from_sqlite(
+            game_build,
+            &dbd_source,
+            &sqlite,
+            &output_dir,
+            LocStringColumns::PerLocale,
+        )
+        .unwrap();
+        assert!(!output_dir.join("Modern.db2").exists());
+        assert!(output_dir.join("Test.dbc").exists());
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, Write};
+use std::path::Path;
+
+use crate::dbd::{DbdDefinition, DbdFile, GameBuild};
+use crate::{
+    DbcRecord, DbcVersion, Error, LazyRecordIterator, Record, Result, Value, WdbFile, WdbWriter,
+};
+
+/// Row of a [`DbcTable`], either a plain [`Record`] or a [`DbcRecord`] struct
+pub trait TableRow: Sized {
+    fn from_record(record: Record) -> Result<Self>;
+
+    fn to_record(&self) -> Record;
+
+    /// Id of the row, `key_index` is the position of the key field in the
+    /// layout of the table
+    fn id(&self, key_index: usize) -> u32;
+}
+
+impl<T: DbcRecord> TableRow for T {
+    fn from_record(record: Record) -> Result<Self> {
+        <T as DbcRecord>::from_record(&record)
+    }
+
+    fn to_record(&self) -> Record {
+        <T as DbcRecord>::to_record(self)
+    }
+
+    fn id(&self, _key_index: usize) -> u32 {
+        <T as DbcRecord>::id(self)
+    }
+}
+
+impl TableRow for Record {
+    fn from_record(record: Record) -> Result<Self> {
+        Ok(record)
+    }
+
+    fn to_record(&self) -> Record {
+        self.clone()
+    }
+
+    fn id(&self, key_index: usize) -> u32 {
+        self.get(key_index)
+            .and_then(Value::as_i64)
+            .unwrap_or_default() as u32
+    }
+}
+
+/// Rows of a table in memory with their layout, indexed by the key field
+/// (`$id$`) or the first field when the layout has none
+#[derive(Debug, Clone)]
+pub struct DbcTable<T = Record> {
+    dbd: DbdFile,
+    key_index: usize,
+    records: Vec<T>,
+    index: HashMap<u32, usize>,
+}
+
+impl<T: TableRow> DbcTable<T> {
+    /// Create a table from rows with the layout of `dbd`. When several rows
+    /// have the same id, [`get`](Self::get) returns the first one.
+    pub fn new(dbd: DbdFile, records: Vec<T>) -> Self {
+        let key_index = dbd
+            .build
+            .fields
+            .iter()
+            .position(|field| field.is_key)
+            .unwrap_or_default();
+        let mut table = Self {
+            dbd,
+            key_index,
+            records,
+            index: HashMap::new(),
+        };
+        table.reindex(0);
+        table
+    }
+
+    /// Read all records of a file with an explicit layout
+    pub fn read_with<R: Read + Seek>(reader: &mut R, dbd: DbdFile) -> Result<Self> {
+        let wdb = WdbFile::wow_read(reader)?;
+        Self::read_wdb(reader, &wdb, dbd)
+    }
+
+    pub fn open_with<P: AsRef<Path>>(path: P, dbd: DbdFile) -> Result<Self> {
+        Self::read_with(&mut BufReader::new(File::open(path)?), dbd)
+    }
+
+    /// Read all records of a file whose header was already read
+    pub fn read_wdb<R: Read + Seek>(reader: &mut R, wdb: &WdbFile, dbd: DbdFile) -> Result<Self> {
+        let records = LazyRecordIterator::new(reader, &dbd, wdb)?
+            .map(|record| T::from_record(record?))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::new(dbd, records))
+    }
+
+    /// Write the rows as a WDBC or WDB2 file for the build of the layout
+    pub fn write<W: Write>(&self, writer: &mut W, version: DbcVersion) -> Result<()> {
+        let records = self.records.iter().map(T::to_record).collect::<Vec<_>>();
+        let mut wdb_writer = WdbWriter::new(&self.dbd, version);
+        wdb_writer.build = self.dbd.game_build.build;
+        wdb_writer.write(writer, &records)
+    }
+
+    pub fn get(&self, id: u32) -> Option<&T> {
+        self.index.get(&id).map(|idx| &self.records[*idx])
+    }
+
+    pub fn contains(&self, id: u32) -> bool {
+        self.index.contains_key(&id)
+    }
+
+    /// Add a row at the end of the table
+    pub fn insert(&mut self, row: T) -> Result<()> {
+        let id = row.id(self.key_index);
+        if self.index.contains_key(&id) {
+            return Err(Error::DuplicateKey(id));
+        }
+        self.index.insert(id, self.records.len());
+        self.records.push(row);
+        Ok(())
+    }
+
+    /// Replace the row with the same id, returning the previous one
+    pub fn update(&mut self, row: T) -> Result<T> {
+        let id = row.id(self.key_index);
+        let idx = *self.index.get(&id).ok_or(Error::KeyNotFound(id))?;
+        Ok(std::mem::replace(&mut self.records[idx], row))
+    }
+
+    /// Remove a row, keeping the order of the others
+    pub fn delete(&mut self, id: u32) -> Option<T> {
+        let idx = self.index.remove(&id)?;
+        let row = self.records.remove(idx);
+        self.reindex(idx);
+        Some(row)
+    }
+
+    /// Ids of the rows, in no particular order
+    pub fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.index.keys().copied()
+    }
+
+    /// Highest id of the table, the next free one is `max_id() + 1`
+    pub fn max_id(&self) -> Option<u32> {
+        self.index.keys().max().copied()
+    }
+
+    pub fn dbd(&self) -> &DbdFile {
+        &self.dbd
+    }
+
+    /// Position of the key field in the layout
+    pub fn key_index(&self) -> usize {
+        self.key_index
+    }
+
+    /// Position of a field of the layout by its name
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.dbd
+            .build
+            .fields
+            .iter()
+            .position(|field| field.name == name)
+    }
+
+    pub fn records(&self) -> &[T] {
+        &self.records
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.records.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn into_records(self) -> Vec<T> {
+        self.records
+    }
+
+    /// Index the rows from `start`, keeping the first row of each id
+    fn reindex(&mut self, start: usize) {
+        self.index.retain(|_, idx| *idx < start);
+        for (idx, row) in self.records.iter().enumerate().skip(start) {
+            self.index.entry(row.id(self.key_index)).or_insert(idx);
+        }
+    }
+}
+
+impl<T: DbcRecord> DbcTable<T> {
+    /// Create a table with the layout of `T`
+    pub fn from_records(game_build: &GameBuild, records: Vec<T>) -> Self {
+        Self::new(T::dbd_file(game_build), records)
+    }
+
+    /// Read all records of a file with the layout of `T`
+    pub fn read<R: Read + Seek>(reader: &mut R, game_build: &GameBuild) -> Result<Self> {
+        Self::read_with(reader, T::dbd_file(game_build))
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P, game_build: &GameBuild) -> Result<Self> {
+        Self::open_with(path, T::dbd_file(game_build))
+    }
+}
+
+impl DbcTable<Record> {
+    /// Read a file with the layout of a definition that it uses, see
+    /// [`DbdDefinition::parse_for`]
+    pub fn open_definition<P: AsRef<Path>>(
+        path: P,
+        definition: &DbdDefinition,
+        game_build: &GameBuild,
+    ) -> Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let wdb = WdbFile::wow_read(&mut reader)?;
+        let dbd = definition.parse_for(&wdb, game_build)?;
+        Self::read_wdb(&mut reader, &wdb, dbd)
+    }
+
+    /// Value of a field of a row by its name
+    pub fn field<'a>(&self, row: &'a Record, name: &str) -> Option<&'a Value> {
+        row.get(self.column_index(name)?)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DbcTable<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::dbd::parse_dbd_content;
+
+    const DBD: &str = "COLUMNS
+int ID
+string Name
+int Parent
+
+BUILD 3.3.5.12340
+$id$ID<32>
+Name
+Parent<32>
+";
+
+    fn table() -> DbcTable {
+        let dbd = parse_dbd_content(&"3.3.5.12340".try_into().unwrap(), DBD).unwrap();
+        let row = |id: i32, name: &str| {
+            vec![
+                Value::Int32(id),
+                Value::String(Some(name.to_owned())),
+                Value::Int32(0),
+            ]
+        };
+        DbcTable::new(dbd, vec![row(3, "Three"), row(1, "One"), row(7, "Seven")])
+    }
+
+    #[test]
+    fn test_crud() {
+        let mut table = table();
+        assert_eq!(table.max_id(), Some(7));
+        assert_eq!(
+            table.field(table.get(1).unwrap(), "Name"),
+            Some(&Value::String(Some("One".into())))
+        );
+
+        let row = vec![
+            Value::Int32(8),
+            Value::String(Some("Eight".into())),
+            Value::Int32(1),
+        ];
+        table.insert(row.clone()).unwrap();
+        assert!(matches!(table.insert(row), Err(Error::DuplicateKey(8))));
+        assert_eq!(table.max_id(), Some(8));
+
+        let updated = vec![
+            Value::Int32(3),
+            Value::String(Some("Drei".into())),
+            Value::Int32(0),
+        ];
+        let old = table.update(updated).unwrap();
+        assert_eq!(old[1], Value::String(Some("Three".into())));
+        assert!(matches!(
+            table.update(vec![Value::Int32(42)]),
+            Err(Error::KeyNotFound(42))
+        ));
+
+        assert!(table.delete(1).is_some());
+        assert!(table.delete(1).is_none());
+        let ids: Vec<u32> = table.iter().map(|row| row.id(0)).collect();
+        assert_eq!(ids, [3, 7, 8]);
+        assert_eq!(
+            table.get(8).unwrap()[1],
+            Value::String(Some("Eight".into()))
+        );
+    }
+
+    #[test]
+    fn test_delete_duplicate_ids() {
+        let dbd = parse_dbd_content(&"3.3.5.12340".try_into().unwrap(), DBD).unwrap();
+        let row = |id: i32, name: &str| {
+            vec![
+                Value::Int32(id),
+                Value::String(Some(name.to_owned())),
+                Value::Int32(0),
+            ]
+        };
+        let mut table = DbcTable::new(
+            dbd,
+            vec![
+                row(1, "One"),
+                row(2, "First"),
+                row(3, "Three"),
+                row(2, "Second"),
+            ],
+        );
+
+        assert!(table.delete(1).is_some());
+        assert_eq!(
+            table.get(2).unwrap()[1],
+            Value::String(Some("First".into()))
+        );
+        assert_eq!(
+            table.get(3).unwrap()[1],
+            Value::String(Some("Three".into()))
+        );
+
+        assert!(table.delete(2).is_some());
+        assert_eq!(
+            table.get(2).unwrap()[1],
+            Value::String(Some("Second".into()))
+        );
+    }
+
+    #[test]
+    fn test_write_read() {
+        let table = table();
+        let mut bytes = Vec::new();
+        table.write(&mut bytes, DbcVersion::WDBC).unwrap();
+
+        let read =
+            DbcTable::<Record>::read_with(&mut Cursor::new(bytes), table.dbd().clone()).unwrap();
+        assert_eq!(read.records(), table.records());
+        assert_eq!(read.get(7), table.get(7));
+    }
+}
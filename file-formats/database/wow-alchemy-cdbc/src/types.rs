@@ -1,4 +1,5 @@
 use std::fmt;
+use std::str::FromStr;
 
 #[cfg(feature = "sqlite")]
 use rusqlite::types::ToSqlOutput;
@@ -18,7 +19,84 @@ impl StringRef {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Client locale in the order of the strings of a `locstring` field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    EnUS,
+    KoKR,
+    FrFR,
+    DeDE,
+    ZhCN,
+    ZhTW,
+    EsES,
+    EsMX,
+    RuRU,
+    JaJP,
+    PtPT,
+    ItIT,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 12] = [
+        Locale::EnUS,
+        Locale::KoKR,
+        Locale::FrFR,
+        Locale::DeDE,
+        Locale::ZhCN,
+        Locale::ZhTW,
+        Locale::EsES,
+        Locale::EsMX,
+        Locale::RuRU,
+        Locale::JaJP,
+        Locale::PtPT,
+        Locale::ItIT,
+    ];
+
+    /// Position of the locale string in a `locstring` field
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::EnUS => "enUS",
+            Locale::KoKR => "koKR",
+            Locale::FrFR => "frFR",
+            Locale::DeDE => "deDE",
+            Locale::ZhCN => "zhCN",
+            Locale::ZhTW => "zhTW",
+            Locale::EsES => "esES",
+            Locale::EsMX => "esMX",
+            Locale::RuRU => "ruRU",
+            Locale::JaJP => "jaJP",
+            Locale::PtPT => "ptPT",
+            Locale::ItIT => "itIT",
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Locale {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| crate::Error::GenericError(format!("unknown locale {s}")))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Int32(i32),
     UInt32(u32),
@@ -32,6 +110,51 @@ pub enum Value {
     Int64(i64),
     UInt64(u64),
     Array(Vec<Value>),
+    /// Localized string of files before Cataclysm, one string per locale (8
+    /// in Vanilla, 16 later) and a mask of flags
+    LocString {
+        strings: Vec<Option<String>>,
+        flags: u32,
+    },
+}
+
+impl Value {
+    /// Integer value of any of the integer variants
+    pub fn as_i64(&self) -> Option<i64> {
+        Some(match self {
+            Value::Int32(v) => *v as i64,
+            Value::UInt32(v) => *v as i64,
+            Value::Bool(v) => *v as i64,
+            Value::UInt8(v) => *v as i64,
+            Value::Int8(v) => *v as i64,
+            Value::UInt16(v) => *v as i64,
+            Value::Int16(v) => *v as i64,
+            Value::Int64(v) => *v,
+            Value::UInt64(v) => *v as i64,
+            _ => return None,
+        })
+    }
+
+    /// Text of a string value. For localized strings it is the one of the
+    /// preferred locale or the first non empty one when it is missing.
+    pub fn localized(&self, locale: Locale) -> Option<&str> {
+        match self {
+            Value::String(v) => v.as_deref(),
+            Value::LocString { strings, .. } => strings
+                .get(locale.index())
+                .and_then(|v| v.as_deref())
+                .filter(|v| !v.is_empty())
+                .or_else(|| {
+                    strings
+                        .iter()
+                        .flatten()
+                        .map(|v| v.as_str())
+                        .find(|v| !v.is_empty())
+                })
+                .or_else(|| strings.first().and_then(|v| v.as_deref())),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Value {
@@ -58,6 +181,16 @@ impl fmt::Display for Value {
             }
             Value::Int64(v) => write!(f, "{v}"),
             Value::UInt64(v) => write!(f, "{v}"),
+            Value::LocString { strings, flags } => {
+                write!(f, "{{")?;
+                for (i, v) in strings.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{v:?}")?;
+                }
+                write!(f, "; flags {flags:#x}}}")
+            }
         }
     }
 }
@@ -74,6 +207,11 @@ impl From<Value> for ToSqlOutput<'_> {
                 }
             }
             Value::Bool(v) => v.into(),
+            Value::LocString { .. } => value
+                .localized(Locale::EnUS)
+                .unwrap_or("")
+                .to_owned()
+                .into(),
             Value::Array(values) => {
                 let parts: Vec<String> = values
                     .iter()
@@ -89,6 +227,12 @@ impl From<Value> for ToSqlOutput<'_> {
                             )
                         }
                         Value::Bool(v) => (if *v { "true" } else { "false" }).into(),
+                        Value::LocString { .. } => {
+                            format!(
+                                "\"{}\"",
+                                i.localized(Locale::EnUS).unwrap_or("").replace("\"", "")
+                            )
+                        }
                         Value::Array(_) => unreachable!(),
                         _ => i.to_string(),
                     })
@@ -103,7 +247,48 @@ impl From<Value> for ToSqlOutput<'_> {
             Value::UInt16(v) => v.into(),
             Value::Int16(v) => v.into(),
             Value::Int64(v) => v.into(),
-            Value::UInt64(v) => v.to_string().into(),
+            // Stored with the same bits, integer columns would turn large
+            // values into lossy reals
+            Value::UInt64(v) => (v as i64).into(),
+        }
+    }
+}
+
+/// Scalars are written as JSON scalars. Floats use the shortest text that
+/// parses back to the same f32, and NaN or infinity, which JSON has no
+/// numbers for, strings of their bits.
+#[cfg(feature = "json")]
+impl serde::Serialize for Value {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{SerializeSeq, SerializeStruct};
+
+        match self {
+            Value::Int8(v) => serializer.serialize_i8(*v),
+            Value::UInt8(v) => serializer.serialize_u8(*v),
+            Value::Int16(v) => serializer.serialize_i16(*v),
+            Value::UInt16(v) => serializer.serialize_u16(*v),
+            Value::Int32(v) => serializer.serialize_i32(*v),
+            Value::UInt32(v) => serializer.serialize_u32(*v),
+            Value::Int64(v) => serializer.serialize_i64(*v),
+            Value::UInt64(v) => serializer.serialize_u64(*v),
+            Value::Bool(v) => serializer.serialize_u8(*v as u8),
+            Value::Float32(v) if v.is_finite() => serializer.serialize_f32(*v),
+            Value::Float32(v) => serializer.serialize_str(&crate::flatten::format_float(*v)),
+            Value::String(Some(v)) => serializer.serialize_str(v),
+            Value::String(None) => serializer.serialize_none(),
+            Value::Array(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            Value::LocString { strings, flags } => {
+                let mut loc_string = serializer.serialize_struct("LocString", 2)?;
+                loc_string.serialize_field("strings", strings)?;
+                loc_string.serialize_field("flags", flags)?;
+                loc_string.end()
+            }
         }
     }
 }
@@ -126,6 +311,8 @@ pub enum FieldType {
     Int16,
     Int64,
     UInt64,
+    /// String offset for each of the locales followed by the flags mask
+    LocString(usize),
 }
 
 impl FieldType {
@@ -142,6 +329,7 @@ impl FieldType {
             FieldType::Int16 => 2,
             FieldType::Int64 => 8,
             FieldType::UInt64 => 8,
+            FieldType::LocString(locales) => 4 * (locales + 1),
         }
     }
 }
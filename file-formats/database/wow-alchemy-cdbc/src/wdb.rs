@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::io::SeekFrom;
 
 use custom_debug::Debug;
@@ -7,11 +7,24 @@ use wow_alchemy_data::{prelude::*, types::MagicStr};
 use wow_alchemy_data_derive::{WowEnumFrom, WowHeaderR, WowHeaderW};
 use wow_alchemy_utils::debug;
 
+use crate::dbd::{GameBuild, GameVersion};
+
+use crate::db2::{
+    FLAG_ID_LIST, FLAG_OFFSET_MAP, FieldStorageInfo, FieldStructure, RecordInfo, RecordLayout,
+    Section,
+};
+
 pub const WDBC: MagicStr = *b"WDBC";
 pub const WDB2: MagicStr = *b"WDB2";
 pub const WDB3: MagicStr = *b"WDB3";
 pub const WDB4: MagicStr = *b"WDB4";
 pub const WDB5: MagicStr = *b"WDB5";
+pub const WDC1: MagicStr = *b"WDC1";
+pub const WDC2: MagicStr = *b"WDC2";
+
+/// WDB2 files of later builds have an id index and string lengths table
+/// between the header and the records when `max_id` is set
+pub const WDB2_INDEX_MIN_BUILD: u32 = 12880;
 
 #[derive(
     Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, WowEnumFrom, WowHeaderR, WowHeaderW,
@@ -34,10 +47,39 @@ pub enum DbcVersion {
     #[wow_data(expr = WDB5)]
     #[default]
     WDB5,
+    /// V6, bitpacked records with pallet and common data, used in Legion
+    /// 7.3.5
+    #[wow_data(expr = WDC1)]
+    WDC1,
+    /// V7, records split into sections, used in BfA
+    #[wow_data(expr = WDC2)]
+    WDC2,
 }
 
 impl DataVersion for DbcVersion {}
 
+impl DbcVersion {
+    /// Format of a table in the client of a build. `.db2` tables from
+    /// Cataclysm to Warlords of Draenor are WDB2, the `.dbc` tables of these
+    /// builds WDBC.
+    pub fn for_build(game_build: &GameBuild, db2: bool) -> Self {
+        match game_build.version() {
+            Ok(GameVersion::Cataclysm | GameVersion::MoP | GameVersion::WoD) if db2 => {
+                DbcVersion::WDB2
+            }
+            Ok(GameVersion::Legion) if *game_build < GameBuild::new(7, 3, 5, 0) => DbcVersion::WDB5,
+            Ok(GameVersion::Legion) => DbcVersion::WDC1,
+            Ok(version) if version > GameVersion::Legion => DbcVersion::WDC2,
+            _ => DbcVersion::WDBC,
+        }
+    }
+
+    /// Whether [`WdbWriter`](crate::WdbWriter) can write files of this format
+    pub fn is_writable(self) -> bool {
+        matches!(self, DbcVersion::WDBC | DbcVersion::WDB2)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, WowHeaderR, WowHeaderW)]
 #[wow_data(version = DbcVersion)]
 pub enum Wdb2Fields {
@@ -89,7 +131,7 @@ impl<T: Default + WowHeaderR + WowHeaderW> Default for VGTE4<T> {
 pub enum VGTE5<T: Default + WowHeaderR + WowHeaderW> {
     None,
 
-    #[wow_data(read_if = version >= DbcVersion::WDB4)]
+    #[wow_data(read_if = version >= DbcVersion::WDB5)]
     Some(T),
 }
 
@@ -115,8 +157,25 @@ pub struct WdbHeader {
 }
 
 impl WdbHeader {
+    /// Size of the WDB2 id index and string lengths table that follows the
+    /// header
+    pub fn index_table_size(&self) -> usize {
+        match self.wdb2 {
+            Wdb2Fields::Fields {
+                build,
+                min_id,
+                max_id,
+                ..
+            } if build > WDB2_INDEX_MIN_BUILD && max_id != 0 => {
+                (max_id.saturating_sub(min_id) as usize + 1) * (4 + 2)
+            }
+            _ => 0,
+        }
+    }
+
     pub fn string_offset(&self) -> u64 {
-        ((4 + self.wow_size()) as u32 + (self.record_size * self.record_count)) as u64
+        ((4 + self.wow_size() + self.index_table_size()) as u32
+            + (self.record_size * self.record_count)) as u64
     }
 }
 
@@ -124,11 +183,33 @@ impl WdbHeader {
 pub struct WdbFile {
     pub version: DbcVersion,
     pub header: WdbHeader,
+    /// Offset of the records of the first section
     pub header_size: usize,
+    /// Layout hash of WDB5+ files, to select the DBD layout
+    pub layout_hash: Option<u32>,
     #[debug(with = debug::trimmed_collection_fmt)]
     pub strings: Vec<String>,
+    /// Index of the string by its offset in the string block, or by its
+    /// position in the file for WDC2+
+    #[debug(skip)]
+    pub string_pos: BTreeMap<usize, usize>,
+    /// Size and position of the fields in WDB5+ records
+    pub field_structures: Vec<FieldStructure>,
+    /// Storage of the fields in WDC1+ records
+    pub storage_info: Vec<FieldStorageInfo>,
+    /// Pallet values of each field
     #[debug(skip)]
-    pub string_pos: HashMap<usize, usize>,
+    pub pallet_data: Vec<Vec<u32>>,
+    /// Common values of each field by record id
+    #[debug(skip)]
+    pub common_data: Vec<HashMap<u32, u32>>,
+    /// Foreign ids of the non-inline relation field by record index
+    #[debug(skip)]
+    pub relationships: HashMap<u32, u32>,
+    /// All records of the file in order, followed by the rows of the copy
+    /// tables
+    #[debug(with = debug::trimmed_collection_fmt)]
+    pub records: Vec<RecordInfo>,
 }
 
 impl WdbFile {
@@ -136,51 +217,108 @@ impl WdbFile {
         reader.seek(SeekFrom::Start(0))?;
         let version: DbcVersion = MagicStr::wow_read(reader)?.try_into()?;
 
-        let header: WdbHeader = reader.wow_read_versioned(version)?;
-
-        let header_size = version.wow_size() + header.wow_size();
-
-        let string_block_offset = header_size as u32 + (header.record_count * header.record_size);
+        match version {
+            DbcVersion::WDB5 => return Self::read_wdb5(reader),
+            DbcVersion::WDC1 => return Self::read_wdc1(reader),
+            DbcVersion::WDC2 => return Self::read_wdc2(reader),
+            _ => {}
+        }
 
-        reader.seek(SeekFrom::Start(string_block_offset as u64))?;
+        let header: WdbHeader = reader.wow_read_versioned(version)?;
 
-        let mut string_block = vec![0u8; header.string_block_size as usize];
-        reader.read_exact(&mut string_block)?;
+        let header_size = version.wow_size() + header.wow_size() + header.index_table_size();
 
-        let strings: Vec<String> = string_block
-            .split(|i| *i == 0)
-            .map(|v| String::from_utf8_lossy(v).into())
-            .collect();
+        let flags = match header.flags {
+            VGTE4::Some(flags) => flags,
+            VGTE4::None => 0,
+        };
+        let copy_table_size = match header.wdb2 {
+            Wdb2Fields::Fields {
+                copy_table_size, ..
+            } => copy_table_size,
+            Wdb2Fields::None => 0,
+        };
+        let sparse = flags & FLAG_OFFSET_MAP != 0;
+        // The string block size of sparse tables is the offset of the offset
+        // map, as their strings are inline
+        let section = Section {
+            file_offset: header_size as u64,
+            record_count: header.record_count,
+            string_table_size: if sparse { 0 } else { header.string_block_size },
+            offset_map_offset: if sparse {
+                header.string_block_size as u64
+            } else {
+                0
+            },
+            id_list_size: if flags & FLAG_ID_LIST != 0 {
+                header.record_count * 4
+            } else {
+                0
+            },
+            copy_table_size,
+            relationship_data_size: 0,
+        };
 
-        let mut string_pos = HashMap::new();
-        let mut current_pos = 0;
-        for (idx, item) in strings.iter().enumerate() {
-            string_pos.insert(current_pos, idx);
-            current_pos += item.len() + 1;
-        }
-
-        Ok(Self {
-            version,
-            header,
-            header_size,
-            strings,
-            string_pos,
-        })
+        let mut wdb = Self::empty(version, header, header_size);
+        wdb.read_section(reader, &section)?;
+        Ok(wdb)
     }
 
     pub fn get_string(&self, index: usize) -> Option<&String> {
         self.strings.get(index)
     }
 
-    pub fn get_string_by_offset(&self, offset: usize) -> Option<&String> {
-        if let Some(item) = self.string_pos.get(&offset) {
-            self.get_string(*item)
+    /// String at an offset of the string block. Some files share the suffix
+    /// of a string with an offset into the middle of it, which gives the
+    /// rest of the string.
+    pub fn get_string_by_offset(&self, offset: usize) -> Option<&str> {
+        let (start, item) = self.string_pos.range(..=offset).next_back()?;
+        self.get_string(*item)?.get(offset - start..)
+    }
+
+    /// String of a field at `field_offset` in the record, with the offset
+    /// stored in the field
+    pub fn get_record_string(
+        &self,
+        record: &RecordInfo,
+        field_offset: usize,
+        offset: u32,
+    ) -> Option<&str> {
+        if self.version >= DbcVersion::WDC2 {
+            self.get_string_by_offset(record.offset as usize + field_offset + offset as usize)
         } else {
-            None
+            self.get_string_by_offset(offset as usize)
         }
     }
 
     pub fn records_start_offset(&self) -> u64 {
         self.header_size as u64
     }
+
+    pub fn flags(&self) -> u32 {
+        match self.header.flags {
+            VGTE4::Some(flags) => flags,
+            VGTE4::None => 0,
+        }
+    }
+
+    /// Index of the id field among the fields stored in the records
+    pub fn id_index(&self) -> u32 {
+        match self.header.id_index {
+            VGTE5::Some(id_index) => id_index,
+            VGTE5::None => 0,
+        }
+    }
+
+    pub fn record_layout(&self) -> RecordLayout {
+        if self.version >= DbcVersion::WDB4 && self.flags() & FLAG_OFFSET_MAP != 0 {
+            RecordLayout::Sparse
+        } else if !self.storage_info.is_empty() {
+            RecordLayout::Packed
+        } else if !self.field_structures.is_empty() {
+            RecordLayout::Structured
+        } else {
+            RecordLayout::Plain
+        }
+    }
 }
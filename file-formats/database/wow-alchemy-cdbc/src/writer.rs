@@ -0,0 +1,694 @@
+use std::collections::{HashMap, HashSet};
+
+use wow_alchemy_data::prelude::*;
+
+use crate::dbd::{DbdFile, GameBuild};
+use crate::wdb::{VGTE4, VGTE5, Wdb2Fields, WdbHeader};
+use crate::{DbcValue, DbcVersion, Error, FieldType, Locale, Record, Result, Value, WdbFile};
+
+/// String block of a DBC file being built. Strings are stored once and the
+/// empty string is always at offset 0, as the client expects.
+#[derive(Debug, Clone)]
+pub struct StringBlock {
+    data: Vec<u8>,
+    offsets: HashMap<String, u32>,
+    /// Reversed strings of a block read from a file with the offset of their
+    /// end, sorted to find a string that ends with another one
+    suffixes: Vec<(Vec<u8>, u32)>,
+}
+
+impl Default for StringBlock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StringBlock {
+    /// Empty string block containing only the empty string
+    pub fn new() -> Self {
+        Self {
+            data: vec![0],
+            offsets: HashMap::from([(String::new(), 0)]),
+            suffixes: Vec::new(),
+        }
+    }
+
+    /// Start from the string block of an existing file, keeping its exact
+    /// layout. Strings already present keep their offsets, also the ones
+    /// only found at the end of another string, and new ones are appended
+    /// at the end.
+    pub fn from_wdb(wdb: &WdbFile) -> Self {
+        if wdb.header.string_block_size == 0 {
+            return Self::new();
+        }
+
+        let mut data = Vec::with_capacity(wdb.header.string_block_size as usize);
+        let mut offsets = HashMap::new();
+        let mut suffixes = Vec::with_capacity(wdb.strings.len());
+        for (idx, string) in wdb.strings.iter().enumerate() {
+            if idx > 0 {
+                data.push(0);
+            }
+            offsets.entry(string.clone()).or_insert(data.len() as u32);
+            data.extend_from_slice(string.as_bytes());
+            suffixes.push((string.bytes().rev().collect(), data.len() as u32));
+        }
+        suffixes.sort_unstable();
+
+        Self {
+            data,
+            offsets,
+            suffixes,
+        }
+    }
+
+    /// Offset of a string at the end of a string of the file
+    fn find_suffix(&self, value: &str) -> Option<u32> {
+        let reversed: Vec<u8> = value.bytes().rev().collect();
+        let index = self
+            .suffixes
+            .partition_point(|(string, _)| string.as_slice() < reversed.as_slice());
+        let (string, end) = self.suffixes.get(index)?;
+        string
+            .starts_with(&reversed)
+            .then(|| end - value.len() as u32)
+    }
+
+    /// Add a string and return its offset in the block. A string that is
+    /// already in the block is not added again.
+    pub fn add(&mut self, value: &str) -> u32 {
+        if let Some(offset) = self.offsets.get(value) {
+            return *offset;
+        }
+        if let Some(offset) = self.find_suffix(value) {
+            self.offsets.insert(value.to_owned(), offset);
+            return offset;
+        }
+
+        let offset = self.data.len() as u32;
+        self.data.extend_from_slice(value.as_bytes());
+        self.data.push(0);
+        self.offsets.insert(value.to_owned(), offset);
+        offset
+    }
+
+    /// Raw bytes of the block as they are stored in the file
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Most entries of the id index of WDB2 files, which has one for every id
+/// from the smallest to the largest
+pub const WDB2_MAX_INDEX_ENTRIES: usize = 1 << 20;
+
+/// Writes records to WDBC or WDB2 files using the field layout of a DBD
+/// definition, the inverse of [`crate::LazyRecordIterator`].
+///
+/// Values don't need to match the field types exactly, so rows coming from
+/// other sources can be written: integers are stored in fields of any size
+/// they fit in, floats also accept integers they represent exactly and bools
+/// accept 0 and 1. Other values fail with [`Error::InvalidValue`].
+#[derive(Debug)]
+pub struct WdbWriter<'a> {
+    dbd: &'a DbdFile,
+    /// Format of the file, only WDBC and WDB2 can be written
+    pub version: DbcVersion,
+    /// WDB2 hash of the table name
+    pub table_hash: u32,
+    /// WDB2 build of the client
+    pub build: u32,
+    /// WDB2 timestamp
+    pub timestamp_last_written: u32,
+    /// WDB2 locale of the strings
+    pub locale: u32,
+    /// Size of the records when it is larger than their fields, like in
+    /// files of some clients that pad them. `None` is the size of the fields.
+    pub record_size: Option<u32>,
+    /// Block that the strings of the records are added to
+    pub strings: StringBlock,
+}
+
+impl<'a> WdbWriter<'a> {
+    /// Create a writer with empty header fields and string block
+    pub fn new(dbd: &'a DbdFile, version: DbcVersion) -> Self {
+        Self {
+            dbd,
+            version,
+            table_hash: 0,
+            build: 0,
+            timestamp_last_written: 0,
+            locale: 0,
+            record_size: None,
+            strings: StringBlock::new(),
+        }
+    }
+
+    /// Create a writer for the format of a table in the client of a build,
+    /// see [`DbcVersion::for_build`]
+    pub fn for_build(dbd: &'a DbdFile, game_build: &GameBuild, db2: bool) -> Self {
+        let mut writer = Self::new(dbd, DbcVersion::for_build(game_build, db2));
+        writer.build = game_build.build;
+        writer
+    }
+
+    /// Create a writer with the version and header fields of an existing
+    /// file. The string block starts empty, set `strings` to
+    /// [`StringBlock::from_wdb`] to preserve its layout.
+    pub fn from_wdb(dbd: &'a DbdFile, wdb: &WdbFile) -> Self {
+        let mut writer = Self::new(dbd, wdb.version);
+        writer.record_size = Some(wdb.header.record_size);
+        if let Wdb2Fields::Fields {
+            table_hash,
+            build,
+            timestamp_last_written,
+            locale,
+            ..
+        } = wdb.header.wdb2
+        {
+            writer.table_hash = table_hash;
+            writer.build = build;
+            writer.timestamp_last_written = timestamp_last_written;
+            writer.locale = locale;
+        }
+        writer
+    }
+
+    /// Write header, records and string block
+    pub fn write<W: Write>(mut self, writer: &mut W, records: &[Record]) -> Result<()> {
+        if !self.version.is_writable() {
+            return Err(Error::UnsupportedVersion(self.version));
+        }
+
+        let field_types = self
+            .dbd
+            .build
+            .fields
+            .iter()
+            .map(|field| {
+                let count = if field.is_array {
+                    field.array_size.unwrap_or(0)
+                } else {
+                    1
+                };
+                Ok((self.dbd.field_type(field)?, count))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let field_count: usize = field_types
+            .iter()
+            .map(|(field_type, count)| match field_type {
+                FieldType::LocString(locales) => (locales + 1) * count,
+                _ => *count,
+            })
+            .sum();
+        let fields_size: usize = field_types
+            .iter()
+            .map(|(field_type, count)| field_type.size() * count)
+            .sum();
+        let record_size = match self.record_size {
+            Some(size) if (size as usize) < fields_size => {
+                return Err(Error::InvalidValue(
+                    "record".into(),
+                    format!("record size {size} is smaller than the {fields_size} bytes of fields"),
+                ));
+            }
+            Some(size) => size as usize,
+            None => fields_size,
+        };
+
+        let key = self.dbd.build.fields.iter().position(|field| field.is_key);
+        let key_index = key.unwrap_or(0);
+        let mut seen_ids = HashSet::new();
+
+        let mut ids = Vec::with_capacity(records.len());
+        let mut string_lengths = Vec::with_capacity(records.len());
+        let mut record_data = Vec::with_capacity(record_size * records.len());
+        for record in records {
+            if record.len() != field_types.len() {
+                return Err(Error::InvalidValue(
+                    "record".into(),
+                    format!(
+                        "expected {} values, got {}",
+                        field_types.len(),
+                        record.len()
+                    ),
+                ));
+            }
+
+            let mut row_string_length = 0;
+            for ((value, (field_type, count)), field) in
+                record.iter().zip(&field_types).zip(&self.dbd.build.fields)
+            {
+                let values = if field.is_array {
+                    match value {
+                        Value::Array(values) if values.len() == *count => values.as_slice(),
+                        _ => {
+                            return Err(Error::InvalidValue(
+                                field.name.clone(),
+                                format!("expected array of {count} values, got {value}"),
+                            ));
+                        }
+                    }
+                } else {
+                    std::slice::from_ref(value)
+                };
+
+                for value in values {
+                    match value {
+                        Value::String(Some(string)) => row_string_length += string.len(),
+                        Value::LocString { strings, .. } => {
+                            row_string_length +=
+                                strings.iter().flatten().map(|v| v.len()).sum::<usize>()
+                        }
+                        _ => {}
+                    }
+                    write_value(
+                        &mut record_data,
+                        value,
+                        *field_type,
+                        &mut self.strings,
+                        &field.name,
+                    )?;
+                }
+            }
+
+            record_data.resize(record_data.len() + record_size - fields_size, 0);
+
+            let id = record.get(key_index).and_then(Value::as_i64).unwrap_or(0) as u32;
+            if key.is_some() && !seen_ids.insert(id) {
+                return Err(Error::DuplicateKey(id));
+            }
+            ids.push(id);
+            string_lengths.push(row_string_length.min(u16::MAX as usize) as u16);
+        }
+
+        let min_id = ids.iter().copied().min().unwrap_or(0);
+        let max_id = ids.iter().copied().max().unwrap_or(0);
+
+        let header = WdbHeader {
+            record_count: records.len() as u32,
+            field_count: field_count as u32,
+            record_size: record_size as u32,
+            string_block_size: self.strings.as_bytes().len() as u32,
+            wdb2: if self.version == DbcVersion::WDB2 {
+                Wdb2Fields::Fields {
+                    table_hash: self.table_hash,
+                    build: self.build,
+                    timestamp_last_written: self.timestamp_last_written,
+                    min_id,
+                    max_id,
+                    locale: self.locale,
+                    copy_table_size: 0,
+                }
+            } else {
+                Wdb2Fields::None
+            },
+            flags: VGTE4::None,
+            id_index: VGTE5::None,
+        };
+
+        let table_len = (max_id - min_id) as usize + 1;
+        if header.index_table_size() > 0 && table_len > WDB2_MAX_INDEX_ENTRIES {
+            return Err(Error::InvalidValue(
+                "ID".into(),
+                format!(
+                    "ids {min_id} to {max_id} need an index of {table_len} entries, more than {WDB2_MAX_INDEX_ENTRIES}"
+                ),
+            ));
+        }
+
+        writer.wow_write(&self.version)?;
+        writer.wow_write(&header)?;
+
+        if header.index_table_size() > 0 {
+            let mut indices = vec![0u32; table_len];
+            let mut lengths = vec![0u16; table_len];
+            for (row, (id, length)) in ids.iter().zip(&string_lengths).enumerate() {
+                indices[(id - min_id) as usize] = row as u32;
+                lengths[(id - min_id) as usize] = *length;
+            }
+            for index in indices {
+                writer.wow_write(&index)?;
+            }
+            for length in lengths {
+                writer.wow_write(&length)?;
+            }
+        }
+
+        writer.write_all(&record_data)?;
+        writer.write_all(self.strings.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+fn write_value<W: Write>(
+    writer: &mut W,
+    value: &Value,
+    field_type: FieldType,
+    string_block: &mut StringBlock,
+    field_name: &str,
+) -> Result<()> {
+    let invalid = || {
+        Error::InvalidValue(
+            field_name.to_owned(),
+            format!("{value} can't be stored as {field_type:?}"),
+        )
+    };
+
+    match field_type {
+        FieldType::String => {
+            let offset = match value {
+                Value::String(Some(string)) => string_block.add(string),
+                Value::String(None) => 0,
+                Value::LocString { .. } => match value.localized(Locale::EnUS) {
+                    Some(string) => string_block.add(string),
+                    None => 0,
+                },
+                _ => return Err(invalid()),
+            };
+            writer.wow_write(&offset)?;
+        }
+        FieldType::LocString(locales) => {
+            // A plain string is stored as the string of the first locale
+            let (strings, flags) = match value {
+                Value::LocString { strings, flags } => (strings.clone(), *flags),
+                Value::String(string) => {
+                    let mut strings = vec![None; locales];
+                    strings[0] = string.clone();
+                    (strings, 0)
+                }
+                _ => return Err(invalid()),
+            };
+            if strings.len() != locales {
+                return Err(Error::InvalidValue(
+                    field_name.to_owned(),
+                    format!("expected {locales} locales, got {}", strings.len()),
+                ));
+            }
+            for string in &strings {
+                let offset = match string {
+                    Some(string) => string_block.add(string),
+                    None => 0,
+                };
+                writer.wow_write(&offset)?;
+            }
+            writer.wow_write(&flags)?;
+        }
+        FieldType::Float32 => {
+            let value = match value {
+                Value::Float32(v) => *v,
+                _ => i64::from_value(value)
+                    .filter(|v| *v as f32 as i64 == *v)
+                    .ok_or_else(invalid)? as f32,
+            };
+            writer.wow_write(&value)?;
+        }
+        FieldType::Bool => {
+            let value = match value {
+                Value::Bool(v) => *v as u32,
+                _ => u32::from_value(value)
+                    .filter(|v| *v <= 1)
+                    .ok_or_else(invalid)?,
+            };
+            writer.wow_write(&value)?;
+        }
+        FieldType::Int32 => writer.wow_write(&i32::from_value(value).ok_or_else(invalid)?)?,
+        FieldType::UInt32 => writer.wow_write(&u32::from_value(value).ok_or_else(invalid)?)?,
+        FieldType::UInt8 => writer.wow_write(&u8::from_value(value).ok_or_else(invalid)?)?,
+        FieldType::Int8 => writer.wow_write(&i8::from_value(value).ok_or_else(invalid)?)?,
+        FieldType::UInt16 => writer.wow_write(&u16::from_value(value).ok_or_else(invalid)?)?,
+        FieldType::Int16 => writer.wow_write(&i16::from_value(value).ok_or_else(invalid)?)?,
+        FieldType::Int64 => writer.wow_write(&i64::from_value(value).ok_or_else(invalid)?)?,
+        FieldType::UInt64 => writer.wow_write(&u64::from_value(value).ok_or_else(invalid)?)?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::dbd::{GameBuild, parse_dbd_content};
+    use crate::{LazyRecordIterator, Locale};
+
+    const DBD: &str = "COLUMNS
+int ID
+string Name
+float Scale
+int<Other::ID> OtherID
+int Flags
+
+BUILD 4.0.0.12911
+$id$ID<32>
+Name
+Scale
+OtherID<u16>
+Flags<8>[3]
+";
+
+    fn records() -> Vec<Record> {
+        vec![
+            vec![
+                Value::Int32(3),
+                Value::String(Some("foo".into())),
+                Value::Float32(1.5),
+                Value::UInt16(7),
+                Value::Array(vec![Value::Int8(1), Value::Int8(-2), Value::Int8(3)]),
+            ],
+            vec![
+                Value::Int32(5),
+                Value::String(Some("".into())),
+                Value::Float32(-0.25),
+                Value::UInt16(65535),
+                Value::Array(vec![Value::Int8(0), Value::Int8(0), Value::Int8(0)]),
+            ],
+            vec![
+                Value::Int32(8),
+                Value::String(Some("foo".into())),
+                Value::Float32(0.0),
+                Value::UInt16(0),
+                Value::Array(vec![Value::Int8(4), Value::Int8(5), Value::Int8(6)]),
+            ],
+        ]
+    }
+
+    fn read_back(bytes: &[u8], dbd: &DbdFile) -> (WdbFile, Vec<Record>) {
+        let mut cursor = Cursor::new(bytes);
+        let wdb = WdbFile::wow_read(&mut cursor).expect("read wdb");
+        let records = LazyRecordIterator::new(&mut cursor, dbd, &wdb)
+            .expect("iterator")
+            .collect::<Result<Vec<_>>>()
+            .expect("records");
+        (wdb, records)
+    }
+
+    #[test]
+    fn test_string_block_dedup() {
+        let mut block = StringBlock::new();
+        assert_eq!(block.add(""), 0);
+        assert_eq!(block.add("foo"), 1);
+        assert_eq!(block.add("bar"), 5);
+        assert_eq!(block.add("foo"), 1);
+        assert_eq!(block.as_bytes(), b"\0foo\0bar\0");
+    }
+
+    #[test]
+    fn test_version_for_build() {
+        let version = |build: &str, db2| {
+            let game_build: GameBuild = build.parse().unwrap();
+            DbcVersion::for_build(&game_build, db2)
+        };
+        assert_eq!(version("3.3.5a", false), DbcVersion::WDBC);
+        assert_eq!(version("3.3.5a", true), DbcVersion::WDBC);
+        assert_eq!(version("4.3.4", false), DbcVersion::WDBC);
+        assert_eq!(version("4.3.4", true), DbcVersion::WDB2);
+        assert_eq!(version("7.2.5.24742", true), DbcVersion::WDB5);
+        assert_eq!(version("7.3.5.26972", true), DbcVersion::WDC1);
+        assert_eq!(version("8.0.1.28153", true), DbcVersion::WDC2);
+
+        let game_build: GameBuild = "4.0.0.12911".parse().unwrap();
+        let dbd = parse_dbd_content(&game_build, DBD).expect("dbd");
+        let writer = WdbWriter::for_build(&dbd, &game_build, true);
+        assert_eq!((writer.version, writer.build), (DbcVersion::WDB2, 12911));
+    }
+
+    #[test]
+    fn test_write_roundtrip() {
+        let game_build: GameBuild = "4.0.0.12911".try_into().unwrap();
+        let dbd = parse_dbd_content(&game_build, DBD).expect("dbd");
+
+        for version in [DbcVersion::WDBC, DbcVersion::WDB2] {
+            let mut writer = WdbWriter::new(&dbd, version);
+            writer.table_hash = 0x1234_5678;
+            writer.build = 12911;
+            let mut bytes = Vec::new();
+            writer.write(&mut bytes, &records()).expect("write");
+
+            let (wdb, read) = read_back(&bytes, &dbd);
+            assert_eq!(wdb.header.record_size, 4 + 4 + 4 + 2 + 3);
+            assert_eq!(wdb.header.field_count, 7);
+            assert_eq!(wdb.header.string_block_size, 5);
+            if let Wdb2Fields::Fields {
+                table_hash,
+                min_id,
+                max_id,
+                ..
+            } = wdb.header.wdb2
+            {
+                assert_eq!(version, DbcVersion::WDB2);
+                assert_eq!((table_hash, min_id, max_id), (0x1234_5678, 3, 8));
+                assert_eq!(wdb.header.index_table_size(), 6 * 6);
+            }
+            assert_eq!(read, records());
+
+            let mut rewritten = Vec::new();
+            let mut writer = WdbWriter::from_wdb(&dbd, &wdb);
+            writer.strings = StringBlock::from_wdb(&wdb);
+            writer.write(&mut rewritten, &read).expect("rewrite");
+            assert_eq!(bytes, rewritten);
+        }
+    }
+
+    #[test]
+    fn test_rewrite_padded_records_and_suffix_strings() {
+        let game_build: GameBuild = "4.0.0.12911".try_into().unwrap();
+        let dbd = parse_dbd_content(&game_build, DBD).expect("dbd");
+        let mut writer = WdbWriter::new(&dbd, DbcVersion::WDBC);
+        writer.record_size = Some(20);
+        let mut bytes = Vec::new();
+        writer.write(&mut bytes, &records()).expect("write");
+        // Name of the second record at the "oo" of "foo", after the header
+        // and the first record
+        bytes[20 + 20 + 4..20 + 20 + 8].copy_from_slice(&2u32.to_le_bytes());
+
+        let (wdb, read) = read_back(&bytes, &dbd);
+        assert_eq!(wdb.header.record_size, 20);
+        assert_eq!(read[1][1], Value::String(Some("oo".into())));
+        assert_eq!(read[2], records()[2]);
+
+        let mut rewritten = Vec::new();
+        let mut writer = WdbWriter::from_wdb(&dbd, &wdb);
+        writer.strings = StringBlock::from_wdb(&wdb);
+        writer.write(&mut rewritten, &read).expect("rewrite");
+        assert_eq!(bytes, rewritten);
+
+        let mut writer = WdbWriter::new(&dbd, DbcVersion::WDBC);
+        writer.record_size = Some(16);
+        assert!(matches!(
+            writer.write(&mut Vec::new(), &records()),
+            Err(Error::InvalidValue(..))
+        ));
+    }
+
+    #[test]
+    fn test_write_invalid_ids() {
+        let game_build: GameBuild = "4.0.0.12911".try_into().unwrap();
+        let dbd = parse_dbd_content(&game_build, DBD).expect("dbd");
+        let mut records = records();
+        records[2][0] = Value::Int32(3);
+        let result = WdbWriter::new(&dbd, DbcVersion::WDBC).write(&mut Vec::new(), &records);
+        assert!(matches!(result, Err(Error::DuplicateKey(3))));
+
+        // Too far apart for the WDB2 index, but fine without one
+        let mut records = self::records();
+        records[2][0] = Value::Int32(i32::MAX);
+        let mut writer = WdbWriter::new(&dbd, DbcVersion::WDB2);
+        writer.build = 12911;
+        let result = writer.write(&mut Vec::new(), &records);
+        assert!(matches!(result, Err(Error::InvalidValue(field, _)) if field == "ID"));
+        WdbWriter::new(&dbd, DbcVersion::WDBC)
+            .write(&mut Vec::new(), &records)
+            .expect("write");
+    }
+
+    #[test]
+    fn test_write_invalid_value() {
+        let game_build: GameBuild = "4.0.0.12911".try_into().unwrap();
+        let dbd = parse_dbd_content(&game_build, DBD).expect("dbd");
+        let mut records = records();
+        records[1][1] = Value::Int32(1);
+        let result = WdbWriter::new(&dbd, DbcVersion::WDBC).write(&mut Vec::new(), &records);
+        assert!(matches!(result, Err(Error::InvalidValue(field, _)) if field == "Name"));
+    }
+
+    #[test]
+    fn test_write_out_of_range_value() {
+        let game_build: GameBuild = "4.0.0.12911".try_into().unwrap();
+        let dbd_content = "COLUMNS
+int ID
+int Level
+float Scale
+
+BUILD 4.0.0.12911
+$id$ID<u32>
+Level<u8>
+Scale
+";
+        let dbd = parse_dbd_content(&game_build, dbd_content).expect("dbd");
+        let write = |id: i64, level: i64, scale: Value| {
+            let record = vec![Value::Int64(id), Value::Int64(level), scale];
+            WdbWriter::new(&dbd, DbcVersion::WDBC).write(&mut Vec::new(), &[record])
+        };
+
+        assert!(write(1, 255, Value::Float32(1.0)).is_ok());
+        assert!(matches!(
+            write(1, 256, Value::Float32(1.0)),
+            Err(Error::InvalidValue(field, _)) if field == "Level"
+        ));
+        assert!(matches!(
+            write(-1, 1, Value::Float32(1.0)),
+            Err(Error::InvalidValue(field, _)) if field == "ID"
+        ));
+        assert!(write(1, 1, Value::Int32(3)).is_ok());
+        assert!(matches!(
+            write(1, 1, Value::Int32(16_777_217)),
+            Err(Error::InvalidValue(field, _)) if field == "Scale"
+        ));
+    }
+
+    #[test]
+    fn test_locstring_layout() {
+        let dbd_content = "COLUMNS
+int ID
+locstring Name_lang
+int Value
+
+BUILD 1.12.1.5875
+$id$ID<32>
+Name_lang
+Value<32>
+";
+        for (build, locales) in [("1.12.1.5875", 8), ("3.3.5.12340", 16), ("4.0.0.12911", 1)] {
+            let game_build: GameBuild = build.try_into().unwrap();
+            let dbd = parse_dbd_content(&game_build, &dbd_content.replace("1.12.1.5875", build))
+                .expect("dbd");
+            let name = if locales == 1 {
+                Value::String(Some("name".into()))
+            } else {
+                let mut strings = vec![Some(String::new()); locales];
+                strings[Locale::DeDE.index()] = Some("name".into());
+                Value::LocString {
+                    strings,
+                    flags: 0x1F,
+                }
+            };
+            let records = vec![vec![Value::Int32(1), name, Value::Int32(-7)]];
+
+            let mut bytes = Vec::new();
+            WdbWriter::new(&dbd, DbcVersion::WDBC)
+                .write(&mut bytes, &records)
+                .expect("write");
+
+            let (wdb, read) = read_back(&bytes, &dbd);
+            let expected_size = if locales == 1 { 12 } else { 4 * (locales + 3) };
+            assert_eq!(wdb.header.record_size as usize, expected_size);
+            assert_eq!(read, records);
+            assert_eq!(read[0][1].localized(Locale::EnUS), Some("name"));
+            assert_eq!(read[0][2], Value::Int32(-7));
+        }
+    }
+}
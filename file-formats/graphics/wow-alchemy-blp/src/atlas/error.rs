@@ -0,0 +1,56 @@
+use thiserror::Error;
+
+/// Errors that can occur when packing or splitting atlases
+#[derive(Debug, Error)]
+pub enum Error {
+    /// There are no images to pack
+    #[error("No images to pack into atlas")]
+    Empty,
+    /// Images don't fit into an atlas of the maximum allowed size
+    #[error("Images don't fit into an atlas of at most {0}x{0} pixels")]
+    TooLarge(u32),
+    /// Maximum atlas size is zero or larger than BLP images can be
+    #[error("Maximum atlas size {0} is not between 1 and {max}", max = crate::types::BLP_MAX_WIDTH)]
+    InvalidMaxSize(u32),
+    /// Two images have the same name, so their entries would clash
+    #[error("Several images are named {0}")]
+    DuplicateName(String),
+    /// Alignment must be a power of two
+    #[error("Alignment {0} is not a power of two")]
+    InvalidAlignment(u32),
+    /// Grid tile size is zero or larger than the atlas
+    #[error("Invalid grid tile size {0}x{1} for atlas {2}x{3}")]
+    InvalidGrid(u32, u32, u32, u32),
+    /// Manifest entry lays outside of the atlas image
+    #[error(
+        "Entry {name} at {x},{y} {width}x{height} is outside of {atlas_width}x{atlas_height} atlas"
+    )]
+    OutOfBounds {
+        /// Name of the entry
+        name: String,
+        /// Left coordinate of the entry
+        x: u32,
+        /// Top coordinate of the entry
+        y: u32,
+        /// Width of the entry
+        width: u32,
+        /// Height of the entry
+        height: u32,
+        /// Width of the atlas
+        atlas_width: u32,
+        /// Height of the atlas
+        atlas_height: u32,
+    },
+    /// Failed to convert between BLP and image
+    #[error("Failed to convert image: {0}")]
+    Convert(#[from] crate::convert::Error),
+    /// Failed to (de)serialize the manifest
+    #[error("Invalid atlas manifest: {0}")]
+    Manifest(#[from] serde_json::Error),
+    /// Failed to parse a TOML manifest
+    #[error("Invalid atlas manifest: {0}")]
+    TomlManifest(#[from] toml::de::Error),
+    /// Failed to serialize a TOML manifest
+    #[error("Failed to write atlas manifest: {0}")]
+    TomlManifestWrite(#[from] toml::ser::Error),
+}
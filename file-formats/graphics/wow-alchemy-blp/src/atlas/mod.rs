@@ -0,0 +1,387 @@
+//! Packing of several images into a single texture atlas and cutting
+//! atlases back into separate images.
+//!
+//! Coordinates of the packed images are described by [AtlasManifest] that
+//! is stored as JSON or TOML next to the atlas texture.
+
+/// Atlas error types
+pub mod error;
+
+use crate::convert::{BlpTarget, FilterType, blp_to_image, image_to_blp};
+use crate::types::*;
+use ::image::{DynamicImage, GenericImageView, RgbaImage};
+pub use error::Error;
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Position of a single image inside of the atlas
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AtlasEntry {
+    /// Name of the image, usually the source file stem
+    pub name: String,
+    /// Left coordinate in pixels
+    pub x: u32,
+    /// Top coordinate in pixels
+    pub y: u32,
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+}
+
+/// Coordinates of all images packed into an atlas
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AtlasManifest {
+    /// Width of the atlas texture
+    pub width: u32,
+    /// Height of the atlas texture
+    pub height: u32,
+    /// Packed images
+    pub entries: Vec<AtlasEntry>,
+}
+
+impl AtlasManifest {
+    /// Manifest that cuts an atlas into a regular grid of tiles, named
+    /// `<row>_<column>`. Incomplete tiles at the right and bottom edges are
+    /// skipped.
+    pub fn grid(width: u32, height: u32, tile_width: u32, tile_height: u32) -> Result<Self, Error> {
+        if tile_width == 0 || tile_height == 0 || tile_width > width || tile_height > height {
+            return Err(Error::InvalidGrid(tile_width, tile_height, width, height));
+        }
+        let mut entries = vec![];
+        for row in 0..height / tile_height {
+            for column in 0..width / tile_width {
+                entries.push(AtlasEntry {
+                    name: format!("{row}_{column}"),
+                    x: column * tile_width,
+                    y: row * tile_height,
+                    width: tile_width,
+                    height: tile_height,
+                });
+            }
+        }
+        Ok(AtlasManifest {
+            width,
+            height,
+            entries,
+        })
+    }
+
+    /// Find entry by name
+    pub fn entry(&self, name: &str) -> Option<&AtlasEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    /// Serialize the manifest into pretty printed JSON
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse the manifest from JSON
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serialize the manifest into TOML
+    pub fn to_toml(&self) -> Result<String, Error> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Parse the manifest from TOML
+    pub fn from_toml(toml: &str) -> Result<Self, Error> {
+        Ok(toml::from_str(toml)?)
+    }
+}
+
+/// Options for [pack_images]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackOptions {
+    /// Pixels reserved around every image. The space is filled with
+    /// extruded edge pixels so filtering doesn't bleed neighbours in.
+    pub padding: u32,
+    /// Every image slot (including padding) starts and ends at a multiple
+    /// of this value, so the first `log2(alignment)` mipmap levels never mix
+    /// two images in one pixel or DXT block. Must be a power of two.
+    pub alignment: u32,
+    /// Maximum width and height of the atlas, at most [`BLP_MAX_WIDTH`]
+    pub max_size: u32,
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        PackOptions {
+            padding: 2,
+            alignment: 4,
+            max_size: 4096,
+        }
+    }
+}
+
+/// Pack images into a power of two atlas with a shelf packer
+pub fn pack_images(
+    images: &[(String, DynamicImage)],
+    options: &PackOptions,
+) -> Result<(DynamicImage, AtlasManifest), Error> {
+    if images.is_empty() {
+        return Err(Error::Empty);
+    }
+    if !options.alignment.is_power_of_two() {
+        return Err(Error::InvalidAlignment(options.alignment));
+    }
+    if !(1..=BLP_MAX_WIDTH.min(BLP_MAX_HEIGHT)).contains(&options.max_size) {
+        return Err(Error::InvalidMaxSize(options.max_size));
+    }
+    let mut names = HashSet::with_capacity(images.len());
+    if let Some((name, _)) = images.iter().find(|(name, _)| !names.insert(name)) {
+        return Err(Error::DuplicateName(name.clone()));
+    }
+
+    let slots: Vec<(u32, u32)> = images
+        .iter()
+        .map(|(_, image)| {
+            (
+                (image.width() + 2 * options.padding).next_multiple_of(options.alignment),
+                (image.height() + 2 * options.padding).next_multiple_of(options.alignment),
+            )
+        })
+        .collect();
+
+    // Tallest first gives the densest shelves
+    let mut order: Vec<usize> = (0..images.len()).collect();
+    order.sort_by_key(|&i| (std::cmp::Reverse(slots[i].1), std::cmp::Reverse(slots[i].0)));
+
+    let total_area: u64 = slots.iter().map(|&(w, h)| (w as u64) * (h as u64)).sum();
+    let (width, height, positions) = atlas_sizes(options.max_size)
+        .into_iter()
+        .filter(|&(w, h)| (w as u64) * (h as u64) >= total_area)
+        .find_map(|(w, h)| shelf_pack(&slots, &order, w, h).map(|p| (w, h, p)))
+        .ok_or(Error::TooLarge(options.max_size))?;
+    debug!("Packed {} images into {width}x{height} atlas", images.len());
+
+    let mut atlas = RgbaImage::new(width, height);
+    let mut entries = Vec::with_capacity(images.len());
+    for (i, (name, image)) in images.iter().enumerate() {
+        let (slot_x, slot_y) = positions[i];
+        let (slot_w, slot_h) = slots[i];
+        let rgba = image.to_rgba8();
+        let last_x = rgba.width().saturating_sub(1) as i64;
+        let last_y = rgba.height().saturating_sub(1) as i64;
+        if rgba.width() > 0 && rgba.height() > 0 {
+            for dy in 0..slot_h {
+                let sy = (dy as i64 - options.padding as i64).clamp(0, last_y) as u32;
+                for dx in 0..slot_w {
+                    let sx = (dx as i64 - options.padding as i64).clamp(0, last_x) as u32;
+                    atlas.put_pixel(slot_x + dx, slot_y + dy, *rgba.get_pixel(sx, sy));
+                }
+            }
+        }
+        entries.push(AtlasEntry {
+            name: name.clone(),
+            x: slot_x + options.padding,
+            y: slot_y + options.padding,
+            width: image.width(),
+            height: image.height(),
+        });
+    }
+
+    Ok((
+        DynamicImage::ImageRgba8(atlas),
+        AtlasManifest {
+            width,
+            height,
+            entries,
+        },
+    ))
+}
+
+/// Pack images into an atlas and encode it as BLP
+pub fn pack_blp(
+    images: &[(String, DynamicImage)],
+    options: &PackOptions,
+    make_mipmaps: bool,
+    target: BlpTarget,
+    mipmap_filter: FilterType,
+) -> Result<(BlpImage, AtlasManifest), Error> {
+    let (atlas, manifest) = pack_images(images, options)?;
+    let blp = image_to_blp(atlas, make_mipmaps, target, mipmap_filter)?;
+    Ok((blp, manifest))
+}
+
+/// Cut an atlas into images described by the manifest. If the atlas size
+/// differs from the manifest (e.g. a lower mipmap level), coordinates are
+/// scaled accordingly.
+pub fn unpack_image(
+    atlas: &DynamicImage,
+    manifest: &AtlasManifest,
+) -> Result<Vec<(String, DynamicImage)>, Error> {
+    let scale_x = atlas.width() as f64 / manifest.width.max(1) as f64;
+    let scale_y = atlas.height() as f64 / manifest.height.max(1) as f64;
+
+    let mut res = Vec::with_capacity(manifest.entries.len());
+    for entry in manifest.entries.iter() {
+        let x = (entry.x as f64 * scale_x) as u32;
+        let y = (entry.y as f64 * scale_y) as u32;
+        let width = ((entry.width as f64 * scale_x) as u32).max(1);
+        let height = ((entry.height as f64 * scale_y) as u32).max(1);
+        if x as u64 + width as u64 > atlas.width() as u64
+            || y as u64 + height as u64 > atlas.height() as u64
+        {
+            return Err(Error::OutOfBounds {
+                name: entry.name.clone(),
+                x,
+                y,
+                width,
+                height,
+                atlas_width: atlas.width(),
+                atlas_height: atlas.height(),
+            });
+        }
+        let tile = atlas.view(x, y, width, height).to_image();
+        res.push((entry.name.clone(), DynamicImage::ImageRgba8(tile)));
+    }
+    Ok(res)
+}
+
+/// Decode the BLP mipmap level and cut it into images described by the
+/// manifest
+pub fn unpack_blp(
+    blp: &BlpImage,
+    manifest: &AtlasManifest,
+    mipmap_level: usize,
+) -> Result<Vec<(String, DynamicImage)>, Error> {
+    let atlas = blp_to_image(blp, mipmap_level)?;
+    unpack_image(&atlas, manifest)
+}
+
+/// Candidate power of two atlas sizes, smallest and most square first
+fn atlas_sizes(max_size: u32) -> Vec<(u32, u32)> {
+    let mut sizes = vec![];
+    let powers = std::iter::successors(Some(1u32), |size| size.checked_mul(2))
+        .take_while(|&size| size <= max_size)
+        .collect::<Vec<_>>();
+    for &w in &powers {
+        for &h in &powers {
+            sizes.push((w, h));
+        }
+    }
+    sizes.sort_by_key(|&(w, h)| ((w as u64) * (h as u64), w.max(h), std::cmp::Reverse(w)));
+    sizes
+}
+
+fn shelf_pack(
+    slots: &[(u32, u32)],
+    order: &[usize],
+    width: u32,
+    height: u32,
+) -> Option<Vec<(u32, u32)>> {
+    let mut positions = vec![(0, 0); slots.len()];
+    let (mut x, mut y, mut shelf_height) = (0u32, 0u32, 0u32);
+    for &i in order {
+        let (w, h) = slots[i];
+        if w > width {
+            return None;
+        }
+        if x + w > width {
+            y += shelf_height;
+            x = 0;
+            shelf_height = 0;
+        }
+        if y + h > height {
+            return None;
+        }
+        positions[i] = (x, y);
+        x += w;
+        shelf_height = shelf_height.max(h);
+    }
+    Some(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::image::Rgba;
+
+    fn solid(width: u32, height: u32, value: u8) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+            width,
+            height,
+            Rgba([value, 255 - value, value / 2, 255]),
+        ))
+    }
+
+    #[test]
+    fn test_pack_unpack_roundtrip() {
+        let images = vec![
+            ("a".to_owned(), solid(30, 20, 10)),
+            ("b".to_owned(), solid(16, 16, 80)),
+            ("c".to_owned(), solid(7, 40, 160)),
+            ("d".to_owned(), solid(64, 3, 240)),
+        ];
+        let (atlas, manifest) = pack_images(&images, &PackOptions::default()).expect("pack");
+        assert!(atlas.width().is_power_of_two());
+        assert!(atlas.height().is_power_of_two());
+
+        for entry in manifest.entries.iter() {
+            assert_eq!((entry.x - 2) % 4, 0);
+            assert_eq!((entry.y - 2) % 4, 0);
+        }
+
+        let json = manifest.to_json().expect("json");
+        let manifest = AtlasManifest::from_json(&json).expect("json");
+        let toml = manifest.to_toml().expect("toml");
+        assert_eq!(AtlasManifest::from_toml(&toml).expect("toml"), manifest);
+        let tiles = unpack_image(&atlas, &manifest).expect("unpack");
+        for ((name, original), (tile_name, tile)) in images.iter().zip(tiles.iter()) {
+            assert_eq!(name, tile_name);
+            assert_eq!(original.to_rgba8(), tile.to_rgba8());
+        }
+    }
+
+    #[test]
+    fn test_too_large() {
+        let images = vec![("a".to_owned(), solid(100, 100, 0))];
+        let options = PackOptions {
+            max_size: 64,
+            ..Default::default()
+        };
+        assert!(matches!(
+            pack_images(&images, &options),
+            Err(Error::TooLarge(64))
+        ));
+
+        for max_size in [0, BLP_MAX_WIDTH + 1, u32::MAX] {
+            let options = PackOptions {
+                max_size,
+                ..Default::default()
+            };
+            assert!(matches!(
+                pack_images(&images, &options),
+                Err(Error::InvalidMaxSize(size)) if size == max_size
+            ));
+        }
+        assert_eq!(atlas_sizes(u32::MAX).len(), 32 * 32);
+    }
+
+    #[test]
+    fn test_duplicate_name() {
+        let images = vec![
+            ("a".to_owned(), solid(8, 8, 0)),
+            ("b".to_owned(), solid(8, 8, 0)),
+            ("a".to_owned(), solid(4, 4, 0)),
+        ];
+        assert!(matches!(
+            pack_images(&images, &PackOptions::default()),
+            Err(Error::DuplicateName(name)) if name == "a"
+        ));
+    }
+
+    #[test]
+    fn test_grid() {
+        let manifest = AtlasManifest::grid(64, 32, 16, 16).expect("grid");
+        assert_eq!(manifest.entries.len(), 8);
+        let entry = manifest.entry("1_3").expect("entry");
+        assert_eq!((entry.x, entry.y), (48, 16));
+        assert!(AtlasManifest::grid(64, 32, 0, 16).is_err());
+    }
+}
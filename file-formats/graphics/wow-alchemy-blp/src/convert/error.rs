@@ -38,4 +38,13 @@ pub enum Error {
     /// Failed to convert decompressed DXT1 data to raw format
     #[error("Failed to process bytes from DXT1 decomporession")]
     Dxt1RawConvertFail,
+    /// Failed to decode JPEG mipmap
+    #[error("Failed to decode JPEG: {0}")]
+    JpegDecode(#[from] jpeg_decoder::Error),
+    /// Failed to encode JPEG mipmap
+    #[error("Failed to encode JPEG: {0}")]
+    JpegEncode(#[from] jpeg_encoder::EncodingError),
+    /// Encoded JPEG has unexpected structure
+    #[error("Invalid JPEG structure: {0}")]
+    InvalidJpeg(String),
 }
@@ -2,16 +2,131 @@ use super::error::Error;
 use super::mipmap::generate_mipmaps;
 use crate::types::jpeg::MAX_JPEG_HEADER;
 use crate::types::*;
-use ::image::{DynamicImage, ImageFormat, ImageReader, RgbaImage, imageops::FilterType};
+use ::image::{DynamicImage, GrayImage, ImageBuffer, RgbImage, RgbaImage, imageops::FilterType};
+use jpeg_decoder::{ColorTransform, PixelFormat};
+use jpeg_encoder::{QuantizationTableType, SamplingFactor};
 use log::*;
-use std::io::Cursor;
+
+/// Sample luminance quantization table from Annex K of ITU-T T.81 in
+/// natural order. Blizzard files use a single table scaled by quality
+/// for all four components.
+const BASE_QUANTIZATION_TABLE: [u16; 64] = [
+    16, 11, 10, 16, 24, 40, 51, 61, //
+    12, 12, 14, 19, 26, 58, 60, 55, //
+    14, 13, 16, 24, 40, 57, 69, 56, //
+    14, 17, 22, 29, 51, 87, 80, 62, //
+    18, 22, 37, 56, 68, 109, 103, 77, //
+    24, 35, 55, 64, 81, 104, 113, 92, //
+    49, 64, 78, 87, 103, 121, 120, 101, //
+    72, 92, 95, 98, 112, 100, 103, 99, //
+];
+
+const MARKER_SOI: u8 = 0xD8;
+const MARKER_SOS: u8 = 0xDA;
+const MARKER_DQT: u8 = 0xDB;
+const MARKER_DHT: u8 = 0xC4;
+const MARKER_SOF0: u8 = 0xC0;
+
+/// Options of the JPEG encoder for BLP content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JpegOptions {
+    /// Quality of all levels from 1 to 100
+    pub quality: u8,
+    /// Quality override for each mipmap level starting from the top one.
+    /// Levels beyond the list use [JpegOptions::quality]. Different quality
+    /// means different quantization tables, so they can't be shared.
+    pub mipmap_quality: Vec<u8>,
+    /// Resolution of color channels relative to alpha
+    pub subsampling: JpegSubsampling,
+    /// Move tables that are equal for all levels into the shared JPEG
+    /// header as Blizzard's encoder does. Otherwise the shared header holds
+    /// only the start of image marker.
+    pub shared_header: bool,
+}
+
+impl Default for JpegOptions {
+    fn default() -> Self {
+        JpegOptions {
+            quality: 90,
+            mipmap_quality: vec![],
+            subsampling: Default::default(),
+            shared_header: true,
+        }
+    }
+}
+
+impl JpegOptions {
+    /// Quality of the given mipmap level
+    pub fn level_quality(&self, level: usize) -> u8 {
+        self.mipmap_quality
+            .get(level)
+            .copied()
+            .unwrap_or(self.quality)
+            .clamp(1, 100)
+    }
+}
+
+/// BLP JPEG stores raw BGRA components without a YCbCr transform, so
+/// subsampling reduces resolution of B, G and R channels while alpha stays
+/// at full resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum JpegSubsampling {
+    /// All channels at full resolution (4:4:4), as in original files
+    #[default]
+    None,
+    /// Color channels at half horizontal resolution (4:2:2)
+    Horizontal,
+    /// Color channels at half horizontal and vertical resolution (4:2:0)
+    Both,
+}
+
+impl From<JpegSubsampling> for SamplingFactor {
+    fn from(value: JpegSubsampling) -> Self {
+        match value {
+            JpegSubsampling::None => SamplingFactor::F_1_1,
+            JpegSubsampling::Horizontal => SamplingFactor::F_2_1,
+            JpegSubsampling::Both => SamplingFactor::F_2_2,
+        }
+    }
+}
 
 pub fn jpeg_to_image(image: &BlpJpeg, mipmap_level: usize) -> Result<DynamicImage, Error> {
     let raw_jpeg = image
         .full_jpeg(mipmap_level)
         .ok_or(Error::MissingImage(mipmap_level))?;
-    let jpeg = ImageReader::with_format(Cursor::new(raw_jpeg), ImageFormat::Jpeg).decode()?;
-    let mut rgba = jpeg.into_rgba8();
+
+    let mut decoder = jpeg_decoder::Decoder::new(raw_jpeg.as_slice());
+    decoder.read_info()?;
+    let info = decoder.info().ok_or(Error::MissingImage(mipmap_level))?;
+    // Four components are BGRA values as is. The decoder treats them as
+    // inverted CMYK, so revert the inversion. Anything else is decoded as
+    // ordinary JPEG.
+    if info.pixel_format == PixelFormat::CMYK32 {
+        decoder.set_color_transform(ColorTransform::CMYK);
+    }
+    let mut pixels = decoder.decode()?;
+    if info.pixel_format == PixelFormat::CMYK32 {
+        pixels.iter_mut().for_each(|v| *v = 255 - *v);
+    }
+    let (width, height) = (info.width as u32, info.height as u32);
+    let mismatch = || Error::MismatchSizes(mipmap_level, width, height, pixels.len());
+
+    let mut rgba = match info.pixel_format {
+        PixelFormat::CMYK32 => RgbaImage::from_raw(width, height, pixels.clone()),
+        PixelFormat::RGB24 => RgbImage::from_raw(width, height, pixels.clone())
+            .map(|image| DynamicImage::ImageRgb8(image).into_rgba8()),
+        PixelFormat::L8 => GrayImage::from_raw(width, height, pixels.clone())
+            .map(|image| DynamicImage::ImageLuma8(image).into_rgba8()),
+        PixelFormat::L16 => {
+            let values = pixels
+                .chunks_exact(2)
+                .map(|v| u16::from_be_bytes([v[0], v[1]]))
+                .collect();
+            ImageBuffer::from_raw(width, height, values)
+                .map(|image| DynamicImage::ImageLuma16(image).into_rgba8())
+        }
+    }
+    .ok_or_else(mismatch)?;
     switch_red_blue(&mut rgba);
     Ok(DynamicImage::ImageRgba8(rgba))
 }
@@ -21,6 +136,7 @@ pub fn image_to_jpeg(
     make_mipmaps: bool,
     mut alpha_bits: u8,
     mipmap_filter: FilterType,
+    options: &JpegOptions,
 ) -> Result<BlpJpeg, Error> {
     if alpha_bits != 0 && alpha_bits != 8 {
         warn!("Invalid alpha bits value for JPEG encoding {alpha_bits}, defaulting to 0");
@@ -32,28 +148,158 @@ pub fn image_to_jpeg(
         fill_opaque_alpha(&mut rgba);
     }
 
-    let mut images: Vec<Vec<u8>> = if make_mipmaps {
-        let images = generate_mipmaps(DynamicImage::ImageRgba8(rgba), mipmap_filter)?;
-        let jpeg_images: Result<Vec<Vec<u8>>, Error> = images
-            .into_iter()
-            .map(|image| {
-                let mut image_bytes = vec![];
-                image.write_to(&mut Cursor::new(&mut image_bytes), ImageFormat::Jpeg)?;
-                Ok(image_bytes)
-            })
-            .collect();
-        jpeg_images?
+    let levels = if make_mipmaps {
+        generate_mipmaps(DynamicImage::ImageRgba8(rgba), mipmap_filter)?
     } else {
-        let mut root_img = vec![];
-        rgba.write_to(&mut Cursor::new(&mut root_img), ImageFormat::Jpeg)?;
-        vec![root_img]
+        vec![DynamicImage::ImageRgba8(rgba)]
+    };
+    let mut images = levels
+        .iter()
+        .enumerate()
+        .map(|(i, level)| {
+            let quality = options.level_quality(i);
+            trace!("Encoding mipmap {i} with quality {quality}");
+            encode_bgra(&level.to_rgba8(), quality, options.subsampling)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let mut header = if options.shared_header {
+        fetch_common_header(&mut images)
+    } else {
+        for image in images.iter_mut() {
+            image.drain(0..2);
+        }
+        vec![0xFF, MARKER_SOI]
     };
-    let mut header = fetch_common_header(&mut images);
     // Add two padding bytes to the header as it always persists in War3 files
     header.extend(&vec![0; 2]);
     Ok(BlpJpeg { header, images })
 }
 
+/// Encode pixels as 4 component JPEG that stores B, G, R and A values
+/// without any color transform.
+fn encode_bgra(
+    bgra: &RgbaImage,
+    quality: u8,
+    subsampling: JpegSubsampling,
+) -> Result<Vec<u8>, Error> {
+    let width = u16::try_from(bgra.width()).map_err(|_| Error::WidthTooLarge(bgra.width()))?;
+    let height = u16::try_from(bgra.height()).map_err(|_| Error::HeightTooLarge(bgra.height()))?;
+
+    // The encoder stores CMYK inverted, as Adobe does, revert it beforehand
+    let inverted: Vec<u8> = bgra.as_raw().iter().map(|v| 255 - v).collect();
+    let table = QuantizationTableType::Custom(Box::new(scaled_quantization_table(quality)));
+
+    let mut output = vec![];
+    let mut encoder = jpeg_encoder::Encoder::new(&mut output, quality);
+    encoder.set_quantization_tables(table.clone(), table);
+    encoder.set_sampling_factor(subsampling.into());
+    encoder.encode(&inverted, width, height, jpeg_encoder::ColorType::Cmyk)?;
+
+    compact_segments(&output)
+}
+
+/// Scale base table by quality as libjpeg does
+fn scaled_quantization_table(quality: u8) -> [u16; 64] {
+    let quality = quality.clamp(1, 100) as u32;
+    let scale = if quality < 50 {
+        5000 / quality
+    } else {
+        200 - quality * 2
+    };
+    BASE_QUANTIZATION_TABLE.map(|v| ((v as u32 * scale + 50) / 100).clamp(1, 255) as u16)
+}
+
+/// Rewrite JPEG segments into the layout of Blizzard files: APP markers are
+/// dropped, duplicate quantization tables are merged, all tables go into
+/// single DQT and DHT segments placed before the frame header. That way the
+/// tables become a prefix shared by all mipmaps.
+fn compact_segments(jpeg: &[u8]) -> Result<Vec<u8>, Error> {
+    let invalid = |reason: &str| Error::InvalidJpeg(reason.to_owned());
+    if jpeg.len() < 4 || jpeg[0] != 0xFF || jpeg[1] != MARKER_SOI {
+        return Err(invalid("missing start of image"));
+    }
+
+    let mut quantization: Vec<(u8, Vec<u8>)> = vec![];
+    let mut huffman = vec![];
+    let mut other = vec![];
+    let mut frame = None;
+    let mut pos = 2;
+    let scan_start = loop {
+        if pos + 4 > jpeg.len() || jpeg[pos] != 0xFF {
+            return Err(invalid("malformed segment"));
+        }
+        let marker = jpeg[pos + 1];
+        let length = u16::from_be_bytes([jpeg[pos + 2], jpeg[pos + 3]]) as usize;
+        if length < 2 || pos + 2 + length > jpeg.len() {
+            return Err(invalid("segment exceeds data"));
+        }
+        let payload = &jpeg[pos + 4..pos + 2 + length];
+        match marker {
+            MARKER_SOS => break pos,
+            MARKER_DQT => {
+                for table in payload.chunks(65) {
+                    if table.len() != 65 || table[0] >> 4 != 0 {
+                        return Err(invalid("unsupported quantization table"));
+                    }
+                    quantization.push((table[0] & 0x0F, table[1..].to_vec()));
+                }
+            }
+            MARKER_DHT => huffman.extend_from_slice(payload),
+            MARKER_SOF0 => frame = Some(payload.to_vec()),
+            0xE0..=0xEF => {}
+            _ => other.push(&jpeg[pos..pos + 2 + length]),
+        }
+        pos += 2 + length;
+    };
+    let mut frame = frame.ok_or_else(|| invalid("missing frame header"))?;
+
+    // Point components to the first table with the same values
+    let mut remap = [0u8; 4];
+    let mut tables: Vec<(u8, Vec<u8>)> = vec![];
+    for (id, values) in quantization {
+        match tables.iter().find(|(_, v)| *v == values) {
+            Some((existing, _)) => remap[id as usize] = *existing,
+            None => {
+                remap[id as usize] = id;
+                tables.push((id, values));
+            }
+        }
+    }
+    let components = frame.get(5).copied().unwrap_or(0) as usize;
+    if frame.len() < 6 + components * 3 {
+        return Err(invalid("truncated frame header"));
+    }
+    for i in 0..components {
+        let table = &mut frame[6 + i * 3 + 2];
+        *table = remap[(*table & 0x03) as usize];
+    }
+
+    let mut output = Vec::with_capacity(jpeg.len());
+    output.extend([0xFF, MARKER_SOI]);
+    push_segment(
+        &mut output,
+        MARKER_DQT,
+        &tables
+            .iter()
+            .flat_map(|(id, values)| std::iter::once(*id).chain(values.iter().copied()))
+            .collect::<Vec<u8>>(),
+    );
+    push_segment(&mut output, MARKER_DHT, &huffman);
+    for segment in other {
+        output.extend_from_slice(segment);
+    }
+    push_segment(&mut output, MARKER_SOF0, &frame);
+    output.extend_from_slice(&jpeg[scan_start..]);
+    Ok(output)
+}
+
+fn push_segment(output: &mut Vec<u8>, marker: u8, payload: &[u8]) {
+    output.extend([0xFF, marker]);
+    output.extend(((payload.len() + 2) as u16).to_be_bytes());
+    output.extend_from_slice(payload);
+}
+
 fn switch_red_blue(image: &mut RgbaImage) {
     for pixel in image.pixels_mut() {
         let blue = pixel.0[0];
@@ -75,20 +321,25 @@ fn fill_opaque_alpha(image: &mut RgbaImage) {
     }
 }
 
-// Allows to get common part of all images and consider it as a 'JPEG header'
+// Allows to get common part of all images and consider it as a 'JPEG header'.
+// The header never crosses the start of scan, so a single image doesn't
+// end up dumped into the header.
 fn fetch_common_header(images: &mut [Vec<u8>]) -> Vec<u8> {
     let mut header = vec![];
     if images.is_empty() || images[0].is_empty() {
         return header;
     }
+    let limit = images
+        .iter()
+        .map(|image| scan_offset(image))
+        .min()
+        .unwrap_or(0)
+        .min(MAX_JPEG_HEADER);
     let mut common_bytes = 0;
-    'outer: for i in 0..MAX_JPEG_HEADER {
-        if images[0].len() <= i {
-            break;
-        }
+    'outer: for i in 0..limit {
         let current_byte = images[0][i];
         for image in images.iter() {
-            if image.len() <= i || image[i] != current_byte {
+            if image[i] != current_byte {
                 break 'outer;
             }
         }
@@ -103,3 +354,89 @@ fn fetch_common_header(images: &mut [Vec<u8>]) -> Vec<u8> {
     }
     header
 }
+
+/// Position of the start of scan marker or the length of data
+fn scan_offset(jpeg: &[u8]) -> usize {
+    let mut pos = 2;
+    while pos + 4 <= jpeg.len() && jpeg[pos] == 0xFF {
+        if jpeg[pos + 1] == MARKER_SOS {
+            return pos;
+        }
+        pos += 2 + u16::from_be_bytes([jpeg[pos + 2], jpeg[pos + 3]]) as usize;
+    }
+    jpeg.len().min(pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::image::Rgba;
+
+    fn gradient(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(width, height, |x, y| {
+            Rgba([
+                (x * 255 / width) as u8,
+                (y * 255 / height) as u8,
+                128,
+                ((x + y) * 255 / (width + height)) as u8,
+            ])
+        }))
+    }
+
+    #[test]
+    fn test_jpeg_keeps_alpha_and_channels() {
+        let image = gradient(64, 32);
+        for subsampling in [
+            JpegSubsampling::None,
+            JpegSubsampling::Horizontal,
+            JpegSubsampling::Both,
+        ] {
+            let options = JpegOptions {
+                quality: 95,
+                subsampling,
+                ..Default::default()
+            };
+            let jpeg =
+                image_to_jpeg(&image, true, 8, FilterType::Nearest, &options).expect("encode");
+            let decoded = jpeg_to_image(&jpeg, 0).expect("decode").to_rgba8();
+            let max_error = image
+                .to_rgba8()
+                .pixels()
+                .zip(decoded.pixels())
+                .flat_map(|(a, b)| a.0.into_iter().zip(b.0).map(|(a, b)| a.abs_diff(b)))
+                .max()
+                .unwrap_or(0);
+            assert!(max_error < 24, "{subsampling:?}: {max_error}");
+        }
+    }
+
+    #[test]
+    fn test_jpeg_shared_header() {
+        let image = gradient(32, 32);
+        let jpeg = image_to_jpeg(&image, true, 0, FilterType::Nearest, &Default::default())
+            .expect("encode");
+        // SOI, one quantization table and one Huffman segment are shared
+        assert_eq!(&jpeg.header[0..4], &[0xFF, MARKER_SOI, 0xFF, MARKER_DQT]);
+        // plus the part of frame header before the height
+        assert_eq!(jpeg.header.len(), 2 + 69 + 4 + 2 * 29 + 2 * 179 + 6 + 2);
+        let decoded = jpeg_to_image(&jpeg, 5).expect("decode");
+        assert_eq!((decoded.width(), decoded.height()), (1, 1));
+        assert!(decoded.to_rgba8().pixels().all(|p| p.0[3] == 0xFF));
+
+        // Different quality of mipmaps keeps quantization tables per image
+        let options = JpegOptions {
+            mipmap_quality: vec![90, 50],
+            ..Default::default()
+        };
+        let jpeg = image_to_jpeg(&image, true, 0, FilterType::Nearest, &options).expect("encode");
+        assert_eq!(
+            jpeg.header,
+            vec![0xFF, MARKER_SOI, 0xFF, MARKER_DQT, 0, 0x43, 0, 0, 0]
+        );
+
+        // Scan data of a single image doesn't get into the header
+        let jpeg = image_to_jpeg(&image, false, 0, FilterType::Nearest, &Default::default())
+            .expect("encode");
+        assert_eq!(&jpeg.images[0][0..2], &[0xFF, MARKER_SOS]);
+    }
+}
@@ -13,8 +13,8 @@ pub fn generate_mipmaps(
         if (width == 1 && height == 1) || mipmaps.len() >= 16 {
             break;
         }
-        let new_width = width >> 1;
-        let new_height = height >> 1;
+        let new_width = (width >> 1).max(1);
+        let new_height = (height >> 1).max(1);
         current_image = current_image.resize_exact(new_width, new_height, filter);
         mipmaps.push(current_image.clone());
     }
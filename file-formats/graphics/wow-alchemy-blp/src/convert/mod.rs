@@ -13,6 +13,7 @@ pub use ::image::imageops::FilterType;
 use dxtn::*;
 pub use error::Error;
 use jpeg::*;
+pub use jpeg::{JpegOptions, JpegSubsampling};
 use raw1::*;
 use raw3::*;
 use std::fmt;
@@ -73,12 +74,17 @@ pub enum BlpOldFormat {
     Jpeg {
         /// Whether the JPEG has an alpha channel
         has_alpha: bool,
+        /// Quality and layout of the encoded JPEG
+        options: JpegOptions,
     },
 }
 
 impl Default for BlpOldFormat {
     fn default() -> Self {
-        BlpOldFormat::Jpeg { has_alpha: true }
+        BlpOldFormat::Jpeg {
+            has_alpha: true,
+            options: Default::default(),
+        }
     }
 }
 
@@ -86,7 +92,7 @@ impl fmt::Display for BlpOldFormat {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             BlpOldFormat::Raw1 { alpha_bits } => write!(f, "Palleted image with {alpha_bits}"),
-            BlpOldFormat::Jpeg { has_alpha } => {
+            BlpOldFormat::Jpeg { has_alpha, .. } => {
                 if *has_alpha {
                     write!(f, "Jpeg image with alpha")
                 } else {
@@ -158,6 +164,8 @@ pub enum Blp2Format {
     Jpeg {
         /// Whether the JPEG has an alpha channel
         has_alpha: bool,
+        /// Quality and layout of the encoded JPEG
+        options: JpegOptions,
     },
     /// ST3C compression, type with 1 bit alpha or 0 bit alpha.
     Dxt1 {
@@ -196,7 +204,7 @@ impl fmt::Display for Blp2Format {
         match self {
             Blp2Format::Raw1 { alpha_bits } => write!(f, "Palleted image with {alpha_bits}"),
             Blp2Format::Raw3 => write!(f, "RGBA raw data"),
-            Blp2Format::Jpeg { has_alpha } => {
+            Blp2Format::Jpeg { has_alpha, .. } => {
                 if *has_alpha {
                     write!(f, "Jpeg image with alpha")
                 } else {
@@ -288,9 +296,10 @@ pub fn image_to_blp(
                     content: BlpContent::Raw1(blp_raw1),
                 })
             }
-            BlpOldFormat::Jpeg { has_alpha } => {
+            BlpOldFormat::Jpeg { has_alpha, options } => {
                 let alpha_bits = if has_alpha { 8 } else { 0 };
-                let blp_jpeg = image_to_jpeg(&image, make_mipmaps, alpha_bits, mipmap_filter)?;
+                let blp_jpeg =
+                    image_to_jpeg(&image, make_mipmaps, alpha_bits, mipmap_filter, &options)?;
                 Ok(BlpImage {
                     header: BlpHeader {
                         version: BlpVersion::Blp0,
@@ -331,9 +340,10 @@ pub fn image_to_blp(
                     content: BlpContent::Raw1(blp_raw1),
                 })
             }
-            BlpOldFormat::Jpeg { has_alpha } => {
+            BlpOldFormat::Jpeg { has_alpha, options } => {
                 let alpha_bits = if has_alpha { 8 } else { 0 };
-                let blp_jpeg = image_to_jpeg(&image, make_mipmaps, alpha_bits, mipmap_filter)?;
+                let blp_jpeg =
+                    image_to_jpeg(&image, make_mipmaps, alpha_bits, mipmap_filter, &options)?;
                 Ok(BlpImage {
                     header: BlpHeader {
                         version: BlpVersion::Blp1,
@@ -396,16 +406,17 @@ pub fn image_to_blp(
                     content: BlpContent::Raw3(blp_raw3),
                 })
             }
-            Blp2Format::Jpeg { has_alpha } => {
+            Blp2Format::Jpeg { has_alpha, options } => {
                 let alpha_bits = if has_alpha { 8 } else { 0 };
-                let blp_jpeg = image_to_jpeg(&image, make_mipmaps, alpha_bits, mipmap_filter)?;
+                let blp_jpeg =
+                    image_to_jpeg(&image, make_mipmaps, alpha_bits, mipmap_filter, &options)?;
                 Ok(BlpImage {
                     header: BlpHeader {
                         version: BlpVersion::Blp2,
                         content: BlpContentTag::Jpeg,
                         flags: BlpFlags::Blp2 {
                             compression: Compression::Jpeg,
-                            alpha_bits: 8,
+                            alpha_bits,
                             alpha_type: AlphaType::None,
                             has_mipmaps: if make_mipmaps { 1 } else { 0 },
                         },
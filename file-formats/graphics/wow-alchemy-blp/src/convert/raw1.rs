@@ -133,10 +133,12 @@ pub fn image_to_raw1(
         .ok_or(Error::MissingImage(0))?
         .into_rgba8();
     let indexed_alpha = index_alpha(&root_image, alpha_bits)?;
-    let (root_quantized, cmap, nq) = quantize_rgba(root_image)?;
+    let (root_quantized, mut cmap, nq) = quantize_rgba(root_image)?;
     if cmap.len() != 255 {
         return Err(Error::PaletteWrongSize(cmap.len()));
     }
+    // BLP always stores 256 colors, the last one is unused
+    cmap.push(0);
     images.push(Raw1Image {
         indexed_rgb: root_quantized,
         indexed_alpha,
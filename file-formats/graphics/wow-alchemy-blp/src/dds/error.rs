@@ -0,0 +1,54 @@
+use crate::types::CompressionType;
+use thiserror::Error;
+
+/// Errors that can occur when moving DXT blocks between BLP and DDS
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Only DXT1/DXT3/DXT5 BLP content can be stored in DDS without recompression
+    #[error("BLP content {0:?} cannot be copied into DDS, only DXT1/DXT3/DXT5 is supported")]
+    NotDxtn(CompressionType),
+    /// The BLP has no mipmap levels to write
+    #[error("BLP image has no mipmap levels")]
+    NoImages,
+    /// Invalid magic bytes at the start of DDS file
+    #[error("Unexpected magic value {0:?}. The file is not DDS.")]
+    WrongMagic([u8; 4]),
+    /// Header size field is not 124
+    #[error("Invalid DDS header size {0}, expected 124")]
+    InvalidHeaderSize(u32),
+    /// Legacy pixel format without a FourCC code (uncompressed RGB/luminance)
+    #[error("DDS pixel format is not block compressed (flags 0x{0:x})")]
+    NotCompressed(u32),
+    /// FourCC code that has no BLP counterpart
+    #[error("Unsupported DDS FourCC {0:?}")]
+    UnsupportedFourCC([u8; 4]),
+    /// DX10 DXGI format that has no BLP counterpart
+    #[error("Unsupported DXGI format {0}")]
+    UnsupportedDxgiFormat(u32),
+    /// DX10 resource that is not a single 2D texture
+    #[error(
+        "Only single 2D textures are supported, got dimension {dimension} with {array_size} layers"
+    )]
+    UnsupportedResource {
+        /// D3D10 resource dimension value
+        dimension: u32,
+        /// Count of elements in texture array
+        array_size: u32,
+    },
+    /// Image width exceeds the maximum supported value of 65,535 pixels
+    #[error("Maximum value for width is 65,535")]
+    WidthTooLarge(u32),
+    /// Image height exceeds the maximum supported value of 65,535 pixels
+    #[error("Maximum value for height is 65,535")]
+    HeightTooLarge(u32),
+    /// Block data of a mipmap is truncated
+    #[error("Mipmap {mipmap} needs {expected} bytes of blocks, but only {actual} left in file")]
+    UnexpectedEof {
+        /// Index of the truncated mipmap
+        mipmap: usize,
+        /// Size of block data expected for the level
+        expected: usize,
+        /// Bytes actually available
+        actual: usize,
+    },
+}
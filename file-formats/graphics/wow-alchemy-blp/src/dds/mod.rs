@@ -0,0 +1,400 @@
+//! Lossless conversion between DXT-compressed BLP and DDS containers.
+//!
+//! Both formats store S3TC blocks and mipmap chains verbatim, so the block
+//! data is copied as is in both directions and never goes through texpresso.
+
+/// DDS conversion error types
+pub mod error;
+
+use crate::types::*;
+pub use error::Error;
+use log::*;
+
+const DDS_MAGIC: [u8; 4] = *b"DDS ";
+const DDS_HEADER_SIZE: u32 = 124;
+const DDS_PIXELFORMAT_SIZE: u32 = 32;
+
+const DDSD_CAPS: u32 = 0x1;
+const DDSD_HEIGHT: u32 = 0x2;
+const DDSD_WIDTH: u32 = 0x4;
+const DDSD_PIXELFORMAT: u32 = 0x1000;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_LINEARSIZE: u32 = 0x80000;
+
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_FOURCC: u32 = 0x4;
+
+const DDSCAPS_COMPLEX: u32 = 0x8;
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+const DDSCAPS_MIPMAP: u32 = 0x400000;
+
+const FOURCC_DXT1: [u8; 4] = *b"DXT1";
+const FOURCC_DXT3: [u8; 4] = *b"DXT3";
+const FOURCC_DXT5: [u8; 4] = *b"DXT5";
+const FOURCC_DX10: [u8; 4] = *b"DX10";
+
+const DXGI_FORMAT_BC1_UNORM: u32 = 71;
+const DXGI_FORMAT_BC1_UNORM_SRGB: u32 = 72;
+const DXGI_FORMAT_BC2_UNORM: u32 = 74;
+const DXGI_FORMAT_BC2_UNORM_SRGB: u32 = 75;
+const DXGI_FORMAT_BC3_UNORM: u32 = 77;
+const DXGI_FORMAT_BC3_UNORM_SRGB: u32 = 78;
+
+const D3D10_RESOURCE_DIMENSION_TEXTURE2D: u32 = 3;
+
+const DDS_ALPHA_MODE_UNKNOWN: u32 = 0;
+const DDS_ALPHA_MODE_STRAIGHT: u32 = 1;
+const DDS_ALPHA_MODE_OPAQUE: u32 = 3;
+
+/// Which DDS header layout to produce in [blp_to_dds]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DdsHeaderFormat {
+    /// Use legacy FourCC header when it can describe the alpha channel
+    /// exactly, otherwise fall back to DX10 extended header. DXT3/DXT5
+    /// without alpha can only be expressed with DX10 alpha mode.
+    #[default]
+    Auto,
+    /// Always write the legacy `DXT1`/`DXT3`/`DXT5` FourCC header
+    FourCC,
+    /// Always write `DX10` extended header with DXGI BC1/BC2/BC3 format
+    Dx10,
+}
+
+/// Size in bytes of the DXT blocks covering a mipmap of given size
+pub fn dxtn_level_size(format: DxtnFormat, width: u32, height: u32) -> usize {
+    let blocks_x = width.div_ceil(4).max(1) as usize;
+    let blocks_y = height.div_ceil(4).max(1) as usize;
+    blocks_x * blocks_y * format.block_size()
+}
+
+/// Write DXT content of BLP image into DDS file bytes without recompression.
+/// Every mipmap level is copied as is.
+pub fn blp_to_dds(image: &BlpImage, header_format: DdsHeaderFormat) -> Result<Vec<u8>, Error> {
+    let (format, content) = match &image.content {
+        BlpContent::Dxt1(v) => (DxtnFormat::Dxt1, v),
+        BlpContent::Dxt3(v) => (DxtnFormat::Dxt3, v),
+        BlpContent::Dxt5(v) => (DxtnFormat::Dxt5, v),
+        _ => return Err(Error::NotDxtn(image.compression_type())),
+    };
+    let Some(first) = content.images.first() else {
+        return Err(Error::NoImages);
+    };
+
+    let has_alpha = image.header.alpha_bits() > 0;
+    let use_dx10 = match header_format {
+        DdsHeaderFormat::Auto => format != DxtnFormat::Dxt1 && !has_alpha,
+        DdsHeaderFormat::FourCC => false,
+        DdsHeaderFormat::Dx10 => true,
+    };
+    let mipmap_count = content.images.len() as u32;
+    trace!("Writing {format:?} DDS with {mipmap_count} mipmaps, DX10 header: {use_dx10}");
+
+    let mut flags = DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT | DDSD_LINEARSIZE;
+    let mut caps = DDSCAPS_TEXTURE;
+    if mipmap_count > 1 {
+        flags |= DDSD_MIPMAPCOUNT;
+        caps |= DDSCAPS_COMPLEX | DDSCAPS_MIPMAP;
+    }
+
+    let mut pf_flags = DDPF_FOURCC;
+    if has_alpha {
+        pf_flags |= DDPF_ALPHAPIXELS;
+    }
+    let fourcc = if use_dx10 {
+        FOURCC_DX10
+    } else {
+        match format {
+            DxtnFormat::Dxt1 => FOURCC_DXT1,
+            DxtnFormat::Dxt3 => FOURCC_DXT3,
+            DxtnFormat::Dxt5 => FOURCC_DXT5,
+        }
+    };
+
+    let data_size = content.images.iter().map(|i| i.len()).sum::<usize>();
+    let mut output = Vec::with_capacity(4 + DDS_HEADER_SIZE as usize + 20 + data_size);
+    output.extend(DDS_MAGIC);
+    for value in [
+        DDS_HEADER_SIZE,
+        flags,
+        image.header.height,
+        image.header.width,
+        first.len() as u32, // pitch or linear size
+        0,                  // depth
+        mipmap_count,
+    ] {
+        output.extend(value.to_le_bytes());
+    }
+    output.extend([0; 11 * 4]); // reserved1
+    for value in [DDS_PIXELFORMAT_SIZE, pf_flags] {
+        output.extend(value.to_le_bytes());
+    }
+    output.extend(fourcc);
+    output.extend([0; 5 * 4]); // rgb bit count and masks
+    for value in [caps, 0, 0, 0, 0] {
+        output.extend(value.to_le_bytes());
+    }
+
+    if use_dx10 {
+        let dxgi_format = match format {
+            DxtnFormat::Dxt1 => DXGI_FORMAT_BC1_UNORM,
+            DxtnFormat::Dxt3 => DXGI_FORMAT_BC2_UNORM,
+            DxtnFormat::Dxt5 => DXGI_FORMAT_BC3_UNORM,
+        };
+        let alpha_mode = if has_alpha {
+            DDS_ALPHA_MODE_STRAIGHT
+        } else {
+            DDS_ALPHA_MODE_OPAQUE
+        };
+        for value in [
+            dxgi_format,
+            D3D10_RESOURCE_DIMENSION_TEXTURE2D,
+            0, // misc flag
+            1, // array size
+            alpha_mode,
+        ] {
+            output.extend(value.to_le_bytes());
+        }
+    }
+
+    for image in content.images.iter() {
+        output.extend(&image.content);
+    }
+    Ok(output)
+}
+
+/// Read DXT1/DXT3/DXT5 DDS file into BLP2 image without recompression.
+/// Every mipmap level (up to 16) is copied as is.
+pub fn dds_to_blp(input: &[u8]) -> Result<BlpImage, Error> {
+    let mut reader = DdsReader { input, position: 0 };
+
+    let mut magic = [0; 4];
+    magic.copy_from_slice(reader.take(4, 0)?);
+    if magic != DDS_MAGIC {
+        return Err(Error::WrongMagic(magic));
+    }
+    let header_size = reader.u32()?;
+    if header_size != DDS_HEADER_SIZE {
+        return Err(Error::InvalidHeaderSize(header_size));
+    }
+    let flags = reader.u32()?;
+    let height = reader.u32()?;
+    let width = reader.u32()?;
+    let _pitch = reader.u32()?;
+    let _depth = reader.u32()?;
+    let mipmap_count = reader.u32()?;
+    reader.take(11 * 4, 0)?; // reserved1
+    let _pf_size = reader.u32()?;
+    let pf_flags = reader.u32()?;
+    let mut fourcc = [0; 4];
+    fourcc.copy_from_slice(reader.take(4, 0)?);
+    reader.take(5 * 4, 0)?; // rgb bit count and masks
+    reader.take(5 * 4, 0)?; // caps and reserved2
+
+    if width > BLP_MAX_WIDTH {
+        return Err(Error::WidthTooLarge(width));
+    }
+    if height > BLP_MAX_HEIGHT {
+        return Err(Error::HeightTooLarge(height));
+    }
+    if pf_flags & DDPF_FOURCC == 0 {
+        return Err(Error::NotCompressed(pf_flags));
+    }
+
+    let (format, has_alpha) = match fourcc {
+        FOURCC_DXT1 => (DxtnFormat::Dxt1, pf_flags & DDPF_ALPHAPIXELS != 0),
+        FOURCC_DXT3 => (DxtnFormat::Dxt3, true),
+        FOURCC_DXT5 => (DxtnFormat::Dxt5, true),
+        FOURCC_DX10 => {
+            let dxgi_format = reader.u32()?;
+            let dimension = reader.u32()?;
+            let _misc_flag = reader.u32()?;
+            let array_size = reader.u32()?;
+            let alpha_mode = reader.u32()? & 0x7;
+            if dimension != D3D10_RESOURCE_DIMENSION_TEXTURE2D || array_size > 1 {
+                return Err(Error::UnsupportedResource {
+                    dimension,
+                    array_size,
+                });
+            }
+            let format = match dxgi_format {
+                DXGI_FORMAT_BC1_UNORM | DXGI_FORMAT_BC1_UNORM_SRGB => DxtnFormat::Dxt1,
+                DXGI_FORMAT_BC2_UNORM | DXGI_FORMAT_BC2_UNORM_SRGB => DxtnFormat::Dxt3,
+                DXGI_FORMAT_BC3_UNORM | DXGI_FORMAT_BC3_UNORM_SRGB => DxtnFormat::Dxt5,
+                _ => return Err(Error::UnsupportedDxgiFormat(dxgi_format)),
+            };
+            let has_alpha = match alpha_mode {
+                DDS_ALPHA_MODE_OPAQUE => false,
+                DDS_ALPHA_MODE_UNKNOWN if format == DxtnFormat::Dxt1 => {
+                    pf_flags & DDPF_ALPHAPIXELS != 0
+                }
+                _ => true,
+            };
+            (format, has_alpha)
+        }
+        _ => return Err(Error::UnsupportedFourCC(fourcc)),
+    };
+
+    let mipmap_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+        mipmap_count.clamp(1, 16) as usize
+    } else {
+        1
+    };
+    trace!("Reading {format:?} DDS {width}x{height}, {mipmap_count} mipmaps, alpha: {has_alpha}");
+
+    let mut images = Vec::with_capacity(mipmap_count);
+    for i in 0..mipmap_count {
+        let level_width = (width >> i).max(1);
+        let level_height = (height >> i).max(1);
+        let size = dxtn_level_size(format, level_width, level_height);
+        let content = reader.take(size, i)?.to_vec();
+        images.push(DxtnImage { content });
+    }
+
+    // Same flags as `image_to_blp` produces for the DXT targets
+    let (alpha_bits, alpha_type) = match format {
+        DxtnFormat::Dxt1 => (if has_alpha { 1 } else { 0 }, AlphaType::None),
+        DxtnFormat::Dxt3 => (if has_alpha { 8 } else { 0 }, AlphaType::OneBit),
+        DxtnFormat::Dxt5 => (if has_alpha { 8 } else { 0 }, AlphaType::Enhanced),
+    };
+
+    // DXT content doesn't use the palette, the client files carry it zeroed too
+    let dxtn = BlpDxtn {
+        format,
+        cmap: vec![0; 256],
+        images,
+    };
+    let header = BlpHeader {
+        version: BlpVersion::Blp2,
+        content: BlpContentTag::Direct,
+        flags: BlpFlags::Blp2 {
+            compression: Compression::Dxtc,
+            alpha_bits,
+            alpha_type,
+            has_mipmaps: if mipmap_count > 1 { 1 } else { 0 },
+        },
+        width,
+        height,
+        mipmap_locator: dxtn.mipmap_locator(BlpVersion::Blp2),
+    };
+    let content = match format {
+        DxtnFormat::Dxt1 => BlpContent::Dxt1(dxtn),
+        DxtnFormat::Dxt3 => BlpContent::Dxt3(dxtn),
+        DxtnFormat::Dxt5 => BlpContent::Dxt5(dxtn),
+    };
+    Ok(BlpImage { header, content })
+}
+
+struct DdsReader<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl<'a> DdsReader<'a> {
+    fn take(&mut self, n: usize, mipmap: usize) -> Result<&'a [u8], Error> {
+        let left = self.input.len().saturating_sub(self.position);
+        if n > left {
+            return Err(Error::UnexpectedEof {
+                mipmap,
+                expected: n,
+                actual: left,
+            });
+        }
+        let bytes = &self.input[self.position..self.position + n];
+        self.position += n;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let mut buf = [0; 4];
+        buf.copy_from_slice(self.take(4, 0)?);
+        Ok(u32::from_le_bytes(buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::{Blp2Format, BlpTarget, DxtAlgorithm, FilterType, image_to_blp};
+    use crate::encode::encode_blp;
+    use crate::parser::parse_blp;
+    use ::image::{DynamicImage, Rgba, RgbaImage};
+
+    fn make_blp(format: Blp2Format) -> BlpImage {
+        let rgba = RgbaImage::from_fn(32, 16, |x, y| {
+            Rgba([
+                (x * 8) as u8,
+                (y * 16) as u8,
+                ((x + y) * 4) as u8,
+                (x * 7) as u8,
+            ])
+        });
+        image_to_blp(
+            DynamicImage::ImageRgba8(rgba),
+            true,
+            BlpTarget::Blp2(format),
+            FilterType::Nearest,
+        )
+        .expect("encode blp")
+    }
+
+    #[test]
+    fn test_roundtrip_all_formats() {
+        let algo = DxtAlgorithm::RangeFit;
+        let formats = [
+            Blp2Format::Dxt1 {
+                has_alpha: false,
+                compress_algorithm: algo,
+            },
+            Blp2Format::Dxt1 {
+                has_alpha: true,
+                compress_algorithm: algo,
+            },
+            Blp2Format::Dxt3 {
+                has_alpha: true,
+                compress_algorithm: algo,
+            },
+            Blp2Format::Dxt5 {
+                has_alpha: true,
+                compress_algorithm: algo,
+            },
+            Blp2Format::Dxt5 {
+                has_alpha: false,
+                compress_algorithm: algo,
+            },
+        ];
+        for format in formats {
+            let blp = make_blp(format);
+            for header_format in [DdsHeaderFormat::Auto, DdsHeaderFormat::Dx10] {
+                let dds = blp_to_dds(&blp, header_format).expect("to dds");
+                let back = dds_to_blp(&dds).expect("from dds");
+                assert_eq!(back, blp);
+                assert_eq!(
+                    encode_blp(&back).expect("encode"),
+                    encode_blp(&blp).expect("encode")
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parsed_blp_to_dds() {
+        let blp = make_blp(Blp2Format::Dxt5 {
+            has_alpha: true,
+            compress_algorithm: DxtAlgorithm::RangeFit,
+        });
+        let parsed = parse_blp(&encode_blp(&blp).expect("encode")).expect("parse");
+        let dds = blp_to_dds(&parsed, DdsHeaderFormat::FourCC).expect("to dds");
+        assert_eq!(&dds[84..88], b"DXT5");
+        assert_eq!(u32::from_le_bytes([dds[28], dds[29], dds[30], dds[31]]), 6);
+        assert_eq!(dds_to_blp(&dds).expect("from dds").content, parsed.content);
+    }
+
+    #[test]
+    fn test_not_dxtn() {
+        let blp = make_blp(Blp2Format::Raw3);
+        assert!(matches!(
+            blp_to_dds(&blp, DdsHeaderFormat::Auto),
+            Err(Error::NotDxtn(CompressionType::Raw3))
+        ));
+    }
+}
@@ -0,0 +1,12 @@
+use thiserror::Error;
+
+/// Errors that can occur when comparing images
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Failed to decode one of the images
+    #[error("Failed to decode image: {0}")]
+    Convert(#[from] crate::convert::Error),
+    /// Compared images have different dimensions
+    #[error("Image sizes differ: {0}x{1} vs {2}x{3}")]
+    SizeMismatch(u32, u32, u32, u32),
+}
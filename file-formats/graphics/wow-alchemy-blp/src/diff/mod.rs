@@ -0,0 +1,380 @@
+//! Quality metrics and visual difference between two textures.
+//!
+//! Images are compared in decoded RGBA8 form, so any combination of BLP
+//! encodings (or a BLP and a source image) can be compared.
+
+/// Diff error types
+pub mod error;
+
+use crate::convert::blp_to_image;
+use crate::types::*;
+use ::image::{DynamicImage, Rgb, RgbImage, RgbaImage};
+pub use error::Error;
+
+/// Names of the channels in [ImageDiff::channels] order
+pub const CHANNEL_NAMES: [&str; 4] = ["R", "G", "B", "A"];
+
+const SSIM_WINDOW: u32 = 8;
+const SSIM_C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const SSIM_C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+/// Quality metrics for a single channel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelMetrics {
+    /// Mean squared error
+    pub mse: f64,
+    /// Peak signal to noise ratio in dB, infinite for identical channels
+    pub psnr: f64,
+    /// Mean structural similarity over 8x8 windows, 1.0 for identical channels
+    pub ssim: f64,
+    /// Largest absolute difference of a single pixel
+    pub max_error: u8,
+}
+
+/// Comparison of two decoded images of the same size
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageDiff {
+    /// Width of compared images
+    pub width: u32,
+    /// Height of compared images
+    pub height: u32,
+    /// Metrics for R, G, B and A channels
+    pub channels: [ChannelMetrics; 4],
+}
+
+impl ImageDiff {
+    /// Lowest PSNR among the channels
+    pub fn min_psnr(&self) -> f64 {
+        self.channels
+            .iter()
+            .map(|c| c.psnr)
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Lowest SSIM among the channels
+    pub fn min_ssim(&self) -> f64 {
+        self.channels.iter().map(|c| c.ssim).fold(1.0, f64::min)
+    }
+
+    /// Largest single pixel error among the channels
+    pub fn max_error(&self) -> u8 {
+        self.channels.iter().map(|c| c.max_error).max().unwrap_or(0)
+    }
+
+    /// Returns `true` if the images are pixel identical
+    pub fn is_identical(&self) -> bool {
+        self.max_error() == 0
+    }
+}
+
+/// Compare two images channel by channel
+pub fn compare_images(a: &DynamicImage, b: &DynamicImage) -> Result<ImageDiff, Error> {
+    let a = a.to_rgba8();
+    let b = b.to_rgba8();
+    check_sizes(&a, &b)?;
+
+    let channels = std::array::from_fn(|c| channel_metrics(&a, &b, c));
+    Ok(ImageDiff {
+        width: a.width(),
+        height: a.height(),
+        channels,
+    })
+}
+
+/// Make a heat map of per pixel differences. Each pixel takes the largest
+/// channel error, multiplied by `scale`, and maps it from black through red
+/// and yellow to white.
+pub fn diff_heatmap(a: &DynamicImage, b: &DynamicImage, scale: f32) -> Result<RgbImage, Error> {
+    let a = a.to_rgba8();
+    let b = b.to_rgba8();
+    check_sizes(&a, &b)?;
+
+    Ok(RgbImage::from_fn(a.width(), a.height(), |x, y| {
+        let pa = a.get_pixel(x, y).0;
+        let pb = b.get_pixel(x, y).0;
+        let error = (0..4).map(|c| pa[c].abs_diff(pb[c])).max().unwrap_or(0);
+        heat_color(((error as f32) * scale / 255.0).clamp(0.0, 1.0))
+    }))
+}
+
+/// A header field that differs between two BLP files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderDifference {
+    /// Name of the field
+    pub field: &'static str,
+    /// Value in the first file
+    pub left: String,
+    /// Value in the second file
+    pub right: String,
+}
+
+/// Which mipmap levels [diff_blp] should decode and compare
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MipmapSelection {
+    /// Compare a single level
+    Level(usize),
+    /// Compare every level present in both images
+    All,
+}
+
+/// Result of comparing a mipmap level of two BLP images
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelDiff {
+    /// Mipmap level
+    pub level: usize,
+    /// Metrics of the level
+    pub diff: ImageDiff,
+}
+
+/// Full comparison of two BLP images
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlpDiff {
+    /// Header fields that differ
+    pub header: Vec<HeaderDifference>,
+    /// Number of mipmap levels in the first image
+    pub left_image_count: usize,
+    /// Number of mipmap levels in the second image
+    pub right_image_count: usize,
+    /// Metrics of compared levels
+    pub levels: Vec<LevelDiff>,
+}
+
+impl BlpDiff {
+    /// Lowest PSNR among all compared levels
+    pub fn min_psnr(&self) -> f64 {
+        self.levels
+            .iter()
+            .map(|l| l.diff.min_psnr())
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Lowest SSIM among all compared levels
+    pub fn min_ssim(&self) -> f64 {
+        self.levels
+            .iter()
+            .map(|l| l.diff.min_ssim())
+            .fold(1.0, f64::min)
+    }
+
+    /// Largest single pixel error among all compared levels
+    pub fn max_error(&self) -> u8 {
+        self.levels
+            .iter()
+            .map(|l| l.diff.max_error())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// List header fields that differ between two BLP headers
+pub fn compare_headers(a: &BlpHeader, b: &BlpHeader) -> Vec<HeaderDifference> {
+    let mut res = vec![];
+    let mut check = |field: &'static str, left: String, right: String| {
+        if left != right {
+            res.push(HeaderDifference { field, left, right });
+        }
+    };
+
+    check("version", a.version.to_string(), b.version.to_string());
+    check(
+        "content",
+        format!("{:?}", a.content),
+        format!("{:?}", b.content),
+    );
+    check("flags", format!("{:?}", a.flags), format!("{:?}", b.flags));
+    check("width", a.width.to_string(), b.width.to_string());
+    check("height", a.height.to_string(), b.height.to_string());
+    check(
+        "has_mipmaps",
+        a.has_mipmaps().to_string(),
+        b.has_mipmaps().to_string(),
+    );
+    res
+}
+
+/// Decode and compare two BLP images at the selected mipmap levels
+pub fn diff_blp(a: &BlpImage, b: &BlpImage, selection: MipmapSelection) -> Result<BlpDiff, Error> {
+    let levels = match selection {
+        MipmapSelection::Level(level) => level..level + 1,
+        MipmapSelection::All => 0..a.image_count().min(b.image_count()),
+    };
+
+    let mut res = vec![];
+    for level in levels {
+        let image_a = blp_to_image(a, level)?;
+        let image_b = blp_to_image(b, level)?;
+        res.push(LevelDiff {
+            level,
+            diff: compare_images(&image_a, &image_b)?,
+        });
+    }
+
+    Ok(BlpDiff {
+        header: compare_headers(&a.header, &b.header),
+        left_image_count: a.image_count(),
+        right_image_count: b.image_count(),
+        levels: res,
+    })
+}
+
+fn check_sizes(a: &RgbaImage, b: &RgbaImage) -> Result<(), Error> {
+    if a.dimensions() != b.dimensions() {
+        return Err(Error::SizeMismatch(
+            a.width(),
+            a.height(),
+            b.width(),
+            b.height(),
+        ));
+    }
+    Ok(())
+}
+
+fn channel_metrics(a: &RgbaImage, b: &RgbaImage, channel: usize) -> ChannelMetrics {
+    let mut sum_sq = 0.0;
+    let mut max_error = 0;
+    for (pa, pb) in a.pixels().zip(b.pixels()) {
+        let error = pa.0[channel].abs_diff(pb.0[channel]);
+        max_error = max_error.max(error);
+        sum_sq += (error as f64) * (error as f64);
+    }
+    let pixels = (a.width() as f64) * (a.height() as f64);
+    let mse = if pixels > 0.0 { sum_sq / pixels } else { 0.0 };
+    let psnr = if mse == 0.0 {
+        f64::INFINITY
+    } else {
+        10.0 * (255.0 * 255.0 / mse).log10()
+    };
+
+    ChannelMetrics {
+        mse,
+        psnr,
+        ssim: channel_ssim(a, b, channel),
+        max_error,
+    }
+}
+
+fn channel_ssim(a: &RgbaImage, b: &RgbaImage, channel: usize) -> f64 {
+    let (width, height) = a.dimensions();
+    let mut total = 0.0;
+    let mut windows = 0;
+
+    for wy in (0..height).step_by(SSIM_WINDOW as usize) {
+        for wx in (0..width).step_by(SSIM_WINDOW as usize) {
+            let xs = wx..(wx + SSIM_WINDOW).min(width);
+            let ys = wy..(wy + SSIM_WINDOW).min(height);
+            let n = (xs.len() * ys.len()) as f64;
+
+            let (mut sum_a, mut sum_b) = (0.0, 0.0);
+            let (mut sum_aa, mut sum_bb, mut sum_ab) = (0.0, 0.0, 0.0);
+            for y in ys {
+                for x in xs.clone() {
+                    let va = a.get_pixel(x, y).0[channel] as f64;
+                    let vb = b.get_pixel(x, y).0[channel] as f64;
+                    sum_a += va;
+                    sum_b += vb;
+                    sum_aa += va * va;
+                    sum_bb += vb * vb;
+                    sum_ab += va * vb;
+                }
+            }
+
+            let mean_a = sum_a / n;
+            let mean_b = sum_b / n;
+            let var_a = sum_aa / n - mean_a * mean_a;
+            let var_b = sum_bb / n - mean_b * mean_b;
+            let cov = sum_ab / n - mean_a * mean_b;
+
+            total += ((2.0 * mean_a * mean_b + SSIM_C1) * (2.0 * cov + SSIM_C2))
+                / ((mean_a * mean_a + mean_b * mean_b + SSIM_C1) * (var_a + var_b + SSIM_C2));
+            windows += 1;
+        }
+    }
+
+    if windows == 0 {
+        1.0
+    } else {
+        total / windows as f64
+    }
+}
+
+fn heat_color(value: f32) -> Rgb<u8> {
+    // black -> red -> yellow -> white
+    let scaled = value * 3.0;
+    let r = scaled.clamp(0.0, 1.0);
+    let g = (scaled - 1.0).clamp(0.0, 1.0);
+    let b = (scaled - 2.0).clamp(0.0, 1.0);
+    Rgb([(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::{Blp2Format, BlpTarget, DxtAlgorithm, FilterType, image_to_blp};
+    use ::image::Rgba;
+
+    fn gradient() -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(16, 16, |x, y| {
+            Rgba([(x * 16) as u8, (y * 16) as u8, 128, 255])
+        }))
+    }
+
+    #[test]
+    fn test_identical_images() {
+        let diff = compare_images(&gradient(), &gradient()).expect("diff");
+        assert!(diff.is_identical());
+        assert_eq!(diff.min_psnr(), f64::INFINITY);
+        assert!((diff.min_ssim() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_single_pixel_error() {
+        let a = gradient();
+        let mut b = a.to_rgba8();
+        b.get_pixel_mut(3, 5).0[1] = b.get_pixel(3, 5).0[1].wrapping_add(100);
+        let b = DynamicImage::ImageRgba8(b);
+
+        let diff = compare_images(&a, &b).expect("diff");
+        assert_eq!(diff.channels[0].max_error, 0);
+        assert_eq!(diff.channels[1].max_error, 100);
+        let expected_psnr = 10.0 * (255.0f64 * 255.0 / (100.0 * 100.0 / 256.0)).log10();
+        assert!((diff.channels[1].psnr - expected_psnr).abs() < 1e-9);
+        assert!(diff.channels[1].ssim < 1.0);
+
+        let heatmap = diff_heatmap(&a, &b, 1.0).expect("heatmap");
+        assert_eq!(heatmap.get_pixel(0, 0).0, [0, 0, 0]);
+        assert_ne!(heatmap.get_pixel(3, 5).0, [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_size_mismatch() {
+        let small = DynamicImage::new_rgba8(8, 8);
+        assert!(matches!(
+            compare_images(&gradient(), &small),
+            Err(Error::SizeMismatch(16, 16, 8, 8))
+        ));
+    }
+
+    #[test]
+    fn test_diff_blp_all_levels() {
+        let make = |format| {
+            image_to_blp(
+                gradient(),
+                true,
+                BlpTarget::Blp2(format),
+                FilterType::Nearest,
+            )
+            .expect("blp")
+        };
+        let a = make(Blp2Format::Raw3);
+        let b = make(Blp2Format::Dxt1 {
+            has_alpha: false,
+            compress_algorithm: DxtAlgorithm::ClusterFit,
+        });
+
+        let diff = diff_blp(&a, &b, MipmapSelection::All).expect("diff");
+        assert_eq!(diff.levels.len(), 5);
+        assert!(diff.header.iter().any(|d| d.field == "flags"));
+        assert!(diff.levels[0].diff.min_psnr() > 20.0);
+        assert!(diff.max_error() > 0);
+    }
+}
@@ -2,8 +2,16 @@
 #![forbid(unsafe_code)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+/// Packing and splitting of texture atlases
+#[cfg(feature = "atlas")]
+#[cfg_attr(docsrs, doc(cfg(feature = "atlas")))]
+pub mod atlas;
 /// Conversion utilities to/from DynamicImage
 pub mod convert;
+/// Lossless conversion between DXT BLP and DDS
+pub mod dds;
+/// Quality metrics and visual difference of textures
+pub mod diff;
 /// Encoding BLP format into stream of bytes
 pub mod encode;
 /// Decoding BLP format from raw bytes
@@ -9,9 +9,22 @@ pub enum LoadError {
     /// File system error when reading BLP or mipmap files
     #[error("File system error with file {0}, due: {1}")]
     FileSystem(std::path::PathBuf, std::io::Error),
+    /// The file provider failed to read the BLP or a mipmap file
+    #[cfg(feature = "vfs")]
+    #[error("Failed to read {0} from the file provider: {1}")]
+    Provider(String, wow_alchemy_vfs::VfsError),
     /// Invalid or malformed BLP filename
     #[error("Cannot derive mipmap name for {0}")]
     InvalidFilename(std::path::PathBuf),
+    /// I/O error when reading from a stream
+    #[error("Failed to read BLP stream: {0}")]
+    Io(#[from] std::io::Error),
+    /// Requested mipmap level is not present in the file
+    #[error("There is no mipmap level {0}, the file has {1} levels")]
+    MissingLevel(usize, usize),
+    /// Failed to decode the read mipmap level
+    #[error("Failed to decode image: {0}")]
+    Convert(#[from] crate::convert::Error),
 }
 
 /// Errors that BLP parser can produce
@@ -7,6 +7,8 @@ mod header;
 mod jpeg;
 /// Native byte reading utilities
 mod reader;
+/// Lazy reading of separate mipmap levels from a stream
+mod stream;
 /// Type definitions used by the BLP parser
 pub mod types;
 
@@ -17,7 +19,10 @@ pub use error::{Error, LoadError};
 use header::parse_header;
 use jpeg::parse_jpeg_content;
 use std::path::{Path, PathBuf};
+pub use stream::BlpReader;
 use types::ParseResult;
+#[cfg(feature = "vfs")]
+use wow_alchemy_vfs::{WowFileProvider, path::strip_extension};
 
 /// Read BLP file from file system. If it BLP0 format, uses the mipmaps near the root file.
 pub fn load_blp<Q>(path: Q) -> Result<BlpImage, LoadError>
@@ -29,6 +34,35 @@ where
     load_blp_ex(Some(path), &input)
 }
 
+/// Read BLP file from a file provider. If it BLP0 format, uses the mipmaps
+/// near the root file in the same provider.
+#[cfg(feature = "vfs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "vfs")))]
+pub fn load_blp_from_provider<P>(provider: &P, path: &str) -> Result<BlpImage, LoadError>
+where
+    P: WowFileProvider + ?Sized,
+{
+    let read = |path: &str| {
+        provider
+            .read(path)
+            .map_err(|e| LoadError::Provider(path.to_owned(), e))
+    };
+    let input = read(path)?;
+
+    let mut mipmaps = vec![];
+    for i in 0..16 {
+        let mipmap_path = format!("{}.b{i:02}", strip_extension(path));
+        if !provider.exists(&mipmap_path) {
+            break;
+        }
+        mipmaps.push(read(&mipmap_path)?);
+    }
+
+    let image = parse_blp_with_externals(&input, |i| preloaded_mipmaps(&mipmaps, i))
+        .map_err(|e| LoadError::Parsing(format!("{e}")))?;
+    Ok(image)
+}
+
 /// Read BLP file from buffer(`Vec<u8>`). If it BLP0 format, uses the mipmaps in the temp dir.
 ///
 /// Since: 1.2.0
@@ -136,3 +170,45 @@ where
         }
     }
 }
+
+#[cfg(all(test, feature = "vfs"))]
+mod tests {
+    use super::*;
+    use crate::convert::{AlphaBits, BlpOldFormat, BlpTarget, FilterType, image_to_blp};
+    use crate::encode::{BlpWithMipmaps, encode_blp0};
+    use ::image::{DynamicImage, Rgba, RgbaImage};
+    use wow_alchemy_vfs::MemoryProvider;
+
+    #[test]
+    fn test_load_from_provider() {
+        let rgba = RgbaImage::from_fn(32, 32, |x, y| Rgba([(x * 8) as u8, (y * 8) as u8, 0, 255]));
+        let blp = image_to_blp(
+            DynamicImage::ImageRgba8(rgba),
+            true,
+            BlpTarget::Blp0(BlpOldFormat::Raw1 {
+                alpha_bits: AlphaBits::Bit8,
+            }),
+            FilterType::Nearest,
+        )
+        .expect("encode blp");
+        let BlpWithMipmaps {
+            blp_bytes,
+            blp_mipmaps,
+        } = encode_blp0(&blp).expect("encode");
+
+        let mut provider = MemoryProvider::new();
+        provider.insert("Textures\\Test.blp", blp_bytes);
+        for (i, mipmap) in blp_mipmaps.into_iter().enumerate() {
+            provider.insert(&format!("textures/test.b{i:02}"), mipmap);
+        }
+        let loaded = load_blp_from_provider(&provider, "Textures\\Test.blp").expect("load");
+        assert_eq!(loaded, blp);
+
+        provider.remove("textures/test.b03");
+        assert!(load_blp_from_provider(&provider, "textures/test.blp").is_err());
+        assert!(matches!(
+            load_blp_from_provider(&provider, "textures/missing.blp"),
+            Err(LoadError::Provider(..))
+        ));
+    }
+}
@@ -0,0 +1,359 @@
+use super::error::{Error, LoadError};
+use super::header::parse_header;
+use super::parse_content;
+use crate::convert::blp_to_image;
+use crate::path::make_mipmap_path;
+use crate::types::*;
+use ::image::DynamicImage;
+use log::*;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Lazy BLP reader that parses only the header (and color map or JPEG
+/// header) up front and reads mipmap levels on demand.
+///
+/// Any `Read + Seek` source works: a file, a `Cursor` over a memory mapped
+/// slice or a stream from an archive. Useful to pick a small mipmap for a
+/// thumbnail without reading the full resolution level.
+///
+/// ```no_run
+/// use wow_alchemy_blp::parser::BlpReader;
+///
+/// let mut reader = BlpReader::open("texture.blp").unwrap();
+/// let level = reader.best_mipmap_for_size(64);
+/// let thumbnail = reader.decode_level(level).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct BlpReader<R> {
+    reader: R,
+    /// Position of the BLP magic in the stream. Offsets of mipmaps are
+    /// relative to it.
+    start: u64,
+    header: BlpHeader,
+    /// Raw bytes between header and images: color map for direct content
+    /// or size prefixed shared header for JPEG content.
+    prelude: Vec<u8>,
+    /// Path of the root file to locate BLP0 external mipmaps
+    path: Option<PathBuf>,
+}
+
+impl BlpReader<BufReader<File>> {
+    /// Open BLP file from file system. For BLP0 the external mipmaps near
+    /// the root file are opened only when the level is requested.
+    pub fn open<Q>(path: Q) -> Result<Self, LoadError>
+    where
+        Q: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| LoadError::FileSystem(path.to_owned(), e))?;
+        let mut reader = Self::new(BufReader::new(file))?;
+        reader.path = Some(path.to_owned());
+        Ok(reader)
+    }
+}
+
+impl<R: Read + Seek> BlpReader<R> {
+    /// Read header of BLP from the current position of the stream. BLP0
+    /// levels cannot be read with a reader created this way as there is no
+    /// path to derive mipmap file names from.
+    pub fn new(mut reader: R) -> Result<Self, LoadError> {
+        let start = reader.stream_position()?;
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        let version = BlpVersion::from_magic(magic).ok_or_else(|| {
+            LoadError::Parsing(format!(
+                "{}",
+                Error::WrongMagic(
+                    std::str::from_utf8(&magic)
+                        .map(|s| s.to_owned())
+                        .unwrap_or_else(|_| format!("{magic:?}"))
+                )
+            ))
+        })?;
+        let mut header_bytes = vec![0; BlpHeader::size(version)];
+        header_bytes[0..4].copy_from_slice(&magic);
+        reader.read_exact(&mut header_bytes[4..])?;
+        let header = parse_header(&header_bytes)
+            .map_err(|e| LoadError::Parsing(format!("{}", e.with_context("header"))))?;
+
+        let prelude = match header.content {
+            BlpContentTag::Jpeg => {
+                let mut size_bytes = [0; 4];
+                reader.read_exact(&mut size_bytes)?;
+                // There is two additional bytes that are not covered by the header size
+                let jpeg_header_size = u32::from_le_bytes(size_bytes) as usize + 2;
+                let mut prelude = Vec::with_capacity(4 + jpeg_header_size);
+                prelude.extend_from_slice(&size_bytes);
+                reader
+                    .by_ref()
+                    .take(jpeg_header_size as u64)
+                    .read_to_end(&mut prelude)?;
+                if prelude.len() != 4 + jpeg_header_size {
+                    return Err(LoadError::Parsing(format!(
+                        "{}",
+                        Error::UnexpectedEof.with_context("jpeg header")
+                    )));
+                }
+                prelude
+            }
+            BlpContentTag::Direct => {
+                let mut cmap = vec![0; 256 * 4];
+                reader.read_exact(&mut cmap)?;
+                cmap
+            }
+        };
+
+        Ok(BlpReader {
+            reader,
+            start,
+            header,
+            prelude,
+            path: None,
+        })
+    }
+
+    /// Parsed header of the file
+    pub fn header(&self) -> &BlpHeader {
+        &self.header
+    }
+
+    /// Amount of mipmap levels that the header declares
+    pub fn image_count(&self) -> usize {
+        if !self.header.has_mipmaps() {
+            return 1;
+        }
+        let count = self.header.mipmaps_count() + 1;
+        match &self.header.mipmap_locator {
+            MipmapLocator::Internal { sizes, .. } => sizes
+                .iter()
+                .take(count.min(16))
+                .take_while(|&&size| size > 0)
+                .count()
+                .max(1),
+            MipmapLocator::External => count,
+        }
+    }
+
+    /// Find the best mipmap level for a target resolution without reading
+    /// any image data.
+    pub fn best_mipmap_for_size(&self, target_size: u32) -> usize {
+        self.header
+            .best_mipmap_for_size(self.image_count(), target_size)
+    }
+
+    /// Read raw bytes of the mipmap level as they are stored in the file
+    pub fn read_level_bytes(&mut self, level: usize) -> Result<Vec<u8>, LoadError> {
+        let count = self.image_count();
+        if level >= count {
+            return Err(LoadError::MissingLevel(level, count));
+        }
+        match self.header.mipmap_locator {
+            MipmapLocator::Internal { offsets, sizes } => {
+                let offset = self.start + offsets[level] as u64;
+                let size = sizes[level] as usize;
+                trace!("Reading mipmap {level} at offset {offset} with size {size}");
+                self.reader.seek(SeekFrom::Start(offset))?;
+                // The size comes from the header, so it isn't trusted to
+                // preallocate the buffer
+                let mut bytes = Vec::new();
+                (&mut self.reader)
+                    .take(size as u64)
+                    .read_to_end(&mut bytes)?;
+                if bytes.len() != size {
+                    return Err(LoadError::Parsing(format!(
+                        "{}",
+                        Error::OutOfBounds {
+                            offset: offsets[level] as usize,
+                            size,
+                        }
+                    )));
+                }
+                Ok(bytes)
+            }
+            MipmapLocator::External => {
+                let root = self
+                    .path
+                    .as_ref()
+                    .ok_or_else(|| LoadError::Parsing(format!("{}", Error::MissingImage(level))))?;
+                let mipmap_path = make_mipmap_path(root, level)
+                    .ok_or_else(|| LoadError::InvalidFilename(root.to_owned()))?;
+                trace!("Reading mipmap {level} from {}", mipmap_path.display());
+                std::fs::read(&mipmap_path).map_err(|e| LoadError::FileSystem(mipmap_path, e))
+            }
+        }
+    }
+
+    /// Read single mipmap level as a standalone BLP image without mipmaps.
+    /// The header of the result has the size of the level.
+    pub fn read_level(&mut self, level: usize) -> Result<BlpImage, LoadError> {
+        let bytes = self.read_level_bytes(level)?;
+
+        let (width, height) = self.header.mipmap_size(level);
+        let mut flags = self.header.flags.clone();
+        match &mut flags {
+            BlpFlags::Blp2 { has_mipmaps, .. } => *has_mipmaps = 0,
+            BlpFlags::Old { has_mipmaps, .. } => *has_mipmaps = 0,
+        }
+        let mipmap_locator = match self.header.mipmap_locator {
+            MipmapLocator::Internal { .. } => {
+                let mut sizes = [0; 16];
+                sizes[0] = bytes.len() as u32;
+                MipmapLocator::Internal {
+                    offsets: [0; 16],
+                    sizes,
+                }
+            }
+            MipmapLocator::External => MipmapLocator::External,
+        };
+        let mut header = BlpHeader {
+            version: self.header.version,
+            content: self.header.content,
+            flags,
+            width,
+            height,
+            mipmap_locator,
+        };
+
+        let external = |i: usize| -> Result<Option<&[u8]>, Box<dyn std::error::Error>> {
+            Ok((i == 0).then_some(bytes.as_slice()))
+        };
+        let content = parse_content(&header, external, &bytes, &self.prelude)
+            .map_err(|e| LoadError::Parsing(format!("{}", e.with_context("image content"))))?;
+
+        if header.version > BlpVersion::Blp0 {
+            header.mipmap_locator = match &content {
+                BlpContent::Jpeg(v) => v.mipmap_locator(header.version),
+                BlpContent::Raw1(v) => v.mipmap_locator(header.version),
+                BlpContent::Raw3(v) => v.mipmap_locator(header.version),
+                BlpContent::Dxt1(v) => v.mipmap_locator(header.version),
+                BlpContent::Dxt3(v) => v.mipmap_locator(header.version),
+                BlpContent::Dxt5(v) => v.mipmap_locator(header.version),
+            };
+        }
+
+        Ok(BlpImage { header, content })
+    }
+
+    /// Read and decode single mipmap level
+    pub fn decode_level(&mut self, level: usize) -> Result<DynamicImage, LoadError> {
+        let image = self.read_level(level)?;
+        Ok(blp_to_image(&image, 0)?)
+    }
+
+    /// Return the underlying stream
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::{
+        AlphaBits, Blp2Format, BlpOldFormat, BlpTarget, FilterType, image_to_blp,
+    };
+    use crate::encode::{encode_blp, save_blp};
+    use ::image::{Rgba, RgbaImage};
+    use std::io::Cursor;
+
+    fn make_blp(target: BlpTarget) -> BlpImage {
+        let rgba = RgbaImage::from_fn(64, 32, |x, y| {
+            Rgba([(x * 4) as u8, (y * 8) as u8, ((x + y) * 2) as u8, 255])
+        });
+        image_to_blp(
+            DynamicImage::ImageRgba8(rgba),
+            true,
+            target,
+            FilterType::Nearest,
+        )
+        .expect("encode blp")
+    }
+
+    fn check_levels<R: Read + Seek>(blp: &BlpImage, reader: &mut BlpReader<R>) {
+        assert_eq!(reader.image_count(), blp.image_count());
+        assert_eq!(
+            reader.best_mipmap_for_size(16),
+            blp.best_mipmap_for_size(16)
+        );
+        for level in 0..blp.image_count() {
+            let expected = blp_to_image(blp, level).expect("decode full");
+            let actual = reader.decode_level(level).expect("decode level");
+            assert_eq!(expected.to_rgba8(), actual.to_rgba8(), "level {level}");
+        }
+        assert!(matches!(
+            reader.read_level(blp.image_count()),
+            Err(LoadError::MissingLevel(..))
+        ));
+    }
+
+    #[test]
+    fn test_stream_levels() {
+        let targets = [
+            BlpTarget::Blp1(BlpOldFormat::Jpeg {
+                has_alpha: true,
+                options: Default::default(),
+            }),
+            BlpTarget::Blp1(BlpOldFormat::Raw1 {
+                alpha_bits: AlphaBits::Bit4,
+            }),
+            BlpTarget::Blp2(Blp2Format::Raw3),
+            BlpTarget::Blp2(Blp2Format::Dxt5 {
+                has_alpha: true,
+                compress_algorithm: Default::default(),
+            }),
+        ];
+        let jpeg = include_bytes!("../../test-data/test_rect_with_alpha.blp").to_vec();
+        let blps = targets
+            .into_iter()
+            .map(|target| {
+                let blp = make_blp(target);
+                let bytes = encode_blp(&blp).expect("encode");
+                (blp, bytes)
+            })
+            .chain([(super::super::parse_blp(&jpeg).expect("parse jpeg"), jpeg)]);
+        for (blp, bytes) in blps {
+            // Reader starting in the middle of a stream, like inside an archive
+            let mut padded = vec![0xAA; 7];
+            padded.extend_from_slice(&bytes);
+            let mut cursor = Cursor::new(padded.as_slice());
+            cursor.set_position(7);
+            let mut reader = BlpReader::new(cursor).expect("reader");
+            check_levels(&blp, &mut reader);
+
+            // Single level image is a valid BLP on its own
+            let level = reader.read_level(1).expect("level");
+            let reparsed = super::super::parse_blp(&encode_blp(&level).expect("encode level"))
+                .expect("parse level");
+            assert_eq!(level, reparsed);
+        }
+    }
+
+    #[test]
+    fn test_stream_level_size_beyond_stream() {
+        let blp = make_blp(BlpTarget::Blp2(Blp2Format::Raw3));
+        let mut bytes = encode_blp(&blp).expect("encode");
+        // First entry of the mipmap sizes, after the 16 offsets
+        bytes[84..88].copy_from_slice(&0xFFFF_FF00u32.to_le_bytes());
+
+        let mut reader = BlpReader::new(Cursor::new(bytes.as_slice())).expect("reader");
+        assert!(matches!(
+            reader.read_level_bytes(0),
+            Err(LoadError::Parsing(..))
+        ));
+    }
+
+    #[test]
+    fn test_stream_blp0() {
+        let blp = make_blp(BlpTarget::Blp0(BlpOldFormat::Raw1 {
+            alpha_bits: AlphaBits::Bit8,
+        }));
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("test.blp");
+        save_blp(&blp, &path).expect("save");
+
+        let mut reader = BlpReader::open(&path).expect("reader");
+        check_levels(&blp, &mut reader);
+    }
+}
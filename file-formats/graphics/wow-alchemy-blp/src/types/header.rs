@@ -1,6 +1,7 @@
 pub use super::locator::MipmapLocator;
 pub use super::version::BlpVersion;
 use std::fmt;
+use wow_alchemy_data::game_version::GameVersion;
 
 /// The content field determines how the image data is stored. CONTENT_JPEG
 /// uses non-standard JPEG (JFIF) file compression of BGRA colour component
@@ -8,6 +9,7 @@ use std::fmt;
 /// CONTENT_DIRECT refers to a variety of storage formats which can be
 /// directly read as pixel values.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlpContentTag {
     /// JPEG compressed image data
     Jpeg,
@@ -48,6 +50,7 @@ impl From<BlpContentTag> for u32 {
 
 /// BLP file header structure
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlpHeader {
     /// BLP format version
     pub version: BlpVersion,
@@ -97,6 +100,25 @@ impl BlpHeader {
         w * h
     }
 
+    /// Find the mipmap level among the first `image_count` ones which
+    /// larger side is closest to the target size.
+    pub fn best_mipmap_for_size(&self, image_count: usize, target_size: u32) -> usize {
+        let mut best_level = 0;
+        let mut best_diff = u32::MAX;
+
+        for level in 0..image_count {
+            let (width, height) = self.mipmap_size(level);
+            let diff = width.max(height).abs_diff(target_size);
+
+            if diff < best_diff {
+                best_diff = diff;
+                best_level = level;
+            }
+        }
+
+        best_level
+    }
+
     /// Return alpha bits count in encoding
     pub fn alpha_bits(&self) -> u32 {
         self.flags.alpha_bits()
@@ -155,6 +177,7 @@ impl Default for BlpHeader {
 /// Alpha channel encoding type for BLP2 format
 /// Based on empirical analysis of WoW versions 1.12.1 through 5.4.8
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AlphaType {
     /// No alpha channel
     None = 0,
@@ -229,6 +252,7 @@ impl From<AlphaType> for u8 {
 
 /// WoW version for format compatibility checking
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WowVersion {
     /// World of Warcraft 1.12.1 (Vanilla)
     Vanilla,
@@ -242,8 +266,22 @@ pub enum WowVersion {
     MoP,
 }
 
+impl From<GameVersion> for WowVersion {
+    /// Later expansions use the formats of Mists of Pandaria
+    fn from(version: GameVersion) -> Self {
+        match version {
+            GameVersion::Vanilla => WowVersion::Vanilla,
+            GameVersion::TBC => WowVersion::TBC,
+            GameVersion::WotLK => WowVersion::WotLK,
+            GameVersion::Cataclysm => WowVersion::Cataclysm,
+            _ => WowVersion::MoP,
+        }
+    }
+}
+
 /// Compression type for BLP2 format
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Compression {
     /// JPEG compression (rarely used in BLP2)
     Jpeg, // adhoc compression, never met in BLP2
@@ -292,6 +330,7 @@ impl From<Compression> for u8 {
 
 /// Part of header that depends on the version
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlpFlags {
     /// For version >= 2
     Blp2 {
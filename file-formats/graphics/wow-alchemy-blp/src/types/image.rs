@@ -84,26 +84,8 @@ impl BlpImage {
     /// Find the best mipmap level for a target resolution.
     /// Returns the mipmap level closest to the target size.
     pub fn best_mipmap_for_size(&self, target_size: u32) -> usize {
-        let image_count = self.image_count();
-        if image_count == 0 {
-            return 0;
-        }
-
-        let mut best_level = 0;
-        let mut best_diff = u32::MAX;
-
-        for level in 0..image_count {
-            let (width, height) = self.header.mipmap_size(level);
-            let size = width.max(height);
-            let diff = size.abs_diff(target_size);
-
-            if diff < best_diff {
-                best_diff = diff;
-                best_level = level;
-            }
-        }
-
-        best_level
+        self.header
+            .best_mipmap_for_size(self.image_count(), target_size)
     }
 
     /// Get information about all mipmap levels
@@ -191,6 +173,21 @@ impl BlpImage {
         header_size + content_size
     }
 
+    /// Average amount of bits per pixel over all mipmap levels, including
+    /// header, color map and shared JPEG header
+    pub fn bits_per_pixel(&self) -> f32 {
+        let pixels = self
+            .mipmap_info()
+            .iter()
+            .map(|info| info.pixel_count as u64)
+            .sum::<u64>();
+        if pixels > 0 {
+            (self.estimated_file_size() * 8) as f32 / pixels as f32
+        } else {
+            0.0
+        }
+    }
+
     /// Get compression efficiency (uncompressed size vs compressed size)
     pub fn compression_ratio(&self) -> f32 {
         let uncompressed_size = self
@@ -224,6 +221,17 @@ pub struct MipMapInfo {
     pub pixel_count: u32,
 }
 
+impl MipMapInfo {
+    /// Amount of bits per pixel in the level data
+    pub fn bits_per_pixel(&self) -> f32 {
+        if self.pixel_count > 0 {
+            (self.data_size * 8) as f32 / self.pixel_count as f32
+        } else {
+            0.0
+        }
+    }
+}
+
 /// Compression type enumeration for easy inspection
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum CompressionType {
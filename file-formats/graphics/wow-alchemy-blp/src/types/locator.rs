@@ -1,5 +1,6 @@
 /// Descibes where to search for mipmaps
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MipmapLocator {
     /// Mipmaps are located inside the BLP file with given offsets
     /// and sizes.
@@ -4,6 +4,7 @@ use std::str;
 /// Version of type format that determines structure of file. Encodes
 /// magic bytes that are expected at start of the file.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlpVersion {
     /// BLP0 format - Used in Warcraft III ROC Beta builds
     Blp0,
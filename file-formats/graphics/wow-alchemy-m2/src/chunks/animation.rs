@@ -2,14 +2,15 @@ use std::fmt;
 
 use custom_debug::Debug;
 
-use wow_alchemy_data::error::Result as WDResult;
+use wow_alchemy_data::error::{Result as WDResult, WowDataError};
 use wow_alchemy_data::prelude::*;
 use wow_alchemy_data::types::{BoundingBox, C3Vector, VWowDataR, WowArray};
-use wow_alchemy_data_derive::{WowDataR, WowEnumFrom, WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowDataR, WowDataW, WowEnumFrom, WowHeaderR, WowHeaderW};
 
 use crate::version::MD20Version;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, WowEnumFrom, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=u16)]
 pub enum M2InterpolationType {
     #[wow_data(expr = 0)]
@@ -25,6 +26,7 @@ pub enum M2InterpolationType {
 bitflags::bitflags! {
     /// Animation flags as defined in the M2 format
     #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, WowHeaderR, WowHeaderW)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[wow_data(bitflags=u32)]
     pub struct M2AnimationFlags: u32 {
         /// Animation has translation keyframes
@@ -48,18 +50,21 @@ bitflags::bitflags! {
 
 /// Animation value ranges
 #[derive(Debug, Clone, Default, PartialEq, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2Range {
     pub minimum: f32,
     pub maximum: f32,
 }
 
 #[derive(Debug, Clone, PartialEq, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2Box {
     pub rotation_speed_min: C3Vector,
     pub rotation_speed_max: C3Vector,
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum TrackArray<T: WowHeaderR + WowHeaderW> {
     Single(WowArray<T>),
@@ -69,6 +74,7 @@ pub enum TrackArray<T: WowHeaderR + WowHeaderW> {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2InterpolationRangeHeader {
     None,
@@ -78,6 +84,7 @@ pub enum M2InterpolationRangeHeader {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum M2InterpolationRange {
     None,
 
@@ -96,7 +103,26 @@ impl VWowDataR<MD20Version, M2InterpolationRangeHeader> for M2InterpolationRange
     }
 }
 
+impl WowDataW<M2InterpolationRangeHeader> for M2InterpolationRange {
+    fn wow_write_data<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        header: &M2InterpolationRangeHeader,
+    ) -> WDResult<M2InterpolationRangeHeader> {
+        Ok(match (header, self) {
+            (M2InterpolationRangeHeader::Some(array), Self::Some(ranges)) => {
+                M2InterpolationRangeHeader::Some(ranges.wow_write_data(writer, array)?)
+            }
+            (M2InterpolationRangeHeader::None, Self::None) => M2InterpolationRangeHeader::None,
+            _ => {
+                return Err(WowDataError::HeaderMismatch("interpolation ranges".into()));
+            }
+        })
+    }
+}
+
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2AnimationBaseTrackHeader {
     pub interpolation_type: M2InterpolationType,
@@ -107,7 +133,8 @@ pub struct M2AnimationBaseTrackHeader {
     pub timestamps: TrackArray<u32>,
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version, header=M2AnimationBaseTrackHeader)]
 pub struct M2AnimationBaseTrackData {
     #[wow_data(versioned)]
@@ -117,6 +144,7 @@ pub struct M2AnimationBaseTrackData {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2AnimationBaseTrack {
     pub header: M2AnimationBaseTrackHeader,
     pub data: M2AnimationBaseTrackData,
@@ -124,6 +152,7 @@ pub struct M2AnimationBaseTrack {
 
 /// An animation track header
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2AnimationTrackHeader<T: WowHeaderR + WowHeaderW> {
     pub interpolation_type: M2InterpolationType,
@@ -155,6 +184,7 @@ impl<T: WowHeaderR + WowHeaderW> M2AnimationTrackHeader<T> {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2SplineKey<T: WowHeaderR + WowHeaderW> {
     pub value: T,
     pub in_tan: T,
@@ -162,6 +192,7 @@ pub struct M2SplineKey<T: WowHeaderR + WowHeaderW> {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TrackVec<T> {
     Single(Vec<T>),
     Multiple(Vec<Vec<T>>),
@@ -176,6 +207,29 @@ impl<T: WowHeaderR + WowHeaderW> VWowDataR<MD20Version, TrackArray<T>> for Track
     }
 }
 
+impl<T: WowHeaderR + WowHeaderW> WowDataW<TrackArray<T>> for TrackVec<T> {
+    fn wow_write_data<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        header: &TrackArray<T>,
+    ) -> WDResult<TrackArray<T>> {
+        Ok(match (header, self) {
+            (TrackArray::Single(array), Self::Single(values)) => {
+                TrackArray::Single(values.wow_write_data(writer, array)?)
+            }
+            (TrackArray::Multiple(_), Self::Multiple(sequences)) => {
+                // The values of each sequence first, then the arrays pointing to them
+                let arrays = sequences
+                    .iter()
+                    .map(|values| values.wow_write(writer))
+                    .collect::<WDResult<Vec<_>>>()?;
+                TrackArray::Multiple(arrays.wow_write(writer)?)
+            }
+            _ => return Err(WowDataError::HeaderMismatch("animation track".into())),
+        })
+    }
+}
+
 #[cfg(feature = "trimmed-debug-output")]
 pub fn trimmed_trackvec_fmt<T: fmt::Debug>(n: &TrackVec<T>, f: &mut fmt::Formatter) -> fmt::Result {
     use std::cmp;
@@ -209,7 +263,8 @@ pub fn trimmed_trackvec_fmt<T: fmt::Debug>(n: &T, f: &mut fmt::Formatter) -> fmt
     write!(f, "{:#?}", n)
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version, header = M2AnimationTrackHeader<T>)]
 pub struct M2AnimationTrackData<T: fmt::Debug + WowHeaderR + WowHeaderW> {
     #[wow_data(versioned)]
@@ -242,6 +297,7 @@ impl<T: fmt::Debug + WowHeaderR + WowHeaderW> M2AnimationTrackData<T> {
 
 /// Animation block for a specific animation type
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2AnimationBlock<T: WowHeaderR + WowHeaderW> {
     #[wow_data(versioned)]
@@ -261,13 +317,15 @@ impl<T: WowHeaderR + WowHeaderW> M2AnimationBlock<T> {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2FakeAnimationBlockHeader<T: WowHeaderR + WowHeaderW> {
     pub timestamps: WowArray<u16>,
     pub keys: WowArray<u16>,
     pub values: WowArray<T>,
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(header = M2FakeAnimationBlockHeader<T>)]
 pub struct M2FakeAnimationBlockData<T: WowHeaderR + WowHeaderW> {
     pub timestamps: Vec<u16>,
@@ -276,12 +334,14 @@ pub struct M2FakeAnimationBlockData<T: WowHeaderR + WowHeaderW> {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2FakeAnimationBlock<T: WowHeaderR + WowHeaderW> {
     pub header: M2FakeAnimationBlockHeader<T>,
     pub data: M2FakeAnimationBlockData<T>,
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2AnimationTiming {
     StartEnd(u32, u32),
@@ -297,6 +357,7 @@ impl Default for M2AnimationTiming {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2AnimationBlending {
     Time(u32),
@@ -313,6 +374,7 @@ impl Default for M2AnimationBlending {
 
 /// Animation data for a model
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2Animation {
     pub animation_id: u16,
@@ -335,6 +397,7 @@ pub struct M2Animation {
 }
 
 #[derive(Debug, Clone, Default, PartialEq, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2SequenceFallback {
     pub fallback_animation_id: i16,
     pub flags: u16,
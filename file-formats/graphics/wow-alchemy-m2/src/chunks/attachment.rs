@@ -1,6 +1,6 @@
 use wow_alchemy_data::prelude::*;
 use wow_alchemy_data::types::C3Vector;
-use wow_alchemy_data_derive::{WowDataR, WowEnumFrom, WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowDataR, WowDataW, WowEnumFrom, WowHeaderR, WowHeaderW};
 
 use crate::chunks::animation::M2AnimationTrackHeader;
 use crate::version::MD20Version;
@@ -8,6 +8,7 @@ use crate::version::MD20Version;
 use super::animation::M2AnimationTrackData;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, WowEnumFrom, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=u32)]
 pub enum M2AttachmentId {
     /// MountMain / ItemVisual0
@@ -136,6 +137,7 @@ pub enum M2AttachmentId {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2AttachmentHeader {
     pub id: M2AttachmentId,
@@ -146,7 +148,8 @@ pub struct M2AttachmentHeader {
     pub animate_attached: M2AnimationTrackHeader<u8>,
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version, header = M2AttachmentHeader)]
 pub struct M2AttachmentData {
     #[wow_data(versioned)]
@@ -154,6 +157,7 @@ pub struct M2AttachmentData {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2Attachment {
     pub header: M2AttachmentHeader,
     pub data: M2AttachmentData,
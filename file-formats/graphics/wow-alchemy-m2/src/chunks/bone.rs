@@ -1,9 +1,9 @@
 use std::io::SeekFrom;
 
-use wow_alchemy_data::error::Result as WDResult;
+use wow_alchemy_data::error::{Result as WDResult, WowDataError};
 use wow_alchemy_data::types::{C3Vector, Quaternion, Quaternion16, VWowDataR, WowArrayV};
 use wow_alchemy_data::{prelude::*, v_wow_collection};
-use wow_alchemy_data_derive::{WowDataR, WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowDataR, WowDataW, WowHeaderR, WowHeaderW};
 
 use crate::Result;
 use crate::version::MD20Version;
@@ -12,6 +12,7 @@ use super::animation::{M2AnimationTrackData, M2AnimationTrackHeader};
 
 bitflags::bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, WowHeaderR, WowHeaderW)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[wow_data(bitflags=u32)]
     pub struct M2BoneFlags: u32 {
         const SPHERICAL_BILLBOARD = 0x8;
@@ -31,6 +32,7 @@ bitflags::bitflags! {
 }
 
 #[derive(Debug, Clone, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum M2BoneRotationHeader {
     Vanilla(M2AnimationTrackHeader<Quaternion>),
     Later(M2AnimationTrackHeader<Quaternion16>),
@@ -47,6 +49,7 @@ impl VWowHeaderR<MD20Version> for M2BoneRotationHeader {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum M2BoneRotationData {
     Vanilla(M2AnimationTrackData<Quaternion>),
     Later(M2AnimationTrackData<Quaternion16>),
@@ -66,7 +69,26 @@ impl VWowDataR<MD20Version, M2BoneRotationHeader> for M2BoneRotationData {
     }
 }
 
+impl WowDataW<M2BoneRotationHeader> for M2BoneRotationData {
+    fn wow_write_data<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        header: &M2BoneRotationHeader,
+    ) -> WDResult<M2BoneRotationHeader> {
+        Ok(match (header, self) {
+            (M2BoneRotationHeader::Vanilla(header), Self::Vanilla(data)) => {
+                M2BoneRotationHeader::Vanilla(data.wow_write_data(writer, header)?)
+            }
+            (M2BoneRotationHeader::Later(header), Self::Later(data)) => {
+                M2BoneRotationHeader::Later(data.wow_write_data(writer, header)?)
+            }
+            _ => return Err(WowDataError::HeaderMismatch("bone rotation".into())),
+        })
+    }
+}
+
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2BoneCrc {
     None,
@@ -80,6 +102,7 @@ pub enum M2BoneCrc {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2BoneHeader {
     pub bone_id: i32,
@@ -101,7 +124,8 @@ pub struct M2BoneHeader {
     pub pivot: C3Vector,
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version=MD20Version, header=M2BoneHeader)]
 pub struct M2BoneData {
     #[wow_data(versioned)]
@@ -113,6 +137,7 @@ pub struct M2BoneData {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2Bone {
     pub header: M2BoneHeader,
     pub data: M2BoneData,
@@ -1,14 +1,15 @@
 use crate::chunks::animation::M2AnimationTrackHeader;
 use crate::version::MD20Version;
-use wow_alchemy_data::error::Result as WDResult;
+use wow_alchemy_data::error::{Result as WDResult, WowDataError};
 use wow_alchemy_data::prelude::*;
 use wow_alchemy_data::types::C3Vector;
-use wow_alchemy_data_derive::{WowDataR, WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowDataR, WowDataW, WowHeaderR, WowHeaderW};
 
 use super::animation::{M2AnimationTrackData, M2SplineKey};
 
 bitflags::bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, WowHeaderR, WowHeaderW)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[wow_data(bitflags=u16)]
     pub struct M2CameraFlags: u16 {
         /// Camera uses custom UVs for positioning
@@ -21,6 +22,7 @@ bitflags::bitflags! {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2CameraFov {
     None,
@@ -30,6 +32,7 @@ pub enum M2CameraFov {
 }
 
 #[derive(Debug, Clone, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = M2Version)]
 pub enum M2CameraFovAnimationHeader {
     None,
@@ -49,6 +52,7 @@ impl VWowHeaderR<MD20Version> for M2CameraFovAnimationHeader {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum M2CameraFovAnimation {
     None,
 
@@ -69,7 +73,24 @@ impl VWowDataR<MD20Version, M2CameraFovAnimationHeader> for M2CameraFovAnimation
     }
 }
 
+impl WowDataW<M2CameraFovAnimationHeader> for M2CameraFovAnimation {
+    fn wow_write_data<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        header: &M2CameraFovAnimationHeader,
+    ) -> WDResult<M2CameraFovAnimationHeader> {
+        Ok(match (header, self) {
+            (M2CameraFovAnimationHeader::Some(header), Self::Some(data)) => {
+                M2CameraFovAnimationHeader::Some(data.wow_write_data(writer, header)?)
+            }
+            (M2CameraFovAnimationHeader::None, Self::None) => M2CameraFovAnimationHeader::None,
+            _ => return Err(WowDataError::HeaderMismatch("camera fov animation".into())),
+        })
+    }
+}
+
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2CameraHeader {
     pub camera_type: u32,
@@ -95,7 +116,8 @@ pub struct M2CameraHeader {
     pub fov_animation: M2CameraFovAnimationHeader,
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version, header = M2CameraHeader)]
 pub struct M2CameraData {
     #[wow_data(versioned)]
@@ -112,6 +134,7 @@ pub struct M2CameraData {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2Camera {
     pub header: M2CameraHeader,
     pub data: M2CameraData,
@@ -1,6 +1,6 @@
 use wow_alchemy_data::prelude::*;
 use wow_alchemy_data::types::Color;
-use wow_alchemy_data_derive::{WowDataR, WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowDataR, WowDataW, WowHeaderR, WowHeaderW};
 
 use crate::chunks::animation::M2AnimationTrackHeader;
 use crate::version::MD20Version;
@@ -8,6 +8,7 @@ use crate::version::MD20Version;
 use super::animation::M2AnimationTrackData;
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2ColorAnimationHeader {
     #[wow_data(versioned)]
@@ -17,7 +18,8 @@ pub struct M2ColorAnimationHeader {
     pub alpha: M2AnimationTrackHeader<u16>,
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version, header = M2ColorAnimationHeader)]
 pub struct M2ColorAnimationData {
     #[wow_data(versioned)]
@@ -27,6 +29,7 @@ pub struct M2ColorAnimationData {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2ColorAnimation {
     pub header: M2ColorAnimationHeader,
     pub data: M2ColorAnimationData,
@@ -1,13 +1,14 @@
 use wow_alchemy_data::prelude::*;
 use wow_alchemy_data::types::C3Vector;
 use wow_alchemy_data::types::MagicStr;
-use wow_alchemy_data_derive::{WowDataR, WowEnumFrom, WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowDataR, WowDataW, WowEnumFrom, WowHeaderR, WowHeaderW};
 
 use crate::version::MD20Version;
 
 use super::animation::{M2AnimationBaseTrackData, M2AnimationBaseTrackHeader};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, WowEnumFrom, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=MagicStr)]
 pub enum M2EventIdentifier {
     /// PlaySoundKit (customAttack\[x\]) | soundEffect ID is defined by CreatureSoundDataRec::m_customAttack\[x\]
@@ -431,6 +432,7 @@ pub enum M2EventIdentifier {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2EventHeader {
     pub identifier: M2EventIdentifier,
@@ -441,7 +443,8 @@ pub struct M2EventHeader {
     pub enabled: M2AnimationBaseTrackHeader,
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version, header = M2EventHeader)]
 pub struct M2EventData {
     #[wow_data(versioned)]
@@ -449,6 +452,7 @@ pub struct M2EventData {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2Event {
     pub header: M2EventHeader,
     pub data: M2EventData,
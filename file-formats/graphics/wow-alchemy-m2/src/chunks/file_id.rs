@@ -20,6 +20,7 @@ pub const TXID: MagicStr = *b"TXID";
 pub type FileId = u32;
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnimationFile {
     pub anim_id: u16,
     pub sub_anim_id: u16,
@@ -27,6 +28,7 @@ pub struct AnimationFile {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SkinFiles {
     pub file_ids: Vec<u32>,
     pub lod_file_ids: Vec<u32>,
@@ -1,6 +1,6 @@
 use wow_alchemy_data::prelude::*;
 use wow_alchemy_data::types::{C3Vector, Color};
-use wow_alchemy_data_derive::{WowDataR, WowEnumFrom, WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowDataR, WowDataW, WowEnumFrom, WowHeaderR, WowHeaderW};
 
 use crate::chunks::animation::M2AnimationTrackHeader;
 use crate::version::MD20Version;
@@ -8,6 +8,7 @@ use crate::version::MD20Version;
 use super::animation::M2AnimationTrackData;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, WowEnumFrom, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=u16)]
 pub enum M2LightType {
     /// Directional light (like the sun)
@@ -25,6 +26,7 @@ pub enum M2LightType {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2LightHeader {
     pub light_type: M2LightType,
@@ -49,7 +51,8 @@ pub struct M2LightHeader {
     pub visibility_animation: M2AnimationTrackHeader<u8>,
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version, header = M2LightHeader)]
 pub struct M2LightData {
     #[wow_data(versioned)]
@@ -71,6 +74,7 @@ pub struct M2LightData {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2Light {
     pub header: M2LightHeader,
     pub data: M2LightData,
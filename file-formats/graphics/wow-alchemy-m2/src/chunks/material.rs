@@ -3,6 +3,7 @@ use wow_alchemy_data_derive::{WowEnumFrom, WowHeaderR, WowHeaderW};
 
 bitflags::bitflags! {
     #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, WowHeaderR, WowHeaderW)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[wow_data(bitflags=u16)]
     pub struct M2RenderFlags: u16 {
         const UNLIT = 0x01;
@@ -22,6 +23,7 @@ bitflags::bitflags! {
 
 bitflags::bitflags! {
     #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, WowHeaderR, WowHeaderW)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[wow_data(bitflags=u16)]
     pub struct M2BlendMode: u16 {
         const OPAQUE = 0;
@@ -37,6 +39,7 @@ bitflags::bitflags! {
 
 /// Material texture uv transformations
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, WowEnumFrom, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=u16)]
 pub enum M2TexTransformType {
     #[default]
@@ -57,6 +60,7 @@ pub enum M2TexTransformType {
 /// Represents a material layer (render flags) in an M2 model
 /// This corresponds to the ModelRenderFlagsM2 structure in WMVx
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2Material {
     pub flags: M2RenderFlags,
     pub blend_mode: M2BlendMode,
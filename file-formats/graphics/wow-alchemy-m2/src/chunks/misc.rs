@@ -5,6 +5,7 @@ use wow_alchemy_data_derive::{WowHeaderR, WowHeaderW};
 pub const TXAC: MagicStr = *b"TXAC";
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TXACData {
     pub _unknown1: u8,
     pub _unknown2: u8,
@@ -1,9 +1,10 @@
 use crate::MD20Version;
 use crate::chunks::animation::M2AnimationTrackHeader;
+use wow_alchemy_data::error::{Result as WDResult, WowDataError};
 use wow_alchemy_data::prelude::*;
+use wow_alchemy_data::types::C3Vector;
 use wow_alchemy_data::types::{C2Vector, ColorA, VectorFp6_9, WowArray, WowCharArray};
-use wow_alchemy_data::{error::Result as WDResult, types::C3Vector};
-use wow_alchemy_data_derive::{WowDataR, WowEnumFrom, WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowDataR, WowDataW, WowEnumFrom, WowHeaderR, WowHeaderW};
 
 use super::animation::{
     M2AnimationTrackData, M2Box, M2FakeAnimationBlockData, M2FakeAnimationBlockHeader, M2Range,
@@ -11,6 +12,7 @@ use super::animation::{
 
 bitflags::bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, WowHeaderR, WowHeaderW)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[wow_data(bitflags=u32)]
     pub struct M2ParticleFlags: u32 {
         /// Particles are billboarded
@@ -57,6 +59,7 @@ bitflags::bitflags! {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, WowEnumFrom)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=u8)]
 pub enum M2ParticleEmitterType {
     /// Point emitter (particles spawn from a single point)
@@ -77,6 +80,7 @@ pub enum M2ParticleEmitterType {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2ParticleEmitterBlending {
     Vanilla {
@@ -93,6 +97,7 @@ pub enum M2ParticleEmitterBlending {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2ParticleEmitterMultiTextureParam {
     PreCata {
@@ -105,6 +110,7 @@ pub enum M2ParticleEmitterMultiTextureParam {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2ParticleEmitterLifespanVary {
     None,
@@ -114,6 +120,7 @@ pub enum M2ParticleEmitterLifespanVary {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2ParticleEmitterEmissionRateVary {
     None,
@@ -123,6 +130,7 @@ pub enum M2ParticleEmitterEmissionRateVary {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2ParticleEmitterColorAnimationHeader {
     UpToTbc {
@@ -147,6 +155,7 @@ pub enum M2ParticleEmitterColorAnimationHeader {
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum M2ParticleEmitterColorAnimation {
     UpToTbc,
     Later {
@@ -192,7 +201,51 @@ impl VWowDataR<MD20Version, M2ParticleEmitterColorAnimationHeader>
     }
 }
 
+impl WowDataW<M2ParticleEmitterColorAnimationHeader> for M2ParticleEmitterColorAnimation {
+    fn wow_write_data<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        header: &M2ParticleEmitterColorAnimationHeader,
+    ) -> WDResult<M2ParticleEmitterColorAnimationHeader> {
+        Ok(match (header, self) {
+            (
+                M2ParticleEmitterColorAnimationHeader::Later {
+                    color_animation,
+                    alpha_animation,
+                    scale_animation,
+                    scale_vary,
+                    head_cell_animation,
+                    tail_cell_animation,
+                },
+                Self::Later {
+                    color_animation: color_data,
+                    alpha_animation: alpha_data,
+                    scale_animation: scale_data,
+                    head_cell_animation: head_cell_data,
+                    tail_cell_animation: tail_cell_data,
+                },
+            ) => M2ParticleEmitterColorAnimationHeader::Later {
+                color_animation: color_data.wow_write_data(writer, color_animation)?,
+                alpha_animation: alpha_data.wow_write_data(writer, alpha_animation)?,
+                scale_animation: scale_data.wow_write_data(writer, scale_animation)?,
+                scale_vary: *scale_vary,
+                head_cell_animation: head_cell_data.wow_write_data(writer, head_cell_animation)?,
+                tail_cell_animation: tail_cell_data.wow_write_data(writer, tail_cell_animation)?,
+            },
+            (M2ParticleEmitterColorAnimationHeader::UpToTbc { .. }, Self::UpToTbc) => {
+                header.clone()
+            }
+            _ => {
+                return Err(WowDataError::HeaderMismatch(
+                    "particle emitter color animation".into(),
+                ));
+            }
+        })
+    }
+}
+
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2ParticleEmitterSpin {
     UpToTbc {
@@ -210,6 +263,7 @@ pub enum M2ParticleEmitterSpin {
 
 /// Represents a particle emitter in an M2 model
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2ParticleEmitterOldHeader {
     pub id: u32,
@@ -272,7 +326,8 @@ pub struct M2ParticleEmitterOldHeader {
     pub enabled_in: M2AnimationTrackHeader<u8>,
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version, header = M2ParticleEmitterOldHeader)]
 pub struct M2ParticleEmitterOldData {
     pub model_filename: String,
@@ -305,12 +360,14 @@ pub struct M2ParticleEmitterOldData {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2ParticleEmitterOld {
     pub header: M2ParticleEmitterOldHeader,
     pub data: M2ParticleEmitterOldData,
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2ParticleEmitterNewHeader {
     #[wow_data(versioned)]
@@ -319,7 +376,8 @@ pub struct M2ParticleEmitterNewHeader {
     pub multi_texture_param_1: [VectorFp6_9; 2],
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version, header = M2ParticleEmitterNewHeader)]
 pub struct M2ParticleEmitterNewData {
     #[wow_data(versioned)]
@@ -327,6 +385,7 @@ pub struct M2ParticleEmitterNewData {
 }
 
 #[derive(Debug, Clone, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = M2Version)]
 pub enum M2ParticleEmitterHeader {
     PreCata(M2ParticleEmitterOldHeader),
@@ -346,23 +405,45 @@ impl VWowHeaderR<MD20Version> for M2ParticleEmitterHeader {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct M2ParticleEmitterNew {
+    pub header: M2ParticleEmitterNewHeader,
+    pub data: M2ParticleEmitterNewData,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum M2ParticleEmitter {
-    PreCata(M2ParticleEmitterOldData),
-    PostCata(M2ParticleEmitterNewData),
+    PreCata(M2ParticleEmitterOld),
+    PostCata(M2ParticleEmitterNew),
 }
 
-impl VWowDataR<MD20Version, M2ParticleEmitterHeader> for M2ParticleEmitter {
-    fn new_from_header<R: Read + Seek>(
+impl M2ParticleEmitter {
+    pub fn read_from_header<R: Read + Seek>(
         reader: &mut R,
-        header: &M2ParticleEmitterHeader,
+        header: M2ParticleEmitterHeader,
     ) -> WDResult<Self> {
         Ok(match header {
-            M2ParticleEmitterHeader::PreCata(header) => {
-                Self::PreCata(reader.v_new_from_header(header)?)
-            }
-            M2ParticleEmitterHeader::PostCata(header) => {
-                Self::PostCata(reader.v_new_from_header(header)?)
-            }
+            M2ParticleEmitterHeader::PreCata(header) => Self::PreCata(M2ParticleEmitterOld {
+                data: reader.v_new_from_header(&header)?,
+                header,
+            }),
+            M2ParticleEmitterHeader::PostCata(header) => Self::PostCata(M2ParticleEmitterNew {
+                data: reader.v_new_from_header(&header)?,
+                header,
+            }),
+        })
+    }
+
+    /// Write the data of the emitter and return its header pointing to it
+    pub fn write_data<W: Write + Seek>(&self, writer: &mut W) -> WDResult<M2ParticleEmitterHeader> {
+        Ok(match self {
+            Self::PreCata(emitter) => M2ParticleEmitterHeader::PreCata(
+                emitter.data.wow_write_data(writer, &emitter.header)?,
+            ),
+            Self::PostCata(emitter) => M2ParticleEmitterHeader::PostCata(
+                emitter.data.wow_write_data(writer, &emitter.header)?,
+            ),
         })
     }
 }
@@ -2,11 +2,12 @@ use crate::chunks::animation::M2AnimationTrackHeader;
 use crate::version::MD20Version;
 use wow_alchemy_data::prelude::*;
 use wow_alchemy_data::types::{C3Vector, Color, WowArray};
-use wow_alchemy_data_derive::{WowDataR, WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowDataR, WowDataW, WowHeaderR, WowHeaderW};
 
 use super::animation::M2AnimationTrackData;
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2RibbonEmitterRest {
     None,
@@ -20,6 +21,7 @@ pub enum M2RibbonEmitterRest {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2RibbonEmitterHeader {
     pub id: u32,
@@ -48,7 +50,8 @@ pub struct M2RibbonEmitterHeader {
     pub rest: M2RibbonEmitterRest,
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version, header = M2RibbonEmitterHeader)]
 pub struct M2RibbonEmitterData {
     pub texture_indices: Vec<u16>,
@@ -68,6 +71,7 @@ pub struct M2RibbonEmitterData {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2RibbonEmitter {
     pub header: M2RibbonEmitterHeader,
     pub data: M2RibbonEmitterData,
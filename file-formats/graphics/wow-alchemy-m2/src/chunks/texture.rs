@@ -4,6 +4,7 @@ use wow_alchemy_data::{error::Result as WDResult, types::WowString};
 use wow_alchemy_data_derive::{WowEnumFrom, WowHeaderR, WowHeaderW};
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, WowEnumFrom, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=u32)]
 pub enum M2TextureType {
     /// Texture defined in filename
@@ -72,6 +73,7 @@ pub enum M2TextureType {
 
 bitflags::bitflags! {
     #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, WowHeaderR, WowHeaderW)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[wow_data(bitflags=u32)]
     pub struct M2TextureFlags: u32 {
         /// Texture is wrapped horizontally
@@ -85,6 +87,7 @@ bitflags::bitflags! {
 }
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2TextureHeader {
     pub texture_type: M2TextureType,
     pub flags: M2TextureFlags,
@@ -102,6 +105,7 @@ impl M2TextureHeader {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2TextureData {
     pub filename: String,
 }
@@ -114,7 +118,20 @@ impl WowDataR<M2TextureHeader> for M2TextureData {
     }
 }
 
+impl WowDataW<M2TextureHeader> for M2TextureData {
+    fn wow_write_data<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        header: &M2TextureHeader,
+    ) -> WDResult<M2TextureHeader> {
+        let mut header = header.clone();
+        header.filename = self.filename.wow_write_data(writer, &header.filename)?;
+        Ok(header)
+    }
+}
+
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2Texture {
     pub header: M2TextureHeader,
     pub data: M2TextureData,
@@ -5,6 +5,7 @@ use crate::chunks::animation::M2AnimationTrackHeader;
 use crate::version::MD20Version;
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, WowEnumFrom, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=u16)]
 pub enum M2TextureAnimationType {
     /// No animation
@@ -22,6 +23,7 @@ pub enum M2TextureAnimationType {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2TextureAnimation {
     pub animation_type: M2TextureAnimationType,
@@ -1,6 +1,6 @@
 use wow_alchemy_data::prelude::*;
 use wow_alchemy_data::types::{C3Vector, Quaternion};
-use wow_alchemy_data_derive::{WowDataR, WowEnumFrom, WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowDataR, WowDataW, WowEnumFrom, WowHeaderR, WowHeaderW};
 
 use crate::chunks::animation::M2AnimationTrackHeader;
 use crate::version::MD20Version;
@@ -8,6 +8,7 @@ use crate::version::MD20Version;
 use super::animation::M2AnimationTrackData;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, WowEnumFrom, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=u16)]
 pub enum M2TextureTransformType {
     #[wow_data(expr = 0)]
@@ -23,6 +24,7 @@ pub enum M2TextureTransformType {
 }
 
 #[derive(Debug, Clone, Copy, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2TextureTransformIdType {
     #[wow_data(read_if = version >= MD20Version::BfAPlus)]
@@ -34,6 +36,7 @@ pub enum M2TextureTransformIdType {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2TextureTransformHeader {
     #[wow_data(versioned)]
@@ -49,7 +52,8 @@ pub struct M2TextureTransformHeader {
     pub scaling: M2AnimationTrackHeader<C3Vector>,
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version, header = M2TextureTransformHeader)]
 pub struct M2TextureTransformData {
     #[wow_data(versioned)]
@@ -63,6 +67,7 @@ pub struct M2TextureTransformData {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2TextureTransform {
     pub header: M2TextureTransformHeader,
     pub data: M2TextureTransformData,
@@ -1,18 +1,20 @@
 use wow_alchemy_data::prelude::*;
-use wow_alchemy_data_derive::{WowDataR, WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowDataR, WowDataW, WowHeaderR, WowHeaderW};
 
 use crate::version::MD20Version;
 
 use super::animation::{M2AnimationTrackData, M2AnimationTrackHeader};
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct M2TransparencyAnimationHeader {
     #[wow_data(versioned)]
     pub alpha: M2AnimationTrackHeader<u16>,
 }
 
-#[derive(Debug, Clone, WowDataR)]
+#[derive(Debug, Clone, WowDataR, WowDataW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version, header = M2TransparencyAnimationHeader)]
 pub struct M2TransparencyAnimationData {
     #[wow_data(versioned)]
@@ -20,6 +22,7 @@ pub struct M2TransparencyAnimationData {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2TransparencyAnimation {
     pub header: M2TransparencyAnimationHeader,
     pub data: M2TransparencyAnimationData,
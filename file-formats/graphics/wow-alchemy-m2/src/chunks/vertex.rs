@@ -1,9 +1,10 @@
 use wow_alchemy_data::prelude::*;
 use wow_alchemy_data::types::{C2Vector, C3Vector};
-use wow_alchemy_data_derive::{WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowHeaderR, WowHeaderW, WowPod};
 
 bitflags::bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, WowHeaderR, WowHeaderW)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[wow_data(bitflags=u8)]
     pub struct M2VertexFlags: u8 {
         const TRANSFORM_BONE_0 = 0x01;
@@ -17,7 +18,9 @@ bitflags::bitflags! {
     }
 }
 
-#[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[derive(Debug, Clone, Copy, Default, WowHeaderR, WowHeaderW, WowPod)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct M2Vertex {
     pub position: C3Vector,
     pub bone_weights: [u8; 4],
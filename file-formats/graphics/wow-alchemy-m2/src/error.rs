@@ -14,6 +14,11 @@ pub enum M2Error {
     #[error("wow-alchemy-data error: {0}")]
     WowData(#[from] wow_alchemy_data::error::WowDataError),
 
+    /// The file provider failed to read a referenced file
+    #[cfg(feature = "vfs")]
+    #[error("File provider error: {0}")]
+    Provider(#[from] wow_alchemy_vfs::VfsError),
+
     /// Invalid magic number in the file header
     #[error("Invalid magic number: expected '{expected}', got '{actual}'")]
     InvalidMagic { expected: String, actual: String },
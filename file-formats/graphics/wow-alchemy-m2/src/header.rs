@@ -1,7 +1,7 @@
 use std::io::SeekFrom;
 
 use bitflags::bitflags;
-use wow_alchemy_data::error::Result as WDResult;
+use wow_alchemy_data::error::{Result as WDResult, WowDataError};
 use wow_alchemy_data::prelude::*;
 use wow_alchemy_data::types::{BoundingBox, C3Vector, MagicStr, WowArray, WowArrayV, WowCharArray};
 use wow_alchemy_data_derive::{WowHeaderR, WowHeaderW};
@@ -26,6 +26,7 @@ pub const MD20_MAGIC: MagicStr = *b"MD20";
 
 bitflags! {
     #[derive(Debug, Clone, Default, Copy, PartialEq, Eq, WowHeaderR, WowHeaderW)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[wow_data(bitflags=u32)]
     pub struct M2ModelFlags: u32 {
         const TILT_X = 0x0001;
@@ -64,6 +65,7 @@ bitflags! {
 }
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2PlayableAnimationLookupHeader {
     #[wow_data(read_if = version <= MD20Version::TBCV4)]
@@ -74,6 +76,7 @@ pub enum M2PlayableAnimationLookupHeader {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum M2PlayableAnimationLookup {
     Some(Vec<M2SequenceFallback>),
 
@@ -95,9 +98,32 @@ impl VWowDataR<MD20Version, M2PlayableAnimationLookupHeader> for M2PlayableAnima
     }
 }
 
+impl WowDataW<M2PlayableAnimationLookupHeader> for M2PlayableAnimationLookup {
+    fn wow_write_data<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        header: &M2PlayableAnimationLookupHeader,
+    ) -> WDResult<M2PlayableAnimationLookupHeader> {
+        Ok(match (header, self) {
+            (M2PlayableAnimationLookupHeader::Some(array), Self::Some(lookup)) => {
+                M2PlayableAnimationLookupHeader::Some(lookup.wow_write_data(writer, array)?)
+            }
+            (M2PlayableAnimationLookupHeader::None, Self::None) => {
+                M2PlayableAnimationLookupHeader::None
+            }
+            _ => {
+                return Err(WowDataError::HeaderMismatch(
+                    "playable animation lookup".into(),
+                ));
+            }
+        })
+    }
+}
+
 pub type M2SkinProfile = u32;
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2SkinProfilesHeader {
     UpToTBC(WowArray<M2SkinProfile>),
@@ -113,6 +139,7 @@ impl Default for M2SkinProfilesHeader {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum M2SkinProfiles {
     Some(Vec<M2SkinProfile>),
 
@@ -132,7 +159,24 @@ impl VWowDataR<MD20Version, M2SkinProfilesHeader> for M2SkinProfiles {
     }
 }
 
+impl WowDataW<M2SkinProfilesHeader> for M2SkinProfiles {
+    fn wow_write_data<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        header: &M2SkinProfilesHeader,
+    ) -> WDResult<M2SkinProfilesHeader> {
+        Ok(match (header, self) {
+            (M2SkinProfilesHeader::UpToTBC(array), Self::Some(profiles)) => {
+                M2SkinProfilesHeader::UpToTBC(profiles.wow_write_data(writer, array)?)
+            }
+            (M2SkinProfilesHeader::Later(count), Self::None) => M2SkinProfilesHeader::Later(*count),
+            _ => return Err(WowDataError::HeaderMismatch("skin profiles".into())),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2TextureFlipbook {
     // 4 uints according to wowdev wiki
     a: u32,
@@ -142,6 +186,7 @@ pub struct M2TextureFlipbook {
 }
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2TextureFlipbooksHeader {
     Some(WowArray<M2TextureFlipbook>),
@@ -152,6 +197,7 @@ pub enum M2TextureFlipbooksHeader {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum M2TextureFlipbooks {
     Some(Vec<M2TextureFlipbook>),
 
@@ -171,7 +217,24 @@ impl VWowDataR<MD20Version, M2TextureFlipbooksHeader> for M2TextureFlipbooks {
     }
 }
 
+impl WowDataW<M2TextureFlipbooksHeader> for M2TextureFlipbooks {
+    fn wow_write_data<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        header: &M2TextureFlipbooksHeader,
+    ) -> WDResult<M2TextureFlipbooksHeader> {
+        Ok(match (header, self) {
+            (M2TextureFlipbooksHeader::Some(array), Self::Some(flipbooks)) => {
+                M2TextureFlipbooksHeader::Some(flipbooks.wow_write_data(writer, array)?)
+            }
+            (M2TextureFlipbooksHeader::None, Self::None) => M2TextureFlipbooksHeader::None,
+            _ => return Err(WowDataError::HeaderMismatch("texture flipbooks".into())),
+        })
+    }
+}
+
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2BlenMapOverrides {
     None,
@@ -181,6 +244,7 @@ pub enum M2BlenMapOverrides {
 }
 
 #[derive(Debug, Clone, Default, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum M2TextureCombinerCombosHeader {
     #[default]
     None,
@@ -195,6 +259,7 @@ impl WowHeaderR for M2TextureCombinerCombosHeader {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum M2TextureCombinerCombos {
     Some(Vec<u16>),
 
@@ -216,7 +281,30 @@ impl WowDataR<M2TextureCombinerCombosHeader> for M2TextureCombinerCombos {
     }
 }
 
+impl WowDataW<M2TextureCombinerCombosHeader> for M2TextureCombinerCombos {
+    fn wow_write_data<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        header: &M2TextureCombinerCombosHeader,
+    ) -> WDResult<M2TextureCombinerCombosHeader> {
+        Ok(match (header, self) {
+            (M2TextureCombinerCombosHeader::Some(array), Self::Some(combos)) => {
+                M2TextureCombinerCombosHeader::Some(combos.wow_write_data(writer, array)?)
+            }
+            (M2TextureCombinerCombosHeader::None, Self::None) => {
+                M2TextureCombinerCombosHeader::None
+            }
+            _ => {
+                return Err(WowDataError::HeaderMismatch(
+                    "texture combiner combos".into(),
+                ));
+            }
+        })
+    }
+}
+
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub enum M2TextureTransforms {
     None,
@@ -227,6 +315,7 @@ pub enum M2TextureTransforms {
 
 /// Based on: <https://wowdev.wiki/M2#Header>
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = MD20Version)]
 pub struct MD20Header {
     pub version: MD20Version,
@@ -386,11 +475,8 @@ impl MD20Header {
             camera_lookup_table: WowArray::default(),
             ribbon_emitters: WowArrayV::default(),
             particle_emitters: WowArrayV::default(),
-            texture_combiner_combos: if version >= MD20Version::TBCV1 {
-                M2TextureCombinerCombosHeader::Some(WowArray::default())
-            } else {
-                M2TextureCombinerCombosHeader::None
-            },
+            // Only present with the USE_TEXTURE_COMBINERS flag
+            texture_combiner_combos: M2TextureCombinerCombosHeader::None,
         }
     }
 }
@@ -9,9 +9,9 @@ pub mod version;
 
 pub use error::{M2Error, Result};
 pub use md20::MD20Model;
-pub use model::M2Model;
+pub use model::{AnimFileData, M2Model};
 pub use phys::PhysFile;
-pub use skin::Skin;
+pub use skin::{Skin, SkinView};
 pub use version::MD20Version;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1,3 +1,5 @@
+use std::io::SeekFrom;
+
 use wow_alchemy_data::error::Result as WDResult;
 use wow_alchemy_data::{prelude::*, v_wow_collection, wow_collection};
 
@@ -21,6 +23,7 @@ use crate::header::{
 };
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MD20Model {
     pub header: MD20Header,
     pub name: String,
@@ -206,7 +209,7 @@ impl WowStructR for MD20Model {
             reader,
             header.version,
             header.particle_emitters,
-            |reader, item_header| reader.v_new_from_header(&item_header)?
+            |reader, item_header| M2ParticleEmitter::read_from_header(reader, item_header)?
         );
 
         Ok(Self {
@@ -253,8 +256,241 @@ impl WowStructR for MD20Model {
     }
 }
 
+/// Write the data of the items of a collection and return their headers
+/// pointing to it, to write as an array after the data
+fn write_items_data<'a, W, H, D>(
+    writer: &mut W,
+    items: impl IntoIterator<Item = (&'a H, &'a D)>,
+) -> WDResult<Vec<H>>
+where
+    W: Write + Seek,
+    H: WowHeaderW + 'a,
+    D: WowDataW<H> + 'a,
+{
+    items
+        .into_iter()
+        .map(|(header, data)| data.wow_write_data(writer, header))
+        .collect()
+}
+
 impl WowStructW for MD20Model {
-    fn wow_write<W: Write + Seek>(&self, _writer: &mut W) -> WDResult<()> {
-        todo!()
+    /// Write the header and then the data in the order of the header fields.
+    ///
+    /// Like when reading, offsets are positions of the writer, so it must be
+    /// right after an `MD20` magic at its start.
+    fn wow_write<W: Write + Seek>(&self, writer: &mut W) -> WDResult<()> {
+        let header_position = writer.stream_position()?;
+        writer.write_all(&vec![0; self.header.wow_size()])?;
+
+        let mut header = self.header.clone();
+        header.name = self.name.wow_write_data(writer, &header.name)?;
+        header.global_sequences = self
+            .global_sequences
+            .wow_write_data(writer, &header.global_sequences)?;
+        header.animations = self.animations.wow_write_data(writer, &header.animations)?;
+        header.animation_lookup = self
+            .animation_lookup
+            .wow_write_data(writer, &header.animation_lookup)?;
+        header.playable_animation_lookup = self
+            .playable_animation_lookup
+            .wow_write_data(writer, &header.playable_animation_lookup)?;
+        header.bones = write_items_data(writer, self.bones.iter().map(|i| (&i.header, &i.data)))?
+            .wow_write_data(writer, &header.bones)?;
+        header.key_bone_lookup = self
+            .key_bone_lookup
+            .wow_write_data(writer, &header.key_bone_lookup)?;
+        header.vertices = self.vertices.wow_write_data(writer, &header.vertices)?;
+        header.skin_profiles = self
+            .skin_profiles
+            .wow_write_data(writer, &header.skin_profiles)?;
+        header.color_animations = write_items_data(
+            writer,
+            self.color_animations.iter().map(|i| (&i.header, &i.data)),
+        )?
+        .wow_write_data(writer, &header.color_animations)?;
+        header.textures =
+            write_items_data(writer, self.textures.iter().map(|i| (&i.header, &i.data)))?
+                .wow_write_data(writer, &header.textures)?;
+        header.texture_weights = write_items_data(
+            writer,
+            self.texture_weights.iter().map(|i| (&i.header, &i.data)),
+        )?
+        .wow_write_data(writer, &header.texture_weights)?;
+        header.texture_flipbooks = self
+            .texture_flipbooks
+            .wow_write_data(writer, &header.texture_flipbooks)?;
+        header.texture_transforms = write_items_data(
+            writer,
+            self.texture_transforms.iter().map(|i| (&i.header, &i.data)),
+        )?
+        .wow_write_data(writer, &header.texture_transforms)?;
+        header.replaceable_texture_lookup = self
+            .replaceable_texture_lookup
+            .wow_write_data(writer, &header.replaceable_texture_lookup)?;
+        header.materials = self.materials.wow_write_data(writer, &header.materials)?;
+        header.bone_lookup_table = self
+            .bone_lookup_table
+            .wow_write_data(writer, &header.bone_lookup_table)?;
+        header.texture_lookup_table = self
+            .texture_lookup_table
+            .wow_write_data(writer, &header.texture_lookup_table)?;
+        header.texture_mapping_lookup_table = self
+            .texture_mapping_lookup_table
+            .wow_write_data(writer, &header.texture_mapping_lookup_table)?;
+        header.transparency_lookup_table = self
+            .transparency_lookup_table
+            .wow_write_data(writer, &header.transparency_lookup_table)?;
+        header.texture_animation_lookup = self
+            .texture_animation_lookup
+            .wow_write_data(writer, &header.texture_animation_lookup)?;
+        header.bounding_triangles = self
+            .bounding_triangles
+            .wow_write_data(writer, &header.bounding_triangles)?;
+        header.bounding_vertices = self
+            .bounding_vertices
+            .wow_write_data(writer, &header.bounding_vertices)?;
+        header.bounding_normals = self
+            .bounding_normals
+            .wow_write_data(writer, &header.bounding_normals)?;
+        header.attachments = write_items_data(
+            writer,
+            self.attachments.iter().map(|i| (&i.header, &i.data)),
+        )?
+        .wow_write_data(writer, &header.attachments)?;
+        header.attachment_lookup_table = self
+            .attachment_lookup_table
+            .wow_write_data(writer, &header.attachment_lookup_table)?;
+        header.events = write_items_data(writer, self.events.iter().map(|i| (&i.header, &i.data)))?
+            .wow_write_data(writer, &header.events)?;
+        header.lights = write_items_data(writer, self.lights.iter().map(|i| (&i.header, &i.data)))?
+            .wow_write_data(writer, &header.lights)?;
+        header.cameras =
+            write_items_data(writer, self.cameras.iter().map(|i| (&i.header, &i.data)))?
+                .wow_write_data(writer, &header.cameras)?;
+        header.camera_lookup_table = self
+            .camera_lookup_table
+            .wow_write_data(writer, &header.camera_lookup_table)?;
+        header.ribbon_emitters = write_items_data(
+            writer,
+            self.ribbon_emitters.iter().map(|i| (&i.header, &i.data)),
+        )?
+        .wow_write_data(writer, &header.ribbon_emitters)?;
+        header.particle_emitters = self
+            .particle_emitters
+            .iter()
+            .map(|emitter| emitter.write_data(writer))
+            .collect::<WDResult<Vec<_>>>()?
+            .wow_write_data(writer, &header.particle_emitters)?;
+        header.texture_combiner_combos = self
+            .texture_combiner_combos
+            .wow_write_data(writer, &header.texture_combiner_combos)?;
+
+        let end = writer.stream_position()?;
+        writer.seek(SeekFrom::Start(header_position))?;
+        writer.wow_write(&header)?;
+        writer.seek(SeekFrom::Start(end))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::MD20Version;
+    use crate::chunks::animation::{M2AnimationTrackData, M2AnimationTrackHeader, TrackVec};
+    use crate::chunks::bone::{
+        M2BoneCrc, M2BoneData, M2BoneFlags, M2BoneHeader, M2BoneRotationData, M2BoneRotationHeader,
+    };
+    use crate::chunks::texture::{M2TextureData, M2TextureHeader};
+    use crate::header::MD20_MAGIC;
+
+    fn write(model: &MD20Model) -> Vec<u8> {
+        let mut data = Cursor::new(Vec::new());
+        data.wow_write(&MD20_MAGIC).unwrap();
+        model.wow_write(&mut data).unwrap();
+        data.into_inner()
+    }
+
+    fn read(data: &[u8]) -> MD20Model {
+        let mut reader = Cursor::new(data);
+        reader.set_position(MD20_MAGIC.len() as u64);
+        MD20Model::wow_read(&mut reader).unwrap()
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let track = M2AnimationTrackData {
+            timestamps: TrackVec::Multiple(vec![vec![0, 500], vec![]]),
+            values: TrackVec::Multiple(vec![vec![C3Vector::default(); 2], vec![]]),
+            ..M2AnimationTrackData::new()
+        };
+        let bone = M2Bone {
+            header: M2BoneHeader {
+                bone_id: -1,
+                flags: M2BoneFlags::TRANSFORMED,
+                parent_bone: -1,
+                submesh_id: 0,
+                bone_crc: M2BoneCrc::Crc(0),
+                position: M2AnimationTrackHeader::new(),
+                rotation: M2BoneRotationHeader::Later(M2AnimationTrackHeader::new()),
+                scaling: M2AnimationTrackHeader::new(),
+                pivot: C3Vector::default(),
+            },
+            data: M2BoneData {
+                position: track.clone(),
+                rotation: M2BoneRotationData::Later(M2AnimationTrackData::new()),
+                scaling: track,
+            },
+        };
+        let model = MD20Model {
+            header: MD20Header::new(MD20Version::WotLK),
+            name: "Murloc".into(),
+            global_sequences: vec![1000],
+            bones: vec![bone],
+            key_bone_lookup: vec![0, -1],
+            textures: vec![
+                M2Texture {
+                    header: M2TextureHeader::default(),
+                    data: M2TextureData {
+                        filename: "Creature\\Murloc\\Murloc.blp".into(),
+                    },
+                },
+                M2Texture::default(),
+            ],
+            ..Default::default()
+        };
+
+        let data = write(&model);
+        let read_model = read(&data);
+        assert_eq!(read_model.name, "Murloc");
+        assert_eq!(read_model.global_sequences, [1000]);
+        assert_eq!(read_model.key_bone_lookup, [0, -1]);
+        assert_eq!(read_model.bones.len(), 1);
+        let TrackVec::Multiple(timestamps) = &read_model.bones[0].data.position.timestamps else {
+            panic!("expected timestamps per animation");
+        };
+        assert_eq!(timestamps, &[vec![0, 500], vec![]]);
+        assert_eq!(
+            read_model.textures[0].data.filename,
+            "Creature\\Murloc\\Murloc.blp"
+        );
+        assert_eq!(read_model.textures[1].data.filename, "");
+
+        // Writing what was read gives the same file
+        assert_eq!(write(&read_model), data);
+    }
+
+    #[test]
+    fn test_write_header_mismatch() {
+        let model = MD20Model {
+            header: MD20Header::new(MD20Version::TBCV1),
+            ..Default::default()
+        };
+        let mut data = Cursor::new(Vec::new());
+        data.wow_write(&MD20_MAGIC).unwrap();
+        assert!(model.wow_write(&mut data).is_err());
     }
 }
@@ -1,18 +1,30 @@
 use std::collections::HashMap;
 use std::io::{Cursor, SeekFrom};
 
+use wow_alchemy_data::chunk_writer::{ChunkSpan, ChunkWriter, MagicOrder};
 use wow_alchemy_data::error::Result as WDResult;
 use wow_alchemy_data::prelude::*;
-use wow_alchemy_data::types::{ChunkHeader, MagicStr, WowStructR};
+use wow_alchemy_data::types::{ChunkHeader, MagicStr, WowStructR, WowStructW};
 use wow_alchemy_data::utils::magic_to_string;
 
 use crate::chunks::{file_id, misc};
 use crate::header::MD20_MAGIC;
 use crate::{M2Error, MD20Model};
 
+#[cfg(feature = "vfs")]
+use {
+    crate::chunks::animation::M2AnimationFlags,
+    crate::header::M2SkinProfilesHeader,
+    crate::skin::SkinVersion,
+    crate::{MD20Version, Result, Skin},
+    wow_alchemy_data::types::VWowStructR,
+    wow_alchemy_vfs::{FileIdResolver, WowFileProvider, path::strip_extension},
+};
+
 pub const MD21_MAGIC: MagicStr = *b"MD21";
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum M2Chunk {
     AFID(Vec<file_id::AnimationFile>),
     SFID(file_id::SkinFiles),
@@ -27,6 +39,7 @@ pub enum M2Chunk {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2Model {
     pub magic: MagicStr,
     pub md20: MD20Model,
@@ -34,6 +47,195 @@ pub struct M2Model {
     pub chunks: Vec<M2Chunk>,
 }
 
+/// The data of an animation sequence stored in a separate `.anim` file
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnimFileData {
+    pub animation_id: u16,
+    pub sub_animation_id: u16,
+    pub data: Vec<u8>,
+}
+
+impl M2Model {
+    /// The skin files from the `SFID` chunk
+    pub fn skin_file_ids(&self) -> Option<&file_id::SkinFiles> {
+        self.chunks.iter().find_map(|chunk| match chunk {
+            M2Chunk::SFID(files) => Some(files),
+            _ => None,
+        })
+    }
+
+    /// The animation files from the `AFID` chunk
+    pub fn anim_file_ids(&self) -> Option<&[file_id::AnimationFile]> {
+        self.chunks.iter().find_map(|chunk| match chunk {
+            M2Chunk::AFID(files) => Some(files.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// The texture files from the `TXID` chunk, one for each texture
+    pub fn texture_file_ids(&self) -> Option<&[file_id::FileId]> {
+        self.chunks.iter().find_map(|chunk| match chunk {
+            M2Chunk::TXID(ids) => Some(ids.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// Replace the texture FileDataIDs of the `TXID` chunk with the file
+    /// names in the textures, with backslashes, which is how models before
+    /// Legion reference them, and remove the chunk. The model is written as
+    /// a plain `MD20` file when no other chunk is left.
+    ///
+    /// Textures without id, like replaceable ones, are left without name.
+    /// Fails without changes when the resolver doesn't know an id.
+    #[cfg(feature = "vfs")]
+    pub fn file_ids_to_paths(&mut self, resolver: &FileIdResolver) -> Result<()> {
+        let Some(ids) = self.texture_file_ids() else {
+            return Ok(());
+        };
+        let names = ids
+            .iter()
+            .map(|&id| match id {
+                0 => Ok(String::new()),
+                _ => resolver
+                    .require_path(id)
+                    .map(|path| path.replace('/', "\\")),
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        for (texture, name) in self.md20.textures.iter_mut().zip(names) {
+            texture.data.filename = name;
+        }
+        self.remove_chunk(file_id::TXID);
+        if self.chunks.is_empty() {
+            self.magic = MD20_MAGIC;
+        }
+        Ok(())
+    }
+
+    /// Replace the file names of the textures with FileDataIDs in a `TXID`
+    /// chunk, which is how models from Legion on reference them
+    ///
+    /// Textures without name, like replaceable ones, get id 0. Fails without
+    /// changes when the resolver doesn't know a name.
+    #[cfg(feature = "vfs")]
+    pub fn paths_to_file_ids(&mut self, resolver: &FileIdResolver) -> Result<()> {
+        let ids = self
+            .md20
+            .textures
+            .iter()
+            .map(|texture| match texture.data.filename.as_str() {
+                "" => Ok(0),
+                name => resolver.require_file_id(name),
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        for texture in &mut self.md20.textures {
+            texture.data.filename.clear();
+        }
+        self.remove_chunk(file_id::TXID);
+        self.chunks.push(M2Chunk::TXID(ids));
+        self.chunk_index
+            .insert(magic_to_string(&file_id::TXID), self.chunks.len() - 1);
+        self.magic = MD21_MAGIC;
+        Ok(())
+    }
+
+    #[cfg(feature = "vfs")]
+    fn remove_chunk(&mut self, magic: MagicStr) {
+        let Some(removed) = self.chunk_index.remove(&magic_to_string(&magic)) else {
+            return;
+        };
+        self.chunks.remove(removed);
+        for index in self.chunk_index.values_mut() {
+            if *index > removed {
+                *index -= 1;
+            }
+        }
+    }
+
+    /// Read the skin profiles of a model from WotLK on, which are in
+    /// separate files: by FileDataID from the `SFID` chunk, or else
+    /// `<name>00.skin` onwards next to the model at `path`
+    ///
+    /// Models before WotLK have their skin profiles inside, so none are read.
+    #[cfg(feature = "vfs")]
+    pub fn load_skins<P: WowFileProvider + ?Sized>(
+        &self,
+        provider: &P,
+        path: &str,
+    ) -> Result<Vec<Skin>> {
+        let M2SkinProfilesHeader::Later(count) = self.md20.header.skin_profiles else {
+            return Ok(Vec::new());
+        };
+        let version = if self.md20.header.version >= MD20Version::Cataclysm {
+            SkinVersion::V3
+        } else {
+            SkinVersion::V2
+        };
+        let file_ids = self.skin_file_ids().map(|files| files.file_ids.as_slice());
+        let base = strip_extension(path);
+
+        (0..count as usize)
+            .map(|i| {
+                let file_id = file_ids.and_then(|ids| ids.get(i)).copied().unwrap_or(0);
+                let data = provider.read_either(file_id, &format!("{base}{i:02}.skin"))?;
+                Ok(Skin::wow_read(&mut Cursor::new(data), version)?)
+            })
+            .collect()
+    }
+
+    /// Read the animation sequences of a model from WotLK on that are in
+    /// separate files: by FileDataID from the `AFID` chunk, or else
+    /// `<name><animation id>-<sub animation id>.anim` next to the model at
+    /// `path` for the sequences whose data isn't in the model
+    #[cfg(feature = "vfs")]
+    pub fn load_anims<P: WowFileProvider + ?Sized>(
+        &self,
+        provider: &P,
+        path: &str,
+    ) -> Result<Vec<AnimFileData>> {
+        if let Some(files) = self.anim_file_ids() {
+            return files
+                .iter()
+                .filter(|file| file.file_id != 0)
+                .map(|file| {
+                    Ok(AnimFileData {
+                        animation_id: file.anim_id,
+                        sub_animation_id: file.sub_anim_id,
+                        data: provider.read_id(file.file_id)?,
+                    })
+                })
+                .collect();
+        }
+
+        if self.md20.header.version < MD20Version::WotLK {
+            return Ok(Vec::new());
+        }
+        let base = strip_extension(path);
+        self.md20
+            .animations
+            .iter()
+            .filter(|animation| {
+                !animation.flags.intersects(
+                    M2AnimationFlags::PRIMARY_BONE_SEQUENCE | M2AnimationFlags::IS_ALIAS,
+                )
+            })
+            .map(|animation| {
+                let anim_path = format!(
+                    "{base}{:04}-{:02}.anim",
+                    animation.animation_id, animation.sub_animation_id
+                );
+                Ok(AnimFileData {
+                    animation_id: animation.animation_id,
+                    sub_animation_id: animation.sub_animation_id,
+                    data: provider.read(&anim_path)?,
+                })
+            })
+            .collect()
+    }
+}
+
 impl WowStructR for M2Model {
     fn wow_read<R: Read + Seek>(reader: &mut R) -> WDResult<Self> {
         let magic: MagicStr = reader.wow_read()?;
@@ -69,11 +271,7 @@ impl WowStructR for M2Model {
                 let mut chunks = Vec::new();
                 let mut chunk_index = HashMap::new();
 
-                loop {
-                    let Ok(chunk_header) = ChunkHeader::wow_read(reader) else {
-                        break;
-                    };
-
+                while let Ok(chunk_header) = ChunkHeader::wow_read(reader) {
                     let (chunk_magic, chunk_data): (&MagicStr, M2Chunk) = match chunk_header.magic {
                         file_id::AFID => (
                             &chunk_header.magic,
@@ -145,3 +343,253 @@ impl WowStructR for M2Model {
         }
     }
 }
+
+impl M2Chunk {
+    fn write<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        magic: MagicStr,
+    ) -> WDResult<ChunkSpan> {
+        match self {
+            Self::AFID(files) => writer.write_items_chunk(magic, files),
+            Self::SFID(files) => {
+                writer.begin_chunk(magic)?;
+                for file_id in files.file_ids.iter().chain(&files.lod_file_ids) {
+                    writer.wow_write(file_id)?;
+                }
+                writer.end_chunk()
+            }
+            Self::BFID(ids)
+            | Self::GPID(ids)
+            | Self::PFID(ids)
+            | Self::RPID(ids)
+            | Self::SKID(ids)
+            | Self::TXID(ids) => writer.write_items_chunk(magic, ids),
+            Self::TXAC(items) => writer.write_items_chunk(magic, items),
+            Self::Unknown(data) => writer.write_items_chunk(magic, data),
+        }
+    }
+}
+
+impl WowStructW for M2Model {
+    /// Write an `MD20` model, or an `MD21` one with the MD20 data in the
+    /// first chunk followed by the other chunks
+    fn wow_write<W: Write + Seek>(&self, writer: &mut W) -> WDResult<()> {
+        // The offsets in the MD20 data are relative to its magic
+        let mut md20 = Cursor::new(Vec::new());
+        md20.wow_write(&MD20_MAGIC)?;
+        self.md20.wow_write(&mut md20)?;
+        let md20 = md20.into_inner();
+
+        match self.magic {
+            MD20_MAGIC => Ok(writer.write_all(&md20)?),
+            MD21_MAGIC => {
+                let mut writer = ChunkWriter::new(writer, MagicOrder::AsIs)?;
+                writer.begin_chunk(MD21_MAGIC)?;
+                writer.write_all(&md20)?;
+                writer.end_chunk()?;
+
+                // Chunks only keep their magic in the index
+                let mut magics = vec![None; self.chunks.len()];
+                for (name, &index) in &self.chunk_index {
+                    if let (Some(magic), Ok(name)) =
+                        (magics.get_mut(index), name.as_bytes().try_into())
+                    {
+                        *magic = Some(name);
+                    }
+                }
+                for (chunk, magic) in self.chunks.iter().zip(magics) {
+                    let magic = magic.ok_or_else(|| {
+                        M2Error::ChunkError("chunk without magic in the chunk index".into())
+                    })?;
+                    chunk.write(&mut writer, magic)?;
+                }
+
+                writer.finish()?;
+                Ok(())
+            }
+            _ => Err(M2Error::InvalidMagic {
+                expected: "MD20 or MD21".into(),
+                actual: magic_to_string(&self.magic),
+            }
+            .into()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "vfs"))]
+mod tests {
+    use super::*;
+    use crate::chunks::animation::M2Animation;
+    use wow_alchemy_data::types::WowStructW;
+    use wow_alchemy_vfs::MemoryProvider;
+
+    fn skin_bytes(indices: Vec<u16>) -> Vec<u8> {
+        let skin = Skin {
+            indices,
+            ..Default::default()
+        };
+        let mut data = Cursor::new(Vec::new());
+        skin.wow_write(&mut data).unwrap();
+        data.into_inner()
+    }
+
+    fn model(version: MD20Version) -> M2Model {
+        let mut model = M2Model::default();
+        model.md20.header.version = version;
+        model.md20.header.skin_profiles = M2SkinProfilesHeader::Later(2);
+        for (animation_id, flags) in [
+            (0, M2AnimationFlags::PRIMARY_BONE_SEQUENCE),
+            (4, M2AnimationFlags::empty()),
+            (5, M2AnimationFlags::IS_ALIAS),
+        ] {
+            model.md20.animations.push(M2Animation {
+                animation_id,
+                flags,
+                ..Default::default()
+            });
+        }
+        model
+    }
+
+    #[test]
+    fn test_load_by_path() {
+        let provider = MemoryProvider::new()
+            .with("Creature\\Murloc\\Murloc00.skin", skin_bytes(vec![0, 1, 2]))
+            .with("creature/murloc/murloc01.skin", skin_bytes(vec![3]))
+            .with("creature/murloc/murloc0004-00.anim", "walk");
+
+        let model = model(MD20Version::Cataclysm);
+        let skins = model
+            .load_skins(&provider, "Creature\\Murloc\\Murloc.m2")
+            .unwrap();
+        assert_eq!(skins.len(), 2);
+        assert_eq!(skins[0].indices, [0, 1, 2]);
+        assert_eq!(skins[1].indices, [3]);
+
+        let anims = model
+            .load_anims(&provider, "Creature\\Murloc\\Murloc.m2")
+            .unwrap();
+        assert_eq!(anims.len(), 1);
+        assert_eq!(
+            (anims[0].animation_id, anims[0].data.as_slice()),
+            (4, b"walk".as_slice())
+        );
+
+        let mut model = model;
+        model.md20.header.skin_profiles = M2SkinProfilesHeader::Later(3);
+        assert!(matches!(
+            model.load_skins(&provider, "creature/murloc/murloc.m2"),
+            Err(M2Error::Provider(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_by_file_id() {
+        let mut provider = MemoryProvider::new();
+        provider.insert_with_id(100, "a.skin", skin_bytes(vec![1]));
+        provider.insert_with_id(101, "b.skin", skin_bytes(vec![2]));
+        provider.insert_with_id(200, "c.anim", "walk");
+
+        let mut model = model(MD20Version::BfAPlus);
+        model.chunks.push(M2Chunk::SFID(file_id::SkinFiles {
+            file_ids: vec![100, 101],
+            lod_file_ids: Vec::new(),
+        }));
+        model.chunks.push(M2Chunk::AFID(vec![
+            file_id::AnimationFile {
+                anim_id: 0,
+                sub_anim_id: 0,
+                file_id: 0,
+            },
+            file_id::AnimationFile {
+                anim_id: 4,
+                sub_anim_id: 0,
+                file_id: 200,
+            },
+        ]));
+
+        let skins = model.load_skins(&provider, "unused.m2").unwrap();
+        assert_eq!(skins[1].indices, [2]);
+        let anims = model.load_anims(&provider, "unused.m2").unwrap();
+        assert_eq!(anims.len(), 1);
+        assert_eq!(anims[0].data, b"walk");
+    }
+
+    #[test]
+    fn test_file_ids_and_paths() {
+        let resolver = FileIdResolver::from_entries([
+            (1000, "creature/murloc/murloc.m2"),
+            (1001, "Creature\\Murloc\\MurlocSkin.blp"),
+        ]);
+        let mut model = model(MD20Version::WotLK);
+        model.magic = MD20_MAGIC;
+        model.md20.textures = vec![Default::default(), Default::default()];
+        model.md20.textures[0].data.filename = "CREATURE\\Murloc\\MurlocSkin.blp".into();
+
+        model.paths_to_file_ids(&resolver).unwrap();
+        assert_eq!(model.magic, MD21_MAGIC);
+        assert_eq!(model.texture_file_ids(), Some([1001, 0].as_slice()));
+        assert!(model.md20.textures[0].data.filename.is_empty());
+
+        model.md20.textures[1].data.filename = "unknown.blp".into();
+        assert!(matches!(
+            model.paths_to_file_ids(&resolver),
+            Err(M2Error::Provider(_))
+        ));
+        model.md20.textures[1].data.filename.clear();
+
+        model.file_ids_to_paths(&resolver).unwrap();
+        assert_eq!(model.magic, MD20_MAGIC);
+        assert!(model.texture_file_ids().is_none());
+        assert!(model.chunk_index.is_empty());
+        assert_eq!(
+            model.md20.textures[0].data.filename,
+            "creature\\murloc\\murlocskin.blp"
+        );
+        assert!(model.md20.textures[1].data.filename.is_empty());
+    }
+
+    #[test]
+    fn test_write_md21() {
+        let mut model = model(MD20Version::MoPPlus);
+        model.magic = MD21_MAGIC;
+        model.md20.textures = vec![Default::default()];
+        model.chunks = vec![
+            M2Chunk::SFID(file_id::SkinFiles {
+                file_ids: vec![100, 101],
+                lod_file_ids: vec![102],
+            }),
+            M2Chunk::TXID(vec![1001]),
+            M2Chunk::Unknown(b"data".to_vec()),
+        ];
+        model.chunk_index =
+            HashMap::from([("SFID".into(), 0), ("TXID".into(), 1), ("ABCD".into(), 2)]);
+
+        let mut data = Cursor::new(Vec::new());
+        model.wow_write(&mut data).unwrap();
+        let data = data.into_inner();
+        assert_eq!(&data[..4], b"MD21");
+        assert_eq!(&data[8..12], b"MD20");
+
+        let read_model = M2Model::wow_read(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(read_model.md20.animations.len(), 3);
+        assert_eq!(read_model.texture_file_ids(), Some([1001].as_slice()));
+        let files = read_model.skin_file_ids().unwrap();
+        assert_eq!(
+            (files.file_ids.as_slice(), files.lod_file_ids.as_slice()),
+            ([100, 101].as_slice(), [102].as_slice())
+        );
+        assert!(matches!(
+            &read_model.chunks[read_model.chunk_index["ABCD"]],
+            M2Chunk::Unknown(unknown) if unknown == b"data"
+        ));
+
+        let mut rewritten = Cursor::new(Vec::new());
+        read_model.wow_write(&mut rewritten).unwrap();
+        assert_eq!(rewritten.into_inner(), data);
+
+        model.chunk_index.remove("ABCD");
+        assert!(model.wow_write(&mut Cursor::new(Vec::new())).is_err());
+    }
+}
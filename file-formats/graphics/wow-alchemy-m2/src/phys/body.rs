@@ -9,6 +9,7 @@ pub const BDY3: MagicStr = string_to_inverted_magic("BDY3");
 pub const BDY4: MagicStr = string_to_inverted_magic("BDY4");
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, WowEnumFrom)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=MagicStr)]
 pub enum Version {
     #[wow_data(expr=BODY)]
@@ -24,6 +25,7 @@ pub enum Version {
 impl DataVersion for Version {}
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub enum BoneIndex {
     Padding([u8; 2]),
@@ -39,6 +41,7 @@ impl Default for BoneIndex {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub enum ShapeIndex {
     #[wow_data(read_if = version <= Version::V2)]
@@ -54,6 +57,7 @@ impl Default for ShapeIndex {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub enum ShapesBaseCount {
     #[wow_data(read_if = version <= Version::V2)]
@@ -78,6 +82,7 @@ impl Default for ShapesBaseCount {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub enum VE1<T: Default + WowHeaderR + WowHeaderW> {
     None,
@@ -93,6 +98,7 @@ impl<T: Default + WowHeaderR + WowHeaderW> Default for VE1<T> {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub enum VGTE2<T: Default + WowHeaderR + WowHeaderW> {
     None,
@@ -108,6 +114,7 @@ impl<T: Default + WowHeaderR + WowHeaderW> Default for VGTE2<T> {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub enum VGTE3<T: Default + WowHeaderR + WowHeaderW> {
     None,
@@ -123,6 +130,7 @@ impl<T: Default + WowHeaderR + WowHeaderW> Default for VGTE3<T> {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub enum VGTE4<T: Default + WowHeaderR + WowHeaderW> {
     None,
@@ -138,6 +146,7 @@ impl<T: Default + WowHeaderR + WowHeaderW> Default for VGTE4<T> {
 }
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub struct Body {
     pub body_type: u16,
@@ -13,6 +13,7 @@ use super::{body, joint, phyt, shape};
 pub const PHYS: MagicStr = string_to_inverted_magic("PHYS");
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PhysChunk {
     ShapeBox(Vec<shape::ShapeBox>),
     ShapeCapsule(Vec<shape::ShapeCapsule>),
@@ -31,6 +32,7 @@ pub enum PhysChunk {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PhysFile {
     pub header: ChunkHeader,
     pub version: PhysVersion,
@@ -54,11 +56,7 @@ impl WowStructR for PhysFile {
         let mut chunks = Vec::new();
         let mut chunk_index = HashMap::new();
 
-        loop {
-            let Ok(chunk_header) = ChunkHeader::wow_read(reader) else {
-                break;
-            };
-
+        while let Ok(chunk_header) = ChunkHeader::wow_read(reader) {
             let (chunk_magic, chunk_data): (&MagicStr, PhysChunk) = match chunk_header.magic {
                 shape::BOXS => (
                     &shape::BOXS,
@@ -2,12 +2,14 @@ use wow_alchemy_data::prelude::*;
 use wow_alchemy_data_derive::{WowHeaderR, WowHeaderW};
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrequencyDamping {
     pub frequency_hz: f32,
     pub damping_ratio: f32,
 }
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TorqueMode {
     pub max_torque: f32,
     pub mode: u32,
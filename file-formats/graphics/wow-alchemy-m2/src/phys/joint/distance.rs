@@ -6,6 +6,7 @@ use wow_alchemy_data_derive::{WowHeaderR, WowHeaderW};
 pub const DSTJ: MagicStr = string_to_inverted_magic("DSTJ");
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct JointDistance {
     pub anchor_a: C3Vector,
     pub anchor_b: C3Vector,
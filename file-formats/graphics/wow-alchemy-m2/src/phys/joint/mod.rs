@@ -21,6 +21,7 @@ use wow_alchemy_data_derive::{WowEnumFrom, WowHeaderR, WowHeaderW};
 pub const JOIN: MagicStr = string_to_inverted_magic("JOIN");
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, WowEnumFrom, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=u16)]
 pub enum JointType {
     #[default]
@@ -39,6 +40,7 @@ pub enum JointType {
 }
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Joint {
     pub body_a_idx: u32,
     pub body_b_idx: u32,
@@ -9,6 +9,7 @@ pub const PRSJ: MagicStr = string_to_inverted_magic("PRSJ");
 pub const PRS2: MagicStr = string_to_inverted_magic("PRS2");
 
 #[derive(Debug, Clone, Default, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, WowEnumFrom)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=MagicStr)]
 pub enum Version {
     #[wow_data(expr=PRSJ)]
@@ -21,6 +22,7 @@ pub enum Version {
 impl DataVersion for Version {}
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub enum VGTE2<T: Default + WowHeaderR + WowHeaderW> {
     None,
@@ -36,6 +38,7 @@ impl<T: Default + WowHeaderR + WowHeaderW> Default for VGTE2<T> {
 }
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub struct JointPrismatic {
     pub frame_a: Mat3x4,
@@ -13,6 +13,7 @@ pub const SHOJ: MagicStr = string_to_inverted_magic("SHOJ");
 pub const SHJ2: MagicStr = string_to_inverted_magic("SHJ2");
 
 #[derive(Debug, Clone, Default, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Version {
     V1,
     V2,
@@ -67,6 +68,7 @@ impl From<Version> for MagicStr {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub enum VGTE2<T: Default + WowHeaderR + WowHeaderW> {
     None,
@@ -82,6 +84,7 @@ impl<T: Default + WowHeaderR + WowHeaderW> Default for VGTE2<T> {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub enum VGTE3<T: Default + WowHeaderR + WowHeaderW> {
     None,
@@ -97,6 +100,7 @@ impl<T: Default + WowHeaderR + WowHeaderW> Default for VGTE3<T> {
 }
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub struct JointShoulder {
     pub frame_a: Mat3x4,
@@ -6,6 +6,7 @@ use wow_alchemy_data_derive::{WowHeaderR, WowHeaderW};
 pub const SPHJ: MagicStr = string_to_inverted_magic("SPHJ");
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct JointSpherical {
     pub anchor_a: C3Vector,
     pub anchor_b: C3Vector,
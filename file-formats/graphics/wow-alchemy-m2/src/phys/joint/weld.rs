@@ -10,6 +10,7 @@ pub const WLJ2: MagicStr = string_to_inverted_magic("WLJ2");
 pub const WLJ3: MagicStr = string_to_inverted_magic("WLJ3");
 
 #[derive(Debug, Clone, Default, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, WowEnumFrom)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=MagicStr)]
 pub enum Version {
     #[wow_data(expr = WELJ)]
@@ -24,6 +25,7 @@ pub enum Version {
 impl DataVersion for Version {}
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub enum VGTE2<T: Default + WowHeaderR + WowHeaderW> {
     None,
@@ -39,6 +41,7 @@ impl<T: Default + WowHeaderR + WowHeaderW> Default for VGTE2<T> {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub enum VGTE3<T: Default + WowHeaderR + WowHeaderW> {
     None,
@@ -54,6 +57,7 @@ impl<T: Default + WowHeaderR + WowHeaderW> Default for VGTE3<T> {
 }
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = Version)]
 pub struct JointWeld {
     pub frame_a: Mat3x4,
@@ -5,6 +5,7 @@ use wow_alchemy_data_derive::{WowHeaderR, WowHeaderW};
 pub const PHYT: MagicStr = string_to_inverted_magic("PHYT");
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Phyt {
     pub phyt: u32,
 }
@@ -15,6 +15,7 @@ use wow_alchemy_data_derive::{WowEnumFrom, WowHeaderR, WowHeaderW};
     WowHeaderR,
     WowHeaderW,
 )]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=u16)]
 pub enum PhysVersion {
     #[wow_data(expr = 0)]
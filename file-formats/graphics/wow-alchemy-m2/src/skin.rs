@@ -1,9 +1,10 @@
 use bitflags::bitflags;
 use custom_debug::Debug;
 use wow_alchemy_data::error::Result as WDResult;
+use wow_alchemy_data::pod::WowArrayView;
 use wow_alchemy_data::prelude::*;
 use wow_alchemy_data::types::{C3Vector, MagicStr, VWowStructR, WowArray, WowStructW};
-use wow_alchemy_data_derive::{WowEnumFrom, WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowEnumFrom, WowHeaderR, WowHeaderW, WowPod};
 use wow_alchemy_utils::debug;
 
 use std::io::Cursor;
@@ -13,6 +14,7 @@ use crate::error::Result;
 pub const SKIN_MAGIC: MagicStr = *b"SKIN";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, WowEnumFrom)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=u32)]
 pub enum SkinVersion {
     /// Used up to WotLK
@@ -29,6 +31,7 @@ pub enum SkinVersion {
 impl DataVersion for SkinVersion {}
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = SkinVersion)]
 pub enum SkinMagic {
     None,
@@ -45,6 +48,7 @@ impl Default for SkinMagic {
 
 bitflags! {
     #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, WowHeaderR, WowHeaderW)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[wow_data(bitflags=u8)]
     /// Usually 0x10(BATCH_SUPPORT) for static textures, and 0 for animated textures
     pub struct M2BatchFlags: u8 {
@@ -59,6 +63,7 @@ bitflags! {
 }
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2Batch {
     pub flags: M2BatchFlags,
     pub priority_plane: i8,
@@ -76,6 +81,7 @@ pub struct M2Batch {
 }
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2ShadowBatch {
     pub flags: u8,
     pub flags2: u8,
@@ -87,6 +93,7 @@ pub struct M2ShadowBatch {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = SkinVersion)]
 pub enum SkinShadowBatchesHeader {
     None,
@@ -110,6 +117,7 @@ impl SkinShadowBatchesHeader {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SkinShadowBatches {
     None,
 
@@ -144,6 +152,7 @@ impl SkinShadowBatches {
 }
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(version = SkinVersion)]
 pub struct SkinHeader {
     /// Magic signature ("SKIN")
@@ -160,7 +169,9 @@ pub struct SkinHeader {
     pub shadow_batches: SkinShadowBatchesHeader,
 }
 
-#[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[derive(Debug, Clone, Copy, Default, WowHeaderR, WowHeaderW, WowPod)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct SkinSubmesh {
     pub id: u16,
     /// Level of detail
@@ -182,6 +193,7 @@ pub struct SkinSubmesh {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Skin {
     pub header: SkinHeader,
     #[debug(with = debug::trimmed_collection_fmt)]
@@ -215,6 +227,34 @@ impl VWowStructR<SkinVersion> for Skin {
     }
 }
 
+/// Borrowed counterpart of [`Skin`] for scanning many files, viewing the
+/// arrays of plain old data in the bytes of the file instead of reading them
+#[derive(Debug, Clone)]
+pub struct SkinView<'a> {
+    pub header: SkinHeader,
+    pub indices: WowArrayView<'a, u16>,
+    /// Triangles (each is 3 indices)
+    pub triangles: WowArrayView<'a, u16>,
+    pub bone_indices: WowArrayView<'a, u8>,
+    pub submeshes: WowArrayView<'a, SkinSubmesh>,
+}
+
+impl<'a> SkinView<'a> {
+    /// View the skin in `data`, the bytes of the whole file, failing if any
+    /// array is out of bounds
+    pub fn new(data: &'a [u8], version: SkinVersion) -> WDResult<Self> {
+        let header: SkinHeader = Cursor::new(data).wow_read_versioned(version)?;
+
+        Ok(Self {
+            indices: header.indices.view(data)?,
+            triangles: header.triangles.view(data)?,
+            bone_indices: header.bone_indices.view(data)?,
+            submeshes: header.submeshes.view(data)?,
+            header,
+        })
+    }
+}
+
 impl WowStructW for Skin {
     fn wow_write<W: Write + Seek>(&self, writer: &mut W) -> WDResult<()> {
         let mut header = self.header.clone();
@@ -282,4 +322,34 @@ mod tests {
         assert_eq!(parsed_submesh.sort_center, C3Vector::new(1.5, 2.5, 3.5));
         assert_eq!(parsed_submesh.bounding_radius, 5.0);
     }
+
+    #[test]
+    fn test_view_matches_read() {
+        let skin = Skin {
+            indices: (0..30).collect(),
+            triangles: vec![0, 1, 2, 2, 1, 3],
+            bone_indices: vec![0, 1, 0, 0],
+            submeshes: vec![SkinSubmesh {
+                vertex_count: 30,
+                triangle_count: 6,
+                center: C3Vector::new(1.0, 2.0, 3.0),
+                bounding_radius: 5.0,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut data = Vec::new();
+        skin.wow_write(&mut Cursor::new(&mut data)).unwrap();
+
+        let view = SkinView::new(&data, SkinVersion::V3).unwrap();
+        assert_eq!(view.indices.to_vec(), skin.indices);
+        assert_eq!(view.triangles.to_vec(), skin.triangles);
+        assert_eq!(view.bone_indices.to_vec(), skin.bone_indices);
+        assert_eq!(view.submeshes.len(), 1);
+        let submesh = view.submeshes.get(0).unwrap();
+        assert_eq!(submesh.triangle_count, 6);
+        assert_eq!(submesh.center, C3Vector::new(1.0, 2.0, 3.0));
+
+        assert!(SkinView::new(&data[..data.len() - 1], SkinVersion::V3).is_err());
+    }
 }
@@ -1,3 +1,4 @@
+use wow_alchemy_data::game_version::GameVersion;
 use wow_alchemy_data::prelude::*;
 use wow_alchemy_data::types::DataVersion;
 use wow_alchemy_data_derive::{WowEnumFrom, WowHeaderR, WowHeaderW};
@@ -16,6 +17,7 @@ use wow_alchemy_data_derive::{WowEnumFrom, WowHeaderR, WowHeaderW};
     WowHeaderR,
     WowHeaderW,
 )]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[wow_data(from_type=u32)]
 pub enum MD20Version {
     #[wow_data(expr = 0x0, default)]
@@ -64,6 +66,23 @@ impl MD20Version {
     }
 }
 
+impl From<GameVersion> for MD20Version {
+    /// The last `MD20` version used by the release build of the expansion
+    fn from(version: GameVersion) -> Self {
+        match version {
+            GameVersion::Vanilla => Self::VanillaV1,
+            GameVersion::TBC => Self::TBCV4,
+            GameVersion::WotLK => Self::WotLK,
+            GameVersion::Cataclysm => Self::Cataclysm,
+            GameVersion::MoP | GameVersion::WoD | GameVersion::Legion => Self::MoPPlus,
+            GameVersion::BfA
+            | GameVersion::Shadowlands
+            | GameVersion::Dragonflight
+            | GameVersion::TheWarWithin => Self::BfAPlus,
+        }
+    }
+}
+
 impl std::fmt::Display for MD20Version {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self)
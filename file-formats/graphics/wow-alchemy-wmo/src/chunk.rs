@@ -1,6 +1,7 @@
 use crate::error::{Result, WmoError};
 use crate::types::ChunkId;
 use std::io::{Read, Seek, SeekFrom, Write};
+use wow_alchemy_data::pod::{WowArrayView, WowPod};
 
 /// Helper function to handle `read_exact` operations with proper EOF handling
 fn read_exact_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<()> {
@@ -108,4 +109,16 @@ impl Chunk {
 
         Ok(data)
     }
+
+    /// View the data of this chunk as an array, like MOVT vertices as
+    /// [`crate::types::Vec3`] or MOVI indices as `u16`, in `data`, the bytes
+    /// of the whole file, without reading it
+    pub fn view<'a, T: WowPod>(&self, data: &'a [u8]) -> Result<WowArrayView<'a, T>> {
+        let start = self.data_position as usize;
+        let bytes = start
+            .checked_add(self.header.size as usize)
+            .and_then(|end| data.get(start..end))
+            .ok_or(WmoError::UnexpectedEof)?;
+        Ok(WowArrayView::from_bytes(bytes)?)
+    }
 }
@@ -78,6 +78,18 @@ impl WmoEditor {
                         },
                     },
                     name_offset: 0,
+                    descriptive_name_offset: 0,
+                    portal_start: 0,
+                    portal_count: 0,
+                    trans_batch_count: 0,
+                    int_batch_count: 0,
+                    ext_batch_count: 0,
+                    padding: 0,
+                    fog_ids: [0; 4],
+                    liquid_type: 0,
+                    unique_id: 0,
+                    flags2: 0,
+                    unused: 0,
                     group_index: 0,
                 },
                 materials: Vec::new(),
@@ -397,6 +409,18 @@ impl WmoEditor {
                 },
             },
             name_offset: 0, // Will be calculated when saving
+            descriptive_name_offset: 0,
+            portal_start: 0,
+            portal_count: 0,
+            trans_batch_count: 0,
+            int_batch_count: 0,
+            ext_batch_count: 0,
+            padding: 0,
+            fog_ids: [0; 4],
+            liquid_type: 0,
+            unique_id: 0,
+            flags2: 0,
+            unused: 0,
             group_index: group_index as u32,
         };
 
@@ -432,6 +456,18 @@ impl WmoEditor {
                         },
                     },
                     name_offset: 0,
+                    descriptive_name_offset: 0,
+                    portal_start: 0,
+                    portal_count: 0,
+                    trans_batch_count: 0,
+                    int_batch_count: 0,
+                    ext_batch_count: 0,
+                    padding: 0,
+                    fog_ids: [0; 4],
+                    liquid_type: 0,
+                    unique_id: 0,
+                    flags2: 0,
+                    unused: 0,
                     group_index: 0,
                 },
                 materials: Vec::new(),
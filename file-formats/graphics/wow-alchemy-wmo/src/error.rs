@@ -7,6 +7,13 @@ pub enum WmoError {
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
 
+    #[error("Data error: {0}")]
+    WowData(#[from] wow_alchemy_data::error::WowDataError),
+
+    #[cfg(feature = "vfs")]
+    #[error("File provider error: {0}")]
+    Provider(#[from] wow_alchemy_vfs::VfsError),
+
     #[error("Invalid magic identifier: expected {expected:?}, found {found:?}")]
     InvalidMagic { expected: [u8; 4], found: [u8; 4] },
 
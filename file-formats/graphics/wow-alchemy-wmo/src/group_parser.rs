@@ -51,6 +51,9 @@ trait ReadLittleEndian: Read {
 
 impl<R: Read> ReadLittleEndian for R {}
 
+/// Size of the header at the start of the MOGP chunk
+const MOGP_HEADER_SIZE: u32 = 68;
+
 /// Parser for WMO group files
 pub struct WmoGroupParser;
 
@@ -175,7 +178,14 @@ impl WmoGroupParser {
                         },
                     );
 
-                    reader.seek(SeekFrom::Current(header.size as i64))?;
+                    // The other chunks of a group are nested in MOGP after
+                    // its header, so only the header is skipped
+                    let skip = if header.id == chunks::MOGP {
+                        header.size.min(MOGP_HEADER_SIZE)
+                    } else {
+                        header.size
+                    };
+                    reader.seek(SeekFrom::Current(skip as i64))?;
                 }
                 Err(WmoError::UnexpectedEof) => {
                     // End of file reached
@@ -221,8 +231,9 @@ impl WmoGroupParser {
 
         mogp_chunk.seek_to_data(reader)?;
 
-        // First 4 bytes are group name offset
+        // First 8 bytes are the group name and descriptive name offsets
         let name_offset = reader.read_u32_le()?;
+        let descriptive_name_offset = reader.read_u32_le()?;
 
         // Next 4 bytes are group flags
         let flags = WmoGroupFlags::from_bits_truncate(reader.read_u32_le()?);
@@ -236,8 +247,20 @@ impl WmoGroupParser {
         let max_y = reader.read_f32_le()?;
         let max_z = reader.read_f32_le()?;
 
-        // Skip the rest of the header (varies by version)
-        reader.seek(SeekFrom::Current(8))?; // Skip 8 bytes
+        let portal_start = reader.read_u16_le()?;
+        let portal_count = reader.read_u16_le()?;
+        let trans_batch_count = reader.read_u16_le()?;
+        let int_batch_count = reader.read_u16_le()?;
+        let ext_batch_count = reader.read_u16_le()?;
+        let padding = reader.read_u16_le()?;
+
+        let mut fog_ids = [0; 4];
+        reader.read_exact(&mut fog_ids)?;
+
+        let liquid_type = reader.read_u32_le()?;
+        let unique_id = reader.read_u32_le()?;
+        let flags2 = reader.read_u32_le()?;
+        let unused = reader.read_u32_le()?;
 
         Ok(WmoGroupHeader {
             flags,
@@ -254,6 +277,18 @@ impl WmoGroupParser {
                 },
             },
             name_offset,
+            descriptive_name_offset,
+            portal_start,
+            portal_count,
+            trans_batch_count,
+            int_batch_count,
+            ext_batch_count,
+            padding,
+            fog_ids,
+            liquid_type,
+            unique_id,
+            flags2,
+            unused,
             group_index,
         })
     }
@@ -276,9 +311,8 @@ impl WmoGroupParser {
         let batch_count = batch_data.len() / 24;
 
         for i in 0..batch_count {
-            let offset = i * 24 + 2; // Material ID is 2 bytes in
-
-            let material_id = u16::from_le_bytes([batch_data[offset], batch_data[offset + 1]]);
+            // Material ID is the last byte of a batch
+            let material_id = batch_data[i * 24 + 23] as u16;
 
             if !materials.contains(&material_id) {
                 materials.push(material_id);
@@ -434,26 +468,32 @@ impl WmoGroupParser {
         for i in 0..batch_count {
             let offset = i * 24;
 
-            let flags = moba_data[offset];
-
-            let material_id = u16::from_le_bytes([moba_data[offset + 2], moba_data[offset + 3]]);
+            // Bounding box of the batch first, as 6 i16
+            let mut bounding_box = [0; 6];
+            for (j, value) in bounding_box.iter_mut().enumerate() {
+                let at = offset + j * 2;
+                *value = i16::from_le_bytes([moba_data[at], moba_data[at + 1]]);
+            }
 
             let start_index = u32::from_le_bytes([
-                moba_data[offset + 4],
-                moba_data[offset + 5],
-                moba_data[offset + 6],
-                moba_data[offset + 7],
+                moba_data[offset + 12],
+                moba_data[offset + 13],
+                moba_data[offset + 14],
+                moba_data[offset + 15],
             ]);
 
-            let count = u16::from_le_bytes([moba_data[offset + 8], moba_data[offset + 9]]);
+            let count = u16::from_le_bytes([moba_data[offset + 16], moba_data[offset + 17]]);
 
-            let start_vertex = u16::from_le_bytes([moba_data[offset + 10], moba_data[offset + 11]]);
+            let start_vertex = u16::from_le_bytes([moba_data[offset + 18], moba_data[offset + 19]]);
 
-            let end_vertex = u16::from_le_bytes([moba_data[offset + 12], moba_data[offset + 13]]);
+            let end_vertex = u16::from_le_bytes([moba_data[offset + 20], moba_data[offset + 21]]);
 
-            // Skip position (8 bytes) at offset + 16
+            let flags = moba_data[offset + 22];
+
+            let material_id = moba_data[offset + 23] as u16;
 
             batches.push(WmoBatch {
+                bounding_box,
                 flags,
                 material_id,
                 start_index,
@@ -746,3 +786,72 @@ impl WmoGroupParser {
         Ok(doodad_refs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::WmoWriter;
+    use std::io::Cursor;
+
+    /// A group file in the layout of the 3.3.5 client: a 68 byte MOGP
+    /// header followed by the nested chunks, and batches starting with their
+    /// bounding box
+    const GROUP: &[u8] = include_bytes!("../test-data/group_000.wmo");
+
+    #[test]
+    fn test_parse_client_group() {
+        let group = WmoGroupParser::new()
+            .parse_group(&mut Cursor::new(GROUP), 0)
+            .unwrap();
+
+        let header = &group.header;
+        assert_eq!(header.name_offset, 0);
+        assert_eq!(header.descriptive_name_offset, 9);
+        assert_eq!(
+            header.flags,
+            WmoGroupFlags::HAS_NORMALS | WmoGroupFlags::HAS_LIGHT
+        );
+        assert_eq!(header.bounding_box.max.z, 2.5);
+        assert_eq!((header.portal_start, header.portal_count), (2, 1));
+        assert_eq!(header.ext_batch_count, 1);
+        assert_eq!(header.fog_ids, [1, 0, 0, 0]);
+        assert_eq!(header.unique_id, 4781);
+
+        assert_eq!(group.vertices.len(), 3);
+        assert_eq!(group.normals.len(), 3);
+        assert_eq!(group.tex_coords.len(), 3);
+        assert_eq!(group.indices, [0, 1, 2]);
+        assert_eq!(group.materials, [1]);
+
+        let batch = &group.batches[0];
+        assert_eq!(batch.bounding_box, [0, 0, 0, 10, 10, 3]);
+        assert_eq!((batch.start_index, batch.count), (0, 3));
+        assert_eq!((batch.start_vertex, batch.end_vertex), (0, 2));
+        assert_eq!((batch.flags, batch.material_id), (0, 1));
+    }
+
+    #[test]
+    fn test_write_client_group_header() {
+        let group = WmoGroupParser::new()
+            .parse_group(&mut Cursor::new(GROUP), 0)
+            .unwrap();
+        let mut written = Cursor::new(Vec::new());
+        WmoWriter::new()
+            .write_group(&mut written, &group, WmoVersion::Wotlk)
+            .unwrap();
+        let written = written.into_inner();
+
+        // MVER, then the MOGP header. The size of MOGP differs as MOPY is
+        // not written.
+        assert_eq!(written[..16], GROUP[..16]);
+        let header = 20..20 + MOGP_HEADER_SIZE as usize;
+        assert_eq!(written[header.clone()], GROUP[header]);
+
+        let reparsed = WmoGroupParser::new()
+            .parse_group(&mut Cursor::new(&written), 0)
+            .unwrap();
+        assert_eq!(reparsed.vertices, group.vertices);
+        assert_eq!(reparsed.indices, group.indices);
+        assert_eq!(reparsed.batches[0].bounding_box, [0, 0, 0, 10, 10, 3]);
+    }
+}
@@ -55,6 +55,26 @@ pub fn parse_wmo_group<R: std::io::Read + std::io::Seek>(
     parser.parse_group(reader, group_index)
 }
 
+/// Parse a WMO root file and its group files from a file provider
+///
+/// Groups are read from `<name>_000.wmo` onwards, one for each group of the
+/// root.
+#[cfg(feature = "vfs")]
+pub fn load_wmo<P: wow_alchemy_vfs::WowFileProvider + ?Sized>(
+    provider: &P,
+    path: &str,
+) -> Result<(WmoRoot, Vec<WmoGroup>)> {
+    let root = parse_wmo(&mut provider.open(path)?)?;
+    let base = wow_alchemy_vfs::path::strip_extension(path);
+    let groups = (0..root.groups.len())
+        .map(|i| {
+            let group_path = format!("{base}_{i:03}.wmo");
+            parse_wmo_group(&mut provider.open(&group_path)?, i as u32)
+        })
+        .collect::<Result<_>>()?;
+    Ok((root, groups))
+}
+
 /// Validate a WMO file from a reader
 pub fn validate_wmo<R: std::io::Read + std::io::Seek>(reader: &mut R) -> Result<bool> {
     // A simple validation just checks if we can parse the file without errors
@@ -1,7 +1,10 @@
 use std::fmt;
 
+use wow_alchemy_data_derive::WowPod;
+
 /// A 4-byte chunk identifier (magic)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChunkId(pub [u8; 4]);
 
 impl ChunkId {
@@ -43,7 +46,9 @@ impl fmt::Display for ChunkId {
 }
 
 /// Represents a 3D vector
-#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, WowPod)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct Vec3 {
     pub x: f32,
     pub y: f32,
@@ -51,7 +56,9 @@ pub struct Vec3 {
 }
 
 /// Represents a bounding box defined by min and max points
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, WowPod)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct BoundingBox {
     pub min: Vec3,
     pub max: Vec3,
@@ -59,6 +66,7 @@ pub struct BoundingBox {
 
 /// Represents RGBA color
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -1,4 +1,7 @@
+use wow_alchemy_data::game_version::GameVersion;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WmoVersion {
     /// Classic/Vanilla (1.12.1) - Version 17, core chunks only
     Classic,
@@ -111,8 +114,27 @@ impl WmoVersion {
     }
 }
 
+impl From<GameVersion> for WmoVersion {
+    fn from(version: GameVersion) -> Self {
+        match version {
+            GameVersion::Vanilla => Self::Classic,
+            GameVersion::TBC => Self::Tbc,
+            GameVersion::WotLK => Self::Wotlk,
+            GameVersion::Cataclysm => Self::Cataclysm,
+            GameVersion::MoP => Self::Mop,
+            GameVersion::WoD => Self::Wod,
+            GameVersion::Legion => Self::Legion,
+            GameVersion::BfA => Self::Bfa,
+            GameVersion::Shadowlands => Self::Shadowlands,
+            GameVersion::Dragonflight => Self::Dragonflight,
+            GameVersion::TheWarWithin => Self::WarWithin,
+        }
+    }
+}
+
 /// Features introduced in different WMO versions
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WmoFeature {
     /// Base WMO features (available in all versions)
     Base,
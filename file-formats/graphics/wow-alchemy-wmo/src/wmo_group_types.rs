@@ -3,6 +3,7 @@ use bitflags::bitflags;
 
 /// Represents a WMO group file
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoGroup {
     /// Group header
     pub header: WmoGroupHeader,
@@ -40,6 +41,7 @@ pub struct WmoGroup {
 
 /// Header for a WMO group
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoGroupHeader {
     /// Group flags
     pub flags: WmoGroupFlags,
@@ -50,13 +52,50 @@ pub struct WmoGroupHeader {
     /// Name offset in MOGN chunk of root file
     pub name_offset: u32,
 
-    /// Index of this group
+    /// Descriptive name offset in MOGN chunk of root file
+    pub descriptive_name_offset: u32,
+
+    /// Index of the first portal reference of this group in MOPR chunk
+    pub portal_start: u16,
+
+    /// Number of portal references of this group
+    pub portal_count: u16,
+
+    /// Number of transparent batches
+    pub trans_batch_count: u16,
+
+    /// Number of interior batches
+    pub int_batch_count: u16,
+
+    /// Number of exterior batches
+    pub ext_batch_count: u16,
+
+    /// Padding after the batch counts
+    pub padding: u16,
+
+    /// Indices of the fogs of this group in MFOG chunk
+    pub fog_ids: [u8; 4],
+
+    /// Liquid type of the group
+    pub liquid_type: u32,
+
+    /// ID of the group in WMOAreaTable
+    pub unique_id: u32,
+
+    /// Additional group flags
+    pub flags2: u32,
+
+    /// Unused
+    pub unused: u32,
+
+    /// Index of this group, from the name of the group file
     pub group_index: u32,
 }
 
 bitflags! {
     /// WMO group flags
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct WmoGroupFlags: u32 {
         /// Has base vertices
         const HAS_BASE_VERTICES = 0x01;
@@ -99,6 +138,7 @@ bitflags! {
 
 /// Texture coordinates
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TexCoord {
     pub u: f32,
     pub v: f32,
@@ -106,7 +146,11 @@ pub struct TexCoord {
 
 /// Represents a rendering batch in a WMO group
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoBatch {
+    /// Bounding box of the batch, min then max, for culling
+    pub bounding_box: [i16; 6],
+
     /// Flags for the batch
     pub flags: u8,
 
@@ -128,6 +172,7 @@ pub struct WmoBatch {
 
 /// BSP tree node for collision and visibility
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoBspNode {
     /// Plane split information
     pub plane: WmoPlane,
@@ -144,6 +189,7 @@ pub struct WmoBspNode {
 
 /// Plane used in BSP node calculations
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoPlane {
     /// Normal vector
     pub normal: Vec3,
@@ -154,6 +200,7 @@ pub struct WmoPlane {
 
 /// Liquid data in a WMO group
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoLiquid {
     /// Liquid type
     pub liquid_type: u32,
@@ -174,6 +221,7 @@ pub struct WmoLiquid {
 
 /// Vertex in a liquid surface
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoLiquidVertex {
     /// Position
     pub position: Vec3,
@@ -184,6 +232,7 @@ pub struct WmoLiquidVertex {
 
 /// Material information for a group
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoMaterialInfo {
     /// Material ID in the root file
     pub material_id: u16,
@@ -5,6 +5,7 @@ use bitflags::bitflags;
 
 /// Represents a WMO root file
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoRoot {
     /// WMO version
     pub version: WmoVersion,
@@ -52,6 +53,7 @@ pub struct WmoRoot {
 
 /// WMO header information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoHeader {
     /// Number of materials
     pub n_materials: u32,
@@ -84,6 +86,7 @@ pub struct WmoHeader {
 bitflags! {
     /// Global WMO flags
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct WmoFlags: u32 {
         /// Contains vertex colors
         const HAS_VERTEX_COLORS = 0x01;
@@ -110,6 +113,7 @@ bitflags! {
 
 /// Represents a WMO material
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoMaterial {
     /// Material flags
     pub flags: WmoMaterialFlags,
@@ -145,6 +149,7 @@ pub struct WmoMaterial {
 bitflags! {
     /// WMO material flags
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct WmoMaterialFlags: u32 {
         /// Unlit
         const UNLIT = 0x01;
@@ -175,6 +180,7 @@ bitflags! {
 
 /// Represents information about a WMO group
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoGroupInfo {
     /// Group flags
     pub flags: WmoGroupFlags,
@@ -188,6 +194,7 @@ pub struct WmoGroupInfo {
 
 /// Represents a WMO portal
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoPortal {
     /// Portal vertices
     pub vertices: Vec<Vec3>,
@@ -198,6 +205,7 @@ pub struct WmoPortal {
 
 /// Represents a WMO portal reference
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoPortalReference {
     /// Portal index
     pub portal_index: u16,
@@ -211,6 +219,7 @@ pub struct WmoPortalReference {
 
 /// Represents a WMO light
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoLight {
     /// Light type
     pub light_type: WmoLightType,
@@ -239,6 +248,7 @@ pub struct WmoLight {
 
 /// Type of WMO light
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WmoLightType {
     /// Omnidirectional point light
     Omni = 0,
@@ -273,6 +283,7 @@ impl WmoLightType {
 
 /// Additional light properties depending on light type
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WmoLightProperties {
     /// Omni light properties (none)
     Omni,
@@ -301,6 +312,7 @@ pub enum WmoLightProperties {
 
 /// Represents a WMO doodad definition
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoDoodadDef {
     /// Doodad name offset in MODN chunk
     pub name_offset: u32,
@@ -323,6 +335,7 @@ pub struct WmoDoodadDef {
 
 /// Represents a WMO doodad set
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoDoodadSet {
     /// Set name
     pub name: String,
@@ -338,6 +351,7 @@ pub struct WmoDoodadSet {
 /// Added in Cataclysm for transport WMOs and world objects requiring collision
 /// Based on empirical analysis: typically 496 bytes in transport WMOs
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoConvexVolumePlane {
     /// Plane normal vector
     pub normal: Vec3,
@@ -352,6 +366,7 @@ pub struct WmoConvexVolumePlane {
 /// Container for MCVP chunk data
 /// Found in Cataclysm+ WMOs, particularly transport objects like ships
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WmoConvexVolumePlanes {
     /// List of convex volume planes
     /// Each plane defines a clipping boundary for the WMO collision system
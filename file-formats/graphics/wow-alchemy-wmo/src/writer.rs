@@ -1,7 +1,8 @@
-use std::io::{Seek, SeekFrom, Write};
+use std::io::{Seek, Write};
 
-use crate::chunk::ChunkHeader;
-use crate::error::Result;
+use wow_alchemy_data::chunk_writer::{ChunkWriter, MagicOrder};
+
+use crate::error::{Result, WmoError};
 use crate::parser::chunks;
 use crate::types::{Color, Vec3};
 use crate::version::{WmoFeature, WmoVersion};
@@ -69,45 +70,48 @@ impl WmoWriter {
         wmo: &WmoRoot,
         target_version: WmoVersion,
     ) -> Result<()> {
+        let mut writer = ChunkWriter::new(writer, MagicOrder::Reversed)?;
+
         // Write version chunk
-        self.write_version(writer, target_version)?;
+        self.write_version(&mut writer, target_version)?;
 
         // Write header chunk
-        self.write_header(writer, wmo, target_version)?;
+        self.write_header(&mut writer, wmo, target_version)?;
 
         // Write textures
-        self.write_textures(writer, &wmo.textures)?;
+        self.write_textures(&mut writer, &wmo.textures)?;
 
         // Write materials
-        self.write_materials(writer, &wmo.materials, target_version)?;
+        self.write_materials(&mut writer, &wmo.materials, target_version)?;
 
         // Write group names
-        self.write_group_names(writer, &wmo.groups)?;
+        self.write_group_names(&mut writer, &wmo.groups)?;
 
         // Write group info
-        self.write_group_info(writer, &wmo.groups, target_version)?;
+        self.write_group_info(&mut writer, &wmo.groups, target_version)?;
 
         // Write skybox if applicable
         if target_version.supports_feature(WmoFeature::SkyboxReferences) && wmo.skybox.is_some() {
-            self.write_skybox(writer, wmo.skybox.as_deref())?;
+            self.write_skybox(&mut writer, wmo.skybox.as_deref())?;
         }
 
         // Write portals
-        self.write_portals(writer, &wmo.portals)?;
+        self.write_portals(&mut writer, &wmo.portals)?;
 
         // Write portal references
-        self.write_portal_references(writer, &wmo.portal_references)?;
+        self.write_portal_references(&mut writer, &wmo.portal_references)?;
 
         // Write visible block lists
-        self.write_visible_block_lists(writer, &wmo.visible_block_lists)?;
+        self.write_visible_block_lists(&mut writer, &wmo.visible_block_lists)?;
 
         // Write lights
-        self.write_lights(writer, &wmo.lights, target_version)?;
+        self.write_lights(&mut writer, &wmo.lights, target_version)?;
 
         // Write doodad definitions and sets
-        self.write_doodad_definitions(writer, &wmo.doodad_defs, target_version)?;
-        self.write_doodad_sets(writer, &wmo.doodad_sets)?;
+        self.write_doodad_definitions(&mut writer, &wmo.doodad_defs, target_version)?;
+        self.write_doodad_sets(&mut writer, &wmo.doodad_sets)?;
 
+        writer.finish()?;
         Ok(())
     }
 
@@ -118,127 +122,120 @@ impl WmoWriter {
         group: &WmoGroup,
         target_version: WmoVersion,
     ) -> Result<()> {
+        let mut writer = ChunkWriter::new(writer, MagicOrder::Reversed)?;
+
         // Write version chunk
-        self.write_version(writer, target_version)?;
+        self.write_version(&mut writer, target_version)?;
 
-        // Start MOGP chunk (we'll need to update its size at the end)
-        let mogp_pos = writer.stream_position()?;
-        let mogp_header = ChunkHeader {
-            id: chunks::MOGP,
-            size: 0, // Placeholder, will update later
-        };
-        mogp_header.write(writer)?;
+        // The MOGP chunk holds the group header and all other chunks
+        writer.begin_chunk(chunks::MOGP.0)?;
 
         // Write group header fields
-        writer.write_u32_le(group.header.name_offset)?;
-        writer.write_u32_le(group.header.flags.bits())?;
+        let header = &group.header;
+        writer.write_u32_le(header.name_offset)?;
+        writer.write_u32_le(header.descriptive_name_offset)?;
+        writer.write_u32_le(header.flags.bits())?;
 
         // Write bounding box
-        writer.write_f32_le(group.header.bounding_box.min.x)?;
-        writer.write_f32_le(group.header.bounding_box.min.y)?;
-        writer.write_f32_le(group.header.bounding_box.min.z)?;
-
-        writer.write_f32_le(group.header.bounding_box.max.x)?;
-        writer.write_f32_le(group.header.bounding_box.max.y)?;
-        writer.write_f32_le(group.header.bounding_box.max.z)?;
-
-        // Write flags and index
-        writer.write_u16_le(0)?; // Flags2, only used in later versions
-        writer.write_u16_le(group.header.group_index as u16)?;
-
-        // Mark the start of subchunks
-        let _subchunks_start = writer.stream_position()?;
+        writer.write_f32_le(header.bounding_box.min.x)?;
+        writer.write_f32_le(header.bounding_box.min.y)?;
+        writer.write_f32_le(header.bounding_box.min.z)?;
+
+        writer.write_f32_le(header.bounding_box.max.x)?;
+        writer.write_f32_le(header.bounding_box.max.y)?;
+        writer.write_f32_le(header.bounding_box.max.z)?;
+
+        writer.write_u16_le(header.portal_start)?;
+        writer.write_u16_le(header.portal_count)?;
+        writer.write_u16_le(header.trans_batch_count)?;
+        writer.write_u16_le(header.int_batch_count)?;
+        writer.write_u16_le(header.ext_batch_count)?;
+        writer.write_u16_le(header.padding)?;
+        writer.write_all(&header.fog_ids)?;
+        writer.write_u32_le(header.liquid_type)?;
+        writer.write_u32_le(header.unique_id)?;
+        writer.write_u32_le(header.flags2)?;
+        writer.write_u32_le(header.unused)?;
+
+        // The group index is not stored, it comes from the file name
 
         // Write vertices
         if !group.vertices.is_empty() {
-            self.write_vertices(writer, &group.vertices)?;
+            self.write_vertices(&mut writer, &group.vertices)?;
         }
 
         // Write indices
         if !group.indices.is_empty() {
-            self.write_indices(writer, &group.indices)?;
+            self.write_indices(&mut writer, &group.indices)?;
         }
 
         // Write normals if available
         if !group.normals.is_empty() {
-            self.write_normals(writer, &group.normals)?;
+            self.write_normals(&mut writer, &group.normals)?;
         }
 
         // Write texture coordinates
         if !group.tex_coords.is_empty() {
-            self.write_texture_coords(writer, &group.tex_coords)?;
+            self.write_texture_coords(&mut writer, &group.tex_coords)?;
         }
 
         // Write vertex colors if available
         if let Some(colors) = &group.vertex_colors {
             if !colors.is_empty() {
-                self.write_vertex_colors(writer, colors)?;
+                self.write_vertex_colors(&mut writer, colors)?;
             }
         }
 
         // Write batches
         if !group.batches.is_empty() {
-            self.write_batches(writer, &group.batches)?;
+            self.write_batches(&mut writer, &group.batches)?;
         }
 
         // Write BSP nodes if available
         if let Some(nodes) = &group.bsp_nodes {
             if !nodes.is_empty() {
-                self.write_bsp_nodes(writer, nodes)?;
+                self.write_bsp_nodes(&mut writer, nodes)?;
             }
         }
 
         // Write liquid data if available
         if let Some(liquid) = &group.liquid {
-            self.write_liquid(writer, liquid, target_version)?;
+            self.write_liquid(&mut writer, liquid, target_version)?;
         }
 
         // Write doodad references if available
         if let Some(refs) = &group.doodad_refs {
             if !refs.is_empty() {
-                self.write_doodad_refs(writer, refs)?;
+                self.write_doodad_refs(&mut writer, refs)?;
             }
         }
 
-        // Update MOGP chunk size
-        let end_pos = writer.stream_position()?;
-        let mogp_size = end_pos - mogp_pos - 8; // Subtract header size
-
-        writer.seek(SeekFrom::Start(mogp_pos + 4))?; // Position at size field
-        writer.write_u32_le(mogp_size as u32)?;
-
-        // Return to end
-        writer.seek(SeekFrom::Start(end_pos))?;
-
+        writer.end_chunk()?;
+        writer.finish()?;
         Ok(())
     }
 
     /// Write version chunk
-    fn write_version<W: Write>(&self, writer: &mut W, version: WmoVersion) -> Result<()> {
-        let header = ChunkHeader {
-            id: chunks::MVER,
-            size: 4,
-        };
-
-        header.write(writer)?;
+    fn write_version<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        version: WmoVersion,
+    ) -> Result<()> {
+        writer.begin_chunk(chunks::MVER.0)?;
         writer.write_u32_le(version.to_raw())?;
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write header chunk
-    fn write_header<W: Write>(
+    fn write_header<W: Write + Seek>(
         &self,
-        writer: &mut W,
+        writer: &mut ChunkWriter<W>,
         wmo: &WmoRoot,
         target_version: WmoVersion,
     ) -> Result<()> {
-        let header = ChunkHeader {
-            id: chunks::MOHD,
-            size: 60, // Fixed size for header (without padding)
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MOHD.0)?;
 
         // Basic counts
         writer.write_u32_le(wmo.materials.len() as u32)?;
@@ -278,27 +275,21 @@ impl WmoWriter {
         writer.write_f32_le(wmo.bounding_box.max.y)?;
         writer.write_f32_le(wmo.bounding_box.max.z)?;
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write textures
-    fn write_textures<W: Write>(&self, writer: &mut W, textures: &[String]) -> Result<()> {
+    fn write_textures<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        textures: &[String],
+    ) -> Result<()> {
         if textures.is_empty() {
             return Ok(());
         }
 
-        // Calculate the size of the MOTX chunk
-        let mut total_size = 0;
-        for texture in textures {
-            total_size += texture.len() + 1; // +1 for null terminator
-        }
-
-        let header = ChunkHeader {
-            id: chunks::MOTX,
-            size: total_size as u32,
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MOTX.0)?;
 
         // Write null-terminated strings
         for texture in textures {
@@ -306,13 +297,14 @@ impl WmoWriter {
             writer.write_u8(0)?; // Null terminator
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write materials
-    fn write_materials<W: Write>(
+    fn write_materials<W: Write + Seek>(
         &self,
-        writer: &mut W,
+        writer: &mut ChunkWriter<W>,
         materials: &[WmoMaterial],
         target_version: WmoVersion,
     ) -> Result<()> {
@@ -320,19 +312,7 @@ impl WmoWriter {
             return Ok(());
         }
 
-        // Determine material size based on version
-        let material_size = if target_version >= WmoVersion::Mop {
-            64
-        } else {
-            40
-        };
-
-        let header = ChunkHeader {
-            id: chunks::MOMT,
-            size: (materials.len() * material_size) as u32,
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MOMT.0)?;
 
         for material in materials {
             writer.write_u32_le(material.flags.bits())?;
@@ -373,27 +353,21 @@ impl WmoWriter {
             }
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write group names
-    fn write_group_names<W: Write>(&self, writer: &mut W, groups: &[WmoGroupInfo]) -> Result<()> {
+    fn write_group_names<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        groups: &[WmoGroupInfo],
+    ) -> Result<()> {
         if groups.is_empty() {
             return Ok(());
         }
 
-        // Calculate the size of the MOGN chunk
-        let mut total_size = 0;
-        for group in groups {
-            total_size += group.name.len() + 1; // +1 for null terminator
-        }
-
-        let header = ChunkHeader {
-            id: chunks::MOGN,
-            size: total_size as u32,
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MOGN.0)?;
 
         // Write null-terminated strings
         for group in groups {
@@ -401,13 +375,14 @@ impl WmoWriter {
             writer.write_u8(0)?; // Null terminator
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write group info
-    fn write_group_info<W: Write>(
+    fn write_group_info<W: Write + Seek>(
         &self,
-        writer: &mut W,
+        writer: &mut ChunkWriter<W>,
         groups: &[WmoGroupInfo],
         _target_version: WmoVersion,
     ) -> Result<()> {
@@ -415,13 +390,10 @@ impl WmoWriter {
             return Ok(());
         }
 
-        let header = ChunkHeader {
-            id: chunks::MOGI,
-            size: (groups.len() * 32) as u32, // 32 bytes per group
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MOGI.0)?;
 
+        // Offset of the name in MOGN, where the names follow each other
+        let mut name_offset = 0;
         for group in groups {
             writer.write_u32_le(group.flags.bits())?;
 
@@ -433,35 +405,37 @@ impl WmoWriter {
             writer.write_f32_le(group.bounding_box.max.y)?;
             writer.write_f32_le(group.bounding_box.max.z)?;
 
-            // Write name offset in MOGN chunk
-            // This is a simplification - in a real implementation, you'd need to calculate actual offsets
-            writer.write_u32_le(0)?; // Placeholder
+            writer.write_u32_le(name_offset)?;
+            name_offset += group.name.len() as u32 + 1; // +1 for null terminator
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write skybox
-    fn write_skybox<W: Write>(&self, writer: &mut W, skybox: Option<&str>) -> Result<()> {
+    fn write_skybox<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        skybox: Option<&str>,
+    ) -> Result<()> {
         if let Some(skybox) = skybox {
-            let size = skybox.len() + 1; // +1 for null terminator
-
-            let header = ChunkHeader {
-                id: chunks::MOSB,
-                size: size as u32,
-            };
-
-            header.write(writer)?;
+            writer.begin_chunk(chunks::MOSB.0)?;
 
             writer.write_all(skybox.as_bytes())?;
             writer.write_u8(0)?; // Null terminator
+            writer.end_chunk()?;
         }
 
         Ok(())
     }
 
     /// Write portals
-    fn write_portals<W: Write>(&self, writer: &mut W, portals: &[WmoPortal]) -> Result<()> {
+    fn write_portals<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        portals: &[WmoPortal],
+    ) -> Result<()> {
         if portals.is_empty() {
             return Ok(());
         }
@@ -473,12 +447,7 @@ impl WmoWriter {
             all_vertices.extend_from_slice(&portal.vertices);
         }
 
-        let mopv_header = ChunkHeader {
-            id: chunks::MOPV,
-            size: (all_vertices.len() * 12) as u32, // 12 bytes per vertex (3 floats)
-        };
-
-        mopv_header.write(writer)?;
+        writer.begin_chunk(chunks::MOPV.0)?;
 
         for vertex in &all_vertices {
             writer.write_f32_le(vertex.x)?;
@@ -486,13 +455,10 @@ impl WmoWriter {
             writer.write_f32_le(vertex.z)?;
         }
 
-        // Now write portal info (MOPT)
-        let mopt_header = ChunkHeader {
-            id: chunks::MOPT,
-            size: (portals.len() * 20) as u32, // 20 bytes per portal
-        };
+        writer.end_chunk()?;
 
-        mopt_header.write(writer)?;
+        // Now write portal info (MOPT)
+        writer.begin_chunk(chunks::MOPT.0)?;
 
         let mut vertex_index = 0;
 
@@ -518,25 +484,21 @@ impl WmoWriter {
             vertex_index += portal.vertices.len();
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write portal references
-    fn write_portal_references<W: Write>(
+    fn write_portal_references<W: Write + Seek>(
         &self,
-        writer: &mut W,
+        writer: &mut ChunkWriter<W>,
         refs: &[WmoPortalReference],
     ) -> Result<()> {
         if refs.is_empty() {
             return Ok(());
         }
 
-        let header = ChunkHeader {
-            id: chunks::MOPR,
-            size: (refs.len() * 8) as u32, // 8 bytes per reference
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MOPR.0)?;
 
         for r in refs {
             writer.write_u16_le(r.portal_index)?;
@@ -545,13 +507,14 @@ impl WmoWriter {
             writer.write_u16_le(0)?; // Padding
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write visible block lists
-    fn write_visible_block_lists<W: Write>(
+    fn write_visible_block_lists<W: Write + Seek>(
         &self,
-        writer: &mut W,
+        writer: &mut ChunkWriter<W>,
         lists: &[Vec<u16>],
     ) -> Result<()> {
         if lists.is_empty() {
@@ -568,27 +531,16 @@ impl WmoWriter {
         }
 
         // Write offset table (MOVV)
-        let movv_header = ChunkHeader {
-            id: chunks::MOVV,
-            size: (offsets.len() * 4) as u32, // 4 bytes per offset (u32)
-        };
-
-        movv_header.write(writer)?;
+        writer.begin_chunk(chunks::MOVV.0)?;
 
         for offset in &offsets {
             writer.write_u32_le(*offset as u32)?;
         }
 
-        // Calculate the total size of visible blocks data
-        let total_block_size = lists.iter().map(|list| (list.len() + 1) * 2).sum::<usize>();
+        writer.end_chunk()?;
 
         // Write visible blocks (MOVB)
-        let movb_header = ChunkHeader {
-            id: chunks::MOVB,
-            size: total_block_size as u32,
-        };
-
-        movb_header.write(writer)?;
+        writer.begin_chunk(chunks::MOVB.0)?;
 
         for list in lists {
             for &index in list {
@@ -598,13 +550,14 @@ impl WmoWriter {
             writer.write_u16_le(0xFFFF)?; // End of list marker
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write lights
-    fn write_lights<W: Write>(
+    fn write_lights<W: Write + Seek>(
         &self,
-        writer: &mut W,
+        writer: &mut ChunkWriter<W>,
         lights: &[WmoLight],
         _target_version: WmoVersion,
     ) -> Result<()> {
@@ -612,12 +565,7 @@ impl WmoWriter {
             return Ok(());
         }
 
-        let header = ChunkHeader {
-            id: chunks::MOLT,
-            size: (lights.len() * 48) as u32, // 48 bytes per light
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MOLT.0)?;
 
         for light in lights {
             writer.write_u8(light.light_type as u8)?;
@@ -679,13 +627,14 @@ impl WmoWriter {
             }
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write doodad definitions
-    fn write_doodad_definitions<W: Write>(
+    fn write_doodad_definitions<W: Write + Seek>(
         &self,
-        writer: &mut W,
+        writer: &mut ChunkWriter<W>,
         doodads: &[WmoDoodadDef],
         _target_version: WmoVersion,
     ) -> Result<()> {
@@ -693,7 +642,7 @@ impl WmoWriter {
             return Ok(());
         }
 
-        // Calculate the size of the MODN chunk (doodad names)
+        // Collect the doodad names (MODN) and their offsets
         let mut doodad_names = Vec::new();
         let mut name_offsets = Vec::new();
         let mut current_offset = 0;
@@ -708,28 +657,17 @@ impl WmoWriter {
         }
 
         // Write doodad names (MODN)
-        let modn_header = ChunkHeader {
-            id: chunks::MODN,
-            size: current_offset as u32,
-        };
-
-        modn_header.write(writer)?;
+        writer.begin_chunk(chunks::MODN.0)?;
 
         for name in &doodad_names {
             writer.write_all(name.as_bytes())?;
             writer.write_u8(0)?; // Null terminator
         }
 
-        // All versions use 40 bytes for doodad definitions
-        let doodad_size = 40;
+        writer.end_chunk()?;
 
         // Write doodad definitions (MODD)
-        let modd_header = ChunkHeader {
-            id: chunks::MODD,
-            size: (doodads.len() * doodad_size) as u32,
-        };
-
-        modd_header.write(writer)?;
+        writer.begin_chunk(chunks::MODD.0)?;
 
         for (i, doodad) in doodads.iter().enumerate() {
             writer.write_u32_le(name_offsets[i] as u32)?;
@@ -754,21 +692,21 @@ impl WmoWriter {
             writer.write_u32_le(color_bytes)?;
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write doodad sets
-    fn write_doodad_sets<W: Write>(&self, writer: &mut W, sets: &[WmoDoodadSet]) -> Result<()> {
+    fn write_doodad_sets<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        sets: &[WmoDoodadSet],
+    ) -> Result<()> {
         if sets.is_empty() {
             return Ok(());
         }
 
-        let header = ChunkHeader {
-            id: chunks::MODS,
-            size: (sets.len() * 32) as u32, // 32 bytes per set
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MODS.0)?;
 
         for set in sets {
             // Write name (20 bytes, null padded)
@@ -787,23 +725,23 @@ impl WmoWriter {
             writer.write_u32_le(0)?; // Unused
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     // Group file writing methods
 
     /// Write vertices
-    fn write_vertices<W: Write>(&self, writer: &mut W, vertices: &[Vec3]) -> Result<()> {
+    fn write_vertices<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        vertices: &[Vec3],
+    ) -> Result<()> {
         if vertices.is_empty() {
             return Ok(());
         }
 
-        let header = ChunkHeader {
-            id: chunks::MOVT,
-            size: (vertices.len() * 12) as u32, // 12 bytes per vertex (3 floats)
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MOVT.0)?;
 
         for vertex in vertices {
             writer.write_f32_le(vertex.x)?;
@@ -811,41 +749,41 @@ impl WmoWriter {
             writer.write_f32_le(vertex.z)?;
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write indices
-    fn write_indices<W: Write>(&self, writer: &mut W, indices: &[u16]) -> Result<()> {
+    fn write_indices<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        indices: &[u16],
+    ) -> Result<()> {
         if indices.is_empty() {
             return Ok(());
         }
 
-        let header = ChunkHeader {
-            id: chunks::MOVI,
-            size: (indices.len() * 2) as u32, // 2 bytes per index (u16)
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MOVI.0)?;
 
         for &index in indices {
             writer.write_u16_le(index)?;
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write normals
-    fn write_normals<W: Write>(&self, writer: &mut W, normals: &[Vec3]) -> Result<()> {
+    fn write_normals<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        normals: &[Vec3],
+    ) -> Result<()> {
         if normals.is_empty() {
             return Ok(());
         }
 
-        let header = ChunkHeader {
-            id: chunks::MONR,
-            size: (normals.len() * 12) as u32, // 12 bytes per normal (3 floats)
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MONR.0)?;
 
         for normal in normals {
             writer.write_f32_le(normal.x)?;
@@ -853,46 +791,42 @@ impl WmoWriter {
             writer.write_f32_le(normal.z)?;
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write texture coordinates
-    fn write_texture_coords<W: Write>(
+    fn write_texture_coords<W: Write + Seek>(
         &self,
-        writer: &mut W,
+        writer: &mut ChunkWriter<W>,
         tex_coords: &[TexCoord],
     ) -> Result<()> {
         if tex_coords.is_empty() {
             return Ok(());
         }
 
-        let header = ChunkHeader {
-            id: chunks::MOTV,
-            size: (tex_coords.len() * 8) as u32, // 8 bytes per tex coord (2 floats)
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MOTV.0)?;
 
         for tex_coord in tex_coords {
             writer.write_f32_le(tex_coord.u)?;
             writer.write_f32_le(tex_coord.v)?;
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write vertex colors
-    fn write_vertex_colors<W: Write>(&self, writer: &mut W, colors: &[Color]) -> Result<()> {
+    fn write_vertex_colors<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        colors: &[Color],
+    ) -> Result<()> {
         if colors.is_empty() {
             return Ok(());
         }
 
-        let header = ChunkHeader {
-            id: chunks::MOCV,
-            size: (colors.len() * 4) as u32, // 4 bytes per color (BGRA)
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MOCV.0)?;
 
         for color in colors {
             writer.write_u8(color.b)?;
@@ -901,56 +835,58 @@ impl WmoWriter {
             writer.write_u8(color.a)?;
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write batches
-    fn write_batches<W: Write>(&self, writer: &mut W, batches: &[WmoBatch]) -> Result<()> {
+    fn write_batches<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        batches: &[WmoBatch],
+    ) -> Result<()> {
         if batches.is_empty() {
             return Ok(());
         }
 
-        let header = ChunkHeader {
-            id: chunks::MOBA,
-            size: (batches.len() * 24) as u32, // 24 bytes per batch
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MOBA.0)?;
 
         for batch in batches {
-            writer.write_u8(batch.flags)?;
-            writer.write_u8(0)?; // Padding
+            for value in batch.bounding_box {
+                writer.write_i16_le(value)?;
+            }
 
-            writer.write_u16_le(batch.material_id)?;
             writer.write_u32_le(batch.start_index)?;
             writer.write_u16_le(batch.count)?;
 
             writer.write_u16_le(batch.start_vertex)?;
             writer.write_u16_le(batch.end_vertex)?;
 
-            writer.write_u8(0)?; // Padding
-            writer.write_u8(0)?; // Padding
-
-            // Write position (for collision?)
-            writer.write_f32_le(0.0)?; // x
-            writer.write_f32_le(0.0)?; // y
+            writer.write_u8(batch.flags)?;
+            let material_id =
+                u8::try_from(batch.material_id).map_err(|_| WmoError::InvalidReference {
+                    field: "batch material_id".to_string(),
+                    value: batch.material_id as u32,
+                    max: u8::MAX as u32,
+                })?;
+            writer.write_u8(material_id)?;
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write BSP nodes
-    fn write_bsp_nodes<W: Write>(&self, writer: &mut W, nodes: &[WmoBspNode]) -> Result<()> {
+    fn write_bsp_nodes<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        nodes: &[WmoBspNode],
+    ) -> Result<()> {
         if nodes.is_empty() {
             return Ok(());
         }
 
-        let header = ChunkHeader {
-            id: chunks::MOBN,
-            size: (nodes.len() * 16) as u32, // 16 bytes per node
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MOBN.0)?;
 
         for node in nodes {
             // Write plane normal and flags packed into first float
@@ -990,39 +926,18 @@ impl WmoWriter {
             writer.write_u16_le(node.num_faces)?;
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write liquid data
-    fn write_liquid<W: Write>(
+    fn write_liquid<W: Write + Seek>(
         &self,
-        writer: &mut W,
+        writer: &mut ChunkWriter<W>,
         liquid: &WmoLiquid,
         target_version: WmoVersion,
     ) -> Result<()> {
-        // Calculate size based on version and content
-        let vertex_size = if target_version >= WmoVersion::Wod {
-            16
-        } else {
-            4
-        }; // 16 bytes for new format, 4 for old
-        let vertices_size = (liquid.width * liquid.height) as usize * vertex_size;
-
-        let has_tile_flags = liquid.tile_flags.is_some();
-        let tile_flags_size = if has_tile_flags {
-            ((liquid.width - 1) * (liquid.height - 1)) as usize
-        } else {
-            0
-        };
-
-        let total_size = 32 + vertices_size + tile_flags_size; // 32 bytes for header
-
-        let header = ChunkHeader {
-            id: chunks::MLIQ,
-            size: total_size as u32,
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MLIQ.0)?;
 
         // Write liquid header
         writer.write_u32_le(liquid.liquid_type)?;
@@ -1081,26 +996,27 @@ impl WmoWriter {
             }
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 
     /// Write doodad references
-    fn write_doodad_refs<W: Write>(&self, writer: &mut W, doodad_refs: &[u16]) -> Result<()> {
+    fn write_doodad_refs<W: Write + Seek>(
+        &self,
+        writer: &mut ChunkWriter<W>,
+        doodad_refs: &[u16],
+    ) -> Result<()> {
         if doodad_refs.is_empty() {
             return Ok(());
         }
 
-        let header = ChunkHeader {
-            id: chunks::MODR,
-            size: (doodad_refs.len() * 2) as u32, // 2 bytes per reference (u16)
-        };
-
-        header.write(writer)?;
+        writer.begin_chunk(chunks::MODR.0)?;
 
         for &doodad_ref in doodad_refs {
             writer.write_u16_le(doodad_ref)?;
         }
 
+        writer.end_chunk()?;
         Ok(())
     }
 }
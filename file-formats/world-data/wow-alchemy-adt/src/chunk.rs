@@ -11,6 +11,7 @@ use crate::version::AdtVersion;
 
 /// Common chunk header structure for all chunk types
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChunkHeader {
     /// Magic signature - 4 bytes identifying the chunk type
     pub magic: [u8; 4],
@@ -53,6 +54,7 @@ impl ChunkHeader {
 
 /// MVER chunk - file version information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MverChunk {
     /// Version number (usually 18)
     pub version: u32,
@@ -96,6 +98,7 @@ impl MverChunk {
 
 /// MHDR chunk - header containing offsets to other chunks
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MhdrChunk {
     /// Flags
     pub flags: u32,
@@ -197,6 +200,7 @@ impl MhdrChunk {
 
 /// MCIN chunk - map chunk index information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct McinChunk {
     /// Entries for each map chunk
     pub entries: Vec<McnkEntry>,
@@ -204,6 +208,7 @@ pub struct McinChunk {
 
 /// Entry in MCIN chunk for a map chunk
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct McnkEntry {
     /// Offset to MCNK chunk
     pub offset: u32,
@@ -256,6 +261,7 @@ impl McinChunk {
 
 /// MTEX chunk - texture filenames
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MtexChunk {
     /// List of texture filenames
     pub filenames: Vec<String>,
@@ -296,6 +302,7 @@ impl MtexChunk {
 
 /// MMDX chunk - model filenames
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MmdxChunk {
     /// List of model filenames
     pub filenames: Vec<String>,
@@ -336,6 +343,7 @@ impl MmdxChunk {
 
 /// MMID chunk - model indices
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MmidChunk {
     /// List of offsets into the MMDX chunk
     pub offsets: Vec<u32>,
@@ -365,6 +373,7 @@ impl MmidChunk {
 
 /// MWMO chunk - WMO filenames
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MwmoChunk {
     /// List of WMO filenames
     pub filenames: Vec<String>,
@@ -405,6 +414,7 @@ impl MwmoChunk {
 
 /// MWID chunk - WMO indices
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MwidChunk {
     /// List of offsets into the MWMO chunk
     pub offsets: Vec<u32>,
@@ -434,6 +444,7 @@ impl MwidChunk {
 
 /// MDDF chunk - doodad placement information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MddfChunk {
     /// List of doodad placements
     pub doodads: Vec<DoodadPlacement>,
@@ -441,6 +452,7 @@ pub struct MddfChunk {
 
 /// Doodad placement information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DoodadPlacement {
     /// Index into the MMID list
     pub name_id: u32,
@@ -491,12 +503,10 @@ impl MddfChunk {
                 *item = context.reader.read_f32_le()?;
             }
 
-            let scale = context.reader.read_f32_le()?;
+            // Scale is stored as fixed point, where 1024 is 1.0
+            let scale = context.reader.read_u16_le()? as f32 / 1024.0;
             let flags = context.reader.read_u16_le()?;
 
-            // Skip padding (2 bytes)
-            context.reader.seek(SeekFrom::Current(2))?;
-
             doodads.push(DoodadPlacement {
                 name_id,
                 unique_id,
@@ -513,6 +523,7 @@ impl MddfChunk {
 
 /// MODF chunk - model placement information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModfChunk {
     /// List of model placements
     pub models: Vec<ModelPlacement>,
@@ -520,6 +531,7 @@ pub struct ModfChunk {
 
 /// Model placement information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModelPlacement {
     /// Index into the MWID list
     pub name_id: u32,
@@ -613,6 +625,7 @@ impl ModfChunk {
 
 /// MCNK chunk - map chunk data
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct McnkChunk {
     /// Flags
     pub flags: u32,
@@ -695,6 +708,7 @@ pub struct McnkChunk {
 
 /// MCNK texture layer information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct McnkTextureLayer {
     /// Texture ID (index into MTEX)
     pub texture_id: u32,
@@ -1179,6 +1193,7 @@ impl McnkChunk {
 /// Each plane has 9 int16 coordinates (18 bytes), totaling 36 bytes.
 /// Validated against TrinityCore 3.3.5a and Cataclysm 4.3.4 implementations.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MfboChunk {
     /// Maximum flight bounds plane (9 int16 values)
     pub max: [i16; 9],
@@ -1222,6 +1237,7 @@ impl MfboChunk {
 
 /// MH2O chunk - water data (WotLK+)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mh2oChunk {
     /// Water data for each chunk (256 entries)
     pub chunks: Vec<Mh2oData>,
@@ -1229,6 +1245,7 @@ pub struct Mh2oChunk {
 
 /// Water data for a single chunk
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mh2oData {
     /// Water flags
     pub flags: u32,
@@ -1244,6 +1261,7 @@ pub struct Mh2oData {
 
 /// Water vertex data
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mh2oVertex {
     /// Depth (height) at this point
     pub depth: f32,
@@ -1347,6 +1365,7 @@ impl Mh2oChunk {
 
 /// MTFX chunk - texture effects (Cataclysm+)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MtfxChunk {
     /// Texture effects
     pub effects: Vec<TextureEffect>,
@@ -1354,6 +1373,7 @@ pub struct MtfxChunk {
 
 /// Texture effect data
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextureEffect {
     /// Effect ID
     pub effect_id: u32,
@@ -1362,6 +1382,7 @@ pub struct TextureEffect {
 /// MAMP chunk - texture amplifier (Cataclysm+)
 /// This chunk is exactly 4 bytes and contains a single u32 value
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MampChunk {
     /// Texture size amplifier value (typically 0 or powers of 2)
     /// Controls texture tiling/scaling for the terrain
@@ -1371,6 +1392,7 @@ pub struct MampChunk {
 /// MTXP chunk - texture parameters (MoP+)
 /// Variable size chunk containing texture transformation parameters
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MtxpChunk {
     /// Texture parameter entries
     pub entries: Vec<TextureParams>,
@@ -1378,6 +1400,7 @@ pub struct MtxpChunk {
 
 /// Texture transformation parameters
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextureParams {
     /// Unknown texture parameter values (4 float values)
     /// These control texture transformation/scaling properties
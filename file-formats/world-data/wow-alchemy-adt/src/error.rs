@@ -13,6 +13,11 @@ pub enum AdtError {
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
+    /// The file provider failed to read a file
+    #[cfg(feature = "vfs")]
+    #[error("File provider error: {0}")]
+    Provider(#[from] wow_alchemy_vfs::VfsError),
+
     /// Invalid magic signature in chunk header
     #[error("Invalid magic: expected {expected}, found {found}")]
     InvalidMagic { expected: String, found: String },
@@ -60,4 +65,8 @@ pub enum AdtError {
     /// Invalid file size
     #[error("Invalid file size: {0}")]
     InvalidFileSize(String),
+
+    /// Error of the chunk writer
+    #[error("{0}")]
+    WowData(#[from] wow_alchemy_data::error::WowDataError),
 }
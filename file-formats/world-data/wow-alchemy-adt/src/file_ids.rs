@@ -0,0 +1,252 @@
+//! Conversion of model placements between file names and FileDataIDs
+
+use crate::Adt;
+
+#[cfg(feature = "vfs")]
+use {
+    crate::chunk::{MmdxChunk, MmidChunk, MwidChunk, MwmoChunk},
+    crate::error::{AdtError, Result},
+    wow_alchemy_vfs::{FileIdResolver, normalize_path},
+};
+
+/// MDDF flag set from Legion on when `name_id` is the FileDataID of the
+/// model instead of an index into MMID
+pub const MDDF_FLAG_FILE_DATA_ID: u16 = 0x40;
+
+/// MODF flag set from Legion on when `name_id` is the FileDataID of the
+/// WMO instead of an index into MWID
+pub const MODF_FLAG_FILE_DATA_ID: u16 = 0x8;
+
+impl Adt {
+    /// Whether a doodad or WMO placement references its model by FileDataID,
+    /// which maps before Legion can't do
+    pub fn uses_file_data_ids(&self) -> bool {
+        let doodads = self.mddf.iter().flat_map(|mddf| &mddf.doodads);
+        let wmos = self.modf.iter().flat_map(|modf| &modf.models);
+        doodads
+            .map(|doodad| doodad.flags & MDDF_FLAG_FILE_DATA_ID)
+            .chain(wmos.map(|wmo| wmo.flags & MODF_FLAG_FILE_DATA_ID))
+            .any(|flag| flag != 0)
+    }
+}
+
+#[cfg(feature = "vfs")]
+impl Adt {
+    /// Replace FileDataIDs in doodad and WMO placements with indices of
+    /// their file names in MMDX/MMID and MWMO/MWID, which is how maps
+    /// before Legion reference models
+    ///
+    /// Fails without changes when the resolver doesn't know an id.
+    pub fn file_ids_to_paths(&mut self, resolver: &FileIdResolver) -> Result<()> {
+        let doodad_paths = self
+            .mddf
+            .iter()
+            .flat_map(|mddf| &mddf.doodads)
+            .map(|doodad| {
+                resolve_id(
+                    resolver,
+                    doodad.name_id,
+                    doodad.flags,
+                    MDDF_FLAG_FILE_DATA_ID,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let wmo_paths = self
+            .modf
+            .iter()
+            .flat_map(|modf| &modf.models)
+            .map(|wmo| resolve_id(resolver, wmo.name_id, wmo.flags, MODF_FLAG_FILE_DATA_ID))
+            .collect::<Result<Vec<_>>>()?;
+
+        if doodad_paths.iter().any(Option::is_some) {
+            let filenames = &mut self
+                .mmdx
+                .get_or_insert_with(|| MmdxChunk {
+                    filenames: Vec::new(),
+                })
+                .filenames;
+            let offsets = &mut self
+                .mmid
+                .get_or_insert_with(|| MmidChunk {
+                    offsets: Vec::new(),
+                })
+                .offsets;
+            let doodads = self.mddf.iter_mut().flat_map(|mddf| &mut mddf.doodads);
+            for (doodad, path) in doodads.zip(doodad_paths) {
+                if let Some(path) = path {
+                    doodad.name_id = intern(filenames, offsets, path);
+                    doodad.flags &= !MDDF_FLAG_FILE_DATA_ID;
+                }
+            }
+        }
+
+        if wmo_paths.iter().any(Option::is_some) {
+            let filenames = &mut self
+                .mwmo
+                .get_or_insert_with(|| MwmoChunk {
+                    filenames: Vec::new(),
+                })
+                .filenames;
+            let offsets = &mut self
+                .mwid
+                .get_or_insert_with(|| MwidChunk {
+                    offsets: Vec::new(),
+                })
+                .offsets;
+            let wmos = self.modf.iter_mut().flat_map(|modf| &mut modf.models);
+            for (wmo, path) in wmos.zip(wmo_paths) {
+                if let Some(path) = path {
+                    wmo.name_id = intern(filenames, offsets, path);
+                    wmo.flags &= !MODF_FLAG_FILE_DATA_ID;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replace the file names referenced by doodad and WMO placements with
+    /// FileDataIDs, which is how maps from Legion on reference models, and
+    /// drop the then unused MMDX/MMID and MWMO/MWID chunks
+    ///
+    /// Fails without changes when the resolver doesn't know a name.
+    pub fn paths_to_file_ids(&mut self, resolver: &FileIdResolver) -> Result<()> {
+        let doodad_ids = {
+            let filenames = self.mmdx.as_ref().map_or(&[][..], |mmdx| &mmdx.filenames);
+            let offsets = self.mmid.as_ref().map_or(&[][..], |mmid| &mmid.offsets);
+            self.mddf
+                .iter()
+                .flat_map(|mddf| &mddf.doodads)
+                .map(|doodad| {
+                    resolve_name(
+                        resolver,
+                        filenames,
+                        offsets,
+                        doodad.name_id,
+                        doodad.flags,
+                        MDDF_FLAG_FILE_DATA_ID,
+                        "MDDF",
+                    )
+                })
+                .collect::<Result<Vec<_>>>()?
+        };
+        let wmo_ids = {
+            let filenames = self.mwmo.as_ref().map_or(&[][..], |mwmo| &mwmo.filenames);
+            let offsets = self.mwid.as_ref().map_or(&[][..], |mwid| &mwid.offsets);
+            self.modf
+                .iter()
+                .flat_map(|modf| &modf.models)
+                .map(|wmo| {
+                    resolve_name(
+                        resolver,
+                        filenames,
+                        offsets,
+                        wmo.name_id,
+                        wmo.flags,
+                        MODF_FLAG_FILE_DATA_ID,
+                        "MODF",
+                    )
+                })
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        let doodads = self.mddf.iter_mut().flat_map(|mddf| &mut mddf.doodads);
+        for (doodad, id) in doodads.zip(doodad_ids) {
+            doodad.name_id = id;
+            doodad.flags |= MDDF_FLAG_FILE_DATA_ID;
+        }
+        let wmos = self.modf.iter_mut().flat_map(|modf| &mut modf.models);
+        for (wmo, id) in wmos.zip(wmo_ids) {
+            wmo.name_id = id;
+            wmo.flags |= MODF_FLAG_FILE_DATA_ID;
+        }
+        self.mmdx = None;
+        self.mmid = None;
+        self.mwmo = None;
+        self.mwid = None;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "vfs")]
+fn resolve_id(
+    resolver: &FileIdResolver,
+    name_id: u32,
+    flags: u16,
+    file_data_id_flag: u16,
+) -> Result<Option<&str>> {
+    if flags & file_data_id_flag == 0 {
+        return Ok(None);
+    }
+    Ok(Some(resolver.require_path(name_id)?))
+}
+
+#[cfg(feature = "vfs")]
+fn resolve_name(
+    resolver: &FileIdResolver,
+    filenames: &[String],
+    offsets: &[u32],
+    name_id: u32,
+    flags: u16,
+    file_data_id_flag: u16,
+    chunk: &str,
+) -> Result<u32> {
+    if flags & file_data_id_flag != 0 {
+        return Ok(name_id);
+    }
+    let name = offsets
+        .get(name_id as usize)
+        .and_then(|&offset| filename_at(filenames, offset))
+        .ok_or_else(|| {
+            AdtError::ValidationError(format!(
+                "{chunk} entry references missing file name {name_id}"
+            ))
+        })?;
+    Ok(resolver.require_file_id(name)?)
+}
+
+/// The file name starting at a byte offset of the name chunk, where names
+/// are stored null-terminated one after the other
+#[cfg(feature = "vfs")]
+fn filename_at(filenames: &[String], offset: u32) -> Option<&str> {
+    let mut start = 0;
+    for filename in filenames {
+        if start == offset {
+            return Some(filename);
+        }
+        start += filename.len() as u32 + 1;
+    }
+    None
+}
+
+/// Index of the offset entry for a file name, appending the name with
+/// backslashes like in maps before Legion and the entry as needed
+#[cfg(feature = "vfs")]
+fn intern(filenames: &mut Vec<String>, offsets: &mut Vec<u32>, path: &str) -> u32 {
+    let normalized = normalize_path(path);
+    let mut start = 0;
+    let mut offset = None;
+    for filename in filenames.iter() {
+        if normalize_path(filename) == normalized {
+            offset = Some(start);
+            break;
+        }
+        start += filename.len() as u32 + 1;
+    }
+    let offset = match offset {
+        Some(offset) => offset,
+        None => {
+            filenames.push(path.replace('/', "\\"));
+            start
+        }
+    };
+
+    match offsets.iter().position(|&existing| existing == offset) {
+        Some(index) => index as u32,
+        None => {
+            offsets.push(offset);
+            offsets.len() as u32 - 1
+        }
+    }
+}
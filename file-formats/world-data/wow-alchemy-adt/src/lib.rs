@@ -6,6 +6,7 @@ mod adt_builder;
 mod chunk;
 mod converter;
 mod error;
+mod file_ids;
 mod io_helpers;
 mod liquid_converter;
 mod mcnk_converter;
@@ -36,6 +37,7 @@ pub use adt_builder::{AdtBuilder, create_flat_terrain};
 pub use chunk::*;
 pub use converter::convert_adt;
 pub use error::{AdtError, Result};
+pub use file_ids::{MDDF_FLAG_FILE_DATA_ID, MODF_FLAG_FILE_DATA_ID};
 pub use mcnk_converter::{convert_mcnk, convert_mcnk_chunks};
 pub use mcnk_subchunks::*;
 pub use merge::{MergeOptions, extract_portion, merge_adts, merge_chunk};
@@ -57,6 +59,7 @@ pub use parallel::{ParallelOptions, batch_convert, batch_validate, process_paral
 
 /// Main ADT structure that holds all the parsed data for a terrain file
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Adt {
     /// Version of the ADT file
     pub version: AdtVersion,
@@ -186,6 +189,48 @@ impl Adt {
         }
     }
 
+    /// Parse a root ADT file and the split files next to it from a file
+    /// provider
+    ///
+    /// From Cataclysm, a tile is split in the root file at `path` and
+    /// `_tex0`, `_tex1`, `_obj0`, `_obj1` and `_lod` files, which are merged
+    /// into the result when the provider has them.
+    #[cfg(feature = "vfs")]
+    pub fn from_provider<P: wow_alchemy_vfs::WowFileProvider + ?Sized>(
+        provider: &P,
+        path: &str,
+    ) -> Result<Self> {
+        let root = Self::from_reader(provider.open(path)?)?;
+        let base = wow_alchemy_vfs::path::strip_extension(path);
+        let open = |suffix: &str| {
+            let split_path = format!("{base}_{suffix}.adt");
+            provider
+                .exists(&split_path)
+                .then(|| provider.open(&split_path))
+                .transpose()
+        };
+
+        let tex0 = open("tex0")?
+            .map(|mut file| split_adt::SplitAdtParser::parse_tex0(&mut file))
+            .transpose()?;
+        let tex1 = open("tex1")?
+            .map(|mut file| split_adt::SplitAdtParser::parse_tex1(&mut file))
+            .transpose()?;
+        let obj0 = open("obj0")?
+            .map(|mut file| split_adt::SplitAdtParser::parse_obj0(&mut file))
+            .transpose()?;
+        let obj1 = open("obj1")?
+            .map(|mut file| split_adt::SplitAdtParser::parse_obj1(&mut file))
+            .transpose()?;
+        let lod = open("lod")?
+            .map(|mut file| split_adt::SplitAdtParser::parse_lod(&mut file))
+            .transpose()?;
+
+        Ok(split_adt::merge_split_adt(
+            root, tex0, tex1, obj0, obj1, lod,
+        ))
+    }
+
     /// Parse an ADT file from any reader that implements Read + Seek
     pub fn from_reader<R: Read + Seek>(mut reader: R) -> Result<Self> {
         // Get file size for bounds checking
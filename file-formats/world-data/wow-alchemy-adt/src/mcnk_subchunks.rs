@@ -8,8 +8,10 @@ use std::io::{Read, Seek, SeekFrom};
 
 /// MCVT subchunk - height map vertices
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct McvtSubchunk {
     /// Height values for each vertex (145 vertices, 9x9 grid + extra control points)
+    #[cfg_attr(feature = "serde", serde(with = "wow_alchemy_data::serde_array"))]
     pub heights: [f32; 145],
 }
 
@@ -42,9 +44,11 @@ impl McvtSubchunk {
 
 /// MCNR subchunk - normal vectors
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct McnrSubchunk {
     /// Normal vectors for each vertex (145 vertices, each normal is 3 bytes)
     /// The normals are stored as signed bytes (-127 to 127) and need to be normalized
+    #[cfg_attr(feature = "serde", serde(with = "wow_alchemy_data::serde_array"))]
     pub normals: [[i8; 3]; 145],
 }
 
@@ -99,6 +103,7 @@ impl McnrSubchunk {
 
 /// MCLY subchunk - texture layer information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MclySubchunk {
     /// Texture layers
     pub layers: Vec<TextureLayer>,
@@ -106,6 +111,7 @@ pub struct MclySubchunk {
 
 /// Texture layer information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextureLayer {
     /// Texture ID (index into MTEX)
     pub texture_id: u32,
@@ -119,6 +125,7 @@ pub struct TextureLayer {
 
 /// MCLY texture layer flags
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MclyFlags {
     /// 0x001: Animation: Rotate 45° clockwise
     Animate1 = 0x001,
@@ -181,6 +188,7 @@ impl MclySubchunk {
 
 /// MCRF subchunk - doodad references
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct McrfSubchunk {
     /// Indices into MMID array
     pub indices: Vec<u32>,
@@ -210,6 +218,7 @@ impl McrfSubchunk {
 
 /// MCRD subchunk - map object references
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct McrdSubchunk {
     /// Indices into MWID array
     pub indices: Vec<u32>,
@@ -239,6 +248,7 @@ impl McrdSubchunk {
 
 /// MCSH subchunk - shadow map
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct McshSubchunk {
     /// Shadow map data (8x8 values, 1 byte per value)
     pub shadow_map: Vec<u8>,
@@ -265,6 +275,7 @@ impl McshSubchunk {
 
 /// MCAL subchunk - alpha maps
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct McalSubchunk {
     /// Raw alpha map data
     pub data: Vec<u8>,
@@ -323,6 +334,7 @@ impl McalSubchunk {
 
 /// MCLQ subchunk - legacy liquid data (pre-WotLK)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MclqSubchunk {
     /// Number of vertices in x direction
     pub x_vertices: u32,
@@ -336,6 +348,7 @@ pub struct MclqSubchunk {
 
 /// Liquid vertex data for pre-WotLK
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LiquidVertex {
     /// Depth of the liquid at this point
     pub depth: f32,
@@ -399,6 +412,7 @@ impl MclqSubchunk {
 
 /// MCCV subchunk - vertex colors
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MccvSubchunk {
     /// Vertex colors (BGRA format, one per vertex)
     pub colors: Vec<[u8; 4]>,
@@ -7,6 +7,7 @@ use std::io::{Read, Seek, SeekFrom};
 
 /// MH2O chunk - water data (WotLK+)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mh2oChunk {
     /// Water data for each map chunk (256 entries)
     pub chunks: Vec<Mh2oEntry>,
@@ -14,6 +15,7 @@ pub struct Mh2oChunk {
 
 /// Water data for a single chunk
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mh2oEntry {
     /// Header for this entry
     pub header: Mh2oHeader,
@@ -25,6 +27,7 @@ pub struct Mh2oEntry {
 
 /// MH2O header for a single map chunk
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mh2oHeader {
     /// Offset to instance data, relative to the start of the MH2O chunk
     pub offset_instances: u32,
@@ -36,6 +39,7 @@ pub struct Mh2oHeader {
 
 /// MH2O water instance (layer)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mh2oInstance {
     /// Liquid type ID
     pub liquid_type: u16,
@@ -51,6 +55,7 @@ pub struct Mh2oInstance {
 
 /// Water height information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WaterLevelData {
     /// Single water level for the entire chunk
     Uniform {
@@ -74,6 +79,7 @@ pub enum WaterLevelData {
 
 /// Water vertex information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WaterVertexData {
     /// Offset to vertex data, relative to MH2O chunk
     pub offset_vertex_data: u32,
@@ -87,6 +93,7 @@ pub struct WaterVertexData {
 
 /// Individual water vertex
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WaterVertex {
     /// Depth at this vertex
     pub depth: f32,
@@ -96,6 +103,7 @@ pub struct WaterVertex {
 
 /// Render mask for water
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mh2oRenderMask {
     /// The mask is an 8x8 grid of bits, stored as 8 bytes
     pub mask: [u8; 8],
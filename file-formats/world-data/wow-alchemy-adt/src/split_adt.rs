@@ -6,6 +6,7 @@ use std::io::{Read, Seek, SeekFrom};
 
 /// Represents a split ADT file type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SplitAdtType {
     /// Main terrain file (no suffix)
     Root,
@@ -247,6 +248,7 @@ impl SplitAdtParser {
 
 /// Texture data from split ADT files
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(dead_code)]
 pub struct TexAdtData {
     pub mtex: Option<MtexChunk>,
@@ -256,6 +258,7 @@ pub struct TexAdtData {
 
 /// MCNK texture data from tex files
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(dead_code)]
 pub struct McnkTexData {
     #[allow(dead_code)]
@@ -268,6 +271,7 @@ pub struct McnkTexData {
 
 /// Object data from split ADT files
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ObjAdtData {
     pub mmdx: Option<MmdxChunk>,
     pub mmid: Option<MmidChunk>,
@@ -279,6 +283,7 @@ pub struct ObjAdtData {
 
 /// Level of detail data from split ADT files
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LodAdtData {
     pub mver: Option<MverChunk>,
     pub simplified_data: Vec<u8>, // Placeholder for LOD-specific data
@@ -1,9 +1,12 @@
 // version.rs - Version handling for ADT files
 
+use wow_alchemy_data::game_version::GameVersion;
+
 use crate::error::{AdtError, Result};
 
 /// Represents the different World of Warcraft versions that ADT files can be from
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AdtVersion {
     /// Vanilla WoW (1.x)
     Vanilla,
@@ -135,3 +138,21 @@ impl std::fmt::Display for AdtVersion {
         write!(f, "{s}")
     }
 }
+
+impl From<GameVersion> for AdtVersion {
+    /// The War Within uses the format of Dragonflight
+    fn from(version: GameVersion) -> Self {
+        match version {
+            GameVersion::Vanilla => AdtVersion::Vanilla,
+            GameVersion::TBC => AdtVersion::TBC,
+            GameVersion::WotLK => AdtVersion::WotLK,
+            GameVersion::Cataclysm => AdtVersion::Cataclysm,
+            GameVersion::MoP => AdtVersion::MoP,
+            GameVersion::WoD => AdtVersion::WoD,
+            GameVersion::Legion => AdtVersion::Legion,
+            GameVersion::BfA => AdtVersion::BfA,
+            GameVersion::Shadowlands => AdtVersion::Shadowlands,
+            GameVersion::Dragonflight | GameVersion::TheWarWithin => AdtVersion::Dragonflight,
+        }
+    }
+}
@@ -39,6 +39,10 @@ pub enum WdlError {
     #[error("Unexpected end of file")]
     UnexpectedEof,
 
+    /// Error of the chunk writer
+    #[error("{0}")]
+    WowData(#[from] wow_alchemy_data::error::WowDataError),
+
     /// Unexpected chunk found
     #[error("Unexpected chunk type: {0}")]
     UnexpectedChunk(String),
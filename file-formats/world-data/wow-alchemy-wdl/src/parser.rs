@@ -1,7 +1,8 @@
 use memchr::memchr;
-use std::collections::HashMap;
 use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
 
+use wow_alchemy_data::chunk_writer::{ChunkWriter, MagicOrder};
+
 use crate::error::{Result, WdlError};
 use crate::types::*;
 use crate::version::WdlVersion;
@@ -224,177 +225,113 @@ impl WdlParser {
     }
 
     pub fn write<W: Write + Seek>(&self, writer: &mut W, file: &WdlFile) -> Result<()> {
-        // We'll build the file in memory first to calculate offsets
-        let mut chunks = Vec::new();
+        // The magic constants are already in file order
+        let mut writer = ChunkWriter::new(writer, MagicOrder::AsIs)?;
 
         // Write MVER chunk
-        let mut mver_data = Vec::new();
-        mver_data
-            .write_all(&file.version.version_number().to_le_bytes())
-            .map_err(WdlError::Io)?;
-        chunks.push(Chunk::new(MVER_MAGIC, mver_data));
+        writer.begin_chunk(MVER_MAGIC)?;
+        writer.write_all(&file.version.version_number().to_le_bytes())?;
+        writer.end_chunk()?;
 
         // Write WMO chunks if supported
         if file.version.has_wmo_chunks() && !file.wmo_filenames.is_empty() {
             // Write MWMO chunk (WMO filenames)
-            let mut mwmo_data = Vec::new();
+            writer.begin_chunk(MWMO_MAGIC)?;
             for name in &file.wmo_filenames {
-                mwmo_data.extend_from_slice(name.as_bytes());
-                mwmo_data.push(0); // Null terminator
+                writer.write_all(name.as_bytes())?;
+                writer.write_all(&[0])?; // Null terminator
             }
-            chunks.push(Chunk::new(MWMO_MAGIC, mwmo_data));
+            writer.end_chunk()?;
 
             // Write MWID chunk (WMO indices)
-            let mut mwid_data = Vec::new();
+            writer.begin_chunk(MWID_MAGIC)?;
             for &idx in &file.wmo_indices {
-                mwid_data
-                    .write_all(&idx.to_le_bytes())
-                    .map_err(WdlError::Io)?;
+                writer.write_all(&idx.to_le_bytes())?;
             }
-            chunks.push(Chunk::new(MWID_MAGIC, mwid_data));
+            writer.end_chunk()?;
 
             // Write MODF chunk (WMO placements)
-            let mut modf_data = Vec::new();
+            writer.begin_chunk(MODF_MAGIC)?;
             for placement in &file.wmo_placements {
-                placement.write(&mut modf_data).map_err(WdlError::Io)?;
+                placement.write(&mut writer)?;
             }
-            chunks.push(Chunk::new(MODF_MAGIC, modf_data));
+            writer.end_chunk()?;
         }
 
         // Write Legion+ chunks if supported
         if file.version.has_ml_chunks() {
             // Write MLDD chunk (M2 placements)
             if !file.m2_placements.is_empty() {
-                let mut mldd_data = Vec::new();
+                writer.begin_chunk(MLDD_MAGIC)?;
                 for placement in &file.m2_placements {
-                    placement.write(&mut mldd_data).map_err(WdlError::Io)?;
+                    placement.write(&mut writer)?;
                 }
-                chunks.push(Chunk::new(MLDD_MAGIC, mldd_data));
+                writer.end_chunk()?;
             }
 
             // Write MLDX chunk (M2 visibility info)
             if !file.m2_visibility.is_empty() {
-                let mut mldx_data = Vec::new();
+                writer.begin_chunk(MLDX_MAGIC)?;
                 for info in &file.m2_visibility {
-                    info.write(&mut mldx_data).map_err(WdlError::Io)?;
+                    info.write(&mut writer)?;
                 }
-                chunks.push(Chunk::new(MLDX_MAGIC, mldx_data));
+                writer.end_chunk()?;
             }
 
             // Write MLMD chunk (WMO Legion placements)
             if !file.wmo_legion_placements.is_empty() {
-                let mut mlmd_data = Vec::new();
+                writer.begin_chunk(MLMD_MAGIC)?;
                 for placement in &file.wmo_legion_placements {
-                    placement.write(&mut mlmd_data).map_err(WdlError::Io)?;
+                    placement.write(&mut writer)?;
                 }
-                chunks.push(Chunk::new(MLMD_MAGIC, mlmd_data));
+                writer.end_chunk()?;
             }
 
             // Write MLMX chunk (WMO Legion visibility info)
             if !file.wmo_legion_visibility.is_empty() {
-                let mut mlmx_data = Vec::new();
+                writer.begin_chunk(MLMX_MAGIC)?;
                 for info in &file.wmo_legion_visibility {
-                    info.write(&mut mlmx_data).map_err(WdlError::Io)?;
+                    info.write(&mut writer)?;
                 }
-                chunks.push(Chunk::new(MLMX_MAGIC, mlmx_data));
+                writer.end_chunk()?;
             }
         }
 
-        // Now we need to determine the positions of the MARE and MAHO chunks
-        // So we can write the correct offsets in the MAOF chunk
-        let mut map_tile_offsets = [0u32; 64 * 64];
-        let mut mare_chunks = HashMap::new();
-        let mut maho_chunks = HashMap::new();
-
-        // Calculate the base offset for the MARE and MAHO chunks
-        // which is right after the MAOF chunk
-        let mut current_offset = 0;
-
-        // Account for all chunks written so far, plus the MAOF chunk
-        for chunk in &chunks {
-            current_offset += 8 + chunk.size; // 8 bytes for magic and size
-        }
-
-        // Add the MAOF chunk size
-        current_offset += 8 + (64 * 64 * 4); // 8 bytes for magic and size, 4 bytes per offset
+        // Write MAOF chunk (Map tile offsets), filled in as the MARE chunks
+        // of the tiles are written after it
+        writer.begin_chunk(MAOF_MAGIC)?;
+        let map_tile_offsets = writer.reserve_u32s(64 * 64)?;
+        writer.end_chunk()?;
 
-        // Now calculate offsets for each map tile
         for y in 0..64 {
             for x in 0..64 {
-                let index = y * 64 + x;
                 let key = (x as u32, y as u32);
 
                 // Skip empty tiles
-                if !file.heightmap_tiles.contains_key(&key) {
-                    map_tile_offsets[index] = 0;
+                let Some(heightmap) = file.heightmap_tiles.get(&key) else {
                     continue;
-                }
+                };
 
-                // Set the offset for this tile
-                map_tile_offsets[index] = current_offset;
+                let offset = writer.position()?;
+                writer.fill_u32(map_tile_offsets[y * 64 + x], offset)?;
 
-                // Create MARE chunk
-                let heightmap = file.heightmap_tiles.get(&key).unwrap();
-                let mut mare_data = Vec::new();
-                heightmap.write(&mut mare_data).map_err(WdlError::Io)?;
-                mare_chunks.insert(key, Chunk::new(MARE_MAGIC, mare_data));
-
-                // Update offset for next chunk
-                current_offset += 8 + (HeightMapTile::TOTAL_COUNT * 2) as u32; // 8 bytes for magic and size, 2 bytes per height value
+                // Write MARE chunk
+                writer.begin_chunk(MARE_MAGIC)?;
+                heightmap.write(&mut writer)?;
+                writer.end_chunk()?;
 
-                // Create MAHO chunk if needed
+                // Write MAHO chunk if present
                 if file.version.has_maho_chunk() {
                     if let Some(holes) = file.holes_data.get(&key) {
-                        let mut maho_data = Vec::new();
-                        holes.write(&mut maho_data).map_err(WdlError::Io)?;
-                        maho_chunks.insert(key, Chunk::new(MAHO_MAGIC, maho_data));
-
-                        // Update offset for next chunk
-                        current_offset += 8 + (HolesData::MASK_COUNT * 2) as u32;
-                        // 8 bytes for magic and size, 2 bytes per mask
+                        writer.begin_chunk(MAHO_MAGIC)?;
+                        holes.write(&mut writer)?;
+                        writer.end_chunk()?;
                     }
                 }
             }
         }
 
-        // Write MAOF chunk (Map tile offsets)
-        let mut maof_data = Vec::new();
-        for &offset in &map_tile_offsets {
-            maof_data
-                .write_all(&offset.to_le_bytes())
-                .map_err(WdlError::Io)?;
-        }
-        chunks.push(Chunk::new(MAOF_MAGIC, maof_data));
-
-        // Write all the chunks that we've prepared
-        for chunk in &chunks {
-            chunk.write(writer).map_err(WdlError::Io)?;
-        }
-
-        // Write the MARE and MAHO chunks for each map tile
-        for y in 0..64 {
-            for x in 0..64 {
-                let key = (x as u32, y as u32);
-
-                // Skip empty tiles
-                if !mare_chunks.contains_key(&key) {
-                    continue;
-                }
-
-                // Write MARE chunk
-                mare_chunks
-                    .get(&key)
-                    .unwrap()
-                    .write(writer)
-                    .map_err(WdlError::Io)?;
-
-                // Write MAHO chunk if present
-                if let Some(maho_chunk) = maho_chunks.get(&key) {
-                    maho_chunk.write(writer).map_err(WdlError::Io)?;
-                }
-            }
-        }
-
+        writer.finish()?;
         Ok(())
     }
 
@@ -31,6 +31,7 @@ pub const MLMX_MAGIC: [u8; 4] = [b'X', b'M', b'L', b'M'];
 
 /// Vector 3D type used in WoW files
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec3d {
     /// X coordinate
     pub x: f32,
@@ -74,6 +75,7 @@ impl Vec3d {
 
 /// Bounding box used in WoW files
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BoundingBox {
     /// Minimum corner of the bounding box
     pub min: Vec3d,
@@ -109,6 +111,7 @@ impl BoundingBox {
 
 /// A chunk in a WDL file
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Chunk {
     /// The four-character identifier for this chunk
     pub magic: [u8; 4],
@@ -161,6 +164,7 @@ impl Chunk {
 
 /// Model placement information (MODF chunk data)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModelPlacement {
     /// Unique ID for this instance
     pub id: u32,
@@ -237,6 +241,7 @@ impl ModelPlacement {
 
 /// M2 Model placement information (MLDD chunk data in Legion+)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2Placement {
     /// Unique ID for this instance
     pub id: u32,
@@ -293,6 +298,7 @@ impl M2Placement {
 
 /// WMO Model visibility info (MLDX chunk data in Legion+)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct M2VisibilityInfo {
     /// Bounding box for visibility check
     pub bounds: BoundingBox,
@@ -329,6 +335,7 @@ impl M2VisibilityInfo {
 ///
 /// This matches the vertex layout of full ADT heightmaps but at lower resolution.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeightMapTile {
     /// Outer heightmap values (17x17 grid)
     /// These represent the height values at the corners of each chunk
@@ -402,6 +409,7 @@ impl HeightMapTile {
 /// - 0 = hole present
 /// - 1 = no hole (solid terrain)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HolesData {
     /// Bitmasks for holes (16 uint16 values, one per row)
     /// Each uint16 represents 16 chunks in a row (bits 0-15 = chunks 0-15)
@@ -496,6 +504,7 @@ impl HolesData {
 
 /// Main WDL file representation
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WdlFile {
     /// Version information
     pub version: WdlVersion,
@@ -504,10 +513,13 @@ pub struct WdlFile {
     /// Map tile offsets (MAOF chunk)
     /// Contains 4096 (64x64) absolute file offsets to MapAreaLow entries
     /// Zero values indicate tiles without low-resolution data
+    #[cfg_attr(feature = "serde", serde(with = "wow_alchemy_data::serde_array"))]
     pub map_tile_offsets: [u32; 64 * 64],
     /// Heightmap tiles (MARE chunks)
+    #[cfg_attr(feature = "serde", serde(with = "serde_tiles"))]
     pub heightmap_tiles: HashMap<(u32, u32), HeightMapTile>,
     /// Holes data (MAHO chunks)
+    #[cfg_attr(feature = "serde", serde(with = "serde_tiles"))]
     pub holes_data: HashMap<(u32, u32), HolesData>,
     /// WMO filenames (MWMO chunk)
     pub wmo_filenames: Vec<String>,
@@ -524,6 +536,7 @@ pub struct WdlFile {
     /// WMO visibility info (MLMX chunk, Legion+)
     pub wmo_legion_visibility: Vec<M2VisibilityInfo>,
     /// All chunks in the file, in order
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub chunks: Vec<Chunk>,
 }
 
@@ -613,3 +626,32 @@ impl Default for WdlFile {
         Self::new()
     }
 }
+
+/// Serde support for maps of tiles, written as a list of `[[x, y], tile]`
+/// entries ordered by coordinates since formats like JSON only have string
+/// keys
+#[cfg(feature = "serde")]
+mod serde_tiles {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S, T>(tiles: &HashMap<(u32, u32), T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        let mut entries: Vec<_> = tiles.iter().collect();
+        entries.sort_by_key(|(coords, _)| **coords);
+        serializer.collect_seq(entries)
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<HashMap<(u32, u32), T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        let entries = Vec::<((u32, u32), T)>::deserialize(deserializer)?;
+        Ok(entries.into_iter().collect())
+    }
+}
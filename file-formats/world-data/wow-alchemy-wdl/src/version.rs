@@ -2,8 +2,11 @@
 
 use std::fmt;
 
+use wow_alchemy_data::game_version::GameVersion;
+
 /// Represents the different versions of WDL files
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WdlVersion {
     /// Vanilla version (Classic, TBC, pre-WotLK)
     Vanilla,
@@ -72,7 +75,7 @@ impl WdlVersion {
     /// Returns true if this version supports MWMO, MWID, and MODF chunks
     pub fn has_wmo_chunks(&self) -> bool {
         match self {
-            WdlVersion::Vanilla => false,
+            WdlVersion::Vanilla => true,
             WdlVersion::Wotlk => true,
             WdlVersion::Cataclysm => true,
             WdlVersion::Mop => true,
@@ -140,3 +143,21 @@ impl fmt::Display for WdlVersion {
         }
     }
 }
+
+impl From<GameVersion> for WdlVersion {
+    /// The Burning Crusade uses the format of Vanilla
+    fn from(version: GameVersion) -> Self {
+        match version {
+            GameVersion::Vanilla | GameVersion::TBC => WdlVersion::Vanilla,
+            GameVersion::WotLK => WdlVersion::Wotlk,
+            GameVersion::Cataclysm => WdlVersion::Cataclysm,
+            GameVersion::MoP => WdlVersion::Mop,
+            GameVersion::WoD => WdlVersion::Wod,
+            GameVersion::Legion => WdlVersion::Legion,
+            GameVersion::BfA => WdlVersion::Bfa,
+            GameVersion::Shadowlands => WdlVersion::Shadowlands,
+            GameVersion::Dragonflight => WdlVersion::Dragonflight,
+            GameVersion::TheWarWithin => WdlVersion::Latest,
+        }
+    }
+}
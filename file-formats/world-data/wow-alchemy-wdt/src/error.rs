@@ -13,6 +13,11 @@ pub enum Error {
     #[error("I/O error: {0}")]
     Io(#[from] io::Error),
 
+    /// The file provider failed to read a file
+    #[cfg(feature = "vfs")]
+    #[error("File provider error: {0}")]
+    Provider(#[from] wow_alchemy_vfs::VfsError),
+
     /// Invalid magic bytes for a chunk
     #[error("Invalid chunk magic: expected {expected:?}, found {found:?}")]
     InvalidMagic { expected: [u8; 4], found: [u8; 4] },
@@ -64,6 +69,9 @@ pub enum Error {
         limit: usize,
         context: String,
     },
+    /// Error of the chunk writer
+    #[error("{0}")]
+    WowData(#[from] wow_alchemy_data::error::WowDataError),
 }
 
 impl Error {
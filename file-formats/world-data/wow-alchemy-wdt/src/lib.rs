@@ -4,12 +4,16 @@ pub mod error;
 pub mod version;
 
 use crate::chunks::{Chunk, MaidChunk, MainChunk, ModfChunk, MphdChunk, MverChunk, MwmoChunk};
+#[cfg(feature = "vfs")]
+use crate::chunks::{FileDataIds, MaidSection};
 use crate::error::{Error, Result};
 use crate::version::{VersionConfig, WowVersion};
 use std::io::{Read, Seek, SeekFrom, Write};
+use wow_alchemy_data::chunk_writer::{ChunkWriter, MagicOrder};
 
 /// A complete WDT file representation
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WdtFile {
     /// Version chunk (always required)
     pub mver: MverChunk,
@@ -80,6 +84,101 @@ impl WdtFile {
         })
     }
 
+    /// Path of a tile file of the map whose WDT is at `wdt_path`, e.g.
+    /// `World\Maps\Azeroth\Azeroth_32_48_obj0.adt`
+    ///
+    /// Only the ADT sections have paths, the textures are only referenced by
+    /// FileDataID.
+    #[cfg(feature = "vfs")]
+    pub fn tile_path(wdt_path: &str, x: usize, y: usize, section: MaidSection) -> Option<String> {
+        let suffix = match section {
+            MaidSection::RootAdt => "",
+            MaidSection::Obj0Adt => "_obj0",
+            MaidSection::Obj1Adt => "_obj1",
+            MaidSection::Tex0Adt => "_tex0",
+            MaidSection::LodAdt => "_lod",
+            _ => return None,
+        };
+        let base = wow_alchemy_vfs::path::strip_extension(wdt_path);
+        Some(format!("{base}_{x}_{y}{suffix}.adt"))
+    }
+
+    /// Read a tile file of the map whose WDT is at `wdt_path` from a file
+    /// provider, by FileDataID from the MAID chunk if there is one, else by
+    /// path
+    ///
+    /// Returns `None` when the tile has no such file.
+    #[cfg(feature = "vfs")]
+    pub fn read_tile_file<P: wow_alchemy_vfs::WowFileProvider + ?Sized>(
+        &self,
+        provider: &P,
+        wdt_path: &str,
+        x: usize,
+        y: usize,
+        section: MaidSection,
+    ) -> Result<Option<Vec<u8>>> {
+        if let Some(ref maid) = self.maid {
+            return match maid.get(section, x, y) {
+                Some(file_data_id) if file_data_id != 0 => {
+                    Ok(Some(provider.read_id(file_data_id)?))
+                }
+                _ => Ok(None),
+            };
+        }
+
+        if !self.get_tile(x, y).is_some_and(|tile| tile.has_adt) {
+            return Ok(None);
+        }
+        match Self::tile_path(wdt_path, x, y, section) {
+            Some(path) if section == MaidSection::RootAdt || provider.exists(&path) => {
+                Ok(Some(provider.read(&path)?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Set the FileDataIDs of the MAID chunk and MPHD from the paths of the
+    /// files of the map whose WDT is at `wdt_path`, which is how WDTs from
+    /// BfA on reference them
+    ///
+    /// Files that the resolver doesn't know get id 0, like files a map
+    /// doesn't have. Map and minimap textures are left at 0.
+    #[cfg(feature = "vfs")]
+    pub fn assign_file_ids(
+        &mut self,
+        resolver: &wow_alchemy_vfs::FileIdResolver,
+        wdt_path: &str,
+    ) -> Result<()> {
+        let mut maid = MaidChunk::new();
+        for y in 0..chunks::WDT_MAP_SIZE {
+            for x in 0..chunks::WDT_MAP_SIZE {
+                // Not `get_tile`, which goes by the MAID chunk being replaced
+                if !self.main.get(x, y).is_some_and(|entry| entry.has_adt()) {
+                    continue;
+                }
+                for &section in MaidSection::all() {
+                    if let Some(path) = Self::tile_path(wdt_path, x, y, section) {
+                        maid.set(section, x, y, resolver.file_id(&path).unwrap_or(0))?;
+                    }
+                }
+            }
+        }
+        self.maid = Some(maid);
+
+        let base = wow_alchemy_vfs::path::strip_extension(wdt_path);
+        let id = |suffix: &str| resolver.file_id(&format!("{base}{suffix}")).unwrap_or(0);
+        self.mphd.set_file_data_ids(FileDataIds {
+            lgt: id("_lgt.wdt"),
+            occ: id("_occ.wdt"),
+            fogs: id("_fogs.wdt"),
+            mpv: id("_mpv.wdt"),
+            tex: id(".tex"),
+            wdl: id(".wdl"),
+            pd4: id("_pd4.wdt"),
+        });
+        Ok(())
+    }
+
     /// Get the detected WoW version
     pub fn version(&self) -> WowVersion {
         self.version_config.version
@@ -144,6 +243,7 @@ impl WdtFile {
 
 /// Information about a specific tile
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TileInfo {
     pub x: usize,
     pub y: usize,
@@ -308,11 +408,11 @@ impl<R: Read + Seek> WdtReader<R> {
 }
 
 /// WDT file writer
-pub struct WdtWriter<W: Write> {
+pub struct WdtWriter<W: Write + Seek> {
     writer: W,
 }
 
-impl<W: Write> WdtWriter<W> {
+impl<W: Write + Seek> WdtWriter<W> {
     /// Create a new WDT writer
     pub fn new(writer: W) -> Self {
         Self { writer }
@@ -320,14 +420,17 @@ impl<W: Write> WdtWriter<W> {
 
     /// Write a complete WDT file
     pub fn write(&mut self, wdt: &WdtFile) -> Result<()> {
+        // The chunk magics are already in file order
+        let mut writer = ChunkWriter::new(&mut self.writer, MagicOrder::AsIs)?;
+
         // Write required chunks in order
-        wdt.mver.write_chunk(&mut self.writer)?;
-        wdt.mphd.write_chunk(&mut self.writer)?;
-        wdt.main.write_chunk(&mut self.writer)?;
+        write_chunk(&mut writer, &wdt.mver)?;
+        write_chunk(&mut writer, &wdt.mphd)?;
+        write_chunk(&mut writer, &wdt.main)?;
 
         // Write optional chunks
         if let Some(ref maid) = wdt.maid {
-            maid.write_chunk(&mut self.writer)?;
+            write_chunk(&mut writer, maid)?;
         }
 
         // Write MWMO only if appropriate for the version and map type
@@ -336,18 +439,27 @@ impl<W: Write> WdtWriter<W> {
                 .version_config
                 .should_have_chunk("MWMO", wdt.is_wmo_only());
             if should_write {
-                mwmo.write_chunk(&mut self.writer)?;
+                write_chunk(&mut writer, mwmo)?;
             }
         }
 
         if let Some(ref modf) = wdt.modf {
-            modf.write_chunk(&mut self.writer)?;
+            write_chunk(&mut writer, modf)?;
         }
 
+        writer.finish()?;
         Ok(())
     }
 }
 
+/// Write a chunk with the size of the data it writes
+fn write_chunk<W: Write + Seek, C: Chunk>(writer: &mut ChunkWriter<W>, chunk: &C) -> Result<()> {
+    writer.begin_chunk(*C::magic())?;
+    chunk.write(writer)?;
+    writer.end_chunk()?;
+    Ok(())
+}
+
 /// Convert ADT tile coordinates to world coordinates
 pub fn tile_to_world(tile_x: u32, tile_y: u32) -> (f32, f32) {
     const MAP_SIZE: f32 = 533.333_3;
@@ -2,9 +2,11 @@
 
 use crate::error::{Error, Result};
 use std::fmt;
+use wow_alchemy_data::game_version::GameVersion;
 
 /// WoW expansion versions that affect WDT format
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WowVersion {
     /// Classic/Vanilla (1.x)
     Classic,
@@ -29,58 +31,19 @@ pub enum WowVersion {
 }
 
 impl WowVersion {
-    /// Parse version from a string (e.g., "1.12.1", "3.3.5a", "4.3.4")
+    /// Parse version from a string (e.g., "1.12.1", "3.3.5a", "4.3.4.15595")
     pub fn from_string(s: &str) -> Result<Self> {
-        let parts: Vec<&str> = s.split('.').collect();
-        if parts.is_empty() {
-            return Err(Error::ValidationError(format!(
-                "Invalid version string: {s}"
-            )));
-        }
-
-        let major = parts[0]
-            .parse::<u32>()
-            .map_err(|_| Error::ValidationError(format!("Invalid major version: {}", parts[0])))?;
-
-        Ok(match major {
-            1 => WowVersion::Classic,
-            2 => WowVersion::TBC,
-            3 => WowVersion::WotLK,
-            4 => WowVersion::Cataclysm,
-            5 => WowVersion::MoP,
-            6 => WowVersion::WoD,
-            7 => WowVersion::Legion,
-            8 => WowVersion::BfA,
-            9 => WowVersion::Shadowlands,
-            10 => WowVersion::Dragonflight,
-            _ => {
-                return Err(Error::ValidationError(format!(
-                    "Unknown WoW version: {major}"
-                )));
-            }
-        })
+        GameVersion::from_string(s)
+            .map(Self::from)
+            .map_err(|err| Error::ValidationError(err.to_string()))
     }
 
     /// Parse version from expansion short names or numeric versions
     /// Supports both numeric versions (e.g., "3.3.5a") and short names (e.g., "WotLK", "TBC")
     pub fn from_expansion_name(s: &str) -> Result<Self> {
-        // First try parsing as a short name
-        match s.to_lowercase().as_str() {
-            "vanilla" | "classic" => Ok(WowVersion::Classic),
-            "tbc" | "bc" | "burningcrusade" | "burning_crusade" => Ok(WowVersion::TBC),
-            "wotlk" | "wrath" | "lichking" | "lich_king" | "wlk" => Ok(WowVersion::WotLK),
-            "cata" | "cataclysm" => Ok(WowVersion::Cataclysm),
-            "mop" | "pandaria" | "mists" | "mists_of_pandaria" => Ok(WowVersion::MoP),
-            "wod" | "draenor" | "warlords" | "warlords_of_draenor" => Ok(WowVersion::WoD),
-            "legion" => Ok(WowVersion::Legion),
-            "bfa" | "bfazeroth" | "battle_for_azeroth" | "battleforazeroth" => Ok(WowVersion::BfA),
-            "sl" | "shadowlands" => Ok(WowVersion::Shadowlands),
-            "df" | "dragonflight" => Ok(WowVersion::Dragonflight),
-            _ => {
-                // If it's not a short name, try parsing as a numeric version
-                Self::from_string(s)
-            }
-        }
+        GameVersion::from_expansion_name(s)
+            .map(Self::from)
+            .map_err(|err| Error::ValidationError(err.to_string()))
     }
 
     /// Check if this version has terrain maps with MWMO chunks
@@ -159,8 +122,27 @@ impl fmt::Display for WowVersion {
     }
 }
 
+impl From<GameVersion> for WowVersion {
+    /// The War Within uses the format of Dragonflight
+    fn from(version: GameVersion) -> Self {
+        match version {
+            GameVersion::Vanilla => WowVersion::Classic,
+            GameVersion::TBC => WowVersion::TBC,
+            GameVersion::WotLK => WowVersion::WotLK,
+            GameVersion::Cataclysm => WowVersion::Cataclysm,
+            GameVersion::MoP => WowVersion::MoP,
+            GameVersion::WoD => WowVersion::WoD,
+            GameVersion::Legion => WowVersion::Legion,
+            GameVersion::BfA => WowVersion::BfA,
+            GameVersion::Shadowlands => WowVersion::Shadowlands,
+            GameVersion::Dragonflight | GameVersion::TheWarWithin => WowVersion::Dragonflight,
+        }
+    }
+}
+
 /// Version-specific configuration for WDT handling
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VersionConfig {
     pub version: WowVersion,
 }
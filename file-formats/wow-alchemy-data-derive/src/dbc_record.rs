@@ -0,0 +1,211 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Attribute, Data, DeriveInput, Fields, LitStr};
+
+#[derive(Default)]
+struct DbcFieldAttrs {
+    name: Option<String>,
+    key: bool,
+    relation: bool,
+    noninline: bool,
+    foreign_key: Option<String>,
+}
+
+fn parse_dbc_attrs(attrs: &[Attribute]) -> syn::Result<DbcFieldAttrs> {
+    let mut dbc_attrs = DbcFieldAttrs::default();
+
+    for attr in attrs {
+        if !attr.path().is_ident("dbc") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("key") {
+                dbc_attrs.key = true;
+            } else if meta.path.is_ident("relation") {
+                dbc_attrs.relation = true;
+            } else if meta.path.is_ident("noninline") {
+                dbc_attrs.noninline = true;
+            } else if meta.path.is_ident("name") {
+                let value: LitStr = meta.value()?.parse()?;
+                dbc_attrs.name = Some(value.value());
+            } else if meta.path.is_ident("table") {
+                return Err(
+                    meta.error("`table` goes on the struct, use `name` for the column of a field")
+                );
+            } else if meta.path.is_ident("foreign_key") {
+                let value: LitStr = meta.value()?.parse()?;
+                dbc_attrs.foreign_key = Some(value.value());
+            } else {
+                return Err(meta.error("unknown dbc attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(dbc_attrs)
+}
+
+/// Name of the table from `#[dbc(table = "...")]` on the struct
+fn parse_table_name(attrs: &[Attribute]) -> syn::Result<Option<String>> {
+    let mut table_name = None;
+
+    for attr in attrs {
+        if !attr.path().is_ident("dbc") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                let value: LitStr = meta.value()?.parse()?;
+                table_name = Some(value.value());
+                Ok(())
+            } else {
+                Err(meta.error("unknown dbc attribute on a struct, only `table` is allowed"))
+            }
+        })?;
+    }
+
+    Ok(table_name)
+}
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let struct_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "DbcRecord can only be derived for structs.",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "DbcRecord can only be derived for structs with named fields.",
+        ));
+    };
+
+    let table_name =
+        parse_table_name(&input.attrs)?.unwrap_or_else(|| struct_name.unraw().to_string());
+
+    let mut columns = Vec::new();
+    let mut dbd_fields = Vec::new();
+    let mut readers = Vec::new();
+    let mut writers = Vec::new();
+    let mut key = None;
+
+    for (index, field) in fields.named.iter().enumerate() {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let attrs = parse_dbc_attrs(&field.attrs)?;
+        let name = attrs
+            .name
+            .clone()
+            .unwrap_or_else(|| ident.unraw().to_string());
+
+        let foreign_key = match &attrs.foreign_key {
+            Some(value) => {
+                let (table, field) = value.split_once("::").unwrap_or((value, "ID"));
+                quote! {
+                    Some(::wow_alchemy_cdbc::dbd::ForeignKey {
+                        table: #table.to_owned(),
+                        field: #field.to_owned(),
+                    })
+                }
+            }
+            None => quote! { None },
+        };
+        columns.push(quote! {
+            ::wow_alchemy_cdbc::dbd::DbdColumn {
+                name: #name.to_owned(),
+                base_type: <#ty as ::wow_alchemy_cdbc::DbcValue>::BASE_TYPE.to_owned(),
+                foreign_key: #foreign_key,
+                comment: None,
+                is_optional: false,
+            }
+        });
+
+        let (is_key, is_relation, is_noninline) = (attrs.key, attrs.relation, attrs.noninline);
+        dbd_fields.push(quote! {
+            ::wow_alchemy_cdbc::dbd::DbdField {
+                name: #name.to_owned(),
+                type_size: <#ty as ::wow_alchemy_cdbc::DbcValue>::TYPE_SIZE,
+                is_array: <#ty as ::wow_alchemy_cdbc::DbcValue>::ARRAY_SIZE.is_some(),
+                array_size: <#ty as ::wow_alchemy_cdbc::DbcValue>::ARRAY_SIZE,
+                is_key: #is_key,
+                is_relation: #is_relation,
+                is_noninline: #is_noninline,
+                comment: None,
+            }
+        });
+
+        readers.push(quote! {
+            #ident: <#ty as ::wow_alchemy_cdbc::DbcValue>::from_field(record, #index, #name)?
+        });
+        writers.push(quote! {
+            ::wow_alchemy_cdbc::DbcValue::to_value(&self.#ident)
+        });
+
+        if attrs.key {
+            if key.is_some() {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "Only one field can be the key of a DbcRecord.",
+                ));
+            }
+            key = Some(ident.clone());
+        }
+    }
+
+    // Tables without an explicit key use the first field, as in DBC files
+    let key = match key.or_else(|| fields.named.first().and_then(|f| f.ident.clone())) {
+        Some(key) => key,
+        None => {
+            return Err(syn::Error::new_spanned(
+                struct_name,
+                "DbcRecord needs at least one field.",
+            ));
+        }
+    };
+    let field_count = fields.named.len();
+
+    Ok(quote! {
+        impl #impl_generics ::wow_alchemy_cdbc::DbcRecord for #struct_name #ty_generics #where_clause {
+            const TABLE_NAME: &'static str = #table_name;
+
+            fn columns() -> Vec<::wow_alchemy_cdbc::dbd::DbdColumn> {
+                vec![#(#columns),*]
+            }
+
+            fn fields() -> Vec<::wow_alchemy_cdbc::dbd::DbdField> {
+                vec![#(#dbd_fields),*]
+            }
+
+            fn from_record(record: &[::wow_alchemy_cdbc::Value]) -> ::wow_alchemy_cdbc::Result<Self> {
+                if record.len() != #field_count {
+                    return Err(::wow_alchemy_cdbc::Error::InvalidRecord(format!(
+                        "expected {} values for {}, got {}",
+                        #field_count,
+                        #table_name,
+                        record.len()
+                    )));
+                }
+                Ok(Self {
+                    #(#readers),*
+                })
+            }
+
+            fn to_record(&self) -> ::wow_alchemy_cdbc::Record {
+                vec![#(#writers),*]
+            }
+
+            fn id(&self) -> u32 {
+                ::wow_alchemy_cdbc::DbcValue::to_value(&self.#key)
+                    .as_i64()
+                    .unwrap_or_default() as u32
+            }
+        }
+    })
+}
@@ -2,6 +2,24 @@ use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::{Arm, Data, DeriveInput, Expr, Fields, Ident, LitStr, Type, parse_macro_input};
 
+mod dbc_record;
+
+/// Map a struct to the layout of a DBC table, implementing
+/// `wow_alchemy_cdbc::DbcRecord`. Fields are read in order and their types
+/// must implement `wow_alchemy_cdbc::DbcValue`.
+///
+/// Attributes: `#[dbc(table = "Spell")]` on the struct, and `key`,
+/// `name = "Column"`, `foreign_key = "Table"` or `"Table::Column"`,
+/// `relation` and `noninline` on fields.
+#[proc_macro_derive(DbcRecord, attributes(dbc))]
+pub fn dbc_record_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match dbc_record::derive(input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
 #[proc_macro_derive(WowHeaderR, attributes(wow_data))]
 pub fn wow_header_r_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -214,6 +232,8 @@ struct WowDataAttrs {
     from_arm: Option<Arm>,
     to_arm: Option<Arm>,
     bitflags: Option<Type>,
+    magic: Option<LitStr>,
+    chunk: bool,
 }
 
 fn parse_wow_data_attrs(attrs: &[syn::Attribute]) -> syn::Result<WowDataAttrs> {
@@ -229,6 +249,8 @@ fn parse_wow_data_attrs(attrs: &[syn::Attribute]) -> syn::Result<WowDataAttrs> {
         from_arm: None,
         to_arm: None,
         bitflags: None,
+        magic: None,
+        chunk: false,
     };
 
     for attr in attrs {
@@ -292,6 +314,15 @@ fn parse_wow_data_attrs(attrs: &[syn::Attribute]) -> syn::Result<WowDataAttrs> {
                 data_attrs.bitflags = Some(value.parse()?);
             }
 
+            if meta.path.is_ident("magic") {
+                let value = meta.value()?;
+                data_attrs.magic = Some(value.parse()?);
+            }
+
+            if meta.path.is_ident("chunk") {
+                data_attrs.chunk = true;
+            }
+
             Ok(())
         })?;
     }
@@ -620,9 +651,9 @@ pub fn wow_data_r_derive(input: TokenStream) -> TokenStream {
         .into();
     };
 
-    TokenStream::from(if struct_wow_data_attrs.version.is_none() {
+    TokenStream::from(if let Some(version_ty) = struct_wow_data_attrs.version {
         quote! {
-            impl #impl_generics wow_alchemy_data::types::WowDataR<#header_ty> for #struct_name #ty_generics #where_clause {
+            impl #impl_generics wow_alchemy_data::types::VWowDataR<#version_ty, #header_ty> for #struct_name #ty_generics #where_clause {
                 fn new_from_header<R: Read + Seek>(reader: &mut R, header: &#header_ty) -> wow_alchemy_data::error::Result<Self> {
                     Ok(Self{
                         #(#initializers),*
@@ -631,9 +662,8 @@ pub fn wow_data_r_derive(input: TokenStream) -> TokenStream {
             }
         }
     } else {
-        let version_ty = struct_wow_data_attrs.version.unwrap();
         quote! {
-            impl #impl_generics wow_alchemy_data::types::VWowDataR<#version_ty, #header_ty> for #struct_name #ty_generics #where_clause {
+            impl #impl_generics wow_alchemy_data::types::WowDataR<#header_ty> for #struct_name #ty_generics #where_clause {
                 fn new_from_header<R: Read + Seek>(reader: &mut R, header: &#header_ty) -> wow_alchemy_data::error::Result<Self> {
                     Ok(Self{
                         #(#initializers),*
@@ -644,6 +674,82 @@ pub fn wow_data_r_derive(input: TokenStream) -> TokenStream {
     })
 }
 
+/// Counterpart of `WowDataR`: writes each field with the field of the same
+/// name in the header and returns the header with the new offsets. Fields
+/// with `override_read` are not written.
+#[proc_macro_derive(WowDataW, attributes(wow_data))]
+pub fn wow_data_w_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let struct_wow_data_attrs = match parse_wow_data_attrs(&input.attrs) {
+        Ok(data_attrs) => data_attrs,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let struct_name = &input.ident;
+
+    let fields = if let Data::Struct(s) = &input.data {
+        if let Fields::Named(f) = &s.fields {
+            &f.named
+        } else {
+            return syn::Error::new_spanned(
+                struct_name,
+                "WowDataW can only be derived for structs with named fields.",
+            )
+            .to_compile_error()
+            .into();
+        }
+    } else {
+        return syn::Error::new_spanned(struct_name, "WowDataW can only be derived for structs.")
+            .to_compile_error()
+            .into();
+    };
+
+    let mut writes = Vec::new();
+
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        let wow_data_attrs = match parse_wow_data_attrs(&field.attrs) {
+            Ok(data_attrs) => data_attrs,
+            Err(e) => return e.to_compile_error().into(),
+        };
+
+        if wow_data_attrs.override_read.is_none() {
+            writes.push(quote! {
+                header.#field_name = self.#field_name.wow_write_data(writer, &header.#field_name)?;
+            });
+        }
+    }
+
+    let Some(header_ty) = struct_wow_data_attrs.header else {
+        return syn::Error::new_spanned(
+            struct_name,
+            "WowDataW needs at least #[wow_data(header = H)] definition.",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    // The header is cloned, which for generic headers needs a bound that
+    // the struct doesn't have
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(syn::parse_quote!(#header_ty: Clone));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    TokenStream::from(quote! {
+        impl #impl_generics wow_alchemy_data::types::WowDataW<#header_ty> for #struct_name #ty_generics #where_clause {
+            fn wow_write_data<W: Write + Seek>(&self, writer: &mut W, header: &#header_ty) -> wow_alchemy_data::error::Result<#header_ty> {
+                let mut header = header.clone();
+                #(#writes)*
+                Ok(header)
+            }
+        }
+    })
+}
+
 fn generate_wow_enum_from_value_lines(
     data: &syn::DataEnum,
 ) -> syn::Result<Vec<proc_macro2::TokenStream>> {
@@ -798,3 +904,175 @@ pub fn wow_enum_from_derive(input: TokenStream) -> TokenStream {
 
     TokenStream::from(expanded)
 }
+
+/// Write a struct as a chunk with `wow_alchemy_data::chunk_writer::ChunkWriter`,
+/// implementing `wow_alchemy_data::types::WowChunkW`.
+///
+/// `#[wow_data(magic = "MVER")]` on the struct gives the magic of the chunk,
+/// as it reads. Without it the fields are written one after the other, like
+/// the chunks of a whole file. Fields are written with `WowHeaderW`, `Vec`
+/// fields item by item, and fields with `#[wow_data(chunk)]` as nested
+/// chunks with `WowChunkW`, which includes `Option` and `Vec` of chunks.
+#[proc_macro_derive(WowChunkW, attributes(wow_data))]
+pub fn wow_chunk_w_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match generate_chunk_writer(&input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn generate_chunk_writer(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_wow_attrs = parse_wow_data_attrs(&input.attrs)?;
+    let ident = &input.ident;
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "WowChunkW can only be derived for structs.",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "WowChunkW can only be derived for structs with named fields.",
+        ));
+    };
+
+    let mut write_statements = Vec::new();
+    for field in &fields.named {
+        let wow_data_attrs = parse_wow_data_attrs(&field.attrs)?;
+        let field_name = field.ident.as_ref().unwrap();
+
+        write_statements.push(if wow_data_attrs.chunk {
+            quote! {
+                wow_alchemy_data::types::WowChunkW::wow_write_chunk(&self.#field_name, writer)?;
+            }
+        } else if is_vec(&field.ty) {
+            quote! {
+                for item in &self.#field_name {
+                    wow_alchemy_data::types::WowHeaderW::wow_write(item, writer)?;
+                }
+            }
+        } else {
+            quote! {
+                wow_alchemy_data::types::WowHeaderW::wow_write(&self.#field_name, writer)?;
+            }
+        });
+    }
+
+    let (begin, end) = match &struct_wow_attrs.magic {
+        Some(magic) => {
+            let bytes = magic.value().into_bytes();
+            if bytes.len() != 4 || !bytes.is_ascii() {
+                return Err(syn::Error::new_spanned(
+                    magic,
+                    "A chunk magic must be 4 ASCII characters.",
+                ));
+            }
+            let magic = syn::LitByteStr::new(&bytes, magic.span());
+            (
+                quote! { writer.begin_chunk(*#magic)?; },
+                quote! { writer.end_chunk()?; },
+            )
+        }
+        None => (quote! {}, quote! {}),
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics wow_alchemy_data::types::WowChunkW for #ident #ty_generics #where_clause {
+            fn wow_write_chunk<W: ::std::io::Write + ::std::io::Seek>(
+                &self,
+                writer: &mut wow_alchemy_data::chunk_writer::ChunkWriter<W>,
+            ) -> wow_alchemy_data::error::Result<()> {
+                #begin
+                #(#write_statements)*
+                #end
+                Ok(())
+            }
+        }
+    })
+}
+
+fn is_vec(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Vec"),
+        _ => false,
+    }
+}
+
+/// Mark a struct as plain old data that can be viewed in place in the bytes
+/// of a file, implementing `wow_alchemy_data::pod::WowPod`.
+///
+/// The struct must be `#[repr(C)]` or `#[repr(transparent)]` and not generic.
+/// Compilation fails if a field is not `WowPod` or the struct has padding.
+#[proc_macro_derive(WowPod)]
+pub fn wow_pod_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match generate_pod(&input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn generate_pod(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let ident = &input.ident;
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "WowPod can only be derived for structs.",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "WowPod can't be derived for generic structs.",
+        ));
+    }
+
+    let mut has_repr = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") || meta.path.is_ident("transparent") {
+                has_repr = true;
+            }
+            Ok(())
+        })?;
+    }
+    if !has_repr {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "WowPod requires #[repr(C)] or #[repr(transparent)].",
+        ));
+    }
+
+    let field_types: Vec<&Type> = data.fields.iter().map(|field| &field.ty).collect();
+    let message = format!("{ident} has padding and can't be WowPod");
+
+    Ok(quote! {
+        unsafe impl wow_alchemy_data::pod::WowPod for #ident {}
+
+        const _: () = {
+            fn assert_pod<T: wow_alchemy_data::pod::WowPod>() {}
+            fn assert_fields() {
+                #(assert_pod::<#field_types>();)*
+            }
+            assert!(
+                ::core::mem::size_of::<#ident>()
+                    == 0 #(+ ::core::mem::size_of::<#field_types>())*,
+                #message
+            );
+        };
+    })
+}
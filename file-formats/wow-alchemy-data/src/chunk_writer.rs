@@ -0,0 +1,372 @@
+//! Writing of IFF-style chunked files, where every chunk is a magic and the
+//! size of its data followed by the data
+
+use std::io::{Seek, SeekFrom, Write};
+
+use crate::error::{Result, WowDataError};
+use crate::types::{MagicStr, WowChunkW, WowHeaderW};
+
+/// Size of the magic and size fields before the data of a chunk
+pub const CHUNK_HEADER_SIZE: u32 = 8;
+
+/// Byte order of chunk magics in a file. Map and WMO files store them
+/// reversed, e.g. `MVER` as `REVM`, while chunked M2 files store them as is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MagicOrder {
+    #[default]
+    AsIs,
+    Reversed,
+}
+
+/// Location of a written chunk, relative to the start of the writer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkSpan {
+    /// Offset of the chunk header
+    pub offset: u32,
+    /// Size of the chunk data
+    pub size: u32,
+}
+
+impl ChunkSpan {
+    /// Offset of the chunk data, after the header
+    pub fn data_offset(&self) -> u32 {
+        self.offset + CHUNK_HEADER_SIZE
+    }
+
+    /// Offset right after the chunk
+    pub fn end(&self) -> u32 {
+        self.data_offset() + self.size
+    }
+}
+
+/// A reserved `u32` that is filled in once its value is known, like an
+/// entry of an MHDR or MCIN offset table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot(u64);
+
+/// Writer of chunked files that fills in chunk sizes when chunks end, so
+/// nested chunks don't need their sizes up front.
+///
+/// Offsets are relative to the position of the underlying writer when the
+/// `ChunkWriter` is created, which usually is the start of the file.
+///
+/// ```
+/// use std::io::Cursor;
+/// use wow_alchemy_data::chunk_writer::{ChunkWriter, MagicOrder};
+/// use wow_alchemy_data::prelude::*;
+///
+/// let mut writer = ChunkWriter::new(Cursor::new(Vec::new()), MagicOrder::Reversed).unwrap();
+/// writer.begin_chunk(*b"MVER").unwrap();
+/// writer.wow_write(&18_u32).unwrap();
+/// let span = writer.end_chunk().unwrap();
+/// assert_eq!((span.offset, span.size), (0, 4));
+///
+/// let data = writer.finish().unwrap().into_inner();
+/// assert_eq!(data, b"REVM\x04\0\0\0\x12\0\0\0");
+/// ```
+#[derive(Debug)]
+pub struct ChunkWriter<W: Write + Seek> {
+    writer: W,
+    order: MagicOrder,
+    /// Position of the writer at offset 0
+    start: u64,
+    /// Positions of the headers of the chunks that have not ended yet
+    open: Vec<u64>,
+}
+
+impl<W: Write + Seek> ChunkWriter<W> {
+    pub fn new(mut writer: W, order: MagicOrder) -> Result<Self> {
+        let start = writer.stream_position()?;
+        Ok(Self {
+            writer,
+            order,
+            start,
+            open: Vec::new(),
+        })
+    }
+
+    pub fn magic_order(&self) -> MagicOrder {
+        self.order
+    }
+
+    /// Current offset relative to the start of the writer
+    pub fn position(&mut self) -> Result<u32> {
+        let position = self.writer.stream_position()?;
+        offset_u32(position - self.start)
+    }
+
+    /// Number of chunks that have begun and not ended yet
+    pub fn depth(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Write the header of a chunk with a size to fill in on
+    /// [`ChunkWriter::end_chunk`]. The magic is given as it reads, like
+    /// `*b"MVER"`, and written in the order of the writer.
+    pub fn begin_chunk(&mut self, magic: MagicStr) -> Result<()> {
+        let position = self.writer.stream_position()?;
+        let magic = match self.order {
+            MagicOrder::AsIs => magic,
+            MagicOrder::Reversed => [magic[3], magic[2], magic[1], magic[0]],
+        };
+        self.writer.write_all(&magic)?;
+        self.writer.write_all(&0_u32.to_le_bytes())?;
+        self.open.push(position);
+        Ok(())
+    }
+
+    /// End the last begun chunk and fill in its size
+    pub fn end_chunk(&mut self) -> Result<ChunkSpan> {
+        let header = self
+            .open
+            .pop()
+            .ok_or_else(|| WowDataError::ChunkWrite("no chunk to end".into()))?;
+        let end = self.writer.stream_position()?;
+        let size = offset_u32(end - header - CHUNK_HEADER_SIZE as u64)?;
+
+        self.writer.seek(SeekFrom::Start(header + 4))?;
+        self.writer.write_all(&size.to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(end))?;
+
+        Ok(ChunkSpan {
+            offset: offset_u32(header - self.start)?,
+            size,
+        })
+    }
+
+    /// Write a whole chunk
+    pub fn write_chunk<T: WowChunkW + ?Sized>(&mut self, chunk: &T) -> Result<()> {
+        chunk.wow_write_chunk(self)
+    }
+
+    /// Write a chunk that is an array of items, the counterpart of
+    /// [`crate::types::WowChunkR`]
+    pub fn write_items_chunk<T: WowHeaderW>(
+        &mut self,
+        magic: MagicStr,
+        items: &[T],
+    ) -> Result<ChunkSpan> {
+        self.begin_chunk(magic)?;
+        for item in items {
+            item.wow_write(self)?;
+        }
+        self.end_chunk()
+    }
+
+    /// Pad with zeros up to a multiple of `alignment` bytes from the start
+    /// of the writer, failing when `alignment` is 0
+    pub fn align(&mut self, alignment: u32) -> Result<()> {
+        if alignment == 0 {
+            return Err(WowDataError::ChunkWrite("alignment of 0 bytes".into()));
+        }
+        let position = self.position()?;
+        let padding = position.next_multiple_of(alignment) - position;
+        self.writer.write_all(&vec![0; padding as usize])?;
+        Ok(())
+    }
+
+    /// Write a zero `u32` to fill in later with [`ChunkWriter::fill_u32`]
+    pub fn reserve_u32(&mut self) -> Result<Slot> {
+        let position = self.writer.stream_position()?;
+        self.writer.write_all(&0_u32.to_le_bytes())?;
+        Ok(Slot(position))
+    }
+
+    /// Reserve a table of `count` consecutive `u32`s
+    pub fn reserve_u32s(&mut self, count: usize) -> Result<Vec<Slot>> {
+        (0..count).map(|_| self.reserve_u32()).collect()
+    }
+
+    /// Fill in a reserved `u32`
+    pub fn fill_u32(&mut self, slot: Slot, value: u32) -> Result<()> {
+        let end = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(slot.0))?;
+        self.writer.write_all(&value.to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        Ok(())
+    }
+
+    /// Return the underlying writer, failing when chunks have not ended
+    pub fn finish(mut self) -> Result<W> {
+        if !self.open.is_empty() {
+            return Err(WowDataError::ChunkWrite(format!(
+                "{} chunks have not ended",
+                self.open.len()
+            )));
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write + Seek> Write for ChunkWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+fn offset_u32(offset: u64) -> Result<u32> {
+    u32::try_from(offset)
+        .map_err(|_| WowDataError::ChunkWrite(format!("offset {offset} exceeds 4 GiB")))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use wow_alchemy_data_derive::{WowChunkW, WowHeaderW};
+
+    use super::*;
+    use crate::types::{C3Vector, WowWriterForHeader};
+
+    mod wow_alchemy_data {
+        pub use crate::*;
+    }
+
+    fn new_writer(order: MagicOrder) -> ChunkWriter<Cursor<Vec<u8>>> {
+        ChunkWriter::new(Cursor::new(Vec::new()), order).unwrap()
+    }
+
+    #[test]
+    fn test_nested_chunks() {
+        let mut writer = new_writer(MagicOrder::AsIs);
+        writer.begin_chunk(*b"MCNK").unwrap();
+        writer.write_all(&[1, 2]).unwrap();
+        writer.begin_chunk(*b"MCVT").unwrap();
+        writer.write_all(&[3, 4, 5]).unwrap();
+        let inner = writer.end_chunk().unwrap();
+        let outer = writer.end_chunk().unwrap();
+
+        assert_eq!(
+            inner,
+            ChunkSpan {
+                offset: 10,
+                size: 3
+            }
+        );
+        assert_eq!(
+            outer,
+            ChunkSpan {
+                offset: 0,
+                size: 13
+            }
+        );
+        assert_eq!(inner.end(), outer.end());
+        assert_eq!(
+            writer.finish().unwrap().into_inner(),
+            b"MCNK\x0d\0\0\0\x01\x02MCVT\x03\0\0\0\x03\x04\x05"
+        );
+    }
+
+    #[test]
+    fn test_offsets_and_alignment() {
+        let mut cursor = Cursor::new(Vec::new());
+        cursor.write_all(b"prefix").unwrap();
+        let mut writer = ChunkWriter::new(cursor, MagicOrder::Reversed).unwrap();
+
+        writer.begin_chunk(*b"MHDR").unwrap();
+        let slots = writer.reserve_u32s(2).unwrap();
+        let mhdr = writer.end_chunk().unwrap();
+        writer.write_all(&[0xff]).unwrap();
+        writer.align(4).unwrap();
+        assert_eq!(writer.position().unwrap(), 20);
+        assert!(matches!(writer.align(0), Err(WowDataError::ChunkWrite(_))));
+
+        let mcin = writer.write_items_chunk(*b"MCIN", &[7_u32]).unwrap();
+        writer
+            .fill_u32(slots[1], mcin.offset - mhdr.data_offset())
+            .unwrap();
+        writer.write_all(&[0xee]).unwrap();
+
+        let data = writer.finish().unwrap().into_inner();
+        assert_eq!(&data[..14], b"prefixRDHM\x08\0\0\0");
+        assert_eq!(&data[14..22], &[0, 0, 0, 0, 12, 0, 0, 0]);
+        assert_eq!(&data[22..26], &[0xff, 0, 0, 0]);
+        assert_eq!(&data[26..], b"NICM\x04\0\0\0\x07\0\0\0\xee");
+    }
+
+    #[test]
+    fn test_unbalanced() {
+        let mut writer = new_writer(MagicOrder::AsIs);
+        assert!(matches!(
+            writer.end_chunk(),
+            Err(WowDataError::ChunkWrite(_))
+        ));
+        writer.begin_chunk(*b"MVER").unwrap();
+        assert_eq!(writer.depth(), 1);
+        assert!(matches!(writer.finish(), Err(WowDataError::ChunkWrite(_))));
+    }
+
+    #[derive(WowHeaderW)]
+    struct Placement {
+        id: u32,
+        position: C3Vector,
+    }
+
+    #[derive(WowChunkW)]
+    #[wow_data(magic = "MVER")]
+    struct Mver {
+        version: u32,
+    }
+
+    #[derive(WowChunkW)]
+    #[wow_data(magic = "MODF")]
+    struct Modf {
+        placements: Vec<Placement>,
+    }
+
+    #[derive(WowChunkW)]
+    #[wow_data(magic = "MCNK")]
+    struct Mcnk {
+        flags: u32,
+        #[wow_data(chunk)]
+        modf: Option<Modf>,
+    }
+
+    #[derive(WowChunkW)]
+    struct File {
+        #[wow_data(chunk)]
+        mver: Mver,
+        #[wow_data(chunk)]
+        chunks: Vec<Mcnk>,
+    }
+
+    #[test]
+    fn test_derive() {
+        let file = File {
+            mver: Mver { version: 18 },
+            chunks: vec![
+                Mcnk {
+                    flags: 1,
+                    modf: None,
+                },
+                Mcnk {
+                    flags: 2,
+                    modf: Some(Modf {
+                        placements: vec![Placement {
+                            id: 3,
+                            position: C3Vector::new(1.0, 2.0, 3.0),
+                        }],
+                    }),
+                },
+            ],
+        };
+
+        let mut writer = new_writer(MagicOrder::Reversed);
+        writer.write_chunk(&file).unwrap();
+        let data = writer.finish().unwrap().into_inner();
+
+        let mut expected = b"REVM\x04\0\0\0\x12\0\0\0".to_vec();
+        expected.extend(b"KNCM\x04\0\0\0\x01\0\0\0");
+        expected.extend(b"KNCM\x1c\0\0\0\x02\0\0\0");
+        expected.extend(b"FDOM\x10\0\0\0\x03\0\0\0");
+        for value in [1.0_f32, 2.0, 3.0] {
+            expected.extend(value.to_le_bytes());
+        }
+        assert_eq!(data, expected);
+    }
+}
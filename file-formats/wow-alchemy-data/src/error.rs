@@ -13,6 +13,31 @@ pub enum WowDataError {
     #[error("Conversion error: cannot convert from version {from} to {to}: {reason}")]
     ConversionError { from: u32, to: u32, reason: String },
 
+    #[error("Invalid game build: {0}")]
+    InvalidGameBuild(String),
+
+    #[error("Chunk write error: {0}")]
+    ChunkWrite(String),
+
+    #[error("Data out of bounds: {size} bytes at offset {offset} of {len} bytes")]
+    OutOfBounds {
+        offset: usize,
+        size: usize,
+        len: usize,
+    },
+
+    #[error("Array of {len} bytes is not a whole number of {item_size} byte items")]
+    InvalidArrayLength { len: usize, item_size: usize },
+
+    #[error("Data is not aligned to {align} bytes for {type_name}")]
+    Misaligned {
+        type_name: &'static str,
+        align: usize,
+    },
+
+    #[error("Data does not match its header: {0}")]
+    HeaderMismatch(String),
+
     #[error("Generic error: {0}")]
     GenericError(String),
 
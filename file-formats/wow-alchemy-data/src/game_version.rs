@@ -1,9 +1,118 @@
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
 use crate::{
     error::{Result, WowDataError},
     types::DataVersion,
 };
 
+/// Client build as `major.minor.patch.build`, e.g. 3.3.5.12340
+///
+/// This is the one version model of all formats: each crate converts the
+/// [`GameVersion`] of a build into the version of its own format.
+///
+/// Builds parse from full build strings, from patch strings like `3.3.5a`,
+/// which get the build number of known releases, and from expansion short
+/// names like `wotlk`, which give the last release of the expansion.
+///
+/// ```
+/// use wow_alchemy_data::game_version::{GameBuild, GameVersion};
+///
+/// let build: GameBuild = "wotlk".parse().unwrap();
+/// assert_eq!(build, GameBuild::new(3, 3, 5, 12340));
+/// assert_eq!("3.3.5a".parse::<GameBuild>().unwrap(), build);
+/// assert_eq!(build.version().unwrap(), GameVersion::WotLK);
+/// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameBuild {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub build: u32,
+}
+
+impl GameBuild {
+    pub const fn new(major: u32, minor: u32, patch: u32, build: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            build,
+        }
+    }
+
+    /// Expansion of the build
+    pub fn version(&self) -> Result<GameVersion> {
+        GameVersion::from_build(self)
+    }
+}
+
+impl fmt::Display for GameBuild {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.patch, self.build
+        )
+    }
+}
+
+impl FromStr for GameBuild {
+    type Err = WowDataError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(version) = GameVersion::from_short_name(s) {
+            return Ok(version.release_build());
+        }
+
+        let invalid = || WowDataError::InvalidGameBuild(s.to_string());
+        let parts: Vec<&str> = s.trim().split('.').collect();
+        if parts.len() > 4 {
+            return Err(invalid());
+        }
+        let mut values = [0; 4];
+        for (index, part) in parts.iter().enumerate() {
+            // Patches without a build may have a letter suffix, like 3.3.5a
+            let digits = match (index, parts.len()) {
+                (2, 3) => part.trim_end_matches(|c: char| c.is_ascii_alphabetic()),
+                _ => part,
+            };
+            values[index] = digits.parse().map_err(|_| invalid())?;
+        }
+
+        let mut build = Self::new(values[0], values[1], values[2], values[3]);
+        if parts.len() < 4 {
+            if let Ok(version) = build.version() {
+                let release = version.release_build();
+                if (release.major, release.minor, release.patch)
+                    == (build.major, build.minor, build.patch)
+                {
+                    build.build = release.build;
+                }
+            }
+        }
+        Ok(build)
+    }
+}
+
+impl TryFrom<&str> for GameBuild {
+    type Error = WowDataError;
+
+    fn try_from(value: &str) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<GameVersion> for GameBuild {
+    fn from(version: GameVersion) -> Self {
+        version.release_build()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameVersion {
     Vanilla,
     TBC,
@@ -19,21 +128,16 @@ pub enum GameVersion {
 }
 
 impl GameVersion {
-    /// Parse version from a string (e.g., "1.12.1", "3.3.5a", "4.3.4")
+    /// Parse version from a string (e.g., "1.12.1", "3.3.5a", "4.3.4.15595")
     pub fn from_string(s: &str) -> Result<Self> {
-        let parts: Vec<&str> = s.split('.').collect();
-        if parts.is_empty() {
-            return Err(WowDataError::GenericError(format!(
-                "Invalid version string: {s}"
-            )));
-        }
-
-        let major = parts[0].parse::<u32>().map_err(|_| {
-            WowDataError::GenericError(format!("Invalid major version: {}", parts[0]))
-        })?;
+        s.parse::<GameBuild>()?.version()
+    }
 
-        Ok(match major {
-            1 => GameVersion::Vanilla,
+    /// Expansion of a build, by its major version. Alpha and beta builds
+    /// 0.x count as Vanilla.
+    pub fn from_build(build: &GameBuild) -> Result<Self> {
+        Ok(match build.major {
+            0 | 1 => GameVersion::Vanilla,
             2 => GameVersion::TBC,
             3 => GameVersion::WotLK,
             4 => GameVersion::Cataclysm,
@@ -44,9 +148,9 @@ impl GameVersion {
             9 => GameVersion::Shadowlands,
             10 => GameVersion::Dragonflight,
             11 => GameVersion::TheWarWithin,
-            _ => {
-                return Err(WowDataError::GenericError(format!(
-                    "Unknown WoW version: {major}"
+            major => {
+                return Err(WowDataError::InvalidGameBuild(format!(
+                    "unknown major version {major} in {build}"
                 )));
             }
         })
@@ -55,25 +159,65 @@ impl GameVersion {
     /// Parse version from expansion short names or numeric versions
     /// Supports both numeric versions (e.g., "3.3.5a") and short names (e.g., "WotLK", "TBC")
     pub fn from_expansion_name(s: &str) -> Result<Self> {
-        // First try parsing as a short name
-        match s.to_lowercase().as_str() {
-            "vanilla" | "classic" => Ok(GameVersion::Vanilla),
-            "tbc" | "bc" | "burningcrusade" | "burning_crusade" => Ok(GameVersion::TBC),
-            "wotlk" | "wrath" | "lichking" | "lich_king" | "wlk" => Ok(GameVersion::WotLK),
-            "cata" | "cataclysm" => Ok(GameVersion::Cataclysm),
-            "mop" | "pandaria" | "mists" | "mists_of_pandaria" => Ok(GameVersion::MoP),
-            "wod" | "draenor" | "warlords" | "warlords_of_draenor" => Ok(GameVersion::WoD),
-            "legion" => Ok(GameVersion::Legion),
-            "bfa" | "bfazeroth" | "battle_for_azeroth" | "battleforazeroth" => Ok(GameVersion::BfA),
-            "sl" | "shadowlands" => Ok(GameVersion::Shadowlands),
-            "df" | "dragonflight" => Ok(GameVersion::Dragonflight),
-            "tww" | "warwithin" | "the_war_within" | "thewarwithin" => {
-                Ok(GameVersion::TheWarWithin)
-            }
-            _ => {
-                // If it's not a short name, try parsing as a numeric version
-                Self::from_string(s)
+        match Self::from_short_name(s) {
+            Some(version) => Ok(version),
+            None => Self::from_string(s),
+        }
+    }
+
+    fn from_short_name(s: &str) -> Option<Self> {
+        Some(match s.trim().to_lowercase().as_str() {
+            "vanilla" | "classic" => GameVersion::Vanilla,
+            "tbc" | "bc" | "burningcrusade" | "burning_crusade" => GameVersion::TBC,
+            "wotlk" | "wrath" | "lichking" | "lich_king" | "wlk" => GameVersion::WotLK,
+            "cata" | "cataclysm" => GameVersion::Cataclysm,
+            "mop" | "pandaria" | "mists" | "mists_of_pandaria" => GameVersion::MoP,
+            "wod" | "draenor" | "warlords" | "warlords_of_draenor" => GameVersion::WoD,
+            "legion" => GameVersion::Legion,
+            "bfa" | "bfazeroth" | "battle_for_azeroth" | "battleforazeroth" => GameVersion::BfA,
+            "sl" | "shadowlands" => GameVersion::Shadowlands,
+            "df" | "dragonflight" => GameVersion::Dragonflight,
+            "tww" | "warwithin" | "the_war_within" | "thewarwithin" | "latest" => {
+                GameVersion::TheWarWithin
             }
+            _ => return None,
+        })
+    }
+
+    /// Major version of the builds of the expansion
+    pub fn major(&self) -> u32 {
+        *self as u32 + 1
+    }
+
+    /// All builds of the expansion, from the first build of its major
+    /// version up to the first one of the next expansion
+    pub fn builds(&self) -> Range<GameBuild> {
+        let start = match self {
+            Self::Vanilla => 0,
+            _ => self.major(),
+        };
+        GameBuild::new(start, 0, 0, 0)..GameBuild::new(self.major() + 1, 0, 0, 0)
+    }
+
+    /// Whether a build belongs to the expansion
+    pub fn contains(&self, build: &GameBuild) -> bool {
+        self.builds().contains(build)
+    }
+
+    /// Last client release of the expansion
+    pub fn release_build(&self) -> GameBuild {
+        match self {
+            Self::Vanilla => GameBuild::new(1, 12, 1, 5875),
+            Self::TBC => GameBuild::new(2, 4, 3, 8606),
+            Self::WotLK => GameBuild::new(3, 3, 5, 12340),
+            Self::Cataclysm => GameBuild::new(4, 3, 4, 15595),
+            Self::MoP => GameBuild::new(5, 4, 8, 18414),
+            Self::WoD => GameBuild::new(6, 2, 4, 21742),
+            Self::Legion => GameBuild::new(7, 3, 5, 26972),
+            Self::BfA => GameBuild::new(8, 3, 7, 35662),
+            Self::Shadowlands => GameBuild::new(9, 2, 7, 45745),
+            Self::Dragonflight => GameBuild::new(10, 2, 7, 55664),
+            Self::TheWarWithin => GameBuild::new(11, 0, 2, 56421),
         }
     }
 
@@ -104,14 +248,14 @@ impl GameVersion {
             Self::Legion => "7.3.5",
             Self::BfA => "8.3.7",
             Self::Shadowlands => "9.2.7",
-            Self::Dragonflight => "10.2.0",
-            Self::TheWarWithin => "11.0.0",
+            Self::Dragonflight => "10.2.7",
+            Self::TheWarWithin => "11.0.2",
         }
     }
 }
 
-impl std::fmt::Display for GameVersion {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
             "{} ({})",
@@ -121,6 +265,14 @@ impl std::fmt::Display for GameVersion {
     }
 }
 
+impl FromStr for GameVersion {
+    type Err = WowDataError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_expansion_name(s)
+    }
+}
+
 impl DataVersion for GameVersion {}
 
 #[cfg(test)]
@@ -174,4 +326,39 @@ mod tests {
             GameVersion::WotLK
         );
     }
+
+    #[test]
+    fn test_build_from_string() {
+        let parse = |s: &str| s.parse::<GameBuild>().unwrap();
+        assert_eq!(parse("3.3.5.12340"), GameBuild::new(3, 3, 5, 12340));
+        assert_eq!(parse("3.3.5a"), GameBuild::new(3, 3, 5, 12340));
+        assert_eq!(parse("3.3.5"), GameBuild::new(3, 3, 5, 12340));
+        assert_eq!(parse("3.3.3a"), GameBuild::new(3, 3, 3, 0));
+        assert_eq!(parse("4.0.1.13164"), GameBuild::new(4, 0, 1, 13164));
+        assert_eq!(parse("Cata"), GameBuild::new(4, 3, 4, 15595));
+        assert_eq!(parse("7"), GameBuild::new(7, 0, 0, 0));
+        assert_eq!(parse("1.12.1.5875").to_string(), "1.12.1.5875");
+
+        assert!("".parse::<GameBuild>().is_err());
+        assert!("3.3.5.12340a".parse::<GameBuild>().is_err());
+        assert!("3.3a.5".parse::<GameBuild>().is_err());
+        assert!("1.2.3.4.5".parse::<GameBuild>().is_err());
+        assert!(GameVersion::from_string("12.0.0").is_err());
+    }
+
+    #[test]
+    fn test_version_builds() {
+        assert!(GameVersion::Vanilla.contains(&GameBuild::new(0, 5, 3, 3368)));
+        assert!(GameVersion::WotLK.contains(&GameBuild::new(3, 0, 1, 8303)));
+        assert!(!GameVersion::WotLK.contains(&GameBuild::new(4, 0, 0, 0)));
+        for version in [
+            GameVersion::Vanilla,
+            GameVersion::MoP,
+            GameVersion::TheWarWithin,
+        ] {
+            assert!(version.contains(&version.release_build()));
+            assert_eq!(version.release_build().version().unwrap(), version);
+            assert_eq!(GameBuild::from(version), version.release_build());
+        }
+    }
 }
@@ -1,15 +1,21 @@
+pub mod chunk_writer;
 pub mod error;
 pub mod game_version;
+pub mod pod;
+pub mod roundtrip;
+#[cfg(feature = "serde")]
+pub mod serde_array;
 pub mod std_impls;
 pub mod types;
 pub mod utils;
 
 pub mod prelude {
+    pub use crate::pod::{WowArrayView, WowPod};
     pub use crate::types::{
         DataVersion, Read, Seek, VWowChunkR, VWowDataR, VWowHeaderR, VWowReaderForChunk,
-        VWowReaderForData, VWowReaderForHeader, VWowWriterForHeader, WowChunkR, WowDataR,
-        WowHeaderR, WowHeaderW, WowReaderForChunk, WowReaderForData, WowReaderForHeader, WowVec,
-        WowWriterForHeader, Write,
+        VWowReaderForData, VWowReaderForHeader, VWowWriterForHeader, WowChunkR, WowChunkW,
+        WowDataR, WowDataW, WowHeaderR, WowHeaderW, WowReaderForChunk, WowReaderForData,
+        WowReaderForHeader, WowVec, WowWriterForHeader, Write,
     };
     pub use byteorder::{ReadBytesExt, WriteBytesExt};
 }
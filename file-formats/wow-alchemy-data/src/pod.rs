@@ -0,0 +1,349 @@
+//! Zero-copy reading of arrays of plain old data from bytes, like a whole
+//! file in memory or a memory map, without seeking or allocating
+
+use std::marker::PhantomData;
+use std::mem::{align_of, size_of};
+
+use crate::error::{Result, WowDataError};
+use crate::types::{DataVersion, VWowHeaderR, WowArray, WowArrayV, WowHeaderR, WowHeaderW};
+
+/// Types that can be viewed in place in the bytes of a file.
+///
+/// Usually implemented with `#[derive(WowPod)]`, which checks the
+/// requirements below for `#[repr(C)]` structs.
+///
+/// # Safety
+///
+/// The type must be `#[repr(C)]` or `#[repr(transparent)]` with no padding,
+/// every bit pattern must be a valid value, and its in-memory layout on a
+/// little-endian target must be the layout in the file.
+pub unsafe trait WowPod: Copy + 'static {}
+
+macro_rules! impl_wow_pod {
+    ($($ty:ty),*) => {
+        $(unsafe impl WowPod for $ty {})*
+    };
+}
+
+impl_wow_pod!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+
+unsafe impl<T: WowPod, const N: usize> WowPod for [T; N] {}
+
+/// Lazy view of `count` items of `T` in borrowed bytes. Items are read
+/// unaligned, so any offset works; [`WowArrayView::as_slice`] borrows them
+/// directly when the bytes happen to be aligned.
+pub struct WowArrayView<'a, T: WowPod> {
+    bytes: &'a [u8],
+    _phantom: PhantomData<T>,
+}
+
+impl<T: WowPod> Clone for WowArrayView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: WowPod> Copy for WowArrayView<'_, T> {}
+
+impl<T: WowPod> std::fmt::Debug for WowArrayView<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WowArrayView")
+            .field("type", &std::any::type_name::<T>())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<'a, T: WowPod> WowArrayView<'a, T> {
+    /// View `count` items at `offset` in `data`, failing if they don't fit
+    pub fn new(data: &'a [u8], offset: usize, count: usize) -> Result<Self> {
+        const { assert!(size_of::<T>() > 0, "zero-sized types can't be viewed") };
+        if cfg!(target_endian = "big") {
+            return Err(WowDataError::GenericError(
+                "zero-copy views need a little-endian target".into(),
+            ));
+        }
+
+        let size = count.checked_mul(size_of::<T>());
+        let end = size.and_then(|size| offset.checked_add(size));
+        match end {
+            Some(end) if end <= data.len() => Ok(Self {
+                bytes: &data[offset..end],
+                _phantom: PhantomData,
+            }),
+            _ => Err(WowDataError::OutOfBounds {
+                offset,
+                size: size.unwrap_or(usize::MAX),
+                len: data.len(),
+            }),
+        }
+    }
+
+    /// View all of `bytes`, like the data of a chunk, failing if they are
+    /// not a whole number of items
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self> {
+        if bytes.len() % size_of::<T>() != 0 {
+            return Err(WowDataError::InvalidArrayLength {
+                len: bytes.len(),
+                item_size: size_of::<T>(),
+            });
+        }
+        Self::new(bytes, 0, bytes.len() / size_of::<T>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len() / size_of::<T>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        let ptr = self.bytes[index * size_of::<T>()..].as_ptr().cast::<T>();
+        // SAFETY: the bytes of the item are in bounds and any bit pattern is
+        // a valid `T`
+        Some(unsafe { ptr.read_unaligned() })
+    }
+
+    /// Borrow the items directly, or `None` if the bytes are not aligned
+    /// for `T`
+    pub fn as_slice(&self) -> Option<&'a [T]> {
+        if self.bytes.as_ptr().align_offset(align_of::<T>()) != 0 {
+            return None;
+        }
+        // SAFETY: the bytes are aligned, hold `len` items and any bit pattern
+        // is a valid `T`
+        Some(unsafe { std::slice::from_raw_parts(self.bytes.as_ptr().cast(), self.len()) })
+    }
+
+    /// Borrow the items directly, failing if the bytes are not aligned for `T`
+    pub fn try_as_slice(&self) -> Result<&'a [T]> {
+        self.as_slice().ok_or(WowDataError::Misaligned {
+            type_name: std::any::type_name::<T>(),
+            align: align_of::<T>(),
+        })
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn iter(&self) -> WowArrayViewIter<'a, T> {
+        WowArrayViewIter {
+            view: *self,
+            front: 0,
+            back: self.len(),
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        match self.as_slice() {
+            Some(slice) => slice.to_vec(),
+            None => self.iter().collect(),
+        }
+    }
+}
+
+impl<'a, T: WowPod> IntoIterator for WowArrayView<'a, T> {
+    type Item = T;
+    type IntoIter = WowArrayViewIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WowArrayViewIter<'a, T: WowPod> {
+    view: WowArrayView<'a, T>,
+    front: usize,
+    back: usize,
+}
+
+impl<T: WowPod> Iterator for WowArrayViewIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.front >= self.back {
+            return None;
+        }
+        self.front += 1;
+        self.view.get(self.front - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<T: WowPod> DoubleEndedIterator for WowArrayViewIter<'_, T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.front >= self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.view.get(self.back)
+    }
+}
+
+impl<T: WowPod> ExactSizeIterator for WowArrayViewIter<'_, T> {}
+
+impl<T> WowArray<T>
+where
+    T: WowHeaderR + WowHeaderW + WowPod,
+{
+    /// Borrowed counterpart of [`WowArray::wow_read_to_vec`], viewing the
+    /// items in `data`, the bytes the offset is relative to
+    pub fn view<'a>(&self, data: &'a [u8]) -> Result<WowArrayView<'a, T>> {
+        WowArrayView::new(data, self.offset as usize, self.count as usize)
+    }
+
+    /// Borrow the items in `data`, failing if they are not aligned for `T`
+    pub fn slice<'a>(&self, data: &'a [u8]) -> Result<&'a [T]> {
+        self.view(data)?.try_as_slice()
+    }
+}
+
+impl<V, T> WowArrayV<V, T>
+where
+    V: DataVersion,
+    T: VWowHeaderR<V> + WowHeaderW + WowPod,
+{
+    /// Borrowed counterpart of [`WowArrayV::wow_read_to_vec`]. The layout of
+    /// `T` can't depend on the version to be viewed.
+    pub fn view<'a>(&self, data: &'a [u8]) -> Result<WowArrayView<'a, T>> {
+        WowArrayView::new(data, self.offset as usize, self.count as usize)
+    }
+}
+
+/// Map a file into memory to view its bytes without reading it.
+///
+/// The file must not be modified while it's mapped.
+#[cfg(feature = "mmap")]
+pub fn map_file<P: AsRef<std::path::Path>>(path: P) -> Result<memmap2::Mmap> {
+    let file = std::fs::File::open(path)?;
+    // SAFETY: the file is only read, and callers must not modify it while
+    // the map is alive
+    Ok(unsafe { memmap2::Mmap::map(&file)? })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use wow_alchemy_data_derive::{WowHeaderR, WowHeaderW, WowPod};
+
+    use super::*;
+    use crate::prelude::*;
+    use crate::types::{C2Vector, C3Vector};
+
+    mod wow_alchemy_data {
+        pub use crate::*;
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, WowHeaderR, WowHeaderW, WowPod)]
+    #[repr(C)]
+    struct Vertex {
+        position: C3Vector,
+        bone_weights: [u8; 4],
+        tex_coords: C2Vector,
+    }
+
+    fn vertex(i: u8) -> Vertex {
+        Vertex {
+            position: C3Vector::new(i as f32, 1.0, 2.0),
+            bone_weights: [i, 0, 0, 255],
+            tex_coords: C2Vector::new(0.5, i as f32),
+        }
+    }
+
+    #[test]
+    fn test_view_matches_read_to_vec() {
+        let vertices: Vec<Vertex> = (0..4).map(vertex).collect();
+        let mut cursor = Cursor::new(vec![0_u8; 3]);
+        cursor.set_position(3);
+        let array = vertices.wow_write(&mut cursor).unwrap();
+        let data = cursor.into_inner();
+
+        let view = array.view(&data).unwrap();
+        assert_eq!(view.len(), 4);
+        assert_eq!(view.get(2), Some(vertex(2)));
+        assert_eq!(view.get(4), None);
+        assert_eq!(view.to_vec(), vertices);
+        assert_eq!(
+            view.iter().rev().collect::<Vec<_>>(),
+            vertices.iter().rev().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            array.wow_read_to_vec(&mut Cursor::new(&data)).unwrap(),
+            vertices
+        );
+    }
+
+    #[test]
+    fn test_slice_alignment() {
+        let data: Vec<u8> = (0..20).collect();
+        let start = data.as_ptr().align_offset(align_of::<u32>());
+
+        let aligned = WowArrayView::<u32>::new(&data, start, 4).unwrap();
+        let slice = aligned.try_as_slice().unwrap();
+        assert_eq!(slice.len(), 4);
+        assert_eq!(
+            slice[0],
+            u32::from_le_bytes([0, 1, 2, 3].map(|b| b + start as u8))
+        );
+        assert_eq!(slice.to_vec(), aligned.iter().collect::<Vec<_>>());
+
+        let misaligned = WowArrayView::<u32>::new(&data, start + 1, 4).unwrap();
+        assert!(misaligned.as_slice().is_none());
+        assert!(matches!(
+            misaligned.try_as_slice(),
+            Err(WowDataError::Misaligned { align: 4, .. })
+        ));
+        assert_eq!(
+            misaligned.get(0),
+            Some(u32::from_le_bytes([1, 2, 3, 4].map(|b| b + start as u8)))
+        );
+    }
+
+    #[test]
+    fn test_bounds() {
+        let data = [0_u8; 16];
+        assert!(WowArrayView::<u32>::new(&data, 4, 3).is_ok());
+        assert!(matches!(
+            WowArrayView::<u32>::new(&data, 8, 3),
+            Err(WowDataError::OutOfBounds {
+                offset: 8,
+                size: 12,
+                len: 16
+            })
+        ));
+        assert!(matches!(
+            WowArrayView::<u32>::new(&data, usize::MAX, 1),
+            Err(WowDataError::OutOfBounds { .. })
+        ));
+        assert!(matches!(
+            WowArrayView::<u64>::new(&data, 0, usize::MAX),
+            Err(WowDataError::OutOfBounds { .. })
+        ));
+        assert!(WowArray::<u16>::new(9, 0).view(&data).is_err());
+        assert!(matches!(
+            WowArrayView::<C3Vector>::from_bytes(&data),
+            Err(WowDataError::InvalidArrayLength {
+                len: 16,
+                item_size: 12
+            })
+        ));
+        assert_eq!(
+            WowArrayView::<C3Vector>::from_bytes(&data[..12])
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
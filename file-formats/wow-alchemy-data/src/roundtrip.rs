@@ -0,0 +1,171 @@
+//! Comparing the bytes of a file with the bytes it is written back as, to
+//! check that a writer reproduces what its parser read
+
+use std::fmt;
+
+use crate::chunk_writer::{CHUNK_HEADER_SIZE, ChunkSpan, MagicOrder};
+use crate::types::MagicStr;
+use crate::utils::magic_to_string;
+
+/// A top-level chunk of a chunked file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkLocation {
+    /// Magic of the chunk as it reads, e.g. `MVER` also in files that store
+    /// it reversed
+    pub magic: MagicStr,
+    pub span: ChunkSpan,
+}
+
+/// Where the written bytes of a file first differ from the original ones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Offset of the first differing byte, or the length of the shorter
+    /// data if it is a prefix of the other
+    pub offset: usize,
+    pub original_len: usize,
+    pub written_len: usize,
+    /// Chunk of the original file containing the offset, for chunked files
+    pub chunk: Option<ChunkLocation>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "first difference at offset 0x{:x}", self.offset)?;
+        if let Some(chunk) = &self.chunk {
+            write!(
+                f,
+                " in {} chunk at 0x{:x}",
+                magic_to_string(&chunk.magic),
+                chunk.span.offset
+            )?;
+        }
+        if self.original_len != self.written_len {
+            write!(
+                f,
+                " (read {} bytes, wrote {})",
+                self.original_len, self.written_len
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Offset of the first byte that differs between `original` and `written`,
+/// or `None` if they are identical
+pub fn first_difference(original: &[u8], written: &[u8]) -> Option<usize> {
+    match original.iter().zip(written).position(|(a, b)| a != b) {
+        Some(offset) => Some(offset),
+        None if original.len() != written.len() => Some(original.len().min(written.len())),
+        None => None,
+    }
+}
+
+/// The top-level chunk of `data` containing `offset`, walking the chunks
+/// from the start. `None` if `data` stops being a sequence of chunks before
+/// reaching `offset`.
+pub fn find_chunk(data: &[u8], offset: usize, order: MagicOrder) -> Option<ChunkLocation> {
+    let header_size = CHUNK_HEADER_SIZE as usize;
+    let mut start = 0_usize;
+
+    while start + header_size <= data.len() {
+        let mut magic: MagicStr = data[start..start + 4].try_into().ok()?;
+        if order == MagicOrder::Reversed {
+            magic.reverse();
+        }
+        let size = u32::from_le_bytes(data[start + 4..start + 8].try_into().ok()?);
+        let end = start + header_size + size as usize;
+        if end > data.len() {
+            return None;
+        }
+
+        if offset < end {
+            return Some(ChunkLocation {
+                magic,
+                span: ChunkSpan {
+                    offset: start as u32,
+                    size,
+                },
+            });
+        }
+        start = end;
+    }
+
+    None
+}
+
+/// Compare the bytes a file was read from with the bytes it was written as,
+/// locating the difference in a chunk when the file is chunked
+pub fn compare(original: &[u8], written: &[u8], order: Option<MagicOrder>) -> Option<Mismatch> {
+    let offset = first_difference(original, written)?;
+
+    Some(Mismatch {
+        offset,
+        original_len: original.len(),
+        written_len: written.len(),
+        chunk: order.and_then(|order| find_chunk(original, offset, order)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunked() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"REVM\x04\0\0\0\x12\0\0\0");
+        data.extend_from_slice(b"DHPM\x08\0\0\0");
+        data.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        data
+    }
+
+    #[test]
+    fn test_first_difference() {
+        assert_eq!(first_difference(b"abcd", b"abcd"), None);
+        assert_eq!(first_difference(b"abcd", b"abxd"), Some(2));
+        assert_eq!(first_difference(b"abcd", b"ab"), Some(2));
+        assert_eq!(first_difference(b"", b"a"), Some(0));
+    }
+
+    #[test]
+    fn test_find_chunk() {
+        let data = chunked();
+
+        let mver = find_chunk(&data, 11, MagicOrder::Reversed).unwrap();
+        assert_eq!(&mver.magic, b"MVER");
+        assert_eq!((mver.span.offset, mver.span.size), (0, 4));
+
+        let mphd = find_chunk(&data, 12, MagicOrder::Reversed).unwrap();
+        assert_eq!(&mphd.magic, b"MPHD");
+        assert_eq!(mphd.span.offset, 12);
+        assert_eq!(
+            &find_chunk(&data, 12, MagicOrder::AsIs).unwrap().magic,
+            b"DHPM"
+        );
+
+        assert_eq!(find_chunk(&data, data.len(), MagicOrder::Reversed), None);
+        assert_eq!(find_chunk(&data[..20], 15, MagicOrder::Reversed), None);
+    }
+
+    #[test]
+    fn test_compare() {
+        let original = chunked();
+        assert_eq!(compare(&original, &original, None), None);
+
+        let mut written = original.clone();
+        written[22] = 0;
+        let mismatch = compare(&original, &written, Some(MagicOrder::Reversed)).unwrap();
+        assert_eq!(mismatch.offset, 22);
+        assert_eq!(&mismatch.chunk.unwrap().magic, b"MPHD");
+        assert_eq!(
+            mismatch.to_string(),
+            "first difference at offset 0x16 in MPHD chunk at 0xc"
+        );
+
+        let mismatch = compare(&original, &original[..14], None).unwrap();
+        assert_eq!(mismatch.chunk, None);
+        assert_eq!(
+            mismatch.to_string(),
+            "first difference at offset 0xe (read 28 bytes, wrote 14)"
+        );
+    }
+}
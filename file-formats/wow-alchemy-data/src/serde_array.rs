@@ -0,0 +1,50 @@
+//! Serde support for arrays of any length, which serde only implements up to
+//! 32 items, like the 145 heights of an ADT MCVT. Use on fields with
+//! `#[serde(with = "wow_alchemy_data::serde_array")]`.
+
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Serialize, Serializer};
+
+pub fn serialize<S, T, const N: usize>(array: &[T; N], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    serializer.collect_seq(array)
+}
+
+pub fn deserialize<'de, D, T, const N: usize>(deserializer: D) -> Result<[T; N], D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let items = Vec::<T>::deserialize(deserializer)?;
+    let len = items.len();
+    items
+        .try_into()
+        .map_err(|_| D::Error::invalid_length(len, &format!("an array of {N} items").as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Heights {
+        #[serde(with = "super")]
+        heights: [f32; 145],
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let heights = Heights {
+            heights: std::array::from_fn(|i| i as f32 / 2.0),
+        };
+        let json = serde_json::to_string(&heights).unwrap();
+        assert_eq!(serde_json::from_str::<Heights>(&json).unwrap(), heights);
+
+        let short = r#"{"heights": [1.0, 2.0]}"#;
+        let err = serde_json::from_str::<Heights>(short).unwrap_err();
+        assert!(err.to_string().contains("an array of 145 items"));
+    }
+}
@@ -279,7 +279,7 @@ impl WowHeaderW for [f32; 3] {
 
 impl WowHeaderW for String {
     fn wow_write<W: Write>(&self, writer: &mut W) -> Result<()> {
-        writer.wow_write(self)?;
+        writer.write_all(self.as_bytes())?;
         // write null terminator
         writer.wow_write(&0_u8)?;
         Ok(())
@@ -337,3 +337,48 @@ where
         header.wow_read_to_vec(reader)
     }
 }
+
+impl WowDataW<WowCharArray> for String {
+    /// Empty strings are written as an empty array, like they are read
+    fn wow_write_data<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        _header: &WowCharArray,
+    ) -> Result<WowCharArray> {
+        if self.is_empty() {
+            return Ok(WowCharArray::new(0, 0));
+        }
+        self.write_wow_char_array(writer)
+    }
+}
+
+impl<T> WowDataW<WowArray<T>> for Vec<T>
+where
+    T: WowHeaderR + WowHeaderW,
+{
+    fn wow_write_data<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        _header: &WowArray<T>,
+    ) -> Result<WowArray<T>> {
+        WowVec::wow_write(self, writer)
+    }
+}
+
+impl<V, T> WowDataW<WowArrayV<V, T>> for Vec<T>
+where
+    V: DataVersion,
+    T: VWowHeaderR<V> + WowHeaderW,
+{
+    fn wow_write_data<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        _header: &WowArrayV<V, T>,
+    ) -> Result<WowArrayV<V, T>> {
+        let offset = writer.stream_position()?;
+        for item in self {
+            writer.wow_write(item)?;
+        }
+        Ok(WowArrayV::new(self.len() as u32, offset as u32))
+    }
+}
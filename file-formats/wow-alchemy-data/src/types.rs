@@ -1,7 +1,8 @@
 use custom_debug::Debug;
-use wow_alchemy_data_derive::{WowHeaderR, WowHeaderW};
+use wow_alchemy_data_derive::{WowHeaderR, WowHeaderW, WowPod};
 
 use crate::{
+    chunk_writer::ChunkWriter,
     error::{Result, WowDataError},
     v_read_chunk_items,
 };
@@ -161,6 +162,13 @@ where
 {
 }
 
+/// Counterpart of [`WowDataR`] and [`VWowDataR`]: writes the data that a
+/// header points to at the current position of `writer`, and returns the
+/// header with the offsets and counts of the written data
+pub trait WowDataW<T: WowHeaderW> {
+    fn wow_write_data<W: Write + Seek>(&self, writer: &mut W, header: &T) -> Result<T>;
+}
+
 pub trait WowStructR: Sized {
     fn wow_read<R: Read + Seek>(reader: &mut R) -> Result<Self>;
 }
@@ -232,7 +240,38 @@ where
 {
 }
 
+/// Chunk, or group of chunks, that writes itself with a [`ChunkWriter`],
+/// usually derived with `#[derive(WowChunkW)]`
+pub trait WowChunkW {
+    fn wow_write_chunk<W: Write + Seek>(&self, writer: &mut ChunkWriter<W>) -> Result<()>;
+}
+
+impl<T: WowChunkW> WowChunkW for Option<T> {
+    fn wow_write_chunk<W: Write + Seek>(&self, writer: &mut ChunkWriter<W>) -> Result<()> {
+        match self {
+            Some(chunk) => chunk.wow_write_chunk(writer),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: WowChunkW> WowChunkW for [T] {
+    fn wow_write_chunk<W: Write + Seek>(&self, writer: &mut ChunkWriter<W>) -> Result<()> {
+        for chunk in self {
+            chunk.wow_write_chunk(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: WowChunkW> WowChunkW for Vec<T> {
+    fn wow_write_chunk<W: Write + Seek>(&self, writer: &mut ChunkWriter<W>) -> Result<()> {
+        self.as_slice().wow_write_chunk(writer)
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VersionedChunk<V: DataVersion, T> {
     pub version: V,
     pub items: Vec<T>,
@@ -350,6 +389,7 @@ where
 }
 
 #[derive(Debug, Default, PartialEq, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WowArray<T>
 where
     T: WowHeaderR + WowHeaderW,
@@ -357,6 +397,7 @@ where
     pub count: u32,
     pub offset: u32,
     #[wow_data(override_read = std::marker::PhantomData)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     _phantom: std::marker::PhantomData<T>,
 }
 
@@ -459,6 +500,7 @@ pub trait WowString {
 }
 
 #[derive(Debug, PartialEq, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WowArrayV<V, T>
 where
     V: DataVersion,
@@ -468,8 +510,10 @@ where
     pub offset: u32,
 
     #[wow_data(override_read = std::marker::PhantomData)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     _phantom: std::marker::PhantomData<T>,
     #[wow_data(override_read = std::marker::PhantomData)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     _version: std::marker::PhantomData<V>,
 }
 
@@ -592,6 +636,15 @@ where
     V: DataVersion,
     T: VWowHeaderR<V> + WowHeaderW,
 {
+    pub fn new(count: u32, offset: u32) -> Self {
+        Self {
+            count,
+            offset,
+            _phantom: std::marker::PhantomData,
+            _version: std::marker::PhantomData,
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.count == 0
     }
@@ -630,7 +683,9 @@ pub trait WowVec<T: WowHeaderR + WowHeaderW> {
     fn wow_write<W: Write + Seek>(&self, writer: &mut W) -> Result<WowArray<T>>;
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Default, WowHeaderR, WowHeaderW)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, WowHeaderR, WowHeaderW, WowPod)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct C4Vector {
     pub x: f32,
     pub y: f32,
@@ -666,7 +721,9 @@ impl C4Vector {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Default, WowHeaderR, WowHeaderW)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, WowHeaderR, WowHeaderW, WowPod)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct C3Vector {
     pub x: f32,
     pub y: f32,
@@ -699,7 +756,9 @@ impl C3Vector {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Default, WowHeaderR, WowHeaderW)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, WowHeaderR, WowHeaderW, WowPod)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct C2Vector {
     pub x: f32,
     pub y: f32,
@@ -720,6 +779,7 @@ impl C2Vector {
 }
 
 #[derive(Debug, Clone, Default, PartialEq, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BoundingBox {
     pub min: C3Vector,
     pub max: C3Vector,
@@ -735,7 +795,9 @@ impl BoundingBox {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, WowHeaderR, WowHeaderW)]
+#[derive(Debug, Clone, Copy, PartialEq, WowHeaderR, WowHeaderW, WowPod)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct Quaternion {
     pub x: f32,
     pub y: f32,
@@ -774,7 +836,9 @@ impl From<Quaternion16> for Quaternion {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, WowHeaderR, WowHeaderW)]
+#[derive(Debug, Clone, Copy, PartialEq, WowHeaderR, WowHeaderW, WowPod)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct Quaternion16 {
     pub x: i16,
     pub y: i16,
@@ -782,14 +846,18 @@ pub struct Quaternion16 {
     pub w: i16,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, WowHeaderR, WowHeaderW)]
+#[derive(Debug, Clone, Copy, PartialEq, WowHeaderR, WowHeaderW, WowPod)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct Color {
     pub r: f32,
     pub g: f32,
     pub b: f32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, WowHeaderR, WowHeaderW)]
+#[derive(Debug, Clone, Copy, PartialEq, WowHeaderR, WowHeaderW, WowPod)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct ColorA {
     pub r: f32,
     pub g: f32,
@@ -834,6 +902,7 @@ impl WowHeaderW for [ColorA; 3] {
 }
 
 #[derive(Debug, Clone, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VectorFp6_9 {
     pub x: u16,
     pub y: u16,
@@ -857,6 +926,7 @@ impl WowHeaderW for [VectorFp6_9; 2] {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mat3x4 {
     pub items: [C4Vector; 3],
 }
@@ -887,6 +957,7 @@ impl WowHeaderW for Mat3x4 {
 }
 
 #[derive(Debug, Clone, Default, WowHeaderR, WowHeaderW)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChunkHeader {
     pub magic: MagicStr,
     pub bytes: u32,
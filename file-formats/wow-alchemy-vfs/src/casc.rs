@@ -0,0 +1,54 @@
+//! Local CASC storages of installations from Cataclysm onwards
+
+use crate::error::{Result, VfsError};
+use crate::provider::WowFileProvider;
+use wow_alchemy_casc::{CascError, CascStorage};
+
+impl WowFileProvider for CascStorage {
+    /// Read the file at `path`, found by the name hashes of the root or
+    /// the names of the loaded listfile
+    fn read(&self, path: &str) -> Result<Vec<u8>> {
+        let file_data_id = self
+            .file_data_id(path)
+            .ok_or_else(|| VfsError::NotFound(path.to_string()))?;
+        self.read_by_id(file_data_id).map_err(|error| match error {
+            CascError::FileNotFound(_) => VfsError::NotFound(path.to_string()),
+            error => archive_error(error),
+        })
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.file_data_id(path)
+            .is_some_and(|file_data_id| self.contains(file_data_id))
+    }
+
+    /// Paths of the files named by the loaded listfile, as the root only
+    /// has name hashes
+    fn list(&self) -> Result<Vec<String>> {
+        let mut paths = self
+            .file_data_ids()
+            .filter_map(|file_data_id| self.file_name(file_data_id))
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn read_id(&self, file_data_id: u32) -> Result<Vec<u8>> {
+        self.read_by_id(file_data_id).map_err(|error| match error {
+            CascError::FileNotFound(_) => VfsError::IdNotFound(file_data_id),
+            error => archive_error(error),
+        })
+    }
+
+    fn exists_id(&self, file_data_id: u32) -> bool {
+        self.contains(file_data_id)
+    }
+}
+
+fn archive_error(error: CascError) -> VfsError {
+    match error {
+        CascError::Io(error) => VfsError::Io(error),
+        error => VfsError::Archive(error.to_string()),
+    }
+}
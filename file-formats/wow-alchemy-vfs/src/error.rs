@@ -0,0 +1,31 @@
+//! Error handling for file providers
+
+use std::io;
+use thiserror::Error;
+
+/// Errors that can occur when reading files from a provider
+#[derive(Debug, Error)]
+pub enum VfsError {
+    /// An I/O error occurred
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    /// No file has the path
+    #[error("File not found: {0}")]
+    NotFound(String),
+
+    /// No file has the FileDataID, or the provider doesn't know FileDataIDs
+    #[error("FileDataID not found: {0}")]
+    IdNotFound(u32),
+
+    /// A line of a listfile isn't `id;path`
+    #[error("Invalid listfile line {line}: {reason}")]
+    InvalidListfile { line: usize, reason: String },
+
+    /// The archive backing the provider failed to read the file
+    #[error("Archive error: {0}")]
+    Archive(String),
+}
+
+/// Result type for file provider operations
+pub type Result<T> = std::result::Result<T, VfsError>;
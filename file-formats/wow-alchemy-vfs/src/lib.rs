@@ -0,0 +1,29 @@
+#![doc = include_str!("../README.md")]
+#![forbid(unsafe_code)]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+pub mod error;
+pub mod loose;
+pub mod memory;
+pub mod overlay;
+pub mod path;
+pub mod provider;
+pub mod resolver;
+
+#[cfg(feature = "casc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "casc")))]
+pub mod casc;
+#[cfg(feature = "mpq")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mpq")))]
+pub mod mpq;
+
+// Re-export primary types
+pub use error::{Result, VfsError};
+pub use loose::LooseProvider;
+pub use memory::MemoryProvider;
+#[cfg(feature = "mpq")]
+pub use mpq::MpqProvider;
+pub use overlay::OverlayProvider;
+pub use path::normalize_path;
+pub use provider::WowFileProvider;
+pub use resolver::FileIdResolver;
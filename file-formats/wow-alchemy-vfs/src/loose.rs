@@ -0,0 +1,137 @@
+//! Loose files in a directory, such as an extracted client
+
+use crate::error::{Result, VfsError};
+use crate::path::normalize_path;
+use crate::provider::WowFileProvider;
+use std::path::{Component, Path, PathBuf};
+
+/// Files in a directory
+///
+/// Paths are matched case-insensitively, as in the game, even on case
+/// sensitive file systems.
+#[derive(Debug, Clone)]
+pub struct LooseProvider {
+    root: PathBuf,
+}
+
+impl LooseProvider {
+    /// Provide the files under `root`
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The directory of the files
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The file system path of the file at game path `path`, if it exists.
+    /// Paths that leave the directory, with `..` or a drive, resolve to
+    /// nothing.
+    pub fn resolve(&self, path: &str) -> Option<PathBuf> {
+        let normalized = path.replace('\\', "/");
+        let relative = Path::new(normalized.trim_start_matches('/'));
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return None;
+        }
+        let exact = self.root.join(relative);
+        if exact.is_file() {
+            return Some(exact);
+        }
+
+        let mut resolved = self.root.clone();
+        for component in normalize_path(path).split('/').filter(|c| !c.is_empty()) {
+            let entry = std::fs::read_dir(&resolved)
+                .ok()?
+                .filter_map(|entry| entry.ok())
+                .find(|entry| {
+                    entry
+                        .file_name()
+                        .to_str()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(component))
+                })?;
+            resolved = entry.path();
+        }
+        resolved.is_file().then_some(resolved)
+    }
+}
+
+impl WowFileProvider for LooseProvider {
+    fn read(&self, path: &str) -> Result<Vec<u8>> {
+        let resolved = self
+            .resolve(path)
+            .ok_or_else(|| VfsError::NotFound(path.to_string()))?;
+        Ok(std::fs::read(resolved)?)
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.resolve(path).is_some()
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        let mut paths = Vec::new();
+        let mut pending = vec![self.root.clone()];
+        while let Some(dir) = pending.pop() {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if let Ok(relative) = path.strip_prefix(&self.root) {
+                    paths.push(relative.to_string_lossy().replace('\\', "/"));
+                }
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loose() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("World/wmo/Dungeon")).unwrap();
+        std::fs::write(dir.path().join("World/wmo/Dungeon/Crypt.wmo"), b"root").unwrap();
+        std::fs::write(dir.path().join("World/wmo/Dungeon/Crypt_000.wmo"), b"group").unwrap();
+
+        let provider = LooseProvider::new(dir.path());
+        assert_eq!(
+            provider.read("World/wmo/Dungeon/Crypt.wmo").unwrap(),
+            b"root"
+        );
+        assert_eq!(
+            provider.read("WORLD\\WMO\\DUNGEON\\CRYPT_000.WMO").unwrap(),
+            b"group"
+        );
+        assert!(provider.exists("world/wmo/dungeon/crypt.wmo"));
+        assert!(!provider.exists("world/wmo/dungeon/crypt_001.wmo"));
+        assert!(!provider.exists("world/wmo/dungeon"));
+        assert!(matches!(
+            provider.read("world/wmo/dungeon/crypt_001.wmo"),
+            Err(VfsError::NotFound(_))
+        ));
+        assert!(matches!(provider.read_id(1), Err(VfsError::IdNotFound(1))));
+        assert_eq!(
+            provider.list().unwrap(),
+            vec![
+                "World/wmo/Dungeon/Crypt.wmo",
+                "World/wmo/Dungeon/Crypt_000.wmo"
+            ]
+        );
+
+        // Nothing outside of the directory
+        std::fs::write(dir.path().join("World/secret.txt"), b"secret").unwrap();
+        let provider = LooseProvider::new(dir.path().join("World/wmo"));
+        assert!(provider.exists("Dungeon/Crypt.wmo"));
+        assert!(!provider.exists("../secret.txt"));
+        assert!(!provider.exists("Dungeon\\..\\..\\secret.txt"));
+        let absolute = dir.path().join("World/secret.txt");
+        assert!(!provider.exists(&absolute.to_string_lossy()));
+    }
+}
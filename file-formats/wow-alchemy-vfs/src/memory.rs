@@ -0,0 +1,108 @@
+//! Files held in memory, for tests and generated content
+
+use crate::error::{Result, VfsError};
+use crate::path::normalize_path;
+use crate::provider::WowFileProvider;
+use std::collections::{BTreeMap, HashMap};
+
+/// Files in memory, by path and optionally by FileDataID
+#[derive(Debug, Clone, Default)]
+pub struct MemoryProvider {
+    files: BTreeMap<String, Vec<u8>>,
+    ids: HashMap<u32, String>,
+}
+
+impl MemoryProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace the file at `path`
+    pub fn insert(&mut self, path: &str, data: impl Into<Vec<u8>>) {
+        self.files.insert(normalize_path(path), data.into());
+    }
+
+    /// Add or replace the file at `path`, which also has FileDataID
+    /// `file_data_id`
+    pub fn insert_with_id(&mut self, file_data_id: u32, path: &str, data: impl Into<Vec<u8>>) {
+        self.insert(path, data);
+        self.ids.insert(file_data_id, normalize_path(path));
+    }
+
+    /// Add the file at `path`, builder style
+    pub fn with(mut self, path: &str, data: impl Into<Vec<u8>>) -> Self {
+        self.insert(path, data);
+        self
+    }
+
+    /// Remove the file at `path`
+    pub fn remove(&mut self, path: &str) -> Option<Vec<u8>> {
+        let path = normalize_path(path);
+        self.ids.retain(|_, id_path| *id_path != path);
+        self.files.remove(&path)
+    }
+
+    /// Number of files
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether there are no files
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+impl WowFileProvider for MemoryProvider {
+    fn read(&self, path: &str) -> Result<Vec<u8>> {
+        self.files
+            .get(&normalize_path(path))
+            .cloned()
+            .ok_or_else(|| VfsError::NotFound(path.to_string()))
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.files.contains_key(&normalize_path(path))
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        Ok(self.files.keys().cloned().collect())
+    }
+
+    fn read_id(&self, file_data_id: u32) -> Result<Vec<u8>> {
+        self.ids
+            .get(&file_data_id)
+            .and_then(|path| self.files.get(path))
+            .cloned()
+            .ok_or(VfsError::IdNotFound(file_data_id))
+    }
+
+    fn exists_id(&self, file_data_id: u32) -> bool {
+        self.ids.contains_key(&file_data_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory() {
+        let mut provider = MemoryProvider::new().with("Creature\\Murloc\\Murloc.m2", "MD20");
+        provider.insert_with_id(124_983, "creature/murloc/murloc00.skin", "SKIN");
+        assert_eq!(provider.read("creature/murloc/MURLOC.M2").unwrap(), b"MD20");
+        assert_eq!(provider.read_id(124_983).unwrap(), b"SKIN");
+        assert_eq!(
+            provider
+                .read_either(0, "creature/murloc/murloc.m2")
+                .unwrap(),
+            b"MD20"
+        );
+        assert!(provider.exists_id(124_983));
+        assert_eq!(provider.len(), 2);
+
+        provider.remove("creature/murloc/murloc00.skin");
+        assert!(!provider.exists_id(124_983));
+        assert_eq!(provider.list().unwrap(), vec!["creature/murloc/murloc.m2"]);
+    }
+}
@@ -0,0 +1,124 @@
+//! MPQ archives of clients up to Mists of Pandaria
+
+use crate::error::{Result, VfsError};
+use crate::provider::WowFileProvider;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use std::sync::Mutex;
+use wow_alchemy_mpq::{Archive, MpqError, PatchChain};
+
+/// The files of a chain of MPQ archives
+///
+/// Reading needs exclusive access to the archives, which the provider
+/// serializes so it can be shared.
+#[derive(Debug)]
+pub struct MpqProvider<R = BufReader<File>> {
+    chain: Mutex<PatchChain<R>>,
+}
+
+impl MpqProvider {
+    /// Open the archives of a client data directory in load order
+    pub fn open_client<P: AsRef<Path>>(data_dir: P, locale: &str) -> Result<Self> {
+        Ok(Self::new(
+            PatchChain::open_client(data_dir, locale).map_err(archive_error)?,
+        ))
+    }
+
+    /// Open a single archive
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut chain = PatchChain::new();
+        chain.push(Archive::open(path).map_err(archive_error)?);
+        Ok(Self::new(chain))
+    }
+}
+
+impl<R: Read + Seek> MpqProvider<R> {
+    /// Provide the files of `chain`
+    pub fn new(chain: PatchChain<R>) -> Self {
+        Self {
+            chain: Mutex::new(chain),
+        }
+    }
+
+    /// The chain of archives
+    pub fn into_inner(self) -> PatchChain<R> {
+        self.chain
+            .into_inner()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn chain(&self) -> std::sync::MutexGuard<'_, PatchChain<R>> {
+        self.chain.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+impl<R: Read + Seek> WowFileProvider for MpqProvider<R> {
+    fn read(&self, path: &str) -> Result<Vec<u8>> {
+        let name = mpq_name(path);
+        self.chain().read_file(&name).map_err(|error| match error {
+            MpqError::FileNotFound(_) => VfsError::NotFound(path.to_string()),
+            error => archive_error(error),
+        })
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.chain().contains(&mpq_name(path))
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        self.chain().list_files().map_err(archive_error)
+    }
+}
+
+/// Archives store names with backslashes
+fn mpq_name(path: &str) -> String {
+    path.replace('/', "\\")
+}
+
+fn archive_error(error: MpqError) -> VfsError {
+    match error {
+        MpqError::Io(error) => VfsError::Io(error),
+        error => VfsError::Archive(error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wow_alchemy_mpq::{ArchiveWriter, FormatVersion};
+
+    #[test]
+    fn test_mpq() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.MPQ");
+        let patch = dir.path().join("patch.MPQ");
+        let mut writer = ArchiveWriter::new(FormatVersion::V1);
+        writer.add("Textures\\Base.blp", "base");
+        writer.add("Textures\\Replaced.blp", "old");
+        writer.save(&base).unwrap();
+        let mut writer = ArchiveWriter::new(FormatVersion::V1);
+        writer.add("Textures\\Replaced.blp", "new");
+        writer.save(&patch).unwrap();
+
+        let provider = MpqProvider::new(PatchChain::open([&base, &patch]).unwrap());
+        assert_eq!(provider.read("textures/base.blp").unwrap(), b"base");
+        assert_eq!(provider.read("TEXTURES/REPLACED.BLP").unwrap(), b"new");
+        assert!(provider.exists("textures/base.blp"));
+        assert!(matches!(
+            provider.read("textures/missing.blp"),
+            Err(VfsError::NotFound(_))
+        ));
+        assert_eq!(
+            provider.list().unwrap(),
+            vec!["Textures\\Base.blp", "Textures\\Replaced.blp"]
+        );
+        assert_eq!(
+            MpqProvider::open(&base)
+                .unwrap()
+                .read("textures/replaced.blp")
+                .unwrap(),
+            b"old"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! A stack of providers, such as modified files over the game archives
+
+use crate::error::{Result, VfsError};
+use crate::path::normalize_path;
+use crate::provider::WowFileProvider;
+use std::collections::BTreeMap;
+
+/// Providers in priority order: a file is read from the last layer that
+/// has it, like the archives of a patch chain
+#[derive(Default)]
+pub struct OverlayProvider {
+    layers: Vec<Box<dyn WowFileProvider>>,
+}
+
+impl OverlayProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a layer that takes precedence over the previous ones
+    pub fn push<P: WowFileProvider + 'static>(&mut self, layer: P) {
+        self.layers.push(Box::new(layer));
+    }
+
+    /// Add a layer, builder style
+    pub fn with<P: WowFileProvider + 'static>(mut self, layer: P) -> Self {
+        self.push(layer);
+        self
+    }
+
+    /// Number of layers
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Whether there are no layers
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+}
+
+impl std::fmt::Debug for OverlayProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OverlayProvider")
+            .field("layers", &self.layers.len())
+            .finish()
+    }
+}
+
+impl WowFileProvider for OverlayProvider {
+    fn read(&self, path: &str) -> Result<Vec<u8>> {
+        self.layers
+            .iter()
+            .rev()
+            .find(|layer| layer.exists(path))
+            .ok_or_else(|| VfsError::NotFound(path.to_string()))?
+            .read(path)
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.layers.iter().any(|layer| layer.exists(path))
+    }
+
+    /// Paths of all layers, the spelling of the topmost layer winning
+    fn list(&self) -> Result<Vec<String>> {
+        let mut paths = BTreeMap::new();
+        for layer in self.layers.iter().rev() {
+            for path in layer.list()? {
+                paths.entry(normalize_path(&path)).or_insert(path);
+            }
+        }
+        Ok(paths.into_values().collect())
+    }
+
+    fn read_id(&self, file_data_id: u32) -> Result<Vec<u8>> {
+        self.layers
+            .iter()
+            .rev()
+            .find(|layer| layer.exists_id(file_data_id))
+            .ok_or(VfsError::IdNotFound(file_data_id))?
+            .read_id(file_data_id)
+    }
+
+    fn exists_id(&self, file_data_id: u32) -> bool {
+        self.layers
+            .iter()
+            .any(|layer| layer.exists_id(file_data_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryProvider;
+
+    #[test]
+    fn test_overlay() {
+        let mut base = MemoryProvider::new()
+            .with("a.blp", "base a")
+            .with("b.blp", "base b");
+        base.insert_with_id(7, "c.blp", "base c");
+        let patch = MemoryProvider::new().with("A.BLP", "patched a");
+        let overlay = OverlayProvider::new().with(base).with(patch);
+
+        assert_eq!(overlay.len(), 2);
+        assert_eq!(overlay.read("a.blp").unwrap(), b"patched a");
+        assert_eq!(overlay.read("b.blp").unwrap(), b"base b");
+        assert_eq!(overlay.read_id(7).unwrap(), b"base c");
+        assert!(overlay.read("d.blp").is_err());
+        assert!(overlay.read_id(8).is_err());
+        assert_eq!(overlay.list().unwrap(), vec!["a.blp", "b.blp", "c.blp"]);
+    }
+}
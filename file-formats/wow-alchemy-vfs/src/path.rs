@@ -0,0 +1,39 @@
+//! Game paths, which are case-insensitive and may use either slash
+
+/// The canonical form of a game path: lower-case, with `/` separators and
+/// without leading separators
+pub fn normalize_path(path: &str) -> String {
+    path.trim_start_matches(['/', '\\'])
+        .chars()
+        .map(|c| match c {
+            '\\' => '/',
+            _ => c.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+/// The path without its extension, e.g. `world/wmo/tree` for
+/// `world/wmo/tree.wmo`
+pub fn strip_extension(path: &str) -> &str {
+    let name_start = path.rfind(['/', '\\']).map_or(0, |i| i + 1);
+    match path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => &path[..name_start + dot],
+        _ => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths() {
+        assert_eq!(
+            normalize_path("\\World\\Maps\\Azeroth\\Azeroth.WDT"),
+            "world/maps/azeroth/azeroth.wdt"
+        );
+        assert_eq!(strip_extension("World\\wmo\\Tree.wmo"), "World\\wmo\\Tree");
+        assert_eq!(strip_extension("dir.d/file"), "dir.d/file");
+        assert_eq!(strip_extension(".hidden"), ".hidden");
+    }
+}
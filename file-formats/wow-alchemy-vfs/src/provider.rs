@@ -0,0 +1,79 @@
+//! The file provider trait
+
+use crate::error::{Result, VfsError};
+use std::io::Cursor;
+use std::sync::Arc;
+
+/// A source of game files, addressed by path and, where the source knows
+/// them, by FileDataID
+///
+/// Paths are game paths: case-insensitive, relative to the game data root
+/// and separated by either slash.
+pub trait WowFileProvider {
+    /// Read the file at `path`
+    fn read(&self, path: &str) -> Result<Vec<u8>>;
+
+    /// Whether there is a file at `path`
+    fn exists(&self, path: &str) -> bool;
+
+    /// Paths of all files the provider knows
+    fn list(&self) -> Result<Vec<String>>;
+
+    /// Read the file `file_data_id`
+    fn read_id(&self, file_data_id: u32) -> Result<Vec<u8>> {
+        Err(VfsError::IdNotFound(file_data_id))
+    }
+
+    /// Whether there is a file `file_data_id`
+    fn exists_id(&self, _file_data_id: u32) -> bool {
+        false
+    }
+
+    /// Open the file at `path` for the `Read + Seek` parsers
+    fn open(&self, path: &str) -> Result<Cursor<Vec<u8>>> {
+        self.read(path).map(Cursor::new)
+    }
+
+    /// Open the file `file_data_id` for the `Read + Seek` parsers
+    fn open_id(&self, file_data_id: u32) -> Result<Cursor<Vec<u8>>> {
+        self.read_id(file_data_id).map(Cursor::new)
+    }
+
+    /// Read the file `file_data_id` if it's nonzero, else the one at `path`,
+    /// for references that have both forms depending on the version
+    fn read_either(&self, file_data_id: u32, path: &str) -> Result<Vec<u8>> {
+        if file_data_id != 0 {
+            self.read_id(file_data_id)
+        } else {
+            self.read(path)
+        }
+    }
+}
+
+macro_rules! forward_provider {
+    ($($ty:ty),*) => {$(
+        impl<T: WowFileProvider + ?Sized> WowFileProvider for $ty {
+            fn read(&self, path: &str) -> Result<Vec<u8>> {
+                (**self).read(path)
+            }
+
+            fn exists(&self, path: &str) -> bool {
+                (**self).exists(path)
+            }
+
+            fn list(&self) -> Result<Vec<String>> {
+                (**self).list()
+            }
+
+            fn read_id(&self, file_data_id: u32) -> Result<Vec<u8>> {
+                (**self).read_id(file_data_id)
+            }
+
+            fn exists_id(&self, file_data_id: u32) -> bool {
+                (**self).exists_id(file_data_id)
+            }
+        }
+    )*};
+}
+
+forward_provider!(&T, Box<T>, Arc<T>);
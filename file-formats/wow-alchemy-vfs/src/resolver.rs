@@ -0,0 +1,222 @@
+//! Mapping between FileDataIDs and game paths from a listfile
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::error::{Result, VfsError};
+use crate::path::normalize_path;
+
+/// Bidirectional map between FileDataIDs and game paths, usually loaded
+/// from a community listfile with one `id;path` line per file
+///
+/// Paths are stored normalized (see [`normalize_path`]) in one string
+/// buffer, so that the millions of entries of a retail listfile stay
+/// compact, and are looked up regardless of case and slashes.
+///
+/// ```
+/// use wow_alchemy_vfs::FileIdResolver;
+///
+/// let resolver = FileIdResolver::parse("189077;World\\Generic\\passivedoodads\\tree.m2\n").unwrap();
+/// assert_eq!(resolver.path(189077), Some("world/generic/passivedoodads/tree.m2"));
+/// assert_eq!(resolver.file_id("WORLD/GENERIC/PassiveDoodads/Tree.m2"), Some(189077));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FileIdResolver {
+    /// Normalized paths concatenated in FileDataID order
+    names: String,
+    /// Sorted FileDataIDs
+    ids: Vec<u32>,
+    /// End of the path of each id in `names`, where the next one starts
+    ends: Vec<u32>,
+    /// Indices into `ids` ordered by path
+    by_path: Vec<u32>,
+}
+
+impl FileIdResolver {
+    /// Build a resolver from pairs of FileDataID and path. When an id is
+    /// given more than once, the last path wins.
+    pub fn from_entries<I, S>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = (u32, S)>,
+        S: AsRef<str>,
+    {
+        let mut entries: Vec<(u32, String)> = entries
+            .into_iter()
+            .map(|(id, path)| (id, normalize_path(path.as_ref())))
+            .collect();
+        // Stable sort keeps the input order of duplicates, so the last one
+        // of each id is kept
+        entries.sort_by_key(|(id, _)| *id);
+        entries.reverse();
+        entries.dedup_by_key(|(id, _)| *id);
+        entries.reverse();
+
+        let mut resolver = Self {
+            names: String::with_capacity(entries.iter().map(|(_, path)| path.len()).sum()),
+            ids: Vec::with_capacity(entries.len()),
+            ends: Vec::with_capacity(entries.len()),
+            by_path: Vec::new(),
+        };
+        for (id, path) in &entries {
+            resolver.names.push_str(path);
+            resolver.ids.push(*id);
+            resolver.ends.push(resolver.names.len() as u32);
+        }
+        let mut by_path: Vec<u32> = (0..entries.len() as u32).collect();
+        by_path.sort_by(|&a, &b| {
+            resolver
+                .entry_path(a as usize)
+                .cmp(resolver.entry_path(b as usize))
+        });
+        resolver.by_path = by_path;
+        resolver
+    }
+
+    /// Parse listfile text with `id;path` lines. Empty lines are skipped.
+    pub fn parse(text: &str) -> Result<Self> {
+        Self::read(text.as_bytes())
+    }
+
+    /// Read a listfile with `id;path` lines
+    pub fn read<R: BufRead>(reader: R) -> Result<Self> {
+        let mut entries = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid = |reason: &str| VfsError::InvalidListfile {
+                line: index + 1,
+                reason: reason.to_string(),
+            };
+            let (id, path) = line
+                .split_once(';')
+                .ok_or_else(|| invalid("expected `id;path`"))?;
+            let id = id
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| invalid("invalid FileDataID"))?;
+            entries.push((id, path.to_string()));
+        }
+        Ok(Self::from_entries(entries))
+    }
+
+    /// Read a listfile from the file system
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Number of FileDataIDs with a path
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Whether the resolver has no entries
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Normalized path of a FileDataID
+    pub fn path(&self, file_data_id: u32) -> Option<&str> {
+        let index = self.ids.binary_search(&file_data_id).ok()?;
+        Some(self.entry_path(index))
+    }
+
+    /// FileDataID of a path, in any case and with either slash. When
+    /// several ids share the path, the lowest one is returned.
+    pub fn file_id(&self, path: &str) -> Option<u32> {
+        let path = normalize_path(path);
+        let position = self
+            .by_path
+            .partition_point(|&index| self.entry_path(index as usize) < path.as_str());
+        let index = *self.by_path.get(position)? as usize;
+        (self.entry_path(index) == path).then(|| self.ids[index])
+    }
+
+    /// Like [`FileIdResolver::path`], failing with
+    /// [`VfsError::IdNotFound`] for unknown ids
+    pub fn require_path(&self, file_data_id: u32) -> Result<&str> {
+        self.path(file_data_id)
+            .ok_or(VfsError::IdNotFound(file_data_id))
+    }
+
+    /// Like [`FileIdResolver::file_id`], failing with
+    /// [`VfsError::NotFound`] for unknown paths
+    pub fn require_file_id(&self, path: &str) -> Result<u32> {
+        self.file_id(path)
+            .ok_or_else(|| VfsError::NotFound(path.to_string()))
+    }
+
+    /// All entries in FileDataID order
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> + '_ {
+        (0..self.ids.len()).map(|index| (self.ids[index], self.entry_path(index)))
+    }
+
+    fn entry_path(&self, index: usize) -> &str {
+        let start = match index {
+            0 => 0,
+            _ => self.ends[index - 1] as usize,
+        };
+        &self.names[start..self.ends[index] as usize]
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<(u32, S)> for FileIdResolver {
+    fn from_iter<I: IntoIterator<Item = (u32, S)>>(iter: I) -> Self {
+        Self::from_entries(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let resolver = FileIdResolver::parse(
+            "53183;World\\Maps\\Azeroth\\Azeroth.wdt\r\n\
+             \n\
+             775971;world/maps/azeroth/azeroth_32_48.adt\n\
+             100;interface/old.blp\n\
+             100;Interface/New.blp\n\
+             775972;WORLD/MAPS/AZEROTH/AZEROTH_32_48.ADT\n",
+        )
+        .unwrap();
+
+        assert_eq!(resolver.len(), 4);
+        assert_eq!(resolver.path(100), Some("interface/new.blp"));
+        assert_eq!(resolver.path(53183), Some("world/maps/azeroth/azeroth.wdt"));
+        assert_eq!(resolver.path(1), None);
+        assert_eq!(resolver.file_id("\\Interface\\NEW.blp"), Some(100));
+        assert_eq!(resolver.file_id("interface/old.blp"), None);
+        assert_eq!(
+            resolver.file_id("world/maps/azeroth/azeroth_32_48.adt"),
+            Some(775971)
+        );
+        assert_eq!(resolver.file_id("world/maps/azeroth/azeroth.wd"), None);
+        assert_eq!(resolver.file_id("zzz"), None);
+        assert!(matches!(
+            resolver.require_path(2),
+            Err(VfsError::IdNotFound(2))
+        ));
+        assert_eq!(
+            resolver.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            [100, 53183, 775971, 775972]
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(matches!(
+            FileIdResolver::parse("1;a.blp\nb.blp\n"),
+            Err(VfsError::InvalidListfile { line: 2, .. })
+        ));
+        assert!(matches!(
+            FileIdResolver::parse("x;a.blp"),
+            Err(VfsError::InvalidListfile { line: 1, .. })
+        ));
+        assert!(FileIdResolver::parse("").unwrap().is_empty());
+    }
+}
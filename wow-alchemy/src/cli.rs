@@ -60,6 +60,42 @@ pub enum Commands {
         command: crate::commands::wdl::WdlCommands,
     },
 
+    #[cfg(feature = "mpq")]
+    Mpq {
+        #[command(subcommand)]
+        command: crate::commands::mpq::MpqCommands,
+    },
+
+    /// Parse files and write them back, reporting where the written bytes
+    /// first differ from the original
+    #[cfg(any(
+        feature = "blp",
+        feature = "m2",
+        feature = "wmo",
+        feature = "adt",
+        feature = "wdt",
+        feature = "wdl"
+    ))]
+    VerifyRoundtrip {
+        /// Directory to check recursively, or a single file
+        path: std::path::PathBuf,
+
+        /// WoW version (e.g., "3.3.5a", "WotLK") to write WDT files and WMO
+        /// groups as, which don't store it
+        #[arg(long, default_value = "WotLK")]
+        version: wow_alchemy_data::game_version::GameVersion,
+
+        /// Also list the files that round-trip
+        #[arg(long)]
+        show_identical: bool,
+
+        /// Pass the files through a JSON or YAML dump before writing them
+        /// back, like the dump and load commands. Skips BLP files, whose
+        /// dumps only have the header.
+        #[arg(long, value_enum)]
+        dump: Option<crate::utils::dump::DumpFormat>,
+    },
+
     Completions {
         #[arg(value_enum)]
         shell: clap_complete::Shell,
@@ -4,7 +4,14 @@ use anyhow::{Context, Result};
 use clap::Subcommand;
 use prettytable::{Cell, Row, Table, format};
 use std::path::Path;
+use wow_alchemy_adt::split_adt::SplitAdtType;
 use wow_alchemy_adt::{Adt, AdtVersion, ValidationLevel};
+use wow_alchemy_data::game_version::GameVersion;
+use wow_alchemy_vfs::path::strip_extension;
+
+use crate::utils::dump::{DumpFormat, read_dump, write_dump};
+use crate::utils::listfile::load_listfile;
+use crate::utils::provider::open_file;
 
 #[derive(Subcommand)]
 pub enum AdtCommands {
@@ -16,6 +23,11 @@ pub enum AdtCommands {
         /// Show detailed chunk information
         #[arg(short, long)]
         detailed: bool,
+
+        /// Read the ADT from a CASC installation, MPQ archive or directory
+        /// instead of the file system
+        #[arg(short, long)]
+        source: Option<String>,
     },
 
     /// Validate an ADT file
@@ -30,6 +42,11 @@ pub enum AdtCommands {
         /// Show warnings in addition to errors
         #[arg(short, long)]
         warnings: bool,
+
+        /// Read the ADT from a CASC installation, MPQ archive or directory
+        /// instead of the file system
+        #[arg(short, long)]
+        source: Option<String>,
     },
 
     /// Convert ADT between different WoW versions
@@ -40,9 +57,19 @@ pub enum AdtCommands {
         /// Output ADT file
         output: String,
 
-        /// Target WoW version (classic, tbc, wotlk, cataclysm)
+        /// Target WoW version up to Cataclysm (e.g., "3.3.5.12340", "3.3.5a", "WotLK")
+        #[arg(short, long)]
+        to: GameVersion,
+
+        /// Listfile with `id;path` lines to replace the model FileDataIDs of
+        /// maps from Legion on with file names
+        #[arg(long)]
+        listfile: Option<std::path::PathBuf>,
+
+        /// Read the ADT from a CASC installation, MPQ archive or directory
+        /// instead of the file system
         #[arg(short, long)]
-        to: String,
+        source: Option<String>,
     },
 
     /// Extract data from ADT files
@@ -73,6 +100,11 @@ pub enum AdtCommands {
         /// Extract all data
         #[arg(long)]
         all: bool,
+
+        /// Read the ADT from a CASC installation, MPQ archive or directory
+        /// instead of the file system
+        #[arg(short, long)]
+        source: Option<String>,
     },
 
     /// Visualize ADT structure as a tree
@@ -99,6 +131,11 @@ pub enum AdtCommands {
         /// Compact output without descriptions
         #[arg(long)]
         compact: bool,
+
+        /// Read the ADT from a CASC installation, MPQ archive or directory
+        /// instead of the file system
+        #[arg(short, long)]
+        source: Option<String>,
     },
 
     /// Batch process multiple ADT files
@@ -114,25 +151,59 @@ pub enum AdtCommands {
         #[arg(short, long)]
         operation: String,
 
-        /// Target version for conversion (classic, tbc, wotlk, cataclysm)
+        /// Target version for conversion up to Cataclysm (e.g., "3.3.5a", "WotLK")
         #[arg(long)]
-        to: Option<String>,
+        to: Option<GameVersion>,
 
         /// Number of parallel threads
         #[arg(short, long)]
         threads: Option<usize>,
     },
+
+    /// Dump an ADT file as JSON or YAML
+    Dump {
+        /// Path to the ADT file
+        file: String,
+
+        /// Dump format
+        #[arg(short, long, value_enum, default_value_t = DumpFormat::Json)]
+        format: DumpFormat,
+
+        /// Write the dump to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Rebuild an ADT file from a JSON or YAML dump
+    Load {
+        /// Path to the dump, read as YAML for .yaml and .yml files and JSON otherwise
+        input: String,
+
+        /// Path to write the ADT file
+        output: String,
+    },
 }
 
 pub fn execute(command: AdtCommands) -> Result<()> {
     match command {
-        AdtCommands::Info { file, detailed } => execute_info(&file, detailed),
+        AdtCommands::Info {
+            file,
+            detailed,
+            source,
+        } => execute_info(&file, detailed, source.as_deref()),
         AdtCommands::Validate {
             file,
             level,
             warnings,
-        } => execute_validate(&file, &level, warnings),
-        AdtCommands::Convert { input, output, to } => execute_convert(&input, &output, &to),
+            source,
+        } => execute_validate(&file, &level, warnings, source.as_deref()),
+        AdtCommands::Convert {
+            input,
+            output,
+            to,
+            listfile,
+            source,
+        } => execute_convert(&input, &output, to, listfile.as_deref(), source.as_deref()),
         AdtCommands::Extract {
             file,
             output,
@@ -141,6 +212,7 @@ pub fn execute(command: AdtCommands) -> Result<()> {
             textures,
             models,
             all,
+            source,
         } => execute_extract(
             &file,
             output.as_deref(),
@@ -148,6 +220,7 @@ pub fn execute(command: AdtCommands) -> Result<()> {
             &heightmap_format,
             textures || all,
             models || all,
+            source.as_deref(),
         ),
         AdtCommands::Tree {
             file,
@@ -156,55 +229,95 @@ pub fn execute(command: AdtCommands) -> Result<()> {
             no_color,
             no_metadata,
             compact,
-        } => execute_tree(&file, depth, show_refs, no_color, no_metadata, compact),
+            source,
+        } => execute_tree(
+            &file,
+            depth,
+            show_refs,
+            no_color,
+            no_metadata,
+            compact,
+            source.as_deref(),
+        ),
         AdtCommands::Batch {
             pattern,
             output,
             operation,
             to,
             threads,
-        } => execute_batch(&pattern, &output, &operation, to.as_deref(), threads),
+        } => execute_batch(&pattern, &output, &operation, to, threads),
+        AdtCommands::Dump {
+            file,
+            format,
+            output,
+        } => execute_dump(&file, format, output.as_deref()),
+        AdtCommands::Load { input, output } => execute_load(&input, &output),
     }
 }
 
-fn execute_info(file: &str, detailed: bool) -> Result<()> {
+fn execute_dump(file: &str, format: DumpFormat, output: Option<&str>) -> Result<()> {
+    let adt = Adt::from_path(file).with_context(|| format!("Failed to parse ADT file: {file}"))?;
+
+    write_dump(&adt, format, output.map(Path::new))
+}
+
+fn execute_load(input: &str, output: &str) -> Result<()> {
+    let adt: Adt = read_dump(Path::new(input))?;
+
+    // Built in memory so that a failed write leaves no output file behind
+    let mut data = std::io::Cursor::new(Vec::new());
+    adt.write(&mut data)
+        .with_context(|| format!("Failed to write ADT file: {output}"))?;
+    std::fs::write(output, data.into_inner())
+        .with_context(|| format!("Failed to create output file: {output}"))?;
+
+    println!("✅ Wrote {output}");
+
+    Ok(())
+}
+
+/// Read an ADT file. From Cataclysm on, the split files of a root file that
+/// are next to it, or in the source, are merged into it and their paths
+/// returned.
+fn read_adt(file: &str, source: Option<&str>) -> Result<(Adt, Vec<String>)> {
+    if SplitAdtType::from_filename(file) != SplitAdtType::Root {
+        if source.is_some() {
+            anyhow::bail!("Only root ADT files can be read from a source: {file}");
+        }
+        let adt =
+            Adt::from_path(file).with_context(|| format!("Failed to parse ADT file: {file}"))?;
+        return Ok((adt, Vec::new()));
+    }
+
+    let (provider, path) = open_file(file, source)?;
+    let adt = Adt::from_provider(&*provider, &path)
+        .with_context(|| format!("Failed to parse ADT file: {file}"))?;
+    let base = strip_extension(&path);
+    let split_files = ["tex0", "tex1", "obj0", "obj1", "lod"]
+        .iter()
+        .map(|suffix| format!("{base}_{suffix}.adt"))
+        .filter(|split_path| provider.exists(split_path))
+        .collect();
+    Ok((adt, split_files))
+}
+
+fn execute_info(file: &str, detailed: bool, source: Option<&str>) -> Result<()> {
     println!("🏔️  ADT File Information");
     println!("=====================");
     println!();
 
-    // Load the ADT file
-    let adt = Adt::from_path(file).with_context(|| format!("Failed to parse ADT file: {file}"))?;
+    // Load the ADT file and its split files
+    let (adt, split_files) = read_adt(file, source)?;
 
     // Basic information
     println!("File: {file}");
     println!("Version: {}", format_version(&adt.version()));
 
-    // Check for split files
-    let path = Path::new(file);
-    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-    let dir = path.parent().unwrap_or(Path::new("."));
-
-    if !stem.ends_with("_obj0") && !stem.ends_with("_tex0") {
-        // Check for Cataclysm+ split files
-        let tex0 = dir.join(format!("{stem}_tex0.adt"));
-        let tex1 = dir.join(format!("{stem}_tex1.adt"));
-        let obj0 = dir.join(format!("{stem}_obj0.adt"));
-        let obj1 = dir.join(format!("{stem}_obj1.adt"));
-
-        if tex0.exists() || obj0.exists() {
-            println!("\n📁 Split Files Detected (Cataclysm+):");
-            if tex0.exists() {
-                println!("  ✓ {stem}_tex0.adt");
-            }
-            if tex1.exists() {
-                println!("  ✓ {stem}_tex1.adt");
-            }
-            if obj0.exists() {
-                println!("  ✓ {stem}_obj0.adt");
-            }
-            if obj1.exists() {
-                println!("  ✓ {stem}_obj1.adt");
-            }
+    if !split_files.is_empty() {
+        println!("\n📁 Split Files Merged (Cataclysm+):");
+        for split_file in &split_files {
+            let name = split_file.rsplit(['/', '\\']).next().unwrap_or(split_file);
+            println!("  ✓ {name}");
         }
     }
 
@@ -279,7 +392,7 @@ fn execute_info(file: &str, detailed: bool) -> Result<()> {
     Ok(())
 }
 
-fn execute_validate(file: &str, level: &str, warnings: bool) -> Result<()> {
+fn execute_validate(file: &str, level: &str, warnings: bool, source: Option<&str>) -> Result<()> {
     let validation_level = match level.to_lowercase().as_str() {
         "basic" => ValidationLevel::Basic,
         "standard" => ValidationLevel::Standard,
@@ -300,7 +413,7 @@ fn execute_validate(file: &str, level: &str, warnings: bool) -> Result<()> {
     println!();
 
     // Load and validate
-    let adt = Adt::from_path(file).with_context(|| format!("Failed to parse ADT file: {file}"))?;
+    let (adt, _) = read_adt(file, source)?;
 
     let report = adt.validate_with_report_and_context(validation_level, file)?;
 
@@ -336,8 +449,14 @@ fn execute_validate(file: &str, level: &str, warnings: bool) -> Result<()> {
     Ok(())
 }
 
-fn execute_convert(input: &str, output: &str, to_version: &str) -> Result<()> {
-    let target_version = parse_version(to_version)?;
+fn execute_convert(
+    input: &str,
+    output: &str,
+    to_version: GameVersion,
+    listfile: Option<&Path>,
+    source: Option<&str>,
+) -> Result<()> {
+    let target_version = conversion_target(to_version)?;
 
     println!("🔄 Converting ADT File");
     println!("====================");
@@ -348,16 +467,26 @@ fn execute_convert(input: &str, output: &str, to_version: &str) -> Result<()> {
     println!();
 
     // Load the ADT
-    let adt =
-        Adt::from_path(input).with_context(|| format!("Failed to parse ADT file: {input}"))?;
+    let (adt, _) = read_adt(input, source)?;
 
     println!("Source version: {}", format_version(&adt.version()));
 
     // Convert
-    let converted = adt
+    let mut converted = adt
         .to_version(target_version)
         .context("Failed to convert ADT")?;
 
+    // All target versions predate Legion, so models are referenced by name
+    if let Some(resolver) = load_listfile(listfile)? {
+        converted
+            .file_ids_to_paths(&resolver)
+            .context("Failed to replace model FileDataIDs with file names")?;
+    } else if converted.uses_file_data_ids() {
+        anyhow::bail!(
+            "Models are referenced by FileDataID, which the target version doesn't support; pass --listfile to replace them with file names"
+        );
+    }
+
     // Save
     use std::fs::File;
     use std::io::BufWriter;
@@ -382,6 +511,7 @@ fn execute_extract(
     heightmap_format: &str,
     textures: bool,
     models: bool,
+    source: Option<&str>,
 ) -> Result<()> {
     use std::path::PathBuf;
     use wow_alchemy_adt::extract::{HeightmapOptions, ImageFormat, extract_heightmap};
@@ -391,7 +521,7 @@ fn execute_extract(
     println!();
 
     // Load the ADT
-    let adt = Adt::from_path(file).with_context(|| format!("Failed to parse ADT file: {file}"))?;
+    let (adt, _) = read_adt(file, source)?;
 
     // Determine output directory
     let output_path = if let Some(dir) = output_dir {
@@ -469,11 +599,12 @@ fn execute_tree(
     no_color: bool,
     no_metadata: bool,
     compact: bool,
+    source: Option<&str>,
 ) -> Result<()> {
     use crate::utils::tree::{NodeType, TreeNode, TreeOptions, render_tree};
 
     // Load the ADT
-    let adt = Adt::from_path(file).with_context(|| format!("Failed to parse ADT file: {file}"))?;
+    let (adt, _) = read_adt(file, source)?;
 
     // Build tree structure
     let mut root = TreeNode::new(
@@ -708,7 +839,7 @@ fn execute_batch(
     pattern: &str,
     output_dir: &str,
     operation: &str,
-    to_version: Option<&str>,
+    to_version: Option<GameVersion>,
     threads: Option<usize>,
 ) -> Result<()> {
     use glob::glob;
@@ -747,19 +878,27 @@ fn execute_batch(
     // Process files in parallel
     files.par_iter().for_each(|file| {
         let result = match operation {
-            "validate" => match Adt::from_path(file) {
-                Ok(adt) => adt
+            "validate" => match read_adt(&file.to_string_lossy(), None) {
+                Ok((adt, _)) => adt
                     .validate()
                     .map(|_| ())
                     .map_err(|e| anyhow::anyhow!("{}", e)),
-                Err(e) => Err(anyhow::anyhow!("{}", e)),
+                Err(e) => Err(e),
             },
             "convert" => {
-                if let Some(version_str) = to_version {
-                    match (Adt::from_path(file), parse_version(version_str)) {
-                        (Ok(adt), Ok(target)) => {
+                if let Some(version) = to_version {
+                    match (
+                        read_adt(&file.to_string_lossy(), None),
+                        conversion_target(version),
+                    ) {
+                        (Ok((adt, _)), Ok(target)) => {
                             let output_path = Path::new(output_dir).join(file.file_name().unwrap());
                             match adt.to_version(target) {
+                                Ok(converted) if converted.uses_file_data_ids() => {
+                                    Err(anyhow::anyhow!(
+                                        "Models are referenced by FileDataID, which the target version doesn't support; convert it with `adt convert --listfile`"
+                                    ))
+                                }
                                 Ok(converted) => {
                                     use std::fs::File;
                                     use std::io::BufWriter;
@@ -776,7 +915,7 @@ fn execute_batch(
                                 Err(e) => Err(anyhow::anyhow!("{}", e)),
                             }
                         }
-                        (Err(e), _) => Err(anyhow::anyhow!("{}", e)),
+                        (Err(e), _) => Err(e),
                         (_, Err(e)) => Err(e),
                     }
                 } else {
@@ -793,7 +932,7 @@ fn execute_batch(
             }
             Err(e) => {
                 failed.fetch_add(1, Ordering::Relaxed);
-                eprintln!("✗ {}: {}", file.display(), e);
+                eprintln!("✗ {}: {:#}", file.display(), e);
             }
         }
     });
@@ -813,17 +952,15 @@ fn execute_batch(
 }
 
 // Helper functions
-fn parse_version(version_str: &str) -> Result<AdtVersion> {
-    match version_str.to_lowercase().as_str() {
-        "classic" | "vanilla" => Ok(AdtVersion::Vanilla),
-        "tbc" | "bc" => Ok(AdtVersion::TBC),
-        "wotlk" | "wrath" => Ok(AdtVersion::WotLK),
-        "cataclysm" | "cata" => Ok(AdtVersion::Cataclysm),
-        _ => anyhow::bail!(
-            "Invalid version: {}. Valid versions: classic, tbc, wotlk, cataclysm",
-            version_str
-        ),
+fn conversion_target(version: GameVersion) -> Result<AdtVersion> {
+    let target = AdtVersion::from(version);
+    if target > AdtVersion::Cataclysm {
+        anyhow::bail!(
+            "Unsupported target version: {}. Conversion supports classic to cataclysm",
+            version.expansion_name()
+        );
     }
+    Ok(target)
 }
 
 fn format_version(version: &AdtVersion) -> &'static str {
@@ -2,17 +2,24 @@
 
 use anyhow::{Context, Result};
 use clap::{Subcommand, ValueEnum};
-use image::{ImageFormat, ImageReader, imageops::FilterType};
+use image::{DynamicImage, ImageFormat, ImageReader, imageops::FilterType};
 use std::path::{Path, PathBuf};
 use wow_alchemy_blp::{
+    atlas::{AtlasManifest, PackOptions, pack_blp, unpack_blp},
     convert::{
-        AlphaBits, Blp2Format, BlpOldFormat, BlpTarget, DxtAlgorithm, blp_to_image, image_to_blp,
+        AlphaBits, Blp2Format, BlpOldFormat, BlpTarget, DxtAlgorithm, JpegOptions, JpegSubsampling,
+        blp_to_image, image_to_blp,
     },
+    dds::{DdsHeaderFormat, blp_to_dds, dds_to_blp},
+    diff::{CHANNEL_NAMES, ImageDiff, MipmapSelection, compare_images, diff_blp, diff_heatmap},
     encode::save_blp,
-    parser::load_blp,
-    types::BlpContent,
+    parser::{BlpReader, load_blp},
+    types::{BlpContent, BlpFlags, BlpImage},
 };
 
+use crate::utils::dump::{DumpFormat, write_dump};
+use crate::utils::table::{add_table_row, create_table};
+
 #[derive(Subcommand)]
 pub enum BlpCommands {
     /// Display information about a BLP file
@@ -64,30 +71,164 @@ pub enum BlpCommands {
         output: PathBuf,
 
         /// Input format (auto-detected from extension if not specified)
-        #[arg(short = 'i', long)]
+        #[arg(short = 'i', long, visible_alias = "from")]
         input_format: Option<InputFormat>,
 
-        /// Output format (auto-detected from extension if not specified)
-        #[arg(short = 'o', long)]
+        /// Output format (auto-detected from extension if not specified).
+        /// BLP <-> DDS conversions of DXT textures copy the compressed blocks
+        /// and mipmaps without recompression.
+        #[arg(short = 'o', long, visible_alias = "to")]
         output_format: Option<OutputFormat>,
 
-        /// BLP version to use when encoding to BLP
-        #[arg(long, default_value = "blp1")]
+        /// BLP version to use when encoding to BLP [default: blp2 for DXT and raw3, blp1 otherwise]
+        #[arg(long)]
+        blp_version: Option<BlpVersionCli>,
+
+        /// BLP encoding format to use [default: jpeg]
+        #[arg(long)]
+        blp_format: Option<BlpFormat>,
+
+        /// Alpha bits (0, 1, 4, or 8) [default: 8]
+        #[arg(long)]
+        alpha_bits: Option<u8>,
+
+        /// Mipmap level to extract when converting from BLP
+        #[arg(long, default_value = "0")]
+        mipmap_level: usize,
+
+        /// Skip mipmap generation when encoding to BLP
+        #[arg(long)]
+        no_mipmaps: bool,
+
+        /// Mipmap filtering algorithm
+        #[arg(long, default_value = "lanczos3")]
+        mipmap_filter: MipmapFilter,
+
+        /// DXT compression quality
+        #[arg(long, default_value = "medium")]
+        dxt_compression: DxtCompression,
+
+        /// DDS header layout when writing DDS
+        #[arg(long, default_value = "auto")]
+        dds_header: DdsHeader,
+
+        /// JPEG quality (1-100) when encoding JPEG BLP
+        #[arg(long, default_value = "90", value_parser = clap::value_parser!(u8).range(1..=100))]
+        jpeg_quality: u8,
+
+        /// JPEG quality per mipmap level starting from the top one,
+        /// e.g. `95,90,80`. Remaining levels use --jpeg-quality
+        #[arg(long, value_delimiter = ',')]
+        jpeg_mipmap_quality: Vec<u8>,
+
+        /// Subsampling of color channels relative to alpha in JPEG BLP
+        #[arg(long, default_value = "none")]
+        jpeg_subsampling: JpegSubsamplingCli,
+
+        /// Keep quantization and Huffman tables in every mipmap instead of
+        /// the shared JPEG header
+        #[arg(long)]
+        no_shared_jpeg_header: bool,
+    },
+
+    /// Compare two textures and report quality metrics
+    Diff {
+        /// First file (BLP or other image format)
+        a: PathBuf,
+
+        /// Second file (BLP or other image format)
+        b: PathBuf,
+
+        /// Mipmap level to compare
+        #[arg(long, default_value = "0", conflicts_with = "all_levels")]
+        mipmap_level: usize,
+
+        /// Compare all mipmap levels present in both BLP files
+        #[arg(long)]
+        all_levels: bool,
+
+        /// Write a heat map of the differences to the given image path
+        #[arg(long)]
+        heatmap: Option<PathBuf>,
+
+        /// Amplification of errors in the heat map
+        #[arg(long, default_value = "4.0")]
+        heatmap_scale: f32,
+
+        /// Fail if any channel has a pixel error larger than this
+        #[arg(long)]
+        max_error: Option<u8>,
+
+        /// Fail if any channel PSNR is lower than this (dB)
+        #[arg(long)]
+        min_psnr: Option<f64>,
+
+        /// Fail if any channel SSIM is lower than this
+        #[arg(long)]
+        min_ssim: Option<f64>,
+    },
+
+    /// Pack images into an atlas or cut an atlas into images
+    Atlas {
+        #[command(subcommand)]
+        command: AtlasCommands,
+    },
+
+    /// Dump the header of a BLP file as JSON or YAML
+    Dump {
+        /// Path to the BLP file
+        file: PathBuf,
+
+        /// Dump format
+        #[arg(short, long, value_enum, default_value_t = DumpFormat::Json)]
+        format: DumpFormat,
+
+        /// Write the dump to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AtlasCommands {
+    /// Pack all images of a directory into a single BLP atlas
+    Pack {
+        /// Directory with the source images (BLP or other image formats)
+        input_dir: PathBuf,
+
+        /// Output BLP atlas path
+        output: PathBuf,
+
+        /// Output manifest path, written as TOML with a .toml extension and
+        /// JSON otherwise (defaults to the output path with .json extension)
+        #[arg(long)]
+        manifest: Option<PathBuf>,
+
+        /// Pixels of extruded edges around every image
+        #[arg(long, default_value = "2")]
+        padding: u32,
+
+        /// Align image slots to multiples of this value (power of two)
+        #[arg(long, default_value = "4")]
+        alignment: u32,
+        /// Maximum atlas width and height, at most 65535
+        /// Maximum atlas width and height
+        #[arg(long, default_value = "4096")]
+        max_size: u32,
+
+        /// BLP version to use when encoding the atlas
+        #[arg(long, default_value = "blp2")]
         blp_version: BlpVersionCli,
 
         /// BLP encoding format to use
-        #[arg(long, default_value = "jpeg")]
+        #[arg(long, default_value = "dxt5")]
         blp_format: BlpFormat,
 
         /// Alpha bits (0, 1, 4, or 8)
         #[arg(long, default_value = "8")]
         alpha_bits: u8,
 
-        /// Mipmap level to extract when converting from BLP
-        #[arg(long, default_value = "0")]
-        mipmap_level: usize,
-
-        /// Skip mipmap generation when encoding to BLP
+        /// Skip mipmap generation
         #[arg(long)]
         no_mipmaps: bool,
 
@@ -99,6 +240,34 @@ pub enum BlpCommands {
         #[arg(long, default_value = "medium")]
         dxt_compression: DxtCompression,
     },
+
+    /// Cut a BLP atlas into separate images
+    Unpack {
+        /// Input BLP atlas path
+        input: PathBuf,
+
+        /// Directory to write the images into
+        output_dir: PathBuf,
+
+        /// Manifest path, read as TOML with a .toml extension and JSON
+        /// otherwise (defaults to the input path with .json extension, or
+        /// .toml when only that exists)
+        #[arg(long, conflicts_with = "grid")]
+        manifest: Option<PathBuf>,
+
+        /// Cut into a fixed grid of tiles instead of using a manifest, e.g. 64x64
+        #[arg(long)]
+        grid: Option<String>,
+
+        /// Mipmap level to cut, manifest coordinates are scaled to it
+        #[arg(long, default_value = "0")]
+        mipmap_level: usize,
+
+        /// Format of the written images. BLP and DDS images are encoded as
+        /// DXT5 with mipmaps.
+        #[arg(long, default_value = "png")]
+        format: OutputFormat,
+    },
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
@@ -130,6 +299,7 @@ pub enum OutputFormat {
     Tiff,
     Pnm,
     Tga,
+    Dds,
     #[value(name = "openexr")]
     OpenExr,
     Farbfeld,
@@ -162,6 +332,16 @@ pub enum BlpFormat {
     Dxt5,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum DdsHeader {
+    /// Legacy FourCC header unless DX10 is needed to describe alpha
+    Auto,
+    /// Legacy DXT1/DXT3/DXT5 FourCC header
+    Fourcc,
+    /// DX10 extended header
+    Dx10,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
 pub enum MipmapFilter {
     /// Nearest Neighbor
@@ -176,6 +356,16 @@ pub enum MipmapFilter {
     Lanczos3,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum JpegSubsamplingCli {
+    /// All channels at full resolution, as in original files
+    None,
+    /// Color channels at half horizontal resolution
+    Horizontal,
+    /// Color channels at half horizontal and vertical resolution
+    Both,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
 pub enum DxtCompression {
     /// Range fit, fast, poor quality
@@ -200,6 +390,16 @@ impl From<MipmapFilter> for FilterType {
     }
 }
 
+impl From<JpegSubsamplingCli> for JpegSubsampling {
+    fn from(value: JpegSubsamplingCli) -> JpegSubsampling {
+        match value {
+            JpegSubsamplingCli::None => JpegSubsampling::None,
+            JpegSubsamplingCli::Horizontal => JpegSubsampling::Horizontal,
+            JpegSubsamplingCli::Both => JpegSubsampling::Both,
+        }
+    }
+}
+
 impl From<DxtCompression> for DxtAlgorithm {
     fn from(value: DxtCompression) -> DxtAlgorithm {
         match value {
@@ -210,6 +410,16 @@ impl From<DxtCompression> for DxtAlgorithm {
     }
 }
 
+impl From<DdsHeader> for DdsHeaderFormat {
+    fn from(value: DdsHeader) -> DdsHeaderFormat {
+        match value {
+            DdsHeader::Auto => DdsHeaderFormat::Auto,
+            DdsHeader::Fourcc => DdsHeaderFormat::FourCC,
+            DdsHeader::Dx10 => DdsHeaderFormat::Dx10,
+        }
+    }
+}
+
 impl TryFrom<OutputFormat> for ImageFormat {
     type Error = anyhow::Error;
 
@@ -224,6 +434,7 @@ impl TryFrom<OutputFormat> for ImageFormat {
             OutputFormat::Tiff => Ok(ImageFormat::Tiff),
             OutputFormat::Pnm => Ok(ImageFormat::Pnm),
             OutputFormat::Tga => Ok(ImageFormat::Tga),
+            OutputFormat::Dds => anyhow::bail!("DDS format handled separately"),
             OutputFormat::OpenExr => Ok(ImageFormat::OpenExr),
             OutputFormat::Farbfeld => Ok(ImageFormat::Farbfeld),
         }
@@ -262,6 +473,7 @@ fn guess_output_format(path: &Path) -> Option<OutputFormat> {
         "tiff" | "tif" => Some(OutputFormat::Tiff),
         "pnm" | "pbm" | "pgm" | "ppm" | "pam" => Some(OutputFormat::Pnm),
         "tga" => Some(OutputFormat::Tga),
+        "dds" => Some(OutputFormat::Dds),
         "exr" => Some(OutputFormat::OpenExr),
         "ff" | "farbfeld" => Some(OutputFormat::Farbfeld),
         _ => None,
@@ -273,6 +485,7 @@ fn make_blp_target(
     format: BlpFormat,
     alpha_bits: u8,
     dxt_algo: DxtCompression,
+    jpeg_options: JpegOptions,
 ) -> Result<BlpTarget> {
     use wow_alchemy_blp::types::BlpVersion;
     let version: BlpVersion = version.into();
@@ -297,7 +510,10 @@ fn make_blp_target(
                         alpha_bits
                     ),
                 };
-                Ok(BlpTarget::Blp0(BlpOldFormat::Jpeg { has_alpha }))
+                Ok(BlpTarget::Blp0(BlpOldFormat::Jpeg {
+                    has_alpha,
+                    options: jpeg_options,
+                }))
             }
             _ => anyhow::bail!("BLP0 only supports Raw1 and JPEG formats"),
         },
@@ -321,7 +537,10 @@ fn make_blp_target(
                         alpha_bits
                     ),
                 };
-                Ok(BlpTarget::Blp1(BlpOldFormat::Jpeg { has_alpha }))
+                Ok(BlpTarget::Blp1(BlpOldFormat::Jpeg {
+                    has_alpha,
+                    options: jpeg_options,
+                }))
             }
             _ => anyhow::bail!("BLP1 only supports Raw1 and JPEG formats"),
         },
@@ -346,7 +565,10 @@ fn make_blp_target(
                         alpha_bits
                     ),
                 };
-                Ok(BlpTarget::Blp2(Blp2Format::Jpeg { has_alpha }))
+                Ok(BlpTarget::Blp2(Blp2Format::Jpeg {
+                    has_alpha,
+                    options: jpeg_options,
+                }))
             }
             BlpFormat::Dxt1 => {
                 let has_alpha = match alpha_bits {
@@ -394,6 +616,35 @@ fn make_blp_target(
     }
 }
 
+/// Whether the BLP copied from a DDS is what the target flags ask for.
+/// Flags left unset accept whatever the DDS holds.
+fn dds_copy_matches(blp: &BlpImage, args: &ConvertArgs) -> bool {
+    let format = match blp.content {
+        BlpContent::Dxt1(_) => BlpFormat::Dxt1,
+        BlpContent::Dxt3(_) => BlpFormat::Dxt3,
+        BlpContent::Dxt5(_) => BlpFormat::Dxt5,
+        _ => return false,
+    };
+    args.blp_version.is_none_or(|v| v == BlpVersionCli::Blp2)
+        && args.blp_format.is_none_or(|f| f == format)
+        && args
+            .alpha_bits
+            .is_none_or(|bits| u32::from(bits) == blp.header.flags.alpha_bits())
+}
+
+/// Keep only the top level of a DXT image
+fn strip_mipmaps(blp: &mut BlpImage) {
+    let dxtn = match &mut blp.content {
+        BlpContent::Dxt1(v) | BlpContent::Dxt3(v) | BlpContent::Dxt5(v) => v,
+        _ => return,
+    };
+    dxtn.images.truncate(1);
+    if let BlpFlags::Blp2 { has_mipmaps, .. } = &mut blp.header.flags {
+        *has_mipmaps = 0;
+    }
+    blp.header.mipmap_locator = dxtn.mipmap_locator(blp.header.version);
+}
+
 fn convert_blp(args: ConvertArgs) -> Result<()> {
     // Determine input format
     let input_format = args
@@ -409,12 +660,58 @@ fn convert_blp(args: ConvertArgs) -> Result<()> {
 
     log::info!("Converting from {input_format:?} to {output_format:?}");
 
-    // Load input image
-    let input_image = if input_format == InputFormat::Blp {
+    // DXT blocks are copied between BLP and DDS without recompression
+    if output_format == OutputFormat::Dds {
+        if input_format != InputFormat::Blp {
+            anyhow::bail!("DDS output is only supported from DXT compressed BLP files");
+        }
         let blp_image = load_blp(&args.input)
             .with_context(|| format!("Failed to load BLP file: {}", args.input.display()))?;
+        let dds = blp_to_dds(&blp_image, args.dds_header.into())
+            .context("Failed to convert BLP to DDS")?;
+        std::fs::write(&args.output, dds)
+            .with_context(|| format!("Failed to save DDS file: {}", args.output.display()))?;
+        println!(
+            "✓ Converted {} to {}",
+            args.input.display(),
+            args.output.display()
+        );
+        return Ok(());
+    }
 
-        blp_to_image(&blp_image, args.mipmap_level)
+    if input_format == InputFormat::Dds && output_format == OutputFormat::Blp {
+        let input = std::fs::read(&args.input)
+            .with_context(|| format!("Failed to read DDS file: {}", args.input.display()))?;
+        match dds_to_blp(&input) {
+            Ok(blp) if !dds_copy_matches(&blp, &args) => {
+                log::info!("DDS doesn't match the requested BLP target, re-encoding")
+            }
+            Ok(mut blp) => {
+                log::info!("Copying DXT blocks without recompression");
+                if args.no_mipmaps {
+                    strip_mipmaps(&mut blp);
+                }
+                save_blp(&blp, &args.output).with_context(|| {
+                    format!("Failed to save BLP file: {}", args.output.display())
+                })?;
+                println!(
+                    "✓ Converted {} to {}",
+                    args.input.display(),
+                    args.output.display()
+                );
+                return Ok(());
+            }
+            Err(e) => log::info!("DDS can't be copied losslessly ({e}), re-encoding"),
+        }
+    }
+
+    // Load input image
+    let input_image = if input_format == InputFormat::Blp {
+        // Only the requested level is read from disk
+        let mut reader = BlpReader::open(&args.input)
+            .with_context(|| format!("Failed to open BLP file: {}", args.input.display()))?;
+        reader
+            .decode_level(args.mipmap_level)
             .with_context(|| format!("Failed to convert BLP mipmap level {}", args.mipmap_level))?
     } else {
         ImageReader::open(&args.input)
@@ -427,10 +724,17 @@ fn convert_blp(args: ConvertArgs) -> Result<()> {
     match output_format {
         OutputFormat::Blp => {
             let target = make_blp_target(
-                args.blp_version,
-                args.blp_format,
-                args.alpha_bits,
+                args.blp_version.unwrap_or(match args.blp_format {
+                    // Only BLP2 can hold these
+                    Some(BlpFormat::Raw3 | BlpFormat::Dxt1 | BlpFormat::Dxt3 | BlpFormat::Dxt5) => {
+                        BlpVersionCli::Blp2
+                    }
+                    _ => BlpVersionCli::Blp1,
+                }),
+                args.blp_format.unwrap_or(BlpFormat::Jpeg),
+                args.alpha_bits.unwrap_or(8),
                 args.dxt_compression,
+                args.jpeg_options,
             )?;
             let blp = image_to_blp(
                 input_image,
@@ -520,6 +824,19 @@ fn show_blp_info(
             "Compression Efficiency: {:.1}%",
             (1.0 - 1.0 / compression_ratio) * 100.0
         );
+        println!("Bits Per Pixel: {:.2}", blp.bits_per_pixel());
+        if let BlpContent::Jpeg(jpeg) = &blp.content {
+            // Shared header is a part of every level when decoding
+            let shared = jpeg.header.len().saturating_sub(2);
+            println!("Shared JPEG Header: {shared} bytes");
+            for info in blp.mipmap_info() {
+                let bits = ((info.data_size + shared) * 8) as f32 / info.pixel_count.max(1) as f32;
+                println!(
+                    "  Level {}: {}x{} {bits:.2} bpp",
+                    info.level, info.width, info.height
+                );
+            }
+        }
     }
 
     // File size breakdown
@@ -582,6 +899,12 @@ fn show_blp_info(
     Ok(())
 }
 
+fn dump_blp(file: &Path, format: DumpFormat, output: Option<&Path>) -> Result<()> {
+    let blp =
+        load_blp(file).with_context(|| format!("Failed to load BLP file: {}", file.display()))?;
+    write_dump(&blp.header, format, output)
+}
+
 fn validate_blp(file: PathBuf, strict: bool) -> Result<()> {
     println!("Validating BLP file: {}", file.display());
 
@@ -627,17 +950,15 @@ fn validate_blp(file: PathBuf, strict: bool) -> Result<()> {
 
     // Format-specific validation
     match &blp.content {
-        BlpContent::Jpeg(jpeg) => {
-            // JPEG-specific validations
-            if jpeg.header.is_empty() {
-                errors.push("JPEG header is empty".to_string());
-            }
+        // JPEG-specific validations
+        BlpContent::Jpeg(jpeg) if jpeg.header.is_empty() => {
+            errors.push("JPEG header is empty".to_string());
         }
-        BlpContent::Dxt1(_) | BlpContent::Dxt3(_) | BlpContent::Dxt5(_) => {
-            // DXT requires dimensions to be multiples of 4
-            if blp.header.width % 4 != 0 || blp.header.height % 4 != 0 {
-                errors.push("DXT format requires dimensions to be multiples of 4".to_string());
-            }
+        // DXT requires dimensions to be multiples of 4
+        BlpContent::Dxt1(_) | BlpContent::Dxt3(_) | BlpContent::Dxt5(_)
+            if blp.header.width % 4 != 0 || blp.header.height % 4 != 0 =>
+        {
+            errors.push("DXT format requires dimensions to be multiples of 4".to_string());
         }
         _ => {}
     }
@@ -670,19 +991,380 @@ fn validate_blp(file: PathBuf, strict: bool) -> Result<()> {
     }
 }
 
+enum DiffInput {
+    Blp(BlpImage),
+    Image(DynamicImage),
+}
+
+impl DiffInput {
+    fn load(path: &Path) -> Result<Self> {
+        if guess_input_format(path) == Some(InputFormat::Blp) {
+            let blp = load_blp(path)
+                .with_context(|| format!("Failed to load BLP file: {}", path.display()))?;
+            Ok(DiffInput::Blp(blp))
+        } else {
+            let image = ImageReader::open(path)
+                .with_context(|| format!("Failed to open image file: {}", path.display()))?
+                .decode()
+                .with_context(|| format!("Failed to decode image: {}", path.display()))?;
+            Ok(DiffInput::Image(image))
+        }
+    }
+
+    fn decode(&self, mipmap_level: usize) -> Result<DynamicImage> {
+        match self {
+            DiffInput::Blp(blp) => blp_to_image(blp, mipmap_level)
+                .with_context(|| format!("Failed to decode BLP mipmap level {mipmap_level}")),
+            DiffInput::Image(image) if mipmap_level == 0 => Ok(image.clone()),
+            DiffInput::Image(_) => {
+                anyhow::bail!("Mipmap level {mipmap_level} requested for a non BLP image")
+            }
+        }
+    }
+}
+
+fn print_image_diff(level: usize, diff: &ImageDiff) {
+    println!("\nMipmap level {level} ({}x{}):", diff.width, diff.height);
+    let mut table = create_table(vec!["Channel", "PSNR (dB)", "SSIM", "Max Error", "MSE"]);
+    for (name, channel) in CHANNEL_NAMES.iter().zip(diff.channels.iter()) {
+        add_table_row(
+            &mut table,
+            vec![
+                name.to_string(),
+                format!("{:.2}", channel.psnr),
+                format!("{:.4}", channel.ssim),
+                channel.max_error.to_string(),
+                format!("{:.3}", channel.mse),
+            ],
+        );
+    }
+    table.printstd();
+}
+
+fn diff_textures(args: DiffArgs) -> Result<()> {
+    let a = DiffInput::load(&args.a)?;
+    let b = DiffInput::load(&args.b)?;
+
+    println!("Comparing {} and {}", args.a.display(), args.b.display());
+    println!("=====================================");
+
+    let level_diffs = match (&a, &b) {
+        (DiffInput::Blp(blp_a), DiffInput::Blp(blp_b)) => {
+            let selection = if args.all_levels {
+                MipmapSelection::All
+            } else {
+                MipmapSelection::Level(args.mipmap_level)
+            };
+            let diff = diff_blp(blp_a, blp_b, selection)?;
+
+            if diff.header.is_empty() {
+                println!("Headers: identical");
+            } else {
+                println!("Header differences:");
+                for field in &diff.header {
+                    println!("  {}: {} != {}", field.field, field.left, field.right);
+                }
+            }
+            if diff.left_image_count != diff.right_image_count {
+                println!(
+                    "Mipmap count differs: {} != {}",
+                    diff.left_image_count, diff.right_image_count
+                );
+            }
+            diff.levels
+                .into_iter()
+                .map(|level| (level.level, level.diff))
+                .collect::<Vec<_>>()
+        }
+        _ => {
+            if args.all_levels {
+                log::warn!("Only mipmap level 0 can be compared with a non BLP image");
+            }
+            let level = if args.all_levels {
+                0
+            } else {
+                args.mipmap_level
+            };
+            vec![(level, compare_images(&a.decode(level)?, &b.decode(level)?)?)]
+        }
+    };
+
+    for (level, diff) in &level_diffs {
+        print_image_diff(*level, diff);
+    }
+
+    if let Some(heatmap_path) = &args.heatmap {
+        let level = level_diffs.first().map(|(level, _)| *level).unwrap_or(0);
+        let heatmap = diff_heatmap(&a.decode(level)?, &b.decode(level)?, args.heatmap_scale)?;
+        heatmap
+            .save(heatmap_path)
+            .with_context(|| format!("Failed to save heat map: {}", heatmap_path.display()))?;
+        println!(
+            "\nHeat map of level {level} saved to {}",
+            heatmap_path.display()
+        );
+    }
+
+    let mut failures = vec![];
+    for (level, diff) in &level_diffs {
+        if let Some(max_error) = args.max_error {
+            if diff.max_error() > max_error {
+                failures.push(format!(
+                    "level {level}: max error {} > {max_error}",
+                    diff.max_error()
+                ));
+            }
+        }
+        if let Some(min_psnr) = args.min_psnr {
+            if diff.min_psnr() < min_psnr {
+                failures.push(format!(
+                    "level {level}: PSNR {:.2} < {min_psnr}",
+                    diff.min_psnr()
+                ));
+            }
+        }
+        if let Some(min_ssim) = args.min_ssim {
+            if diff.min_ssim() < min_ssim {
+                failures.push(format!(
+                    "level {level}: SSIM {:.4} < {min_ssim}",
+                    diff.min_ssim()
+                ));
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        println!("\nThresholds exceeded:");
+        for failure in &failures {
+            println!("  ✗ {failure}");
+        }
+        anyhow::bail!("Texture difference exceeds thresholds")
+    }
+}
+
+fn load_any_image(path: &Path) -> Result<DynamicImage> {
+    if guess_input_format(path) == Some(InputFormat::Blp) {
+        let blp = load_blp(path)
+            .with_context(|| format!("Failed to load BLP file: {}", path.display()))?;
+        Ok(blp_to_image(&blp, 0)?)
+    } else {
+        Ok(ImageReader::open(path)
+            .with_context(|| format!("Failed to open image file: {}", path.display()))?
+            .decode()
+            .with_context(|| format!("Failed to decode image: {}", path.display()))?)
+    }
+}
+
+fn pack_atlas(args: AtlasPackArgs) -> Result<()> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(&args.input_dir)
+        .with_context(|| format!("Failed to read directory: {}", args.input_dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && guess_input_format(path).is_some())
+        .collect();
+    paths.sort();
+
+    let mut images = Vec::with_capacity(paths.len());
+    for path in paths {
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        log::info!("Adding {}", path.display());
+        images.push((name, load_any_image(&path)?));
+    }
+
+    let target = make_blp_target(
+        args.blp_version,
+        args.blp_format,
+        args.alpha_bits,
+        args.dxt_compression,
+        JpegOptions::default(),
+    )?;
+    let options = PackOptions {
+        padding: args.padding,
+        alignment: args.alignment,
+        max_size: args.max_size,
+    };
+    let (blp, manifest) = pack_blp(
+        &images,
+        &options,
+        !args.no_mipmaps,
+        target,
+        args.mipmap_filter.into(),
+    )
+    .context("Failed to pack atlas")?;
+
+    save_blp(&blp, &args.output)
+        .with_context(|| format!("Failed to save BLP file: {}", args.output.display()))?;
+    let manifest_path = args
+        .manifest
+        .unwrap_or_else(|| args.output.with_extension("json"));
+    let manifest_text = if is_toml_manifest(&manifest_path) {
+        manifest.to_toml()?
+    } else {
+        manifest.to_json()?
+    };
+    std::fs::write(&manifest_path, manifest_text)
+        .with_context(|| format!("Failed to save manifest: {}", manifest_path.display()))?;
+
+    println!(
+        "✓ Packed {} images into {}x{} atlas {} (manifest {})",
+        manifest.entries.len(),
+        manifest.width,
+        manifest.height,
+        args.output.display(),
+        manifest_path.display()
+    );
+    Ok(())
+}
+
+fn parse_grid(value: &str) -> Result<(u32, u32)> {
+    let (width, height) = value
+        .split_once(['x', 'X'])
+        .context("Grid must be given as <width>x<height>")?;
+    Ok((
+        width.trim().parse().context("Invalid grid width")?,
+        height.trim().parse().context("Invalid grid height")?,
+    ))
+}
+
+fn unpack_atlas(args: AtlasUnpackArgs) -> Result<()> {
+    let blp = load_blp(&args.input)
+        .with_context(|| format!("Failed to load BLP file: {}", args.input.display()))?;
+
+    let manifest = if let Some(grid) = &args.grid {
+        let (tile_width, tile_height) = parse_grid(grid)?;
+        let (width, height) = blp.header.mipmap_size(args.mipmap_level);
+        AtlasManifest::grid(width, height, tile_width, tile_height)?
+    } else {
+        let manifest_path = args.manifest.clone().unwrap_or_else(|| {
+            let json = args.input.with_extension("json");
+            let toml = args.input.with_extension("toml");
+            if !json.exists() && toml.exists() {
+                toml
+            } else {
+                json
+            }
+        });
+        let text = std::fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Failed to read manifest: {}", manifest_path.display()))?;
+        if is_toml_manifest(&manifest_path) {
+            AtlasManifest::from_toml(&text)?
+        } else {
+            AtlasManifest::from_json(&text)?
+        }
+    };
+
+    let extension = match args.format {
+        OutputFormat::Blp => "blp",
+        OutputFormat::Dds => "dds",
+        format => {
+            let img_format: ImageFormat = format.try_into()?;
+            img_format
+                .extensions_str()
+                .first()
+                .copied()
+                .unwrap_or("img")
+        }
+    };
+    std::fs::create_dir_all(&args.output_dir)
+        .with_context(|| format!("Failed to create directory: {}", args.output_dir.display()))?;
+
+    let tiles = unpack_blp(&blp, &manifest, args.mipmap_level).context("Failed to cut atlas")?;
+    for (name, image) in tiles.iter() {
+        let path = args.output_dir.join(format!("{name}.{extension}"));
+        save_atlas_tile(image, &path, args.format)
+            .with_context(|| format!("Failed to save image: {}", path.display()))?;
+    }
+
+    println!(
+        "✓ Extracted {} images into {}",
+        tiles.len(),
+        args.output_dir.display()
+    );
+    Ok(())
+}
+
+/// Whether a manifest is TOML rather than JSON, going by its extension
+fn is_toml_manifest(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+}
+
+/// Save an image cut from an atlas, encoding BLP and DDS images as DXT5
+fn save_atlas_tile(image: &DynamicImage, path: &Path, format: OutputFormat) -> Result<()> {
+    let encode = || {
+        image_to_blp(
+            image.clone(),
+            true,
+            BlpTarget::Blp2(Blp2Format::default()),
+            FilterType::Lanczos3,
+        )
+    };
+    match format {
+        OutputFormat::Blp => save_blp(&encode()?, path)?,
+        OutputFormat::Dds => std::fs::write(path, blp_to_dds(&encode()?, DdsHeaderFormat::Auto)?)?,
+        format => image.save_with_format(path, format.try_into()?)?,
+    }
+    Ok(())
+}
+
+// Helper struct for atlas pack arguments
+struct AtlasPackArgs {
+    input_dir: PathBuf,
+    output: PathBuf,
+    manifest: Option<PathBuf>,
+    padding: u32,
+    alignment: u32,
+    max_size: u32,
+    blp_version: BlpVersionCli,
+    blp_format: BlpFormat,
+    alpha_bits: u8,
+    no_mipmaps: bool,
+    mipmap_filter: MipmapFilter,
+    dxt_compression: DxtCompression,
+}
+
+// Helper struct for atlas unpack arguments
+struct AtlasUnpackArgs {
+    input: PathBuf,
+    output_dir: PathBuf,
+    manifest: Option<PathBuf>,
+    grid: Option<String>,
+    mipmap_level: usize,
+    format: OutputFormat,
+}
+
+// Helper struct for diff arguments
+struct DiffArgs {
+    a: PathBuf,
+    b: PathBuf,
+    mipmap_level: usize,
+    all_levels: bool,
+    heatmap: Option<PathBuf>,
+    heatmap_scale: f32,
+    max_error: Option<u8>,
+    min_psnr: Option<f64>,
+    min_ssim: Option<f64>,
+}
+
 // Helper struct for convert arguments
 struct ConvertArgs {
     input: PathBuf,
     output: PathBuf,
     input_format: Option<InputFormat>,
     output_format: Option<OutputFormat>,
-    blp_version: BlpVersionCli,
-    blp_format: BlpFormat,
-    alpha_bits: u8,
+    blp_version: Option<BlpVersionCli>,
+    blp_format: Option<BlpFormat>,
+    alpha_bits: Option<u8>,
     mipmap_level: usize,
     no_mipmaps: bool,
     mipmap_filter: MipmapFilter,
     dxt_compression: DxtCompression,
+    dds_header: DdsHeader,
+    jpeg_options: JpegOptions,
 }
 
 pub fn execute(command: BlpCommands) -> Result<()> {
@@ -699,6 +1381,11 @@ pub fn execute(command: BlpCommands) -> Result<()> {
             no_mipmaps,
             mipmap_filter,
             dxt_compression,
+            dds_header,
+            jpeg_quality,
+            jpeg_mipmap_quality,
+            jpeg_subsampling,
+            no_shared_jpeg_header,
         } => convert_blp(ConvertArgs {
             input,
             output,
@@ -711,6 +1398,13 @@ pub fn execute(command: BlpCommands) -> Result<()> {
             no_mipmaps,
             mipmap_filter,
             dxt_compression,
+            dds_header,
+            jpeg_options: JpegOptions {
+                quality: jpeg_quality,
+                mipmap_quality: jpeg_mipmap_quality,
+                subsampling: jpeg_subsampling.into(),
+                shared_header: !no_shared_jpeg_header,
+            },
         }),
         BlpCommands::Info {
             file,
@@ -722,5 +1416,75 @@ pub fn execute(command: BlpCommands) -> Result<()> {
             all,
         } => show_blp_info(file, mipmaps, raw, compression, size, best_mipmap_for, all),
         BlpCommands::Validate { file, strict } => validate_blp(file, strict),
+        BlpCommands::Dump {
+            file,
+            format,
+            output,
+        } => dump_blp(&file, format, output.as_deref()),
+        BlpCommands::Diff {
+            a,
+            b,
+            mipmap_level,
+            all_levels,
+            heatmap,
+            heatmap_scale,
+            max_error,
+            min_psnr,
+            min_ssim,
+        } => diff_textures(DiffArgs {
+            a,
+            b,
+            mipmap_level,
+            all_levels,
+            heatmap,
+            heatmap_scale,
+            max_error,
+            min_psnr,
+            min_ssim,
+        }),
+        BlpCommands::Atlas { command } => match command {
+            AtlasCommands::Pack {
+                input_dir,
+                output,
+                manifest,
+                padding,
+                alignment,
+                max_size,
+                blp_version,
+                blp_format,
+                alpha_bits,
+                no_mipmaps,
+                mipmap_filter,
+                dxt_compression,
+            } => pack_atlas(AtlasPackArgs {
+                input_dir,
+                output,
+                manifest,
+                padding,
+                alignment,
+                max_size,
+                blp_version,
+                blp_format,
+                alpha_bits,
+                no_mipmaps,
+                mipmap_filter,
+                dxt_compression,
+            }),
+            AtlasCommands::Unpack {
+                input,
+                output_dir,
+                manifest,
+                grid,
+                mipmap_level,
+                format,
+            } => unpack_atlas(AtlasUnpackArgs {
+                input,
+                output_dir,
+                manifest,
+                grid,
+                mipmap_level,
+                format,
+            }),
+        },
     }
 }
@@ -1,18 +1,149 @@
-use anyhow::Result;
-use clap::Subcommand;
+use anyhow::{Context, Result};
+use clap::{Subcommand, ValueEnum};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 
-use wow_alchemy_cdbc::sqlite_converter::convert_to_sqlite;
+use wow_alchemy_cdbc::csv_converter::{read_csv, write_csv};
+use wow_alchemy_cdbc::dbd::codegen::generate_module;
+use wow_alchemy_cdbc::dbd::{DbdSource, GameBuild};
+use wow_alchemy_cdbc::diff::{TableDiff, diff_tables};
+use wow_alchemy_cdbc::flatten::format_value;
+use wow_alchemy_cdbc::json_converter::{read_json, write_json};
+use wow_alchemy_cdbc::sqlite_converter::{
+    LocStringColumns, convert_from_sqlite, convert_to_sqlite,
+};
+use wow_alchemy_cdbc::{DbcTable, Locale, Value, WdbWriter};
+
+use crate::utils::table::{add_table_row, create_table};
 
 #[derive(Subcommand)]
 pub enum DbcCommands {
     /// Convert all dbc files in a folder to a SQLite database
     Convert {
-        /// A build in the format x.x.x.xxxx, for example 3.3.5.12340
-        game_build: String,
+        /// A build like 3.3.5.12340, a patch like 3.3.5a or an expansion like wotlk
+        game_build: GameBuild,
         source_dir: PathBuf,
         output_sqlite: PathBuf,
+        /// Store localized strings as a single column of this locale (enUS, frFR...)
+        /// instead of one column per locale
+        #[arg(long)]
+        locale: Option<Locale>,
+        /// Directory of .dbd files, like the definitions folder of a WoWDBDefs checkout,
+        /// searched before WOW_ALCHEMY_DBD_DIR, the embedded definitions and downloading them
+        /// (dbd-download feature)
+        #[arg(long)]
+        definitions: Option<PathBuf>,
+    },
+    /// Convert the tables of a SQLite database created by `convert` back to the dbc and db2
+    /// files they came from
+    ExportSqlite {
+        /// A build like 3.3.5.12340, a patch like 3.3.5a or an expansion like wotlk
+        game_build: GameBuild,
+        input_sqlite: PathBuf,
+        output_dir: PathBuf,
+        /// Localized strings are stored as a single column of this locale, as
+        /// with `convert --locale`
+        #[arg(long)]
+        locale: Option<Locale>,
+        /// Directory of .dbd files, like the definitions folder of a WoWDBDefs checkout,
+        /// searched before WOW_ALCHEMY_DBD_DIR, the embedded definitions and downloading them
+        /// (dbd-download feature)
+        #[arg(long)]
+        definitions: Option<PathBuf>,
+    },
+    /// Generate Rust structs with `#[derive(DbcRecord)]` for tables from their DBD definitions
+    Codegen {
+        /// A build like 3.3.5.12340, a patch like 3.3.5a or an expansion like wotlk
+        game_build: GameBuild,
+        /// Table names like Spell or Spell.dbc
+        #[arg(required = true)]
+        tables: Vec<String>,
+        /// Write the module to this file instead of printing it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Directory of .dbd files, like the definitions folder of a WoWDBDefs checkout,
+        /// searched before WOW_ALCHEMY_DBD_DIR, the embedded definitions and downloading them
+        /// (dbd-download feature)
+        #[arg(long)]
+        definitions: Option<PathBuf>,
+    },
+    /// Export a dbc file to CSV or JSON, with a column per field, array element and locale
+    Export {
+        /// A build like 3.3.5.12340, a patch like 3.3.5a or an expansion like wotlk
+        game_build: GameBuild,
+        file: PathBuf,
+        #[arg(short, long, default_value = "csv")]
+        format: TextFormat,
+        /// Write to this file instead of printing
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Export localized strings as a single column of this locale (enUS, frFR...)
+        /// instead of one column per locale
+        #[arg(long)]
+        locale: Option<Locale>,
+        /// Directory of .dbd files, like the definitions folder of a WoWDBDefs checkout,
+        /// searched before WOW_ALCHEMY_DBD_DIR, the embedded definitions and downloading them
+        /// (dbd-download feature)
+        #[arg(long)]
+        definitions: Option<PathBuf>,
     },
+    /// Rebuild a dbc file from a CSV or JSON file created by `export`
+    Import {
+        /// A build like 3.3.5.12340, a patch like 3.3.5a or an expansion like wotlk
+        game_build: GameBuild,
+        input: PathBuf,
+        /// The dbc or db2 file to write, its name selects the DBD definition. It is
+        /// written in the format of the build, WDB2 for db2 files from Cataclysm on.
+        output: PathBuf,
+        /// Format of the input (auto-detected from extension if not specified)
+        #[arg(short, long)]
+        format: Option<TextFormat>,
+        /// Table name like Spell when it differs from the output file name
+        #[arg(long)]
+        table: Option<String>,
+        /// Localized strings are a single column of this locale, as with
+        /// `export --locale`
+        #[arg(long)]
+        locale: Option<Locale>,
+        /// Directory of .dbd files, like the definitions folder of a WoWDBDefs checkout,
+        /// searched before WOW_ALCHEMY_DBD_DIR, the embedded definitions and downloading them
+        /// (dbd-download feature)
+        #[arg(long)]
+        definitions: Option<PathBuf>,
+    },
+    /// Compare two versions of a dbc file: added and removed ids and changed values
+    Diff {
+        /// A build like 3.3.5.12340, a patch like 3.3.5a or an expansion like wotlk
+        game_build: GameBuild,
+        old: PathBuf,
+        new: PathBuf,
+        /// Build of the new file when it uses another layout, columns are matched by name
+        #[arg(long)]
+        new_build: Option<GameBuild>,
+        /// Table name like Spell when it differs from the old file name
+        #[arg(long)]
+        table: Option<String>,
+        #[arg(short, long, default_value = "text")]
+        format: DiffFormat,
+        /// Directory of .dbd files, like the definitions folder of a WoWDBDefs checkout,
+        /// searched before WOW_ALCHEMY_DBD_DIR, the embedded definitions and downloading them
+        /// (dbd-download feature)
+        #[arg(long)]
+        definitions: Option<PathBuf>,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum DiffFormat {
+    Text,
+    Json,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TextFormat {
+    Csv,
+    Json,
 }
 
 pub fn execute(command: DbcCommands) -> Result<()> {
@@ -21,11 +152,340 @@ pub fn execute(command: DbcCommands) -> Result<()> {
             game_build,
             source_dir,
             output_sqlite,
-        } => convert_command(&game_build, &source_dir, &output_sqlite),
+            locale,
+            definitions,
+        } => convert_command(
+            &game_build,
+            &source_dir,
+            &output_sqlite,
+            locale,
+            dbd_source(definitions),
+        ),
+        DbcCommands::ExportSqlite {
+            game_build,
+            input_sqlite,
+            output_dir,
+            locale,
+            definitions,
+        } => export_sqlite_command(
+            &game_build,
+            &input_sqlite,
+            &output_dir,
+            locale,
+            dbd_source(definitions),
+        ),
+        DbcCommands::Codegen {
+            game_build,
+            tables,
+            output,
+            definitions,
+        } => codegen_command(
+            &game_build,
+            &tables,
+            output.as_deref(),
+            dbd_source(definitions),
+        ),
+        DbcCommands::Export {
+            game_build,
+            file,
+            format,
+            output,
+            locale,
+            definitions,
+        } => export_command(
+            &game_build,
+            &file,
+            format,
+            output.as_deref(),
+            locale,
+            dbd_source(definitions),
+        ),
+        DbcCommands::Import {
+            game_build,
+            input,
+            output,
+            format,
+            table,
+            locale,
+            definitions,
+        } => import_command(
+            &game_build,
+            &input,
+            &output,
+            format,
+            table.as_deref(),
+            locale,
+            dbd_source(definitions),
+        ),
+        DbcCommands::Diff {
+            game_build,
+            old,
+            new,
+            new_build,
+            table,
+            format,
+            definitions,
+        } => diff_command(
+            &game_build,
+            &old,
+            &new,
+            new_build,
+            table.as_deref(),
+            format,
+            dbd_source(definitions),
+        ),
+    }
+}
+
+/// Directory of .dbd files used when `--definitions` is not given or lacks a
+/// table, the same variable the `embedded-dbd` feature embeds at build time
+const DBD_DIR_VAR: &str = "WOW_ALCHEMY_DBD_DIR";
+
+/// Sources of definitions, tried in order: the `--definitions` directory, the
+/// directory in `WOW_ALCHEMY_DBD_DIR`, the embedded definitions and finally
+/// downloading them when the `dbd-download` feature is enabled
+fn dbd_source(definitions: Option<PathBuf>) -> DbdSource {
+    let mut sources = Vec::new();
+    if let Some(dir) = definitions {
+        sources.push(DbdSource::Directory(dir));
+    }
+    if let Some(dir) = std::env::var_os(DBD_DIR_VAR) {
+        sources.push(DbdSource::Directory(dir.into()));
     }
+    #[cfg(feature = "embedded-dbd")]
+    sources.push(DbdSource::Embedded);
+    #[cfg(feature = "dbd-download")]
+    sources.push(DbdSource::Download);
+    DbdSource::Chain(sources)
+}
+
+fn locstring_columns(locale: Option<Locale>) -> LocStringColumns {
+    match locale {
+        Some(locale) => LocStringColumns::Locale(locale),
+        None => LocStringColumns::PerLocale,
+    }
+}
+
+fn convert_command(
+    game_build: &GameBuild,
+    source_dir: &Path,
+    output_sqlite: &Path,
+    locale: Option<Locale>,
+    dbd_source: DbdSource,
+) -> Result<()> {
+    convert_to_sqlite(
+        *game_build,
+        &dbd_source,
+        source_dir,
+        output_sqlite,
+        locstring_columns(locale),
+    )?;
+    Ok(())
+}
+
+fn export_sqlite_command(
+    game_build: &GameBuild,
+    input_sqlite: &Path,
+    output_dir: &Path,
+    locale: Option<Locale>,
+    dbd_source: DbdSource,
+) -> Result<()> {
+    convert_from_sqlite(
+        *game_build,
+        &dbd_source,
+        input_sqlite,
+        output_dir,
+        locstring_columns(locale),
+    )?;
+    Ok(())
 }
 
-fn convert_command(game_build: &str, source_dir: &Path, output_sqlite: &Path) -> Result<()> {
-    convert_to_sqlite(game_build.try_into()?, source_dir, output_sqlite)?;
+fn codegen_command(
+    game_build: &GameBuild,
+    tables: &[String],
+    output: Option<&Path>,
+    dbd_source: DbdSource,
+) -> Result<()> {
+    let mut layouts = Vec::with_capacity(tables.len());
+    for table in tables {
+        let definition = dbd_source.load(table)?;
+        let dbd_file = definition.parse(game_build)?;
+        layouts.push((definition.name, dbd_file));
+    }
+
+    let source = generate_module(&layouts)?;
+    match output {
+        Some(path) => std::fs::write(path, source)?,
+        None => print!("{source}"),
+    }
     Ok(())
 }
+
+fn export_command(
+    game_build: &GameBuild,
+    file: &Path,
+    format: TextFormat,
+    output: Option<&Path>,
+    locale: Option<Locale>,
+    dbd_source: DbdSource,
+) -> Result<()> {
+    let file_name = file
+        .file_name()
+        .with_context(|| format!("not a file: {}", file.display()))?;
+    let definition = dbd_source.load(&file_name.to_string_lossy())?;
+
+    let table = DbcTable::open_definition(file, &definition, game_build)?;
+
+    let writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let locstrings = locstring_columns(locale);
+    match format {
+        TextFormat::Csv => write_csv(writer, table.dbd(), table.records(), locstrings)?,
+        TextFormat::Json => write_json(writer, table.dbd(), table.records(), locstrings)?,
+    }
+    Ok(())
+}
+
+fn import_command(
+    game_build: &GameBuild,
+    input: &Path,
+    output: &Path,
+    format: Option<TextFormat>,
+    table: Option<&str>,
+    locale: Option<Locale>,
+    dbd_source: DbdSource,
+) -> Result<()> {
+    let format = match format {
+        Some(format) => format,
+        None => match input.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => TextFormat::Json,
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => TextFormat::Csv,
+            _ => anyhow::bail!(
+                "can't detect the format of {}, use --format",
+                input.display()
+            ),
+        },
+    };
+    let table = match table {
+        Some(table) => table.to_owned(),
+        None => output
+            .file_name()
+            .with_context(|| format!("not a file: {}", output.display()))?
+            .to_string_lossy()
+            .into_owned(),
+    };
+    let dbd = dbd_source.load(&table)?.parse(game_build)?;
+
+    let reader = BufReader::new(File::open(input)?);
+    let locstrings = locstring_columns(locale);
+    let records = match format {
+        TextFormat::Csv => read_csv(reader, &dbd, locstrings)?,
+        TextFormat::Json => read_json(reader, &dbd, locstrings)?,
+    };
+
+    let mut writer = BufWriter::new(File::create(output)?);
+    let db2 = output
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("db2"));
+    WdbWriter::for_build(&dbd, game_build, db2).write(&mut writer, &records)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn diff_command(
+    game_build: &GameBuild,
+    old: &Path,
+    new: &Path,
+    new_build: Option<GameBuild>,
+    table: Option<&str>,
+    format: DiffFormat,
+    dbd_source: DbdSource,
+) -> Result<()> {
+    let new_build = new_build.unwrap_or(*game_build);
+    let table = match table {
+        Some(table) => table.to_owned(),
+        None => old
+            .file_name()
+            .with_context(|| format!("not a file: {}", old.display()))?
+            .to_string_lossy()
+            .into_owned(),
+    };
+    let definition = dbd_source.load(&table)?;
+
+    let old_table = DbcTable::open_definition(old, &definition, game_build)?;
+    let new_table = DbcTable::open_definition(new, &definition, &new_build)?;
+    let diff = diff_tables(&old_table, &new_table);
+
+    match format {
+        DiffFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+        DiffFormat::Text => print_diff(&definition.name, &diff),
+    }
+    Ok(())
+}
+
+fn diff_cell(value: &Value) -> String {
+    match value {
+        Value::String(Some(v)) => format!("{v:?}"),
+        _ => format_value(value),
+    }
+}
+
+fn print_diff(table: &str, diff: &TableDiff) {
+    println!(
+        "{table}: {} added, {} removed, {} changed",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len()
+    );
+
+    let ids = |ids: &[u32]| {
+        ids.iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if !diff.added_columns.is_empty() {
+        println!("Added columns: {}", diff.added_columns.join(", "));
+    }
+    if !diff.removed_columns.is_empty() {
+        println!("Removed columns: {}", diff.removed_columns.join(", "));
+    }
+    if !diff.added.is_empty() {
+        println!("Added ids: {}", ids(&diff.added));
+    }
+    if !diff.removed.is_empty() {
+        println!("Removed ids: {}", ids(&diff.removed));
+    }
+    if !diff.old_duplicates.is_empty() {
+        println!(
+            "Duplicate ids in old, only the first row compared: {}",
+            ids(&diff.old_duplicates)
+        );
+    }
+    if !diff.new_duplicates.is_empty() {
+        println!(
+            "Duplicate ids in new, only the first row compared: {}",
+            ids(&diff.new_duplicates)
+        );
+    }
+
+    if !diff.changed.is_empty() {
+        let mut table = create_table(vec!["ID", "Column", "Old", "New"]);
+        for row in &diff.changed {
+            for change in &row.changes {
+                add_table_row(
+                    &mut table,
+                    vec![
+                        row.id.to_string(),
+                        change.column.clone(),
+                        diff_cell(&change.old),
+                        diff_cell(&change.new),
+                    ],
+                );
+            }
+        }
+        table.printstd();
+    }
+}
@@ -2,12 +2,17 @@
 
 use anyhow::{Context, Result};
 use clap::Subcommand;
-use std::{fs::File, path::PathBuf};
-use wow_alchemy_data::types::{VWowStructR, WowStructR};
+use std::{fs::File, io::Cursor, path::PathBuf};
+use wow_alchemy_data::types::{VWowStructR, WowStructR, WowStructW};
 
 use wow_alchemy_blp::parser::load_blp;
+use wow_alchemy_m2::model::M2Chunk;
 use wow_alchemy_m2::{M2Model, PhysFile, Skin, skin::SkinVersion};
+use wow_alchemy_vfs::FileIdResolver;
 
+use crate::utils::dump::{DumpFormat, read_dump, write_dump};
+use crate::utils::listfile::{describe_file_id, load_listfile};
+use crate::utils::provider::open_file;
 use crate::utils::{NodeType, TreeNode, TreeOptions, render_tree};
 
 #[derive(Subcommand)]
@@ -20,6 +25,20 @@ pub enum M2Commands {
         /// Show detailed information
         #[arg(short, long)]
         detailed: bool,
+
+        /// Listfile with `id;path` lines to show the paths of FileDataIDs
+        #[arg(long)]
+        listfile: Option<PathBuf>,
+
+        /// Also read the skin and animation files of the model, next to it
+        /// or in the source
+        #[arg(long)]
+        external: bool,
+
+        /// Read the model from a CASC installation, MPQ archive or directory
+        /// instead of the file system
+        #[arg(short, long)]
+        source: Option<String>,
     },
 
     // /// Validate an M2 model file
@@ -59,6 +78,10 @@ pub enum M2Commands {
         /// Include references
         #[arg(short, long)]
         refs: bool,
+
+        /// Listfile with `id;path` lines to show the paths of FileDataIDs
+        #[arg(long)]
+        listfile: Option<PathBuf>,
     },
 
     /// Display information about a Skin file
@@ -127,11 +150,81 @@ pub enum M2Commands {
         #[arg(short, long)]
         detailed: bool,
     },
+
+    /// Dump an M2 model as JSON or YAML
+    Dump {
+        /// Path to the M2 file
+        file: PathBuf,
+
+        /// Dump format
+        #[arg(short, long, value_enum, default_value_t = DumpFormat::Json)]
+        format: DumpFormat,
+
+        /// Write the dump to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Rebuild an M2 model from a JSON or YAML dump
+    Load {
+        /// Path to the dump, read as YAML for .yaml and .yml files and JSON otherwise
+        input: PathBuf,
+
+        /// Path to write the M2 file
+        output: PathBuf,
+    },
+
+    /// Dump a Skin file as JSON or YAML
+    SkinDump {
+        /// Path to the Skin file
+        file: PathBuf,
+
+        /// Parse version
+        #[arg(long)]
+        version: u32,
+
+        /// Dump format
+        #[arg(short, long, value_enum, default_value_t = DumpFormat::Json)]
+        format: DumpFormat,
+
+        /// Write the dump to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Rebuild a Skin file from a JSON or YAML dump
+    SkinLoad {
+        /// Path to the dump, read as YAML for .yaml and .yml files and JSON otherwise
+        input: PathBuf,
+
+        /// Path to write the Skin file
+        output: PathBuf,
+    },
+
+    /// Dump a Phys file as JSON or YAML
+    PhysDump {
+        /// Path to the Phys file
+        file: PathBuf,
+
+        /// Dump format
+        #[arg(short, long, value_enum, default_value_t = DumpFormat::Json)]
+        format: DumpFormat,
+
+        /// Write the dump to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
 }
 
 pub fn execute(cmd: M2Commands) -> Result<()> {
     match cmd {
-        M2Commands::Info { file, detailed } => handle_info(file, detailed),
+        M2Commands::Info {
+            file,
+            detailed,
+            listfile,
+            external,
+            source,
+        } => handle_info(file, detailed, listfile, external, source),
         // M2Commands::Convert {
         //     input,
         //     output,
@@ -143,7 +236,8 @@ pub fn execute(cmd: M2Commands) -> Result<()> {
             depth,
             size,
             refs,
-        } => handle_tree(file, depth, size, refs),
+            listfile,
+        } => handle_tree(file, depth, size, refs, listfile),
         M2Commands::SkinInfo {
             file,
             detailed,
@@ -162,19 +256,152 @@ pub fn execute(cmd: M2Commands) -> Result<()> {
         // } => handle_anim_convert(input, output, version),
         M2Commands::BlpInfo { file, detailed } => handle_blp_info(file, detailed),
         M2Commands::PhysInfo { file, detailed } => handle_phys_info(file, detailed),
+        M2Commands::Dump {
+            file,
+            format,
+            output,
+        } => handle_dump(file, format, output),
+        M2Commands::Load { input, output } => handle_load(input, output),
+        M2Commands::SkinDump {
+            file,
+            version,
+            format,
+            output,
+        } => handle_skin_dump(file, version, format, output),
+        M2Commands::SkinLoad { input, output } => handle_skin_load(input, output),
+        M2Commands::PhysDump {
+            file,
+            format,
+            output,
+        } => handle_phys_dump(file, format, output),
+    }
+}
+
+/// The files a model references, grouped by kind: texture names before
+/// Legion, FileDataIDs from the chunks of later models
+fn file_references(
+    model: &M2Model,
+    resolver: Option<&FileIdResolver>,
+) -> Vec<(&'static str, Vec<String>)> {
+    let texture_ids = model.texture_file_ids();
+    let textures = model
+        .md20
+        .textures
+        .iter()
+        .enumerate()
+        .map(
+            |(i, texture)| match texture_ids.and_then(|ids| ids.get(i)) {
+                Some(&id) if id != 0 => describe_file_id(id, resolver),
+                _ if texture.data.filename.is_empty() => {
+                    format!("<{:?}>", texture.header.texture_type)
+                }
+                _ => texture.data.filename.clone(),
+            },
+        )
+        .collect();
+
+    let mut references = vec![("Textures", textures)];
+    for chunk in &model.chunks {
+        let describe_all = |ids: &[u32]| -> Vec<String> {
+            ids.iter()
+                .map(|&id| describe_file_id(id, resolver))
+                .collect()
+        };
+        let (kind, files) = match chunk {
+            M2Chunk::SFID(files) => (
+                "Skin files",
+                describe_all(&[files.file_ids.as_slice(), &files.lod_file_ids].concat()),
+            ),
+            M2Chunk::AFID(files) => (
+                "Animation files",
+                files
+                    .iter()
+                    .filter(|file| file.file_id != 0)
+                    .map(|file| {
+                        format!(
+                            "{:04}-{:02}: {}",
+                            file.anim_id,
+                            file.sub_anim_id,
+                            describe_file_id(file.file_id, resolver)
+                        )
+                    })
+                    .collect(),
+            ),
+            M2Chunk::BFID(ids) => ("Bone files", describe_all(ids)),
+            M2Chunk::SKID(ids) => ("Skeleton files", describe_all(ids)),
+            M2Chunk::PFID(ids) => ("Physics files", describe_all(ids)),
+            M2Chunk::GPID(ids) => ("Geometry particle files", describe_all(ids)),
+            M2Chunk::RPID(ids) => ("Recursive particle files", describe_all(ids)),
+            _ => continue,
+        };
+        references.push((kind, files));
     }
+    references
 }
 
-fn handle_info(path: PathBuf, detailed: bool) -> Result<()> {
+fn handle_info(
+    path: PathBuf,
+    detailed: bool,
+    listfile: Option<PathBuf>,
+    external: bool,
+    source: Option<String>,
+) -> Result<()> {
     println!("Loading M2 model: {}", path.display());
 
-    let mut fp = File::open(path)?;
-    let model = M2Model::wow_read(&mut fp)?;
+    let resolver = load_listfile(listfile.as_deref())?;
+    let (provider, path) = open_file(&path.to_string_lossy(), source.as_deref())?;
+    let model = M2Model::wow_read(
+        &mut provider
+            .open(&path)
+            .with_context(|| format!("Failed to read model: {path}"))?,
+    )?;
 
     println!("\n=== M2 Model Information ===");
 
     println!("File loaded successfully!");
 
+    for (kind, files) in file_references(&model, resolver.as_ref()) {
+        if files.is_empty() {
+            continue;
+        }
+        println!("\n{kind}:");
+        for (i, file) in files.iter().enumerate() {
+            println!("  [{i}] {file}");
+        }
+    }
+
+    if external {
+        let skins = model
+            .load_skins(&*provider, &path)
+            .context("Failed to read skin files")?;
+        if !skins.is_empty() {
+            println!("\nSkin profiles:");
+            for (i, skin) in skins.iter().enumerate() {
+                println!(
+                    "  [{i}] {} triangles, {} submeshes, {} batches",
+                    skin.triangles.len() / 3,
+                    skin.submeshes.len(),
+                    skin.texture_units.len()
+                );
+            }
+        }
+
+        let anims = model
+            .load_anims(&*provider, &path)
+            .context("Failed to read animation files")?;
+        if !anims.is_empty() {
+            println!("\nAnimation file data:");
+            for anim in &anims {
+                println!(
+                    "  {:04}-{:02}: {} bytes",
+                    anim.animation_id,
+                    anim.sub_animation_id,
+                    anim.data.len()
+                );
+            }
+        }
+    }
+
     if detailed {
         println!("\n=== Detailed Information ===");
         println!("{:#?}", &model);
@@ -231,11 +458,29 @@ fn handle_info(path: PathBuf, detailed: bool) -> Result<()> {
 //     Ok(())
 // }
 
-fn handle_tree(path: PathBuf, max_depth: usize, _show_size: bool, _show_refs: bool) -> Result<()> {
+fn handle_tree(
+    path: PathBuf,
+    max_depth: usize,
+    _show_size: bool,
+    _show_refs: bool,
+    listfile: Option<PathBuf>,
+) -> Result<()> {
+    let resolver = load_listfile(listfile.as_deref())?;
     let mut fp = File::open(path)?;
-    let _model = M2Model::wow_read(&mut fp)?;
+    let model = M2Model::wow_read(&mut fp)?;
 
-    let root = TreeNode::new("M2 Model".to_string(), NodeType::Root);
+    let mut root = TreeNode::new("M2 Model".to_string(), NodeType::Root);
+    for (kind, files) in file_references(&model, resolver.as_ref()) {
+        if files.is_empty() {
+            continue;
+        }
+        let mut node = TreeNode::new(kind.to_string(), NodeType::Table)
+            .with_metadata("count", &files.len().to_string());
+        for file in files {
+            node = node.add_child(TreeNode::new(file, NodeType::File));
+        }
+        root = root.add_child(node);
+    }
 
     // Since most model fields are private, we can only show a basic structure
     // A real implementation would need the M2Model to expose more information
@@ -362,3 +607,62 @@ fn handle_phys_info(path: PathBuf, detailed: bool) -> Result<()> {
 
     Ok(())
 }
+
+fn handle_dump(path: PathBuf, format: DumpFormat, output: Option<PathBuf>) -> Result<()> {
+    let mut fp = File::open(&path)?;
+    let model = M2Model::wow_read(&mut fp)
+        .with_context(|| format!("Failed to parse M2 model: {}", path.display()))?;
+    write_dump(&model, format, output.as_deref())
+}
+
+fn handle_load(input: PathBuf, output: PathBuf) -> Result<()> {
+    let model: M2Model = read_dump(&input)?;
+
+    // Built in memory so that a failed write leaves no output file behind
+    let mut data = Cursor::new(Vec::new());
+    model
+        .wow_write(&mut data)
+        .with_context(|| format!("Failed to write M2 model: {}", output.display()))?;
+    std::fs::write(&output, data.into_inner())
+        .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+
+    println!("✓ Wrote {}", output.display());
+
+    Ok(())
+}
+
+fn handle_skin_dump(
+    path: PathBuf,
+    version: u32,
+    format: DumpFormat,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let version: SkinVersion = version.try_into()?;
+
+    let mut fp = File::open(&path)?;
+    let skin = Skin::wow_read(&mut fp, version)
+        .with_context(|| format!("Failed to parse Skin file: {}", path.display()))?;
+    write_dump(&skin, format, output.as_deref())
+}
+
+fn handle_skin_load(input: PathBuf, output: PathBuf) -> Result<()> {
+    let skin: Skin = read_dump(&input)?;
+
+    // Built in memory so that a failed write leaves no output file behind
+    let mut data = Cursor::new(Vec::new());
+    skin.wow_write(&mut data)
+        .with_context(|| format!("Failed to write Skin file: {}", output.display()))?;
+    std::fs::write(&output, data.into_inner())
+        .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+
+    println!("✓ Wrote {}", output.display());
+
+    Ok(())
+}
+
+fn handle_phys_dump(path: PathBuf, format: DumpFormat, output: Option<PathBuf>) -> Result<()> {
+    let mut fp = File::open(&path)?;
+    let phys = PhysFile::wow_read(&mut fp)
+        .with_context(|| format!("Failed to parse Phys file: {}", path.display()))?;
+    write_dump(&phys, format, output.as_deref())
+}
@@ -18,3 +18,16 @@ pub mod wdt;
 
 #[cfg(feature = "wdl")]
 pub mod wdl;
+
+#[cfg(feature = "mpq")]
+pub mod mpq;
+
+#[cfg(any(
+    feature = "blp",
+    feature = "m2",
+    feature = "wmo",
+    feature = "adt",
+    feature = "wdt",
+    feature = "wdl"
+))]
+pub mod roundtrip;
@@ -0,0 +1,427 @@
+//! MPQ archive command implementations
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use wow_alchemy_mpq::compression::{COMPRESSION_BZIP2, COMPRESSION_PKWARE, COMPRESSION_ZLIB};
+use wow_alchemy_mpq::{Archive, ArchiveWriter, FileFlags, FileOptions, FormatVersion};
+
+use crate::utils::table::{add_table_row, create_table};
+
+#[derive(Subcommand)]
+pub enum MpqCommands {
+    /// List the files of an archive from its (listfile)
+    List {
+        archive: PathBuf,
+        /// Show the sizes, flags and locales of the files
+        #[arg(short, long)]
+        long: bool,
+    },
+    /// Extract files of an archive to a directory
+    Extract {
+        archive: PathBuf,
+        output_dir: PathBuf,
+        /// Names or glob patterns like "DBFilesClient/*.dbc" of the files to extract,
+        /// all files of the (listfile) by default
+        files: Vec<String>,
+    },
+    /// Create an archive from the files of a directory, named by their path in it
+    Create {
+        dir: PathBuf,
+        output: PathBuf,
+        /// Format version of the archive, v1 for clients before Cataclysm
+        #[arg(long, value_enum, default_value_t = MpqVersion::V1)]
+        version: MpqVersion,
+        #[command(flatten)]
+        options: WriteOptions,
+    },
+    /// Add files or directories to an archive, replacing files of the same name
+    Add {
+        archive: PathBuf,
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Directory the names in the archive are relative to, the current directory
+        /// by default
+        #[arg(long)]
+        base: Option<PathBuf>,
+        #[command(flatten)]
+        options: WriteOptions,
+    },
+    /// Remove files from an archive
+    Remove {
+        archive: PathBuf,
+        #[arg(required = true)]
+        names: Vec<String>,
+        /// Add delete markers, which hide the files of lower archives of a patch chain
+        #[arg(long)]
+        marker: bool,
+    },
+    /// Rewrite an archive without unused space
+    Compact {
+        archive: PathBuf,
+        /// Write the compacted archive to this file instead of replacing the archive
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+pub struct WriteOptions {
+    /// Compression of files without a --type rule
+    #[arg(long, value_enum, default_value_t = Compression::Zlib)]
+    compression: Compression,
+    /// Compression of the files with an extension, like "blp=zlib" or "ogg=none"
+    #[arg(long = "type", value_name = "EXT=METHOD")]
+    types: Vec<String>,
+    /// Size of the sectors files are split into, a power of two from 512
+    #[arg(long)]
+    sector_size: Option<usize>,
+    /// Encrypt the added files
+    #[arg(long)]
+    encrypt: bool,
+    /// Don't write a (listfile), which is needed to extract or modify the archive later
+    #[arg(long)]
+    no_listfile: bool,
+    /// Don't write (attributes) with the CRC32, MD5 and time of the files
+    #[arg(long)]
+    no_attributes: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum MpqVersion {
+    V1,
+    V2,
+    V3,
+    V4,
+}
+
+impl From<MpqVersion> for FormatVersion {
+    fn from(value: MpqVersion) -> Self {
+        match value {
+            MpqVersion::V1 => FormatVersion::V1,
+            MpqVersion::V2 => FormatVersion::V2,
+            MpqVersion::V3 => FormatVersion::V3,
+            MpqVersion::V4 => FormatVersion::V4,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Compression {
+    None,
+    Zlib,
+    Bzip2,
+    Pkware,
+}
+
+impl Compression {
+    fn options(self, encrypt: bool) -> FileOptions {
+        let options = match self {
+            Compression::None => FileOptions::stored(),
+            Compression::Zlib => FileOptions::compressed(COMPRESSION_ZLIB),
+            Compression::Bzip2 => FileOptions::compressed(COMPRESSION_BZIP2),
+            Compression::Pkware => FileOptions::compressed(COMPRESSION_PKWARE),
+        };
+        if encrypt {
+            options.encrypted()
+        } else {
+            options
+        }
+    }
+}
+
+pub fn execute(command: MpqCommands) -> Result<()> {
+    match command {
+        MpqCommands::List { archive, long } => list_command(&archive, long),
+        MpqCommands::Extract {
+            archive,
+            output_dir,
+            files,
+        } => extract_command(&archive, &output_dir, &files),
+        MpqCommands::Create {
+            dir,
+            output,
+            version,
+            options,
+        } => create_command(&dir, &output, version.into(), &options),
+        MpqCommands::Add {
+            archive,
+            paths,
+            base,
+            options,
+        } => add_command(&archive, &paths, base.as_deref(), &options),
+        MpqCommands::Remove {
+            archive,
+            names,
+            marker,
+        } => remove_command(&archive, &names, marker),
+        MpqCommands::Compact { archive, output } => compact_command(&archive, output.as_deref()),
+    }
+}
+
+fn open_archive(path: &Path) -> Result<Archive<std::io::BufReader<fs::File>>> {
+    Archive::open(path).with_context(|| format!("Failed to open archive: {}", path.display()))
+}
+
+fn load_archive(path: &Path) -> Result<ArchiveWriter> {
+    let mut archive = open_archive(path)?;
+    ArchiveWriter::from_archive(&mut archive)
+        .with_context(|| format!("Failed to load the files of {}", path.display()))
+}
+
+/// Write an archive next to its destination and move it there, so a
+/// failed write doesn't lose the archive it replaces
+fn save_archive(writer: &ArchiveWriter, path: &Path) -> Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    writer
+        .save(&temp)
+        .with_context(|| format!("Failed to write archive: {}", temp.display()))?;
+    fs::rename(&temp, path)
+        .with_context(|| format!("Failed to replace archive: {}", path.display()))?;
+    Ok(())
+}
+
+fn apply_options(writer: &mut ArchiveWriter, options: &WriteOptions) -> Result<()> {
+    writer.set_default_options(options.compression.options(options.encrypt));
+    for rule in &options.types {
+        let (extension, method) = rule
+            .split_once('=')
+            .with_context(|| format!("Expected EXT=METHOD instead of {rule}"))?;
+        let method = Compression::from_str(method, true)
+            .map_err(|e| anyhow::anyhow!("Invalid compression of {extension}: {e}"))?;
+        writer.set_file_type(extension, method.options(options.encrypt));
+    }
+    if let Some(size) = options.sector_size {
+        writer.set_sector_size(size)?;
+    }
+    writer.set_listfile(!options.no_listfile);
+    writer.set_attributes(!options.no_attributes);
+    Ok(())
+}
+
+/// Files in a directory and its subdirectories, sorted by path
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Name in an archive of a file relative to a directory
+fn archive_name(path: &Path, base: &Path) -> Result<String> {
+    let relative = path.strip_prefix(base).with_context(|| {
+        format!(
+            "{} is not in the base directory {}",
+            path.display(),
+            base.display()
+        )
+    })?;
+    Ok(relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("\\"))
+}
+
+fn add_paths(writer: &mut ArchiveWriter, paths: &[PathBuf], base: &Path) -> Result<usize> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            collect_files(path, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+    for file in &files {
+        let name = archive_name(file, base)?;
+        writer
+            .add_path(&name, file)
+            .with_context(|| format!("Failed to add {}", file.display()))?;
+        log::info!("Added {name}");
+    }
+    Ok(files.len())
+}
+
+fn describe_flags(flags: FileFlags) -> String {
+    [
+        (FileFlags::IMPLODE, "implode"),
+        (FileFlags::COMPRESS, "compressed"),
+        (FileFlags::ENCRYPTED, "encrypted"),
+        (FileFlags::PATCH_FILE, "patch"),
+        (FileFlags::SINGLE_UNIT, "single unit"),
+        (FileFlags::DELETE_MARKER, "deleted"),
+        (FileFlags::SECTOR_CRC, "crc"),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| flags.contains(*flag))
+    .map(|(_, name)| name)
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+fn list_command(path: &Path, long: bool) -> Result<()> {
+    let mut archive = open_archive(path)?;
+    let names = archive.list_files()?;
+    if names.is_empty() && archive.find_file("(listfile)").is_none() {
+        log::warn!("{} has no (listfile)", path.display());
+    }
+
+    if !long {
+        for name in &names {
+            println!("{name}");
+        }
+        return Ok(());
+    }
+
+    let mut table = create_table(vec!["Name", "Size", "Packed", "Flags", "Locale"]);
+    for name in &names {
+        for (locale, index) in archive.find_locales(name) {
+            let entry = archive.files()[index];
+            add_table_row(
+                &mut table,
+                vec![
+                    name.clone(),
+                    entry.file_size.to_string(),
+                    entry.compressed_size.to_string(),
+                    describe_flags(entry.flags),
+                    format!("{locale:#06x}"),
+                ],
+            );
+        }
+    }
+    table.printstd();
+    println!(
+        "{} files, {:?}, {} byte sectors",
+        names.len(),
+        archive.header().format_version,
+        archive.header().sector_size()
+    );
+    Ok(())
+}
+
+fn extract_command(path: &Path, output_dir: &Path, filters: &[String]) -> Result<()> {
+    let mut archive = open_archive(path)?;
+
+    let mut names = Vec::new();
+    let mut patterns = Vec::new();
+    for filter in filters {
+        if filter.contains(['*', '?', '[']) {
+            patterns.push(glob::Pattern::new(&filter.replace('\\', "/"))?);
+        } else {
+            names.push(filter.replace('/', "\\"));
+        }
+    }
+    if filters.is_empty() || !patterns.is_empty() {
+        let options = glob::MatchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        };
+        names.extend(archive.list_files()?.into_iter().filter(|name| {
+            filters.is_empty()
+                || patterns
+                    .iter()
+                    .any(|p| p.matches_with(&name.replace('\\', "/"), options))
+        }));
+    }
+
+    for name in &names {
+        let data = archive
+            .read_file(name)
+            .with_context(|| format!("Failed to read {name}"))?;
+        let output = output_dir.join(name.replace('\\', "/"));
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&output, data)
+            .with_context(|| format!("Failed to write {}", output.display()))?;
+        log::info!("Extracted {name}");
+    }
+    println!(
+        "Extracted {} files to {}",
+        names.len(),
+        output_dir.display()
+    );
+    Ok(())
+}
+
+fn create_command(
+    dir: &Path,
+    output: &Path,
+    version: FormatVersion,
+    options: &WriteOptions,
+) -> Result<()> {
+    let mut writer = ArchiveWriter::new(version);
+    apply_options(&mut writer, options)?;
+    let count = add_paths(&mut writer, &[dir.to_path_buf()], dir)?;
+    writer
+        .save(output)
+        .with_context(|| format!("Failed to write archive: {}", output.display()))?;
+    println!("Created {} with {count} files", output.display());
+    Ok(())
+}
+
+fn add_command(
+    path: &Path,
+    paths: &[PathBuf],
+    base: Option<&Path>,
+    options: &WriteOptions,
+) -> Result<()> {
+    let mut writer = load_archive(path)?;
+    apply_options(&mut writer, options)?;
+
+    let current_dir = std::env::current_dir()?;
+    let base = base.map_or(current_dir.clone(), |base| current_dir.join(base));
+    let paths = paths
+        .iter()
+        .map(|p| current_dir.join(p))
+        .collect::<Vec<_>>();
+    let count = add_paths(&mut writer, &paths, &base)?;
+    save_archive(&writer, path)?;
+    println!("Added {count} files to {}", path.display());
+    Ok(())
+}
+
+fn remove_command(path: &Path, names: &[String], marker: bool) -> Result<()> {
+    let mut writer = load_archive(path)?;
+    for name in names {
+        if marker {
+            writer.add_delete_marker(name);
+        } else if !writer.remove(name) {
+            anyhow::bail!("File not found: {name}");
+        }
+    }
+    save_archive(&writer, path)?;
+    if marker {
+        println!("Marked {} files as deleted", names.len());
+    } else {
+        println!("Removed {} files", names.len());
+    }
+    Ok(())
+}
+
+fn compact_command(path: &Path, output: Option<&Path>) -> Result<()> {
+    let old_size = fs::metadata(path)?.len();
+    let writer = load_archive(path)?;
+    let output = output.unwrap_or(path);
+    save_archive(&writer, output)?;
+    println!(
+        "Compacted {} from {} to {} bytes",
+        path.display(),
+        old_size,
+        fs::metadata(output)?.len()
+    );
+    Ok(())
+}
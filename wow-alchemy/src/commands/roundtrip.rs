@@ -0,0 +1,118 @@
+//! Checking that files are written back identically to how they were read
+
+use anyhow::{Context, Result, bail};
+use console::style;
+use std::fs;
+use std::path::{Path, PathBuf};
+use wow_alchemy_data::game_version::GameVersion;
+
+use crate::utils::dump::DumpFormat;
+use crate::utils::roundtrip::RoundTripFormat;
+
+#[derive(Debug, Default)]
+struct Summary {
+    identical: usize,
+    different: usize,
+    failed: usize,
+    skipped: usize,
+}
+
+pub fn execute(
+    path: PathBuf,
+    version: GameVersion,
+    show_identical: bool,
+    dump: Option<DumpFormat>,
+) -> Result<()> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        collect_files(&path, &mut files)?;
+    } else {
+        files.push(path);
+    }
+
+    let mut summary = Summary::default();
+    for file in &files {
+        let data =
+            fs::read(file).with_context(|| format!("Failed to read file: {}", file.display()))?;
+        let Some(format) = RoundTripFormat::detect(file, &data) else {
+            summary.skipped += 1;
+            continue;
+        };
+
+        let result = match dump {
+            #[cfg(feature = "blp")]
+            Some(_) if format == RoundTripFormat::Blp => {
+                summary.skipped += 1;
+                continue;
+            }
+            Some(dump) => format.verify_dump(file, &data, version, dump),
+            None => format.verify(file, &data, version),
+        };
+        match result {
+            Ok(None) => {
+                summary.identical += 1;
+                if show_identical {
+                    println!(
+                        "{} {} ({})",
+                        style("✓").green(),
+                        file.display(),
+                        format.name()
+                    );
+                }
+            }
+            Ok(Some(mismatch)) => {
+                summary.different += 1;
+                println!(
+                    "{} {} ({}): {mismatch}",
+                    style("✗").red(),
+                    file.display(),
+                    format.name()
+                );
+            }
+            Err(e) => {
+                summary.failed += 1;
+                println!(
+                    "{} {} ({}): {e:#}",
+                    style("!").yellow(),
+                    file.display(),
+                    format.name()
+                );
+            }
+        }
+    }
+
+    println!(
+        "\nChecked {} files: {} identical, {} different, {} failed, {} skipped",
+        files.len() - summary.skipped,
+        summary.identical,
+        summary.different,
+        summary.failed,
+        summary.skipped
+    );
+
+    if summary.different + summary.failed > 0 {
+        bail!(
+            "{} files did not round-trip",
+            summary.different + summary.failed
+        );
+    }
+
+    Ok(())
+}
+
+/// Files in a directory and its subdirectories, sorted by path
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -3,13 +3,16 @@
 use anyhow::{Context, Result};
 use clap::Subcommand;
 use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::io::{BufReader, BufWriter, Cursor};
 use std::path::PathBuf;
 
+use wow_alchemy_data::game_version::GameVersion;
 use wow_alchemy_wdl::parser::WdlParser;
+use wow_alchemy_wdl::types::WdlFile;
 use wow_alchemy_wdl::validation::validate_wdl_file;
 use wow_alchemy_wdl::version::WdlVersion;
 
+use crate::utils::dump::{DumpFormat, read_dump, write_dump};
 use crate::utils::{NodeType, TreeNode, TreeOptions, detect_ref_type, render_tree};
 
 #[derive(Subcommand)]
@@ -25,9 +28,9 @@ pub enum WdlCommands {
         /// Path to the WDL file
         file: PathBuf,
 
-        /// Explicitly specify the WDL version to validate against (e.g., "WotLK", "TBC", "MoP", "Legion")
+        /// Explicitly specify the WDL version to validate against (e.g., "3.3.5.12340", "3.3.5a", "WotLK", "Legion")
         #[arg(long, value_name = "VERSION")]
-        version: Option<String>,
+        version: Option<GameVersion>,
     },
 
     /// Convert a WDL file from one version to another
@@ -40,11 +43,11 @@ pub enum WdlCommands {
 
         /// Source version (if not auto-detected, e.g., "WotLK", "TBC", "MoP", "Legion")
         #[arg(long, value_name = "VERSION")]
-        from: Option<String>,
+        from: Option<GameVersion>,
 
-        /// Target version (e.g., "WotLK", "TBC", "MoP", "Legion")
+        /// Target version (e.g., "3.3.5.12340", "3.3.5a", "WotLK", "Legion")
         #[arg(short, long, value_name = "VERSION")]
-        to: String,
+        to: GameVersion,
     },
 
     /// Show tree structure of a WDL file
@@ -52,9 +55,9 @@ pub enum WdlCommands {
         /// Path to the WDL file
         file: PathBuf,
 
-        /// WDL version (e.g., "WotLK", "TBC", "MoP", "Legion")
+        /// WDL version (e.g., "3.3.5.12340", "3.3.5a", "WotLK", "Legion")
         #[arg(long, default_value = "WotLK")]
-        version: String,
+        version: GameVersion,
 
         /// Maximum depth to display
         #[arg(long)]
@@ -72,39 +75,29 @@ pub enum WdlCommands {
         #[arg(long)]
         compact: bool,
     },
-}
 
-/// Maps a version string to a WdlVersion
-/// Supports both expansion short names and numeric versions
-fn parse_version(version_str: &str) -> Result<WdlVersion> {
-    match version_str.to_lowercase().as_str() {
-        "vanilla" | "classic" => Ok(WdlVersion::Vanilla),
-        "tbc" | "bc" | "burningcrusade" | "burning_crusade" => Ok(WdlVersion::Vanilla), // TBC uses same format as Vanilla
-        "wotlk" | "wrath" | "lichking" | "lich_king" | "wlk" => Ok(WdlVersion::Wotlk),
-        "cata" | "cataclysm" => Ok(WdlVersion::Cataclysm),
-        "mop" | "pandaria" | "mists" | "mists_of_pandaria" => Ok(WdlVersion::Mop),
-        "wod" | "draenor" | "warlords" | "warlords_of_draenor" => Ok(WdlVersion::Wod),
-        "legion" => Ok(WdlVersion::Legion),
-        "bfa" | "bfazeroth" | "battle_for_azeroth" | "battleforazeroth" => Ok(WdlVersion::Bfa),
-        "sl" | "shadowlands" => Ok(WdlVersion::Shadowlands),
-        "df" | "dragonflight" => Ok(WdlVersion::Dragonflight),
-        "latest" => Ok(WdlVersion::Latest),
-        _ => {
-            // Try parsing as numeric version for compatibility
-            match version_str.split('.').next().unwrap_or("").parse::<u32>() {
-                Ok(1) | Ok(2) => Ok(WdlVersion::Vanilla), // 1.x (Classic) and 2.x (TBC)
-                Ok(3) => Ok(WdlVersion::Wotlk),           // 3.x (WotLK)
-                Ok(4) => Ok(WdlVersion::Cataclysm),       // 4.x (Cataclysm)
-                Ok(5) => Ok(WdlVersion::Mop),             // 5.x (MoP)
-                Ok(6) => Ok(WdlVersion::Wod),             // 6.x (WoD)
-                Ok(7) => Ok(WdlVersion::Legion),          // 7.x (Legion)
-                Ok(8) => Ok(WdlVersion::Bfa),             // 8.x (BfA)
-                Ok(9) => Ok(WdlVersion::Shadowlands),     // 9.x (Shadowlands)
-                Ok(10) => Ok(WdlVersion::Dragonflight),   // 10.x (Dragonflight)
-                _ => anyhow::bail!("Unknown version: {}", version_str),
-            }
-        }
-    }
+    /// Dump a WDL file as JSON or YAML
+    Dump {
+        /// Path to the WDL file
+        file: PathBuf,
+
+        /// Dump format
+        #[arg(short, long, value_enum, default_value_t = DumpFormat::Json)]
+        format: DumpFormat,
+
+        /// Write the dump to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Rebuild a WDL file from a JSON or YAML dump
+    Load {
+        /// Path to the dump, read as YAML for .yaml and .yml files and JSON otherwise
+        input: PathBuf,
+
+        /// Path to write the WDL file
+        output: PathBuf,
+    },
 }
 
 pub fn execute(command: WdlCommands) -> Result<()> {
@@ -125,10 +118,16 @@ pub fn execute(command: WdlCommands) -> Result<()> {
             no_color,
             compact,
         } => execute_tree(file, version, depth, !no_external_refs, no_color, compact),
+        WdlCommands::Dump {
+            file,
+            format,
+            output,
+        } => execute_dump(file, format, output),
+        WdlCommands::Load { input, output } => execute_load(input, output),
     }
 }
 
-fn execute_validate(path: PathBuf, version: Option<String>) -> Result<()> {
+fn execute_validate(path: PathBuf, version: Option<GameVersion>) -> Result<()> {
     use console::style;
 
     let file =
@@ -137,9 +136,8 @@ fn execute_validate(path: PathBuf, version: Option<String>) -> Result<()> {
     let mut reader = BufReader::new(file);
 
     // Create parser with specified version if provided
-    let parser = if let Some(version_str) = version {
-        let version = parse_version(&version_str)?;
-        WdlParser::with_version(version)
+    let parser = if let Some(version) = version {
+        WdlParser::with_version(version.into())
     } else {
         WdlParser::new()
     };
@@ -169,8 +167,8 @@ fn execute_validate(path: PathBuf, version: Option<String>) -> Result<()> {
 fn execute_convert(
     input: PathBuf,
     output: PathBuf,
-    from: Option<String>,
-    to: String,
+    from: Option<GameVersion>,
+    to: GameVersion,
 ) -> Result<()> {
     use crate::utils::progress::create_progress_bar;
     use console::style;
@@ -182,9 +180,8 @@ fn execute_convert(
     let mut reader = BufReader::new(input_file);
 
     // Create parser with specified source version if provided
-    let parser = if let Some(version_str) = from {
-        let version = parse_version(&version_str)?;
-        WdlParser::with_version(version)
+    let parser = if let Some(version) = from {
+        WdlParser::with_version(version.into())
     } else {
         WdlParser::new()
     };
@@ -196,7 +193,7 @@ fn execute_convert(
         .with_context(|| format!("Failed to parse WDL file: {}", input.display()))?;
 
     // Parse the target version
-    let target_version = parse_version(&to)?;
+    let target_version = WdlVersion::from(to);
 
     // Show progress for conversion
     let pb = create_progress_bar(100, "Converting WDL file");
@@ -231,6 +228,33 @@ fn execute_convert(
     Ok(())
 }
 
+fn execute_dump(path: PathBuf, format: DumpFormat, output: Option<PathBuf>) -> Result<()> {
+    let file =
+        File::open(&path).with_context(|| format!("Failed to open file: {}", path.display()))?;
+
+    let wdl_file = WdlParser::new()
+        .parse(&mut BufReader::new(file))
+        .with_context(|| format!("Failed to parse WDL file: {}", path.display()))?;
+
+    write_dump(&wdl_file, format, output.as_deref())
+}
+
+fn execute_load(input: PathBuf, output: PathBuf) -> Result<()> {
+    let wdl_file: WdlFile = read_dump(&input)?;
+
+    // Built in memory so that a failed write leaves no output file behind
+    let mut data = Cursor::new(Vec::new());
+    WdlParser::with_version(wdl_file.version)
+        .write(&mut data, &wdl_file)
+        .with_context(|| format!("Failed to write WDL file: {}", output.display()))?;
+    std::fs::write(&output, data.into_inner())
+        .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+
+    println!("✓ Wrote {}", output.display());
+
+    Ok(())
+}
+
 fn execute_info(path: PathBuf) -> Result<()> {
     use crate::utils::table::create_table;
     use console::style;
@@ -348,13 +372,13 @@ fn execute_info(path: PathBuf) -> Result<()> {
 
 fn execute_tree(
     path: PathBuf,
-    version_str: String,
+    version: GameVersion,
     depth: Option<usize>,
     show_external_refs: bool,
     no_color: bool,
     compact: bool,
 ) -> Result<()> {
-    let version = parse_version(&version_str).context("Invalid version string")?;
+    let version = WdlVersion::from(version);
 
     let file = File::open(&path).context("Failed to open WDL file")?;
     let mut reader = BufReader::new(file);
@@ -6,13 +6,16 @@ use std::fs::File;
 use std::io::{BufReader, BufWriter};
 use std::path::PathBuf;
 
+use wow_alchemy_data::game_version::GameVersion;
 use wow_alchemy_wdt::{
-    WdtReader, WdtWriter,
-    chunks::{Chunk, MphdFlags},
+    WdtFile, WdtReader, WdtWriter,
+    chunks::{Chunk, MaidSection, MphdFlags},
     conversion::{convert_wdt, get_conversion_summary},
     version::WowVersion,
 };
 
+use crate::utils::dump::{DumpFormat, read_dump, write_dump};
+use crate::utils::listfile::{describe_file_id, load_listfile};
 use crate::utils::{NodeType, TreeNode, TreeOptions, detect_ref_type, render_tree};
 
 #[derive(Subcommand)]
@@ -22,13 +25,17 @@ pub enum WdtCommands {
         /// Path to the WDT file
         file: PathBuf,
 
-        /// WoW version (e.g., "1.12.1", "3.3.5a", "WotLK", "TBC", "MoP")
+        /// WoW version (e.g., "3.3.5.12340", "3.3.5a", "WotLK", "MoP")
         #[arg(long, default_value = "WotLK")]
-        version: String,
+        version: GameVersion,
 
         /// Show detailed chunk information
         #[arg(short, long)]
         detailed: bool,
+
+        /// Listfile with `id;path` lines to show the paths of FileDataIDs
+        #[arg(long)]
+        listfile: Option<PathBuf>,
     },
 
     /// Validate a WDT file
@@ -36,9 +43,9 @@ pub enum WdtCommands {
         /// Path to the WDT file
         file: PathBuf,
 
-        /// WoW version (e.g., "1.12.1", "3.3.5a", "WotLK", "TBC", "MoP")
+        /// WoW version (e.g., "3.3.5.12340", "3.3.5a", "WotLK", "MoP")
         #[arg(long, default_value = "WotLK")]
-        version: String,
+        version: GameVersion,
 
         /// Show all warnings (not just errors)
         #[arg(short, long)]
@@ -53,17 +60,22 @@ pub enum WdtCommands {
         /// Output WDT file
         output: PathBuf,
 
-        /// Source WoW version (e.g., "1.12.1", "3.3.5a", "WotLK", "TBC", "MoP")
+        /// Source WoW version (e.g., "3.3.5.12340", "3.3.5a", "WotLK", "MoP")
         #[arg(short = 'f', long)]
-        from_version: String,
+        from_version: GameVersion,
 
-        /// Target WoW version (e.g., "1.12.1", "3.3.5a", "WotLK", "TBC", "MoP")
+        /// Target WoW version (e.g., "3.3.5.12340", "3.3.5a", "WotLK", "MoP")
         #[arg(short = 't', long)]
-        to_version: String,
+        to_version: GameVersion,
 
         /// Preview changes without writing
         #[arg(short, long)]
         preview: bool,
+
+        /// Listfile with `id;path` lines to fill the FileDataIDs of the map
+        /// files when converting to BfA or later
+        #[arg(long)]
+        listfile: Option<PathBuf>,
     },
 
     /// List all tiles with ADT data
@@ -71,9 +83,9 @@ pub enum WdtCommands {
         /// Path to the WDT file
         file: PathBuf,
 
-        /// WoW version (e.g., "1.12.1", "3.3.5a", "WotLK", "TBC", "MoP")
+        /// WoW version (e.g., "3.3.5.12340", "3.3.5a", "WotLK", "MoP")
         #[arg(long, default_value = "WotLK")]
-        version: String,
+        version: GameVersion,
 
         /// Output format (text, json, csv)
         #[arg(short, long, default_value = "text")]
@@ -85,9 +97,9 @@ pub enum WdtCommands {
         /// Path to the WDT file
         file: PathBuf,
 
-        /// WoW version (e.g., "1.12.1", "3.3.5a", "WotLK", "TBC", "MoP")
+        /// WoW version (e.g., "3.3.5.12340", "3.3.5a", "WotLK", "MoP")
         #[arg(long, default_value = "WotLK")]
-        version: String,
+        version: GameVersion,
 
         /// Maximum depth to display
         #[arg(long)]
@@ -104,6 +116,37 @@ pub enum WdtCommands {
         /// Show compact metadata inline
         #[arg(long)]
         compact: bool,
+
+        /// Listfile with `id;path` lines to show the paths of FileDataIDs
+        #[arg(long)]
+        listfile: Option<PathBuf>,
+    },
+
+    /// Dump a WDT file as JSON or YAML
+    Dump {
+        /// Path to the WDT file
+        file: PathBuf,
+
+        /// WoW version (e.g., "3.3.5.12340", "3.3.5a", "WotLK", "MoP")
+        #[arg(long, default_value = "WotLK")]
+        version: GameVersion,
+
+        /// Dump format
+        #[arg(short, long, value_enum, default_value_t = DumpFormat::Json)]
+        format: DumpFormat,
+
+        /// Write the dump to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Rebuild a WDT file from a JSON or YAML dump
+    Load {
+        /// Path to the dump, read as YAML for .yaml and .yml files and JSON otherwise
+        input: PathBuf,
+
+        /// Path to write the WDT file
+        output: PathBuf,
     },
 }
 
@@ -113,7 +156,8 @@ pub fn execute(command: WdtCommands) -> Result<()> {
             file,
             version,
             detailed,
-        } => execute_info(file, version, detailed),
+            listfile,
+        } => execute_info(file, version, detailed, listfile),
         WdtCommands::Validate {
             file,
             version,
@@ -125,7 +169,8 @@ pub fn execute(command: WdtCommands) -> Result<()> {
             from_version,
             to_version,
             preview,
-        } => execute_convert(input, output, from_version, to_version, preview),
+            listfile,
+        } => execute_convert(input, output, from_version, to_version, preview, listfile),
         WdtCommands::Tiles {
             file,
             version,
@@ -138,15 +183,71 @@ pub fn execute(command: WdtCommands) -> Result<()> {
             no_external_refs,
             no_color,
             compact,
-        } => execute_tree(file, version, depth, !no_external_refs, no_color, compact),
+            listfile,
+        } => execute_tree(
+            file,
+            version,
+            depth,
+            !no_external_refs,
+            no_color,
+            compact,
+            listfile,
+        ),
+        WdtCommands::Dump {
+            file,
+            version,
+            format,
+            output,
+        } => execute_dump(file, version, format, output),
+        WdtCommands::Load { input, output } => execute_load(input, output),
     }
 }
 
-fn execute_info(path: PathBuf, version_str: String, detailed: bool) -> Result<()> {
+fn execute_dump(
+    path: PathBuf,
+    version: GameVersion,
+    format: DumpFormat,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let file = File::open(&path).context("Failed to open WDT file")?;
+    let mut reader = WdtReader::new(BufReader::new(file), WowVersion::from(version));
+    let wdt = reader.read().context("Failed to parse WDT file")?;
+
+    write_dump(&wdt, format, output.as_deref())
+}
+
+fn execute_load(input: PathBuf, output: PathBuf) -> Result<()> {
+    use console::style;
+
+    let wdt: WdtFile = read_dump(&input)?;
+
+    // Built in memory so that a failed write leaves no output file behind
+    let mut data = std::io::Cursor::new(Vec::new());
+    WdtWriter::new(&mut data)
+        .write(&wdt)
+        .context("Failed to write output file")?;
+    std::fs::write(&output, data.into_inner()).context("Failed to create output file")?;
+
+    println!("{} Wrote {}", style("✓").green(), output.display());
+
+    Ok(())
+}
+
+fn execute_info(
+    path: PathBuf,
+    version: GameVersion,
+    detailed: bool,
+    listfile: Option<PathBuf>,
+) -> Result<()> {
     use console::style;
 
-    let version =
-        WowVersion::from_expansion_name(&version_str).context("Invalid version string")?;
+    let resolver = load_listfile(listfile.as_deref())?;
+    let describe = |file_data_id: Option<u32>| match file_data_id {
+        Some(file_data_id) => describe_file_id(file_data_id, resolver.as_ref()),
+        None => "None".to_string(),
+    };
+
+    let version = WowVersion::from(version);
 
     println!("{}", style("WDT File Information").bold().cyan());
     println!("{}", style("===================").cyan());
@@ -203,13 +304,16 @@ fn execute_info(path: PathBuf, version_str: String, detailed: bool) -> Result<()
         println!("\n{} (32 bytes)", style("MPHD").yellow());
         println!("  Flags: 0x{:08X}", wdt.mphd.flags.bits());
         if wdt.mphd.has_maid() {
-            println!("  LGT FileDataID: {:?}", wdt.mphd.lgt_file_data_id);
-            println!("  OCC FileDataID: {:?}", wdt.mphd.occ_file_data_id);
-            println!("  FOGS FileDataID: {:?}", wdt.mphd.fogs_file_data_id);
-            println!("  MPV FileDataID: {:?}", wdt.mphd.mpv_file_data_id);
-            println!("  TEX FileDataID: {:?}", wdt.mphd.tex_file_data_id);
-            println!("  WDL FileDataID: {:?}", wdt.mphd.wdl_file_data_id);
-            println!("  PD4 FileDataID: {:?}", wdt.mphd.pd4_file_data_id);
+            println!("  LGT FileDataID: {}", describe(wdt.mphd.lgt_file_data_id));
+            println!("  OCC FileDataID: {}", describe(wdt.mphd.occ_file_data_id));
+            println!(
+                "  FOGS FileDataID: {}",
+                describe(wdt.mphd.fogs_file_data_id)
+            );
+            println!("  MPV FileDataID: {}", describe(wdt.mphd.mpv_file_data_id));
+            println!("  TEX FileDataID: {}", describe(wdt.mphd.tex_file_data_id));
+            println!("  WDL FileDataID: {}", describe(wdt.mphd.wdl_file_data_id));
+            println!("  PD4 FileDataID: {}", describe(wdt.mphd.pd4_file_data_id));
         }
 
         // MAIN details
@@ -221,6 +325,16 @@ fn execute_info(path: PathBuf, version_str: String, detailed: bool) -> Result<()
             println!("\n{} ({} bytes)", style("MAID").yellow(), maid.size());
             println!("  Sections: {}", maid.section_count());
             println!("  Tiles with data: {}", maid.count_existing_tiles());
+            for y in 0..64 {
+                for x in 0..64 {
+                    match maid.get(MaidSection::RootAdt, x, y) {
+                        Some(file_data_id) if file_data_id != 0 => {
+                            println!("    [{x:02},{y:02}] {}", describe(Some(file_data_id)));
+                        }
+                        _ => {}
+                    }
+                }
+            }
         }
 
         // MWMO details
@@ -265,11 +379,10 @@ fn execute_info(path: PathBuf, version_str: String, detailed: bool) -> Result<()
     Ok(())
 }
 
-fn execute_validate(path: PathBuf, version_str: String, show_warnings: bool) -> Result<()> {
+fn execute_validate(path: PathBuf, version: GameVersion, show_warnings: bool) -> Result<()> {
     use console::style;
 
-    let version =
-        WowVersion::from_expansion_name(&version_str).context("Invalid version string")?;
+    let version = WowVersion::from(version);
 
     println!("{}", style("Validating WDT File").bold().cyan());
     println!("{}", style("==================").cyan());
@@ -324,15 +437,15 @@ fn execute_validate(path: PathBuf, version_str: String, show_warnings: bool) ->
 fn execute_convert(
     input: PathBuf,
     output: PathBuf,
-    from_str: String,
-    to_str: String,
+    from_version: GameVersion,
+    to_version: GameVersion,
     preview: bool,
+    listfile: Option<PathBuf>,
 ) -> Result<()> {
     use console::style;
 
-    let from_version =
-        WowVersion::from_expansion_name(&from_str).context("Invalid source version")?;
-    let to_version = WowVersion::from_expansion_name(&to_str).context("Invalid target version")?;
+    let from_version = WowVersion::from(from_version);
+    let to_version = WowVersion::from(to_version);
 
     println!("{}", style("WDT Version Conversion").bold().cyan());
     println!("{}", style("=====================").cyan());
@@ -372,6 +485,25 @@ fn execute_convert(
     println!();
     convert_wdt(&mut wdt, from_version, to_version).context("Conversion failed")?;
 
+    if to_version >= WowVersion::BfA && !wdt.is_wmo_only() {
+        if let Some(resolver) = load_listfile(listfile.as_deref())? {
+            // Maps live at world/maps/<name>/<name>.wdt in the game files
+            let name = input
+                .file_stem()
+                .context("Invalid input file name")?
+                .to_string_lossy();
+            wdt.assign_file_ids(&resolver, &format!("world/maps/{name}/{name}.wdt"))
+                .context("Failed to assign FileDataIDs")?;
+            println!(
+                "{} Filled MAID for {} tiles from the listfile",
+                style("✓").green(),
+                wdt.maid
+                    .as_ref()
+                    .map_or(0, |maid| maid.count_existing_tiles())
+            );
+        }
+    }
+
     let output_file = File::create(&output).context("Failed to create output file")?;
     let mut writer = WdtWriter::new(BufWriter::new(output_file));
     writer.write(&wdt).context("Failed to write output file")?;
@@ -381,11 +513,10 @@ fn execute_convert(
     Ok(())
 }
 
-fn execute_tiles(path: PathBuf, version_str: String, format: String) -> Result<()> {
+fn execute_tiles(path: PathBuf, version: GameVersion, format: String) -> Result<()> {
     use console::style;
 
-    let version =
-        WowVersion::from_expansion_name(&version_str).context("Invalid version string")?;
+    let version = WowVersion::from(version);
 
     let file = File::open(&path).context("Failed to open WDT file")?;
     let mut reader = WdtReader::new(BufReader::new(file), version);
@@ -476,14 +607,15 @@ fn print_flags(flags: &MphdFlags) {
 
 fn execute_tree(
     path: PathBuf,
-    version_str: String,
+    version: GameVersion,
     depth: Option<usize>,
     show_external_refs: bool,
     no_color: bool,
     compact: bool,
+    listfile: Option<PathBuf>,
 ) -> Result<()> {
-    let version =
-        WowVersion::from_expansion_name(&version_str).context("Invalid version string")?;
+    let resolver = load_listfile(listfile.as_deref())?;
+    let version = WowVersion::from(version);
 
     let file = File::open(&path).context("Failed to open WDT file")?;
     let mut reader = WdtReader::new(BufReader::new(file), version);
@@ -540,13 +672,19 @@ fn execute_tree(
     if wdt.mphd.has_maid() {
         if let Some(lgt_id) = wdt.mphd.lgt_file_data_id {
             mphd_node = mphd_node.with_external_ref(
-                &format!("LGT FileDataID: {lgt_id}"),
+                &format!(
+                    "LGT FileDataID: {}",
+                    describe_file_id(lgt_id, resolver.as_ref())
+                ),
                 detect_ref_type("file.lgt"),
             );
         }
         if let Some(occ_id) = wdt.mphd.occ_file_data_id {
             mphd_node = mphd_node.with_external_ref(
-                &format!("OCC FileDataID: {occ_id}"),
+                &format!(
+                    "OCC FileDataID: {}",
+                    describe_file_id(occ_id, resolver.as_ref())
+                ),
                 detect_ref_type("file.occ"),
             );
         }
@@ -577,6 +715,18 @@ fn execute_tree(
                                 .with_metadata("area_id", &tile_info.area_id.to_string())
                                 .with_metadata("has_adt", "true");
 
+                        let file_data_id = wdt
+                            .maid
+                            .as_ref()
+                            .and_then(|maid| maid.get(MaidSection::RootAdt, x, y))
+                            .filter(|&file_data_id| file_data_id != 0);
+                        if let Some(file_data_id) = file_data_id {
+                            tile_node = tile_node.with_metadata(
+                                "file_data_id",
+                                &describe_file_id(file_data_id, resolver.as_ref()),
+                            );
+                        }
+
                         if show_external_refs {
                             tile_node = tile_node.with_external_ref(
                                 &format!("{base_name}_{x:02}_{y:02}.adt"),
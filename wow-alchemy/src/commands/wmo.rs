@@ -1,13 +1,20 @@
 //! WMO world map object command implementations
 
+use crate::utils::dump::{DumpFormat, read_dump, write_dump};
+use crate::utils::provider::open_file;
 use crate::utils::tree::{NodeType, RefType, TreeNode, TreeOptions};
 use anyhow::{Context, Result};
 use clap::Subcommand;
 use prettytable::{Cell, Row, Table, format};
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Cursor};
 use std::path::Path;
-use wow_alchemy_wmo::{WmoVersion, convert_wmo, parse_wmo, validate_wmo, validate_wmo_detailed};
+use wow_alchemy_data::game_version::GameVersion;
+use wow_alchemy_vfs::path::strip_extension;
+use wow_alchemy_wmo::{
+    WmoGroup, WmoRoot, WmoVersion, WmoWriter, convert_wmo, parse_wmo, parse_wmo_group,
+    validate_wmo, validate_wmo_detailed,
+};
 
 #[derive(Subcommand)]
 pub enum WmoCommands {
@@ -43,9 +50,9 @@ pub enum WmoCommands {
         /// Output WMO file
         output: String,
 
-        /// Target WoW version number (17-27)
+        /// Target WoW version (e.g., "3.3.5.12340", "3.3.5a", "WotLK", "MoP")
         #[arg(short, long)]
-        to: u32,
+        to: GameVersion,
     },
 
     /// Export WMO data
@@ -92,6 +99,11 @@ pub enum WmoCommands {
         /// Output directory
         #[arg(short, long)]
         output: Option<String>,
+
+        /// Read the WMO from a CASC installation, MPQ archive or directory
+        /// instead of the file system
+        #[arg(short, long)]
+        source: Option<String>,
     },
 
     /// Visualize WMO structure as a tree
@@ -119,6 +131,42 @@ pub enum WmoCommands {
         #[arg(long)]
         compact: bool,
     },
+
+    /// Dump a WMO root or group file as JSON or YAML
+    Dump {
+        /// Path to the WMO file
+        file: String,
+
+        /// Read a group file with this index instead of a root file
+        #[arg(long, value_name = "INDEX")]
+        group: Option<u32>,
+
+        /// Dump format
+        #[arg(short, long, value_enum, default_value_t = DumpFormat::Json)]
+        format: DumpFormat,
+
+        /// Write the dump to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Rebuild a WMO root or group file from a JSON or YAML dump
+    Load {
+        /// Path to the dump, read as YAML for .yaml and .yml files and JSON otherwise
+        input: String,
+
+        /// Path to write the WMO file
+        output: String,
+
+        /// The dump is of a group file
+        #[arg(long)]
+        group: bool,
+
+        /// WoW version to write (e.g., "3.3.5a", "WotLK"), by default the
+        /// version of a root dump; required for groups
+        #[arg(short, long)]
+        to: Option<GameVersion>,
+    },
 }
 
 pub fn execute(command: WmoCommands) -> Result<()> {
@@ -134,9 +182,12 @@ pub fn execute(command: WmoCommands) -> Result<()> {
             anyhow::bail!("WMO export functionality not yet implemented");
         }
         WmoCommands::List { file, component } => list(&file, &component),
-        WmoCommands::ExtractGroups { .. } => {
-            anyhow::bail!("WMO group extraction not yet implemented");
-        }
+        WmoCommands::ExtractGroups {
+            file,
+            groups,
+            output,
+            source,
+        } => extract_groups(&file, &groups, output.as_deref(), source.as_deref()),
         WmoCommands::Tree {
             file,
             depth,
@@ -145,7 +196,122 @@ pub fn execute(command: WmoCommands) -> Result<()> {
             no_metadata,
             compact,
         } => tree(&file, depth, show_refs, no_color, no_metadata, compact),
+        WmoCommands::Dump {
+            file,
+            group,
+            format,
+            output,
+        } => dump(&file, group, format, output.as_deref()),
+        WmoCommands::Load {
+            input,
+            output,
+            group,
+            to,
+        } => load(&input, &output, group, to),
+    }
+}
+
+fn dump(path: &str, group: Option<u32>, format: DumpFormat, output: Option<&str>) -> Result<()> {
+    let file = File::open(path).with_context(|| format!("Failed to open file: {path}"))?;
+    let mut reader = BufReader::new(file);
+    let output = output.map(Path::new);
+
+    match group {
+        Some(index) => {
+            let group = parse_wmo_group(&mut reader, index)
+                .with_context(|| format!("Failed to parse WMO group file: {path}"))?;
+            write_dump(&group, format, output)
+        }
+        None => {
+            let root = parse_wmo(&mut reader)
+                .with_context(|| format!("Failed to parse WMO file: {path}"))?;
+            write_dump(&root, format, output)
+        }
+    }
+}
+
+fn load(input: &str, output: &str, group: bool, to: Option<GameVersion>) -> Result<()> {
+    let input = Path::new(input);
+    let writer = WmoWriter::new();
+    let mut data = Cursor::new(Vec::new());
+
+    // Built in memory so that a bad dump leaves no output file behind
+    if group {
+        let version = to.context("The version to write a group file with is required (--to)")?;
+        let group: WmoGroup = read_dump(input)?;
+        writer
+            .write_group(&mut data, &group, WmoVersion::from(version))
+            .with_context(|| format!("Failed to write WMO group file: {output}"))?;
+    } else {
+        let root: WmoRoot = read_dump(input)?;
+        let version = to.map_or(root.version, WmoVersion::from);
+        writer
+            .write_root(&mut data, &root, version)
+            .with_context(|| format!("Failed to write WMO file: {output}"))?;
+    }
+
+    std::fs::write(output, data.into_inner())
+        .with_context(|| format!("Failed to create output file: {output}"))?;
+
+    println!("✓ Wrote {output}");
+
+    Ok(())
+}
+
+fn extract_groups(
+    file: &str,
+    groups: &str,
+    output: Option<&str>,
+    source: Option<&str>,
+) -> Result<()> {
+    let (provider, path) = open_file(file, source)?;
+    let root = parse_wmo(
+        &mut provider
+            .open(&path)
+            .with_context(|| format!("Failed to read WMO: {path}"))?,
+    )
+    .with_context(|| format!("Failed to parse WMO: {path}"))?;
+
+    let count = root.groups.len();
+    let indices: Vec<usize> = if groups.eq_ignore_ascii_case("all") {
+        (0..count).collect()
+    } else {
+        groups
+            .split(',')
+            .map(|index| {
+                let index: usize = index
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("Invalid group index: {index}"))?;
+                if index >= count {
+                    anyhow::bail!("Group index {index} out of range, WMO has {count} groups");
+                }
+                Ok(index)
+            })
+            .collect::<Result<_>>()?
+    };
+
+    let output = Path::new(output.unwrap_or("."));
+    std::fs::create_dir_all(output)
+        .with_context(|| format!("Failed to create directory: {}", output.display()))?;
+
+    let base = strip_extension(&path);
+    for index in indices {
+        let group_path = format!("{base}_{index:03}.wmo");
+        let data = provider
+            .read(&group_path)
+            .with_context(|| format!("Failed to read group: {group_path}"))?;
+        parse_wmo_group(&mut Cursor::new(&data), index as u32)
+            .with_context(|| format!("Failed to parse group: {group_path}"))?;
+
+        let name = group_path.rsplit(['/', '\\']).next().unwrap_or(&group_path);
+        let target = output.join(name);
+        std::fs::write(&target, &data)
+            .with_context(|| format!("Failed to write file: {}", target.display()))?;
+        println!("Extracted group {index} to {}", target.display());
     }
+
+    Ok(())
 }
 
 fn info(path: &str, detailed: bool) -> Result<()> {
@@ -301,7 +467,7 @@ fn validate(path: &str, show_warnings: bool, detailed: bool) -> Result<()> {
     Ok(())
 }
 
-fn convert(input_path: &str, output_path: &str, target_version: u32) -> Result<()> {
+fn convert(input_path: &str, output_path: &str, target_version: GameVersion) -> Result<()> {
     let input_path = Path::new(input_path);
     let output_path = Path::new(output_path);
 
@@ -309,8 +475,7 @@ fn convert(input_path: &str, output_path: &str, target_version: u32) -> Result<(
         anyhow::bail!("Input file not found: {}", input_path.display());
     }
 
-    let version = WmoVersion::from_raw(target_version)
-        .ok_or_else(|| anyhow::anyhow!("Invalid WMO version: {}", target_version))?;
+    let version = WmoVersion::from(target_version);
 
     println!("Converting WMO file...");
     println!("  Input: {}", input_path.display());
@@ -47,6 +47,24 @@ fn main() -> Result<()> {
         #[cfg(feature = "wdl")]
         Commands::Wdl { command } => commands::wdl::execute(command),
 
+        #[cfg(feature = "mpq")]
+        Commands::Mpq { command } => commands::mpq::execute(command),
+
+        #[cfg(any(
+            feature = "blp",
+            feature = "m2",
+            feature = "wmo",
+            feature = "adt",
+            feature = "wdt",
+            feature = "wdl"
+        ))]
+        Commands::VerifyRoundtrip {
+            path,
+            version,
+            show_identical,
+            dump,
+        } => commands::roundtrip::execute(path, version, show_identical, dump),
+
         Commands::Completions { shell } => {
             print_completions(shell, &mut Cli::command());
             Ok(())
@@ -0,0 +1,108 @@
+//! Dumping parsed files as JSON or YAML, and reading the dumps back
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+#[cfg(any(
+    feature = "m2",
+    feature = "wmo",
+    feature = "adt",
+    feature = "wdt",
+    feature = "wdl"
+))]
+use serde::de::DeserializeOwned;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DumpFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+// BLP headers are only dumped, never loaded back
+#[cfg(any(
+    feature = "m2",
+    feature = "wmo",
+    feature = "adt",
+    feature = "wdt",
+    feature = "wdl"
+))]
+impl DumpFormat {
+    /// The format of a dump file from its extension: YAML for `.yaml` and
+    /// `.yml`, JSON otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml") => {
+                Self::Yaml
+            }
+            _ => Self::Json,
+        }
+    }
+}
+
+/// Serialize `value` as a dump
+pub fn to_dump<T: Serialize>(value: &T, format: DumpFormat) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    match format {
+        DumpFormat::Json => {
+            serde_json::to_writer_pretty(&mut data, value).context("Failed to write JSON")?;
+            data.push(b'\n');
+        }
+        DumpFormat::Yaml => {
+            serde_norway::to_writer(&mut data, value).context("Failed to write YAML")?;
+        }
+    }
+    Ok(data)
+}
+
+/// Deserialize a dump written by [`to_dump`]
+#[cfg(any(
+    feature = "m2",
+    feature = "wmo",
+    feature = "adt",
+    feature = "wdt",
+    feature = "wdl"
+))]
+pub fn from_dump<T: DeserializeOwned>(data: &[u8], format: DumpFormat) -> Result<T> {
+    match format {
+        DumpFormat::Json => serde_json::from_slice(data).context("Failed to read JSON dump"),
+        DumpFormat::Yaml => serde_norway::from_slice(data).context("Failed to read YAML dump"),
+    }
+}
+
+/// Write `value` as a dump to `output`, or to stdout without one
+pub fn write_dump<T: Serialize>(
+    value: &T,
+    format: DumpFormat,
+    output: Option<&Path>,
+) -> Result<()> {
+    let data = to_dump(value, format)?;
+    match output {
+        Some(path) => std::fs::write(path, data)
+            .with_context(|| format!("Failed to create output file: {}", path.display()))?,
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(&data)?;
+            stdout.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a dump written by [`write_dump`], in the format given by its extension
+#[cfg(any(
+    feature = "m2",
+    feature = "wmo",
+    feature = "adt",
+    feature = "wdt",
+    feature = "wdl"
+))]
+pub fn read_dump<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let data =
+        std::fs::read(path).with_context(|| format!("Failed to open dump: {}", path.display()))?;
+    from_dump(&data, DumpFormat::from_path(path))
+        .with_context(|| format!("Invalid dump: {}", path.display()))
+}
@@ -0,0 +1,23 @@
+//! Showing the paths of FileDataIDs from a community listfile
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use wow_alchemy_vfs::FileIdResolver;
+
+/// Load the listfile given with `--listfile`, if any
+pub fn load_listfile(path: Option<&Path>) -> Result<Option<FileIdResolver>> {
+    path.map(|path| {
+        FileIdResolver::open(path)
+            .with_context(|| format!("Failed to read listfile: {}", path.display()))
+    })
+    .transpose()
+}
+
+/// A FileDataID followed by its path when the listfile knows it
+#[cfg(any(feature = "m2", feature = "wdt"))]
+pub fn describe_file_id(file_data_id: u32, resolver: Option<&FileIdResolver>) -> String {
+    match resolver.and_then(|resolver| resolver.path(file_data_id)) {
+        Some(path) => format!("{file_data_id} ({path})"),
+        None => file_data_id.to_string(),
+    }
+}
@@ -20,6 +20,7 @@ pub mod progress;
     feature = "adt",
     feature = "wdt",
     feature = "wdl",
+    feature = "mpq",
     test
 ))]
 pub mod table;
@@ -35,6 +36,32 @@ pub mod table;
 ))]
 pub mod tree;
 
+#[cfg(any(
+    feature = "blp",
+    feature = "m2",
+    feature = "wmo",
+    feature = "adt",
+    feature = "wdt",
+    feature = "wdl"
+))]
+pub mod dump;
+
+#[cfg(any(
+    feature = "blp",
+    feature = "m2",
+    feature = "wmo",
+    feature = "adt",
+    feature = "wdt",
+    feature = "wdl"
+))]
+pub mod roundtrip;
+
+#[cfg(any(feature = "m2", feature = "adt", feature = "wdt"))]
+pub mod listfile;
+
+#[cfg(any(feature = "m2", feature = "wmo", feature = "adt"))]
+pub mod provider;
+
 // Re-export utilities only when actually used by commands
 
 #[cfg(any(
@@ -0,0 +1,55 @@
+//! Opening game file sources for commands that follow references between files
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use wow_alchemy_vfs::{LooseProvider, WowFileProvider};
+
+/// Open a source of game files: a CASC installation (directory with a
+/// `.build.info`), an MPQ archive or a directory of loose files.
+pub fn open_source(source: &Path) -> Result<Box<dyn WowFileProvider>> {
+    if source.is_dir() {
+        #[cfg(feature = "casc")]
+        if source.join(".build.info").is_file() {
+            let storage = wow_alchemy_casc::CascStorage::open(source)
+                .with_context(|| format!("Failed to open CASC storage: {}", source.display()))?;
+            return Ok(Box::new(storage));
+        }
+        return Ok(Box::new(LooseProvider::new(source)));
+    }
+
+    #[cfg(feature = "mpq")]
+    if source.is_file() {
+        let archive = wow_alchemy_vfs::MpqProvider::open(source)
+            .with_context(|| format!("Failed to open MPQ archive: {}", source.display()))?;
+        return Ok(Box::new(archive));
+    }
+
+    anyhow::bail!("Unsupported file source: {}", source.display())
+}
+
+/// Resolve a file argument to a provider and a path inside it. With a
+/// source the file is a game path inside of it, otherwise it is a file on
+/// disk whose directory provides the files it references.
+pub fn open_file(file: &str, source: Option<&str>) -> Result<(Box<dyn WowFileProvider>, String)> {
+    if let Some(source) = source {
+        return Ok((open_source(Path::new(source))?, file.to_owned()));
+    }
+
+    let path = Path::new(file);
+    if !path.is_file() {
+        anyhow::bail!("File not found: {}", path.display());
+    }
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .with_context(|| format!("Invalid file name: {}", path.display()))?;
+    Ok((
+        Box::new(LooseProvider::new(if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        })),
+        name.to_owned(),
+    ))
+}
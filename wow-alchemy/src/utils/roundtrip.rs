@@ -0,0 +1,258 @@
+//! Checking that the writers of the format crates reproduce the bytes their
+//! parsers read, by parsing files and writing them back
+
+use anyhow::{Context, Result};
+use std::io::Cursor;
+use std::path::Path;
+use wow_alchemy_data::chunk_writer::MagicOrder;
+use wow_alchemy_data::game_version::GameVersion;
+use wow_alchemy_data::roundtrip::{Mismatch, compare};
+
+use crate::utils::dump::DumpFormat;
+
+/// A format whose files can be parsed and written back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundTripFormat {
+    #[cfg(feature = "adt")]
+    Adt,
+    #[cfg(feature = "blp")]
+    Blp,
+    #[cfg(feature = "m2")]
+    Skin,
+    #[cfg(feature = "wdl")]
+    Wdl,
+    #[cfg(feature = "wdt")]
+    Wdt,
+    #[cfg(feature = "wmo")]
+    WmoRoot,
+    #[cfg(feature = "wmo")]
+    WmoGroup,
+}
+
+impl RoundTripFormat {
+    /// Detect the format of a file from its magic, or from its extension for
+    /// chunked files that all start with MVER. `None` for other files and
+    /// for files the writers don't cover, like split ADTs and BLP0 textures
+    /// with external mipmaps.
+    pub fn detect(path: &Path, data: &[u8]) -> Option<Self> {
+        let magic = data.get(..4)?;
+        match magic {
+            #[cfg(feature = "blp")]
+            b"BLP1" | b"BLP2" => return Some(Self::Blp),
+            #[cfg(feature = "m2")]
+            b"SKIN" => return Some(Self::Skin),
+            b"REVM" => {}
+            _ => return None,
+        }
+
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            #[cfg(feature = "adt")]
+            "adt" => {
+                use wow_alchemy_adt::split_adt::SplitAdtType;
+
+                let name = path.file_name()?.to_str()?;
+                (SplitAdtType::from_filename(name) == SplitAdtType::Root).then_some(Self::Adt)
+            }
+            #[cfg(feature = "wdl")]
+            "wdl" => Some(Self::Wdl),
+            #[cfg(feature = "wdt")]
+            "wdt" => Some(Self::Wdt),
+            #[cfg(feature = "wmo")]
+            "wmo" => match data.get(12..16) {
+                Some(b"PGOM") => Some(Self::WmoGroup),
+                _ => Some(Self::WmoRoot),
+            },
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "adt")]
+            Self::Adt => "ADT",
+            #[cfg(feature = "blp")]
+            Self::Blp => "BLP",
+            #[cfg(feature = "m2")]
+            Self::Skin => "Skin",
+            #[cfg(feature = "wdl")]
+            Self::Wdl => "WDL",
+            #[cfg(feature = "wdt")]
+            Self::Wdt => "WDT",
+            #[cfg(feature = "wmo")]
+            Self::WmoRoot => "WMO root",
+            #[cfg(feature = "wmo")]
+            Self::WmoGroup => "WMO group",
+        }
+    }
+
+    /// Order of the chunk magics, or `None` if the format is not chunked
+    pub fn magic_order(&self) -> Option<MagicOrder> {
+        match self {
+            #[cfg(feature = "blp")]
+            Self::Blp => None,
+            #[cfg(feature = "m2")]
+            Self::Skin => None,
+            #[allow(unreachable_patterns)]
+            _ => Some(MagicOrder::Reversed),
+        }
+    }
+
+    /// Parse `data` and write it back with the writer of the format.
+    /// `version` is used where the file doesn't say which version to write,
+    /// which is for WDT files and WMO groups.
+    pub fn rewrite(&self, path: &Path, data: &[u8], version: GameVersion) -> Result<Vec<u8>> {
+        self.rewrite_through(path, data, version, None)
+    }
+
+    /// Like [`RoundTripFormat::rewrite`], passing the parsed file through a
+    /// dump and back when `dump` is given, like `dump` and `load` commands
+    /// do. BLP files only dump their header, so they can't.
+    #[cfg_attr(
+        not(all(feature = "wdt", feature = "wmo")),
+        allow(unused_variables, unused_mut)
+    )]
+    pub fn rewrite_through(
+        &self,
+        path: &Path,
+        data: &[u8],
+        version: GameVersion,
+        dump: Option<DumpFormat>,
+    ) -> Result<Vec<u8>> {
+        let mut reader = Cursor::new(data);
+        let mut writer = Cursor::new(Vec::new());
+
+        match self {
+            #[cfg(feature = "adt")]
+            Self::Adt => {
+                let adt = through_dump(wow_alchemy_adt::Adt::from_reader(&mut reader)?, dump)?;
+                adt.write(&mut writer)?;
+            }
+            #[cfg(feature = "blp")]
+            Self::Blp => {
+                use std::io::Write;
+
+                if dump.is_some() {
+                    anyhow::bail!("BLP files can't be loaded from a dump");
+                }
+                let image =
+                    wow_alchemy_blp::parser::parse_blp(data).map_err(|e| anyhow::anyhow!("{e}"))?;
+                writer.write_all(&wow_alchemy_blp::encode::encode_blp(&image)?)?;
+            }
+            #[cfg(feature = "m2")]
+            Self::Skin => {
+                use wow_alchemy_data::types::{VWowStructR, WowStructW};
+                use wow_alchemy_m2::Skin;
+
+                let skin = through_dump(Skin::wow_read(&mut reader, skin_version(data))?, dump)?;
+                skin.wow_write(&mut writer)?;
+            }
+            #[cfg(feature = "wdl")]
+            Self::Wdl => {
+                use wow_alchemy_wdl::parser::WdlParser;
+
+                let wdl = through_dump(WdlParser::new().parse(&mut reader)?, dump)?;
+                WdlParser::with_version(wdl.version).write(&mut writer, &wdl)?;
+            }
+            #[cfg(feature = "wdt")]
+            Self::Wdt => {
+                use wow_alchemy_wdt::{WdtReader, WdtWriter, version::WowVersion};
+
+                let wdt = WdtReader::new(&mut reader, WowVersion::from(version)).read()?;
+                let wdt = through_dump(wdt, dump)?;
+                WdtWriter::new(&mut writer).write(&wdt)?;
+            }
+            #[cfg(feature = "wmo")]
+            Self::WmoRoot => {
+                let root = through_dump(wow_alchemy_wmo::parse_wmo(&mut reader)?, dump)?;
+                wow_alchemy_wmo::WmoWriter::new().write_root(&mut writer, &root, root.version)?;
+            }
+            #[cfg(feature = "wmo")]
+            Self::WmoGroup => {
+                use wow_alchemy_wmo::{WmoVersion, WmoWriter, parse_wmo_group};
+
+                let group = parse_wmo_group(&mut reader, wmo_group_index(path))?;
+                let group = through_dump(group, dump)?;
+                WmoWriter::new().write_group(&mut writer, &group, WmoVersion::from(version))?;
+            }
+        }
+
+        Ok(writer.into_inner())
+    }
+
+    /// Parse `data`, write it back, and compare the written bytes with
+    /// `data`. `None` if they are identical.
+    pub fn verify(
+        &self,
+        path: &Path,
+        data: &[u8],
+        version: GameVersion,
+    ) -> Result<Option<Mismatch>> {
+        let written = self
+            .rewrite(path, data, version)
+            .with_context(|| format!("Failed to round-trip {} file", self.name()))?;
+        Ok(compare(data, &written, self.magic_order()))
+    }
+
+    /// Like [`RoundTripFormat::verify`], passing the parsed file through a
+    /// dump in `format` before writing it back
+    pub fn verify_dump(
+        &self,
+        path: &Path,
+        data: &[u8],
+        version: GameVersion,
+        format: DumpFormat,
+    ) -> Result<Option<Mismatch>> {
+        let written = self
+            .rewrite_through(path, data, version, Some(format))
+            .with_context(|| format!("Failed to round-trip {} file through a dump", self.name()))?;
+        Ok(compare(data, &written, self.magic_order()))
+    }
+}
+
+/// Serialize `value` as a dump and deserialize it back, when `dump` is given
+#[cfg(any(
+    feature = "m2",
+    feature = "wmo",
+    feature = "adt",
+    feature = "wdt",
+    feature = "wdl"
+))]
+fn through_dump<T>(value: T, dump: Option<DumpFormat>) -> Result<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    use crate::utils::dump::{from_dump, to_dump};
+
+    match dump {
+        Some(format) => from_dump(&to_dump(&value, format)?, format),
+        None => Ok(value),
+    }
+}
+
+/// Version of a Skin file from the offset of its indices, which follow the
+/// header. Only Cataclysm and later headers have room for shadow batches.
+#[cfg(feature = "m2")]
+fn skin_version(data: &[u8]) -> wow_alchemy_m2::skin::SkinVersion {
+    use wow_alchemy_m2::skin::SkinVersion;
+
+    const V3_HEADER_SIZE: u32 = 56;
+
+    let indices_offset = data
+        .get(8..12)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()));
+    match indices_offset {
+        Some(offset) if offset < V3_HEADER_SIZE => SkinVersion::V2,
+        _ => SkinVersion::V3,
+    }
+}
+
+/// Index of a WMO group from its file name, like 2 for `Building_002.wmo`
+#[cfg(feature = "wmo")]
+fn wmo_group_index(path: &Path) -> u32 {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.rsplit_once('_'))
+        .and_then(|(_, index)| index.parse().ok())
+        .unwrap_or(0)
+}
@@ -0,0 +1,754 @@
+//! Round trips of synthetic files, generated per format and written with the
+//! crate writers, through the `verify-roundtrip` harness and through JSON and
+//! YAML dumps like the `dump` and `load` commands
+
+#![cfg(all(
+    feature = "adt",
+    feature = "blp",
+    feature = "m2",
+    feature = "wdl",
+    feature = "wdt",
+    feature = "wmo"
+))]
+
+use std::io::Cursor;
+use std::path::Path;
+
+use proptest::prelude::*;
+use proptest::sample::select;
+use wow_alchemy::utils::dump::DumpFormat;
+use wow_alchemy::utils::roundtrip::RoundTripFormat;
+use wow_alchemy_data::game_version::GameVersion;
+use wow_alchemy_data::roundtrip::Mismatch;
+
+/// Check that `data` is detected as `format` and written back identically,
+/// directly and, except for BLP files whose dumps only have the header,
+/// through dumps
+fn check_round_trip(
+    format: RoundTripFormat,
+    name: &str,
+    data: &[u8],
+    version: GameVersion,
+) -> Result<(), TestCaseError> {
+    let path = Path::new(name);
+    prop_assert_eq!(RoundTripFormat::detect(path, data), Some(format));
+
+    check_verified(name, format.verify(path, data, version))?;
+    if format != RoundTripFormat::Blp {
+        for dump in [DumpFormat::Json, DumpFormat::Yaml] {
+            check_verified(name, format.verify_dump(path, data, version, dump))?;
+        }
+    }
+    Ok(())
+}
+
+fn check_verified(
+    name: &str,
+    result: anyhow::Result<Option<Mismatch>>,
+) -> Result<(), TestCaseError> {
+    match result {
+        Ok(None) => Ok(()),
+        Ok(Some(mismatch)) => Err(TestCaseError::fail(format!("{name}: {mismatch}"))),
+        Err(e) => Err(TestCaseError::fail(format!("{name}: {e:#}"))),
+    }
+}
+
+fn file_name(extension: &'static str) -> impl Strategy<Value = String> {
+    "[A-Za-z0-9_]{1,12}".prop_map(move |stem| format!("World\\{stem}.{extension}"))
+}
+
+fn coordinate() -> impl Strategy<Value = f32> {
+    -17066.0_f32..17066.0
+}
+
+mod adt {
+    use super::*;
+    use wow_alchemy_adt::{AdtBuilder, AdtVersion};
+
+    /// Versions the parser detects back from the chunks the builder writes:
+    /// TBC from MFBO, which is written for flight bounds. The builder doesn't
+    /// write MH2O, which WotLK is detected from.
+    fn version() -> impl Strategy<Value = AdtVersion> {
+        select(vec![AdtVersion::Vanilla, AdtVersion::TBC])
+    }
+
+    prop_compose! {
+        fn adt()(
+            version in version(),
+            textures in prop::collection::vec(file_name("blp"), 1..4),
+            models in prop::collection::vec(file_name("m2"), 1..3),
+            heights in prop::collection::vec(
+                ((0_usize..16, 0_usize..16), prop::collection::vec(-500.0_f32..500.0, 145)),
+                0..4,
+            ),
+            doodads in prop::collection::vec(
+                ([coordinate(), coordinate(), coordinate()], 0.5_f32..2.0, any::<u16>()),
+                0..4,
+            ),
+            (max_plane, min_plane) in (any::<[i16; 9]>(), any::<[i16; 9]>()),
+        ) -> Vec<u8> {
+            let mut builder = AdtBuilder::new(version);
+            if version >= AdtVersion::TBC {
+                builder.set_flight_bounds(max_plane, min_plane).unwrap();
+            }
+            for texture in &textures {
+                builder.add_texture(texture);
+            }
+            for (i, model) in models.iter().enumerate() {
+                let id = builder.add_model(model);
+                if let Some(&(position, scale, flags)) = doodads.get(i) {
+                    builder.add_doodad(id, position, [0.0; 3], scale, flags).unwrap();
+                }
+            }
+            for ((x, y), chunk_heights) in &heights {
+                builder.set_chunk_heights(*x, *y, chunk_heights).unwrap();
+            }
+
+            let mut writer = Cursor::new(Vec::new());
+            builder.build().unwrap().write(&mut writer).unwrap();
+            writer.into_inner()
+        }
+    }
+
+    /// MDDF entries are 36 bytes with a fixed-point scale, they used to be
+    /// written as 40 bytes with a float scale
+    #[test]
+    fn test_adt_doodad_round_trip() {
+        let mut builder = AdtBuilder::new(AdtVersion::Vanilla);
+        builder.add_texture("World\\Grass.blp");
+        let id = builder.add_model("World\\Tree.m2");
+        builder
+            .add_doodad(id, [100.0, 200.0, 50.0], [0.0; 3], 1.5, 0)
+            .unwrap();
+
+        let mut writer = Cursor::new(Vec::new());
+        builder.build().unwrap().write(&mut writer).unwrap();
+        let data = writer.into_inner();
+
+        let mddf = data
+            .windows(4)
+            .position(|magic| magic == b"FDDM")
+            .expect("MDDF chunk");
+        assert_eq!(data[mddf + 4..mddf + 8], 36u32.to_le_bytes());
+        check_round_trip(
+            RoundTripFormat::Adt,
+            "Map_32_48.adt",
+            &data,
+            GameVersion::WotLK,
+        )
+        .unwrap();
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_adt_round_trip(data in adt()) {
+            check_round_trip(RoundTripFormat::Adt, "Map_32_48.adt", &data, GameVersion::WotLK)?;
+        }
+    }
+}
+
+mod blp {
+    use super::*;
+    use image::{DynamicImage, RgbaImage, imageops::FilterType};
+    use wow_alchemy_blp::convert::{AlphaBits, Blp2Format, BlpOldFormat, BlpTarget, image_to_blp};
+    use wow_alchemy_blp::encode::encode_blp;
+
+    /// The BLP target with the `kind`th encoding and `alpha`th alpha depth
+    fn target(kind: usize, alpha: usize, has_alpha: bool) -> BlpTarget {
+        let alpha_bits = [
+            AlphaBits::NoAlpha,
+            AlphaBits::Bit1,
+            AlphaBits::Bit4,
+            AlphaBits::Bit8,
+        ][alpha];
+        match kind {
+            0 => BlpTarget::Blp1(BlpOldFormat::Raw1 { alpha_bits }),
+            1 => BlpTarget::Blp2(Blp2Format::Raw1 { alpha_bits }),
+            2 => BlpTarget::Blp2(Blp2Format::Raw3),
+            3 => BlpTarget::Blp2(Blp2Format::Dxt1 {
+                has_alpha,
+                compress_algorithm: Default::default(),
+            }),
+            _ => BlpTarget::Blp2(Blp2Format::default()),
+        }
+    }
+
+    prop_compose! {
+        fn blp()(
+            (width, height, pixels) in (1_u32..=4, 1_u32..=4).prop_flat_map(|(w, h)| {
+                let (width, height) = (1 << w, 1 << h);
+                (
+                    Just(width),
+                    Just(height),
+                    prop::collection::vec(any::<u8>(), (width * height * 4) as usize),
+                )
+            }),
+            (kind, alpha, has_alpha) in (0_usize..5, 0_usize..4, any::<bool>()),
+            make_mipmaps in any::<bool>(),
+        ) -> Vec<u8> {
+            let image = RgbaImage::from_raw(width, height, pixels).unwrap();
+            let blp = image_to_blp(
+                DynamicImage::ImageRgba8(image),
+                make_mipmaps,
+                target(kind, alpha, has_alpha),
+                FilterType::Nearest,
+            )
+            .unwrap();
+            encode_blp(&blp).unwrap()
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn test_blp_round_trip(data in blp()) {
+            check_round_trip(RoundTripFormat::Blp, "texture.blp", &data, GameVersion::WotLK)?;
+        }
+    }
+}
+
+/// Models aren't in the `verify-roundtrip` harness, as the writer lays out
+/// the data in its own order, so only their round trip through dumps is
+/// checked, against the bytes the writer gives
+mod m2 {
+    use super::*;
+    use std::collections::HashMap;
+    use wow_alchemy::utils::dump::{from_dump, to_dump};
+    use wow_alchemy_data::types::{C3Vector, WowStructR, WowStructW};
+    use wow_alchemy_m2::chunks::texture::M2TextureData;
+    use wow_alchemy_m2::chunks::{M2Texture, M2Vertex};
+    use wow_alchemy_m2::header::{MD20_MAGIC, MD20Header};
+    use wow_alchemy_m2::model::{M2Chunk, MD21_MAGIC};
+    use wow_alchemy_m2::{M2Model, MD20Model, MD20Version};
+
+    fn write(model: &M2Model) -> Vec<u8> {
+        let mut writer = Cursor::new(Vec::new());
+        model.wow_write(&mut writer).unwrap();
+        writer.into_inner()
+    }
+
+    prop_compose! {
+        fn model()(
+            version in select(vec![MD20Version::WotLK, MD20Version::Cataclysm, MD20Version::MoPPlus]),
+            name in "[A-Za-z]{0,12}",
+            global_sequences in prop::collection::vec(any::<u32>(), 0..4),
+            textures in prop::collection::vec(prop::option::of(file_name("blp")), 0..4),
+            positions in prop::collection::vec([coordinate(), coordinate(), coordinate()], 0..8),
+            texture_file_ids in prop::option::of(any::<u32>()),
+        ) -> Vec<u8> {
+            let md20 = MD20Model {
+                header: MD20Header::new(version),
+                name,
+                global_sequences,
+                textures: textures
+                    .into_iter()
+                    .map(|filename| M2Texture {
+                        data: M2TextureData {
+                            filename: filename.unwrap_or_default(),
+                        },
+                        ..Default::default()
+                    })
+                    .collect(),
+                vertices: positions
+                    .into_iter()
+                    .map(|[x, y, z]| M2Vertex {
+                        position: C3Vector { x, y, z },
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            };
+            let model = match texture_file_ids {
+                Some(file_id) => M2Model {
+                    magic: MD21_MAGIC,
+                    md20,
+                    chunk_index: HashMap::from([("TXID".into(), 0)]),
+                    chunks: vec![M2Chunk::TXID(vec![file_id])],
+                },
+                None => M2Model {
+                    magic: MD20_MAGIC,
+                    md20,
+                    ..Default::default()
+                },
+            };
+            write(&model)
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn test_m2_dump_round_trip(data in model()) {
+            let model = M2Model::wow_read(&mut Cursor::new(&data)).unwrap();
+            for format in [DumpFormat::Json, DumpFormat::Yaml] {
+                let loaded: M2Model = from_dump(&to_dump(&model, format).unwrap(), format).unwrap();
+                prop_assert_eq!(&write(&loaded), &data);
+            }
+        }
+    }
+}
+
+mod skin {
+    use super::*;
+    use wow_alchemy_data::types::{C3Vector, WowStructW};
+    use wow_alchemy_m2::Skin;
+    use wow_alchemy_m2::skin::{
+        M2Batch, M2ShadowBatch, SkinHeader, SkinMagic, SkinShadowBatches, SkinShadowBatchesHeader,
+        SkinSubmesh,
+    };
+
+    prop_compose! {
+        fn submesh()(
+            id in any::<u16>(),
+            vertex_start in any::<u16>(),
+            vertex_count in any::<u16>(),
+            triangle_start in any::<u16>(),
+            triangle_count in any::<u16>(),
+            center in [coordinate(), coordinate(), coordinate()],
+            bounding_radius in 0.0_f32..100.0,
+        ) -> SkinSubmesh {
+            SkinSubmesh {
+                id,
+                vertex_start,
+                vertex_count,
+                triangle_start,
+                triangle_count,
+                center: C3Vector::new(center[0], center[1], center[2]),
+                sort_center: C3Vector::new(center[0], center[1], center[2]),
+                bounding_radius,
+                ..Default::default()
+            }
+        }
+    }
+
+    prop_compose! {
+        fn batch()(
+            shader_id in any::<u16>(),
+            skin_section_index in any::<u16>(),
+            material_index in any::<u16>(),
+            texture_count in 1_u16..4,
+            texture_combo_index in any::<u16>(),
+        ) -> M2Batch {
+            M2Batch {
+                shader_id,
+                skin_section_index,
+                material_index,
+                texture_count,
+                texture_combo_index,
+                ..Default::default()
+            }
+        }
+    }
+
+    prop_compose! {
+        fn skin()(
+            has_shadow_batches in any::<bool>(),
+            indices in prop::collection::vec(any::<u16>(), 0..64),
+            triangles in prop::collection::vec(any::<u16>(), 0..64),
+            bone_indices in prop::collection::vec(any::<u8>(), 0..32),
+            submeshes in prop::collection::vec(submesh(), 0..4),
+            texture_units in prop::collection::vec(batch(), 0..4),
+            shadow_batches in prop::collection::vec(
+                (any::<u8>(), any::<u16>()).prop_map(|(flags, submesh_id)| M2ShadowBatch {
+                    flags,
+                    submesh_id,
+                    ..Default::default()
+                }),
+                0..3,
+            ),
+            bone_count_max in 0_u32..256,
+        ) -> Vec<u8> {
+            let skin = Skin {
+                header: SkinHeader {
+                    magic: SkinMagic::Some(*b"SKIN"),
+                    bone_count_max,
+                    shadow_batches: if has_shadow_batches {
+                        SkinShadowBatchesHeader::Some(Default::default())
+                    } else {
+                        SkinShadowBatchesHeader::None
+                    },
+                    ..Default::default()
+                },
+                indices,
+                triangles,
+                bone_indices,
+                submeshes,
+                texture_units,
+                shadow_batches: if has_shadow_batches {
+                    SkinShadowBatches::Some(shadow_batches)
+                } else {
+                    SkinShadowBatches::None
+                },
+            };
+
+            let mut data = Vec::new();
+            skin.wow_write(&mut Cursor::new(&mut data)).unwrap();
+            data
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn test_skin_round_trip(data in skin()) {
+            check_round_trip(RoundTripFormat::Skin, "Model00.skin", &data, GameVersion::WotLK)?;
+        }
+    }
+}
+
+mod wdl {
+    use super::*;
+    use wow_alchemy_wdl::WdlVersion;
+    use wow_alchemy_wdl::parser::WdlParser;
+    use wow_alchemy_wdl::types::{HeightMapTile, HolesData, WdlFile};
+
+    fn version() -> impl Strategy<Value = WdlVersion> {
+        select(vec![
+            WdlVersion::Vanilla,
+            WdlVersion::Wotlk,
+            WdlVersion::Cataclysm,
+            WdlVersion::Mop,
+            WdlVersion::Legion,
+        ])
+    }
+
+    prop_compose! {
+        fn tile()(
+            outer_values in prop::collection::vec(any::<i16>(), HeightMapTile::OUTER_COUNT),
+            inner_values in prop::collection::vec(any::<i16>(), HeightMapTile::INNER_COUNT),
+            hole_masks in any::<[u16; 16]>(),
+        ) -> (HeightMapTile, HolesData) {
+            let mut holes = HolesData::new();
+            holes.hole_masks = hole_masks;
+            (HeightMapTile { outer_values, inner_values }, holes)
+        }
+    }
+
+    prop_compose! {
+        fn wdl()(
+            version in version(),
+            tiles in prop::collection::btree_map((0_u32..64, 0_u32..64), tile(), 0..6),
+            wmo_filenames in prop::collection::vec(file_name("wmo"), 0..3),
+        ) -> Vec<u8> {
+            let mut file = WdlFile::with_version(version);
+            for (coords, (heightmap, holes)) in tiles {
+                file.heightmap_tiles.insert(coords, heightmap);
+                file.holes_data.insert(coords, holes);
+            }
+            let mut offset = 0;
+            for name in &wmo_filenames {
+                file.wmo_indices.push(offset);
+                offset += name.len() as u32 + 1;
+            }
+            file.wmo_filenames = wmo_filenames;
+
+            let mut writer = Cursor::new(Vec::new());
+            WdlParser::with_version(version).write(&mut writer, &file).unwrap();
+            writer.into_inner()
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn test_wdl_round_trip(data in wdl()) {
+            check_round_trip(RoundTripFormat::Wdl, "Map.wdl", &data, GameVersion::WotLK)?;
+        }
+    }
+}
+
+mod wdt {
+    use super::*;
+    use wow_alchemy_wdt::chunks::mphd::MphdFlags;
+    use wow_alchemy_wdt::chunks::{ModfChunk, ModfEntry, MwmoChunk};
+    use wow_alchemy_wdt::version::WowVersion;
+    use wow_alchemy_wdt::{WdtFile, WdtWriter};
+
+    fn version() -> impl Strategy<Value = GameVersion> {
+        select(vec![
+            GameVersion::Vanilla,
+            GameVersion::TBC,
+            GameVersion::WotLK,
+            GameVersion::Cataclysm,
+            GameVersion::MoP,
+            GameVersion::Legion,
+        ])
+    }
+
+    prop_compose! {
+        fn wdt()(
+            version in version(),
+            flags in any::<u32>(),
+            tiles in prop::collection::btree_map(
+                (0_usize..64, 0_usize..64),
+                (any::<u32>(), any::<u32>()),
+                0..16,
+            ),
+            global_wmo in prop::option::of((
+                file_name("wmo"),
+                [coordinate(), coordinate(), coordinate()],
+                any::<u16>(),
+            )),
+        ) -> (GameVersion, Vec<u8>) {
+            let mut wdt = WdtFile::new(WowVersion::from(version));
+            wdt.mphd.flags = MphdFlags::from_bits_truncate(flags)
+                - MphdFlags::WDT_HAS_MAID
+                - MphdFlags::WDT_USES_GLOBAL_MAP_OBJ;
+
+            match global_wmo {
+                Some((name, position, doodad_set)) => {
+                    wdt.mphd.flags |= MphdFlags::WDT_USES_GLOBAL_MAP_OBJ;
+                    let mut mwmo = MwmoChunk::new();
+                    mwmo.add_filename(name);
+                    wdt.mwmo = Some(mwmo);
+
+                    let mut modf = ModfChunk::new();
+                    modf.add_entry(ModfEntry {
+                        position,
+                        lower_bounds: position,
+                        upper_bounds: position,
+                        doodad_set,
+                        ..ModfEntry::new()
+                    });
+                    wdt.modf = Some(modf);
+                }
+                None => {
+                    for ((x, y), (tile_flags, area_id)) in tiles {
+                        let entry = wdt.main.get_mut(x, y).unwrap();
+                        entry.flags = tile_flags;
+                        entry.area_id = area_id;
+                    }
+                }
+            }
+
+            let mut writer = Cursor::new(Vec::new());
+            WdtWriter::new(&mut writer).write(&wdt).unwrap();
+            (version, writer.into_inner())
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn test_wdt_round_trip((version, data) in wdt()) {
+            check_round_trip(RoundTripFormat::Wdt, "Map.wdt", &data, version)?;
+        }
+    }
+}
+
+mod wmo {
+    use super::*;
+    use wow_alchemy_wmo::types::{BoundingBox, Color, Vec3};
+    use wow_alchemy_wmo::wmo_group_types::{
+        TexCoord, WmoBatch, WmoGroup, WmoGroupFlags, WmoGroupHeader,
+    };
+    use wow_alchemy_wmo::wmo_types::{WmoDoodadSet, WmoFlags, WmoGroupInfo, WmoHeader, WmoRoot};
+    use wow_alchemy_wmo::{WmoVersion, WmoWriter};
+
+    fn vec3() -> impl Strategy<Value = Vec3> {
+        (coordinate(), coordinate(), coordinate()).prop_map(|(x, y, z)| Vec3 { x, y, z })
+    }
+
+    fn bounding_box() -> impl Strategy<Value = BoundingBox> {
+        (vec3(), vec3()).prop_map(|(a, b)| BoundingBox {
+            min: Vec3 {
+                x: a.x.min(b.x),
+                y: a.y.min(b.y),
+                z: a.z.min(b.z),
+            },
+            max: Vec3 {
+                x: a.x.max(b.x),
+                y: a.y.max(b.y),
+                z: a.z.max(b.z),
+            },
+        })
+    }
+
+    /// The box around all groups, which is what the parser reads the
+    /// bounding box of a root file as
+    fn union(groups: &[WmoGroupInfo]) -> BoundingBox {
+        let mut boxes = groups.iter().map(|group| group.bounding_box);
+        let Some(first) = boxes.next() else {
+            return BoundingBox {
+                min: Vec3 {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+                max: Vec3 {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+            };
+        };
+        boxes.fold(first, |acc, b| BoundingBox {
+            min: Vec3 {
+                x: acc.min.x.min(b.min.x),
+                y: acc.min.y.min(b.min.y),
+                z: acc.min.z.min(b.min.z),
+            },
+            max: Vec3 {
+                x: acc.max.x.max(b.max.x),
+                y: acc.max.y.max(b.max.y),
+                z: acc.max.z.max(b.max.z),
+            },
+        })
+    }
+
+    fn version() -> impl Strategy<Value = WmoVersion> {
+        select(vec![
+            WmoVersion::Classic,
+            WmoVersion::Tbc,
+            WmoVersion::Wotlk,
+        ])
+    }
+
+    prop_compose! {
+        fn root()(
+            version in version(),
+            textures in prop::collection::vec(file_name("blp"), 0..4),
+            groups in prop::collection::vec(
+                ("[A-Za-z]{1,12}", bounding_box()).prop_map(|(name, bounding_box)| {
+                    WmoGroupInfo {
+                        flags: WmoGroupFlags::empty(),
+                        bounding_box,
+                        name,
+                    }
+                }),
+                0..4,
+            ),
+            doodad_sets in prop::collection::vec(
+                ("[A-Za-z_]{1,19}", 0_u32..16, 0_u32..16).prop_map(|(name, start_doodad, n_doodads)| {
+                    WmoDoodadSet { name, start_doodad, n_doodads }
+                }),
+                1..3,
+            ),
+            ambient_color in any::<[u8; 4]>(),
+        ) -> Vec<u8> {
+            let root = WmoRoot {
+                version,
+                materials: Vec::new(),
+                bounding_box: union(&groups),
+                header: WmoHeader {
+                    n_materials: 0,
+                    n_groups: groups.len() as u32,
+                    n_portals: 0,
+                    n_lights: 0,
+                    n_doodad_names: 0,
+                    n_doodad_defs: 0,
+                    n_doodad_sets: doodad_sets.len() as u32,
+                    flags: WmoFlags::empty(),
+                    ambient_color: Color {
+                        r: ambient_color[0],
+                        g: ambient_color[1],
+                        b: ambient_color[2],
+                        a: ambient_color[3],
+                    },
+                },
+                groups,
+                portals: Vec::new(),
+                portal_references: Vec::new(),
+                visible_block_lists: Vec::new(),
+                lights: Vec::new(),
+                doodad_defs: Vec::new(),
+                doodad_sets,
+                textures,
+                skybox: None,
+                convex_volume_planes: None,
+            };
+
+            let mut writer = Cursor::new(Vec::new());
+            WmoWriter::new().write_root(&mut writer, &root, version).unwrap();
+            writer.into_inner()
+        }
+    }
+
+    prop_compose! {
+        fn group()(
+            (vertices, normals, tex_coords) in (1_usize..16).prop_flat_map(|count| (
+                prop::collection::vec(vec3(), count),
+                prop::collection::vec(vec3(), count),
+                prop::collection::vec(
+                    (0.0_f32..1.0, 0.0_f32..1.0).prop_map(|(u, v)| TexCoord { u, v }),
+                    count,
+                ),
+            )),
+            triangles in prop::collection::vec(any::<[u16; 3]>(), 1..16),
+            bounding_box in bounding_box(),
+            (name_offset, descriptive_name_offset) in (any::<u32>(), any::<u32>()),
+            (portal_start, portal_count) in (any::<u16>(), any::<u16>()),
+            fog_ids in any::<[u8; 4]>(),
+            (liquid_type, unique_id, flags2) in (any::<u32>(), any::<u32>(), any::<u32>()),
+            batch_box in any::<[i16; 6]>(),
+            material_id in any::<u8>(),
+        ) -> Vec<u8> {
+            let indices: Vec<u16> = triangles.iter().flatten().copied().collect();
+            let group = WmoGroup {
+                header: WmoGroupHeader {
+                    flags: WmoGroupFlags::HAS_NORMALS,
+                    bounding_box,
+                    name_offset,
+                    descriptive_name_offset,
+                    portal_start,
+                    portal_count,
+                    trans_batch_count: 0,
+                    int_batch_count: 0,
+                    ext_batch_count: 1,
+                    padding: 0,
+                    fog_ids,
+                    liquid_type,
+                    unique_id,
+                    flags2,
+                    unused: 0,
+                    group_index: 0,
+                },
+                materials: vec![material_id as u16],
+                batches: vec![WmoBatch {
+                    bounding_box: batch_box,
+                    flags: 0,
+                    material_id: material_id as u16,
+                    start_index: 0,
+                    count: indices.len() as u16,
+                    start_vertex: 0,
+                    end_vertex: vertices.len() as u16 - 1,
+                }],
+                vertices,
+                normals,
+                tex_coords,
+                indices,
+                vertex_colors: None,
+                bsp_nodes: None,
+                liquid: None,
+                doodad_refs: None,
+            };
+
+            let mut writer = Cursor::new(Vec::new());
+            WmoWriter::new()
+                .write_group(&mut writer, &group, WmoVersion::Wotlk)
+                .unwrap();
+            writer.into_inner()
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn test_wmo_root_round_trip(data in root()) {
+            check_round_trip(RoundTripFormat::WmoRoot, "Building.wmo", &data, GameVersion::WotLK)?;
+        }
+
+        #[test]
+        fn test_wmo_group_round_trip(data in group()) {
+            check_round_trip(RoundTripFormat::WmoGroup, "Building_000.wmo", &data, GameVersion::WotLK)?;
+        }
+    }
+}